@@ -0,0 +1,67 @@
+use std::hint::black_box;
+
+use criterion::{Criterion, Throughput, criterion_group, criterion_main};
+use klv::crc::{Crc16CcittFalse, Crc32Mpeg2};
+
+/// Size of a typical ST 0806 packet.
+const PACKET_LENGTH: usize = 1024;
+
+/// CRC-32/MPEG-2 computed one bit at a time.
+fn naive_crc32_mpeg2(bytes: &[u8]) -> u32 {
+    let mut crc = 0xFFFF_FFFFu32;
+    for byte in bytes {
+        crc ^= u32::from(*byte) << 24;
+        for _ in 0..8 {
+            crc = if crc & 0x8000_0000 != 0 {
+                (crc << 1) ^ 0x04C1_1DB7
+            } else {
+                crc << 1
+            };
+        }
+    }
+    crc
+}
+
+/// CRC-16/CCITT-FALSE computed one bit at a time.
+fn naive_crc16_ccitt_false(bytes: &[u8]) -> u16 {
+    let mut crc = 0xFFFFu16;
+    for byte in bytes {
+        crc ^= u16::from(*byte) << 8;
+        for _ in 0..8 {
+            crc = if crc & 0x8000 != 0 {
+                (crc << 1) ^ 0x1021
+            } else {
+                crc << 1
+            };
+        }
+    }
+    crc
+}
+
+fn crc(c: &mut Criterion) {
+    let packet = (0..PACKET_LENGTH).map(|i| i as u8).collect::<Vec<_>>();
+    assert_eq!(Crc32Mpeg2::checksum(&packet), naive_crc32_mpeg2(&packet));
+    assert_eq!(
+        Crc16CcittFalse::checksum(&packet),
+        naive_crc16_ccitt_false(&packet)
+    );
+
+    let mut group = c.benchmark_group("crc");
+    group.throughput(Throughput::Bytes(PACKET_LENGTH as u64));
+    group.bench_function("crc32_mpeg2/table", |b| {
+        b.iter(|| Crc32Mpeg2::checksum(black_box(&packet)))
+    });
+    group.bench_function("crc32_mpeg2/naive", |b| {
+        b.iter(|| naive_crc32_mpeg2(black_box(&packet)))
+    });
+    group.bench_function("crc16_ccitt_false/table", |b| {
+        b.iter(|| Crc16CcittFalse::checksum(black_box(&packet)))
+    });
+    group.bench_function("crc16_ccitt_false/naive", |b| {
+        b.iter(|| naive_crc16_ccitt_false(black_box(&packet)))
+    });
+    group.finish();
+}
+
+criterion_group!(benches, crc);
+criterion_main!(benches);
@@ -0,0 +1,761 @@
+//! Quality checks across the sets of a whole recording.
+
+use std::{
+    cell::RefCell,
+    collections::BTreeMap,
+    fmt,
+    io::{Read, Seek, SeekFrom},
+    rc::Rc,
+    time::{Duration, SystemTime},
+};
+
+use crate::{
+    encoding::{
+        self, ber::encode_ber, ber_oid::encode_ber_oid, integer::is_minimal_signed_integer,
+        unsigned_integer::is_minimal_unsigned_integer,
+    },
+    format::KlvFormat,
+    klv::{ItemKey, Klv},
+    options::ParseOptions,
+    tag::{Tag, ValueLength},
+    time_index::{PRECISION_TIME_STAMP_TAG, precision_time_stamp},
+    universal_set::{
+        OwnedUniversalSet, SetExtent, UNIVERSAL_KEY_LENGTH, UniversalKey, UniversalSet,
+    },
+};
+
+/// Intervals between packets longer than this many expected periods are
+/// reported as gaps by `timestamp_report`.
+pub const GAP_PERIODS: u32 = 2;
+
+/// How the timestamp of a packet breaks the cadence of the stream.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum TimestampFindingKind {
+    /// The timestamp is earlier than that of an earlier packet by `by`.
+    OutOfOrder { by: Duration },
+    /// The timestamp is the same as that of an earlier packet.
+    Duplicate,
+    /// The timestamp is `interval` after that of the earlier packet, more
+    /// than the allowed number of expected periods.
+    Gap { interval: Duration },
+}
+
+/// A packet whose timestamp breaks the cadence of the stream.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, getset::CopyGetters)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[getset(get_copy = "pub")]
+pub struct TimestampFinding {
+    /// Offset of the first byte of the packet's Universal Key.
+    offset: u64,
+    /// Offset of the packet with the latest timestamp before this one, which
+    /// the timestamp is compared against.
+    previous_offset: u64,
+    timestamp: SystemTime,
+    kind: TimestampFindingKind,
+}
+
+impl fmt::Display for TimestampFindingKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::OutOfOrder { by } => write!(f, "goes back {by:?}"),
+            Self::Duplicate => write!(f, "is repeated"),
+            Self::Gap { interval } => write!(f, "jumps ahead {interval:?}"),
+        }
+    }
+}
+
+impl fmt::Display for TimestampFinding {
+    /// Formats the finding as `Packet at offset <offset>: timestamp <kind>
+    /// from packet at offset <previous_offset>`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Packet at offset {}: timestamp {} from packet at offset {}",
+            self.offset, self.kind, self.previous_offset
+        )
+    }
+}
+
+/// Every timestamp finding in a stream, in the order the packets appear in.
+#[derive(Clone, Debug, Default, PartialEq, Eq, getset::CopyGetters, getset::Getters)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TimestampReport {
+    /// Number of packets checked, with or without a timestamp.
+    #[getset(get_copy = "pub")]
+    packets: usize,
+    /// Offsets of the packets without a Precision Time Stamp.
+    #[getset(get = "pub")]
+    missing: Vec<u64>,
+    #[getset(get = "pub")]
+    findings: Vec<TimestampFinding>,
+}
+
+impl TimestampReport {
+    /// Whether every timestamp is present and no finding was made.
+    pub fn is_clean(&self) -> bool {
+        self.missing.is_empty() && self.findings.is_empty()
+    }
+}
+
+impl fmt::Display for TimestampReport {
+    /// Formats a summary line followed by each finding on its own line.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "{} packets, {} without a timestamp, {} findings",
+            self.packets,
+            self.missing.len(),
+            self.findings.len()
+        )?;
+        for finding in &self.findings {
+            writeln!(f, "  {finding}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Checks that the Precision Time Stamps of `sets` never go backwards or
+/// repeat and, given an `expected_period`, that no interval between them is
+/// longer than `GAP_PERIODS` periods.
+///
+/// See `timestamp_report_with_gap_periods`.
+pub fn timestamp_report(
+    sets: impl IntoIterator<Item = OwnedUniversalSet>,
+    expected_period: Option<Duration>,
+) -> TimestampReport {
+    timestamp_report_with_gap_periods(sets, expected_period, GAP_PERIODS)
+}
+
+/// Checks that the Precision Time Stamps of `sets` never go backwards or
+/// repeat and, given an `expected_period`, that no interval between them is
+/// longer than `gap_periods` periods.
+///
+/// Each timestamp is compared against the latest one before it, so a single
+/// packet that goes backwards is reported once rather than also making the
+/// packet after it look like a gap. No gap is reported when `gap_periods`
+/// periods are longer than a `Duration` can hold.
+pub fn timestamp_report_with_gap_periods(
+    sets: impl IntoIterator<Item = OwnedUniversalSet>,
+    expected_period: Option<Duration>,
+    gap_periods: u32,
+) -> TimestampReport {
+    let longest = expected_period.and_then(|period| period.checked_mul(gap_periods));
+    let mut report = TimestampReport::default();
+    let mut latest: Option<(SystemTime, u64)> = None;
+    for set in sets {
+        report.packets += 1;
+        let Some(timestamp) = set
+            .items()
+            .iter()
+            .find(|klv| klv.key() == ItemKey::Tag(PRECISION_TIME_STAMP_TAG))
+            .and_then(|klv| precision_time_stamp(klv.value()))
+        else {
+            report.missing.push(set.offset());
+            continue;
+        };
+
+        let Some((previous, previous_offset)) = latest else {
+            latest = Some((timestamp, set.offset()));
+            continue;
+        };
+        let kind = match timestamp.duration_since(previous) {
+            Err(error) => Some(TimestampFindingKind::OutOfOrder {
+                by: error.duration(),
+            }),
+            Ok(Duration::ZERO) => Some(TimestampFindingKind::Duplicate),
+            Ok(interval) => longest
+                .filter(|longest| interval > *longest)
+                .map(|_| TimestampFindingKind::Gap { interval }),
+        };
+        if let Some(kind) = kind {
+            report.findings.push(TimestampFinding {
+                offset: set.offset(),
+                previous_offset,
+                timestamp,
+                kind,
+            });
+        }
+        if timestamp > previous {
+            latest = Some((timestamp, set.offset()));
+        }
+    }
+    report
+}
+
+/// Part of an encoding that takes up more bytes than it needs.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum WasteCategory {
+    /// A BER-OID tag padded with leading `0x80` bytes.
+    Tag,
+    /// A BER length in the long form where the short form would do, or in a
+    /// long form padded with leading zero bytes.
+    Length,
+    /// An integer value with leading bytes its standard allows to be
+    /// truncated.
+    Integer,
+}
+
+impl fmt::Display for WasteCategory {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Tag => write!(f, "tags"),
+            Self::Length => write!(f, "lengths"),
+            Self::Integer => write!(f, "integers"),
+        }
+    }
+}
+
+/// Bytes that could be saved by encoding every packet of a stream minimally.
+#[derive(Clone, Debug, Default, PartialEq, Eq, getset::CopyGetters, getset::Getters)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct EfficiencyReport {
+    /// Number of packets read.
+    #[getset(get_copy = "pub")]
+    packets: usize,
+    /// Number of bytes the packets read take up.
+    #[getset(get_copy = "pub")]
+    total_bytes: u64,
+    /// Offsets of the packets that could not be read.
+    #[getset(get = "pub")]
+    unreadable: Vec<u64>,
+    /// Bytes that could be saved in each category.
+    #[getset(get = "pub")]
+    by_category: BTreeMap<WasteCategory, u64>,
+    /// Bytes that could be saved in the items of each key. The length of the
+    /// packets themselves is counted under their Universal Key.
+    #[getset(get = "pub")]
+    by_key: BTreeMap<ItemKey, u64>,
+}
+
+impl EfficiencyReport {
+    /// Bytes that could be saved across every category.
+    pub fn savable_bytes(&self) -> u64 {
+        self.by_category.values().sum()
+    }
+
+    fn add(&mut self, category: WasteCategory, key: ItemKey, bytes: u64) {
+        if bytes == 0 {
+            return;
+        }
+        *self.by_category.entry(category).or_default() += bytes;
+        *self.by_key.entry(key).or_default() += bytes;
+    }
+}
+
+impl fmt::Display for EfficiencyReport {
+    /// Formats a summary line followed by the bytes that could be saved in
+    /// each category and then in the items of each key.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "{} packets, {} of {} bytes could be saved, {} unreadable",
+            self.packets,
+            self.savable_bytes(),
+            self.total_bytes,
+            self.unreadable.len()
+        )?;
+        for (category, bytes) in &self.by_category {
+            writeln!(f, "  {category}: {bytes}")?;
+        }
+        for (key, bytes) in &self.by_key {
+            writeln!(f, "  Item {key}: {bytes}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Counts the bytes every packet keyed by `key` in the buffer wastes on
+/// padded tags, long-form lengths, and integers wider than they need to be,
+/// starting from its first byte wherever the buffer is.
+///
+/// Integers are only counted for the tags of `G` whose format is `Uint` or
+/// `Int` without a range and whose length is not `ValueLength::Required`, as
+/// truncating any other would change the value.
+///
+/// # Returns
+///
+/// - `Ok(EfficiencyReport)` - Every packet was visited. Packets that could
+///   not be parsed are recorded in the report.
+/// - `Err(encoding::Error)` - The buffer could not be searched for the key.
+///
+/// # Side Effects
+///
+/// Moves the current position in the buffer.
+pub fn efficiency_report<G, T>(
+    key: impl Into<UniversalKey>,
+    buf: Rc<RefCell<T>>,
+) -> Result<EfficiencyReport, encoding::Error>
+where
+    G: Tag,
+    T: Read + Seek,
+{
+    let key = key.into();
+    let locations = {
+        let mut buf = buf.borrow_mut();
+        buf.seek(SeekFrom::Start(0))?;
+        UniversalSet::start_locations(&key, &mut *buf)?
+    };
+    let options = ParseOptions {
+        skip_fill: false,
+        ..ParseOptions::default()
+    };
+
+    let mut report = EfficiencyReport::default();
+    for offset in locations {
+        let Ok(set) = UniversalSet::new_with_options(key, buf.clone(), offset, &options) else {
+            report.unreadable.push(offset);
+            continue;
+        };
+        let extent = set.extent();
+        let length_width = extent.value_offset() - offset - UNIVERSAL_KEY_LENGTH as u64;
+        let minimal = encode_ber(extent.value_length().into()).len() as u64;
+        report.add(
+            WasteCategory::Length,
+            ItemKey::Ul(key),
+            length_width - minimal,
+        );
+        for klv in set.data().iter() {
+            item_waste::<G, T>(klv, &mut report)?;
+        }
+        report.packets += 1;
+        report.total_bytes += extent.total_length();
+    }
+    Ok(report)
+}
+
+/// Adds the bytes the tag, length, and value of `klv` waste to `report`.
+fn item_waste<G, T>(klv: &Klv<T>, report: &mut EfficiencyReport) -> Result<(), encoding::Error>
+where
+    G: Tag,
+    T: Read + Seek,
+{
+    let raw = klv.split_raw()?;
+    if let Some(tag) = klv.tag() {
+        let minimal = encode_ber_oid(tag).len();
+        report.add(
+            WasteCategory::Tag,
+            klv.key(),
+            (raw.tag_bytes.len() - minimal) as u64,
+        );
+    }
+    let minimal = encode_ber(raw.value_len.into()).len();
+    report.add(
+        WasteCategory::Length,
+        klv.key(),
+        (raw.length_bytes.len() - minimal) as u64,
+    );
+
+    let Some(format) = klv
+        .tag()
+        .and_then(|number| G::try_from(number).ok())
+        .filter(|tag| !matches!(tag.value_length(), Some(ValueLength::Required(_))))
+        .and_then(|tag| tag.format())
+        .filter(|format| format.params.range.is_none())
+    else {
+        return Ok(());
+    };
+    let is_minimal = match format.klv {
+        KlvFormat::Uint => is_minimal_unsigned_integer,
+        KlvFormat::Int => is_minimal_signed_integer,
+        _ => return Ok(()),
+    };
+    let value = klv.read_value()?;
+    let mut minimal = &value[..];
+    while !is_minimal(minimal) {
+        minimal = &minimal[1..];
+    }
+    report.add(
+        WasteCategory::Integer,
+        klv.key(),
+        (value.len() - minimal.len()) as u64,
+    );
+    Ok(())
+}
+
+/// How a set is laid out against the sets around it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum LayoutFindingKind {
+    /// The declared extent of the set runs `by` bytes past the key of the
+    /// next set.
+    Overlap { by: u64 },
+    /// `length` bytes between the end of the set and the key of the next set
+    /// belong to neither.
+    Gap { length: u64 },
+    /// The next set lies entirely inside the declared extent of the set.
+    Contains,
+}
+
+/// A pair of sets whose extents overlap or leave bytes between them.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, getset::CopyGetters)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[getset(get_copy = "pub")]
+pub struct LayoutFinding {
+    /// Offset of the first byte of the Universal Key of the earlier set.
+    offset: u64,
+    /// Offset of the first byte of the Universal Key of the set after it.
+    next_offset: u64,
+    kind: LayoutFindingKind,
+}
+
+impl fmt::Display for LayoutFindingKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Overlap { by } => write!(f, "runs {by} bytes into"),
+            Self::Gap { length } => write!(f, "ends {length} bytes before"),
+            Self::Contains => write!(f, "contains"),
+        }
+    }
+}
+
+impl fmt::Display for LayoutFinding {
+    /// Formats the finding as `Set at offset <offset> <kind> set at offset
+    /// <next_offset>`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Set at offset {} {} set at offset {}",
+            self.offset, self.kind, self.next_offset
+        )
+    }
+}
+
+/// Every layout finding in a stream, in the order the sets appear in.
+#[derive(Clone, Debug, Default, PartialEq, Eq, getset::CopyGetters, getset::Getters)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct LayoutReport {
+    /// Number of sets checked.
+    #[getset(get_copy = "pub")]
+    sets: usize,
+    #[getset(get = "pub")]
+    findings: Vec<LayoutFinding>,
+}
+
+impl LayoutReport {
+    /// Whether every set ends where the next one starts, give or take a gap
+    /// no longer than the threshold.
+    pub fn is_clean(&self) -> bool {
+        self.findings.is_empty()
+    }
+}
+
+impl fmt::Display for LayoutReport {
+    /// Formats a summary line followed by each finding on its own line.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{} sets, {} findings", self.sets, self.findings.len())?;
+        for finding in &self.findings {
+            writeln!(f, "  {finding}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Checks that the declared extent of each of `sets` ends where the next set
+/// starts, reporting extents that overlap or contain the next set and gaps
+/// longer than `gap_threshold` bytes.
+///
+/// Sets found by `UniversalSet::read_all` never overlap, as it jumps over the
+/// value of each set it finds, so overlaps are only found in sets located by
+/// scanning for every key such as with `KeyScanner`.
+///
+/// Sets are taken in the order of their offsets. Each set is compared against
+/// the earlier set whose extent reaches furthest, so a set whose length runs
+/// past several others is reported against each of them rather than making
+/// every later pair look like a gap.
+pub fn layout_report<T>(sets: &[UniversalSet<T>], gap_threshold: u64) -> LayoutReport
+where
+    T: Read + Seek,
+{
+    let mut extents = sets.iter().map(|set| *set.extent()).collect::<Vec<_>>();
+    extents.sort_by_key(|extent| extent.key_offset());
+
+    let mut report = LayoutReport {
+        sets: extents.len(),
+        ..LayoutReport::default()
+    };
+    let mut furthest: Option<SetExtent> = None;
+    for extent in extents {
+        let Some(previous) = furthest else {
+            furthest = Some(extent);
+            continue;
+        };
+        let kind = if previous.end() > extent.key_offset() {
+            if extent.end() <= previous.end() {
+                Some(LayoutFindingKind::Contains)
+            } else {
+                Some(LayoutFindingKind::Overlap {
+                    by: previous.end() - extent.key_offset(),
+                })
+            }
+        } else {
+            Some(extent.key_offset() - previous.end())
+                .filter(|length| *length > gap_threshold)
+                .map(|length| LayoutFindingKind::Gap { length })
+        };
+        if let Some(kind) = kind {
+            report.findings.push(LayoutFinding {
+                offset: previous.key_offset(),
+                next_offset: extent.key_offset(),
+                kind,
+            });
+        }
+        if extent.end() > previous.end() {
+            furthest = Some(extent);
+        }
+    }
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use super::*;
+    use crate::{
+        keys,
+        klv::OwnedKlv,
+        local_set::OwnedLocalSet,
+        universal_set::{KeyScanner, UniversalSetReader},
+    };
+    use itertools::{Itertools, chain};
+
+    /// 30 Hz.
+    const PERIOD: Duration = Duration::from_micros(33_333);
+
+    fn sets(periods: &[u64]) -> Vec<OwnedUniversalSet> {
+        let buf = periods
+            .iter()
+            .flat_map(|periods| {
+                let micros = 1_700_000_000_000_000 + periods * PERIOD.as_micros() as u64;
+                OwnedLocalSet::new(vec![OwnedKlv::new(2, micros.to_be_bytes().to_vec())])
+                    .serialize(&keys::ST0601, true)
+            })
+            .collect_vec();
+        UniversalSetReader::new(keys::ST0601, Cursor::new(buf))
+            .collect::<Result<_, _>>()
+            .unwrap()
+    }
+
+    #[test]
+    fn backwards_jump_and_gap_are_found() {
+        // Packets 3 and 4 are swapped and the one after 7 is 10 periods late.
+        let sets = sets(&[0, 1, 2, 4, 3, 5, 6, 7, 17, 18]);
+        let report = timestamp_report(sets.clone(), Some(PERIOD));
+
+        assert_eq!(report.packets(), 10);
+        assert_eq!(
+            report
+                .findings()
+                .iter()
+                .map(|finding| finding.kind())
+                .collect_vec(),
+            [
+                TimestampFindingKind::OutOfOrder { by: PERIOD },
+                TimestampFindingKind::Gap {
+                    interval: PERIOD * 10
+                },
+            ]
+        );
+        assert_eq!(report.findings()[0].offset(), sets[4].offset());
+        assert_eq!(report.findings()[0].previous_offset(), sets[3].offset());
+        assert_eq!(report.findings()[1].offset(), sets[8].offset());
+        assert_eq!(report.findings()[1].previous_offset(), sets[7].offset());
+
+        #[cfg(feature = "serde")]
+        {
+            let json = serde_json::to_string(&report).unwrap();
+            assert_eq!(
+                serde_json::from_str::<TimestampReport>(&json).unwrap(),
+                report
+            );
+        }
+    }
+
+    #[test]
+    fn duplicates_are_found_without_a_period() {
+        let report = timestamp_report(sets(&[0, 1, 1, 40]), None);
+
+        assert_eq!(
+            report
+                .findings()
+                .iter()
+                .map(|finding| finding.kind())
+                .collect_vec(),
+            [TimestampFindingKind::Duplicate]
+        );
+        assert!(!report.is_clean());
+        assert!(timestamp_report(sets(&[0, 1, 2]), Some(PERIOD)).is_clean());
+    }
+
+    #[test]
+    fn gap_threshold_is_configurable() {
+        // The interval after packet 2 is three periods.
+        let sets = sets(&[0, 1, 2, 5]);
+        assert!(!timestamp_report(sets.clone(), Some(PERIOD)).is_clean());
+        assert!(timestamp_report_with_gap_periods(sets.clone(), Some(PERIOD), 3).is_clean());
+        assert!(timestamp_report_with_gap_periods(sets, Some(Duration::MAX), 2).is_clean());
+    }
+
+    #[test]
+    fn missing_timestamps_are_listed() {
+        let buf = OwnedLocalSet::new(vec![OwnedKlv::new(3, b"MISSION".to_vec())])
+            .serialize(&keys::ST0601, true);
+        let sets = UniversalSetReader::new(keys::ST0601, Cursor::new(buf))
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+
+        let report = timestamp_report(sets, Some(PERIOD));
+        assert_eq!(report.missing(), &[0]);
+        assert!(
+            report
+                .to_string()
+                .starts_with("1 packets, 1 without a timestamp, 0 findings")
+        );
+    }
+
+    crate::tag_table! {
+        #[repr(u8)]
+        enum CounterTag {
+            Count = 1, "Count", Max(8);
+            Offset = 2, "Offset";
+            Timestamp = 3, "Timestamp", Required(8);
+        }
+
+        required: []
+
+        formats: {
+            Count => Uint as Uint64,
+            Offset => Int as Int64,
+            Timestamp => Uint as Uint64,
+        }
+    }
+
+    #[test]
+    fn padded_packet_reports_savable_bytes() {
+        let minimal = OwnedLocalSet::new(vec![
+            OwnedKlv::new(1, vec![0x05]),
+            OwnedKlv::new(2, vec![0xFF]),
+            OwnedKlv::new(3, 5u64.to_be_bytes().to_vec()),
+        ])
+        .serialize(&keys::ST0601, false);
+        // The same items with a long-form set length (1 byte), a padded tag
+        // (1 byte), a padded item length (2 bytes), and integers with
+        // redundant leading bytes (3 and 1 bytes).
+        let items = chain!(
+            [0x80, 0x01, 0x82, 0x00, 0x04, 0x00, 0x00, 0x00, 0x05],
+            [0x02, 0x02, 0xFF, 0xFF],
+            [0x03, 0x08],
+            5u64.to_be_bytes()
+        )
+        .collect_vec();
+        let padded = chain!(keys::ST0601.to_vec(), [0x81, items.len() as u8], items).collect_vec();
+        let buf = [minimal.clone(), padded.clone()].concat();
+
+        let report = efficiency_report::<CounterTag, _>(
+            keys::ST0601,
+            Rc::new(RefCell::new(Cursor::new(buf))),
+        )
+        .unwrap();
+
+        assert_eq!(report.packets(), 2);
+        assert_eq!(report.total_bytes(), (minimal.len() + padded.len()) as u64);
+        assert_eq!(report.savable_bytes(), 8);
+        assert_eq!(
+            report.by_category().iter().collect_vec(),
+            [
+                (&WasteCategory::Tag, &1),
+                (&WasteCategory::Length, &3),
+                (&WasteCategory::Integer, &4),
+            ]
+        );
+        assert_eq!(
+            report.by_key().iter().collect_vec(),
+            [
+                (&ItemKey::Tag(1), &6),
+                (&ItemKey::Tag(2), &1),
+                (&ItemKey::Ul(keys::ST0601), &1),
+            ]
+        );
+        assert!(report.to_string().starts_with("2 packets, 8 of "));
+    }
+
+    #[test]
+    fn overlap_and_gap_are_found() {
+        // Starts with tag 1 and a length of 0, so a set that runs two bytes
+        // into the key of the next still parses.
+        let key = UniversalKey::new([
+            0x01, 0x00, 0x2B, 0x34, 0x02, 0x0B, 0x01, 0x01, 0x0E, 0x01, 0x03, 0x01, 0x01, 0x00,
+            0x00, 0x00,
+        ]);
+        let buf = chain!(
+            // Declares 6 bytes but only its own 4 come before the next key.
+            *key,
+            [0x06, 0x02, 0x02, 0xAA, 0xBB],
+            *key,
+            [0x04, 0x03, 0x02, 0xCC, 0xDD],
+            // Belongs to neither set.
+            [0xFF; 5],
+            *key,
+            [0x02, 0x04, 0x00]
+        )
+        .collect_vec();
+        // `read_all` jumps over the value of each set it finds, so find every
+        // key as a scanner of a damaged mux would.
+        let buf = Rc::new(RefCell::new(Cursor::new(buf)));
+        let offsets = KeyScanner::new(key).scan_chunk(buf.borrow().get_ref(), 0);
+        let sets = offsets
+            .into_iter()
+            .map(|offset| UniversalSet::new(key, buf.clone(), offset).unwrap())
+            .collect_vec();
+        let report = layout_report(&sets, 0);
+
+        assert_eq!(report.sets(), 3);
+        assert_eq!(
+            report.findings(),
+            &[
+                LayoutFinding {
+                    offset: 0,
+                    next_offset: 21,
+                    kind: LayoutFindingKind::Overlap { by: 2 },
+                },
+                LayoutFinding {
+                    offset: 21,
+                    next_offset: 47,
+                    kind: LayoutFindingKind::Gap { length: 5 },
+                },
+            ]
+        );
+        assert_eq!(
+            report.findings()[0].to_string(),
+            "Set at offset 0 runs 2 bytes into set at offset 21"
+        );
+        assert_eq!(layout_report(&sets, 5).findings().len(), 1);
+    }
+
+    #[test]
+    fn set_inside_another_is_found() {
+        let key = keys::ST0601;
+        let inner =
+            OwnedLocalSet::new(vec![OwnedKlv::new(3, b"INNER".to_vec())]).serialize(&key, false);
+        let outer =
+            OwnedLocalSet::new(vec![OwnedKlv::new(4, inner.clone())]).serialize(&key, false);
+        let buf = Rc::new(RefCell::new(Cursor::new(outer)));
+        let sets = [
+            UniversalSet::new(key, buf.clone(), 0).unwrap(),
+            UniversalSet::new(key, buf, 19).unwrap(),
+        ];
+        let report = layout_report(&sets, 0);
+
+        assert_eq!(
+            report
+                .findings()
+                .iter()
+                .map(|finding| finding.kind())
+                .collect_vec(),
+            [LayoutFindingKind::Contains]
+        );
+        assert_eq!(report.findings()[0].next_offset(), 19);
+    }
+}
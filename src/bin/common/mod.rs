@@ -0,0 +1,104 @@
+//! Helpers shared by the command line tools.
+
+// Each tool is compiled separately and does not use every helper.
+#![allow(dead_code)]
+
+use std::{
+    cell::RefCell,
+    io::{Read, Seek, SeekFrom},
+    rc::Rc,
+};
+
+use klv::{
+    encoding,
+    klv::OwnedKlv,
+    options::ParseOptions,
+    universal_set::{SetExtent, UniversalKey, UniversalSet},
+};
+
+/// Item of a set along with where it was found in the file.
+pub struct Item {
+    /// Offset of the first byte of the item's tag.
+    pub offset: u64,
+
+    /// Offset of the first byte of the item's value.
+    pub value_offset: u64,
+
+    pub klv: OwnedKlv,
+}
+
+/// Options sets are read with, which keep every item including fill.
+fn parse_options() -> ParseOptions {
+    ParseOptions {
+        skip_fill: false,
+        ..ParseOptions::default()
+    }
+}
+
+/// Reads every set keyed by `key` in file order.
+///
+/// Unless `lenient`, reading stops at the first set that can't be read, which
+/// is returned as the last entry. Otherwise each set that can't be read,
+/// including one whose length is corrupt, is returned as an error in its
+/// place and the sets after it are still read.
+///
+/// # Returns
+///
+/// - `Ok(Vec<Result<UniversalSet, encoding::Error>>)` - Every set found.
+/// - `Err(encoding::Error)` - The file could not be searched for the key.
+pub fn read_sets<T>(
+    key: UniversalKey,
+    buf: Rc<RefCell<T>>,
+    lenient: bool,
+) -> Result<Vec<Result<UniversalSet<T>, encoding::Error>>, encoding::Error>
+where
+    T: Read + Seek,
+{
+    let options = parse_options();
+    if !lenient {
+        let (sets, error) = UniversalSet::read_all_partial(key, buf, &options);
+        return Ok(sets.into_iter().map(Ok).chain(error.map(Err)).collect());
+    }
+
+    let report = UniversalSet::scan_report(key, buf.clone(), &options)?;
+    Ok(report
+        .sets()
+        .iter()
+        .map(|summary| {
+            UniversalSet::new_with_options(key, buf.clone(), summary.offset(), &options)
+                .map_err(|e| e.in_set(summary.offset()))
+        })
+        .collect())
+}
+
+/// Reads the value of the set described by `extent`.
+pub fn read_value<T>(buf: &mut T, extent: &SetExtent) -> Result<Vec<u8>, encoding::Error>
+where
+    T: Read + Seek,
+{
+    buf.seek(SeekFrom::Start(extent.value_offset()))?;
+    let mut value = Vec::new();
+    buf.take(extent.value_length()).read_to_end(&mut value)?;
+    if (value.len() as u64) < extent.value_length() {
+        return Err(encoding::Error::Truncated("value"));
+    }
+    Ok(value)
+}
+
+/// Copies every item of `set` out of the file in file order.
+pub fn read_items<T>(set: &UniversalSet<T>) -> Result<Vec<Item>, encoding::Error>
+where
+    T: Read + Seek,
+{
+    set.data()
+        .iter()
+        .map(|klv| {
+            Ok(Item {
+                offset: klv.offset(),
+                value_offset: klv.value_offset(),
+                klv: klv.to_owned_klv()?,
+            })
+        })
+        .collect::<Result<_, encoding::Error>>()
+        .map_err(|e| e.in_set(set.extent().key_offset()))
+}
@@ -0,0 +1,183 @@
+//! Prints every item of every Universal Set found in a file.
+
+mod common;
+
+use std::{
+    cell::RefCell,
+    fs::File,
+    io::{self, BufReader, BufWriter, Write},
+    path::PathBuf,
+    process::ExitCode,
+    rc::Rc,
+};
+
+use clap::Parser;
+use klv::{
+    encoding, keys,
+    registry::{self, UlDictionary},
+    universal_set::{OwnedUniversalSet, UniversalKey},
+};
+
+use crate::common::{read_items, read_sets};
+
+/// Number of value bytes shown in the preview of each item.
+const PREVIEW_LENGTH: usize = 16;
+
+#[derive(Debug, Parser)]
+#[command(
+    name = "klv-dump",
+    version,
+    about = "Prints every item of every Universal Set found in a file"
+)]
+struct Args {
+    /// File to scan for Universal Sets.
+    path: PathBuf,
+
+    /// Universal Key to search for as 32 hex digits. Defaults to the ST 0601
+    /// UAS Datalink Local Set key.
+    #[arg(long)]
+    key: Option<UniversalKey>,
+
+    /// Print one JSON object per set instead of one line per item.
+    #[arg(long)]
+    json: bool,
+
+    /// Print the number of sets and bytes found after the items.
+    #[arg(long)]
+    stats: bool,
+
+    /// Skip sets that fail to parse instead of stopping with an error.
+    #[arg(long)]
+    lenient: bool,
+
+    /// CSV or JSON dictionary of ULs used to label each set. The format is
+    /// picked from the file extension.
+    #[arg(long)]
+    dictionary: Option<PathBuf>,
+}
+
+#[derive(Debug, Default)]
+struct Stats {
+    sets: u64,
+    items: u64,
+    set_bytes: u64,
+    value_bytes: u64,
+    errors: u64,
+}
+
+fn main() -> ExitCode {
+    let args = Args::parse();
+    let dictionary = match args.dictionary.as_ref().map(load_dictionary).transpose() {
+        Ok(dictionary) => dictionary,
+        Err(e) => {
+            eprintln!("klv-dump: {e}");
+            return ExitCode::FAILURE;
+        }
+    };
+
+    match run(&args, dictionary.as_ref()) {
+        Ok(stats) if stats.errors > 0 && !args.lenient => ExitCode::FAILURE,
+        Ok(_) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("klv-dump: {e}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+/// Loads a UL dictionary, treating files ending in `.json` as JSON and
+/// anything else as CSV.
+fn load_dictionary(path: &PathBuf) -> Result<UlDictionary, String> {
+    let file = File::open(path).map_err(|e| format!("{}: {e}", path.display()))?;
+    let file = BufReader::new(file);
+    let dictionary = match path.extension().and_then(|ext| ext.to_str()) {
+        Some(ext) if ext.eq_ignore_ascii_case("json") => UlDictionary::from_json(file),
+        _ => UlDictionary::from_csv(file),
+    };
+    dictionary.map_err(|e: registry::Error| format!("{}: {e}", path.display()))
+}
+
+fn run(args: &Args, dictionary: Option<&UlDictionary>) -> Result<Stats, encoding::Error> {
+    let key = args.key.unwrap_or(keys::ST0601);
+    let file = File::open(&args.path).map_err(|e| encoding::Error::from(e).in_file(&args.path))?;
+    let buf = Rc::new(RefCell::new(BufReader::new(file)));
+    let sets = read_sets(key, buf, args.lenient).map_err(|e| e.in_file(&args.path))?;
+
+    let mut out = BufWriter::new(io::stdout().lock());
+    let mut stats = Stats::default();
+    for set in sets {
+        let (extent, items) = match set.and_then(|set| Ok((*set.extent(), read_items(&set)?))) {
+            Ok(set) => set,
+            Err(e) => {
+                stats.errors += 1;
+                eprintln!("klv-dump: {}", e.in_file(&args.path));
+                if args.lenient {
+                    continue;
+                }
+                break;
+            }
+        };
+        let offset = extent.key_offset();
+
+        stats.sets += 1;
+        stats.items += items.len() as u64;
+        stats.set_bytes += extent.total_length();
+        stats.value_bytes += items.iter().map(|item| item.klv.length()).sum::<u64>();
+
+        if args.json {
+            let set =
+                OwnedUniversalSet::new(key, offset, items.into_iter().map(|i| i.klv).collect());
+            serde_json::to_writer(&mut out, &set).map_err(io::Error::from)?;
+            writeln!(out)?;
+        } else {
+            if let Some(dictionary) = dictionary {
+                writeln!(out, "# {offset} {}", dictionary.label(&key))?;
+            }
+            for item in &items {
+                writeln!(
+                    out,
+                    "{:>10} {:>5} {:>6}  {}",
+                    item.offset,
+                    item.klv.key(),
+                    item.klv.length(),
+                    preview(item.klv.value())
+                )?;
+            }
+        }
+    }
+
+    if args.stats {
+        writeln!(
+            out,
+            "sets: {}, items: {}, set bytes: {}, value bytes: {}, errors: {}",
+            stats.sets, stats.items, stats.set_bytes, stats.value_bytes, stats.errors
+        )?;
+    }
+    out.flush()?;
+
+    Ok(stats)
+}
+
+/// Formats the start of a value as hex followed by its printable ASCII
+/// characters.
+fn preview(value: &[u8]) -> String {
+    let shown = &value[..value.len().min(PREVIEW_LENGTH)];
+    let hex = shown.iter().map(|b| format!("{b:02X}")).collect::<Vec<_>>();
+    let ascii = shown
+        .iter()
+        .map(|b| match b {
+            0x20..=0x7E => *b as char,
+            _ => '.',
+        })
+        .collect::<String>();
+    let ellipsis = if value.len() > PREVIEW_LENGTH {
+        " ..."
+    } else {
+        ""
+    };
+    format!(
+        "{:<width$}  |{ascii}|{ellipsis}",
+        hex.join(" "),
+        width = PREVIEW_LENGTH * 3 - 1
+    )
+}
@@ -0,0 +1,183 @@
+//! Writes the values of the Universal Sets found in a file, or the values of
+//! a single tag within them, out to separate files or one concatenated file.
+
+mod common;
+
+use std::{
+    cell::RefCell,
+    fs::{self, File},
+    io::{self, BufReader, BufWriter, Write},
+    path::PathBuf,
+    process::ExitCode,
+    rc::Rc,
+};
+
+use clap::{ArgGroup, Parser};
+use klv::{
+    encoding, keys,
+    universal_set::{UniversalKey, UniversalSet},
+};
+
+use crate::common::{read_items, read_sets, read_value};
+
+#[derive(Debug, Parser)]
+#[command(
+    name = "klv-extract",
+    version,
+    about = "Writes the values of the Universal Sets found in a file to disk",
+    group(ArgGroup::new("destination").required(true).args(["out_dir", "output"]))
+)]
+struct Args {
+    /// File to scan for Universal Sets.
+    path: PathBuf,
+
+    /// Universal Key to search for as 32 hex digits. Defaults to the ST 0601
+    /// UAS Datalink Local Set key.
+    #[arg(long)]
+    key: Option<UniversalKey>,
+
+    /// Extract the value of every item with this tag instead of the value of
+    /// every set.
+    #[arg(long)]
+    tag: Option<u128>,
+
+    /// Directory to write each record to as its own numbered file.
+    #[arg(long)]
+    out_dir: Option<PathBuf>,
+
+    /// File to write every record to, one after another.
+    #[arg(short, long)]
+    output: Option<PathBuf>,
+
+    /// Prefix each record with the offset of the record in the input file and
+    /// its length, both as big-endian u64s.
+    #[arg(long)]
+    with_offsets: bool,
+
+    /// Skip sets that fail to parse instead of stopping with an error.
+    #[arg(long)]
+    lenient: bool,
+}
+
+/// Value pulled out of the input file along with where it was found.
+struct Record {
+    offset: u64,
+    data: Vec<u8>,
+}
+
+/// Where records are written to.
+enum Sink {
+    Directory { path: PathBuf, count: usize },
+    File(BufWriter<File>),
+}
+
+impl Sink {
+    fn write(&mut self, record: &Record, with_offsets: bool) -> io::Result<()> {
+        let write_record = |out: &mut dyn Write| -> io::Result<()> {
+            if with_offsets {
+                out.write_all(&record.offset.to_be_bytes())?;
+                out.write_all(&(record.data.len() as u64).to_be_bytes())?;
+            }
+            out.write_all(&record.data)
+        };
+
+        match self {
+            Self::Directory { path, count } => {
+                let mut file = File::create(path.join(format!("{count:06}.bin")))?;
+                *count += 1;
+                write_record(&mut file)
+            }
+            Self::File(file) => write_record(file),
+        }
+    }
+
+    fn finish(self) -> io::Result<()> {
+        match self {
+            Self::Directory { .. } => Ok(()),
+            Self::File(mut file) => file.flush(),
+        }
+    }
+}
+
+fn main() -> ExitCode {
+    let args = Args::parse();
+    match run(&args) {
+        Ok(0) => ExitCode::SUCCESS,
+        Ok(_) if args.lenient => ExitCode::SUCCESS,
+        Ok(_) => ExitCode::FAILURE,
+        Err(e) => {
+            eprintln!("klv-extract: {e}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+/// Extracts every record from the input file.
+///
+/// # Returns
+///
+/// The number of sets that could not be parsed.
+fn run(args: &Args) -> Result<u64, encoding::Error> {
+    let key = args.key.unwrap_or(keys::ST0601);
+    let file = File::open(&args.path).map_err(|e| encoding::Error::from(e).in_file(&args.path))?;
+    let buf = Rc::new(RefCell::new(BufReader::new(file)));
+    let sets = read_sets(key, buf.clone(), args.lenient).map_err(|e| e.in_file(&args.path))?;
+
+    let mut sink = match (&args.out_dir, &args.output) {
+        (Some(dir), _) => {
+            fs::create_dir_all(dir).map_err(|e| encoding::Error::from(e).in_file(dir))?;
+            Sink::Directory {
+                path: dir.clone(),
+                count: 0,
+            }
+        }
+        (None, Some(path)) => Sink::File(BufWriter::new(
+            File::create(path).map_err(|e| encoding::Error::from(e).in_file(path))?,
+        )),
+        (None, None) => unreachable!("clap requires one of the outputs"),
+    };
+
+    let mut errors = 0;
+    for set in sets {
+        match set.and_then(|set| read_records(&buf, &set, args.tag)) {
+            Ok(records) => {
+                for record in &records {
+                    sink.write(record, args.with_offsets)?;
+                }
+            }
+            Err(e) => {
+                errors += 1;
+                eprintln!("klv-extract: {}", e.in_file(&args.path));
+                if !args.lenient {
+                    break;
+                }
+            }
+        }
+    }
+    sink.finish()?;
+
+    Ok(errors)
+}
+
+/// Reads the records to extract from `set`.
+fn read_records(
+    buf: &RefCell<BufReader<File>>,
+    set: &UniversalSet<BufReader<File>>,
+    tag: Option<u128>,
+) -> Result<Vec<Record>, encoding::Error> {
+    let Some(tag) = tag else {
+        return Ok(vec![Record {
+            offset: set.extent().value_offset(),
+            data: read_value(&mut *buf.borrow_mut(), set.extent())?,
+        }]);
+    };
+
+    Ok(read_items(set)?
+        .into_iter()
+        .filter(|item| item.klv.tag() == Some(tag))
+        .map(|item| Record {
+            offset: item.value_offset,
+            data: item.klv.value().clone(),
+        })
+        .collect())
+}
@@ -0,0 +1,224 @@
+//! Building KLV packets item by item, optionally checked against the tag
+//! table of their standard as they are built.
+
+use itertools::Itertools;
+
+use crate::{
+    encoding, keys,
+    klv::ItemKey,
+    local_set::OwnedLocalSet,
+    tag::Tag,
+    universal_set::UniversalKey,
+    validation::{Severity, ValidationProfile, Violation, validate_packet_bytes},
+};
+
+/// Checks a built packet keyed by the given key against a tag table.
+type TableCheck = fn(UniversalKey, &[u8]) -> Result<Vec<Violation>, encoding::Error>;
+
+#[derive(Debug, thiserror::Error)]
+pub enum BuildError {
+    /// The packet breaks the rules of the tag table it was built with. Every
+    /// violation found is listed, with offsets into the packet that would
+    /// have been built.
+    #[error("Packet breaks its tag table: {}", .0.iter().join("; "))]
+    Violations(Vec<Violation>),
+    /// The built packet could not be read back to be checked.
+    #[error(transparent)]
+    Encoding(#[from] encoding::Error),
+}
+
+/// Packet made by `KlvPacketBuilder::build`.
+#[derive(Clone, Debug, PartialEq, Eq, getset::Getters)]
+#[getset(get = "pub")]
+pub struct BuiltPacket {
+    /// Universal Key, length, and items of the packet.
+    bytes: Vec<u8>,
+    /// Violations of the tag table that did not stop the packet being built.
+    warnings: Vec<Violation>,
+}
+
+impl BuiltPacket {
+    pub fn into_bytes(self) -> Vec<u8> {
+        self.bytes
+    }
+}
+
+/// Builds a packet keyed by a Universal Key out of raw item values.
+///
+/// Items are written with BER-OID tags and BER lengths in the order they are
+/// first added, and ST 0601 packets get a checksum. Once a tag table is given
+/// with `KlvPacketBuilder::with_table`, the packet is checked against it when
+/// it is built: lengths, values their format can't convert such as text
+/// that is not UTF-8, enumerated values the table does not define, and
+/// missing required items.
+#[derive(Clone, Debug)]
+pub struct KlvPacketBuilder {
+    key: UniversalKey,
+    items: OwnedLocalSet,
+    check: Option<TableCheck>,
+    strict: bool,
+}
+
+impl KlvPacketBuilder {
+    pub fn new(key: impl Into<UniversalKey>) -> Self {
+        Self {
+            key: key.into(),
+            items: OwnedLocalSet::default(),
+            check: None,
+            strict: false,
+        }
+    }
+
+    /// Checks the packet against the tag table `G` when it is built.
+    pub fn with_table<G>(mut self) -> Self
+    where
+        G: Tag,
+    {
+        self.check = Some(|key, packet| {
+            validate_packet_bytes(key, packet, &ValidationProfile::<G>::lenient())
+        });
+        self
+    }
+
+    /// Refuses to build a packet that breaks a rule of its tag table when
+    /// set. Otherwise the packet is built anyway and the violations are
+    /// returned as warnings alongside it.
+    pub fn strict(mut self, strict: bool) -> Self {
+        self.strict = strict;
+        self
+    }
+
+    /// Sets the raw value of `key`, replacing any value it already has.
+    pub fn item(mut self, key: impl Into<ItemKey>, value: Vec<u8>) -> Self {
+        self.items.set(key.into(), value);
+        self
+    }
+
+    /// # Returns
+    ///
+    /// - `Ok(BuiltPacket)` - The packet, along with every violation of the
+    ///   tag table found when not strict and every warning otherwise.
+    /// - `Err(BuildError::Violations)` - The builder is strict and the packet
+    ///   breaks at least one rule of its tag table.
+    /// - `Err(BuildError::Encoding)` - The packet could not be read back to
+    ///   be checked.
+    pub fn build(&self) -> Result<BuiltPacket, BuildError> {
+        let checksum = self.key.eq_ignoring_version(&keys::ST0601);
+        let bytes = self.items.serialize(&self.key, checksum);
+        let violations = match self.check {
+            Some(check) => check(self.key, &bytes)?,
+            None => Vec::new(),
+        };
+        let refused = self.strict
+            && violations
+                .iter()
+                .any(|violation| violation.severity() == Severity::Error);
+        if refused {
+            return Err(BuildError::Violations(violations));
+        }
+        Ok(BuiltPacket {
+            bytes,
+            warnings: violations,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{misb::st0601::St0601Tag, tag::ValueLength, validation::ViolationKind};
+
+    fn packet() -> KlvPacketBuilder {
+        KlvPacketBuilder::new(keys::ST0601)
+            .with_table::<St0601Tag>()
+            .item(St0601Tag::MissionId.number(), vec![b'A'; 128])
+            .item(St0601Tag::UasDatalinkLsVersionNumber.number(), vec![19])
+    }
+
+    #[test]
+    fn every_violation_is_listed() {
+        let Err(BuildError::Violations(violations)) = packet().strict(true).build() else {
+            panic!("Packet was built");
+        };
+        assert_eq!(
+            violations
+                .iter()
+                .map(|violation| (violation.key(), violation.kind()))
+                .collect_vec(),
+            [
+                (
+                    ItemKey::Tag(St0601Tag::PrecisionTimeStamp.number()),
+                    ViolationKind::MissingRequired
+                ),
+                (
+                    ItemKey::Tag(St0601Tag::MissionId.number()),
+                    ViolationKind::InvalidLength {
+                        expected: ValueLength::Max(127),
+                        actual: 128
+                    }
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn lenient_builder_builds_anyway() {
+        let built = packet().build().unwrap();
+        assert_eq!(built.warnings().len(), 2);
+
+        let fixed = packet()
+            .strict(true)
+            .item(St0601Tag::MissionId.number(), b"MISSION".to_vec())
+            .item(St0601Tag::PrecisionTimeStamp.number(), vec![0x00; 8])
+            .build()
+            .unwrap();
+        assert!(fixed.warnings().is_empty());
+        assert_eq!(fixed.bytes()[..16], *keys::ST0601);
+    }
+
+    #[test]
+    fn unconvertible_value_is_a_violation() {
+        let built = KlvPacketBuilder::new(keys::ST0601)
+            .with_table::<St0601Tag>()
+            .item(St0601Tag::PrecisionTimeStamp.number(), vec![0x00; 8])
+            .item(St0601Tag::UasDatalinkLsVersionNumber.number(), vec![19])
+            .item(St0601Tag::PlatformTailNumber.number(), vec![0xFF, 0xFE])
+            .build()
+            .unwrap();
+        assert_eq!(
+            built
+                .warnings()
+                .iter()
+                .map(|violation| violation.kind())
+                .collect_vec(),
+            [ViolationKind::InvalidValue]
+        );
+    }
+
+    #[test]
+    fn enumeration_out_of_range_is_a_violation() {
+        let built = KlvPacketBuilder::new(keys::ST0601)
+            .with_table::<St0601Tag>()
+            .item(St0601Tag::PrecisionTimeStamp.number(), vec![0x00; 8])
+            .item(St0601Tag::UasDatalinkLsVersionNumber.number(), vec![19])
+            .item(St0601Tag::OperationalMode.number(), vec![200])
+            .item(St0601Tag::IcingDetected.number(), vec![2])
+            .build()
+            .unwrap();
+        assert_eq!(
+            built
+                .warnings()
+                .iter()
+                .map(|violation| (violation.key(), violation.kind()))
+                .collect_vec(),
+            [(
+                ItemKey::Tag(St0601Tag::OperationalMode.number()),
+                ViolationKind::OutOfRange {
+                    value: 200,
+                    min: 0,
+                    max: 5
+                }
+            )]
+        );
+    }
+}
@@ -0,0 +1,235 @@
+//! Table-driven CRCs used to check the integrity of packets.
+//!
+//! Each CRC can be computed in one call or fed a piece at a time, so a
+//! packet can be checked while it is being copied out of a stream.
+
+/// Polynomial of CRC-32, including the MPEG-2 variant ST 0806 uses.
+const CRC32_POLYNOMIAL: u32 = 0x04C1_1DB7;
+
+/// Polynomial of CRC-16-CCITT.
+const CRC16_CCITT_POLYNOMIAL: u16 = 0x1021;
+
+/// Lookup table of a CRC-32 that shifts the most significant bit out first.
+const CRC32_TABLE: [u32; 256] = {
+    let mut table = [0; 256];
+    let mut i = 0;
+    while i < 256 {
+        let mut crc = (i as u32) << 24;
+        let mut bit = 0;
+        while bit < 8 {
+            crc = if crc & 0x8000_0000 != 0 {
+                (crc << 1) ^ CRC32_POLYNOMIAL
+            } else {
+                crc << 1
+            };
+            bit += 1;
+        }
+        table[i] = crc;
+        i += 1;
+    }
+    table
+};
+
+/// Lookup table of a CRC-32 that shifts the least significant bit out first.
+const CRC32_REFLECTED_TABLE: [u32; 256] = {
+    let polynomial = CRC32_POLYNOMIAL.reverse_bits();
+    let mut table = [0; 256];
+    let mut i = 0;
+    while i < 256 {
+        let mut crc = i as u32;
+        let mut bit = 0;
+        while bit < 8 {
+            crc = if crc & 1 != 0 {
+                (crc >> 1) ^ polynomial
+            } else {
+                crc >> 1
+            };
+            bit += 1;
+        }
+        table[i] = crc;
+        i += 1;
+    }
+    table
+};
+
+/// Lookup table of a CRC-16 that shifts the most significant bit out first.
+const CRC16_CCITT_TABLE: [u16; 256] = {
+    let mut table = [0; 256];
+    let mut i = 0;
+    while i < 256 {
+        let mut crc = (i as u16) << 8;
+        let mut bit = 0;
+        while bit < 8 {
+            crc = if crc & 0x8000 != 0 {
+                (crc << 1) ^ CRC16_CCITT_POLYNOMIAL
+            } else {
+                crc << 1
+            };
+            bit += 1;
+        }
+        table[i] = crc;
+        i += 1;
+    }
+    table
+};
+
+/// CRC-32/MPEG-2, the CRC ST 0806 and MPEG-2 transport streams use.
+///
+/// Starts from `0xFFFFFFFF` and neither reflects nor inverts the result.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Crc32Mpeg2 {
+    crc: u32,
+}
+
+impl Default for Crc32Mpeg2 {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Crc32Mpeg2 {
+    pub const fn new() -> Self {
+        Self { crc: 0xFFFF_FFFF }
+    }
+
+    /// Computes the CRC of `bytes` in one call.
+    pub fn checksum(bytes: &[u8]) -> u32 {
+        let mut crc = Self::new();
+        crc.update(bytes);
+        crc.finish()
+    }
+
+    /// Adds `bytes` to the CRC.
+    pub fn update(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.crc = (self.crc << 8) ^ CRC32_TABLE[((self.crc >> 24) as u8 ^ byte) as usize];
+        }
+    }
+
+    /// CRC of every byte added so far.
+    pub fn finish(&self) -> u32 {
+        self.crc
+    }
+}
+
+/// CRC-32/ISO-HDLC, the CRC most tools mean by "CRC-32".
+///
+/// Starts from `0xFFFFFFFF`, reflects every byte, and inverts the result.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Crc32 {
+    crc: u32,
+}
+
+impl Default for Crc32 {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Crc32 {
+    pub const fn new() -> Self {
+        Self { crc: 0xFFFF_FFFF }
+    }
+
+    /// Computes the CRC of `bytes` in one call.
+    pub fn checksum(bytes: &[u8]) -> u32 {
+        let mut crc = Self::new();
+        crc.update(bytes);
+        crc.finish()
+    }
+
+    /// Adds `bytes` to the CRC.
+    pub fn update(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.crc = (self.crc >> 8) ^ CRC32_REFLECTED_TABLE[(self.crc as u8 ^ byte) as usize];
+        }
+    }
+
+    /// CRC of every byte added so far.
+    pub fn finish(&self) -> u32 {
+        !self.crc
+    }
+}
+
+/// CRC-16/CCITT-FALSE.
+///
+/// Starts from `0xFFFF` and neither reflects nor inverts the result.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Crc16CcittFalse {
+    crc: u16,
+}
+
+impl Default for Crc16CcittFalse {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Crc16CcittFalse {
+    pub const fn new() -> Self {
+        Self { crc: 0xFFFF }
+    }
+
+    /// Computes the CRC of `bytes` in one call.
+    pub fn checksum(bytes: &[u8]) -> u16 {
+        let mut crc = Self::new();
+        crc.update(bytes);
+        crc.finish()
+    }
+
+    /// Adds `bytes` to the CRC.
+    pub fn update(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.crc = (self.crc << 8) ^ CRC16_CCITT_TABLE[((self.crc >> 8) as u8 ^ byte) as usize];
+        }
+    }
+
+    /// CRC of every byte added so far.
+    pub fn finish(&self) -> u16 {
+        self.crc
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_case::test_case;
+
+    /// Input whose CRC is the check value given in every CRC catalogue.
+    const CHECK_INPUT: &[u8] = b"123456789";
+
+    #[test]
+    fn crc32_mpeg2_check_value() {
+        assert_eq!(Crc32Mpeg2::checksum(CHECK_INPUT), 0x0376_E6E7);
+    }
+
+    #[test]
+    fn crc32_check_value() {
+        assert_eq!(Crc32::checksum(CHECK_INPUT), 0xCBF4_3926);
+    }
+
+    #[test]
+    fn crc16_ccitt_false_check_value() {
+        assert_eq!(Crc16CcittFalse::checksum(CHECK_INPUT), 0x29B1);
+    }
+
+    #[test_case(0; "Empty first piece")]
+    #[test_case(4; "Middle")]
+    #[test_case(9; "Empty last piece")]
+    fn incremental_matches_one_shot(split: usize) {
+        let (first, second) = CHECK_INPUT.split_at(split);
+
+        let mut crc32_mpeg2 = Crc32Mpeg2::new();
+        let mut crc32 = Crc32::new();
+        let mut crc16 = Crc16CcittFalse::new();
+        for piece in [first, second] {
+            crc32_mpeg2.update(piece);
+            crc32.update(piece);
+            crc16.update(piece);
+        }
+
+        assert_eq!(crc32_mpeg2.finish(), Crc32Mpeg2::checksum(CHECK_INPUT));
+        assert_eq!(crc32.finish(), Crc32::checksum(CHECK_INPUT));
+        assert_eq!(crc16.finish(), Crc16CcittFalse::checksum(CHECK_INPUT));
+    }
+}
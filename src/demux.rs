@@ -0,0 +1,100 @@
+//! Separating the packets of a stream that interleaves several Universal
+//! Keys.
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    io::{self, Read, Seek, SeekFrom, Write},
+};
+
+use crate::{
+    encoding::{self, io_util::copy_limited},
+    universal_set::{UniversalKey, UniversalSet},
+};
+
+/// What `split_by_key` copied.
+#[derive(Clone, Debug, Default, PartialEq, Eq, getset::CopyGetters, getset::Getters)]
+pub struct DemuxStats {
+    /// Number of packets copied for each key. Keys that were not found have
+    /// a count of 0.
+    #[getset(get = "pub")]
+    packets: BTreeMap<UniversalKey, usize>,
+    /// Number of bytes that are not part of a packet of any of the keys.
+    #[getset(get_copy = "pub")]
+    unmatched_bytes: u64,
+}
+
+/// Copies every packet of the keys in `outputs` to the writer for its key,
+/// searching the buffer only once from the current position.
+///
+/// Packets are copied byte for byte, including their key and length, so each
+/// output can be parsed on its own. The bytes between packets, including any
+/// packets keyed by other keys and a last packet that runs past the end of
+/// the buffer, are copied to `catch_all` when one is given.
+///
+/// # Returns
+///
+/// - `Ok(DemuxStats)` - Every packet was copied.
+/// - `Err(encoding::Error)` - The length of a packet could not be read, or
+///   there was an issue reading from the buffer or writing to an output.
+///
+/// # Side Effects
+///
+/// Moves the current position in the buffer to the end of the buffer.
+pub fn split_by_key<T>(
+    buf: &mut T,
+    outputs: &mut HashMap<UniversalKey, Box<dyn Write>>,
+    mut catch_all: Option<&mut dyn Write>,
+) -> Result<DemuxStats, encoding::Error>
+where
+    T: Read + Seek,
+{
+    let keys = outputs.keys().copied().collect::<Vec<_>>();
+    let mut stats = DemuxStats {
+        packets: keys.iter().map(|key| (*key, 0)).collect(),
+        unmatched_bytes: 0,
+    };
+
+    let start = buf.stream_position()?;
+    let end = buf.seek(SeekFrom::End(0))?;
+    buf.seek(SeekFrom::Start(start))?;
+    let locations = UniversalSet::scan_keys(&keys, buf)?;
+
+    let mut copied_to = start;
+    for (i, offset) in locations {
+        let extent = UniversalSet::read_extent(buf, offset)?;
+        if extent.end() > end {
+            break;
+        }
+        copy_unmatched(buf, copied_to..offset, &mut catch_all, &mut stats)?;
+        buf.seek(SeekFrom::Start(offset))?;
+        let output = outputs.get_mut(&keys[i]).expect("Every key has an output");
+        copy_limited(buf, output, extent.total_length())?;
+        *stats.packets.get_mut(&keys[i]).unwrap() += 1;
+        copied_to = extent.end();
+    }
+    copy_unmatched(buf, copied_to..end, &mut catch_all, &mut stats)?;
+    Ok(stats)
+}
+
+/// Copies the bytes in `range` of the buffer to `catch_all`, if there is one,
+/// and counts them as unmatched.
+fn copy_unmatched<T>(
+    buf: &mut T,
+    range: std::ops::Range<u64>,
+    catch_all: &mut Option<&mut dyn Write>,
+    stats: &mut DemuxStats,
+) -> Result<(), io::Error>
+where
+    T: Read + Seek,
+{
+    let length = range.end - range.start;
+    stats.unmatched_bytes += length;
+    if let Some(catch_all) = catch_all
+        && length > 0
+    {
+        buf.seek(SeekFrom::Start(range.start))?;
+        copy_limited(buf, catch_all, length)?;
+    }
+    buf.seek(SeekFrom::Start(range.end))?;
+    Ok(())
+}
@@ -0,0 +1,159 @@
+//! Structural differences between two local sets.
+
+use std::fmt;
+
+use crate::{
+    format::{SoftwareValue, hex},
+    klv::ItemKey,
+    tag::{Tag, TagReading},
+};
+
+/// How an item differs between two sets.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum DiffKind {
+    /// The item is only in the newer set.
+    Added { new_bytes: Vec<u8> },
+    /// The item is only in the older set.
+    Removed { old_bytes: Vec<u8> },
+    /// The item is in both sets with different values.
+    Changed {
+        old_bytes: Vec<u8>,
+        new_bytes: Vec<u8>,
+    },
+}
+
+/// Difference in a single item between two sets.
+#[derive(Clone, Debug, PartialEq, getset::CopyGetters, getset::Getters)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DiffEntry {
+    #[getset(get_copy = "pub")]
+    key: ItemKey,
+    #[getset(get = "pub")]
+    kind: DiffKind,
+    /// Name of the item's tag, once decoded with a tag table.
+    #[getset(get_copy = "pub")]
+    #[cfg_attr(feature = "serde", serde(skip_deserializing))]
+    name: Option<&'static str>,
+    /// Value in the older set, once decoded with a tag table.
+    #[getset(get = "pub")]
+    old_value: Option<SoftwareValue>,
+    /// Value in the newer set, once decoded with a tag table.
+    #[getset(get = "pub")]
+    new_value: Option<SoftwareValue>,
+}
+
+impl DiffEntry {
+    pub fn new(key: ItemKey, kind: DiffKind) -> Self {
+        Self {
+            key,
+            kind,
+            name: None,
+            old_value: None,
+            new_value: None,
+        }
+    }
+
+    /// Bytes of the item in the older set, if it is in it.
+    pub fn old_bytes(&self) -> Option<&[u8]> {
+        match &self.kind {
+            DiffKind::Removed { old_bytes } | DiffKind::Changed { old_bytes, .. } => {
+                Some(old_bytes)
+            }
+            DiffKind::Added { .. } => None,
+        }
+    }
+
+    /// Bytes of the item in the newer set, if it is in it.
+    pub fn new_bytes(&self) -> Option<&[u8]> {
+        match &self.kind {
+            DiffKind::Added { new_bytes } | DiffKind::Changed { new_bytes, .. } => Some(new_bytes),
+            DiffKind::Removed { .. } => None,
+        }
+    }
+}
+
+impl fmt::Display for DiffEntry {
+    /// Formats the entry as a single line starting with `+` for added, `-` for
+    /// removed, and `~` for changed items. Decoded values are shown in place
+    /// of the bytes when there are any.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let marker = match self.kind {
+            DiffKind::Added { .. } => '+',
+            DiffKind::Removed { .. } => '-',
+            DiffKind::Changed { .. } => '~',
+        };
+        write!(f, "{marker} {}", self.key)?;
+        if let Some(name) = self.name {
+            write!(f, " ({name})")?;
+        }
+
+        let show = |value: &Option<SoftwareValue>, bytes: &[u8]| match value {
+            Some(value) => value.to_string(),
+            None => hex(bytes),
+        };
+        match (self.old_bytes(), self.new_bytes()) {
+            (Some(old), Some(new)) => write!(
+                f,
+                ": {} -> {}",
+                show(&self.old_value, old),
+                show(&self.new_value, new)
+            ),
+            (Some(old), None) => write!(f, ": {}", show(&self.old_value, old)),
+            (None, Some(new)) => write!(f, ": {}", show(&self.new_value, new)),
+            (None, None) => Ok(()),
+        }
+    }
+}
+
+/// Every item that differs between two sets, ordered by key.
+#[derive(Clone, Debug, Default, PartialEq, getset::Getters)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[getset(get = "pub")]
+pub struct SetDiff {
+    entries: Vec<DiffEntry>,
+}
+
+impl SetDiff {
+    pub fn new(entries: Vec<DiffEntry>) -> Self {
+        Self { entries }
+    }
+
+    /// Whether the two sets had the same items.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Decodes the name and values of every entry whose tag is in the table
+    /// `G`.
+    ///
+    /// Values that are special values or fail to convert are left undecoded.
+    pub fn decoded<G>(mut self) -> Self
+    where
+        G: Tag,
+    {
+        let decode = |tag: G, bytes: Option<&[u8]>| match tag.decode(bytes?) {
+            Ok(TagReading::Value(value)) => Some(value),
+            _ => None,
+        };
+        for entry in &mut self.entries {
+            let Some(Ok(tag)) = entry.key.tag().map(G::try_from) else {
+                continue;
+            };
+            entry.name = Some(tag.name());
+            entry.old_value = decode(tag, entry.old_bytes());
+            entry.new_value = decode(tag, entry.new_bytes());
+        }
+        self
+    }
+}
+
+impl fmt::Display for SetDiff {
+    /// Formats each entry on its own line.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for entry in &self.entries {
+            writeln!(f, "{entry}")?;
+        }
+        Ok(())
+    }
+}
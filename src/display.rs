@@ -0,0 +1,180 @@
+//! Human-readable tables of the items of a set, for reports and terminal
+//! output.
+
+use std::{
+    io::{Read, Seek},
+    marker::PhantomData,
+};
+
+#[cfg(feature = "registry")]
+use crate::registry::UlDictionary;
+use crate::{
+    format::{SoftwareValue, hex},
+    klv::ItemKey,
+    local_set::LocalSet,
+    tag::{Tag, TagReading},
+};
+
+/// Values longer than this many characters are cut short, ending in `...`.
+pub const MAX_VALUE_WIDTH: usize = 40;
+
+/// Names, units, and decodes the items of a set for display.
+pub trait TagNamer {
+    /// Name of the item keyed by `key`, if it is known.
+    fn name(&self, key: &ItemKey) -> Option<&str>;
+
+    /// Unit of the decoded value of the item keyed by `key`, if it has one.
+    fn unit(&self, _key: &ItemKey) -> Option<&str> {
+        None
+    }
+
+    /// Decodes the value of the item keyed by `key` into text.
+    ///
+    /// # Returns
+    ///
+    /// - `Some(String)` - The decoded value.
+    /// - `None` - The value can't be decoded, so is shown as hex.
+    fn decode(&self, _key: &ItemKey, _raw: &[u8]) -> Option<String> {
+        None
+    }
+}
+
+/// Names and decodes items with the tag table `G`.
+#[derive(Clone, Copy, Debug)]
+pub struct TagTable<G>
+where
+    G: Tag,
+{
+    table: PhantomData<G>,
+}
+
+impl<G> TagTable<G>
+where
+    G: Tag,
+{
+    pub fn new() -> Self {
+        Self { table: PhantomData }
+    }
+
+    fn tag(key: &ItemKey) -> Option<G> {
+        key.tag().and_then(|tag| G::try_from(tag).ok())
+    }
+}
+
+impl<G> Default for TagTable<G>
+where
+    G: Tag,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<G> TagNamer for TagTable<G>
+where
+    G: Tag,
+{
+    fn name(&self, key: &ItemKey) -> Option<&str> {
+        Self::tag(key).map(|tag| tag.name())
+    }
+
+    fn unit(&self, key: &ItemKey) -> Option<&str> {
+        Self::tag(key).and_then(|tag| tag.unit())
+    }
+
+    /// Decodes strings unquoted and special values as their meaning.
+    fn decode(&self, key: &ItemKey, raw: &[u8]) -> Option<String> {
+        match Self::tag(key)?.decode(raw).ok()? {
+            TagReading::Value(SoftwareValue::String(value)) => Some(value),
+            TagReading::Value(value) => Some(value.to_string()),
+            TagReading::Special(special) => Some(special.meaning.to_string()),
+            TagReading::Empty => Some(String::new()),
+        }
+    }
+}
+
+/// Names items keyed by Universal Labels with the dictionary.
+#[cfg(feature = "registry")]
+impl TagNamer for UlDictionary {
+    fn name(&self, key: &ItemKey) -> Option<&str> {
+        match key {
+            ItemKey::Ul(key) => self.lookup(key).map(|entry| entry.name().as_str()),
+            ItemKey::Tag(_) => None,
+        }
+    }
+}
+
+/// Renders the items of `set` as a table with a row per item and aligned
+/// columns for the key, name, length, value, and unit.
+///
+/// Items are named, decoded, and given units by `names`. Values that aren't
+/// decoded are shown as hex, and values that can't be read from the buffer
+/// as the error in angle brackets.
+pub fn render_table<T>(set: &LocalSet<T>, names: Option<&dyn TagNamer>) -> String
+where
+    T: Read + Seek,
+{
+    let header = ["Tag", "Name", "Length", "Value", "Unit"].map(String::from);
+    let rows = set.iter().map(|klv| {
+        let key = klv.key();
+        let value = match klv.read_value() {
+            Ok(raw) => names
+                .and_then(|names| names.decode(&key, &raw))
+                .unwrap_or_else(|| hex(&raw)),
+            Err(error) => format!("<{error}>"),
+        };
+        [
+            key.to_string(),
+            names
+                .and_then(|names| names.name(&key))
+                .unwrap_or_default()
+                .to_string(),
+            klv.length().to_string(),
+            truncate(&value),
+            names
+                .and_then(|names| names.unit(&key))
+                .unwrap_or_default()
+                .to_string(),
+        ]
+    });
+    let rows = std::iter::once(header).chain(rows).collect::<Vec<_>>();
+
+    let mut widths = [0; 5];
+    for row in &rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+    let mut table = String::new();
+    for [key, name, length, value, unit] in &rows {
+        let line = format!(
+            "{key:<0$}  {name:<1$}  {length:>2$}  {value:<3$}  {unit}",
+            widths[0], widths[1], widths[2], widths[3]
+        );
+        table.push_str(line.trim_end());
+        table.push('\n');
+    }
+    table
+}
+
+/// Cuts `value` down to `MAX_VALUE_WIDTH` characters.
+fn truncate(value: &str) -> String {
+    if value.chars().count() <= MAX_VALUE_WIDTH {
+        return value.to_string();
+    }
+    let kept = value.chars().take(MAX_VALUE_WIDTH - 3).collect::<String>();
+    format!("{kept}...")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_case::test_case;
+
+    #[test_case("ALPHA", "ALPHA"; "Short")]
+    #[test_case(&"a".repeat(MAX_VALUE_WIDTH), &"a".repeat(MAX_VALUE_WIDTH); "Exactly the limit")]
+    #[test_case(&"é".repeat(50), &format!("{}...", "é".repeat(MAX_VALUE_WIDTH - 3)); "Long")]
+    fn truncate_values(value: &str, expected: &str) {
+        assert_eq!(truncate(value), expected);
+    }
+}
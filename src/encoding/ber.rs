@@ -2,52 +2,239 @@ use bitvec::field::BitField;
 use bitvec::prelude::BitVec;
 use bitvec::prelude::Msb0;
 use bitvec::view::BitView;
+#[cfg(feature = "std")]
 use byteorder::ReadBytesExt;
+#[cfg(feature = "std")]
 use std::io;
-use std::io::Read;
-use std::io::Seek;
+#[cfg(feature = "std")]
+use std::io::{Read, Write};
 
-/// Read in a BER value from the buffer.
+use alloc::{vec, vec::Vec};
+#[cfg(feature = "bignum")]
+use num_bigint::BigUint;
+
+use crate::encoding::Error;
+#[cfg(feature = "std")]
+use crate::encoding::invalid_data;
+
+/// Decode a BER value from the start of a slice.
 ///
 /// Handles both BER short-form and BER long-form depending on the first bit of
 /// the MSB.
 ///
+/// Long forms that are wider than they need to be are accepted, including
+/// values that would fit in the short form and long forms padded with
+/// leading zero bytes, such as the `0x84 0x00 0x00 0x01 0x2A` some hardware
+/// encoders write for every length. `validation` reports them as
+/// `ViolationKind::NonMinimalEncoding` warnings.
+///
+/// # Returns
+///
+/// - `Ok((u128, usize))` - The decoded value and the number of bytes it was
+///   encoded in.
+/// - `Err(encoding::Error::Truncated)` - The slice ends before the BER value
+///   does.
+/// - `Err(encoding::Error::Malformed)` - The first bit is set but all other
+///   bits in the first byte are unset.
+/// - `Err(encoding::Error::TooLarge)` - The value won't fit in a u128.
+pub fn decode_ber(bytes: &[u8]) -> Result<(u128, usize), Error> {
+    let first_byte = *bytes.first().ok_or(Error::Truncated("BER value"))?;
+    let bits = first_byte.view_bits::<Msb0>();
+    if !*bits.get(0).expect("Failed to get first bit from BER byte") {
+        return Ok((first_byte as u128, 1));
+    }
+
+    let num_bytes_to_read: usize = bits
+        .get(1..bits.len())
+        .expect("Failed to read bits 1-7 for BER byte")
+        .load_be();
+    if num_bytes_to_read == 0 {
+        return Err(Error::Malformed {
+            what: "a BER value",
+            value: alloc::format!("{first_byte:#04X}"),
+        });
+    }
+
+    let long_form = bytes
+        .get(1..1 + num_bytes_to_read)
+        .ok_or(Error::Truncated("BER value"))?;
+    Ok((decode_ber_long_form(long_form)?, 1 + num_bytes_to_read))
+}
+
+/// Decode the bytes following the first byte of a BER long-form value.
+///
+/// # Returns
+///
+/// - `Ok(u128)` - The decoded value.
+/// - `Err(encoding::Error::TooLarge)` - The value won't fit in a u128.
+pub fn decode_ber_long_form(bytes: &[u8]) -> Result<u128, Error> {
+    let mut bitvec = BitVec::<u8, Msb0>::new();
+    for byte in bytes {
+        bitvec.extend_from_bitslice(byte.view_bits::<Msb0>());
+    }
+
+    bitvec = bitvec.drain(bitvec.leading_zeros()..bitvec.len()).collect();
+    if bitvec.len() > 128 {
+        return Err(Error::TooLarge {
+            what: "BER",
+            bits: bitvec.len(),
+        });
+    }
+    // Producers are free to use the long form for values that would fit in
+    // the short form, or to pad it with leading zero bytes.
+    if bitvec.is_empty() {
+        return Ok(0);
+    }
+    Ok(bitvec.load_be::<u128>())
+}
+
+/// Encode a value as BER, using the short form when it fits and otherwise
+/// the shortest long form.
+pub fn encode_ber(value: u128) -> Vec<u8> {
+    if value < 0x80 {
+        return vec![value as u8];
+    }
+
+    let bytes = value.to_be_bytes();
+    let significant = &bytes[value.leading_zeros() as usize / 8..];
+    let mut encoded = Vec::with_capacity(1 + significant.len());
+    encoded.push(0x80 | significant.len() as u8);
+    encoded.extend_from_slice(significant);
+    encoded
+}
+
+/// Encode a value as BER in exactly `width` bytes, padding the long form with
+/// leading zero bytes when the shortest form is narrower.
+///
+/// # Returns
+///
+/// - `Some(Vec<u8>)` - The encoded value.
+/// - `None` - The value does not fit in `width` bytes, or `width` is more
+///   than the 128 bytes the long form can take up.
+pub fn encode_ber_padded(value: u128, width: usize) -> Option<Vec<u8>> {
+    let shortest = encode_ber(value);
+    if width < shortest.len() || width > 128 {
+        return None;
+    }
+    if width == shortest.len() {
+        return Some(shortest);
+    }
+
+    let significant = &value.to_be_bytes()[value.leading_zeros() as usize / 8..];
+    let mut encoded = Vec::with_capacity(width);
+    encoded.push(0x80 | (width - 1) as u8);
+    encoded.resize(width - significant.len(), 0);
+    encoded.extend_from_slice(significant);
+    Some(encoded)
+}
+
+/// How a BER value is written.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum BerForm {
+    /// As few bytes as the value fits in, as `encode_ber` writes it.
+    #[default]
+    Minimal,
+    /// The long form with exactly this many bytes after the first, padded
+    /// with leading zero bytes as needed. Keeps a rewritten length as wide as
+    /// the one it replaces so nothing after it moves.
+    LongForm(u8),
+}
+
+/// Encode a value as BER in `form`.
+///
+/// # Returns
+///
+/// - `Ok(Vec<u8>)` - The encoded value.
+/// - `Err(encoding::Error::ValueOutOfRange)` - The value does not fit in the
+///   long form asked for.
+/// - `Err(encoding::Error::InvalidValueLength)` - The long form asks for no
+///   bytes or more than 127.
+pub fn encode_ber_with_form(value: u128, form: BerForm) -> Result<Vec<u8>, Error> {
+    match form {
+        BerForm::Minimal => Ok(encode_ber(value)),
+        BerForm::LongForm(count @ 1..=127) => encode_ber_padded(value, count as usize + 1)
+            .ok_or_else(|| Error::ValueOutOfRange {
+                what: alloc::format!("a {count} byte BER long form"),
+                value: alloc::format!("{value}"),
+            }),
+        BerForm::LongForm(count) => Err(Error::InvalidValueLength {
+            what: "BER long form",
+            length: count.into(),
+        }),
+    }
+}
+
+/// Whether the BER value at the start of a slice is written in as few bytes
+/// as `encode_ber` would write it in.
+///
+/// # Returns
+///
+/// - `Ok(bool)` - `false` when the long form is used for a value below 128
+///   or is padded with leading zero bytes.
+/// - `Err(encoding::Error)` - The value can't be decoded, as in `decode_ber`.
+pub fn is_minimal_ber(bytes: &[u8]) -> Result<bool, Error> {
+    let (value, width) = decode_ber(bytes)?;
+    Ok(encode_ber(value).len() == width)
+}
+
+/// Read in a BER value from the buffer.
+///
+/// Handles both BER short-form and BER long-form depending on the first bit of
+/// the MSB. Padded long forms are accepted, as in `decode_ber`.
+///
 /// # Returns
 ///
 /// - Ok(u128) - When a valid u128 BER value can be read from the given buffer.
 /// - Err(std::io::Error) - When a valid u128 BER value cannot be read from the given buffer.
+///   Values that `decode_ber` rejects, such as a first byte of `0x80` or a
+///   long form wider than a u128, are `io::ErrorKind::InvalidData` errors
+///   holding the `encoding::Error`.
 ///
 /// # Side Effects
 ///
 /// Moves the current position in the buffer to the byte after the last BER
-/// byte.
+/// byte. The bytes read before an error are consumed, since the buffer may
+/// not be able to seek. Wrap the call in `encoding::rewind_on_error` to move
+/// back to the first byte of the value instead.
+#[cfg(feature = "std")]
+pub fn read_ber<T>(buf: &mut T) -> Result<u128, io::Error>
+where
+    T: Read,
+{
+    read_ber_counted(buf).map(|(value, _)| value)
+}
+
+/// Read in a BER value from the buffer along with the number of bytes it was
+/// encoded in.
 ///
-/// # Panics
+/// See `read_ber`.
 ///
-/// - The value parsed from the BER is long-form and won't fit in a u128.
-/// - The first bit is set but all other bits in the first byte are unset.
-pub fn read_ber<T>(buf: &mut T) -> Result<u128, io::Error>
+/// # Returns
+///
+/// - Ok((u128, usize)) - The value and the number of bytes read, including
+///   the first byte of a long-form value.
+/// - Err(std::io::Error) - When a valid u128 BER value cannot be read from the
+///   given buffer.
+#[cfg(feature = "std")]
+pub fn read_ber_counted<T>(buf: &mut T) -> Result<(u128, usize), io::Error>
 where
-    T: Read + Seek,
+    T: Read,
 {
     let first_byte = buf.read_u8()?;
-    let bits = first_byte.view_bits::<Msb0>();
-    let value = if *bits.get(0).expect("Failed to get first bit from BER byte") {
-        let num_bytes_to_read = bits
-            .get(1..bits.len())
-            .expect("Failed to read bits 1-7 for BER byte")
-            .load_be();
-
-        if num_bytes_to_read == 0 {
-            panic!("MSB in BER is 1 but all other bits are 0");
-        }
+    if first_byte & 0x80 == 0 {
+        return Ok((first_byte as u128, 1));
+    }
 
-        read_ber_long_form(buf, num_bytes_to_read)?
-    } else {
-        first_byte as u128
-    };
+    let num_bytes_to_read = first_byte & 0x7F;
+    if num_bytes_to_read == 0 {
+        return Err(invalid_data(Error::Malformed {
+            what: "a BER value",
+            value: alloc::format!("{first_byte:#04X}"),
+        }));
+    }
 
-    Ok(value)
+    let value = read_ber_long_form(buf, num_bytes_to_read)?;
+    Ok((value, 1 + usize::from(num_bytes_to_read)))
 }
 
 /// Read in a BER long-form value from the buffer using the number of bytes.
@@ -59,42 +246,207 @@ where
 ///
 /// - Ok(u128) - When a valid u128 BER long-form value can be read from the given buffer.
 /// - Err(std::io::Error) - When a valid u128 BER long-form value cannot be read from the given buffer.
+///   A value wider than a u128 is an `io::ErrorKind::InvalidData` error
+///   holding `encoding::Error::TooLarge`.
 ///
 /// # Side Effects
 ///
 /// Moves the current position in the buffer to the byte after the last BER
 /// byte.
+#[cfg(feature = "std")]
+pub fn read_ber_long_form<T>(buf: &mut T, num_bytes_to_read: u8) -> Result<u128, io::Error>
+where
+    T: Read,
+{
+    let mut bytes = vec![0; num_bytes_to_read as usize];
+    buf.read_exact(&mut bytes)?;
+
+    decode_ber_long_form(&bytes).map_err(invalid_data)
+}
+
+/// Decode a BER value of any size from the start of a slice.
 ///
-/// # Panics
+/// See `decode_ber`.
+#[cfg(feature = "bignum")]
+pub fn decode_ber_big(bytes: &[u8]) -> Result<(BigUint, usize), Error> {
+    let first_byte = *bytes.first().ok_or(Error::Truncated("BER value"))?;
+    if first_byte & 0x80 == 0 {
+        return Ok((first_byte.into(), 1));
+    }
+
+    let num_bytes_to_read = (first_byte & 0x7F) as usize;
+    if num_bytes_to_read == 0 {
+        return Err(Error::Malformed {
+            what: "a BER value",
+            value: alloc::format!("{first_byte:#04X}"),
+        });
+    }
+
+    let long_form = bytes
+        .get(1..1 + num_bytes_to_read)
+        .ok_or(Error::Truncated("BER value"))?;
+    Ok((BigUint::from_bytes_be(long_form), 1 + num_bytes_to_read))
+}
+
+/// Encode a value of any size as BER, using the short form when it fits and
+/// otherwise the shortest long form.
 ///
-/// - The value parsed from the BER long form won't fit in a u128.
-pub fn read_ber_long_form<T>(buf: &mut T, num_bytes_to_read: u8) -> Result<u128, io::Error>
+/// # Returns
+///
+/// - `Some(Vec<u8>)` - The encoded value.
+/// - `None` - The value takes up more than 127 bytes, which the long form
+///   cannot describe.
+#[cfg(feature = "bignum")]
+pub fn encode_ber_big(value: &BigUint) -> Option<Vec<u8>> {
+    let significant = value.to_bytes_be();
+    if let [byte] = significant[..]
+        && byte < 0x80
+    {
+        return Some(vec![byte]);
+    }
+    if significant.len() > 0x7F {
+        return None;
+    }
+
+    let mut encoded = Vec::with_capacity(1 + significant.len());
+    encoded.push(0x80 | significant.len() as u8);
+    encoded.extend_from_slice(&significant);
+    Some(encoded)
+}
+
+/// Read in a BER value of any size from the buffer.
+///
+/// # Returns
+///
+/// - Ok(BigUint) - The value was read.
+/// - Err(std::io::Error) - The value could not be read. A first byte of
+///   `0x80` is an `io::ErrorKind::InvalidData` error holding
+///   `encoding::Error::Malformed`, as in `decode_ber_big`.
+///
+/// # Side Effects
+///
+/// Moves the current position in the buffer to the byte after the last BER
+/// byte.
+#[cfg(feature = "bignum")]
+pub fn read_ber_big<T>(buf: &mut T) -> Result<BigUint, io::Error>
 where
-    T: Read + Seek,
+    T: Read,
 {
-    let mut bitvec = BitVec::<u8, Msb0>::new();
-    for _ in 0..num_bytes_to_read {
-        bitvec.extend_from_bitslice(buf.read_u8()?.view_bits::<Msb0>());
+    let first_byte = buf.read_u8()?;
+    if first_byte & 0x80 == 0 {
+        return Ok(first_byte.into());
     }
 
-    // Panic if the BER-OID bits make a number larger than can be represented in
-    // a u128.
-    bitvec = bitvec.drain(bitvec.leading_zeros()..bitvec.len()).collect();
-    if bitvec.len() > 128 {
-        panic!("BER value was too large, with {} bits.", bitvec.len());
+    let num_bytes_to_read = first_byte & 0x7F;
+    if num_bytes_to_read == 0 {
+        return Err(invalid_data(Error::Malformed {
+            what: "a BER value",
+            value: alloc::format!("{first_byte:#04X}"),
+        }));
     }
-    let val = bitvec.load_be::<u128>();
-    debug_assert!(val > 127, "BER long-form value could be stored short-form");
-    Ok(val)
+
+    let mut bytes = vec![0; num_bytes_to_read as usize];
+    buf.read_exact(&mut bytes)?;
+    Ok(BigUint::from_bytes_be(&bytes))
 }
 
-#[cfg(test)]
+/// Write `value` as BER to the buffer in `form`.
+///
+/// See `encode_ber_with_form`.
+///
+/// # Returns
+///
+/// - `Ok(usize)` - The number of bytes written.
+/// - `Err(encoding::Error::ValueOutOfRange)` - The value does not fit in
+///   `form`. Nothing is written.
+/// - `Err(encoding::Error::InvalidValueLength)` - `form` asks for no bytes or
+///   more than 127. Nothing is written.
+/// - `Err(encoding::Error)` - The value could not be written.
+#[cfg(feature = "std")]
+pub fn write_ber_with_form<T>(buf: &mut T, value: u128, form: BerForm) -> Result<usize, Error>
+where
+    T: Write,
+{
+    let bytes = encode_ber_with_form(value, form)?;
+    buf.write_all(&bytes)?;
+    Ok(bytes.len())
+}
+
+/// Read in a BER value from an async reader.
+///
+/// The bytes making up the BER value are gathered first and then decoded with
+/// `read_ber`, so the two share their decoding behavior.
+///
+/// # Returns
+///
+/// - Ok(u128) - When a valid u128 BER value can be read from the given buffer.
+/// - Err(std::io::Error) - When a valid u128 BER value cannot be read from the given buffer.
+///   See `read_ber`.
+///
+/// # Side Effects
+///
+/// Moves the current position in the buffer to the byte after the last BER
+/// byte.
+#[cfg(feature = "tokio")]
+pub async fn read_ber_async<T>(buf: &mut T) -> Result<u128, io::Error>
+where
+    T: tokio::io::AsyncRead + Unpin,
+{
+    use tokio::io::AsyncReadExt;
+
+    let first_byte = buf.read_u8().await?;
+    let mut bytes = vec![first_byte];
+    if first_byte & 0x80 != 0 {
+        let mut long_form = vec![0; (first_byte & 0x7F) as usize];
+        buf.read_exact(&mut long_form).await?;
+        bytes.extend(long_form);
+    }
+
+    read_ber(&mut &bytes[..])
+}
+
+/// Write `value` as BER to an async writer, using the shortest form it fits
+/// in.
+///
+/// See `encode_ber`.
+///
+/// # Returns
+///
+/// - Ok(usize) - The number of bytes written.
+/// - Err(std::io::Error) - The value could not be written.
+#[cfg(feature = "tokio")]
+pub async fn write_ber_async<T>(buf: &mut T, value: u128) -> Result<usize, io::Error>
+where
+    T: tokio::io::AsyncWrite + Unpin,
+{
+    use tokio::io::AsyncWriteExt;
+
+    let bytes = encode_ber(value);
+    buf.write_all(&bytes).await?;
+    Ok(bytes.len())
+}
+
+#[cfg(all(test, feature = "std"))]
 mod tests {
     use std::io;
 
     use super::*;
     use test_case::test_case;
 
+    #[cfg(feature = "tokio")]
+    #[tokio::test]
+    async fn write_ber_async_round_trip() {
+        let mut written = Vec::new();
+        for value in [0, 0x7F, 0x80, 0x01_0000, u128::MAX] {
+            let count = write_ber_async(&mut written, value).await.unwrap();
+            assert_eq!(count, encode_ber(value).len());
+        }
+        let mut reader = &written[..];
+        for value in [0, 0x7F, 0x80, 0x01_0000, u128::MAX] {
+            assert_eq!(read_ber_async(&mut reader).await.unwrap(), value);
+        }
+    }
+
     #[test_case(&[0x00], 0; "Zero")]
     #[test_case(&[0x01], 1; "Smallest single-byte")]
     #[test_case(&[0x7F], 127; "Largest single-byte")]
@@ -107,6 +459,52 @@ mod tests {
         );
     }
 
+    #[test_case(&[0x05], 5, 1; "Short-form")]
+    #[test_case(&[0x81, 0x80], 128, 2; "Long-form")]
+    #[test_case(&[0x92, 0x00, 0x00, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF], u128::MAX, 19; "Padded long-form")]
+    #[test_case(&[0x84, 0x00, 0x00, 0x01, 0x2A], 298, 5; "Four byte padded long-form")]
+    #[test_case(&[0x84, 0x00, 0x00, 0x00, 0x00], 0, 5; "Four byte padded zero")]
+    fn read_ber_counted_ok(input: &[u8], expected: u128, count: usize) {
+        let mut cursor = std::io::Cursor::new([input, &[0xAA]].concat());
+        assert_eq!(read_ber_counted(&mut cursor).unwrap(), (expected, count));
+        assert_eq!(cursor.position(), count as u64);
+    }
+
+    #[test_case(0, &[0x00]; "Zero")]
+    #[test_case(127, &[0x7F]; "Largest short-form")]
+    #[test_case(128, &[0x81, 0x80]; "Smallest long-form")]
+    #[test_case(0x0100, &[0x82, 0x01, 0x00]; "Two byte long-form")]
+    fn encode_ber_ok(value: u128, expected: &[u8]) {
+        assert_eq!(encode_ber(value), expected);
+        assert_eq!(decode_ber(expected).unwrap(), (value, expected.len()));
+    }
+
+    #[test_case(5, 1, Some(&[0x05]); "Shortest form")]
+    #[test_case(5, 3, Some(&[0x82, 0x00, 0x05]); "Padded short-form")]
+    #[test_case(0, 2, Some(&[0x81, 0x00]); "Padded zero")]
+    #[test_case(0x0100, 4, Some(&[0x83, 0x00, 0x01, 0x00]); "Padded long-form")]
+    #[test_case(0x0100, 2, None; "Too narrow")]
+    fn encode_ber_padded_ok(value: u128, width: usize, expected: Option<&[u8]>) {
+        let encoded = encode_ber_padded(value, width);
+        assert_eq!(encoded.as_deref(), expected);
+        if let Some(encoded) = encoded {
+            assert_eq!(decode_ber(&encoded).unwrap(), (value, width));
+        }
+    }
+
+    #[test_case(5, BerForm::Minimal, Some(&[0x05]); "Minimal")]
+    #[test_case(5, BerForm::LongForm(1), Some(&[0x81, 0x05]); "Short-form value")]
+    #[test_case(298, BerForm::LongForm(4), Some(&[0x84, 0x00, 0x00, 0x01, 0x2A]); "Four byte long-form")]
+    #[test_case(0x01_0000, BerForm::LongForm(2), None; "Too narrow")]
+    #[test_case(5, BerForm::LongForm(0), None; "No bytes")]
+    #[test_case(5, BerForm::LongForm(128), None; "Too many bytes")]
+    fn write_ber_with_form_ok(value: u128, form: BerForm, expected: Option<&[u8]>) {
+        let mut written = Vec::new();
+        let result = write_ber_with_form(&mut written, value, form);
+        assert_eq!(result.ok(), expected.map(<[u8]>::len));
+        assert_eq!(written, expected.unwrap_or_default());
+    }
+
     #[test_case( &[], io::Error::from(io::ErrorKind::UnexpectedEof); "BER buffer has no bytes")]
     #[test_case( &[0x81], io::Error::from(io::ErrorKind::UnexpectedEof); "BER long-form ends after first byte")]
     fn read_ber_err(input: &[u8], expected: io::Error) {
@@ -115,9 +513,99 @@ mod tests {
         assert_eq!(err.kind(), expected.kind())
     }
 
-    #[should_panic]
-    #[test_case(&[0x91, 0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00]; "Largest representable plus 1")]
-    fn read_ber_panics(input: &[u8]) {
-        let _ = read_ber(&mut std::io::Cursor::new(input));
+    #[test]
+    fn read_ber_invalid_data() {
+        let largest_plus_one = [
+            0x91, 0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+            0x00, 0x00, 0x00, 0x00,
+        ];
+        let error = read_ber(&mut std::io::Cursor::new(largest_plus_one)).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+        assert!(matches!(
+            Error::from(error),
+            Error::TooLarge { bits: 129, .. }
+        ));
+
+        let error = read_ber(&mut std::io::Cursor::new([0x80])).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+        assert!(matches!(Error::from(error), Error::Malformed { .. }));
+    }
+}
+
+#[cfg(test)]
+mod slice_tests {
+    use super::*;
+    use test_case::test_case;
+
+    #[test_case(&[0x00], 0, 1; "Zero")]
+    #[test_case(&[0x7F, 0xFF], 127, 1; "Largest single-byte with trailing data")]
+    #[test_case(&[0x81, 0x80], 128, 2; "Smallest two-byte")]
+    #[test_case(&[0x81, 0x05], 5, 2; "Non-minimal long-form")]
+    #[test_case(&[0x82, 0x00, 0x00], 0, 3; "Long-form zero")]
+    // Written by hardware encoders that always use four byte lengths.
+    #[test_case(&[0x84, 0x00, 0x00, 0x01, 0x2A], 298, 5; "Four byte padded long-form")]
+    #[test_case(&[0x90, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF], u128::MAX, 17; "Largest representable")]
+    fn decode_ber_ok(input: &[u8], expected: u128, consumed: usize) {
+        assert_eq!(decode_ber(input).unwrap(), (expected, consumed));
+    }
+
+    #[test_case(&[0x05], true; "Short-form")]
+    #[test_case(&[0x81, 0x80], true; "Smallest two-byte")]
+    #[test_case(&[0x81, 0x05], false; "Long-form for a short-form value")]
+    #[test_case(&[0x82, 0x00, 0x80], false; "Padded long-form")]
+    fn is_minimal_ber_ok(input: &[u8], expected: bool) {
+        assert_eq!(is_minimal_ber(input).unwrap(), expected);
+    }
+
+    #[test_case(&[]; "No bytes")]
+    #[test_case(&[0x82, 0x01]; "Long-form ends early")]
+    fn decode_ber_truncated(input: &[u8]) {
+        assert!(matches!(decode_ber(input), Err(Error::Truncated(_))));
+    }
+
+    #[test]
+    fn decode_ber_invalid() {
+        assert!(matches!(decode_ber(&[0x80]), Err(Error::Malformed { .. })));
+        let largest_plus_one = [
+            0x91, 0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+            0x00, 0x00, 0x00, 0x00,
+        ];
+        assert!(matches!(
+            decode_ber(&largest_plus_one),
+            Err(Error::TooLarge { bits: 129, .. })
+        ));
+    }
+
+    #[cfg(feature = "bignum")]
+    #[test_case(BigUint::from(0u8); "Zero")]
+    #[test_case(BigUint::from(128u8); "Smallest long-form")]
+    #[test_case(BigUint::from(1u8) << 128; "129 bits")]
+    #[test_case((BigUint::from(1u8) << 256) - 1u8; "256 bits")]
+    fn big_round_trip(value: BigUint) {
+        let encoded = encode_ber_big(&value).unwrap();
+        assert_eq!(
+            decode_ber_big(&encoded).unwrap(),
+            (value.clone(), encoded.len())
+        );
+        assert_eq!(read_ber_big(&mut io::Cursor::new(&encoded)).unwrap(), value);
+    }
+
+    #[cfg(feature = "bignum")]
+    #[test]
+    fn big_errors() {
+        assert!(encode_ber_big(&(BigUint::from(1u8) << (8 * 127))).is_none());
+        assert!(encode_ber_big(&((BigUint::from(1u8) << (8 * 127)) - 1u8)).is_some());
+        let error = read_ber_big(&mut io::Cursor::new([0x80])).unwrap_err();
+        assert!(matches!(Error::from(error), Error::Malformed { .. }));
+    }
+
+    #[test]
+    fn too_large_mentions_bignum() {
+        use alloc::string::ToString;
+
+        let mut encoded = vec![0x91, 0x01];
+        encoded.extend([0x00; 16]);
+        let error = decode_ber(&encoded).unwrap_err().to_string();
+        assert!(error.contains("bignum"), "{error}");
     }
 }
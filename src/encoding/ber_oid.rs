@@ -2,34 +2,53 @@ use bitvec::field::BitField;
 use bitvec::prelude::BitVec;
 use bitvec::prelude::Msb0;
 use bitvec::view::BitView;
+#[cfg(feature = "std")]
 use byteorder::ReadBytesExt;
+#[cfg(feature = "std")]
 use std::io;
+#[cfg(feature = "std")]
 use std::io::Read;
-use std::io::Seek;
 
-/// Read in a BER-OID value from the buffer.
-///
-/// # Returns
-///
-/// - Ok(u128) - When a valid u128 BER-OID value can be read from the given buffer.
-/// - Err(std::io::Error) - When a valid u128 BER-OID value cannot be read from the given buffer.
-///
-/// # Side Effects
+use alloc::vec::Vec;
+#[cfg(feature = "bignum")]
+use num_bigint::BigUint;
+
+use crate::encoding::Error;
+#[cfg(feature = "std")]
+use crate::encoding::invalid_data;
+
+/// Most bytes a BER-OID value of up to 128 bits is encoded in, leaving out
+/// any leading `0x80` padding.
+#[cfg(feature = "std")]
+const MAX_BER_OID_LENGTH: usize = 19;
+
+/// Most bytes `read_ber_oid_big` reads, padding included, so a stream of
+/// continuation bytes can't grow the value forever. This is as wide as the
+/// widest value BER can describe, 127 bytes.
+#[cfg(feature = "bignum")]
+pub const MAX_BER_OID_BIG_LENGTH: usize = (127 * 8usize).div_ceil(7);
+
+/// Decode a BER-OID value from the start of a slice.
 ///
-/// Moves the current position in the buffer to the byte after the last BER-OID
-/// byte.
+/// Values padded with leading `0x80` bytes are accepted. `validation`
+/// reports them as `ViolationKind::NonMinimalEncoding` warnings.
 ///
-/// # Panics
+/// # Returns
 ///
-/// - The value parsed from the BER-OID form won't fit in a u128.
-pub fn read_ber_oid<T>(buf: &mut T) -> Result<u128, io::Error>
-where
-    T: Read + Seek,
-{
+/// - `Ok((u128, usize))` - The decoded value and the number of bytes it was
+///   encoded in.
+/// - `Err(encoding::Error::Truncated)` - The slice ends before the BER-OID
+///   value does.
+/// - `Err(encoding::Error::TooLarge)` - The value won't fit in a u128.
+pub fn decode_ber_oid(bytes: &[u8]) -> Result<(u128, usize), Error> {
     // Tag number should always start at the first byte.
     let mut bitvec = BitVec::<u8, Msb0>::new();
+    let mut consumed = 0;
     loop {
-        let byte = buf.read_u8()?;
+        let byte = *bytes
+            .get(consumed)
+            .ok_or(Error::Truncated("BER-OID value"))?;
+        consumed += 1;
         let bits = byte.view_bits::<Msb0>();
         bitvec.extend_from_bitslice(
             bits.get(1..bits.len())
@@ -39,13 +58,6 @@ where
         if !*bits.get(0).expect("Failed to get first bit from byte") {
             break;
         }
-
-        if bitvec.len() == 7 {
-            debug_assert!(
-                bitvec.load_be::<u8>() != 0,
-                "Multi-byte BER-OID starts with leading zero"
-            );
-        }
     }
 
     // Check to see if the bitvec only contains zeros, if it does then we can
@@ -53,26 +65,266 @@ where
     // NOTE: This is only needed because of how we strip the leading zeros
     // below.
     if bitvec.len() == bitvec.leading_zeros() {
-        return Ok(0);
+        return Ok((0, consumed));
     }
 
-    // Panic if the BER-OID bits make a number larger than can be represented in
-    // a u128.
     bitvec = bitvec.drain(bitvec.leading_zeros()..bitvec.len()).collect();
     if bitvec.len() > 128 {
-        panic!("BER-OID value was too large, with {} bits.", bitvec.len());
+        return Err(Error::TooLarge {
+            what: "BER-OID",
+            bits: bitvec.len(),
+        });
     }
 
-    Ok(bitvec.load_be::<u128>())
+    Ok((bitvec.load_be::<u128>(), consumed))
 }
 
-#[cfg(test)]
+/// Encode a value as BER-OID in the fewest bytes possible.
+pub fn encode_ber_oid(value: u128) -> Vec<u8> {
+    let groups = (u128::BITS - value.leading_zeros()).div_ceil(7).max(1);
+    (0..groups)
+        .rev()
+        .map(|group| {
+            let bits = (value >> (7 * group)) as u8 & 0x7F;
+            if group == 0 { bits } else { bits | 0x80 }
+        })
+        .collect()
+}
+
+/// Whether the BER-OID value at the start of a slice is written in as few
+/// bytes as `encode_ber_oid` would write it in.
+///
+/// # Returns
+///
+/// - `Ok(bool)` - `false` when the value is padded with leading `0x80`
+///   bytes.
+/// - `Err(encoding::Error)` - The value can't be decoded, as in
+///   `decode_ber_oid`.
+pub fn is_minimal_ber_oid(bytes: &[u8]) -> Result<bool, Error> {
+    let (value, width) = decode_ber_oid(bytes)?;
+    Ok(encode_ber_oid(value).len() == width)
+}
+
+/// Read in a BER-OID value from the buffer.
+///
+/// # Returns
+///
+/// - Ok(u128) - When a valid u128 BER-OID value can be read from the given buffer.
+/// - Err(std::io::Error) - When a valid u128 BER-OID value cannot be read from the given buffer.
+///   A value wider than a u128 is an `io::ErrorKind::InvalidData` error
+///   holding `encoding::Error::TooLarge`. Reading stops as soon as the value
+///   is known to be too wide.
+///
+/// # Side Effects
+///
+/// Moves the current position in the buffer to the byte after the last BER-OID
+/// byte. The bytes read before an error are consumed, since the buffer may
+/// not be able to seek. Wrap the call in `encoding::rewind_on_error` to move
+/// back to the first byte of the value instead.
+#[cfg(feature = "std")]
+pub fn read_ber_oid<T>(buf: &mut T) -> Result<u128, io::Error>
+where
+    T: Read,
+{
+    read_ber_oid_counted(buf).map(|(value, _)| value)
+}
+
+/// Read in a BER-OID value from the buffer along with the number of bytes it
+/// was encoded in.
+///
+/// See `read_ber_oid`.
+///
+/// # Returns
+///
+/// - Ok((u128, usize)) - The value and the number of bytes read.
+/// - Err(std::io::Error) - When a valid u128 BER-OID value cannot be read from
+///   the given buffer.
+#[cfg(feature = "std")]
+pub fn read_ber_oid_counted<T>(buf: &mut T) -> Result<(u128, usize), io::Error>
+where
+    T: Read,
+{
+    // Padding is counted rather than kept, since any amount of it is allowed.
+    let mut padding = 0;
+    let mut byte = buf.read_u8()?;
+    while byte == 0x80 {
+        padding += 1;
+        byte = buf.read_u8()?;
+    }
+    let mut bytes = vec![byte];
+    while byte & 0x80 != 0 {
+        if bytes.len() == MAX_BER_OID_LENGTH {
+            let leading = 8 - (bytes[0] & 0x7F).leading_zeros() as usize;
+            return Err(invalid_data(Error::TooLarge {
+                what: "BER-OID",
+                bits: 7 * MAX_BER_OID_LENGTH + leading,
+            }));
+        }
+        byte = buf.read_u8()?;
+        bytes.push(byte);
+    }
+
+    let (value, consumed) = decode_ber_oid(&bytes).map_err(invalid_data)?;
+    Ok((value, padding + consumed))
+}
+
+/// Decode a BER-OID value of any size from the start of a slice.
+///
+/// See `decode_ber_oid`.
+#[cfg(feature = "bignum")]
+pub fn decode_ber_oid_big(bytes: &[u8]) -> Result<(BigUint, usize), Error> {
+    let mut value = BigUint::ZERO;
+    for (i, byte) in bytes.iter().enumerate() {
+        value = (value << 7u8) | BigUint::from(byte & 0x7F);
+        if byte & 0x80 == 0 {
+            return Ok((value, i + 1));
+        }
+    }
+    Err(Error::Truncated("BER-OID value"))
+}
+
+/// Encode a value of any size as BER-OID in the fewest bytes possible.
+#[cfg(feature = "bignum")]
+pub fn encode_ber_oid_big(value: &BigUint) -> Vec<u8> {
+    let mut groups = value.to_radix_be(128);
+    let last = groups.len() - 1;
+    groups[..last].iter_mut().for_each(|group| *group |= 0x80);
+    groups
+}
+
+/// Read in a BER-OID value of any size from the buffer.
+///
+/// # Returns
+///
+/// - Ok(BigUint) - The value was read.
+/// - Err(std::io::Error) - The value could not be read. A value longer than
+///   `MAX_BER_OID_BIG_LENGTH` bytes is an `io::ErrorKind::InvalidData` error
+///   holding `encoding::Error::InvalidValueLength`.
+///
+/// # Side Effects
+///
+/// Moves the current position in the buffer to the byte after the last BER-OID
+/// byte.
+#[cfg(feature = "bignum")]
+pub fn read_ber_oid_big<T>(buf: &mut T) -> Result<BigUint, io::Error>
+where
+    T: Read,
+{
+    let mut bytes = vec![buf.read_u8()?];
+    while bytes.last().is_some_and(|byte| byte & 0x80 != 0) {
+        if bytes.len() == MAX_BER_OID_BIG_LENGTH {
+            return Err(invalid_data(Error::InvalidValueLength {
+                what: "BER-OID value",
+                length: MAX_BER_OID_BIG_LENGTH as u64 + 1,
+            }));
+        }
+        bytes.push(buf.read_u8()?);
+    }
+
+    let (value, _) = decode_ber_oid_big(&bytes).expect("Every byte of the BER-OID value was read");
+    Ok(value)
+}
+
+/// Read in a BER-OID value from an async reader.
+///
+/// The bytes making up the BER-OID value are gathered first and then decoded
+/// with `read_ber_oid`, so the two share their decoding behavior.
+///
+/// # Returns
+///
+/// - Ok(u128) - When a valid u128 BER-OID value can be read from the given buffer.
+/// - Err(std::io::Error) - When a valid u128 BER-OID value cannot be read from the given buffer.
+///
+/// # Side Effects
+///
+/// Moves the current position in the buffer to the byte after the last BER-OID
+/// byte.
+///
+/// See `read_ber_oid` for the errors returned.
+#[cfg(feature = "tokio")]
+pub async fn read_ber_oid_async<T>(buf: &mut T) -> Result<u128, io::Error>
+where
+    T: tokio::io::AsyncRead + Unpin,
+{
+    use tokio::io::AsyncReadExt;
+
+    let mut byte = buf.read_u8().await?;
+    while byte == 0x80 {
+        byte = buf.read_u8().await?;
+    }
+    // One byte past the widest value is enough for `read_ber_oid` to know the
+    // value is too wide.
+    let mut bytes = vec![byte];
+    while byte & 0x80 != 0 && bytes.len() <= MAX_BER_OID_LENGTH {
+        byte = buf.read_u8().await?;
+        bytes.push(byte);
+    }
+
+    read_ber_oid(&mut &bytes[..])
+}
+
+/// Write `value` as BER-OID to an async writer.
+///
+/// See `encode_ber_oid`.
+///
+/// # Returns
+///
+/// - Ok(usize) - The number of bytes written.
+/// - Err(std::io::Error) - The value could not be written.
+#[cfg(feature = "tokio")]
+pub async fn write_ber_oid_async<T>(buf: &mut T, value: u128) -> Result<usize, io::Error>
+where
+    T: tokio::io::AsyncWrite + Unpin,
+{
+    use tokio::io::AsyncWriteExt;
+
+    let bytes = encode_ber_oid(value);
+    buf.write_all(&bytes).await?;
+    Ok(bytes.len())
+}
+
+#[cfg(all(test, feature = "std"))]
 mod tests {
     use std::io;
 
     use super::*;
     use test_case::test_case;
 
+    #[cfg(feature = "tokio")]
+    #[tokio::test]
+    async fn write_ber_oid_async_round_trip() {
+        let mut written = Vec::new();
+        for value in [0, 0x7F, 0x80, 0x3FFF, u128::MAX] {
+            let count = write_ber_oid_async(&mut written, value).await.unwrap();
+            assert_eq!(count, encode_ber_oid(value).len());
+        }
+        let mut reader = &written[..];
+        for value in [0, 0x7F, 0x80, 0x3FFF, u128::MAX] {
+            assert_eq!(read_ber_oid_async(&mut reader).await.unwrap(), value);
+        }
+    }
+
+    #[test_case(0, &[0x00]; "Zero")]
+    #[test_case(127, &[0x7F]; "Largest single-byte")]
+    #[test_case(128, &[0x81, 0x00]; "Smallest two-byte")]
+    #[test_case(16_383, &[0xFF, 0x7F]; "Largest two-byte")]
+    fn encode_ber_oid_ok(value: u128, expected: &[u8]) {
+        assert_eq!(encode_ber_oid(value), expected);
+        assert_eq!(decode_ber_oid(expected).unwrap(), (value, expected.len()));
+    }
+
+    #[test_case(&[0x05], 5, 1; "Single-byte")]
+    #[test_case(&[0x81, 0x00], 128, 2; "Two-byte")]
+    #[test_case(&[0x83, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0x7F], u128::MAX, 19; "Nineteen-byte")]
+    fn read_ber_oid_counted_ok(input: &[u8], expected: u128, count: usize) {
+        let mut cursor = std::io::Cursor::new([input, &[0xAA]].concat());
+        assert_eq!(
+            read_ber_oid_counted(&mut cursor).unwrap(),
+            (expected, count)
+        );
+        assert_eq!(cursor.position(), count as u64);
+    }
+
     #[test_case(&[0x00], 0; "Zero")]
     #[test_case(&[0x01], 1; "Smallest single-byte")]
     #[test_case(&[0x7F], 127; "Largest single-byte")]
@@ -94,9 +346,104 @@ mod tests {
         assert_eq!(err.kind(), expected.kind())
     }
 
-    #[should_panic]
     #[test_case(&[0x84, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x00]; "Largest representable plus 1")]
-    fn read_ber_oid_panics(input: &[u8]) {
-        let _ = read_ber_oid(&mut std::io::Cursor::new(input));
+    #[test_case(&[0xFF; 64]; "Continuation bytes past the widest value")]
+    fn read_ber_oid_too_large(input: &[u8]) {
+        let error = read_ber_oid(&mut std::io::Cursor::new(input)).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+        assert!(matches!(Error::from(error), Error::TooLarge { .. }));
+    }
+
+    #[test]
+    fn read_ber_oid_counts_padding() {
+        let mut cursor =
+            std::io::Cursor::new([0x80; 40].into_iter().chain([0x05]).collect::<Vec<_>>());
+        assert_eq!(read_ber_oid_counted(&mut cursor).unwrap(), (5, 41));
+    }
+
+    #[cfg(feature = "bignum")]
+    #[test]
+    fn read_ber_oid_big_stops_at_cap() {
+        let mut cursor = std::io::Cursor::new([0xFF; 1024]);
+        let error = read_ber_oid_big(&mut cursor).unwrap_err();
+        assert!(matches!(
+            Error::from(error),
+            Error::InvalidValueLength { .. }
+        ));
+        assert_eq!(cursor.position(), MAX_BER_OID_BIG_LENGTH as u64);
+    }
+}
+
+#[cfg(test)]
+mod slice_tests {
+    use super::*;
+    use test_case::test_case;
+
+    #[test_case(&[0x00], 0, 1; "Zero")]
+    #[test_case(&[0x7F, 0x01], 127, 1; "Largest single-byte with trailing data")]
+    #[test_case(&[0x81, 0x00], 128, 2; "Smallest two-byte")]
+    #[test_case(&[0xFF, 0x7F], 16_383, 2; "Largest two-byte")]
+    #[test_case(&[0x83, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0x7F], u128::MAX, 19; "Largest representable")]
+    fn decode_ber_oid_ok(input: &[u8], expected: u128, consumed: usize) {
+        assert_eq!(decode_ber_oid(input).unwrap(), (expected, consumed));
+    }
+
+    #[test_case(&[0x80, 0x05], 5, 2; "Padded single-byte")]
+    #[test_case(&[0x80, 0x80, 0x00], 0, 3; "Padded zero")]
+    fn decode_ber_oid_padded(input: &[u8], expected: u128, consumed: usize) {
+        assert_eq!(decode_ber_oid(input).unwrap(), (expected, consumed));
+        assert!(!is_minimal_ber_oid(input).unwrap());
+    }
+
+    #[test_case(&[0x05]; "Single-byte")]
+    #[test_case(&[0x81, 0x00]; "Two-byte")]
+    fn is_minimal_ber_oid_ok(input: &[u8]) {
+        assert!(is_minimal_ber_oid(input).unwrap());
+    }
+
+    #[test_case(&[]; "No bytes")]
+    #[test_case(&[0x81]; "Ends with MSB set")]
+    fn decode_ber_oid_truncated(input: &[u8]) {
+        assert!(matches!(decode_ber_oid(input), Err(Error::Truncated(_))));
+    }
+
+    #[test]
+    fn decode_ber_oid_too_large() {
+        let input = [
+            0x84, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80,
+            0x80, 0x80, 0x80, 0x80, 0x00,
+        ];
+        assert!(matches!(
+            decode_ber_oid(&input),
+            Err(Error::TooLarge { .. })
+        ));
+    }
+
+    #[cfg(feature = "bignum")]
+    #[test_case(BigUint::from(0u8), &[0x00]; "Zero")]
+    #[test_case(BigUint::from(128u8), &[0x81, 0x00]; "Smallest two-byte")]
+    fn big_encoding(value: BigUint, expected: &[u8]) {
+        assert_eq!(encode_ber_oid_big(&value), expected);
+    }
+
+    #[cfg(feature = "bignum")]
+    #[test_case(BigUint::from(1u8) << 128; "129 bits")]
+    #[test_case((BigUint::from(1u8) << 256) - 1u8; "256 bits")]
+    fn big_round_trip(value: BigUint) {
+        let encoded = encode_ber_oid_big(&value);
+        assert_eq!(
+            decode_ber_oid_big(&encoded).unwrap(),
+            (value.clone(), encoded.len())
+        );
+        assert_eq!(
+            read_ber_oid_big(&mut io::Cursor::new(&encoded)).unwrap(),
+            value
+        );
+        assert!(
+            decode_ber_oid(&encoded)
+                .unwrap_err()
+                .to_string()
+                .contains("bignum")
+        );
     }
 }
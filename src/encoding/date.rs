@@ -0,0 +1,185 @@
+//! Calendar dates written as ISO 8601 text, such as the Declassification
+//! Date of MISB ST 0102.
+//!
+//! The standards call for the compact `YYYYMMDD` form but the extended
+//! `YYYY-MM-DD` form is common in the wild, so both are read. Dates are
+//! always written in the compact form.
+
+use alloc::{format, string::String, vec::Vec};
+use core::fmt;
+#[cfg(feature = "std")]
+use std::io::{Read, Seek};
+
+use crate::encoding::Error;
+#[cfg(feature = "std")]
+use crate::encoding::rewind_on_error;
+
+/// A day of the Gregorian calendar.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Date {
+    year: u16,
+    month: u8,
+    day: u8,
+}
+
+impl Date {
+    /// # Returns
+    ///
+    /// - `Some(Date)` - `day` is a day of `month` in `year`.
+    /// - `None` - `month` is not 1 to 12, or `day` is not a day of it, such
+    ///   as February 30th.
+    pub fn new(year: u16, month: u8, day: u8) -> Option<Self> {
+        let days = match month {
+            1 | 3 | 5 | 7 | 8 | 10 | 12 => 31,
+            4 | 6 | 9 | 11 => 30,
+            2 if is_leap_year(year) => 29,
+            2 => 28,
+            _ => return None,
+        };
+        (1..=days)
+            .contains(&day)
+            .then_some(Self { year, month, day })
+    }
+
+    pub fn year(&self) -> u16 {
+        self.year
+    }
+
+    pub fn month(&self) -> u8 {
+        self.month
+    }
+
+    pub fn day(&self) -> u8 {
+        self.day
+    }
+}
+
+impl fmt::Display for Date {
+    /// Formats the date in the extended `YYYY-MM-DD` form.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:04}-{:02}-{:02}", self.year, self.month, self.day)
+    }
+}
+
+fn is_leap_year(year: u16) -> bool {
+    year.is_multiple_of(4) && (!year.is_multiple_of(100) || year.is_multiple_of(400))
+}
+
+/// Decodes a date from every byte of `bytes`, written as `YYYYMMDD` or
+/// `YYYY-MM-DD`.
+///
+/// # Returns
+///
+/// - `Ok(Date)` - The decoded date.
+/// - `Err(encoding::Error::Malformed)` - `bytes` is in neither form or is
+///   not a day of the calendar.
+pub fn decode_iso_date(bytes: &[u8]) -> Result<Date, Error> {
+    let invalid = || Error::Malformed {
+        what: "an ISO 8601 date",
+        value: format!("{:?}", bytes.escape_ascii()),
+    };
+    let digits = match bytes {
+        [year @ .., b'-', m1, m2, b'-', d1, d2] if year.len() == 4 => {
+            [year, &[*m1, *m2], &[*d1, *d2]].concat()
+        }
+        _ if bytes.len() == 8 => bytes.to_vec(),
+        _ => return Err(invalid()),
+    };
+    if !digits.iter().all(u8::is_ascii_digit) {
+        return Err(invalid());
+    }
+    let number = |digits: &[u8]| {
+        digits
+            .iter()
+            .fold(0u16, |number, digit| number * 10 + u16::from(digit - b'0'))
+    };
+    Date::new(
+        number(&digits[..4]),
+        number(&digits[4..6]) as u8,
+        number(&digits[6..]) as u8,
+    )
+    .ok_or_else(invalid)
+}
+
+/// Encodes a date in the compact `YYYYMMDD` form.
+pub fn encode_iso_date(date: &Date) -> Vec<u8> {
+    let text: String = format!("{:04}{:02}{:02}", date.year, date.month, date.day);
+    text.into_bytes()
+}
+
+/// Read in a date from a value `length` bytes long, written as `YYYYMMDD`
+/// or `YYYY-MM-DD`.
+///
+/// # Returns
+///
+/// - `Ok(Date)` - The date was read.
+/// - `Err(encoding::Error::InvalidValueLength)` - `length` is not 8 or 10
+///   bytes.
+/// - `Err(encoding::Error::Malformed)` - The value is not a valid date.
+/// - `Err(encoding::Error)` - There was an error reading from the buffer.
+///
+/// # Side Effects
+///
+/// Moves the current position in the buffer to the byte after the last byte
+/// read. The position is left where it was when an error is returned.
+#[cfg(feature = "std")]
+pub fn read_iso_date<T>(buf: &mut T, length: u64) -> Result<Date, Error>
+where
+    T: Read + Seek,
+{
+    rewind_on_error(buf, |buf| {
+        if !matches!(length, 8 | 10) {
+            return Err(Error::InvalidValueLength {
+                what: "ISO 8601 date",
+                length,
+            });
+        }
+        let mut bytes = [0; 10];
+        buf.read_exact(&mut bytes[..length as usize])?;
+        decode_iso_date(&bytes[..length as usize])
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_case::test_case;
+
+    #[test_case(b"20250228", 2025, 2, 28; "Compact")]
+    #[test_case(b"2025-02-28", 2025, 2, 28; "Extended")]
+    #[test_case(b"20240229", 2024, 2, 29; "Leap day")]
+    fn decode_date(bytes: &[u8], year: u16, month: u8, day: u8) {
+        assert_eq!(
+            decode_iso_date(bytes).unwrap(),
+            Date::new(year, month, day).unwrap()
+        );
+    }
+
+    #[test_case(b"20251301"; "Month 13")]
+    #[test_case(b"20250230"; "February 30th")]
+    #[test_case(b"19000229"; "Not a leap year")]
+    #[test_case(b"2025/02/28"; "Wrong separator")]
+    #[test_case(b"2025-0228"; "One separator")]
+    #[test_case(b"2025022"; "Too short")]
+    #[test_case(b"2025+228"; "Not digits")]
+    fn invalid_date(bytes: &[u8]) {
+        assert!(matches!(
+            decode_iso_date(bytes),
+            Err(Error::Malformed { .. })
+        ));
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn read_round_trips_through_writer() {
+        let mut cursor = std::io::Cursor::new(b"1999-12-31".to_vec());
+        let date = read_iso_date(&mut cursor, 10).unwrap();
+        assert_eq!(date.to_string(), "1999-12-31");
+        assert_eq!(encode_iso_date(&date), b"19991231");
+
+        let mut cursor = std::io::Cursor::new(encode_iso_date(&date));
+        assert_eq!(read_iso_date(&mut cursor, 8).unwrap(), date);
+        assert!(read_iso_date(&mut cursor, 9).is_err());
+        assert_eq!(cursor.position(), 8);
+    }
+}
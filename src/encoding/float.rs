@@ -0,0 +1,143 @@
+#[cfg(feature = "std")]
+use std::io::{Read, Seek};
+
+use crate::encoding::Error;
+#[cfg(feature = "std")]
+use crate::encoding::rewind_on_error;
+
+/// Decode an IEEE 754 floating-point number of 4 or 8 bytes from the start of
+/// a slice.
+///
+/// # Returns
+///
+/// - `Ok(f64)` - The decoded number. 4 byte numbers are widened to an `f64`.
+/// - `Err(encoding::Error::InvalidValueLength)` - `length` is not 4 or 8.
+/// - `Err(encoding::Error::Truncated)` - The slice is shorter than `length`.
+pub fn decode_float(bytes: &[u8], length: u8) -> Result<f64, Error> {
+    Ok(match length {
+        4 => f32::from_be_bytes(float_bytes(bytes)?).into(),
+        8 => f64::from_be_bytes(float_bytes(bytes)?),
+        _ => return Err(float_length_error(length.into())),
+    })
+}
+
+/// Decode a little-endian IEEE 754 floating-point number of 4 or 8 bytes from
+/// the start of a slice, such as one from a vendor payload carried inside a
+/// KLV item.
+///
+/// See `decode_float`, which every KLV standard uses.
+pub fn decode_float_le(bytes: &[u8], length: u8) -> Result<f64, Error> {
+    Ok(match length {
+        4 => f32::from_le_bytes(float_bytes(bytes)?).into(),
+        8 => f64::from_le_bytes(float_bytes(bytes)?),
+        _ => return Err(float_length_error(length.into())),
+    })
+}
+
+fn float_bytes<const N: usize>(bytes: &[u8]) -> Result<[u8; N], Error> {
+    bytes
+        .get(..N)
+        .map(|bytes| bytes.try_into().expect("Slice is N bytes long"))
+        .ok_or(Error::Truncated("floating-point value"))
+}
+
+fn float_length_error(length: u64) -> Error {
+    Error::InvalidValueLength {
+        what: "floating-point value",
+        length,
+    }
+}
+
+/// Read in an IEEE 754 floating-point number of 4 or 8 bytes.
+///
+/// # Side Effects
+///
+/// Moves the current position in the buffer to the byte after the last byte
+/// read. The position is left where it was when an error is returned.
+#[cfg(feature = "std")]
+pub fn read_float<T>(buf: &mut T, length: u64) -> Result<f64, Error>
+where
+    T: Read + Seek,
+{
+    read_float_with(buf, length, decode_float)
+}
+
+/// Read in a little-endian IEEE 754 floating-point number of 4 or 8 bytes.
+///
+/// See `read_float`, which every KLV standard uses.
+#[cfg(feature = "std")]
+pub fn read_float_le<T>(buf: &mut T, length: u64) -> Result<f64, Error>
+where
+    T: Read + Seek,
+{
+    read_float_with(buf, length, decode_float_le)
+}
+
+#[cfg(feature = "std")]
+fn read_float_with<T>(
+    buf: &mut T,
+    length: u64,
+    decode: fn(&[u8], u8) -> Result<f64, Error>,
+) -> Result<f64, Error>
+where
+    T: Read + Seek,
+{
+    rewind_on_error(buf, |buf| {
+        let length = match length {
+            4 => 4,
+            8 => 8,
+            _ => return Err(float_length_error(length)),
+        };
+        let mut bytes = [0; 8];
+        buf.read_exact(&mut bytes[..length as usize])?;
+        decode(&bytes, length)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_case::test_case;
+
+    #[test_case(&1.5f32.to_be_bytes(), &1.5f32.to_le_bytes(), 1.5; "Four bytes")]
+    #[test_case(&(-0.1f64).to_be_bytes(), &(-0.1f64).to_le_bytes(), -0.1; "Eight bytes")]
+    fn decode_float_ok(be: &[u8], le: &[u8], expected: f64) {
+        let length = be.len() as u8;
+        assert_eq!(decode_float(be, length).unwrap(), expected);
+        assert_eq!(decode_float_le(le, length).unwrap(), expected);
+    }
+
+    #[test]
+    fn decode_float_err() {
+        assert!(matches!(
+            decode_float(&[0x00; 2], 2),
+            Err(Error::InvalidValueLength { length: 2, .. })
+        ));
+        assert!(matches!(
+            decode_float_le(&[0x00; 3], 4),
+            Err(Error::Truncated(_))
+        ));
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn read_float_le_ok() {
+        let mut cursor = std::io::Cursor::new([1.5f32.to_le_bytes().as_slice(), &[0xAA]].concat());
+        assert_eq!(read_float_le(&mut cursor, 4).unwrap(), 1.5);
+        assert_eq!(cursor.position(), 4);
+        assert!(read_float_le(&mut cursor, 8).is_err());
+        assert_eq!(cursor.position(), 4);
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn read_float_rejects_wrapping_length() {
+        // 260 wraps around to 4 when narrowed to a `u8`.
+        let mut cursor = std::io::Cursor::new([0x3F; 260]);
+        assert!(matches!(
+            read_float(&mut cursor, 260),
+            Err(Error::InvalidValueLength { length: 260, .. })
+        ));
+        assert_eq!(cursor.position(), 0);
+    }
+}
@@ -1,16 +1,25 @@
 use bitvec::field::BitField;
 use bitvec::order::Msb0;
 use bitvec::view::BitView;
+#[cfg(feature = "std")]
 use byteorder::BigEndian;
+#[cfg(feature = "std")]
 use byteorder::ReadBytesExt;
-use std::io::Seek;
 
+#[cfg(feature = "std")]
 use std::io::Read;
+#[cfg(feature = "std")]
+use std::io::Seek;
+
+#[cfg(feature = "std")]
+use crate::encoding::{integer_length, rewind_on_error};
+use alloc::vec::Vec;
 
-use crate::encoding::Error;
+use crate::encoding::{Error, PrecisionLoss, fits_f64_mantissa};
 
 /// Integer types that can be read in using `read_signed_integer`.
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 pub enum SignedInteger {
     I8(i8),
     I16(i16),
@@ -19,6 +28,157 @@ pub enum SignedInteger {
     I128(i128),
 }
 
+impl From<SignedInteger> for i128 {
+    fn from(value: SignedInteger) -> Self {
+        match value {
+            SignedInteger::I8(value) => value.into(),
+            SignedInteger::I16(value) => value.into(),
+            SignedInteger::I32(value) => value.into(),
+            SignedInteger::I64(value) => value.into(),
+            SignedInteger::I128(value) => value,
+        }
+    }
+}
+
+impl SignedInteger {
+    /// Decodes a signed integer from every byte of `bytes`, following the same
+    /// rules as `read_signed_integer`, including sign extension from the most
+    /// significant bit.
+    ///
+    /// # Returns
+    ///
+    /// - `Ok(SignedInteger)` - The integer in the smallest datatype the number
+    ///   of bytes fits in.
+    /// - `Err(encoding::Error::InvalidValueLength)` - `bytes` is empty or
+    ///   longer than 16 bytes.
+    pub fn from_be_bytes(bytes: &[u8]) -> Result<Self, Error> {
+        let length = u8::try_from(bytes.len()).map_err(|_| Error::InvalidValueLength {
+            what: "signed integer",
+            length: bytes.len() as u64,
+        })?;
+        Ok(decode_signed_integer(bytes, length)?.0)
+    }
+
+    /// Two's complement big-endian bytes of the integer, as many as its
+    /// datatype holds (e.g., an `I32` is always 4 bytes).
+    pub fn to_be_bytes(&self) -> Vec<u8> {
+        match self {
+            Self::I8(value) => value.to_be_bytes().to_vec(),
+            Self::I16(value) => value.to_be_bytes().to_vec(),
+            Self::I32(value) => value.to_be_bytes().to_vec(),
+            Self::I64(value) => value.to_be_bytes().to_vec(),
+            Self::I128(value) => value.to_be_bytes().to_vec(),
+        }
+    }
+
+    /// Two's complement big-endian bytes of the integer without leading bytes
+    /// that only repeat the sign bit.
+    pub fn to_minimal_be_bytes(&self) -> Vec<u8> {
+        let value = i128::from(*self);
+        let redundant = if value < 0 {
+            value.leading_ones()
+        } else {
+            value.leading_zeros()
+        } as usize;
+        // One sign bit must remain.
+        value.to_be_bytes()[(redundant - 1) / 8..].to_vec()
+    }
+
+    /// Converts the integer to an `f64`, failing if it would be rounded.
+    ///
+    /// # Returns
+    ///
+    /// - `Ok(f64)` - The integer converted without any loss.
+    /// - `Err(PrecisionLoss)` - The integer has more significant bits than an
+    ///   `f64` can hold.
+    pub fn to_f64_exact(&self) -> Result<f64, PrecisionLoss> {
+        let nearest = self.to_f64_lossy();
+        if fits_f64_mantissa(i128::from(*self).unsigned_abs()) {
+            Ok(nearest)
+        } else {
+            Err(PrecisionLoss { nearest })
+        }
+    }
+
+    /// Converts the integer to the nearest `f64`.
+    pub fn to_f64_lossy(&self) -> f64 {
+        i128::from(*self) as f64
+    }
+}
+
+/// Decode a variable length signed integer from the start of a slice.
+///
+/// Successfully decoded signed integers are always returned in the smallest
+/// datatype the number of bytes fits in (e.g., 3 bytes will always return an
+/// `i32`).
+///
+/// # Args
+///
+/// - `bytes` - Slice to decode from.
+/// - `length` - Number of bytes to decode and interpret as a signed integer.
+///
+/// # Returns
+///
+/// - `Ok((SignedInteger, usize))` - The decoded integer and the number of
+///   bytes it was encoded in, which is always `length`.
+/// - `Err(encoding::Error::InvalidValueLength)` - `length` is zero or cannot
+///   fit into a signed integer container.
+/// - `Err(encoding::Error::Truncated)` - The slice is shorter than `length`.
+pub fn decode_signed_integer(bytes: &[u8], length: u8) -> Result<(SignedInteger, usize), Error> {
+    if !(1..=16).contains(&length) {
+        return Err(Error::InvalidValueLength {
+            what: "signed integer",
+            length: length.into(),
+        });
+    }
+    let bytes = bytes
+        .get(..length as usize)
+        .ok_or(Error::Truncated("signed integer"))?;
+
+    // Sign extend into an i128 and then narrow to the container for the
+    // length.
+    let mut bits_mut = bytes.view_bits::<Msb0>().to_bitvec();
+    let is_negative = *bits_mut.first().unwrap();
+    ((length as u32 * 8)..i128::BITS).for_each(|_| bits_mut.insert(0, is_negative));
+    let value: i128 = bits_mut.load_be();
+
+    let value = match length {
+        1 => SignedInteger::I8(value as i8),
+        2 => SignedInteger::I16(value as i16),
+        3 | 4 => SignedInteger::I32(value as i32),
+        5..=8 => SignedInteger::I64(value as i64),
+        _ => SignedInteger::I128(value),
+    };
+
+    Ok((value, length as usize))
+}
+
+/// Decode a variable length little-endian signed integer from the start of a
+/// slice, such as one from a vendor payload carried inside a KLV item.
+///
+/// See `decode_signed_integer`, which every KLV standard uses.
+pub fn decode_signed_integer_le(bytes: &[u8], length: u8) -> Result<(SignedInteger, usize), Error> {
+    let end = bytes.len().min(length.into()).min(16);
+    let mut reversed = [0; 16];
+    reversed[..end].copy_from_slice(&bytes[..end]);
+    reversed[..end].reverse();
+    decode_signed_integer(&reversed[..end], length)
+}
+
+/// Whether every byte of `bytes` is needed to hold the big-endian signed
+/// integer they encode, i.e., the first byte does not only repeat the sign
+/// bit of the second.
+///
+/// A value that is not minimal can be truncated by the standards that allow
+/// it without changing the integer it decodes to.
+pub fn is_minimal_signed_integer(bytes: &[u8]) -> bool {
+    match bytes {
+        [0x00, next, ..] => next & 0x80 != 0,
+        [0xFF, next, ..] => next & 0x80 == 0,
+        _ => true,
+    }
+}
+
 /// Read in a variable length signed integer.
 ///
 /// Signed integers can be stored in variable lengths that adjust based on their
@@ -42,45 +202,36 @@ pub enum SignedInteger {
 /// # Side Effects
 ///
 /// Moves the current position in the buffer to the byte after the last byte
-/// read
-pub fn read_signed_integer<T>(buf: &mut T, length: u8) -> Result<SignedInteger, Error>
+/// read. The position is left where it was when an error is returned.
+#[cfg(feature = "std")]
+pub fn read_signed_integer<T>(buf: &mut T, length: u64) -> Result<SignedInteger, Error>
 where
     T: Read + Seek,
 {
-    let value = match length {
-        1 => SignedInteger::I8(buf.read_i8()?),
-        2 => SignedInteger::I16(buf.read_i16::<byteorder::BigEndian>()?),
-        3 | 4 => SignedInteger::I32(
-            buf.read_int::<byteorder::BigEndian>(length as usize)?
-                .try_into()
-                .unwrap_or_else(|_| panic!("{length} bytes doesn't fit in an `i32` somehow")),
-        ),
-        5..=8 => SignedInteger::I64(buf.read_int::<byteorder::BigEndian>(length as usize)?),
-        9..=16 => {
-            let bytes = (0..length)
-                .map(|_| buf.read_u8().map_err(Error::from))
-                .collect::<Result<Vec<u8>, Error>>()?;
-            let mut bits_mut = bytes.view_bits::<Msb0>().to_bitvec();
-            let initial_bits = bits_mut.clone();
-            let is_negative = initial_bits.first().unwrap();
-            // If the first bit is set then this represents a negative number
-            // and the MSB must be moved when growing the number to the full
-            // length of an i128
-            if *is_negative {
-                *bits_mut.first_mut().unwrap() = false
-            }
-            // Insert 0s until the bitvec is the same size as a normal i128
-            ((length * 8) as u32..i128::BITS).for_each(|_| bits_mut.insert(0, false));
-            if *is_negative {
-                *bits_mut.first_mut().unwrap() = true
-            }
+    rewind_on_error(buf, |buf| {
+        let length = integer_length(length, "signed integer")?;
+        let mut bytes = [0; 16];
+        buf.read_exact(&mut bytes[..length as usize])?;
 
-            SignedInteger::I128(bits_mut.load_be())
-        }
-        _ => return Err(Error::DecodingError("signed_integer".to_string())),
-    };
+        Ok(decode_signed_integer(&bytes, length)?.0)
+    })
+}
 
-    Ok(value)
+/// Read in a variable length little-endian signed integer.
+///
+/// See `read_signed_integer`, which every KLV standard uses.
+#[cfg(feature = "std")]
+pub fn read_signed_integer_le<T>(buf: &mut T, length: u64) -> Result<SignedInteger, Error>
+where
+    T: Read + Seek,
+{
+    rewind_on_error(buf, |buf| {
+        let length = integer_length(length, "signed integer")?;
+        let mut bytes = [0; 16];
+        buf.read_exact(&mut bytes[..length as usize])?;
+
+        Ok(decode_signed_integer_le(&bytes, length)?.0)
+    })
 }
 
 /// Reads 1 byte and interprets it as na `i8`.
@@ -97,6 +248,7 @@ where
 ///
 /// Moves the current position in the buffer to the byte after the last byte
 /// read
+#[cfg(feature = "std")]
 pub fn read_i8<T>(buf: &mut T) -> Result<i8, Error>
 where
     T: Read + Seek,
@@ -118,6 +270,7 @@ where
 ///
 /// Moves the current position in the buffer to the byte after the last byte
 /// read
+#[cfg(feature = "std")]
 pub fn read_i16<T>(buf: &mut T) -> Result<i16, Error>
 where
     T: Read + Seek,
@@ -139,6 +292,7 @@ where
 ///
 /// Moves the current position in the buffer to the byte after the last byte
 /// read
+#[cfg(feature = "std")]
 pub fn read_i32<T>(buf: &mut T) -> Result<i32, Error>
 where
     T: Read + Seek,
@@ -160,6 +314,7 @@ where
 ///
 /// Moves the current position in the buffer to the byte after the last byte
 /// read
+#[cfg(feature = "std")]
 pub fn read_i64<T>(buf: &mut T) -> Result<i64, Error>
 where
     T: Read + Seek,
@@ -181,6 +336,7 @@ where
 ///
 /// Moves the current position in the buffer to the byte after the last byte
 /// read
+#[cfg(feature = "std")]
 pub fn read_i128<T>(buf: &mut T) -> Result<i128, Error>
 where
     T: Read + Seek,
@@ -188,13 +344,67 @@ where
     Ok(buf.read_i128::<BigEndian>()?)
 }
 
-#[cfg(test)]
+#[cfg(all(test, feature = "std"))]
 mod tests {
     use std::io;
 
     use super::*;
     use test_case::test_case;
 
+    const TWO_POW_53: i64 = 1 << 53;
+
+    #[test_case(1, false)]
+    #[test_case(2, true)]
+    #[test_case(3, false)]
+    #[test_case(4, true)]
+    #[test_case(5, true)]
+    #[test_case(8, false)]
+    #[test_case(9, true)]
+    #[test_case(12, false)]
+    #[test_case(16, true)]
+    fn little_endian_matches_from_le_bytes(length: u8, negative: bool) {
+        let mut bytes: Vec<u8> = (1..=length)
+            .map(|byte| byte.wrapping_mul(0x11) & 0x7F)
+            .collect();
+        if negative {
+            bytes[length as usize - 1] |= 0x80;
+        }
+        // Sign extend the way `from_le_bytes` expects.
+        let mut padded = [if negative { 0xFF } else { 0x00 }; 16];
+        padded[..bytes.len()].copy_from_slice(&bytes);
+
+        let (value, read) = decode_signed_integer_le(&bytes, length).unwrap();
+        assert_eq!(i128::from(value), i128::from_le_bytes(padded));
+        assert_eq!(read, length as usize);
+        assert_eq!(
+            read_signed_integer_le(&mut io::Cursor::new(&bytes), length.into()).unwrap(),
+            value
+        );
+    }
+
+    #[test_case(SignedInteger::I64(TWO_POW_53 - 1), true; "Below 2^53")]
+    #[test_case(SignedInteger::I64(TWO_POW_53), true; "2^53")]
+    #[test_case(SignedInteger::I64(TWO_POW_53 + 1), false; "Above 2^53")]
+    #[test_case(SignedInteger::I64(-TWO_POW_53 + 1), true; "Above minus 2^53")]
+    #[test_case(SignedInteger::I64(-TWO_POW_53), true; "Minus 2^53")]
+    #[test_case(SignedInteger::I64(-TWO_POW_53 - 1), false; "Below minus 2^53")]
+    #[test_case(SignedInteger::I64(i64::MIN), true; "i64 Min")]
+    #[test_case(SignedInteger::I64(i64::MAX), false; "i64 Max")]
+    #[test_case(SignedInteger::I128(i128::MIN), true; "i128 Min")]
+    fn to_f64_exact(integer: SignedInteger, exact: bool) {
+        let lossy = integer.to_f64_lossy();
+        match integer.to_f64_exact() {
+            Ok(value) => {
+                assert!(exact);
+                assert_eq!(value as i128, i128::from(integer));
+            }
+            Err(error) => {
+                assert!(!exact);
+                assert_eq!(error.nearest, lossy);
+            }
+        }
+    }
+
     #[test_case(&[0x00], SignedInteger::I8(0); "i8 Zero")]
     #[test_case(&[0x80], SignedInteger::I8(i8::MIN); "i8 Min")]
     #[test_case(&[0x7F], SignedInteger::I8(i8::MAX); "i8 Max")]
@@ -231,11 +441,111 @@ mod tests {
         assert_eq!(err.kind(), expected.kind())
     }
 
-    #[test_case( &[0x00, 0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08, 0x09, 0x0A, 0x0B, 0x0C, 0x0D, 0x0E, 0x0F], 17, Error::DecodingError("signed_integer".to_string()); "Length too long")]
-    #[test_case( &[0x00], 0, Error::DecodingError("signed_integer".to_string()); "Length to read is zero")]
-    fn read_signed_integer_decoding_err(input: &[u8], length: u8, expected: Error) {
+    #[test_case( &[0x00, 0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08, 0x09, 0x0A, 0x0B, 0x0C, 0x0D, 0x0E, 0x0F], 17; "Length too long")]
+    #[test_case( &[0x00], 0; "Length to read is zero")]
+    fn read_signed_integer_decoding_err(input: &[u8], length: u64) {
         let err = read_signed_integer(&mut std::io::Cursor::new(input), length)
             .expect_err("Testcase should fail here but does not");
-        assert_eq!(err.to_string(), expected.to_string())
+        assert!(matches!(
+            err,
+            Error::InvalidValueLength { what: "signed integer", length: read } if read == length
+        ))
+    }
+
+    #[test]
+    fn read_signed_integer_rejects_wrapping_length() {
+        // 260 wraps around to 4 when narrowed to a `u8`, which would read a
+        // bogus `i32` from the first 4 bytes.
+        let mut cursor = std::io::Cursor::new([0x01; 260]);
+        assert!(matches!(
+            read_signed_integer(&mut cursor, 260),
+            Err(Error::InvalidValueLength { length: 260, .. })
+        ));
+        assert_eq!(cursor.position(), 0);
+    }
+}
+
+#[cfg(test)]
+mod slice_tests {
+    use super::*;
+    use test_case::test_case;
+
+    #[test_case(&[0xFF], SignedInteger::I8(-1); "i8 Negative one")]
+    #[test_case(&[0x80, 0x00], SignedInteger::I16(i16::MIN); "i16 Min")]
+    #[test_case(&[0xFF, 0xFF, 0xFE], SignedInteger::I32(-2); "i24 Negative two")]
+    #[test_case(&[0x7F, 0xFF, 0xFF], SignedInteger::I32(0x7F_FFFF); "i24 Max")]
+    #[test_case(&[0x80, 0x00, 0x00, 0x00, 0x00], SignedInteger::I64(-(1 << 39)); "i40 Min")]
+    #[test_case(&[0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF], SignedInteger::I128(-1); "i72 Negative one")]
+    #[test_case(&[0x80, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00], SignedInteger::I128(i128::MIN); "i128 Min")]
+    fn decode_signed_integer_ok(input: &[u8], expected: SignedInteger) {
+        assert_eq!(
+            decode_signed_integer(input, input.len() as u8).unwrap(),
+            (expected, input.len())
+        );
+    }
+
+    #[test_case(&[0x00], true; "Zero")]
+    #[test_case(&[0x00, 0x80], true; "Sign byte needed")]
+    #[test_case(&[0xFF, 0x7F], true; "Negative sign byte needed")]
+    #[test_case(&[0x00, 0x7F], false; "Redundant zero")]
+    #[test_case(&[0xFF, 0xFF, 0x80], false; "Redundant sign byte")]
+    fn is_minimal_signed_integer_ok(input: &[u8], expected: bool) {
+        assert_eq!(is_minimal_signed_integer(input), expected);
+    }
+
+    #[test]
+    fn decode_signed_integer_ignores_trailing_bytes() {
+        assert_eq!(
+            decode_signed_integer(&[0x01, 0x02, 0x03], 2).unwrap(),
+            (SignedInteger::I16(0x0102), 2)
+        );
+    }
+
+    #[test_case(&[0x00], SignedInteger::I8(0), &[0x00]; "Zero")]
+    #[test_case(&[0xFF, 0xFF], SignedInteger::I16(-1), &[0xFF]; "Negative one")]
+    #[test_case(&[0x00, 0x80], SignedInteger::I16(0x80), &[0x00, 0x80]; "Sign byte kept")]
+    #[test_case(&[0xFF, 0x7F], SignedInteger::I16(-0x81), &[0xFF, 0x7F]; "Negative sign byte kept")]
+    #[test_case(&[0xFF, 0x80, 0x00], SignedInteger::I32(-0x8000), &[0x80, 0x00]; "i24")]
+    #[test_case(&[0x00, 0x00, 0x00, 0x00, 0x01], SignedInteger::I64(1), &[0x01]; "i40 One")]
+    #[test_case(&[0xFF; 9], SignedInteger::I128(-1), &[0xFF]; "i72 Negative one")]
+    #[test_case(&[0x80, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00], SignedInteger::I128(i128::MIN), &[0x80, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00]; "i128 Min")]
+    fn from_be_bytes_matches_reader(input: &[u8], expected: SignedInteger, minimal: &[u8]) {
+        let value = SignedInteger::from_be_bytes(input).unwrap();
+        assert_eq!(value, expected);
+        #[cfg(feature = "std")]
+        assert_eq!(
+            read_signed_integer(&mut std::io::Cursor::new(input), input.len() as u64).unwrap(),
+            value
+        );
+
+        assert_eq!(
+            SignedInteger::from_be_bytes(&value.to_be_bytes()).unwrap(),
+            value
+        );
+        assert_eq!(value.to_minimal_be_bytes(), minimal);
+        assert_eq!(
+            i128::from(SignedInteger::from_be_bytes(minimal).unwrap()),
+            i128::from(value)
+        );
+    }
+
+    #[test]
+    fn decode_signed_integer_errors() {
+        assert!(matches!(
+            decode_signed_integer(&[0x01], 2),
+            Err(Error::Truncated(_))
+        ));
+        assert!(matches!(
+            decode_signed_integer(&[0x01], 0),
+            Err(Error::InvalidValueLength { .. })
+        ));
+        assert!(matches!(
+            decode_signed_integer(&[0x01; 17], 17),
+            Err(Error::InvalidValueLength { .. })
+        ));
+        assert!(matches!(
+            SignedInteger::from_be_bytes(&[]),
+            Err(Error::InvalidValueLength { .. })
+        ));
     }
 }
@@ -0,0 +1,109 @@
+//! Bounded reads and copies of values whose lengths come from the stream
+//! being read, so can't be trusted to fit in memory or in what is left of
+//! the stream.
+
+use std::io::{self, Read, Write};
+
+/// Number of bytes `read_up_to` callers grow their buffer by at a time.
+pub const DEFAULT_CHUNK: usize = 8192;
+
+/// Reads up to `n` bytes from `buf`, growing the returned buffer by at most
+/// `chunk` bytes at a time so a corrupt length can't cause a huge
+/// allocation.
+///
+/// # Returns
+///
+/// - `Ok((Vec<u8>, false))` - All `n` bytes were read.
+/// - `Ok((Vec<u8>, true))` - `buf` ran out of bytes first. The returned
+///   bytes are every byte that was left.
+/// - `Err(io::Error)` - Any other issue reading from `buf`.
+pub fn read_up_to<T>(buf: &mut T, n: u64, chunk: usize) -> Result<(Vec<u8>, bool), io::Error>
+where
+    T: Read,
+{
+    let chunk = chunk.max(1) as u64;
+    let mut bytes = Vec::new();
+    let mut remaining = n;
+    while remaining > 0 {
+        let size = remaining.min(chunk);
+        let read = buf.by_ref().take(size).read_to_end(&mut bytes)? as u64;
+        if read < size {
+            return Ok((bytes, true));
+        }
+        remaining -= read;
+    }
+    Ok((bytes, false))
+}
+
+/// Reads exactly `n` bytes from `buf`, growing the returned buffer a chunk at
+/// a time like `read_up_to`.
+///
+/// # Returns
+///
+/// - `Ok(Vec<u8>)` - All `n` bytes were read.
+/// - `Err(io::Error)` - `buf` ran out of bytes first, with
+///   `io::ErrorKind::UnexpectedEof`, or any other issue reading from `buf`.
+pub fn read_exactly<T>(buf: &mut T, n: u64) -> Result<Vec<u8>, io::Error>
+where
+    T: Read,
+{
+    match read_up_to(buf, n, DEFAULT_CHUNK)? {
+        (bytes, false) => Ok(bytes),
+        (_, true) => Err(io::ErrorKind::UnexpectedEof.into()),
+    }
+}
+
+/// Copies up to `n` bytes from `r` to `w`.
+///
+/// # Returns
+///
+/// - `Ok(u64)` - Number of bytes copied, which is less than `n` when `r` ran
+///   out of bytes first.
+/// - `Err(io::Error)` - Any issue reading from `r` or writing to `w`.
+pub fn copy_limited<R, W>(r: &mut R, w: &mut W, n: u64) -> Result<u64, io::Error>
+where
+    R: Read + ?Sized,
+    W: Write + ?Sized,
+{
+    io::copy(&mut r.take(n), w)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_case::test_case;
+
+    #[test_case(4, 2, &[1, 2, 3, 4], false; "Exact")]
+    #[test_case(6, 4, &[1, 2, 3, 4, 5], true; "Short")]
+    #[test_case(0, 4, &[], false; "Zero length")]
+    #[test_case(3, 0, &[1, 2, 3], false; "Zero chunk")]
+    fn read_up_to_reports_eof(n: u64, chunk: usize, expected: &[u8], eof: bool) {
+        let mut buf: &[u8] = &[1, 2, 3, 4, 5];
+        assert_eq!(
+            read_up_to(&mut buf, n, chunk).unwrap(),
+            (expected.to_vec(), eof)
+        );
+        assert_eq!(buf.len(), 5 - expected.len());
+    }
+
+    #[test]
+    fn read_exactly_fails_when_short() {
+        assert_eq!(read_exactly(&mut &[1, 2][..], 2).unwrap(), [1, 2]);
+        assert_eq!(
+            read_exactly(&mut &[1, 2][..], 3).unwrap_err().kind(),
+            io::ErrorKind::UnexpectedEof
+        );
+    }
+
+    #[test_case(3, 3; "Exact")]
+    #[test_case(8, 5; "Short")]
+    #[test_case(0, 0; "Zero length")]
+    fn copy_limited_counts_bytes(n: u64, copied: u64) {
+        let mut output = Vec::new();
+        assert_eq!(
+            copy_limited(&mut &[1, 2, 3, 4, 5][..], &mut output, n).unwrap(),
+            copied
+        );
+        assert_eq!(output, [1, 2, 3, 4, 5][..copied as usize]);
+    }
+}
@@ -0,0 +1,186 @@
+//! Linear mappings of integers onto floating-point ranges, such as the
+//! scaled angles and distances of MISB ST 0601.
+
+/// Error returned when a value can't be mapped onto a raw integer because it
+/// is outside of the output range, is not a number, or rounds to the
+/// sentinel.
+#[derive(Clone, Copy, Debug, PartialEq, thiserror::Error)]
+#[error("{value} is outside of the range {min}..={max}")]
+pub struct OutOfRange {
+    pub value: f64,
+    pub min: f64,
+    pub max: f64,
+}
+
+/// Maps the raw integers `raw_min..=raw_max` evenly onto the values
+/// `out_min..=out_max`, so that `raw_min` is `out_min` and `raw_max` is
+/// `out_max`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct LinearMap {
+    pub raw_min: i128,
+    pub raw_max: i128,
+    pub out_min: f64,
+    pub out_max: f64,
+    /// Raw value reserved to mean something other than a number, such as
+    /// "Out of Range". It decodes to `None` and is never encoded.
+    pub sentinel: Option<i128>,
+}
+
+impl LinearMap {
+    /// The mapping MISB standards give an unsigned integer `length` bytes
+    /// long, whose full range maps onto `min..=max`.
+    ///
+    /// The raw range of 16 byte integers is cut short at `i128::MAX`.
+    ///
+    /// # Panics
+    ///
+    /// - `length` is zero or more than 16.
+    pub fn unsigned(length: usize, min: f64, max: f64) -> Self {
+        assert!((1..=16).contains(&length), "{length} byte integer");
+        Self {
+            raw_min: 0,
+            raw_max: (u128::MAX >> (128 - 8 * length)).min(i128::MAX as u128) as i128,
+            out_min: min,
+            out_max: max,
+            sentinel: None,
+        }
+    }
+
+    /// The mapping MISB standards give a signed integer `length` bytes long,
+    /// whose range excluding its most negative value maps onto `min..=max`.
+    /// The most negative value lies outside of the mapping and decodes to
+    /// `None`.
+    ///
+    /// # Panics
+    ///
+    /// - `length` is zero or more than 16.
+    pub fn signed(length: usize, min: f64, max: f64) -> Self {
+        assert!((1..=16).contains(&length), "{length} byte integer");
+        let half = (u128::MAX >> (129 - 8 * length)) as i128;
+        Self {
+            raw_min: -half,
+            raw_max: half,
+            out_min: min,
+            out_max: max,
+            sentinel: None,
+        }
+    }
+
+    /// Adds a raw value reserved to mean something other than a number.
+    pub fn with_sentinel(self, sentinel: i128) -> Self {
+        Self {
+            sentinel: Some(sentinel),
+            ..self
+        }
+    }
+
+    /// Maps `raw` onto the output range.
+    ///
+    /// # Returns
+    ///
+    /// - `Some(f64)` - The mapped value.
+    /// - `None` - `raw` is the sentinel or outside of `raw_min..=raw_max`.
+    pub fn decode(&self, raw: i128) -> Option<f64> {
+        if self.sentinel == Some(raw) || !(self.raw_min..=self.raw_max).contains(&raw) {
+            return None;
+        }
+        Some(
+            (raw - self.raw_min) as f64 * (self.out_max - self.out_min) / self.steps()
+                + self.out_min,
+        )
+    }
+
+    /// Maps `value` onto the nearest raw integer, rounding halfway cases to
+    /// the even one.
+    ///
+    /// # Returns
+    ///
+    /// - `Ok(i128)` - The raw integer.
+    /// - `Err(OutOfRange)` - `value` is outside of `out_min..=out_max`, is
+    ///   not a number, or rounds to the sentinel.
+    pub fn encode(&self, value: f64) -> Result<i128, OutOfRange> {
+        let out_of_range = OutOfRange {
+            value,
+            min: self.out_min,
+            max: self.out_max,
+        };
+        if !(self.out_min..=self.out_max).contains(&value) {
+            return Err(out_of_range);
+        }
+        let steps = ((value - self.out_min) * self.steps() / (self.out_max - self.out_min))
+            .round_ties_even() as i128;
+        let raw = self.raw_min + steps;
+        if self.sentinel == Some(raw) {
+            return Err(out_of_range);
+        }
+        Ok(raw)
+    }
+
+    /// Number of raw steps between the ends of the mapping.
+    fn steps(&self) -> f64 {
+        (self.raw_max - self.raw_min) as f64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_case::test_case;
+
+    /// Three raw values, one per unit.
+    const UNIT: LinearMap = LinearMap {
+        raw_min: 0,
+        raw_max: 2,
+        out_min: 0.0,
+        out_max: 2.0,
+        sentinel: None,
+    };
+
+    #[test_case(0.0, 0; "Bottom of range")]
+    #[test_case(0.5, 0; "Half rounds down to even")]
+    #[test_case(1.5, 2; "Half rounds up to even")]
+    #[test_case(1.4, 1; "Below half")]
+    #[test_case(2.0, 2; "Top of range")]
+    fn encode_rounds_half_to_even(value: f64, expected: i128) {
+        assert_eq!(UNIT.encode(value), Ok(expected));
+    }
+
+    #[test_case(-0.001; "Below range")]
+    #[test_case(2.001; "Above range")]
+    #[test_case(f64::NAN; "Not a number")]
+    fn encode_out_of_range(value: f64) {
+        assert!(UNIT.encode(value).is_err());
+    }
+
+    #[test_case(0, Some(-90.0); "Bottom of range")]
+    #[test_case(0x7FFF_FFFF, Some(0.0); "Middle of range")]
+    #[test_case(0xFFFF_FFFF, None; "Sentinel")]
+    fn unsigned_decode(raw: i128, expected: Option<f64>) {
+        let map = LinearMap::unsigned(4, -90.0, 90.0).with_sentinel(0xFFFF_FFFF);
+        match expected {
+            Some(expected) => assert!((map.decode(raw).unwrap() - expected).abs() < 1e-7),
+            None => assert_eq!(map.decode(raw), None),
+        }
+    }
+
+    #[test]
+    fn signed_ends_map_onto_range() {
+        let map = LinearMap::signed(2, -20.0, 20.0);
+
+        assert_eq!(map.decode(-0x7FFF), Some(-20.0));
+        assert_eq!(map.decode(0), Some(0.0));
+        assert_eq!(map.decode(0x7FFF), Some(20.0));
+        // The most negative value is reserved.
+        assert_eq!(map.decode(-0x8000), None);
+        assert_eq!(map.encode(-20.0), Ok(-0x7FFF));
+        assert_eq!(map.encode(20.0), Ok(0x7FFF));
+    }
+
+    #[test]
+    fn sentinel_is_never_encoded() {
+        let map = UNIT.with_sentinel(2);
+        assert_eq!(map.decode(2), None);
+        assert!(map.encode(2.0).is_err());
+        assert_eq!(map.encode(1.4), Ok(1));
+    }
+}
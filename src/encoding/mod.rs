@@ -1,15 +1,27 @@
-use std::io;
+use alloc::{string::String, vec::Vec};
+#[cfg(feature = "std")]
+use std::{
+    io::{self, Seek, SeekFrom},
+    path::PathBuf,
+};
 
 use crate::encoding::{integer::SignedInteger, unsigned_integer::UnsignedInteger};
 
 pub mod ber;
 pub mod ber_oid;
+pub mod date;
+pub mod float;
 pub mod integer;
+#[cfg(feature = "std")]
+pub mod io_util;
+#[cfg(feature = "std")]
+pub mod linear;
 pub mod unsigned_integer;
 
 /// Values enumerated here are copied from _Table 40_ on page 115 of
 /// _MISP-2025.1: Motion Imagery Handbook_
 #[derive(Clone, Debug, strum::EnumDiscriminants)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 pub enum SimpleDataType {
     Ber(u128),
     BerOid(u128),
@@ -34,7 +46,7 @@ pub enum SimpleDataType {
     /// publications. Some existing MISP standards use ISO7 for historical and
     /// backward compatibility reasons. The replacement data type for ISO7 is
     /// UTF8.
-    Iso7(String),
+    Iso7(#[cfg_attr(feature = "arbitrary", arbitrary(with = arbitrary_iso7))] String),
 
     /// MISP standards use UTF8 for character strings because it expands as
     /// necessary to support alternate languages in support of NATO countries.
@@ -62,12 +74,475 @@ pub enum SimpleDataType {
 
     SignedInteger(SignedInteger),
     UnsignedInteger(UnsignedInteger),
+
+    /// A 16 byte universally unique identifier as specified by RFC 9562,
+    /// such as the identifiers of MISB ST 1204.
+    #[cfg(feature = "uuid")]
+    Uuid(uuid::Uuid),
+}
+
+/// Error returned when a format string from a MISB tag table could not be
+/// parsed.
+#[derive(Clone, Debug, PartialEq, Eq, thiserror::Error)]
+pub enum ParseFormatError {
+    #[error("`{0}` is not a format used by MISB tag tables")]
+    Unrecognized(String),
+    /// The string names a set or pack, which are groups of items rather than
+    /// a format of a single value.
+    #[error("`{0}` is a group, not the format of a single value")]
+    Group(String),
+    /// The string is a MISB format that has no equivalent in the type being
+    /// parsed.
+    #[error("`{0}` is not supported")]
+    Unsupported(String),
+}
+
+/// Error returned when an integer can't be converted to an `f64` without
+/// rounding, which happens to integers above 2^53 that need more than the 53
+/// bits of precision an `f64` has.
+#[derive(Clone, Copy, Debug, PartialEq, thiserror::Error)]
+#[error("Integer can't be represented exactly as an f64, the nearest is {nearest}")]
+pub struct PrecisionLoss {
+    /// The `f64` the integer rounds to.
+    pub nearest: f64,
+}
+
+/// Whether an integer with the magnitude `value` has no more significant
+/// bits than the mantissa of an `f64` can hold.
+fn fits_f64_mantissa(value: u128) -> bool {
+    value == 0 || value >> value.trailing_zeros() < 1 << f64::MANTISSA_DIGITS
+}
+
+impl SimpleDataTypeDiscriminants {
+    /// Parses a format as written in the _Format_ column of MISB tag tables,
+    /// such as `uint16`, `IMAPB`, or `BER-OID`.
+    ///
+    /// Case, whitespace, `-`, and `_` are ignored, and integer and floating
+    /// point widths are optional since the tables often leave them out.
+    pub fn from_misb_str(s: &str) -> Result<Self, ParseFormatError> {
+        let normalized: String = s
+            .chars()
+            .filter(|c| !c.is_whitespace() && !matches!(c, '-' | '_'))
+            .flat_map(char::to_lowercase)
+            .collect();
+        let sized = |prefix: &str| {
+            normalized
+                .strip_prefix(prefix)
+                .is_some_and(|width| width.chars().all(|c| c.is_ascii_digit()))
+        };
+
+        Ok(match normalized.as_str() {
+            "ber" => Self::Ber,
+            "beroid" | "oid" => Self::BerOid,
+            "binary" | "byte" | "bytes" | "bytearray" | "opaque" => Self::Binary,
+            "bool" | "boolean" => Self::Boolean,
+            "iso7" | "iso646" | "ascii" => Self::Iso7,
+            "utf8" | "string" => Self::Utf8,
+            "utf16" => Self::Utf16,
+            "enum" | "enumeration" | "enumerated" => Self::Enumeration,
+            "imap" | "imapa" | "imapb" => Self::IMAP,
+            "double" | "ieee754" => Self::FloatingPoint,
+            #[cfg(feature = "uuid")]
+            "uuid" => Self::Uuid,
+            "set" | "localset" | "dlp" | "vlp" | "flp" | "pack" | "truncationpack" => {
+                return Err(ParseFormatError::Group(s.into()));
+            }
+            _ if sized("uint") || sized("unsignedint") || sized("unsignedinteger") => {
+                Self::UnsignedInteger
+            }
+            _ if sized("int") || sized("integer") || sized("signedint") => Self::SignedInteger,
+            _ if sized("float") => Self::FloatingPoint,
+            _ => return Err(ParseFormatError::Unrecognized(s.into())),
+        })
+    }
+}
+
+/// Generates a string of ISO 646 characters, which all fit in 7 bits.
+#[cfg(feature = "arbitrary")]
+fn arbitrary_iso7(u: &mut arbitrary::Unstructured<'_>) -> arbitrary::Result<String> {
+    u.arbitrary_iter::<u8>()?
+        .map(|byte| byte.map(|byte| char::from(byte & 0x7F)))
+        .collect()
 }
 
 #[derive(Debug, strum::EnumTryAs, thiserror::Error)]
 pub enum Error {
-    #[error("Failed to decode {0}")]
-    DecodingError(String),
+    /// A value is a length its format can't be decoded from, such as a 17
+    /// byte integer or a 6 byte float.
+    #[error("Failed to decode {length} byte {what}")]
+    InvalidValueLength { what: &'static str, length: u64 },
+    /// A value was decoded but is not one `what` can hold, such as a boolean
+    /// byte other than 0 or 1.
+    ///
+    /// The values reported here are integers of up to 128 bits, floats,
+    /// lengths, raw bytes and software values, so `value` holds the value
+    /// formatted where it was found rather than a single numeric type.
+    #[error("{value} is out of the range of {what}")]
+    ValueOutOfRange { what: String, value: String },
+    /// A string holds a byte or sequence of bytes its encoding does not
+    /// allow. `offset` is that of the first such byte within the string.
+    #[error("Invalid character at byte {offset} of {what}")]
+    InvalidCharacter { what: &'static str, offset: usize },
+    /// A variable length value needs more bits than the widest integer it can
+    /// be decoded into.
+    #[error("{what} value needs {bits} bits, more than fit in a u128 without the `bignum` feature")]
+    TooLarge { what: &'static str, bits: usize },
+    /// A value of the format `from` can't be converted to or from `to`.
+    #[error("Can't convert between {from} and {to}")]
+    IncompatibleFormats { from: String, to: String },
+    /// A value of `what` can't be converted without `parameter`, such as the
+    /// range of an IMAPB value.
+    #[error("{what} value without a {parameter}")]
+    MissingParameter {
+        what: String,
+        parameter: &'static str,
+    },
+    /// `value` is not laid out the way `what` is.
+    #[error("Failed to decode {value} as {what}")]
+    Malformed { what: &'static str, value: String },
+    /// The value of `what` is one of its special values, which have no plain
+    /// value to convert.
+    #[error("{what} holds a special value")]
+    SpecialValue { what: &'static str },
+    /// The check value written alongside `what` does not match its contents.
+    #[error("{what} has check value {expected:04X} but its contents give {actual:04X}")]
+    CheckValueMismatch {
+        what: &'static str,
+        expected: u16,
+        actual: u16,
+    },
+    /// A set is missing an item that it must hold to be decoded.
+    #[error("{set} without a {item}")]
+    MissingItem {
+        set: &'static str,
+        item: &'static str,
+    },
+    #[error(transparent)]
+    PrecisionLoss(#[from] PrecisionLoss),
+    /// The stream ended cleanly before the first byte of an element.
+    #[error("Reached the end of the stream")]
+    EndOfStream,
+    /// The stream ended part way through an element.
+    #[error("Stream ended in the middle of a {0}")]
+    Truncated(&'static str),
+    /// An item key appeared more than once in a set parsed with
+    /// `DuplicatePolicy::Error`. The offsets are those of the first byte of
+    /// each value.
+    #[cfg(feature = "std")]
+    #[error("Item {key} found at offset {first_offset} is repeated at offset {second_offset}")]
+    DuplicateItem {
+        key: crate::klv::ItemKey,
+        first_offset: u64,
+        second_offset: u64,
+    },
+    /// An item's length breaks the constraint its tag table places on it. The
+    /// offset is that of the first byte of the value.
+    #[cfg(feature = "std")]
+    #[error("Item {key} at offset {offset} is {actual} bytes long, expected {expected:?}")]
+    InvalidLength {
+        key: crate::klv::ItemKey,
+        offset: u64,
+        expected: crate::tag::ValueLength,
+        actual: u64,
+    },
+    /// The checksum of an ST 0601 packet does not match its contents. The
+    /// offset is that of the first byte of the packet's Universal Key.
+    #[cfg(feature = "std")]
+    #[error(
+        "Packet at offset {offset} has checksum {expected:#06x} but its contents sum to {actual:#06x}"
+    )]
+    ChecksumMismatch {
+        offset: u64,
+        expected: u16,
+        actual: u16,
+    },
+    /// An error that happened while reading a set nested in an item, along
+    /// with where that item lies.
+    #[cfg(feature = "std")]
+    #[error("{context}: {source}")]
+    InContext {
+        context: crate::klv::ItemContext,
+        source: Box<Error>,
+    },
+    /// An error that happened while reading the set whose Universal Key
+    /// starts at `offset`.
+    #[cfg(feature = "std")]
+    #[error("Set at offset {offset}: {source}")]
+    InSet { offset: u64, source: Box<Error> },
+    /// The set at `offset` is not the length an index gives it.
+    #[cfg(feature = "std")]
+    #[error("Set at offset {offset} is {actual} bytes long, not {expected} as indexed")]
+    IndexedLengthMismatch {
+        offset: u64,
+        expected: u64,
+        actual: u64,
+    },
+    /// A set nested in the value of the item at `offset` would be more than
+    /// `ParseOptions::max_depth` sets deep.
+    #[error("Set at offset {offset} is nested {depth} sets deep")]
+    DepthExceeded { depth: usize, offset: u64 },
+    /// Reading more would break one of the resource ceilings of
+    /// `ParseOptions`, whose value is kept alongside it.
+    #[error("Exceeded the limit of {1} {0}")]
+    LimitExceeded(crate::options::Limit, u64),
+    /// A scan was cancelled through its `ProgressHandle`. `offsets` are the
+    /// offsets of the first byte of every set found before it was.
+    #[cfg(feature = "std")]
+    #[error("Scan cancelled after {processed} bytes")]
+    Cancelled { processed: u64, offsets: Vec<u64> },
+    /// An error that happened while reading from a file.
+    #[cfg(feature = "std")]
+    #[error("{}: {source}", path.display())]
+    File { path: PathBuf, source: Box<Error> },
+    #[cfg(feature = "std")]
     #[error(transparent)]
-    Other(#[from] io::Error),
+    Other(io::Error),
+}
+
+/// Unwraps the decoding errors that the `io::Error` readers, such as
+/// `read_ber`, report as `io::ErrorKind::InvalidData`, so they can be matched
+/// on like those of the slice decoders.
+#[cfg(feature = "std")]
+impl From<io::Error> for Error {
+    fn from(error: io::Error) -> Self {
+        if error.get_ref().is_some_and(|inner| inner.is::<Error>()) {
+            let inner = error.into_inner().expect("The error has an inner error");
+            return *inner
+                .downcast::<Error>()
+                .expect("The inner error is an Error");
+        }
+        Self::Other(error)
+    }
+}
+
+/// Narrows a length read from a stream to one that can be jumped over with
+/// `Seek`, whose relative seeks take an `i64`.
+///
+/// # Returns
+///
+/// - `Ok(u64)` - `length` is no more than `i64::MAX`.
+/// - `Err(encoding::Error::ValueOutOfRange)` - `length` is larger, which no
+///   real stream is, so the length is corrupt.
+pub fn seekable_length(length: u128) -> Result<u64, Error> {
+    u64::try_from(length)
+        .ok()
+        .filter(|length| i64::try_from(*length).is_ok())
+        .ok_or_else(|| Error::ValueOutOfRange {
+            what: "a seekable length".into(),
+            value: alloc::format!("{length}"),
+        })
+}
+
+/// Offset of the byte after `length` bytes starting at `offset`.
+///
+/// # Returns
+///
+/// - `Ok(u64)` - The offset, which is no more than `i64::MAX`.
+/// - `Err(encoding::Error::ValueOutOfRange)` - The offset is larger, so
+///   `length` is corrupt.
+pub fn checked_end(offset: u64, length: u64) -> Result<u64, Error> {
+    offset
+        .checked_add(length)
+        .filter(|end| i64::try_from(*end).is_ok())
+        .ok_or_else(|| Error::ValueOutOfRange {
+            what: alloc::format!("a length starting at offset {offset}"),
+            value: alloc::format!("{length}"),
+        })
+}
+
+/// Reports a decoding error from one of the `io::Error` readers.
+#[cfg(feature = "std")]
+pub(crate) fn invalid_data(error: Error) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, error)
+}
+
+#[cfg(feature = "std")]
+impl Error {
+    /// Attaches where the item whose value holds the nested set being read
+    /// lies to the error.
+    pub fn in_context(self, context: crate::klv::ItemContext) -> Self {
+        Self::InContext {
+            context,
+            source: Box::new(self),
+        }
+    }
+
+    /// Attaches the offset of the set being read to the error.
+    pub fn in_set(self, offset: u64) -> Self {
+        Self::InSet {
+            offset,
+            source: Box::new(self),
+        }
+    }
+
+    /// Attaches the path of the file being read to the error.
+    pub fn in_file(self, path: impl Into<PathBuf>) -> Self {
+        match self {
+            Self::File { .. } => self,
+            _ => Self::File {
+                path: path.into(),
+                source: Box::new(self),
+            },
+        }
+    }
+}
+
+/// Decodes a UUID from every byte of `bytes`.
+///
+/// # Returns
+///
+/// - `Ok(Uuid)` - The decoded UUID.
+/// - `Err(encoding::Error::InvalidValueLength)` - `bytes` is not exactly 16
+///   bytes.
+#[cfg(feature = "uuid")]
+pub fn decode_uuid(bytes: &[u8]) -> Result<uuid::Uuid, Error> {
+    uuid::Uuid::from_slice(bytes).map_err(|_| uuid_length_error(bytes.len() as u64))
+}
+
+/// Read in a UUID from a value `length` bytes long.
+///
+/// # Returns
+///
+/// - `Ok(Uuid)` - The UUID was read.
+/// - `Err(encoding::Error::InvalidValueLength)` - `length` is not exactly 16.
+/// - `Err(encoding::Error)` - There was an error reading from the buffer.
+///
+/// # Side Effects
+///
+/// Moves the current position in the buffer to the byte after the last byte
+/// read. The position is left where it was when an error is returned.
+#[cfg(feature = "uuid")]
+pub fn read_uuid<T>(buf: &mut T, length: u64) -> Result<uuid::Uuid, Error>
+where
+    T: io::Read + Seek,
+{
+    rewind_on_error(buf, |buf| {
+        if length != 16 {
+            return Err(uuid_length_error(length));
+        }
+        let mut bytes = [0; 16];
+        buf.read_exact(&mut bytes)?;
+        Ok(uuid::Uuid::from_bytes(bytes))
+    })
+}
+
+#[cfg(feature = "uuid")]
+pub(crate) fn uuid_length_error(length: u64) -> Error {
+    Error::InvalidValueLength {
+        what: "UUID",
+        length,
+    }
+}
+
+/// Runs `read` on `buf`, moving the position in the buffer back to where it
+/// was if `read` fails, so the caller can skip ahead and try again.
+///
+/// # Returns
+///
+/// - `Ok(V)` - What `read` returned. The position is left wherever `read`
+///   moved it.
+/// - `Err(E)` - What `read` returned, or the error from seeking back.
+#[cfg(feature = "std")]
+pub fn rewind_on_error<T, V, E>(
+    buf: &mut T,
+    read: impl FnOnce(&mut T) -> Result<V, E>,
+) -> Result<V, E>
+where
+    T: Seek,
+    E: From<io::Error>,
+{
+    let start = buf.stream_position()?;
+    read(buf).or_else(|error| {
+        buf.seek(SeekFrom::Start(start))?;
+        Err(error)
+    })
+}
+
+/// Narrows the length of an integer to read, which KLV lengths hold as a
+/// `u64`, checking it before it can wrap.
+///
+/// # Returns
+///
+/// - `Ok(u8)` - `length` is 1 to 16 bytes.
+/// - `Err(encoding::Error::InvalidValueLength)` - Any other length, named in
+///   the error along with `kind`.
+#[cfg(feature = "std")]
+pub(crate) fn integer_length(length: u64, kind: &'static str) -> Result<u8, Error> {
+    match u8::try_from(length) {
+        Ok(length @ 1..=16) => Ok(length),
+        _ => Err(Error::InvalidValueLength { what: kind, length }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_case::test_case;
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn rewind_on_error_restores_position() {
+        let mut cursor = io::Cursor::new([0x82, 0x01]);
+        assert!(rewind_on_error(&mut cursor, ber::read_ber).is_err());
+        assert_eq!(cursor.position(), 0);
+
+        cursor.get_mut()[0] = 0x01;
+        assert_eq!(rewind_on_error(&mut cursor, ber::read_ber).unwrap(), 1);
+        assert_eq!(cursor.position(), 1);
+    }
+
+    #[cfg(feature = "uuid")]
+    #[test]
+    fn read_uuid_requires_16_bytes() {
+        let bytes = [
+            0x67, 0xE5, 0x50, 0x44, 0x10, 0xB1, 0x42, 0x6F, 0x92, 0x47, 0xBB, 0x68, 0x0E, 0x5F,
+            0xE0, 0xC8,
+        ];
+        let mut cursor = io::Cursor::new(bytes);
+        assert_eq!(
+            read_uuid(&mut cursor, 16).unwrap().to_string(),
+            "67e55044-10b1-426f-9247-bb680e5fe0c8"
+        );
+
+        cursor.set_position(0);
+        assert!(matches!(
+            read_uuid(&mut cursor, 15),
+            Err(Error::InvalidValueLength { length: 15, .. })
+        ));
+        assert_eq!(cursor.position(), 0);
+        assert!(decode_uuid(&bytes[..15]).is_err());
+    }
+
+    #[test_case("uint8", SimpleDataTypeDiscriminants::UnsignedInteger; "Sized uint")]
+    #[test_case("uint", SimpleDataTypeDiscriminants::UnsignedInteger; "Unsized uint")]
+    #[test_case("UINT64", SimpleDataTypeDiscriminants::UnsignedInteger; "Upper case uint")]
+    #[test_case("unsigned int", SimpleDataTypeDiscriminants::UnsignedInteger; "Spelled out uint")]
+    #[test_case("int8", SimpleDataTypeDiscriminants::SignedInteger; "Sized int")]
+    #[test_case("int", SimpleDataTypeDiscriminants::SignedInteger; "Unsized int")]
+    #[test_case("Integer", SimpleDataTypeDiscriminants::SignedInteger; "Integer")]
+    #[test_case("BER-OID", SimpleDataTypeDiscriminants::BerOid; "BER-OID")]
+    #[test_case("ber_oid", SimpleDataTypeDiscriminants::BerOid; "Snake case BER-OID")]
+    #[test_case("BER", SimpleDataTypeDiscriminants::Ber; "BER")]
+    #[test_case("IMAPB", SimpleDataTypeDiscriminants::IMAP; "IMAPB")]
+    #[test_case("imapa", SimpleDataTypeDiscriminants::IMAP; "IMAPA")]
+    #[test_case("utf8", SimpleDataTypeDiscriminants::Utf8; "UTF8")]
+    #[test_case("UTF-8", SimpleDataTypeDiscriminants::Utf8; "Hyphenated UTF8")]
+    #[test_case("UTF16", SimpleDataTypeDiscriminants::Utf16; "UTF16")]
+    #[test_case("ISO7", SimpleDataTypeDiscriminants::Iso7; "ISO7")]
+    #[test_case("Byte", SimpleDataTypeDiscriminants::Binary; "Byte")]
+    #[test_case("float", SimpleDataTypeDiscriminants::FloatingPoint; "Unsized float")]
+    #[test_case("Float32", SimpleDataTypeDiscriminants::FloatingPoint; "Sized float")]
+    #[test_case("bool", SimpleDataTypeDiscriminants::Boolean; "Bool")]
+    #[test_case("Enumerated", SimpleDataTypeDiscriminants::Enumeration; "Enumerated")]
+    fn from_misb_str(s: &str, expected: SimpleDataTypeDiscriminants) {
+        assert_eq!(SimpleDataTypeDiscriminants::from_misb_str(s), Ok(expected));
+    }
+
+    #[test_case("Set", ParseFormatError::Group("Set".into()); "Set")]
+    #[test_case("DLP", ParseFormatError::Group("DLP".into()); "DLP")]
+    #[test_case("uint8x", ParseFormatError::Unrecognized("uint8x".into()); "Trailing garbage")]
+    #[test_case("", ParseFormatError::Unrecognized("".into()); "Empty")]
+    fn from_misb_str_errors(s: &str, expected: ParseFormatError) {
+        assert_eq!(SimpleDataTypeDiscriminants::from_misb_str(s), Err(expected));
+    }
 }
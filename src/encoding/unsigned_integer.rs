@@ -1,16 +1,25 @@
 use bitvec::field::BitField;
 use bitvec::order::Msb0;
 use bitvec::view::BitView;
+#[cfg(feature = "std")]
 use byteorder::BigEndian;
+#[cfg(feature = "std")]
 use byteorder::ReadBytesExt;
-use std::io::Seek;
 
+#[cfg(feature = "std")]
 use std::io::Read;
+#[cfg(feature = "std")]
+use std::io::Seek;
+
+#[cfg(feature = "std")]
+use crate::encoding::{integer_length, rewind_on_error};
+use alloc::vec::Vec;
 
-use crate::encoding::Error;
+use crate::encoding::{Error, PrecisionLoss, fits_f64_mantissa};
 
 /// UnsignedInteger types that can be read in using `read_unsigned_integer`.
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 pub enum UnsignedInteger {
     U8(u8),
     U16(u16),
@@ -19,6 +28,148 @@ pub enum UnsignedInteger {
     U128(u128),
 }
 
+impl From<UnsignedInteger> for u128 {
+    fn from(value: UnsignedInteger) -> Self {
+        match value {
+            UnsignedInteger::U8(value) => value.into(),
+            UnsignedInteger::U16(value) => value.into(),
+            UnsignedInteger::U32(value) => value.into(),
+            UnsignedInteger::U64(value) => value.into(),
+            UnsignedInteger::U128(value) => value,
+        }
+    }
+}
+
+impl UnsignedInteger {
+    /// Decodes an unsigned integer from every byte of `bytes`, following the
+    /// same rules as `read_unsigned_integer`.
+    ///
+    /// # Returns
+    ///
+    /// - `Ok(UnsignedInteger)` - The integer in the smallest datatype the
+    ///   number of bytes fits in.
+    /// - `Err(encoding::Error::InvalidValueLength)` - `bytes` is empty or
+    ///   longer than 16 bytes.
+    pub fn from_be_bytes(bytes: &[u8]) -> Result<Self, Error> {
+        let length = u8::try_from(bytes.len()).map_err(|_| Error::InvalidValueLength {
+            what: "unsigned integer",
+            length: bytes.len() as u64,
+        })?;
+        Ok(decode_unsigned_integer(bytes, length)?.0)
+    }
+
+    /// Big-endian bytes of the integer, as many as its datatype holds (e.g., a
+    /// `U32` is always 4 bytes).
+    pub fn to_be_bytes(&self) -> Vec<u8> {
+        match self {
+            Self::U8(value) => value.to_be_bytes().to_vec(),
+            Self::U16(value) => value.to_be_bytes().to_vec(),
+            Self::U32(value) => value.to_be_bytes().to_vec(),
+            Self::U64(value) => value.to_be_bytes().to_vec(),
+            Self::U128(value) => value.to_be_bytes().to_vec(),
+        }
+    }
+
+    /// Big-endian bytes of the integer without leading zero bytes. Zero is a
+    /// single byte.
+    pub fn to_minimal_be_bytes(&self) -> Vec<u8> {
+        let value = u128::from(*self);
+        let redundant = (value.leading_zeros() as usize / 8).min(15);
+        value.to_be_bytes()[redundant..].to_vec()
+    }
+
+    /// Converts the integer to an `f64`, failing if it would be rounded.
+    ///
+    /// # Returns
+    ///
+    /// - `Ok(f64)` - The integer converted without any loss.
+    /// - `Err(PrecisionLoss)` - The integer has more significant bits than an
+    ///   `f64` can hold.
+    pub fn to_f64_exact(&self) -> Result<f64, PrecisionLoss> {
+        let nearest = self.to_f64_lossy();
+        if fits_f64_mantissa(u128::from(*self)) {
+            Ok(nearest)
+        } else {
+            Err(PrecisionLoss { nearest })
+        }
+    }
+
+    /// Converts the integer to the nearest `f64`.
+    pub fn to_f64_lossy(&self) -> f64 {
+        u128::from(*self) as f64
+    }
+}
+
+/// Decode a variable length unsigned integer from the start of a slice.
+///
+/// Successfully decoded unsigned integers are always returned in the smallest
+/// datatype the number of bytes fits in (e.g., 3 bytes will always return a
+/// `u32`).
+///
+/// # Args
+///
+/// - `bytes` - Slice to decode from.
+/// - `length` - Number of bytes to decode and interpret as an unsigned
+///   integer.
+///
+/// # Returns
+///
+/// - `Ok((UnsignedInteger, usize))` - The decoded integer and the number of
+///   bytes it was encoded in, which is always `length`.
+/// - `Err(encoding::Error::InvalidValueLength)` - `length` is zero or cannot
+///   fit into an unsigned integer container.
+/// - `Err(encoding::Error::Truncated)` - The slice is shorter than `length`.
+pub fn decode_unsigned_integer(
+    bytes: &[u8],
+    length: u8,
+) -> Result<(UnsignedInteger, usize), Error> {
+    if !(1..=16).contains(&length) {
+        return Err(Error::InvalidValueLength {
+            what: "unsigned integer",
+            length: length.into(),
+        });
+    }
+    let bytes = bytes
+        .get(..length as usize)
+        .ok_or(Error::Truncated("unsigned integer"))?;
+    let value: u128 = bytes.view_bits::<Msb0>().load_be();
+
+    let value = match length {
+        1 => UnsignedInteger::U8(value as u8),
+        2 => UnsignedInteger::U16(value as u16),
+        3 | 4 => UnsignedInteger::U32(value as u32),
+        5..=8 => UnsignedInteger::U64(value as u64),
+        _ => UnsignedInteger::U128(value),
+    };
+
+    Ok((value, length as usize))
+}
+
+/// Decode a variable length little-endian unsigned integer from the start of a
+/// slice, such as one from a vendor payload carried inside a KLV item.
+///
+/// See `decode_unsigned_integer`, which every KLV standard uses.
+pub fn decode_unsigned_integer_le(
+    bytes: &[u8],
+    length: u8,
+) -> Result<(UnsignedInteger, usize), Error> {
+    let end = bytes.len().min(length.into()).min(16);
+    let mut reversed = [0; 16];
+    reversed[..end].copy_from_slice(&bytes[..end]);
+    reversed[..end].reverse();
+    decode_unsigned_integer(&reversed[..end], length)
+}
+
+/// Whether every byte of `bytes` is needed to hold the big-endian unsigned
+/// integer they encode, i.e., there is no leading zero byte other than a
+/// lone zero.
+///
+/// A value that is not minimal can be truncated by the standards that allow
+/// it without changing the integer it decodes to.
+pub fn is_minimal_unsigned_integer(bytes: &[u8]) -> bool {
+    !matches!(bytes, [0x00, _, ..])
+}
+
 /// Read in a variable length unsigned integer.
 ///
 /// Unsigned integers can be stored in variable lengths that adjust based on
@@ -42,30 +193,36 @@ pub enum UnsignedInteger {
 /// # Side Effects
 ///
 /// Moves the current position in the buffer to the byte after the last byte
-/// read
-pub fn read_unsigned_integer<T>(buf: &mut T, length: u8) -> Result<UnsignedInteger, Error>
+/// read. The position is left where it was when an error is returned.
+#[cfg(feature = "std")]
+pub fn read_unsigned_integer<T>(buf: &mut T, length: u64) -> Result<UnsignedInteger, Error>
 where
     T: Read + Seek,
 {
-    let value = match length {
-        1 => UnsignedInteger::U8(buf.read_u8()?),
-        2 => UnsignedInteger::U16(buf.read_u16::<byteorder::BigEndian>()?),
-        3 | 4 => UnsignedInteger::U32(
-            buf.read_uint::<byteorder::BigEndian>(length as usize)?
-                .try_into()
-                .unwrap_or_else(|_| panic!("{length} bytes doesn't fit in a `u32` somehow")),
-        ),
-        5..=8 => UnsignedInteger::U64(buf.read_uint::<byteorder::BigEndian>(length as usize)?),
-        9..=16 => {
-            let bytes = (0..length)
-                .map(|_| buf.read_u8().map_err(Error::from))
-                .collect::<Result<Vec<u8>, Error>>()?;
-            UnsignedInteger::U128(bytes.view_bits::<Msb0>().load_be())
-        }
-        _ => return Err(Error::DecodingError("unsigned_integer".to_string())),
-    };
+    rewind_on_error(buf, |buf| {
+        let length = integer_length(length, "unsigned integer")?;
+        let mut bytes = [0; 16];
+        buf.read_exact(&mut bytes[..length as usize])?;
 
-    Ok(value)
+        Ok(decode_unsigned_integer(&bytes, length)?.0)
+    })
+}
+
+/// Read in a variable length little-endian unsigned integer.
+///
+/// See `read_unsigned_integer`, which every KLV standard uses.
+#[cfg(feature = "std")]
+pub fn read_unsigned_integer_le<T>(buf: &mut T, length: u64) -> Result<UnsignedInteger, Error>
+where
+    T: Read + Seek,
+{
+    rewind_on_error(buf, |buf| {
+        let length = integer_length(length, "unsigned integer")?;
+        let mut bytes = [0; 16];
+        buf.read_exact(&mut bytes[..length as usize])?;
+
+        Ok(decode_unsigned_integer_le(&bytes, length)?.0)
+    })
 }
 
 /// Reads 1 byte and interprets it as na `u8`.
@@ -82,6 +239,7 @@ where
 ///
 /// Moves the current position in the buffer to the byte after the last byte
 /// read
+#[cfg(feature = "std")]
 pub fn read_u8<T>(buf: &mut T) -> Result<u8, Error>
 where
     T: Read + Seek,
@@ -103,6 +261,7 @@ where
 ///
 /// Moves the current position in the buffer to the byte after the last byte
 /// read
+#[cfg(feature = "std")]
 pub fn read_u16<T>(buf: &mut T) -> Result<u16, Error>
 where
     T: Read + Seek,
@@ -124,6 +283,7 @@ where
 ///
 /// Moves the current position in the buffer to the byte after the last byte
 /// read
+#[cfg(feature = "std")]
 pub fn read_u32<T>(buf: &mut T) -> Result<u32, Error>
 where
     T: Read + Seek,
@@ -145,6 +305,7 @@ where
 ///
 /// Moves the current position in the buffer to the byte after the last byte
 /// read
+#[cfg(feature = "std")]
 pub fn read_u64<T>(buf: &mut T) -> Result<u64, Error>
 where
     T: Read + Seek,
@@ -166,6 +327,7 @@ where
 ///
 /// Moves the current position in the buffer to the byte after the last byte
 /// read
+#[cfg(feature = "std")]
 pub fn read_u128<T>(buf: &mut T) -> Result<u128, Error>
 where
     T: Read + Seek,
@@ -173,13 +335,72 @@ where
     Ok(buf.read_u128::<BigEndian>()?)
 }
 
-#[cfg(test)]
+#[cfg(all(test, feature = "std"))]
 mod tests {
     use std::io;
 
     use super::*;
     use test_case::test_case;
 
+    const TWO_POW_53: u64 = 1 << 53;
+
+    #[test_case(1)]
+    #[test_case(2)]
+    #[test_case(3)]
+    #[test_case(4)]
+    #[test_case(5)]
+    #[test_case(8)]
+    #[test_case(9)]
+    #[test_case(12)]
+    #[test_case(16)]
+    fn little_endian_matches_from_le_bytes(length: u8) {
+        let bytes: Vec<u8> = (1..=length).map(|byte| byte.wrapping_mul(0x11)).collect();
+        let mut padded = [0; 16];
+        padded[..bytes.len()].copy_from_slice(&bytes);
+
+        let (value, read) = decode_unsigned_integer_le(&bytes, length).unwrap();
+        assert_eq!(u128::from(value), u128::from_le_bytes(padded));
+        assert_eq!(read, length as usize);
+        assert_eq!(
+            read_unsigned_integer_le(&mut io::Cursor::new(&bytes), length.into()).unwrap(),
+            value
+        );
+    }
+
+    #[test]
+    fn failed_read_leaves_position() {
+        let mut cursor = io::Cursor::new([0x01, 0x02, 0x03]);
+        cursor.set_position(1);
+        assert!(read_unsigned_integer(&mut cursor, 4).is_err());
+        assert_eq!(cursor.position(), 1);
+        assert_eq!(
+            read_unsigned_integer(&mut cursor, 2).unwrap(),
+            UnsignedInteger::U16(0x0203)
+        );
+    }
+
+    #[test_case(UnsignedInteger::U64(TWO_POW_53 - 1), true; "Below 2^53")]
+    #[test_case(UnsignedInteger::U64(TWO_POW_53), true; "2^53")]
+    #[test_case(UnsignedInteger::U64(TWO_POW_53 + 1), false; "Above 2^53")]
+    #[test_case(UnsignedInteger::U64(TWO_POW_53 + 2), true; "Even above 2^53")]
+    #[test_case(UnsignedInteger::U64(u64::MAX), false; "u64 Max")]
+    #[test_case(UnsignedInteger::U128(1 << 127), true; "Power of two")]
+    #[test_case(UnsignedInteger::U128(u128::MAX), false; "u128 Max")]
+    #[test_case(UnsignedInteger::U8(0), true; "Zero")]
+    fn to_f64_exact(integer: UnsignedInteger, exact: bool) {
+        let lossy = integer.to_f64_lossy();
+        match integer.to_f64_exact() {
+            Ok(value) => {
+                assert!(exact);
+                assert_eq!(value as u128, u128::from(integer));
+            }
+            Err(error) => {
+                assert!(!exact);
+                assert_eq!(error.nearest, lossy);
+            }
+        }
+    }
+
     #[test_case(&[0x00], UnsignedInteger::U8(0); "u8 Zero")]
     #[test_case(&[0x00], UnsignedInteger::U8(u8::MIN); "u8 Min")]
     #[test_case(&[0xFF], UnsignedInteger::U8(u8::MAX); "u8 Max")]
@@ -216,11 +437,101 @@ mod tests {
         assert_eq!(err.kind(), expected.kind())
     }
 
-    #[test_case( &[0x00, 0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08, 0x09, 0x0A, 0x0B, 0x0C, 0x0D, 0x0E, 0x0F], 17, Error::DecodingError("unsigned_integer".to_string()); "Length too long")]
-    #[test_case( &[0x00], 0, Error::DecodingError("unsigned_integer".to_string()); "Length to read is zero")]
-    fn read_integer_decoding_err(input: &[u8], length: u8, expected: Error) {
+    #[test_case( &[0x00, 0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08, 0x09, 0x0A, 0x0B, 0x0C, 0x0D, 0x0E, 0x0F], 17; "Length too long")]
+    #[test_case( &[0x00], 0; "Length to read is zero")]
+    fn read_integer_decoding_err(input: &[u8], length: u64) {
         let err = read_unsigned_integer(&mut std::io::Cursor::new(input), length)
             .expect_err("Testcase should fail here but does not");
-        assert_eq!(err.to_string(), expected.to_string())
+        assert!(matches!(
+            err,
+            Error::InvalidValueLength { what: "unsigned integer", length: read } if read == length
+        ))
+    }
+
+    #[test]
+    fn read_integer_rejects_wrapping_length() {
+        let mut cursor = std::io::Cursor::new([0x01; 260]);
+        assert!(matches!(
+            read_unsigned_integer(&mut cursor, 260),
+            Err(Error::InvalidValueLength { length: 260, .. })
+        ));
+        assert_eq!(cursor.position(), 0);
+    }
+}
+
+#[cfg(test)]
+mod slice_tests {
+    use super::*;
+    use test_case::test_case;
+
+    #[test_case(&[0x00], true; "Zero")]
+    #[test_case(&[0x01, 0x00], true; "Two-byte")]
+    #[test_case(&[0x00, 0x01], false; "Leading zero")]
+    #[test_case(&[0x00, 0x00], false; "Padded zero")]
+    fn is_minimal_unsigned_integer_ok(input: &[u8], expected: bool) {
+        assert_eq!(is_minimal_unsigned_integer(input), expected);
+    }
+
+    #[test_case(&[0xFF], UnsignedInteger::U8(u8::MAX); "u8 Max")]
+    #[test_case(&[0x01, 0x02], UnsignedInteger::U16(0x0102); "u16")]
+    #[test_case(&[0xFF, 0xFF, 0xFF], UnsignedInteger::U32(0xFF_FFFF); "u24 Max")]
+    #[test_case(&[0x01, 0x00, 0x00, 0x00, 0x00], UnsignedInteger::U64(1 << 32); "u40")]
+    #[test_case(&[0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00], UnsignedInteger::U128(1 << 64); "u72")]
+    #[test_case(&[0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF], UnsignedInteger::U128(u128::MAX); "u128 Max")]
+    fn decode_unsigned_integer_ok(input: &[u8], expected: UnsignedInteger) {
+        assert_eq!(
+            decode_unsigned_integer(input, input.len() as u8).unwrap(),
+            (expected, input.len())
+        );
+    }
+
+    #[test_case(&[0x00], UnsignedInteger::U8(0), &[0x00]; "Zero")]
+    #[test_case(&[0x00, 0xFF], UnsignedInteger::U16(0xFF), &[0xFF]; "Leading zero")]
+    #[test_case(&[0x01, 0x02, 0x03], UnsignedInteger::U32(0x01_0203), &[0x01, 0x02, 0x03]; "u24")]
+    #[test_case(&[0x00, 0x00, 0x00, 0x00, 0x01], UnsignedInteger::U64(1), &[0x01]; "u40 One")]
+    #[test_case(&[0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00], UnsignedInteger::U128(1 << 64), &[0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00]; "u72")]
+    #[test_case(&[0xFF; 16], UnsignedInteger::U128(u128::MAX), &[0xFF; 16]; "u128 Max")]
+    fn from_be_bytes_matches_reader(input: &[u8], expected: UnsignedInteger, minimal: &[u8]) {
+        let value = UnsignedInteger::from_be_bytes(input).unwrap();
+        assert_eq!(value, expected);
+        #[cfg(feature = "std")]
+        assert_eq!(
+            read_unsigned_integer(&mut std::io::Cursor::new(input), input.len() as u64).unwrap(),
+            value
+        );
+
+        assert_eq!(
+            UnsignedInteger::from_be_bytes(&value.to_be_bytes()).unwrap(),
+            value
+        );
+        assert_eq!(value.to_minimal_be_bytes(), minimal);
+        assert_eq!(
+            u128::from(UnsignedInteger::from_be_bytes(minimal).unwrap()),
+            u128::from(value)
+        );
+    }
+
+    #[test]
+    fn decode_unsigned_integer_errors() {
+        assert!(matches!(
+            decode_unsigned_integer(&[0x01], 2),
+            Err(Error::Truncated(_))
+        ));
+        assert!(matches!(
+            decode_unsigned_integer(&[0x01], 0),
+            Err(Error::InvalidValueLength { .. })
+        ));
+        assert!(matches!(
+            decode_unsigned_integer(&[0x01; 17], 17),
+            Err(Error::InvalidValueLength { .. })
+        ));
+        assert!(matches!(
+            UnsignedInteger::from_be_bytes(&[]),
+            Err(Error::InvalidValueLength { .. })
+        ));
+        assert!(matches!(
+            UnsignedInteger::from_be_bytes(&[0x01; 300]),
+            Err(Error::InvalidValueLength { length: 300, .. })
+        ));
     }
 }
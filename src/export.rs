@@ -0,0 +1,187 @@
+//! Exports of the items of many sets into tabular formats.
+
+use std::io::{self, Read, Seek, Write};
+
+use crate::{
+    format::{SoftwareValue, hex},
+    klv::ItemKey,
+    local_set::LocalSet,
+    tag::{Tag, TagReading},
+    time_index::PRECISION_TIME_STAMP_TAG,
+};
+
+/// Writes one CSV row per set with a `timestamp` column, holding the
+/// Precision Time Stamp in microseconds since the epoch, followed by one
+/// column per tag in `tags`.
+///
+/// Every value is written as hex and tags are named by their number. Tags
+/// that are not in a set are left empty. See `to_csv_as` to decode values.
+pub fn to_csv<'a, T, W>(
+    sets: impl IntoIterator<Item = &'a LocalSet<T>>,
+    tags: &[u128],
+    writer: W,
+) -> Result<(), io::Error>
+where
+    T: Read + Seek + 'a,
+    W: Write,
+{
+    write_csv(sets, tags, |tag| tag.to_string(), |_, raw| hex(raw), writer)
+}
+
+/// Writes the same rows as `to_csv` with tags named and values decoded with
+/// the tag table `G`.
+///
+/// Special values are written as their meaning. Values of tags `G` does not
+/// define, and values that fail to convert, are written as hex.
+pub fn to_csv_as<'a, G, T, W>(
+    sets: impl IntoIterator<Item = &'a LocalSet<T>>,
+    tags: &[u128],
+    writer: W,
+) -> Result<(), io::Error>
+where
+    G: Tag,
+    T: Read + Seek + 'a,
+    W: Write,
+{
+    write_csv(
+        sets,
+        tags,
+        |tag| match G::try_from(tag) {
+            Ok(tag) => tag.name().to_string(),
+            Err(_) => tag.to_string(),
+        },
+        |tag, raw| match G::try_from(tag).map(|tag| tag.decode(raw)) {
+            Ok(Ok(TagReading::Value(SoftwareValue::String(value)))) => value,
+            Ok(Ok(TagReading::Value(value))) => value.to_string(),
+            Ok(Ok(TagReading::Special(special))) => special.meaning.to_string(),
+            _ => hex(raw),
+        },
+        writer,
+    )
+}
+
+fn write_csv<'a, T, W>(
+    sets: impl IntoIterator<Item = &'a LocalSet<T>>,
+    tags: &[u128],
+    name: impl Fn(u128) -> String,
+    cell: impl Fn(u128, &[u8]) -> String,
+    mut writer: W,
+) -> Result<(), io::Error>
+where
+    T: Read + Seek + 'a,
+    W: Write,
+{
+    let header = std::iter::once("timestamp".to_string()).chain(tags.iter().map(|tag| name(*tag)));
+    write_row(&mut writer, header)?;
+
+    for set in sets {
+        let value = |tag| -> Result<Option<Vec<u8>>, io::Error> {
+            set.get(&ItemKey::Tag(tag))
+                .map(|klv| klv.read_value())
+                .transpose()
+        };
+        let timestamp = value(PRECISION_TIME_STAMP_TAG)?
+            .and_then(|raw| <[u8; 8]>::try_from(raw).ok())
+            .map(|raw| u64::from_be_bytes(raw).to_string())
+            .unwrap_or_default();
+        let mut row = vec![timestamp];
+        for tag in tags {
+            row.push(value(*tag)?.map(|raw| cell(*tag, &raw)).unwrap_or_default());
+        }
+        write_row(&mut writer, row)?;
+    }
+    Ok(())
+}
+
+/// Writes a line of fields, quoting those that need it as described in
+/// RFC 4180.
+fn write_row<W>(writer: &mut W, fields: impl IntoIterator<Item = String>) -> Result<(), io::Error>
+where
+    W: Write,
+{
+    let line = fields
+        .into_iter()
+        .map(|field| {
+            if field.contains([',', '"', '\n', '\r']) {
+                format!("\"{}\"", field.replace('"', "\"\""))
+            } else {
+                field
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(",");
+    writeln!(writer, "{line}")
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::RefCell, io::Cursor, rc::Rc};
+
+    use super::*;
+    use crate::{
+        keys, klv::OwnedKlv, local_set::OwnedLocalSet, misb::st0601::St0601Tag,
+        universal_set::UniversalSet,
+    };
+    use itertools::Itertools;
+
+    fn packet(timestamp: u64, mission: &[u8], latitude: Option<[u8; 4]>) -> Vec<u8> {
+        let items = [
+            Some(OwnedKlv::new(2, timestamp.to_be_bytes().to_vec())),
+            Some(OwnedKlv::new(3, mission.to_vec())),
+            latitude.map(|latitude| OwnedKlv::new(13, latitude.to_vec())),
+            Some(OwnedKlv::new(200, vec![0xAB, 0xCD])),
+        ];
+        OwnedLocalSet::new(items.into_iter().flatten().collect()).serialize(&keys::ST0601, true)
+    }
+
+    fn sets() -> Vec<UniversalSet<Cursor<Vec<u8>>>> {
+        let stream = [
+            packet(1_000_000, b"ALPHA", Some([0x00; 4])),
+            packet(2_000_000, b"BRAVO, \"TWO\"", Some([0x80, 0x00, 0x00, 0x00])),
+            packet(3_000_000, b"CHARLIE", None),
+        ]
+        .concat();
+        UniversalSet::read_all(keys::ST0601, Rc::new(RefCell::new(Cursor::new(stream)))).unwrap()
+    }
+
+    #[test]
+    fn decoded_csv() {
+        let sets = sets();
+        let mut csv = Vec::new();
+        to_csv_as::<St0601Tag, _, _>(
+            sets.iter().map(|set| set.data()),
+            &[3, 13, 15, 200],
+            &mut csv,
+        )
+        .unwrap();
+
+        assert_eq!(
+            String::from_utf8(csv).unwrap(),
+            [
+                "timestamp,Mission ID,Sensor Latitude,Sensor True Altitude,200",
+                "1000000,ALPHA,0,,abcd",
+                "2000000,\"BRAVO, \"\"TWO\"\"\",N/A (Off-Earth),,abcd",
+                "3000000,CHARLIE,,,abcd",
+                "",
+            ]
+            .join("\n")
+        );
+    }
+
+    #[test]
+    fn raw_csv() {
+        let sets = sets();
+        let mut csv = Vec::new();
+        to_csv(sets.iter().map(|set| set.data()), &[3, 13], &mut csv).unwrap();
+
+        assert_eq!(
+            String::from_utf8(csv).unwrap().lines().collect_vec(),
+            [
+                "timestamp,3,13",
+                "1000000,414c504841,00000000",
+                "2000000,425241564f2c202254574f22,80000000",
+                "3000000,434841524c4945,",
+            ]
+        );
+    }
+}
@@ -0,0 +1,184 @@
+use std::{
+    fs::File,
+    io::{BufReader, Read, Seek, SeekFrom},
+    path::{Path, PathBuf},
+    thread,
+    time::{Duration, Instant},
+};
+
+use crate::{
+    encoding,
+    universal_set::{
+        OwnedUniversalSet, UNIVERSAL_KEY_LENGTH, UniversalKey, UniversalSet, UniversalSetReader,
+    },
+};
+
+/// How long `FollowReader::wait_for_data` sleeps between polls by default.
+pub const DEFAULT_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Longest value a set may declare by default before `FollowReader` stops
+/// waiting for it to be written.
+pub const DEFAULT_MAX_SET_LENGTH: u64 = 16 * 1024 * 1024;
+
+/// Reads Universal Sets out of a file that is still being written to, like
+/// `tail -f`.
+///
+/// Reaching the end of the file does not end reading. Instead `poll` returns
+/// `Ok(None)` and the next call picks up where the last complete set ended,
+/// including any bytes appended in the meantime. A set whose declared length
+/// runs past the current end of the file is not returned until all of its
+/// bytes have been written, unless it declares a value longer than the
+/// maximum set length, which no set being written would.
+///
+/// Nothing blocks unless the caller asks it to with `wait_for_data`.
+#[derive(Debug)]
+pub struct FollowReader<R> {
+    key: UniversalKey,
+    reader: R,
+    /// Offset in the file where the next poll starts searching for the key.
+    position: u64,
+    poll_interval: Duration,
+    /// Longest value a set may declare and still be waited for.
+    max_set_length: u64,
+    /// File the reader was opened from, attached to any errors.
+    path: Option<PathBuf>,
+}
+
+impl<R> FollowReader<R>
+where
+    R: Read + Seek,
+{
+    pub fn new(key: UniversalKey, reader: R) -> Self {
+        Self {
+            key,
+            reader,
+            position: 0,
+            poll_interval: DEFAULT_POLL_INTERVAL,
+            max_set_length: DEFAULT_MAX_SET_LENGTH,
+            path: None,
+        }
+    }
+
+    /// Sets how long `wait_for_data` sleeps between polls.
+    pub fn with_poll_interval(mut self, poll_interval: Duration) -> Self {
+        self.poll_interval = poll_interval;
+        self
+    }
+
+    /// Sets the longest value a set may declare and still be waited for.
+    pub fn with_max_set_length(mut self, max_set_length: u64) -> Self {
+        self.max_set_length = max_set_length;
+        self
+    }
+
+    /// Offset in the file where the next poll will start searching for the
+    /// Universal Key.
+    pub fn position(&self) -> u64 {
+        self.position
+    }
+
+    /// Tries to read the next Universal Set without waiting for the file to
+    /// grow.
+    ///
+    /// # Returns
+    ///
+    /// - `Ok(Some(OwnedUniversalSet))` - The next complete set in the file.
+    /// - `Ok(None)` - The file does not contain another complete set yet.
+    /// - `Err(encoding::Error::ValueOutOfRange)` - The next set declares a
+    ///   value longer than the maximum set length and isn't all there, so it
+    ///   is taken to be corrupt. The next poll searches again from the byte
+    ///   after its key.
+    /// - `Err(encoding::Error)` - The next set could not be read. The set is
+    ///   skipped so the next poll continues after it.
+    ///
+    /// # Side Effects
+    ///
+    /// Moves the current position in the reader to wherever reading stopped.
+    pub fn poll(&mut self) -> Result<Option<OwnedUniversalSet>, encoding::Error> {
+        self.read_next().map_err(|e| match &self.path {
+            Some(path) => e.in_file(path),
+            None => e,
+        })
+    }
+
+    /// Polls for the next Universal Set until one is available or `timeout`
+    /// has passed, sleeping for the poll interval between attempts.
+    ///
+    /// # Returns
+    ///
+    /// The same as `poll`, with `Ok(None)` meaning no complete set was
+    /// written before the timeout.
+    pub fn wait_for_data(
+        &mut self,
+        timeout: Duration,
+    ) -> Result<Option<OwnedUniversalSet>, encoding::Error> {
+        let deadline = Instant::now() + timeout;
+        loop {
+            if let Some(set) = self.poll()? {
+                return Ok(Some(set));
+            }
+
+            let now = Instant::now();
+            if now >= deadline {
+                return Ok(None);
+            }
+            thread::sleep(self.poll_interval.min(deadline - now));
+        }
+    }
+
+    fn read_next(&mut self) -> Result<Option<OwnedUniversalSet>, encoding::Error> {
+        self.reader.seek(SeekFrom::Start(self.position))?;
+        let mut sets = UniversalSetReader::starting_at(self.key, &mut self.reader, self.position);
+
+        match sets.next() {
+            Some(Ok(set)) => {
+                self.position = sets.position();
+                Ok(Some(set))
+            }
+            Some(Err(_)) if sets.ended_mid_set() => {
+                let key_offset = sets.key_offset();
+                match UniversalSet::read_extent(&mut self.reader, key_offset) {
+                    Ok(extent) if extent.value_length() > self.max_set_length => {
+                        self.position = key_offset + UNIVERSAL_KEY_LENGTH as u64;
+                        Err(encoding::Error::ValueOutOfRange {
+                            what: format!(
+                                "a followed set of at most {} bytes",
+                                self.max_set_length
+                            ),
+                            value: extent.value_length().to_string(),
+                        })
+                    }
+                    // The set has not been completely written yet so it is
+                    // read again from the start of its key on the next poll.
+                    _ => Ok(None),
+                }
+            }
+            Some(Err(e)) => {
+                self.position = sets.position();
+                Err(e)
+            }
+            None => {
+                // The last bytes of the file could be the start of a key that
+                // is still being written.
+                let searched_to = sets.position();
+                self.position = searched_to
+                    .saturating_sub(UNIVERSAL_KEY_LENGTH as u64 - 1)
+                    .max(self.position);
+                Ok(None)
+            }
+        }
+    }
+}
+
+impl FollowReader<BufReader<File>> {
+    /// Opens the file at `path` to follow it as it grows.
+    ///
+    /// Errors opening or reading the file carry the path.
+    pub fn from_path(key: UniversalKey, path: impl AsRef<Path>) -> Result<Self, encoding::Error> {
+        let path = path.as_ref();
+        let file = File::open(path).map_err(|e| encoding::Error::from(e).in_file(path))?;
+        let mut reader = Self::new(key, BufReader::new(file));
+        reader.path = Some(path.to_path_buf());
+        Ok(reader)
+    }
+}
@@ -0,0 +1,726 @@
+//! Formats values are encoded in within a KLV item and the software types they
+//! are converted to.
+
+use std::fmt;
+
+use crate::encoding::{
+    self, ParseFormatError, SimpleDataTypeDiscriminants,
+    ber::{decode_ber, encode_ber},
+    ber_oid::{decode_ber_oid, encode_ber_oid},
+    float::decode_float,
+    integer::decode_signed_integer,
+    linear::LinearMap,
+    unsigned_integer::{UnsignedInteger, decode_unsigned_integer},
+};
+
+/// How a value is encoded within a KLV item, following _Table 40_ of the
+/// _MISP Motion Imagery Handbook_.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum KlvFormat {
+    /// Big-endian unsigned integer as long as the value.
+    Uint,
+    /// Big-endian two's complement integer as long as the value.
+    Int,
+    Ber,
+    BerOid,
+    /// Unsigned integer mapped onto a floating-point range as specified by
+    /// MISB ST 1201.
+    Imapb,
+    /// IEEE 754 floating-point number of 4 or 8 bytes.
+    Float,
+    /// A single byte where zero is false and one is true.
+    Boolean,
+    Iso7,
+    Utf8,
+    /// Bytes whose meaning is described by the standard.
+    Binary,
+    /// 16 byte UUID as specified by RFC 9562.
+    #[cfg(feature = "uuid")]
+    Uuid,
+}
+
+/// Type a value is converted to for use in software.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SoftwareFormat {
+    Bool,
+    Uint64,
+    Int64,
+    Float64,
+    String,
+    Bytes,
+    #[cfg(feature = "uuid")]
+    Uuid,
+}
+
+/// A value converted to one of the `SoftwareFormat`s.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum SoftwareValue {
+    Bool(bool),
+    Uint64(u64),
+    Int64(i64),
+    Float64(f64),
+    String(String),
+    Bytes(Vec<u8>),
+    /// Written out as the hyphenated string, e.g.
+    /// `67e55044-10b1-426f-9247-bb680e5fe0c8`, by serializers for
+    /// human-readable formats.
+    #[cfg(feature = "uuid")]
+    Uuid(uuid::Uuid),
+}
+
+impl fmt::Display for SoftwareValue {
+    /// Formats strings quoted, bytes as lowercase hex, and UUIDs hyphenated.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Bool(value) => write!(f, "{value}"),
+            Self::Uint64(value) => write!(f, "{value}"),
+            Self::Int64(value) => write!(f, "{value}"),
+            Self::Float64(value) => write!(f, "{value}"),
+            Self::String(value) => write!(f, "{value:?}"),
+            Self::Bytes(value) => write!(f, "{}", hex(value)),
+            #[cfg(feature = "uuid")]
+            Self::Uuid(value) => write!(f, "{value}"),
+        }
+    }
+}
+
+/// Formats `bytes` as lowercase hex without separators.
+pub(crate) fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+impl SoftwareValue {
+    /// Format this value is in.
+    pub fn format(&self) -> SoftwareFormat {
+        match self {
+            Self::Bool(_) => SoftwareFormat::Bool,
+            Self::Uint64(_) => SoftwareFormat::Uint64,
+            Self::Int64(_) => SoftwareFormat::Int64,
+            Self::Float64(_) => SoftwareFormat::Float64,
+            Self::String(_) => SoftwareFormat::String,
+            Self::Bytes(_) => SoftwareFormat::Bytes,
+            #[cfg(feature = "uuid")]
+            Self::Uuid(_) => SoftwareFormat::Uuid,
+        }
+    }
+}
+
+/// Parameters a conversion needs beyond the formats themselves.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct FormatParams {
+    /// Smallest and largest software values an integer or IMAPB value maps
+    /// onto when converted to `SoftwareFormat::Float64`.
+    ///
+    /// Unsigned integers map their full range onto it while signed integers
+    /// map their range excluding the most negative value, which MISB
+    /// standards reserve. Integers without a range are converted as is.
+    pub range: Option<(f64, f64)>,
+}
+
+impl FormatParams {
+    /// Parameters mapping values onto `min..=max`.
+    pub const fn range(min: f64, max: f64) -> Self {
+        Self {
+            range: Some((min, max)),
+        }
+    }
+}
+
+/// How an item's value is encoded and what it is converted to.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ValueFormat {
+    pub klv: KlvFormat,
+    pub software: SoftwareFormat,
+    pub params: FormatParams,
+}
+
+impl ValueFormat {
+    /// Converts `klv` from this format.
+    ///
+    /// See `convert`.
+    pub fn convert(&self, klv: &[u8]) -> Result<SoftwareValue, encoding::Error> {
+        convert(klv, &self.klv, &self.software, &self.params)
+    }
+
+    /// Converts `value` to this format.
+    ///
+    /// See `convert_to_klv`.
+    pub fn convert_to_klv(
+        &self,
+        value: &SoftwareValue,
+        length: Option<usize>,
+    ) -> Result<Vec<u8>, encoding::Error> {
+        convert_to_klv(value, &self.klv, length, &self.params)
+    }
+}
+
+impl KlvFormat {
+    /// Parses a format as written in the _Format_ column of MISB tag tables.
+    ///
+    /// Enumerations are read as unsigned integers. IMAPA and UTF-16 values
+    /// have no `KlvFormat` and are `ParseFormatError::Unsupported`.
+    ///
+    /// See `SimpleDataTypeDiscriminants::from_misb_str`.
+    pub fn from_misb_str(s: &str) -> Result<Self, ParseFormatError> {
+        use SimpleDataTypeDiscriminants as Simple;
+
+        Ok(match SimpleDataTypeDiscriminants::from_misb_str(s)? {
+            Simple::UnsignedInteger | Simple::Enumeration => Self::Uint,
+            Simple::SignedInteger => Self::Int,
+            Simple::Ber => Self::Ber,
+            Simple::BerOid => Self::BerOid,
+            Simple::Binary => Self::Binary,
+            Simple::Boolean => Self::Boolean,
+            Simple::Iso7 => Self::Iso7,
+            Simple::Utf8 => Self::Utf8,
+            Simple::FloatingPoint => Self::Float,
+            #[cfg(feature = "uuid")]
+            Simple::Uuid => Self::Uuid,
+            Simple::IMAP if !s.to_ascii_lowercase().contains("imapa") => Self::Imapb,
+            Simple::IMAP | Simple::Utf16 => return Err(ParseFormatError::Unsupported(s.into())),
+        })
+    }
+
+    /// Software format values in this format convert to without any mapping.
+    ///
+    /// IMAPB values are the unsigned integers they are encoded as.
+    pub fn unmapped(&self) -> (Self, SoftwareFormat) {
+        match self {
+            Self::Uint | Self::Imapb => (Self::Uint, SoftwareFormat::Uint64),
+            Self::Int => (Self::Int, SoftwareFormat::Int64),
+            Self::Ber | Self::BerOid => (*self, SoftwareFormat::Uint64),
+            Self::Float => (*self, SoftwareFormat::Float64),
+            Self::Boolean => (*self, SoftwareFormat::Bool),
+            Self::Iso7 | Self::Utf8 => (*self, SoftwareFormat::String),
+            Self::Binary => (*self, SoftwareFormat::Bytes),
+            #[cfg(feature = "uuid")]
+            Self::Uuid => (*self, SoftwareFormat::Uuid),
+        }
+    }
+
+    /// Whether values in this format can be converted to `software`.
+    pub fn is_compatible_with(&self, software: &SoftwareFormat) -> bool {
+        use SoftwareFormat as S;
+
+        match (self, software) {
+            (_, S::Bytes)
+            | (Self::Uint, S::Uint64 | S::Int64 | S::Float64)
+            | (Self::Int, S::Int64 | S::Float64)
+            | (Self::Ber | Self::BerOid, S::Uint64)
+            | (Self::Imapb | Self::Float, S::Float64)
+            | (Self::Boolean, S::Bool)
+            | (Self::Iso7 | Self::Utf8, S::String) => true,
+            #[cfg(feature = "uuid")]
+            (Self::Uuid, S::Uuid) => true,
+            _ => false,
+        }
+    }
+}
+
+/// Converts the value `klv`, encoded in the format `format`, to `software`.
+///
+/// # Returns
+///
+/// - `Ok(SoftwareValue)` - The converted value, always in the `software`
+///   format.
+/// - `Err(encoding::Error::IncompatibleFormats)` - The formats are not
+///   compatible.
+/// - `Err(encoding::Error::ValueOutOfRange)` - The value does not fit in the
+///   software type, or is a boolean other than 0 or 1.
+/// - `Err(encoding::Error::InvalidCharacter)` - A string is not valid in its
+///   format.
+/// - `Err(encoding::Error::MissingParameter)` - An IMAPB value has no range.
+/// - `Err(encoding::Error::InvalidValueLength)` - The value is a length its
+///   format can't be decoded from.
+/// - `Err(encoding::Error::PrecisionLoss)` - An integer converted to
+///   `SoftwareFormat::Float64` would be rounded.
+/// - `Err(encoding::Error::Truncated)` - The value ends part way through its
+///   encoding.
+pub fn convert(
+    klv: &[u8],
+    format: &KlvFormat,
+    software: &SoftwareFormat,
+    params: &FormatParams,
+) -> Result<SoftwareValue, encoding::Error> {
+    if !format.is_compatible_with(software) {
+        return Err(encoding::Error::IncompatibleFormats {
+            from: format!("{format:?}"),
+            to: format!("{software:?}"),
+        });
+    }
+    if *software == SoftwareFormat::Bytes {
+        return Ok(SoftwareValue::Bytes(klv.to_vec()));
+    }
+
+    let out_of_range = || encoding::Error::ValueOutOfRange {
+        what: format!("{software:?}"),
+        value: format!("{format:?} value {klv:02X?}"),
+    };
+    Ok(match format {
+        KlvFormat::Uint => {
+            let (integer, _) = decode_unsigned_integer(klv, length(klv)?)?;
+            let value = u128::from(integer);
+            match software {
+                SoftwareFormat::Uint64 => {
+                    SoftwareValue::Uint64(value.try_into().map_err(|_| out_of_range())?)
+                }
+                SoftwareFormat::Int64 => {
+                    SoftwareValue::Int64(value.try_into().map_err(|_| out_of_range())?)
+                }
+                _ => {
+                    let exact = integer.to_f64_exact()?;
+                    SoftwareValue::Float64(match params.range {
+                        Some((min, max)) => LinearMap::unsigned(klv.len(), min, max)
+                            .decode(value.try_into().map_err(|_| out_of_range())?)
+                            .ok_or_else(out_of_range)?,
+                        None => exact,
+                    })
+                }
+            }
+        }
+        KlvFormat::Int => {
+            let (integer, _) = decode_signed_integer(klv, length(klv)?)?;
+            match software {
+                SoftwareFormat::Int64 => SoftwareValue::Int64(
+                    i128::from(integer).try_into().map_err(|_| out_of_range())?,
+                ),
+                _ => {
+                    let exact = integer.to_f64_exact()?;
+                    SoftwareValue::Float64(match params.range {
+                        Some((min, max)) => LinearMap::signed(klv.len(), min, max)
+                            .decode(integer.into())
+                            .ok_or_else(out_of_range)?,
+                        None => exact,
+                    })
+                }
+            }
+        }
+        KlvFormat::Ber | KlvFormat::BerOid => {
+            let (value, _) = match format {
+                KlvFormat::Ber => decode_ber(klv)?,
+                _ => decode_ber_oid(klv)?,
+            };
+            SoftwareValue::Uint64(value.try_into().map_err(|_| out_of_range())?)
+        }
+        KlvFormat::Imapb => {
+            let (min, max) = params
+                .range
+                .ok_or_else(|| encoding::Error::MissingParameter {
+                    what: "IMAPB".into(),
+                    parameter: "range",
+                })?;
+            let (value, _) = decode_unsigned_integer(klv, length(klv)?)?;
+            SoftwareValue::Float64(imapb_to_f64(value.to_f64_exact()?, klv.len(), min, max))
+        }
+        KlvFormat::Float => SoftwareValue::Float64(decode_float(klv, length(klv)?)?),
+        KlvFormat::Boolean => {
+            SoftwareValue::Bool(match decode_unsigned_integer(klv, length(klv)?)?.0 {
+                UnsignedInteger::U8(0) => false,
+                UnsignedInteger::U8(1) => true,
+                _ => {
+                    return Err(encoding::Error::ValueOutOfRange {
+                        what: "a boolean".into(),
+                        value: format!("{klv:02X?}"),
+                    });
+                }
+            })
+        }
+        KlvFormat::Iso7 if !klv.is_ascii() => {
+            return Err(encoding::Error::InvalidCharacter {
+                what: "ISO7 string",
+                offset: klv.iter().take_while(|byte| byte.is_ascii()).count(),
+            });
+        }
+        KlvFormat::Iso7 | KlvFormat::Utf8 => {
+            SoftwareValue::String(String::from_utf8(klv.to_vec()).map_err(|error| {
+                encoding::Error::InvalidCharacter {
+                    what: "UTF8 string",
+                    offset: error.utf8_error().valid_up_to(),
+                }
+            })?)
+        }
+        KlvFormat::Binary => unreachable!("Binary values are only compatible with bytes"),
+        #[cfg(feature = "uuid")]
+        KlvFormat::Uuid => SoftwareValue::Uuid(encoding::decode_uuid(klv)?),
+    })
+}
+
+/// Converts `value` to the format `format`, reversing `convert`.
+///
+/// # Args
+///
+/// - `length` - Number of bytes to encode integer, IMAPB, and floating-point
+///   values in. Integers without a length use as few bytes as they fit in.
+///
+/// # Returns
+///
+/// - `Ok(Vec<u8>)` - The encoded value.
+/// - `Err(encoding::Error::IncompatibleFormats)` - The formats are not
+///   compatible.
+/// - `Err(encoding::Error::ValueOutOfRange)` - The value is outside of its
+///   range or does not fit in `length` bytes.
+/// - `Err(encoding::Error::MissingParameter)` - A mapped, IMAPB, or
+///   floating-point value has no length, or an IMAPB value has no range.
+/// - `Err(encoding::Error::InvalidValueLength)` - A floating-point value is
+///   to be encoded in a length other than 4 or 8 bytes, or an integer or
+///   IMAPB value in a length other than 1 to 16 bytes.
+/// - `Err(encoding::Error::InvalidCharacter)` - An ISO7 string holds a
+///   character outside of ASCII.
+pub fn convert_to_klv(
+    value: &SoftwareValue,
+    format: &KlvFormat,
+    length: Option<usize>,
+    params: &FormatParams,
+) -> Result<Vec<u8>, encoding::Error> {
+    let software = value.format();
+    if !format.is_compatible_with(&software) {
+        return Err(encoding::Error::IncompatibleFormats {
+            from: format!("{software:?}"),
+            to: format!("{format:?}"),
+        });
+    }
+    let out_of_range = || encoding::Error::ValueOutOfRange {
+        what: format!("{format:?}"),
+        value: format!("{value:?}"),
+    };
+    let fixed_length = || {
+        length.ok_or_else(|| encoding::Error::MissingParameter {
+            what: format!("{format:?}"),
+            parameter: "length",
+        })
+    };
+
+    Ok(match (format, value) {
+        (_, SoftwareValue::Bytes(bytes)) => bytes.clone(),
+        (KlvFormat::Uint | KlvFormat::Int | KlvFormat::Imapb, _)
+            if length.is_some_and(|length| !(1..=16).contains(&length)) =>
+        {
+            return Err(encoding::Error::InvalidValueLength {
+                what: "integer",
+                length: length.unwrap_or_default() as u64,
+            });
+        }
+        (KlvFormat::Uint, SoftwareValue::Uint64(value)) => {
+            uint_to_klv((*value).into(), length).ok_or_else(out_of_range)?
+        }
+        (KlvFormat::Uint, SoftwareValue::Int64(value)) => {
+            let value = u128::try_from(*value).map_err(|_| out_of_range())?;
+            uint_to_klv(value, length).ok_or_else(out_of_range)?
+        }
+        (KlvFormat::Uint, SoftwareValue::Float64(value)) => match params.range {
+            Some((min, max)) => {
+                let length = fixed_length()?;
+                let raw = LinearMap::unsigned(length, min, max)
+                    .encode(*value)
+                    .map_err(|_| out_of_range())?;
+                uint_to_klv(raw as u128, Some(length)).ok_or_else(out_of_range)?
+            }
+            None if value.fract() == 0.0 && *value >= 0.0 => {
+                uint_to_klv(*value as u128, length).ok_or_else(out_of_range)?
+            }
+            None => return Err(out_of_range()),
+        },
+        (KlvFormat::Int, SoftwareValue::Int64(value)) => {
+            int_to_klv((*value).into(), length).ok_or_else(out_of_range)?
+        }
+        (KlvFormat::Int, SoftwareValue::Float64(value)) => match params.range {
+            Some((min, max)) => {
+                let length = fixed_length()?;
+                let raw = LinearMap::signed(length, min, max)
+                    .encode(*value)
+                    .map_err(|_| out_of_range())?;
+                int_to_klv(raw, Some(length)).ok_or_else(out_of_range)?
+            }
+            None if value.fract() == 0.0 => {
+                int_to_klv(*value as i128, length).ok_or_else(out_of_range)?
+            }
+            None => return Err(out_of_range()),
+        },
+        (KlvFormat::Ber, SoftwareValue::Uint64(value)) => encode_ber((*value).into()),
+        (KlvFormat::BerOid, SoftwareValue::Uint64(value)) => encode_ber_oid((*value).into()),
+        (KlvFormat::Imapb, SoftwareValue::Float64(value)) => {
+            let (min, max) = params
+                .range
+                .ok_or_else(|| encoding::Error::MissingParameter {
+                    what: "IMAPB".into(),
+                    parameter: "range",
+                })?;
+            if !(min..=max).contains(value) {
+                return Err(out_of_range());
+            }
+            let length = fixed_length()?;
+            uint_to_klv(f64_to_imapb(*value, length, min, max), Some(length))
+                .ok_or_else(out_of_range)?
+        }
+        (KlvFormat::Float, SoftwareValue::Float64(value)) => match fixed_length()? {
+            4 => (*value as f32).to_be_bytes().to_vec(),
+            8 => value.to_be_bytes().to_vec(),
+            length => {
+                return Err(encoding::Error::InvalidValueLength {
+                    what: "floating-point value",
+                    length: length as u64,
+                });
+            }
+        },
+        (KlvFormat::Boolean, SoftwareValue::Bool(value)) => vec![u8::from(*value)],
+        #[cfg(feature = "uuid")]
+        (KlvFormat::Uuid, SoftwareValue::Uuid(value)) => value.as_bytes().to_vec(),
+        (KlvFormat::Iso7, SoftwareValue::String(value)) if !value.is_ascii() => {
+            return Err(encoding::Error::InvalidCharacter {
+                what: "ISO7 string",
+                offset: value.bytes().take_while(u8::is_ascii).count(),
+            });
+        }
+        (KlvFormat::Iso7 | KlvFormat::Utf8, SoftwareValue::String(value)) => {
+            value.as_bytes().to_vec()
+        }
+        _ => unreachable!("Compatible formats are all handled above"),
+    })
+}
+
+/// Big-endian bytes of `value`, `length` bytes long or as short as it fits in.
+fn uint_to_klv(value: u128, length: Option<usize>) -> Option<Vec<u8>> {
+    let minimal = (16 - value.leading_zeros() as usize / 8).max(1);
+    let length = length.unwrap_or(minimal);
+    (minimal <= length && length <= 16).then(|| value.to_be_bytes()[16 - length..].to_vec())
+}
+
+/// Two's complement bytes of `value`, `length` bytes long or as short as it
+/// fits in.
+fn int_to_klv(value: i128, length: Option<usize>) -> Option<Vec<u8>> {
+    let redundant = if value < 0 {
+        value.leading_ones()
+    } else {
+        value.leading_zeros()
+    } as usize;
+    // One sign bit must remain.
+    let minimal = (16 - (redundant - 1) / 8).max(1);
+    let length = length.unwrap_or(minimal);
+    (minimal <= length && length <= 16).then(|| value.to_be_bytes()[16 - length..].to_vec())
+}
+
+/// Length of `klv` as the length of an integer to decode.
+fn length(klv: &[u8]) -> Result<u8, encoding::Error> {
+    klv.len()
+        .try_into()
+        .map_err(|_| encoding::Error::InvalidValueLength {
+            what: "integer",
+            length: klv.len() as u64,
+        })
+}
+
+/// Maps a floating-point value in `min..=max` onto an unsigned integer
+/// `length` bytes long as specified by MISB ST 1201.
+fn f64_to_imapb(value: f64, length: usize, min: f64, max: f64) -> u128 {
+    let b_pow = (max - min).log2().ceil();
+    let d_pow = (8 * length - 1) as f64;
+    let s_f = 2f64.powf(d_pow - b_pow);
+    let z_offset = if min < 0.0 && max > 0.0 {
+        s_f * min - (s_f * min).floor()
+    } else {
+        0.0
+    };
+    (s_f * (value - min) + z_offset).floor() as u128
+}
+
+/// Reverses the MISB ST 1201 mapping of a floating-point value in
+/// `min..=max` onto an unsigned integer `length` bytes long.
+fn imapb_to_f64(value: f64, length: usize, min: f64, max: f64) -> f64 {
+    let b_pow = (max - min).log2().ceil();
+    let d_pow = (8 * length - 1) as f64;
+    let s_f = 2f64.powf(d_pow - b_pow);
+    let s_r = 2f64.powf(b_pow - d_pow);
+    let z_offset = if min < 0.0 && max > 0.0 {
+        s_f * min - (s_f * min).floor()
+    } else {
+        0.0
+    };
+    s_r * (value - z_offset) + min
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_case::test_case;
+
+    #[test_case("uint16", Ok(KlvFormat::Uint))]
+    #[test_case("Enumeration", Ok(KlvFormat::Uint))]
+    #[test_case("int32", Ok(KlvFormat::Int))]
+    #[test_case("IMAPB", Ok(KlvFormat::Imapb))]
+    #[test_case("float64", Ok(KlvFormat::Float))]
+    #[test_case("IMAPA", Err(ParseFormatError::Unsupported("IMAPA".into())))]
+    #[test_case("UTF16", Err(ParseFormatError::Unsupported("UTF16".into())))]
+    #[test_case("DLP", Err(ParseFormatError::Group("DLP".into())))]
+    fn klv_format_from_misb_str(s: &str, expected: Result<KlvFormat, ParseFormatError>) {
+        assert_eq!(KlvFormat::from_misb_str(s), expected);
+    }
+
+    #[test_case(KlvFormat::Uint, SoftwareFormat::Uint64, true)]
+    #[test_case(KlvFormat::Uint, SoftwareFormat::Int64, true)]
+    #[test_case(KlvFormat::Uint, SoftwareFormat::Float64, true)]
+    #[test_case(KlvFormat::Uint, SoftwareFormat::String, false)]
+    #[test_case(KlvFormat::Int, SoftwareFormat::Int64, true)]
+    #[test_case(KlvFormat::Int, SoftwareFormat::Uint64, false)]
+    #[test_case(KlvFormat::Int, SoftwareFormat::Float64, true)]
+    #[test_case(KlvFormat::Ber, SoftwareFormat::Uint64, true)]
+    #[test_case(KlvFormat::BerOid, SoftwareFormat::Float64, false)]
+    #[test_case(KlvFormat::Imapb, SoftwareFormat::Float64, true)]
+    #[test_case(KlvFormat::Imapb, SoftwareFormat::Uint64, false)]
+    #[test_case(KlvFormat::Float, SoftwareFormat::Float64, true)]
+    #[test_case(KlvFormat::Boolean, SoftwareFormat::Bool, true)]
+    #[test_case(KlvFormat::Boolean, SoftwareFormat::Uint64, false)]
+    #[test_case(KlvFormat::Utf8, SoftwareFormat::String, true)]
+    #[test_case(KlvFormat::Utf8, SoftwareFormat::Uint64, false)]
+    #[test_case(KlvFormat::Iso7, SoftwareFormat::String, true)]
+    #[test_case(KlvFormat::Binary, SoftwareFormat::Bytes, true)]
+    #[test_case(KlvFormat::Binary, SoftwareFormat::String, false)]
+    #[test_case(KlvFormat::Utf8, SoftwareFormat::Bytes, true)]
+    fn compatibility(klv: KlvFormat, software: SoftwareFormat, compatible: bool) {
+        assert_eq!(klv.is_compatible_with(&software), compatible);
+        assert_eq!(
+            matches!(
+                convert(&[0x01], &klv, &software, &FormatParams::range(0.0, 1.0)),
+                Err(encoding::Error::IncompatibleFormats { .. })
+            ),
+            !compatible
+        );
+    }
+
+    #[test_case(&[0xFF, 0xFF], KlvFormat::Uint, SoftwareFormat::Uint64, FormatParams::default(), SoftwareValue::Uint64(0xFFFF); "Uint")]
+    #[test_case(&[0xFF, 0xFF], KlvFormat::Uint, SoftwareFormat::Float64, FormatParams::range(0.0, 360.0), SoftwareValue::Float64(360.0); "Mapped uint")]
+    #[test_case(&[0xFF, 0xFE], KlvFormat::Int, SoftwareFormat::Int64, FormatParams::default(), SoftwareValue::Int64(-2); "Int")]
+    #[test_case(&[0x80, 0x01], KlvFormat::Int, SoftwareFormat::Float64, FormatParams::range(-20.0, 20.0), SoftwareValue::Float64(-20.0); "Mapped int")]
+    #[test_case(&[0x81, 0x00], KlvFormat::BerOid, SoftwareFormat::Uint64, FormatParams::default(), SoftwareValue::Uint64(128); "BER-OID")]
+    #[test_case(&[0x01, 0x00], KlvFormat::Imapb, SoftwareFormat::Float64, FormatParams::range(0.0, 256.0), SoftwareValue::Float64(2.0); "IMAPB")]
+    #[test_case(&[0x3F, 0xC0, 0x00, 0x00], KlvFormat::Float, SoftwareFormat::Float64, FormatParams::default(), SoftwareValue::Float64(1.5); "Float")]
+    #[test_case(&[0x01], KlvFormat::Boolean, SoftwareFormat::Bool, FormatParams::default(), SoftwareValue::Bool(true); "Boolean")]
+    #[test_case(b"MISB", KlvFormat::Utf8, SoftwareFormat::String, FormatParams::default(), SoftwareValue::String("MISB".into()); "UTF8")]
+    #[test_case(&[0xAA], KlvFormat::Binary, SoftwareFormat::Bytes, FormatParams::default(), SoftwareValue::Bytes(vec![0xAA]); "Binary")]
+    fn conversion(
+        klv: &[u8],
+        format: KlvFormat,
+        software: SoftwareFormat,
+        params: FormatParams,
+        expected: SoftwareValue,
+    ) {
+        assert_eq!(convert(klv, &format, &software, &params).unwrap(), expected);
+    }
+
+    #[test_case(KlvFormat::Uint, &[0x00, 0x20, 0x00, 0x00, 0x00, 0x00, 0x00, 0x01]; "Uint above 2^53")]
+    #[test_case(KlvFormat::Int, &[0xFF, 0xDF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF]; "Int below minus 2^53")]
+    #[test_case(KlvFormat::Imapb, &[0x00, 0x20, 0x00, 0x00, 0x00, 0x00, 0x00, 0x01]; "IMAPB above 2^53")]
+    fn conversion_loses_precision(format: KlvFormat, klv: &[u8]) {
+        let params = FormatParams::range(0.0, 1.0);
+        assert!(matches!(
+            convert(klv, &format, &SoftwareFormat::Float64, &params),
+            Err(encoding::Error::PrecisionLoss(_))
+        ));
+        let exact = [&klv[..7], &[klv[7] ^ 0x01]].concat();
+        assert!(convert(&exact, &format, &SoftwareFormat::Float64, &params).is_ok());
+    }
+
+    #[test_case(SoftwareValue::Uint64(0xFFFF), KlvFormat::Uint, None, FormatParams::default(), &[0xFF, 0xFF]; "Uint")]
+    #[test_case(SoftwareValue::Uint64(1), KlvFormat::Uint, Some(4), FormatParams::default(), &[0x00, 0x00, 0x00, 0x01]; "Uint with a length")]
+    #[test_case(SoftwareValue::Float64(360.0), KlvFormat::Uint, Some(2), FormatParams::range(0.0, 360.0), &[0xFF, 0xFF]; "Mapped uint")]
+    #[test_case(SoftwareValue::Int64(-2), KlvFormat::Int, None, FormatParams::default(), &[0xFE]; "Int")]
+    #[test_case(SoftwareValue::Int64(128), KlvFormat::Int, None, FormatParams::default(), &[0x00, 0x80]; "Int needing a sign byte")]
+    #[test_case(SoftwareValue::Float64(-20.0), KlvFormat::Int, Some(2), FormatParams::range(-20.0, 20.0), &[0x80, 0x01]; "Mapped int")]
+    #[test_case(SoftwareValue::Uint64(128), KlvFormat::BerOid, None, FormatParams::default(), &[0x81, 0x00]; "BER-OID")]
+    #[test_case(SoftwareValue::Float64(2.0), KlvFormat::Imapb, Some(2), FormatParams::range(0.0, 256.0), &[0x01, 0x00]; "IMAPB")]
+    #[test_case(SoftwareValue::Float64(1.5), KlvFormat::Float, Some(4), FormatParams::default(), &[0x3F, 0xC0, 0x00, 0x00]; "Float")]
+    #[test_case(SoftwareValue::Bool(true), KlvFormat::Boolean, None, FormatParams::default(), &[0x01]; "Boolean")]
+    #[test_case(SoftwareValue::String("MISB".into()), KlvFormat::Utf8, None, FormatParams::default(), b"MISB"; "UTF8")]
+    fn conversion_to_klv(
+        value: SoftwareValue,
+        format: KlvFormat,
+        length: Option<usize>,
+        params: FormatParams,
+        expected: &[u8],
+    ) {
+        assert_eq!(
+            convert_to_klv(&value, &format, length, &params).unwrap(),
+            expected
+        );
+    }
+
+    #[test_case(SoftwareValue::Float64(20.5), KlvFormat::Int, Some(2), FormatParams::range(-20.0, 20.0); "Outside of range")]
+    #[test_case(SoftwareValue::Uint64(0x0100), KlvFormat::Uint, Some(1), FormatParams::default(); "Too long for length")]
+    #[test_case(SoftwareValue::Float64(1.0), KlvFormat::Uint, None, FormatParams::range(0.0, 1.0); "Mapped without length")]
+    #[test_case(SoftwareValue::String("MISB".into()), KlvFormat::Uint, None, FormatParams::default(); "Incompatible")]
+    fn invalid_values_to_klv(
+        value: SoftwareValue,
+        format: KlvFormat,
+        length: Option<usize>,
+        params: FormatParams,
+    ) {
+        assert!(convert_to_klv(&value, &format, length, &params).is_err());
+    }
+
+    #[test_case(KlvFormat::Uint, Some(20), FormatParams::range(0.0, 1.0); "Mapped past 16 bytes")]
+    #[test_case(KlvFormat::Int, Some(0), FormatParams::range(-1.0, 1.0); "Mapped in no bytes")]
+    #[test_case(KlvFormat::Uint, Some(17), FormatParams::default(); "Integer past 16 bytes")]
+    #[test_case(KlvFormat::Imapb, Some(0), FormatParams::range(0.0, 1.0); "IMAPB in no bytes")]
+    #[test_case(KlvFormat::Imapb, Some(20), FormatParams::range(0.0, 1.0); "IMAPB past 16 bytes")]
+    fn invalid_integer_lengths_to_klv(
+        format: KlvFormat,
+        length: Option<usize>,
+        params: FormatParams,
+    ) {
+        let value = match params.range {
+            Some(_) => SoftwareValue::Float64(1.0),
+            None => SoftwareValue::Uint64(1),
+        };
+        assert!(matches!(
+            convert_to_klv(&value, &format, length, &params),
+            Err(encoding::Error::InvalidValueLength { length: actual, .. })
+                if Some(actual as usize) == length
+        ));
+    }
+
+    #[test_case(&[0x80], KlvFormat::Iso7; "ISO7 with the eighth bit set")]
+    #[test_case(&[0x02], KlvFormat::Boolean; "Boolean other than zero or one")]
+    #[test_case(&[0x00, 0x00], KlvFormat::Float; "Two byte float")]
+    fn invalid_values(klv: &[u8], format: KlvFormat) {
+        let software = match format {
+            KlvFormat::Iso7 => SoftwareFormat::String,
+            KlvFormat::Boolean => SoftwareFormat::Bool,
+            _ => SoftwareFormat::Float64,
+        };
+        assert!(convert(klv, &format, &software, &FormatParams::default()).is_err());
+    }
+
+    #[cfg(feature = "uuid")]
+    #[test]
+    fn uuid_conversion() {
+        let klv = (0x00..0x10).collect::<Vec<u8>>();
+        let value = convert(
+            &klv,
+            &KlvFormat::Uuid,
+            &SoftwareFormat::Uuid,
+            &FormatParams::default(),
+        )
+        .unwrap();
+
+        assert_eq!(value.to_string(), "00010203-0405-0607-0809-0a0b0c0d0e0f");
+        assert_eq!(
+            convert_to_klv(&value, &KlvFormat::Uuid, None, &FormatParams::default()).unwrap(),
+            klv
+        );
+        assert_eq!(KlvFormat::from_misb_str("UUID"), Ok(KlvFormat::Uuid));
+        assert!(
+            convert(
+                &klv[..15],
+                &KlvFormat::Uuid,
+                &SoftwareFormat::Uuid,
+                &FormatParams::default()
+            )
+            .is_err()
+        );
+        #[cfg(feature = "serde")]
+        assert_eq!(
+            serde_json::to_string(&value).unwrap(),
+            r#"{"Uuid":"00010203-0405-0607-0809-0a0b0c0d0e0f"}"#
+        );
+    }
+}
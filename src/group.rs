@@ -0,0 +1,333 @@
+//! SMPTE 336M group constructs and how the items inside of them are encoded.
+
+use std::io::{self, Read};
+
+use byteorder::{BigEndian, ReadBytesExt};
+
+use crate::{
+    encoding::{
+        ber::read_ber_counted, ber_oid::read_ber_oid_counted, invalid_data, seekable_length,
+    },
+    klv::ItemKey,
+    universal_set::{UNIVERSAL_KEY_LENGTH, UniversalKey},
+};
+
+/// First four bytes of every SMPTE Universal Label.
+const UL_PREFIX: [u8; 4] = [0x06, 0x0E, 0x2B, 0x34];
+
+/// Value of the category designator (byte 5) for groups.
+const GROUP_CATEGORY: u8 = 0x02;
+
+/// How the key of each item in a group is encoded.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TagEncoding {
+    /// BER-OID encoded tag number, as used by MISB local sets.
+    BerOid,
+    /// Big-endian tag number of a fixed number of bytes.
+    Fixed(u8),
+    /// Full 16-byte Universal Label.
+    Ul,
+}
+
+/// Length of the key of a KLV triplet.
+///
+/// SMPTE 336M allows keys of 1, 2, 4, or 16 bytes, and MISB local sets key
+/// their items with BER-OID tags of however many bytes they need.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum KeyLength {
+    One,
+    Two,
+    Four,
+    /// Full 16-byte Universal Label.
+    Sixteen,
+    BerOid,
+}
+
+impl From<KeyLength> for TagEncoding {
+    fn from(key_length: KeyLength) -> Self {
+        match key_length {
+            KeyLength::One => Self::Fixed(1),
+            KeyLength::Two => Self::Fixed(2),
+            KeyLength::Four => Self::Fixed(4),
+            KeyLength::Sixteen => Self::Ul,
+            KeyLength::BerOid => Self::BerOid,
+        }
+    }
+}
+
+/// How the length of each item in a group is encoded.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LengthEncoding {
+    /// BER short or long form.
+    Ber,
+    /// Big-endian length of a fixed number of bytes.
+    Fixed(u8),
+}
+
+/// How the tag and length of every item in a group are encoded.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ItemEncoding {
+    pub tag: TagEncoding,
+    pub length: LengthEncoding,
+}
+
+impl Default for ItemEncoding {
+    /// BER-OID tags and BER lengths, as used by every MISB local set.
+    fn default() -> Self {
+        Self {
+            tag: TagEncoding::BerOid,
+            length: LengthEncoding::Ber,
+        }
+    }
+}
+
+impl ItemEncoding {
+    /// Reads a tag encoded the way this group encodes them.
+    ///
+    /// Universal Labels are returned as a big-endian `u128`.
+    ///
+    /// # Side Effects
+    ///
+    /// Moves the current position in the buffer to the byte after the last
+    /// byte of the tag.
+    pub fn read_tag<R>(&self, buf: &mut R) -> Result<u128, io::Error>
+    where
+        R: Read,
+    {
+        self.read_tag_counted(buf).map(|(tag, _)| tag)
+    }
+
+    /// Reads a tag the same way as `ItemEncoding::read_tag`, along with the
+    /// number of bytes it was encoded in.
+    pub fn read_tag_counted<R>(&self, buf: &mut R) -> Result<(u128, usize), io::Error>
+    where
+        R: Read,
+    {
+        match self.tag {
+            TagEncoding::BerOid => read_ber_oid_counted(buf),
+            TagEncoding::Fixed(n) => Ok((buf.read_uint128::<BigEndian>(n.into())?, n.into())),
+            TagEncoding::Ul => Ok((buf.read_u128::<BigEndian>()?, UNIVERSAL_KEY_LENGTH)),
+        }
+    }
+
+    /// Reads a tag encoded the way this group encodes them as an `ItemKey`.
+    ///
+    /// # Side Effects
+    ///
+    /// Moves the current position in the buffer to the byte after the last
+    /// byte of the tag.
+    pub fn read_key<R>(&self, buf: &mut R) -> Result<ItemKey, io::Error>
+    where
+        R: Read,
+    {
+        self.read_key_counted(buf).map(|(key, _)| key)
+    }
+
+    /// Reads a key the same way as `ItemEncoding::read_key`, along with the
+    /// number of bytes it was encoded in.
+    pub fn read_key_counted<R>(&self, buf: &mut R) -> Result<(ItemKey, usize), io::Error>
+    where
+        R: Read,
+    {
+        let (tag, width) = self.read_tag_counted(buf)?;
+        let key = match self.tag {
+            TagEncoding::Ul => ItemKey::Ul(UniversalKey::new(tag.to_be_bytes())),
+            _ => ItemKey::Tag(tag),
+        };
+        Ok((key, width))
+    }
+
+    /// Reads a length encoded the way this group encodes them.
+    ///
+    /// # Side Effects
+    ///
+    /// Moves the current position in the buffer to the byte after the last
+    /// byte of the length.
+    pub fn read_length<R>(&self, buf: &mut R) -> Result<u64, io::Error>
+    where
+        R: Read,
+    {
+        self.read_length_counted(buf).map(|(length, _)| length)
+    }
+
+    /// Reads a length the same way as `ItemEncoding::read_length`, along with
+    /// the number of bytes it was encoded in.
+    pub fn read_length_counted<R>(&self, buf: &mut R) -> Result<(u64, usize), io::Error>
+    where
+        R: Read,
+    {
+        let (length, width) = match self.length {
+            LengthEncoding::Ber => read_ber_counted(buf)?,
+            LengthEncoding::Fixed(n) => (buf.read_uint128::<BigEndian>(n.into())?, n.into()),
+        };
+        Ok((seekable_length(length).map_err(invalid_data)?, width))
+    }
+}
+
+/// Kind of group a Universal Key identifies, taken from the registry
+/// designator (byte 6) of the key.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GroupType {
+    /// Every item is keyed by a full 16-byte Universal Label.
+    UniversalSet,
+    /// Every item is keyed by a 2-byte tag.
+    GlobalSet { length: LengthEncoding },
+    /// Every item is keyed by a short tag, BER-OID in the case of MISB local
+    /// sets.
+    LocalSet {
+        tag: TagEncoding,
+        length: LengthEncoding,
+    },
+    /// Items have no keys and are identified by their order.
+    VariableLengthPack,
+    /// Items have no keys or lengths and are identified by their order.
+    DefinedLengthPack,
+}
+
+impl GroupType {
+    /// Reads the group type out of the designators of `key`.
+    ///
+    /// # Returns
+    ///
+    /// - `Some(GroupType)` - The key is a SMPTE group key.
+    /// - `None` - The key is not a SMPTE UL, is not in the groups category, or
+    ///   uses a reserved registry designator.
+    pub fn from_key(key: &UniversalKey) -> Option<Self> {
+        if key[..4] != UL_PREFIX || key[4] != GROUP_CATEGORY {
+            return None;
+        }
+
+        let designator = key[5];
+        // Bits 5-6 of the designator give the length encoding of sets.
+        let length = match (designator >> 5) & 0b11 {
+            0 => LengthEncoding::Ber,
+            1 => LengthEncoding::Fixed(1),
+            2 => LengthEncoding::Fixed(2),
+            _ => LengthEncoding::Fixed(4),
+        };
+        // Bits 3-4 of the designator give the tag encoding of local sets.
+        let tag = match (designator >> 3) & 0b11 {
+            0 => TagEncoding::Fixed(1),
+            1 => TagEncoding::BerOid,
+            2 => TagEncoding::Fixed(2),
+            _ => TagEncoding::Fixed(4),
+        };
+
+        match designator & 0b111 {
+            0x01 => Some(Self::UniversalSet),
+            0x02 => Some(Self::GlobalSet { length }),
+            0x03 => Some(Self::LocalSet { tag, length }),
+            0x04 => Some(Self::VariableLengthPack),
+            0x05 => Some(Self::DefinedLengthPack),
+            _ => None,
+        }
+    }
+
+    /// How the items in this kind of group are encoded.
+    ///
+    /// # Returns
+    ///
+    /// - `Some(ItemEncoding)` - The group is a set.
+    /// - `None` - The group is a pack, whose items have no keys.
+    pub fn item_encoding(&self) -> Option<ItemEncoding> {
+        match *self {
+            Self::UniversalSet => Some(ItemEncoding {
+                tag: TagEncoding::Ul,
+                length: LengthEncoding::Ber,
+            }),
+            Self::GlobalSet { length } => Some(ItemEncoding {
+                tag: TagEncoding::Fixed(2),
+                length,
+            }),
+            Self::LocalSet { tag, length } => Some(ItemEncoding { tag, length }),
+            Self::VariableLengthPack | Self::DefinedLengthPack => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::RefCell, io::Cursor, rc::Rc};
+
+    use super::*;
+    use crate::{keys, universal_set::UniversalSet};
+    use itertools::{Itertools, chain};
+    use test_case::test_case;
+
+    fn group_key(designator: u8) -> UniversalKey {
+        UniversalKey::new([
+            0x06, 0x0E, 0x2B, 0x34, 0x02, designator, 0x01, 0x01, 0x0E, 0x01, 0x03, 0x01, 0x01,
+            0x00, 0x00, 0x00,
+        ])
+    }
+
+    #[test_case(0x01, Some(GroupType::UniversalSet); "Universal set")]
+    #[test_case(0x53, Some(GroupType::LocalSet { tag: TagEncoding::Fixed(2), length: LengthEncoding::Fixed(2) }); "Local set with fixed tags and lengths")]
+    #[test_case(0x0B, Some(GroupType::LocalSet { tag: TagEncoding::BerOid, length: LengthEncoding::Ber }); "MISB local set")]
+    #[test_case(0x04, Some(GroupType::VariableLengthPack); "Variable length pack")]
+    #[test_case(0x05, Some(GroupType::DefinedLengthPack); "Defined length pack")]
+    #[test_case(0x06, None; "Reserved")]
+    fn test_from_key(designator: u8, expected: Option<GroupType>) {
+        assert_eq!(GroupType::from_key(&group_key(designator)), expected);
+    }
+
+    #[test]
+    fn test_from_key_not_a_group() {
+        assert_eq!(GroupType::from_key(&keys::FILL), None);
+        assert_eq!(GroupType::from_key(&UniversalKey::new([0x00; 16])), None);
+    }
+
+    /// Parses a single set whose key is `key` and returns each tag and value.
+    fn parse(key: UniversalKey, value: &[u8]) -> Vec<(ItemKey, Vec<u8>)> {
+        let buf = chain!(*key, [value.len() as u8], value.iter().copied()).collect_vec();
+        let buf = Rc::new(RefCell::new(Cursor::new(buf)));
+        let set = UniversalSet::new(key, buf, 0).unwrap();
+        set.data()
+            .iter()
+            .map(|klv| (klv.key(), klv.read_value().unwrap()))
+            .collect()
+    }
+
+    #[test]
+    fn universal_set() {
+        let first = [0x11; 16];
+        let second = [0x22; 16];
+        let value = chain!(first, [0x01, 0xAA], second, [0x02, 0xBB, 0xCC]).collect_vec();
+        assert_eq!(
+            parse(group_key(0x01), &value),
+            vec![
+                (ItemKey::Ul(UniversalKey::new(first)), vec![0xAA]),
+                (ItemKey::Ul(UniversalKey::new(second)), vec![0xBB, 0xCC])
+            ]
+        );
+    }
+
+    #[test]
+    fn global_set() {
+        let value = [0x01, 0x02, 0x01, 0xAA, 0x03, 0x04, 0x02, 0xBB, 0xCC];
+        assert_eq!(
+            parse(group_key(0x02), &value),
+            vec![
+                (ItemKey::Tag(0x0102), vec![0xAA]),
+                (ItemKey::Tag(0x0304), vec![0xBB, 0xCC])
+            ]
+        );
+    }
+
+    #[test]
+    fn local_set_with_fixed_tags_and_lengths() {
+        let value = [0x00, 0x01, 0x00, 0x01, 0xAA, 0x00, 0x02, 0x00, 0x00];
+        assert_eq!(
+            parse(group_key(0x53), &value),
+            vec![(ItemKey::Tag(1), vec![0xAA]), (ItemKey::Tag(2), vec![])]
+        );
+    }
+
+    #[test]
+    fn pack_is_not_supported() {
+        let key = group_key(0x05);
+        let buf = chain!(*key, [0x02, 0xAA, 0xBB]).collect_vec();
+        let buf = Rc::new(RefCell::new(Cursor::new(buf)));
+        assert!(UniversalSet::new(key, buf, 0).is_err());
+    }
+}
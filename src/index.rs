@@ -0,0 +1,386 @@
+//! Index of where the packets in a buffer are, saved alongside a recording so
+//! that reopening it doesn't mean searching it again.
+
+use std::{
+    cell::RefCell,
+    io::{self, Read, Seek, SeekFrom, Write},
+    rc::Rc,
+    time::{Duration, SystemTime},
+};
+
+use byteorder::{BigEndian, ReadBytesExt, WriteBytesExt};
+
+use crate::{
+    crc::Crc32,
+    encoding,
+    universal_set::{UNIVERSAL_KEY_LENGTH, UniversalKey, UniversalSet},
+};
+
+/// First bytes of every saved index.
+pub const INDEX_MAGIC: [u8; 4] = *b"KLVI";
+
+/// Version of the format `KlvIndex::save` writes.
+pub const INDEX_VERSION: u16 = 1;
+
+#[derive(Debug, thiserror::Error)]
+pub enum IndexError {
+    #[error("Not a KLV index")]
+    BadMagic,
+    #[error("Index format version {0} is not supported")]
+    UnsupportedVersion(u16),
+    #[error("Index has checksum {expected:#010x} but its contents sum to {actual:#010x}")]
+    ChecksumMismatch { expected: u32, actual: u32 },
+    /// The buffer is not the size it was when the index was built.
+    #[error("Index is stale: buffer was {indexed} bytes long and is now {actual}")]
+    SizeChanged { indexed: u64, actual: u64 },
+    /// The first packet in the buffer is not the one the index was built
+    /// from.
+    #[error("Index is stale: the first packet has changed")]
+    FirstPacketChanged,
+    #[error(transparent)]
+    Io(#[from] io::Error),
+}
+
+/// Where a single packet lies in the buffer.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, getset::CopyGetters)]
+#[getset(get_copy = "pub")]
+pub struct IndexEntry {
+    key: UniversalKey,
+    /// Offset of the first byte of the packet's Universal Key.
+    offset: u64,
+    /// Number of bytes that make up the packet's value.
+    payload_length: u64,
+    /// Precision Time Stamp of the packet, if it has one that could be read.
+    timestamp: Option<SystemTime>,
+}
+
+/// Every packet keyed by the same Universal Key in a buffer, in buffer
+/// order.
+///
+/// Saved indexes record the length of the buffer and a CRC-32 of its first
+/// packet so that loading one for a buffer that has since changed fails.
+#[derive(Clone, Debug, PartialEq, Eq, getset::CopyGetters, getset::Getters)]
+pub struct KlvIndex {
+    #[getset(get_copy = "pub")]
+    key: UniversalKey,
+    /// Length of the buffer the index was built from.
+    #[getset(get_copy = "pub")]
+    source_length: u64,
+    /// CRC-32 of every byte of the first packet. 0 when there are no packets.
+    first_packet_crc: u32,
+    #[getset(get = "pub")]
+    entries: Vec<IndexEntry>,
+}
+
+/// Finds every packet keyed by `key` in the buffer.
+///
+/// Packets whose length can't be read are left out.
+///
+/// # Returns
+///
+/// - `Ok(KlvIndex)` - Every packet was visited.
+/// - `Err(encoding::Error)` - The buffer could not be searched for the key.
+///
+/// # Side Effects
+///
+/// Moves the current position in the buffer.
+pub fn build_index<T>(
+    key: impl Into<UniversalKey>,
+    buf: Rc<RefCell<T>>,
+) -> Result<KlvIndex, encoding::Error>
+where
+    T: Read + Seek,
+{
+    let key = key.into();
+    let encoding = UniversalSet::<T>::item_encoding(&key)?;
+    let (source_length, extents) = {
+        let mut buf = buf.borrow_mut();
+        let source_length = buf.seek(SeekFrom::End(0))?;
+        buf.seek(SeekFrom::Start(0))?;
+        let extents = UniversalSet::start_locations(&key, &mut *buf)?
+            .into_iter()
+            .filter_map(|offset| UniversalSet::read_extent(&mut *buf, offset).ok())
+            .collect::<Vec<_>>();
+        (source_length, extents)
+    };
+
+    let entries = extents
+        .iter()
+        .map(|extent| IndexEntry {
+            key,
+            offset: extent.key_offset(),
+            payload_length: extent.value_length(),
+            timestamp: UniversalSet::read_time_stamp(extent.key_offset(), &buf, &encoding),
+        })
+        .collect::<Vec<_>>();
+    let first_packet_crc = first_packet_crc(&mut *buf.borrow_mut(), entries.first())?;
+    Ok(KlvIndex {
+        key,
+        source_length,
+        first_packet_crc,
+        entries,
+    })
+}
+
+/// CRC-32 of every byte of the packet of `entry`, or 0 without one.
+fn first_packet_crc<T>(buf: &mut T, entry: Option<&IndexEntry>) -> Result<u32, encoding::Error>
+where
+    T: Read + Seek,
+{
+    let Some(entry) = entry else {
+        return Ok(0);
+    };
+    let extent = UniversalSet::read_extent(buf, entry.offset)?;
+    buf.seek(SeekFrom::Start(entry.offset))?;
+    let mut packet = Vec::new();
+    buf.take(extent.total_length()).read_to_end(&mut packet)?;
+    Ok(Crc32::checksum(&packet))
+}
+
+impl KlvIndex {
+    /// Writes the index out as `INDEX_MAGIC`, `INDEX_VERSION`, the key, the
+    /// length of the buffer, the CRC-32 of its first packet, and every entry,
+    /// followed by a CRC-32 of everything before it. Numbers are big-endian.
+    pub fn save<W>(&self, writer: &mut W) -> Result<(), io::Error>
+    where
+        W: Write,
+    {
+        let mut bytes = INDEX_MAGIC.to_vec();
+        bytes.write_u16::<BigEndian>(INDEX_VERSION)?;
+        bytes.extend_from_slice(&self.key[..]);
+        bytes.write_u64::<BigEndian>(self.source_length)?;
+        bytes.write_u32::<BigEndian>(self.first_packet_crc)?;
+        bytes.write_u64::<BigEndian>(self.entries.len() as u64)?;
+        for entry in &self.entries {
+            bytes.write_u64::<BigEndian>(entry.offset)?;
+            bytes.write_u64::<BigEndian>(entry.payload_length)?;
+            let micros = entry
+                .timestamp
+                .and_then(|time| time.duration_since(SystemTime::UNIX_EPOCH).ok())
+                .map(|since| since.as_micros() as u64);
+            bytes.write_u8(micros.is_some().into())?;
+            bytes.write_u64::<BigEndian>(micros.unwrap_or_default())?;
+        }
+        bytes.write_u32::<BigEndian>(Crc32::checksum(&bytes))?;
+        writer.write_all(&bytes)
+    }
+
+    /// Reads an index written by `KlvIndex::save` and checks that it still
+    /// describes the buffer.
+    ///
+    /// # Returns
+    ///
+    /// - `Ok(KlvIndex)` - The index was read and matches the buffer.
+    /// - `Err(IndexError::SizeChanged | IndexError::FirstPacketChanged)` -
+    ///   The buffer has changed since the index was built.
+    /// - `Err(IndexError)` - The index is corrupt, from an unsupported
+    ///   version, or could not be read.
+    ///
+    /// # Side Effects
+    ///
+    /// Moves the current position in the buffer.
+    pub fn load<R, T>(reader: &mut R, buf: &mut T) -> Result<Self, IndexError>
+    where
+        R: Read,
+        T: Read + Seek,
+    {
+        let index = Self::read(reader)?;
+        let actual = buf.seek(SeekFrom::End(0))?;
+        if actual != index.source_length {
+            return Err(IndexError::SizeChanged {
+                indexed: index.source_length,
+                actual,
+            });
+        }
+        match first_packet_crc(buf, index.entries.first()) {
+            Ok(crc) if crc == index.first_packet_crc => Ok(index),
+            _ => Err(IndexError::FirstPacketChanged),
+        }
+    }
+
+    fn read<R>(reader: &mut R) -> Result<Self, IndexError>
+    where
+        R: Read,
+    {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes)?;
+        // Anything shorter can't hold the magic and the checksum.
+        if bytes.len() < INDEX_MAGIC.len() + 4 || !bytes.starts_with(&INDEX_MAGIC) {
+            return Err(IndexError::BadMagic);
+        }
+        let (contents, checksum) = bytes.split_at(bytes.len() - 4);
+        let expected = u32::from_be_bytes(checksum.try_into().unwrap());
+        let actual = Crc32::checksum(contents);
+        if expected != actual {
+            return Err(IndexError::ChecksumMismatch { expected, actual });
+        }
+
+        let mut contents = &contents[INDEX_MAGIC.len()..];
+        let version = contents.read_u16::<BigEndian>()?;
+        if version != INDEX_VERSION {
+            return Err(IndexError::UnsupportedVersion(version));
+        }
+        let mut key = [0; UNIVERSAL_KEY_LENGTH];
+        contents.read_exact(&mut key)?;
+        let key = UniversalKey::new(key);
+        let source_length = contents.read_u64::<BigEndian>()?;
+        let first_packet_crc = contents.read_u32::<BigEndian>()?;
+        let count = contents.read_u64::<BigEndian>()?;
+        let entries = (0..count)
+            .map(|_| -> Result<_, io::Error> {
+                let offset = contents.read_u64::<BigEndian>()?;
+                let payload_length = contents.read_u64::<BigEndian>()?;
+                let has_timestamp = contents.read_u8()? != 0;
+                let micros = contents.read_u64::<BigEndian>()?;
+                Ok(IndexEntry {
+                    key,
+                    offset,
+                    payload_length,
+                    timestamp: has_timestamp
+                        .then(|| SystemTime::UNIX_EPOCH + Duration::from_micros(micros)),
+                })
+            })
+            .collect::<Result<_, _>>()?;
+
+        Ok(Self {
+            key,
+            source_length,
+            first_packet_crc,
+            entries,
+        })
+    }
+}
+
+impl<T> UniversalSet<T>
+where
+    T: Read + Seek,
+{
+    /// Reads the set `entry` points to without searching for it.
+    ///
+    /// # Returns
+    ///
+    /// - `Ok(UniversalSet)` - The set was read.
+    /// - `Err(encoding::Error)` - The set could not be read, or its length is
+    ///   not the one in the index.
+    pub fn from_index_entry(
+        buf: Rc<RefCell<T>>,
+        entry: &IndexEntry,
+    ) -> Result<Self, encoding::Error> {
+        let set = Self::new(entry.key, buf, entry.offset)?;
+        if set.extent().value_length() != entry.payload_length {
+            return Err(encoding::Error::IndexedLengthMismatch {
+                offset: entry.offset,
+                expected: entry.payload_length,
+                actual: set.extent().value_length(),
+            });
+        }
+        Ok(set)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use super::*;
+    use crate::{keys, klv::OwnedKlv, local_set::OwnedLocalSet};
+    use itertools::Itertools;
+
+    /// Three packets with timestamps and one without, separated by junk.
+    fn recording() -> Vec<u8> {
+        let packet = |items| OwnedLocalSet::new(items).serialize(&keys::ST0601, true);
+        let timestamped =
+            |micros: u64| packet(vec![OwnedKlv::new(2, micros.to_be_bytes().to_vec())]);
+        [
+            timestamped(1_000_000),
+            vec![0xAA; 5],
+            packet(vec![OwnedKlv::new(3, b"MISSION".to_vec())]),
+            timestamped(2_000_000),
+            timestamped(3_000_000),
+        ]
+        .concat()
+    }
+
+    fn index(buf: &[u8]) -> KlvIndex {
+        build_index(
+            keys::ST0601,
+            Rc::new(RefCell::new(Cursor::new(buf.to_vec()))),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn save_and_load_round_trip() {
+        let buf = recording();
+        let index = index(&buf);
+        assert_eq!(
+            index
+                .entries()
+                .iter()
+                .map(|entry| entry.timestamp().map(|time| time
+                    .duration_since(SystemTime::UNIX_EPOCH)
+                    .unwrap()
+                    .as_secs()))
+                .collect_vec(),
+            [Some(1), None, Some(2), Some(3)]
+        );
+
+        let mut saved = Vec::new();
+        index.save(&mut saved).unwrap();
+        let loaded = KlvIndex::load(&mut saved.as_slice(), &mut Cursor::new(&buf)).unwrap();
+        assert_eq!(loaded, index);
+
+        let shared = Rc::new(RefCell::new(Cursor::new(buf)));
+        let set = UniversalSet::from_index_entry(shared, &loaded.entries()[2]).unwrap();
+        assert_eq!(set.extent().key_offset(), loaded.entries()[2].offset());
+    }
+
+    #[test]
+    fn stale_index_is_reported() {
+        let buf = recording();
+        let mut saved = Vec::new();
+        index(&buf).save(&mut saved).unwrap();
+
+        let longer = [buf.as_slice(), &[0x00]].concat();
+        assert!(matches!(
+            KlvIndex::load(&mut saved.as_slice(), &mut Cursor::new(&longer)),
+            Err(IndexError::SizeChanged { .. })
+        ));
+
+        // Same length, but a byte of the first packet's timestamp differs.
+        let mut changed = buf.clone();
+        changed[UNIVERSAL_KEY_LENGTH + 5] ^= 0xFF;
+        assert!(matches!(
+            KlvIndex::load(&mut saved.as_slice(), &mut Cursor::new(&changed)),
+            Err(IndexError::FirstPacketChanged)
+        ));
+    }
+
+    #[test]
+    fn corrupt_index_is_reported() {
+        let buf = recording();
+        let mut saved = Vec::new();
+        index(&buf).save(&mut saved).unwrap();
+
+        let mut corrupt = saved.clone();
+        corrupt[30] ^= 0x01;
+        assert!(matches!(
+            KlvIndex::load(&mut corrupt.as_slice(), &mut Cursor::new(&buf)),
+            Err(IndexError::ChecksumMismatch { .. })
+        ));
+
+        let mut newer = saved[..saved.len() - 4].to_vec();
+        newer[5] = 2;
+        let crc = Crc32::checksum(&newer);
+        newer.extend(crc.to_be_bytes());
+        assert!(matches!(
+            KlvIndex::load(&mut newer.as_slice(), &mut Cursor::new(&buf)),
+            Err(IndexError::UnsupportedVersion(2))
+        ));
+
+        assert!(matches!(
+            KlvIndex::load(&mut &b"KLVI"[..], &mut Cursor::new(&buf)),
+            Err(IndexError::BadMagic)
+        ));
+    }
+}
@@ -0,0 +1,23 @@
+//! Universal Keys for commonly used sets.
+
+use crate::universal_set::UniversalKey;
+
+/// Universal Key for the MISB ST 0601 UAS Datalink Local Set.
+pub const ST0601: UniversalKey = UniversalKey::new([
+    0x06, 0x0E, 0x2B, 0x34, 0x02, 0x0B, 0x01, 0x01, 0x0E, 0x01, 0x03, 0x01, 0x01, 0x00, 0x00, 0x00,
+]);
+
+/// Universal Key for the legacy MISB EG 0104 Predator UAV Basic Universal
+/// Set, whose items are keyed by full Universal Labels.
+pub const EG0104: UniversalKey = UniversalKey::new([
+    0x06, 0x0E, 0x2B, 0x34, 0x02, 0x01, 0x01, 0x01, 0x0E, 0x01, 0x01, 0x02, 0x01, 0x01, 0x00, 0x00,
+]);
+
+/// Universal Key of a SMPTE 336M KLV fill item, whose value is meaningless
+/// padding.
+///
+/// Older encoders use version 1 of this key, so compare against it with
+/// `UniversalKey::is_fill` rather than `==`.
+pub const FILL: UniversalKey = UniversalKey::new([
+    0x06, 0x0E, 0x2B, 0x34, 0x01, 0x01, 0x01, 0x02, 0x03, 0x01, 0x02, 0x10, 0x01, 0x00, 0x00, 0x00,
+]);
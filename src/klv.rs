@@ -1,27 +1,538 @@
 use std::cell::RefCell;
+use std::fmt;
+use std::fs::File;
+use std::hash::{DefaultHasher, Hasher};
 use std::io;
+use std::io::BufReader;
+use std::io::Cursor;
 use std::io::Read;
 use std::io::Seek;
 use std::io::SeekFrom;
+use std::io::Write;
+use std::path::Path;
+use std::path::PathBuf;
 use std::rc::Rc;
 
 use crate::encoding;
-use crate::encoding::ber::read_ber;
-use crate::encoding::ber_oid::read_ber_oid;
+use crate::encoding::ber::{BerForm, encode_ber_with_form, read_ber};
+use crate::encoding::ber_oid::{encode_ber_oid, read_ber_oid};
+use crate::encoding::io_util::{DEFAULT_CHUNK, copy_limited, read_exactly, read_up_to};
+#[cfg(feature = "tokio")]
+use crate::encoding::{ber::read_ber_async, ber_oid::read_ber_oid_async};
+use crate::encoding::{checked_end, invalid_data, rewind_on_error, seekable_length};
+use crate::group::{ItemEncoding, KeyLength, LengthEncoding, TagEncoding};
+use crate::options::ParseOptions;
+use crate::tag::Tag;
+use crate::universal_set::{UNIVERSAL_KEY_LENGTH, UniversalKey};
 
 pub type RawValueData = Vec<u8>;
 
-#[derive(Debug, getset::CopyGetters)]
+/// Buffer that can be both read and seeked.
+///
+/// Lets buffers of different types be stored together as
+/// `Box<dyn ReadSeek>`, which is itself a buffer every reader in the crate
+/// accepts, such as `UniversalSet<Box<dyn ReadSeek>>`.
+pub trait ReadSeek: Read + Seek {}
+
+impl<T> ReadSeek for T where T: Read + Seek + ?Sized {}
+
+/// Identifies an item within a set.
+///
+/// Most sets key their items with a short tag number but some, such as
+/// SMPTE 336M universal sets, key them with a full Universal Label.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[cfg_attr(feature = "serde", serde(untagged))]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub enum ItemKey {
+    Tag(u128),
+    Ul(UniversalKey),
+}
+
+impl ItemKey {
+    /// Tag number of the item, if it is keyed by one.
+    pub fn tag(&self) -> Option<u128> {
+        match self {
+            Self::Tag(tag) => Some(*tag),
+            Self::Ul(_) => None,
+        }
+    }
+
+    /// Number the item is looked up by in a tag table. Universal Labels are
+    /// read as big-endian numbers.
+    pub fn number(&self) -> u128 {
+        match self {
+            Self::Tag(tag) => *tag,
+            Self::Ul(key) => u128::from_be_bytes(**key),
+        }
+    }
+
+    /// Whether this is the key of a KLV fill item.
+    pub fn is_fill(&self) -> bool {
+        matches!(self, Self::Ul(key) if key.is_fill())
+    }
+}
+
+/// Tags are read from numbers and Universal Labels from strings, matching how
+/// they are serialized.
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for ItemKey {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        struct ItemKeyVisitor;
+
+        impl serde::de::Visitor<'_> for ItemKeyVisitor {
+            type Value = ItemKey;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("a tag number or a Universal Label string")
+            }
+
+            fn visit_u64<E>(self, v: u64) -> Result<Self::Value, E> {
+                Ok(ItemKey::Tag(v.into()))
+            }
+
+            fn visit_u128<E>(self, v: u128) -> Result<Self::Value, E> {
+                Ok(ItemKey::Tag(v))
+            }
+
+            fn visit_str<E>(self, v: &str) -> Result<Self::Value, E>
+            where
+                E: serde::de::Error,
+            {
+                v.parse().map(ItemKey::Ul).map_err(E::custom)
+            }
+        }
+
+        deserializer.deserialize_any(ItemKeyVisitor)
+    }
+}
+
+impl From<u128> for ItemKey {
+    fn from(tag: u128) -> Self {
+        Self::Tag(tag)
+    }
+}
+
+impl From<UniversalKey> for ItemKey {
+    fn from(key: UniversalKey) -> Self {
+        Self::Ul(key)
+    }
+}
+
+impl fmt::Display for ItemKey {
+    /// Formats tags as a decimal number and Universal Labels the same way as
+    /// `UniversalKey`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Tag(tag) => f.pad(&tag.to_string()),
+            Self::Ul(key) => f.pad(&key.to_string()),
+        }
+    }
+}
+
+/// Where an item read out of a nested set lies, for describing it in
+/// diagnostics.
+///
+/// Formats as the chain of sets and keys leading to the item followed by the
+/// offset of its value, such as `ST0601[48]/ST0102[3] @ 0x1A2`.
+#[derive(Clone, Debug, PartialEq, Eq, getset::CopyGetters, getset::Getters)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct ItemContext {
+    /// Name of each set from the outermost inwards, paired with the key of
+    /// the item in it. The last entry is the item itself.
+    #[getset(get = "pub")]
+    path: Vec<(&'static str, ItemKey)>,
+    /// Position of the item among the items of its parent.
+    #[getset(get_copy = "pub")]
+    index: usize,
+    /// Offset in the buffer of the first byte of the item's value.
+    #[getset(get_copy = "pub")]
+    offset: u64,
+}
+
+impl ItemContext {
+    /// Context of the item at `index` in a set keyed by the tags of `G`.
+    pub fn new<G>(key: ItemKey, index: usize, offset: u64) -> Self
+    where
+        G: Tag,
+    {
+        Self {
+            path: vec![(G::set_name(), key)],
+            index,
+            offset,
+        }
+    }
+
+    /// Context of the item at `index` in the set of tags `G` nested in the
+    /// value of this item.
+    pub fn nested<G>(&self, key: ItemKey, index: usize, offset: u64) -> Self
+    where
+        G: Tag,
+    {
+        let mut path = self.path.clone();
+        path.push((G::set_name(), key));
+        Self {
+            path,
+            index,
+            offset,
+        }
+    }
+
+    /// Number of sets the item is in, counting the outermost set.
+    pub fn depth(&self) -> usize {
+        self.path.len()
+    }
+
+    /// Key of the item itself.
+    pub fn key(&self) -> ItemKey {
+        self.path.last().expect("Context has at least one set").1
+    }
+}
+
+impl fmt::Display for ItemContext {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, (set, key)) in self.path.iter().enumerate() {
+            if i > 0 {
+                f.write_str("/")?;
+            }
+            write!(f, "{}[{key}]", set.to_uppercase())?;
+        }
+        write!(f, " @ {:#X}", self.offset)
+    }
+}
+
+/// KLV triplet whose value has been copied out of the source it was read from.
+///
+/// Produced by the forward-only readers which never seek, so the value has to
+/// be read eagerly instead of being looked up later.
+///
+/// Triplets are equal when their keys and values are, however their keys and
+/// lengths were encoded.
+#[derive(Clone, Debug, getset::CopyGetters, getset::Getters)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub struct OwnedKlv {
+    /// Identifies this KLV triplet in its set.
+    #[getset(get_copy = "pub")]
+    key: ItemKey,
+
+    /// Bytes that make up the value for this KLV triplet.
+    #[getset(get = "pub")]
+    value: RawValueData,
+
+    /// Key and length exactly as they were encoded, for triplets copied out
+    /// of a buffer with `Klv::to_owned_klv`.
+    #[getset(get = "pub")]
+    #[cfg_attr(feature = "serde", serde(skip))]
+    #[cfg_attr(feature = "arbitrary", arbitrary(default))]
+    original_header: Option<RawHeader>,
+}
+
+/// Bytes of the key and length of a KLV triplet exactly as they were
+/// encoded.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct RawHeader {
+    pub tag_bytes: Vec<u8>,
+    pub length_bytes: Vec<u8>,
+}
+
+impl PartialEq for OwnedKlv {
+    fn eq(&self, other: &Self) -> bool {
+        self.key == other.key && self.value == other.value
+    }
+}
+
+impl Eq for OwnedKlv {}
+
+impl OwnedKlv {
+    pub fn new(tag: u128, value: RawValueData) -> Self {
+        Self::with_key(ItemKey::Tag(tag), value)
+    }
+
+    pub fn with_key(key: ItemKey, value: RawValueData) -> Self {
+        Self {
+            key,
+            value,
+            original_header: None,
+        }
+    }
+
+    /// Tag number of this KLV triplet, if it is keyed by one.
+    pub fn tag(&self) -> Option<u128> {
+        self.key.tag()
+    }
+
+    /// Number of bytes that make up the value for this KLV triplet.
+    pub fn length(&self) -> u64 {
+        self.value.len() as u64
+    }
+
+    /// Writes the key, length, and value to `writer`, with the length in
+    /// `length_form`. The key is written as it was encoded when the triplet
+    /// remembers it, and as a BER-OID tag or Universal Label otherwise.
+    ///
+    /// # Returns
+    ///
+    /// - `Ok(u64)` - The number of bytes written.
+    /// - `Err(encoding::Error::ValueOutOfRange)` - The length does not fit in
+    ///   `length_form`. Nothing is written.
+    /// - `Err(encoding::Error)` - The triplet could not be written.
+    pub fn write_to<W>(&self, writer: &mut W, length_form: BerForm) -> Result<u64, encoding::Error>
+    where
+        W: Write,
+    {
+        let key = match (&self.original_header, self.key) {
+            (Some(header), _) => header.tag_bytes.clone(),
+            (None, ItemKey::Tag(tag)) => encode_ber_oid(tag),
+            (None, ItemKey::Ul(key)) => key.to_vec(),
+        };
+        let length = encode_ber_with_form(self.length().into(), length_form)?;
+        writer.write_all(&key)?;
+        writer.write_all(&length)?;
+        writer.write_all(&self.value)?;
+        Ok((key.len() + length.len() + self.value.len()) as u64)
+    }
+
+    /// Reads in a new KLV triplet from the current reader position without
+    /// ever seeking.
+    ///
+    /// # Returns
+    ///
+    /// - `Ok(OwnedKlv)` - The tag, length, and value were all read.
+    /// - `Err(encoding::Error::EndOfStream)` - The reader had no bytes left
+    ///   before the tag.
+    /// - `Err(encoding::Error::Truncated)` - The reader ran out of bytes part
+    ///   way through the tag, length, or value.
+    /// - `Err(encoding::Error)` - Any other issue reading from the reader.
+    ///
+    /// # Side Effects
+    ///
+    /// Moves the current position in the reader to the byte after the last
+    /// byte of the value.
+    pub fn read<R>(reader: &mut R) -> Result<Self, encoding::Error>
+    where
+        R: Read,
+    {
+        Self::read_with_encoding(reader, &ItemEncoding::default())
+    }
+
+    /// Reads in a new KLV triplet whose tag and length are encoded as
+    /// described by `encoding`, without ever seeking.
+    ///
+    /// See `OwnedKlv::read`.
+    pub fn read_with_encoding<R>(
+        reader: &mut R,
+        encoding: &ItemEncoding,
+    ) -> Result<Self, encoding::Error>
+    where
+        R: Read,
+    {
+        let mut first_byte = [0];
+        loop {
+            match reader.read(&mut first_byte) {
+                Ok(0) => return Err(encoding::Error::EndOfStream),
+                Ok(_) => break,
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                Err(e) => return Err(e.into()),
+            }
+        }
+
+        let key = encoding
+            .read_key(&mut first_byte.chain(&mut *reader))
+            .map_err(|e| truncated_or_other(e, "tag"))?;
+        let length = encoding
+            .read_length(reader)
+            .map_err(|e| truncated_or_other(e, "length"))?;
+
+        let (value, eof) = read_up_to(reader, length, DEFAULT_CHUNK)?;
+        if eof {
+            return Err(encoding::Error::Truncated("value"));
+        }
+
+        Ok(Self::with_key(key, value))
+    }
+
+    /// Reads in a new KLV triplet from the current position of an async
+    /// reader.
+    ///
+    /// Behaves the same as `OwnedKlv::read`.
+    #[cfg(feature = "tokio")]
+    pub async fn read_async<R>(reader: &mut R) -> Result<Self, encoding::Error>
+    where
+        R: tokio::io::AsyncRead + Unpin,
+    {
+        use tokio::io::AsyncReadExt;
+
+        let mut first_byte = [0];
+        if reader.read(&mut first_byte).await? == 0 {
+            return Err(encoding::Error::EndOfStream);
+        }
+
+        let tag = read_ber_oid_async(&mut AsyncReadExt::chain(&first_byte[..], &mut *reader))
+            .await
+            .map_err(|e| truncated_or_other(e, "tag"))?;
+        let length = seekable_length(
+            read_ber_async(reader)
+                .await
+                .map_err(|e| truncated_or_other(e, "length"))?,
+        )?;
+
+        let mut value = Vec::new();
+        (&mut *reader).take(length).read_to_end(&mut value).await?;
+        if (value.len() as u64) < length {
+            return Err(encoding::Error::Truncated("value"));
+        }
+
+        Ok(Self::new(tag, value))
+    }
+}
+
+/// Reads in a KLV triplet whose key is `key_length` long and whose length
+/// is encoded as described by `length`, without ever seeking.
+///
+/// Keys of 1, 2, or 4 bytes are read as big-endian tag numbers and 16-byte
+/// keys as Universal Labels, so triplets coded outside of MISB local sets,
+/// such as the 4-byte keys of some SDI ancillary payloads, can be read as
+/// well.
+///
+/// See `OwnedKlv::read`.
+pub fn read_triplet<R>(
+    buf: &mut R,
+    key_length: KeyLength,
+    length: LengthEncoding,
+) -> Result<OwnedKlv, encoding::Error>
+where
+    R: Read,
+{
+    let encoding = ItemEncoding {
+        tag: key_length.into(),
+        length,
+    };
+    OwnedKlv::read_with_encoding(buf, &encoding)
+}
+
+/// Maps an `UnexpectedEof` from reading part of an element to
+/// `encoding::Error::Truncated`.
+pub(crate) fn truncated_or_other(err: io::Error, element: &'static str) -> encoding::Error {
+    if err.kind() == io::ErrorKind::UnexpectedEof {
+        encoding::Error::Truncated(element)
+    } else {
+        err.into()
+    }
+}
+
+/// Iterator over the KLV triplets in a forward-only reader.
+///
+/// Iteration ends when the reader runs out of bytes between triplets. Running
+/// out of bytes part way through a triplet yields an
+/// `encoding::Error::Truncated` and then ends iteration.
+#[derive(Debug)]
+pub struct KlvReader<R>
+where
+    R: Read,
+{
+    reader: R,
+    /// File the reader was opened from, attached to any errors.
+    path: Option<PathBuf>,
+    done: bool,
+}
+
+impl<R> KlvReader<R>
+where
+    R: Read,
+{
+    pub fn new(reader: R) -> Self {
+        Self {
+            reader,
+            path: None,
+            done: false,
+        }
+    }
+
+    /// Returns the reader that triplets are being read from.
+    pub fn into_inner(self) -> R {
+        self.reader
+    }
+}
+
+impl<R> Iterator for KlvReader<R>
+where
+    R: Read,
+{
+    type Item = Result<OwnedKlv, encoding::Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+
+        match OwnedKlv::read(&mut self.reader) {
+            Ok(klv) => Some(Ok(klv)),
+            Err(encoding::Error::EndOfStream) => {
+                self.done = true;
+                None
+            }
+            Err(e) => {
+                self.done = true;
+                Some(Err(match &self.path {
+                    Some(path) => e.in_file(path),
+                    None => e,
+                }))
+            }
+        }
+    }
+}
+
+impl KlvReader<BufReader<File>> {
+    /// Opens the file at `path` and returns an iterator over every KLV triplet
+    /// in it.
+    ///
+    /// Errors opening or reading the file carry the path.
+    pub fn from_path(path: impl AsRef<Path>) -> Result<Self, encoding::Error> {
+        let path = path.as_ref();
+        let file = File::open(path).map_err(|e| encoding::Error::from(e).in_file(path))?;
+        Ok(Self {
+            reader: BufReader::new(file),
+            path: Some(path.to_path_buf()),
+            done: false,
+        })
+    }
+}
+
+/// Where the bytes making up the value of a KLV triplet can be read from.
+#[derive(Debug)]
+enum ValueSource<T>
+where
+    T: Read + Seek,
+{
+    /// The value is read from the shared buffer on demand.
+    Buffer(Rc<RefCell<T>>),
+
+    /// The value lives in a copy of the payload of the set the triplet was
+    /// parsed from.
+    Payload {
+        /// Bytes making up the entire payload of the set.
+        data: Rc<[u8]>,
+
+        /// Offset in the buffer of the first byte of `data`.
+        offset: u64,
+    },
+}
+
+#[derive(Debug, getset::CopyGetters, getset::Getters)]
 pub struct Klv<T>
 where
     T: Read + Seek,
 {
-    /// Reference to the buffer that the KLV data is found in
-    buf: Rc<RefCell<T>>,
+    /// Where the value for this KLV triplet can be read from.
+    source: ValueSource<T>,
 
-    /// Number that identifies this KLV triplet in a LocalSet.
+    /// Identifies this KLV triplet in its set.
     #[getset(get_copy = "pub")]
-    tag: u128,
+    key: ItemKey,
 
     /// Number of bytes that make up the value for this KLV triplet.
     ///
@@ -34,9 +545,33 @@ where
     #[getset(get_copy = "pub")]
     length: u64,
 
+    /// Offset in the file of the first byte of the key for this KLV triplet.
+    #[getset(get_copy = "pub")]
+    offset: u64,
+
+    /// Offset in the file of the first byte of the length for this KLV
+    /// triplet.
+    #[getset(get_copy = "pub")]
+    length_offset: u64,
+
     /// Starting offset in the file for the first byte that makes up the value
     /// for this KLV triplet.
+    #[getset(get_copy = "pub")]
     value_offset: u64,
+
+    /// Where the triplet lies, for triplets read by `Klv::nested_items`.
+    #[getset(get = "pub")]
+    context: Option<ItemContext>,
+}
+
+/// Bytes of the key and length of a KLV triplet exactly as they appear in
+/// the buffer, along with where its value lies.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RawKlvParts {
+    pub tag_bytes: Vec<u8>,
+    pub length_bytes: Vec<u8>,
+    pub value_offset: u64,
+    pub value_len: u64,
 }
 
 impl<T> Klv<T>
@@ -56,26 +591,256 @@ where
     /// # Side Effects
     ///
     /// Moves the current position in the buffer to the byte after the last
-    /// byte of the value.
+    /// byte of the value. The position is left where it was when an error is
+    /// returned.
     pub fn new(buf: Rc<RefCell<T>>) -> Result<Self, encoding::Error> {
+        Self::new_with_encoding(buf, &ItemEncoding::default())
+    }
+
+    /// Reads in a new KLV triplet whose tag and length are encoded as
+    /// described by `encoding`.
+    ///
+    /// See `Klv::new`.
+    pub fn new_with_encoding(
+        buf: Rc<RefCell<T>>,
+        encoding: &ItemEncoding,
+    ) -> Result<Self, encoding::Error> {
         let mut buf_ref = buf.borrow_mut();
+        let offset = buf_ref.stream_position()?;
 
-        let tag = Self::read_tag(&mut *buf_ref)?;
-        let length = Self::read_length(&mut *buf_ref)?;
-        let starting_offset = buf_ref.stream_position().unwrap();
-        // Move the cursor position to the next byte after the value
-        buf_ref.seek_relative(length.try_into().unwrap()).unwrap();
+        let (key, length, length_offset, starting_offset) =
+            rewind_on_error(&mut *buf_ref, |buf| -> Result<_, encoding::Error> {
+                let key = match encoding.tag {
+                    TagEncoding::Ul => None,
+                    _ => Self::read_fill_key(buf)?,
+                };
+                let (key, key_width) = match key {
+                    Some(key) => (key, UNIVERSAL_KEY_LENGTH),
+                    None => encoding.read_key_counted(buf)?,
+                };
+                let (length, length_width) = encoding.read_length_counted(buf)?;
+                let length_offset = offset + key_width as u64;
+                let starting_offset = length_offset + length_width as u64;
+                // Move the cursor position to the next byte after the value
+                buf.seek(SeekFrom::Start(checked_end(starting_offset, length)?))?;
+                Ok((key, length, length_offset, starting_offset))
+            })?;
 
         drop(buf_ref);
 
+        trace_event!(debug, %key, length, offset, "Read item");
         Ok(Self {
-            buf,
-            tag,
+            source: ValueSource::Buffer(buf),
+            key,
             length,
+            offset,
+            length_offset,
             value_offset: starting_offset,
+            context: None,
         })
     }
 
+    /// Reads in a new KLV triplet from a copy of a set payload, using the
+    /// current position of `cursor` as the start of the Tag data.
+    ///
+    /// # Args
+    ///
+    /// - `payload` - Bytes making up the entire payload of the set.
+    /// - `payload_offset` - Offset in the original buffer of the first byte of
+    ///   `payload`.
+    /// - `cursor` - Cursor over `payload` positioned at the start of the tag.
+    /// - `encoding` - How the tag and length are encoded.
+    ///
+    /// # Returns
+    ///
+    /// - Ok(Klv) - When the tag number and length can successfully be read and
+    ///   the value fits inside of the payload.
+    /// - Err(encoding::Error) - When the tag/length couldn't be parsed or the
+    ///   value extends past the end of the payload.
+    ///
+    /// # Side Effects
+    ///
+    /// Moves the current position in the cursor to the byte after the last
+    /// byte of the value.
+    pub(crate) fn from_payload(
+        payload: &Rc<[u8]>,
+        payload_offset: u64,
+        cursor: &mut Cursor<&[u8]>,
+        encoding: &ItemEncoding,
+    ) -> Result<Self, encoding::Error> {
+        let offset = payload_offset + cursor.position();
+        let rest = &payload[cursor.position() as usize..];
+        let fill_key = rest
+            .get(..UNIVERSAL_KEY_LENGTH)
+            .map(|key| UniversalKey::new(key.try_into().unwrap()))
+            .filter(|key| encoding.tag != TagEncoding::Ul && key.is_fill());
+        let key = match fill_key {
+            Some(key) => {
+                cursor.set_position(cursor.position() + UNIVERSAL_KEY_LENGTH as u64);
+                ItemKey::Ul(key)
+            }
+            None => encoding.read_key(cursor)?,
+        };
+        let length_offset = payload_offset + cursor.position();
+        let length = encoding.read_length(cursor)?;
+        let value_start = cursor.position();
+        let value_end = value_start
+            .checked_add(length)
+            .filter(|end| *end <= payload.len() as u64)
+            .ok_or_else(|| io::Error::from(io::ErrorKind::UnexpectedEof))?;
+        cursor.set_position(value_end);
+
+        trace_event!(debug, %key, length, offset, "Read item");
+        Ok(Self {
+            source: ValueSource::Payload {
+                data: payload.clone(),
+                offset: payload_offset,
+            },
+            key,
+            length,
+            offset,
+            length_offset,
+            value_offset: payload_offset + value_start,
+            context: None,
+        })
+    }
+
+    /// Reads the items of the local set nested in the value of this triplet,
+    /// keyed by the tags of `G`, such as the ST 0102 Security Local Set
+    /// carried by ST 0601.
+    ///
+    /// # Args
+    ///
+    /// - `parent` - Where this triplet lies. Each item read is given a context
+    ///   extending it.
+    ///
+    /// # Returns
+    ///
+    /// - `Ok(Vec<Klv>)` - Every item in the value, with offsets in the
+    ///   original buffer.
+    /// - `Err(encoding::Error::InContext)` - An item could not be read or runs
+    ///   past the end of the value.
+    pub fn nested_items<G>(&self, parent: &ItemContext) -> Result<Vec<Self>, encoding::Error>
+    where
+        G: Tag,
+    {
+        self.nested_items_with_options::<G>(parent, &ParseOptions::default())
+    }
+
+    /// Reads the items of the local set nested in the value of this triplet
+    /// the same way as `Klv::nested_items`, refusing to go deeper than
+    /// `options.max_depth` sets.
+    ///
+    /// Every item read lies strictly inside the value of this triplet, so
+    /// reading the sets nested in them always works on fewer bytes.
+    ///
+    /// # Returns
+    ///
+    /// - `Ok(Vec<Klv>)` - Every item in the value, with offsets in the
+    ///   original buffer.
+    /// - `Err(encoding::Error::DepthExceeded)` - The items would be nested in
+    ///   more than `options.max_depth` sets. Nothing is read.
+    /// - `Err(encoding::Error::InContext)` - An item could not be read or runs
+    ///   past the end of the value.
+    pub fn nested_items_with_options<G>(
+        &self,
+        parent: &ItemContext,
+        options: &ParseOptions,
+    ) -> Result<Vec<Self>, encoding::Error>
+    where
+        G: Tag,
+    {
+        let depth = parent.depth() + 1;
+        if depth > options.max_depth {
+            return Err(encoding::Error::DepthExceeded {
+                depth,
+                offset: self.value_offset,
+            });
+        }
+
+        let payload: Rc<[u8]> = self
+            .read_value()
+            .map_err(|e| encoding::Error::from(e).in_context(parent.clone()))?
+            .into();
+        let mut cursor = Cursor::new(&payload[..]);
+        let mut items = Vec::new();
+        while cursor.position() < payload.len() as u64 {
+            let mut klv = Self::from_payload(
+                &payload,
+                self.value_offset,
+                &mut cursor,
+                &ItemEncoding::default(),
+            )
+            .map_err(|e| e.in_context(parent.clone()))?;
+            klv.context = Some(parent.nested::<G>(klv.key, items.len(), klv.value_offset));
+            items.push(klv);
+        }
+        Ok(items)
+    }
+
+    /// Whether this is a KLV fill item whose value is meaningless padding.
+    pub fn is_fill(&self) -> bool {
+        self.key.is_fill()
+    }
+
+    /// Number of bytes the key, length, and value of this KLV triplet take up
+    /// together.
+    pub fn total_length(&self) -> u64 {
+        self.value_offset + self.length - self.offset
+    }
+
+    /// Number of bytes the key of this KLV triplet is encoded in.
+    pub fn key_width(&self) -> u64 {
+        self.length_offset - self.offset
+    }
+
+    /// Number of bytes the length of this KLV triplet is encoded in.
+    pub fn length_width(&self) -> u64 {
+        self.value_offset - self.length_offset
+    }
+
+    /// Tag number of this KLV triplet, if it is keyed by one.
+    pub fn tag(&self) -> Option<u128> {
+        self.key.tag()
+    }
+
+    /// Checks if the next bytes in the buffer are the Universal Key of a fill
+    /// item.
+    ///
+    /// # Side Effects
+    ///
+    /// Moves the current position in the buffer past the key if it is a fill
+    /// key, otherwise leaves it where it was.
+    fn read_fill_key(buf: &mut T) -> Result<Option<ItemKey>, io::Error> {
+        // Every Universal Key starts with 0x06, so other tags are ruled out
+        // after reading a single byte.
+        let mut first = [0];
+        match buf.read_exact(&mut first) {
+            Err(err) if err.kind() == io::ErrorKind::UnexpectedEof => return Ok(None),
+            result => result?,
+        }
+        if first[0] != 0x06 {
+            buf.seek_relative(-1)?;
+            return Ok(None);
+        }
+
+        let start = buf.stream_position()? - 1;
+        let mut key = Vec::with_capacity(UNIVERSAL_KEY_LENGTH);
+        key.push(first[0]);
+        buf.by_ref()
+            .take(UNIVERSAL_KEY_LENGTH as u64 - 1)
+            .read_to_end(&mut key)?;
+
+        let fill_key = <[u8; UNIVERSAL_KEY_LENGTH]>::try_from(key)
+            .ok()
+            .map(UniversalKey::new)
+            .filter(UniversalKey::is_fill);
+        if fill_key.is_none() {
+            buf.seek(SeekFrom::Start(start))?;
+        }
+        Ok(fill_key.map(ItemKey::Ul))
+    }
+
     /// Reads the tag number from the current buffer location
     ///
     /// Tag numbers are always stored in BER-OID format according to the `ST
@@ -107,38 +872,631 @@ where
     ///
     /// # Returns
     ///
-    /// - Ok(u128) - When a valid u128 BER value can be read from the given
-    ///   buffer.
-    /// - Err(std::io::Error) - When a valid u128 BER value cannot be read from
-    ///   the given buffer.
+    /// - Ok(u64) - When a valid BER value that can be seeked over can be read
+    ///   from the given buffer.
+    /// - Err(std::io::Error) - When a valid BER value cannot be read from the
+    ///   given buffer, or is too large to seek over.
     ///
     /// # Side Effects
     ///
     /// Moves the current position in the buffer to the byte after the last BER
     /// byte.
-    ///
-    /// # Panics
-    ///
-    /// - The value parsed from the BER is long-form and won't fit in a u128.
     pub fn read_length(buf: &mut T) -> Result<u64, io::Error> {
-        read_ber(buf).map(|val| {
-            val.try_into().expect(
-                "Seek trait only supports 64 bit integers but Length requiring 128 bit integer was found",
-            )
+        seekable_length(read_ber(buf)?).map_err(invalid_data)
+    }
+
+    /// Copies the tag and value out of the buffer into an `OwnedKlv`, which
+    /// remembers how the key and length were encoded.
+    pub fn to_owned_klv(&self) -> Result<OwnedKlv, io::Error> {
+        let RawKlvParts {
+            tag_bytes,
+            length_bytes,
+            ..
+        } = self.split_raw()?;
+        Ok(OwnedKlv {
+            original_header: Some(RawHeader {
+                tag_bytes,
+                length_bytes,
+            }),
+            ..OwnedKlv::with_key(self.key, self.read_value()?)
         })
     }
 
     /// Returns a copy of the bytes making up the value.
     pub fn read_value(&self) -> Result<Vec<u8>, io::Error> {
-        let mut buf = self.buf.borrow_mut();
-        let current_position = buf.stream_position().unwrap();
-        buf.seek(SeekFrom::Start(self.value_offset)).unwrap();
+        self.read_bytes(self.value_offset, self.length)
+    }
+
+    /// Hashes the bytes making up the value without copying the whole value
+    /// out of the buffer.
+    ///
+    /// Values with the same bytes hash the same no matter where they were
+    /// read from. Hashes are only meant to be compared within one run of a
+    /// program, as the hashing algorithm may change between Rust releases.
+    pub fn value_hash(&self) -> Result<u64, io::Error> {
+        match &self.source {
+            ValueSource::Buffer(buf) => {
+                hash_range(&mut *buf.borrow_mut(), self.value_offset, self.length)
+            }
+            ValueSource::Payload { data, offset } => {
+                let start = (self.value_offset - offset) as usize;
+                let mut hasher = DefaultHasher::new();
+                hasher.write(&data[start..start + self.length as usize]);
+                Ok(hasher.finish())
+            }
+        }
+    }
+
+    /// Returns a copy of the bytes making up the key and length exactly as
+    /// they were encoded, including any non-minimal BER encodings.
+    pub fn split_raw(&self) -> Result<RawKlvParts, io::Error> {
+        Ok(RawKlvParts {
+            tag_bytes: self.read_bytes(self.offset, self.key_width())?,
+            length_bytes: self.read_bytes(self.length_offset, self.length_width())?,
+            value_offset: self.value_offset,
+            value_len: self.length,
+        })
+    }
+
+    /// Writes the key, length, and value to `writer` exactly as they were
+    /// encoded.
+    ///
+    /// Fails with `io::ErrorKind::UnexpectedEof` when the buffer ends before
+    /// the value does, after writing every byte that was left.
+    pub fn copy_raw_to<W>(&self, writer: &mut W) -> Result<(), io::Error>
+    where
+        W: Write,
+    {
+        let length = self.total_length();
+        let copied = match &self.source {
+            ValueSource::Buffer(buf) => at_position(&mut *buf.borrow_mut(), self.offset, |buf| {
+                copy_limited(buf, writer, length)
+            })?,
+            ValueSource::Payload { data, offset } => {
+                let start = (self.offset - offset) as usize;
+                copy_limited(&mut &data[start..], writer, length)?
+            }
+        };
+        if copied < length {
+            return Err(io::ErrorKind::UnexpectedEof.into());
+        }
+        Ok(())
+    }
+
+    /// Writes the key and value to `writer` as they were encoded, with the
+    /// length in `length_form` instead.
+    ///
+    /// # Returns
+    ///
+    /// - `Ok(u64)` - The number of bytes written.
+    /// - `Err(encoding::Error::ValueOutOfRange)` - The length does not fit in
+    ///   `length_form`. Nothing is written.
+    /// - `Err(encoding::Error::Truncated)` - The buffer ends before the value
+    ///   does, after writing every byte that was left.
+    /// - `Err(encoding::Error)` - Any other issue reading the buffer or
+    ///   writing to `writer`.
+    pub fn write_to<W>(&self, writer: &mut W, length_form: BerForm) -> Result<u64, encoding::Error>
+    where
+        W: Write,
+    {
+        let key = self.read_bytes(self.offset, self.key_width())?;
+        let length = encode_ber_with_form(self.length.into(), length_form)?;
+        writer.write_all(&key)?;
+        writer.write_all(&length)?;
+        let copied = match &self.source {
+            ValueSource::Buffer(buf) => {
+                at_position(&mut *buf.borrow_mut(), self.value_offset, |buf| {
+                    copy_limited(buf, writer, self.length)
+                })?
+            }
+            ValueSource::Payload { data, offset } => {
+                let start = (self.value_offset - offset) as usize;
+                copy_limited(&mut &data[start..], writer, self.length)?
+            }
+        };
+        if copied < self.length {
+            return Err(encoding::Error::Truncated("value"));
+        }
+        Ok((key.len() + length.len()) as u64 + copied)
+    }
+
+    /// Returns a copy of `length` bytes starting at `start` in the buffer.
+    ///
+    /// The position in the buffer is left where it was.
+    fn read_bytes(&self, start: u64, length: u64) -> Result<Vec<u8>, io::Error> {
+        match &self.source {
+            ValueSource::Buffer(buf) => at_position(&mut *buf.borrow_mut(), start, |buf| {
+                read_exactly(buf, length)
+            }),
+            ValueSource::Payload { data, offset } => {
+                let start = (start - offset) as usize;
+                read_exactly(&mut &data[start..], length)
+            }
+        }
+    }
+}
+
+/// Runs `f` with `buf` moved to `start`, then moves `buf` back to where it
+/// was whether or not `f` succeeded.
+fn at_position<T, R>(
+    buf: &mut T,
+    start: u64,
+    f: impl FnOnce(&mut T) -> Result<R, io::Error>,
+) -> Result<R, io::Error>
+where
+    T: Seek,
+{
+    let current_position = buf.stream_position()?;
+    buf.seek(SeekFrom::Start(start))?;
+    let result = f(buf);
+    buf.seek(SeekFrom::Start(current_position))?;
+    result
+}
+
+/// Hashes `length` bytes starting at `start` in `buf` a chunk at a time.
+///
+/// The position in the buffer is left where it was.
+pub(crate) fn hash_range<T>(buf: &mut T, start: u64, length: u64) -> Result<u64, io::Error>
+where
+    T: Read + Seek,
+{
+    at_position(buf, start, |buf| {
+        let mut hasher = DefaultHasher::new();
+        let mut chunk = [0; DEFAULT_CHUNK];
+        let mut remaining = length;
+        while remaining > 0 {
+            let size = remaining.min(chunk.len() as u64) as usize;
+            buf.read_exact(&mut chunk[..size])?;
+            hasher.write(&chunk[..size]);
+            remaining -= size as u64;
+        }
+        Ok(hasher.finish())
+    })
+}
+
+// The forward-only readers never touch a `Klv`'s buffer so they live on a
+// concrete instantiation, which lets callers write `Klv::read_owned(..)`
+// without naming a buffer type (the same trick as `HashMap::new`).
+impl Klv<io::Empty> {
+    /// Reads in a new KLV triplet from a reader that does not support seeking.
+    ///
+    /// See `OwnedKlv::read`.
+    pub fn read_owned<R>(reader: &mut R) -> Result<OwnedKlv, encoding::Error>
+    where
+        R: Read,
+    {
+        OwnedKlv::read(reader)
+    }
+
+    /// Reads in a new KLV triplet from an async reader.
+    ///
+    /// See `OwnedKlv::read_async`.
+    #[cfg(feature = "tokio")]
+    pub async fn read_owned_async<R>(reader: &mut R) -> Result<OwnedKlv, encoding::Error>
+    where
+        R: tokio::io::AsyncRead + Unpin,
+    {
+        OwnedKlv::read_async(reader).await
+    }
+
+    /// Returns an iterator over every KLV triplet in a reader that does not
+    /// support seeking.
+    ///
+    /// See `KlvReader`.
+    pub fn read_stream<R>(reader: R) -> KlvReader<R>
+    where
+        R: Read,
+    {
+        KlvReader::new(reader)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use itertools::Itertools;
+    use test_case::test_case;
+
+    use crate::keys;
 
-        let mut temp_buf = vec![0; self.length as usize];
-        buf.read_exact(&mut temp_buf)?;
+    /// Reader that panics if anything tries to seek it.
+    struct NoSeek<'a>(&'a [u8]);
 
-        buf.seek(SeekFrom::Start(current_position)).unwrap();
+    impl Read for NoSeek<'_> {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            self.0.read(buf)
+        }
+    }
+
+    impl Seek for NoSeek<'_> {
+        fn seek(&mut self, _: SeekFrom) -> io::Result<u64> {
+            panic!("Forward-only reader attempted to seek");
+        }
+    }
+
+    #[test_case(0; "In place")]
+    #[test_case(4096; "Batched")]
+    fn raw_parts_keep_original_encoding(batch_threshold: u64) {
+        use crate::{local_set::LocalSet, options::ParseOptions};
+
+        // Tag 129 followed by a length of 2 in a non-minimal long form.
+        let item = [0x81, 0x01, 0x82, 0x00, 0x02, 0xAA, 0xBB];
+        let buf = [
+            [0x00; UNIVERSAL_KEY_LENGTH].as_slice(),
+            &[item.len() as u8],
+            &item,
+        ]
+        .concat();
+        let options = ParseOptions {
+            batch_threshold,
+            ..ParseOptions::default()
+        };
+        let set = LocalSet::read_with_options(0, Rc::new(RefCell::new(Cursor::new(buf))), &options)
+            .unwrap();
+        let klv = &set[&ItemKey::Tag(129)];
+
+        assert_eq!((klv.key_width(), klv.length_width()), (2, 3));
+        assert_eq!(
+            klv.split_raw().unwrap(),
+            RawKlvParts {
+                tag_bytes: vec![0x81, 0x01],
+                length_bytes: vec![0x82, 0x00, 0x02],
+                value_offset: 22,
+                value_len: 2,
+            }
+        );
+        let mut copied = Vec::new();
+        klv.copy_raw_to(&mut copied).unwrap();
+        assert_eq!(copied, item);
+    }
+
+    #[test]
+    fn rewrite_value_with_forced_length_form() {
+        use crate::local_set::LocalSet;
+
+        let buf = [
+            [0x00; UNIVERSAL_KEY_LENGTH].as_slice(),
+            &[0x0D],
+            // Tag 2 with a length of 3 padded to a four byte long form.
+            &[0x02, 0x84, 0x00, 0x00, 0x00, 0x03, 0xAA, 0xBB, 0xCC],
+            &[0x03, 0x02, 0xDD, 0xEE],
+        ]
+        .concat();
+        let read = |buf: Vec<u8>| {
+            let buf = Rc::new(RefCell::new(Cursor::new(buf)));
+            LocalSet::read(0, buf).unwrap()
+        };
+        let set = read(buf.clone());
+        let klv = &set[&ItemKey::Tag(2)];
+
+        let mut minimal = Vec::new();
+        klv.write_to(&mut minimal, BerForm::Minimal).unwrap();
+        assert_eq!(minimal, [0x02, 0x03, 0xAA, 0xBB, 0xCC]);
+
+        let mut patched = buf.clone();
+        let mut item = Vec::new();
+        let written = OwnedKlv::new(2, vec![0x11, 0x22, 0x33])
+            .write_to(&mut item, BerForm::LongForm(4))
+            .unwrap();
+        assert_eq!(written, klv.total_length());
+        let start = klv.offset() as usize;
+        patched.splice(start..start + item.len(), item);
+
+        let patched_set = read(patched.clone());
+        assert_eq!(patched.len(), buf.len());
+        assert_eq!(
+            patched_set[&ItemKey::Tag(2)].read_value().unwrap(),
+            [0x11, 0x22, 0x33]
+        );
+        assert_eq!(
+            patched_set[&ItemKey::Tag(3)].offset(),
+            set[&ItemKey::Tag(3)].offset()
+        );
+        assert!(
+            OwnedKlv::new(2, vec![0; 0x0100])
+                .write_to(&mut Vec::new(), BerForm::LongForm(1))
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn nested_items_carry_their_context() {
+        use crate::{
+            keys,
+            local_set::OwnedLocalSet,
+            misb::{st0102::St0102Tag, st0601::St0601Tag},
+            universal_set::UniversalSet,
+        };
+
+        let security = [[0x01, 0x01, 0x01], [0x02, 0x01, 0x01]].concat();
+        let security = [security.as_slice(), &[0x03, 0x04], b"//US"].concat();
+        let packet = OwnedLocalSet::new(vec![
+            OwnedKlv::new(3, b"MISSION".to_vec()),
+            OwnedKlv::new(48, security),
+        ])
+        .serialize(&keys::ST0601, true);
+        let buf = Rc::new(RefCell::new(Cursor::new(packet)));
+        let set = UniversalSet::new(keys::ST0601, buf, 0).unwrap();
+        let (index, parent) = set
+            .data()
+            .iter()
+            .find_position(|klv| klv.tag() == Some(48))
+            .unwrap();
+        let context = ItemContext::new::<St0601Tag>(parent.key(), index, parent.value_offset());
+
+        let items = parent.nested_items::<St0102Tag>(&context).unwrap();
+        let country = items[2].context().as_ref().unwrap();
+        // Past the first two items and the tag and length of the third.
+        let offset = parent.value_offset() + 3 + 3 + 2;
+        assert_eq!(country.offset(), offset);
+        assert_eq!(
+            country.to_string(),
+            format!("ST0601[48]/ST0102[3] @ {offset:#X}")
+        );
+        assert_eq!(country.index(), 2);
+        assert_eq!(items[2].read_value().unwrap(), b"//US");
+
+        // The nested item claims four bytes but only one is left.
+        let buf = Rc::new(RefCell::new(Cursor::new(vec![
+            0x30, 0x03, 0x03, 0x04, b'U',
+        ])));
+        let parent = Klv::new(buf).unwrap();
+        let context = ItemContext::new::<St0601Tag>(parent.key(), 0, parent.value_offset());
+        let error = parent.nested_items::<St0102Tag>(&context).unwrap_err();
+        assert!(matches!(&error, encoding::Error::InContext { context: c, .. } if *c == context));
+        assert!(error.to_string().starts_with("ST0601[48] @ 0x2: "));
+    }
+
+    #[test]
+    fn deeply_nested_sets_exceed_max_depth() {
+        use crate::{misb::st0601::St0601Tag, options::DEFAULT_MAX_DEPTH};
+
+        // Tag 1 nested inside itself 40 times.
+        let buf = (0..40).fold(vec![0x01, 0x00], |inner, _| {
+            [[0x01, inner.len() as u8].as_slice(), &inner].concat()
+        });
+        let buf = Rc::new(RefCell::new(Cursor::new(buf)));
+        let outer = Klv::new(buf).unwrap();
+
+        fn walk(klv: &Klv<Cursor<Vec<u8>>>, context: &ItemContext) -> Result<(), encoding::Error> {
+            for item in klv.nested_items::<St0601Tag>(context)? {
+                walk(&item, item.context().as_ref().unwrap())?;
+            }
+            Ok(())
+        }
+        let context = ItemContext::new::<St0601Tag>(outer.key(), 0, outer.value_offset());
+        match walk(&outer, &context) {
+            Err(encoding::Error::DepthExceeded { depth, offset }) => {
+                assert_eq!(depth, DEFAULT_MAX_DEPTH + 1);
+                assert_eq!(offset, 2 * DEFAULT_MAX_DEPTH as u64);
+            }
+            other => panic!("Expected the depth to be exceeded, got {other:?}"),
+        }
+
+        let options = ParseOptions {
+            max_depth: 1,
+            ..ParseOptions::default()
+        };
+        assert!(matches!(
+            outer.nested_items_with_options::<St0601Tag>(&context, &options),
+            Err(encoding::Error::DepthExceeded {
+                depth: 2,
+                offset: 2
+            })
+        ));
+    }
+
+    #[test]
+    fn value_hash_does_not_depend_on_source() {
+        use crate::{local_set::LocalSet, options::ParseOptions};
+
+        // Longer than one chunk of `hash_range`.
+        let value = (0..10_000u32).map(|byte| byte as u8).collect::<Vec<_>>();
+        let item = [[0x01, 0x82, 0x27, 0x10].as_slice(), &value].concat();
+        let buf = [
+            [0x00; UNIVERSAL_KEY_LENGTH].as_slice(),
+            &[0x82, 0x27, 0x14],
+            &item,
+        ]
+        .concat();
+        let hashes = [0, u64::MAX].map(|batch_threshold| {
+            let options = ParseOptions {
+                batch_threshold,
+                ..ParseOptions::default()
+            };
+            let buf = Rc::new(RefCell::new(Cursor::new(buf.clone())));
+            let set = LocalSet::read_with_options(0, buf.clone(), &options).unwrap();
+            let position = buf.borrow_mut().stream_position().unwrap();
+            let hash = set[&ItemKey::Tag(1)].value_hash().unwrap();
+            assert_eq!(buf.borrow_mut().stream_position().unwrap(), position);
+            hash
+        });
+
+        assert_eq!(hashes[0], hashes[1]);
+        let mut hasher = DefaultHasher::new();
+        hasher.write(&value);
+        assert_eq!(hashes[0], hasher.finish());
+        let mut changed = Cursor::new([&value[..9_999], &[0x00]].concat());
+        assert_ne!(hash_range(&mut changed, 0, 10_000).unwrap(), hashes[0]);
+    }
+
+    #[test]
+    fn failed_read_leaves_position() {
+        // Tag 5 with a two byte long-form length that is cut off.
+        let buf = Rc::new(RefCell::new(Cursor::new(vec![0xFF, 0x05, 0x82, 0x00])));
+        buf.borrow_mut().set_position(1);
+        assert!(Klv::new(buf.clone()).is_err());
+        assert_eq!(buf.borrow().position(), 1);
+
+        // Tag 5 whose value would end past `i64::MAX`.
+        let huge = [[0xFF, 0x05, 0x88, 0x7F].as_slice(), &[0xFF; 7]].concat();
+        *buf.borrow_mut().get_mut() = huge;
+        assert!(matches!(
+            Klv::new(buf.clone()),
+            Err(encoding::Error::ValueOutOfRange { .. })
+        ));
+        assert_eq!(buf.borrow().position(), 1);
+
+        buf.borrow_mut().get_mut().splice(1.., [0x05, 0x01, 0xAA]);
+        let klv = Klv::new(buf.clone()).unwrap();
+        assert_eq!(klv.offset(), 1);
+        assert_eq!(klv.read_value().unwrap(), [0xAA]);
+    }
+
+    #[test]
+    fn tag_6_is_not_mistaken_for_fill() {
+        // Starts like a Universal Key but is too short to be one.
+        let buf = Rc::new(RefCell::new(Cursor::new(vec![0x06, 0x02, 0xAA, 0xBB])));
+        let klv = Klv::new(buf.clone()).unwrap();
+        assert_eq!(klv.key(), ItemKey::Tag(6));
+        assert_eq!(klv.read_value().unwrap(), [0xAA, 0xBB]);
+        assert_eq!(buf.borrow().position(), 4);
+    }
+
+    #[test]
+    fn read_owned_never_seeks() {
+        let mut reader = NoSeek(&[0x81, 0x01, 0x02, 0xAA, 0xBB, 0x05]);
+        assert_eq!(
+            Klv::read_owned(&mut reader).unwrap(),
+            OwnedKlv::new(129, vec![0xAA, 0xBB])
+        );
+        assert_eq!(reader.0, &[0x05]);
+    }
+
+    #[test]
+    fn read_stream_yields_every_triplet() {
+        let klvs = Klv::read_stream(NoSeek(&[
+            0x01, 0x01, 0xAA, 0x02, 0x00, 0x03, 0x02, 0xBB, 0xCC,
+        ]))
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+        assert_eq!(
+            klvs,
+            vec![
+                OwnedKlv::new(1, vec![0xAA]),
+                OwnedKlv::new(2, vec![]),
+                OwnedKlv::new(3, vec![0xBB, 0xCC]),
+            ]
+        );
+    }
+
+    #[test_case(&[], None; "Empty stream")]
+    #[test_case(&[0x81], Some("tag"); "Ends in tag")]
+    #[test_case(&[0x01], Some("length"); "Ends before length")]
+    #[test_case(&[0x01, 0x82, 0x01], Some("length"); "Ends in long-form length")]
+    #[test_case(&[0x01, 0x03, 0xAA], Some("value"); "Ends in value")]
+    fn read_stream_reports_truncation(input: &[u8], expected: Option<&str>) {
+        let mut stream = Klv::read_stream(NoSeek(input));
+        let truncated = stream.next().map(|res| match res.unwrap_err() {
+            encoding::Error::Truncated(element) => element,
+            other => panic!("Unexpected error {other}"),
+        });
+        assert_eq!(truncated, expected);
+        assert!(stream.next().is_none());
+    }
+
+    #[test_case(KeyLength::One, &[0x05], ItemKey::Tag(0x05); "One byte")]
+    #[test_case(KeyLength::Two, &[0x01, 0x02], ItemKey::Tag(0x0102); "Two bytes")]
+    #[test_case(KeyLength::Four, &[0x12, 0x34, 0x56, 0x78], ItemKey::Tag(0x1234_5678); "Four bytes")]
+    #[test_case(KeyLength::Sixteen, &*keys::ST0601, ItemKey::Ul(keys::ST0601); "Sixteen bytes")]
+    #[test_case(KeyLength::BerOid, &[0x81, 0x01], ItemKey::Tag(129); "BER-OID")]
+    fn read_triplet_with_key_length(key_length: KeyLength, key: &[u8], expected: ItemKey) {
+        let bytes = [key, &[0x02, 0xAA, 0xBB, 0x05]].concat();
+        let mut reader = NoSeek(&bytes);
+        let klv = read_triplet(&mut reader, key_length, LengthEncoding::Ber).unwrap();
+        assert_eq!(klv, OwnedKlv::with_key(expected, vec![0xAA, 0xBB]));
+        assert_eq!(reader.0, &[0x05]);
+
+        let bytes = [key, &[0x00, 0x02, 0xAA, 0xBB]].concat();
+        let klv = read_triplet(&mut NoSeek(&bytes), key_length, LengthEncoding::Fixed(2)).unwrap();
+        assert_eq!(klv.key(), expected);
+        assert_eq!(klv.length(), 2);
+    }
+
+    #[test]
+    fn read_triplet_reports_truncated_key() {
+        assert!(matches!(
+            read_triplet(
+                &mut NoSeek(&[0x12, 0x34]),
+                KeyLength::Four,
+                LengthEncoding::Ber
+            ),
+            Err(encoding::Error::Truncated("tag"))
+        ));
+    }
+
+    #[test]
+    fn read_owned_reports_end_of_stream() {
+        assert!(matches!(
+            Klv::read_owned(&mut NoSeek(&[])),
+            Err(encoding::Error::EndOfStream)
+        ));
+    }
+
+    #[test_case(&[0x01, 0x80]; "Indefinite length")]
+    #[test_case(&[0x01, 0x88, 0x80, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00]; "Length of 2^63")]
+    #[test_case(&[0x01, 0x91, 0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00]; "Length past u128")]
+    fn read_owned_reports_bad_lengths(input: &[u8]) {
+        assert!(matches!(
+            Klv::read_owned(&mut NoSeek(input)),
+            Err(encoding::Error::Malformed { .. }
+                | encoding::Error::ValueOutOfRange { .. }
+                | encoding::Error::TooLarge { .. })
+        ));
+    }
+
+    #[cfg(feature = "tokio")]
+    #[tokio::test]
+    async fn read_owned_async_reports_huge_length() {
+        let input = [0x01, 0x88, 0x80, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00];
+        assert!(matches!(
+            Klv::read_owned_async(&mut &input[..]).await,
+            Err(encoding::Error::ValueOutOfRange { .. })
+        ));
+    }
+
+    #[test]
+    fn key_style_follows_encoding() {
+        // The same bytes are a tag of 6 with a 14 byte value under BER-OID
+        // tags, or a 16 byte UL with a 2 byte value.
+        let bytes = [
+            0x06, 0x0E, 0x2B, 0x34, 0x02, 0x0B, 0x01, 0x01, 0x0E, 0x01, 0x03, 0x01, 0x01, 0x00,
+            0x00, 0x00, 0x02, 0xAA, 0xBB,
+        ];
+
+        let buf = Rc::new(RefCell::new(Cursor::new(bytes)));
+        let klv = Klv::new(buf).unwrap();
+        assert_eq!(klv.key(), ItemKey::Tag(6));
+        assert_eq!(klv.tag(), Some(6));
+        assert_eq!(klv.length(), 14);
+
+        let buf = Rc::new(RefCell::new(Cursor::new(bytes)));
+        let encoding = ItemEncoding {
+            tag: TagEncoding::Ul,
+            ..Default::default()
+        };
+        let klv = Klv::new_with_encoding(buf, &encoding).unwrap();
+        assert_eq!(
+            klv.key(),
+            ItemKey::Ul(UniversalKey::new(bytes[..16].try_into().unwrap()))
+        );
+        assert_eq!(klv.tag(), None);
+        assert_eq!(klv.read_value().unwrap(), vec![0xAA, 0xBB]);
+        assert_eq!(klv.key().to_string(), "060e2b34.020b0101.0e010301.01000000");
+    }
 
-        Ok(temp_buf)
+    #[cfg(feature = "serde")]
+    #[test]
+    fn item_key_serde() {
+        let items = vec![
+            OwnedKlv::new(2, vec![0xAA]),
+            OwnedKlv::with_key(ItemKey::Ul(UniversalKey::new([0x11; 16])), vec![]),
+        ];
+        let json = serde_json::to_string(&items).unwrap();
+        assert_eq!(
+            json,
+            r#"[{"key":2,"value":[170]},{"key":"11111111.11111111.11111111.11111111","value":[]}]"#
+        );
+        assert_eq!(serde_json::from_str::<Vec<OwnedKlv>>(&json).unwrap(), items);
     }
 }
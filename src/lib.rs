@@ -1,4 +1,89 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+
+/// Emits a `tracing` event at `$level` when the `tracing` feature is enabled.
+/// Compiles to nothing otherwise.
+#[allow(unused_macros)]
+macro_rules! trace_event {
+    ($level:ident, $($arg:tt)*) => {
+        #[cfg(feature = "tracing")]
+        tracing::$level!($($arg)*);
+    };
+}
+
+/// Emits a `tracing` warning carrying `$offset` when `$result` is an error
+/// and the `tracing` feature is enabled, then evaluates to `$result`.
+#[allow(unused_macros)]
+macro_rules! warn_on_err {
+    ($result:expr, $offset:expr) => {{
+        let result = $result;
+        #[cfg(feature = "tracing")]
+        if let Err(error) = &result {
+            tracing::warn!(offset = $offset, %error, "Parse failed");
+        }
+        result
+    }};
+}
+
+#[cfg(feature = "std")]
+pub mod analysis;
+#[cfg(feature = "std")]
+pub mod builder;
+pub mod crc;
+#[cfg(feature = "std")]
+pub mod demux;
+#[cfg(feature = "std")]
+pub mod diff;
+#[cfg(feature = "std")]
+pub mod display;
 pub mod encoding;
+#[cfg(feature = "std")]
+pub mod export;
+#[cfg(feature = "std")]
+pub mod follow;
+#[cfg(feature = "std")]
+pub mod format;
+#[cfg(feature = "std")]
+pub mod group;
+#[cfg(feature = "std")]
+pub mod index;
+#[cfg(feature = "std")]
+pub mod keys;
+#[cfg(feature = "std")]
 pub mod klv;
+#[cfg(feature = "std")]
 pub mod local_set;
+#[cfg(feature = "std")]
+pub mod misb;
+pub mod options;
+#[cfg(feature = "threads")]
+pub mod pipeline;
+#[cfg(feature = "std")]
+pub mod prelude;
+#[cfg(feature = "std")]
+pub mod progress;
+#[cfg(feature = "uom")]
+pub mod quantity;
+#[cfg(feature = "registry")]
+pub mod registry;
+#[cfg(feature = "std")]
+pub mod repair;
+#[cfg(feature = "std")]
+pub mod scan;
+#[cfg(feature = "std")]
+pub mod stream_parser;
+#[cfg(feature = "std")]
+pub mod stream_writer;
+#[cfg(feature = "std")]
+pub mod tag;
+#[cfg(feature = "std")]
+pub mod time_index;
+#[cfg(feature = "ts")]
+pub mod ts;
+#[cfg(feature = "std")]
+pub mod typed;
+#[cfg(feature = "std")]
 pub mod universal_set;
+#[cfg(feature = "std")]
+pub mod validation;
@@ -1,39 +1,74 @@
 use std::{
     cell::RefCell,
     collections::BTreeMap,
-    io::{Read, Seek, SeekFrom},
-    ops::Deref,
+    fmt,
+    io::{self, Cursor, Read, Seek, SeekFrom},
+    ops::{Index, Range},
     rc::Rc,
 };
 
+use itertools::Itertools;
+
 use crate::{
-    encoding::{self, ber::read_ber},
-    klv::Klv,
-    universal_set::UNIVERSAL_KEY_LENGTH,
+    diff::{DiffEntry, DiffKind, SetDiff},
+    encoding::{
+        self,
+        ber::{encode_ber, encode_ber_padded, read_ber},
+        ber_oid::encode_ber_oid,
+        checked_end, seekable_length,
+    },
+    format::SoftwareValue,
+    group::{ItemEncoding, LengthEncoding, TagEncoding},
+    klv::{ItemContext, ItemKey, Klv, OwnedKlv},
+    misb::st0601::{self, St0601Tag},
+    options::{ChecksumMode, DuplicatePolicy, LengthForm, Limit, ParseOptions, SerializeOptions},
+    tag::{Tag, TagReading, UnknownTag},
+    universal_set::{UNIVERSAL_KEY_LENGTH, UniversalKey},
+    validation::{Severity, ValidationReport, Violation, ViolationKind},
 };
 
-// This is just used to make the indexing into the BTreeMap more understandable
-type TagNumber = u128;
-
 /// Set of data that must be found in reference to Universal Key
 #[derive(Debug, getset::Getters)]
-#[getset(get = "pub")]
 pub struct LocalSet<T>
 where
     T: Read + Seek,
 {
-    /// Locations in the file for each tag that can be parsed.
-    data: BTreeMap<TagNumber, Klv<T>>,
+    /// Every item kept from the set, in the order they appear in the buffer.
+    #[getset(get = "pub")]
+    items: Vec<Klv<T>>,
+    /// Positions in `items` of every occurrence of each key.
+    index: BTreeMap<ItemKey, Vec<usize>>,
+    /// Problems found while parsing that did not stop the parse.
+    #[getset(get = "pub")]
+    warnings: Vec<Violation>,
 }
 
-impl<T> Deref for LocalSet<T>
+impl<T> Index<&ItemKey> for LocalSet<T>
 where
     T: Read + Seek,
 {
-    type Target = BTreeMap<TagNumber, Klv<T>>;
+    type Output = Klv<T>;
+
+    /// # Panics
+    ///
+    /// - `key` is not in the set.
+    fn index(&self, key: &ItemKey) -> &Self::Output {
+        self.get(key).expect("Key not found in local set")
+    }
+}
 
-    fn deref(&self) -> &Self::Target {
-        &self.data
+impl<T> fmt::Display for LocalSet<T>
+where
+    T: Read + Seek,
+{
+    /// Formats the set as `<items> items, <warnings> warnings`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} items, {} warnings",
+            self.items.len(),
+            self.warnings.len()
+        )
     }
 }
 
@@ -42,8 +77,62 @@ where
     T: Read + Seek,
 {
     pub fn read(universal_key_pos: u64, buf: Rc<RefCell<T>>) -> Result<Self, encoding::Error> {
-        // Stores all of the tags
-        let mut bmap = BTreeMap::new();
+        Self::read_with_options(universal_key_pos, buf, &ParseOptions::default())
+    }
+
+    /// Reads the local set whose Universal Key starts at `universal_key_pos`
+    /// and checks the length of every item against the tag table `G`.
+    ///
+    /// An item whose length breaks a `ValueLength::Required` or
+    /// `ValueLength::Max` constraint fails the parse when
+    /// `options.strict_lengths` is set. Otherwise the item is kept, so it can
+    /// still be decoded from whatever bytes it has, and a warning is added to
+    /// `LocalSet::warnings`.
+    pub fn read_as<G>(
+        universal_key_pos: u64,
+        buf: Rc<RefCell<T>>,
+        options: &ParseOptions,
+    ) -> Result<Self, encoding::Error>
+    where
+        G: Tag,
+    {
+        let mut set = Self::read_with_options(universal_key_pos, buf, options)?;
+        set.check_lengths::<G>(options.strict_lengths)?;
+        Ok(set)
+    }
+
+    /// Reads the local set whose Universal Key starts at `universal_key_pos`.
+    ///
+    /// Payloads no longer than `options.batch_threshold` are copied out of the
+    /// buffer in a single read and parsed from memory. Longer payloads are
+    /// parsed in place.
+    ///
+    /// Tags and lengths written in more bytes than they need and fill items
+    /// dropped because of `options.skip_fill` are recorded in
+    /// `LocalSet::warnings`. Keeping more than `options.max_items_per_set`
+    /// items fails the parse with `encoding::Error::LimitExceeded`.
+    pub fn read_with_options(
+        universal_key_pos: u64,
+        buf: Rc<RefCell<T>>,
+        options: &ParseOptions,
+    ) -> Result<Self, encoding::Error> {
+        Self::read_with_encoding(universal_key_pos, buf, &ItemEncoding::default(), options)
+    }
+
+    /// Reads the set whose Universal Key starts at `universal_key_pos` and
+    /// whose items have their tags and lengths encoded as described by
+    /// `encoding`.
+    ///
+    /// See `LocalSet::read_with_options`.
+    pub fn read_with_encoding(
+        universal_key_pos: u64,
+        buf: Rc<RefCell<T>>,
+        encoding: &ItemEncoding,
+        options: &ParseOptions,
+    ) -> Result<Self, encoding::Error> {
+        // Stores all of the items in the order they are found
+        let mut items = Vec::new();
+        let mut warnings = Vec::new();
 
         // Location of the first byte that denotes how long the value for the
         // KLV triplet is.
@@ -52,26 +141,1631 @@ where
         let mut buf_ref = buf.borrow_mut();
 
         // Move the file pointer to the start of the length
-        buf_ref
-            .seek(SeekFrom::Start(length_pos))
-            .expect("Failed to seek to the start of local set value portion");
+        buf_ref.seek(SeekFrom::Start(length_pos))?;
 
         // Length of the value portion of this KLV triplet.
-        let value_length: u64 = read_ber(&mut *buf_ref)?
-            .try_into()
-            .expect("Seek trait only supports u64 values");
+        let value_length = seekable_length(read_ber(&mut *buf_ref)?)?;
 
         // The value always starts immediately after the length
-        let value_start_pos = buf_ref.stream_position().unwrap();
-        let final_value_position = value_start_pos + value_length;
+        let value_start_pos = buf_ref.stream_position()?;
+        let final_value_position = checked_end(value_start_pos, value_length)?;
+
+        if value_length <= options.batch_threshold {
+            let mut payload = vec![0; value_length as usize];
+            buf_ref.read_exact(&mut payload)?;
+            drop(buf_ref);
+
+            let payload: Rc<[u8]> = payload.into();
+            let mut cursor = Cursor::new(&payload[..]);
+            while cursor.position() < value_length {
+                let klv = warn_on_err!(
+                    Klv::from_payload(&payload, value_start_pos, &mut cursor, encoding),
+                    value_start_pos + cursor.position()
+                )?;
+                warnings.extend(item_warnings(&klv, encoding, options));
+                if keeps(options, &klv) {
+                    check_item_limit(options, items.len())?;
+                    items.push(klv);
+                }
+            }
+
+            let mut set = Self::from_items(items, options.duplicates)?;
+            set.warnings = warnings;
+            return Ok(set);
+        }
 
         drop(buf_ref);
 
-        while buf.borrow_mut().stream_position().unwrap() != final_value_position + 1 {
-            let klv = Klv::new(buf.clone())?;
-            bmap.insert(klv.tag(), klv);
+        loop {
+            let offset = buf.borrow_mut().stream_position()?;
+            if offset >= final_value_position {
+                break;
+            }
+            // An item running past the set fails the same way as when it
+            // runs past the end of a batched payload.
+            let klv = Klv::new_with_encoding(buf.clone(), encoding).and_then(|klv| {
+                match checked_end(klv.value_offset(), klv.length())? {
+                    end if end <= final_value_position => Ok(klv),
+                    _ => Err(io::Error::from(io::ErrorKind::UnexpectedEof).into()),
+                }
+            });
+            let klv = warn_on_err!(klv, offset)?;
+            warnings.extend(item_warnings(&klv, encoding, options));
+            if keeps(options, &klv) {
+                check_item_limit(options, items.len())?;
+                items.push(klv);
+            }
+        }
+
+        let mut set = Self::from_items(items, options.duplicates)?;
+        set.warnings = warnings;
+        Ok(set)
+    }
+
+    /// Parses the items of the set whose Universal Key starts at
+    /// `universal_key_pos` one at a time, without building the set.
+    ///
+    /// Items are read in place as the iterator is advanced, so stopping early
+    /// leaves the rest of the set unread. Every item is yielded, including
+    /// fill items and repeated keys.
+    ///
+    /// # Returns
+    ///
+    /// - `Ok(SetItems)` - The length of the set was read.
+    /// - `Err(encoding::Error)` - The length of the set could not be read.
+    pub fn stream_items(
+        universal_key_pos: u64,
+        buf: Rc<RefCell<T>>,
+        encoding: &ItemEncoding,
+    ) -> Result<SetItems<T>, encoding::Error> {
+        let (start, end) = {
+            let mut buf_ref = buf.borrow_mut();
+            buf_ref.seek(SeekFrom::Start(
+                universal_key_pos + UNIVERSAL_KEY_LENGTH as u64,
+            ))?;
+            let value_length = seekable_length(read_ber(&mut *buf_ref)?)?;
+            let start = buf_ref.stream_position()?;
+            (start, checked_end(start, value_length)?)
+        };
+        Ok(Self::items_in(buf, start..end, encoding))
+    }
+
+    /// Parses the items lying in `range` of the buffer one at a time.
+    ///
+    /// See `LocalSet::stream_items`.
+    pub fn items_in(
+        buf: Rc<RefCell<T>>,
+        range: Range<u64>,
+        encoding: &ItemEncoding,
+    ) -> SetItems<T> {
+        SetItems {
+            buf,
+            position: range.start,
+            end: range.end,
+            encoding: *encoding,
+        }
+    }
+
+    /// Builds the set out of every item read from it, resolving repeated keys
+    /// according to `policy`.
+    fn from_items(items: Vec<Klv<T>>, policy: DuplicatePolicy) -> Result<Self, encoding::Error> {
+        let mut occurrences: BTreeMap<ItemKey, Vec<usize>> = BTreeMap::new();
+        for (i, klv) in items.iter().enumerate() {
+            let seen = occurrences.entry(klv.key()).or_default();
+            if policy == DuplicatePolicy::Error
+                && let Some(&first) = seen.first()
+            {
+                return Err(encoding::Error::DuplicateItem {
+                    key: klv.key(),
+                    first_offset: items[first].value_offset(),
+                    second_offset: klv.value_offset(),
+                });
+            }
+            seen.push(i);
+        }
+
+        let keep = |i: usize, klv: &Klv<T>| {
+            let seen = &occurrences[&klv.key()];
+            match policy {
+                DuplicatePolicy::KeepFirst => seen.first() == Some(&i),
+                DuplicatePolicy::KeepLast => seen.last() == Some(&i),
+                DuplicatePolicy::KeepAll | DuplicatePolicy::Error => true,
+            }
+        };
+        let items = items
+            .into_iter()
+            .enumerate()
+            .filter(|(i, klv)| keep(*i, klv))
+            .map(|(_, klv)| klv)
+            .collect_vec();
+
+        let mut index: BTreeMap<ItemKey, Vec<usize>> = BTreeMap::new();
+        for (i, klv) in items.iter().enumerate() {
+            index.entry(klv.key()).or_default().push(i);
+        }
+
+        Ok(Self {
+            items,
+            index,
+            warnings: Vec::new(),
+        })
+    }
+
+    /// Records problems with the set that did not stop the parse ahead of
+    /// the ones found in its items.
+    pub(crate) fn push_warnings_front(&mut self, warnings: impl IntoIterator<Item = Violation>) {
+        self.warnings.splice(0..0, warnings);
+    }
+
+    /// Drops every item with `key`.
+    pub(crate) fn remove_all(&mut self, key: &ItemKey) {
+        if self.index.remove(key).is_none() {
+            return;
+        }
+        self.items.retain(|klv| klv.key() != *key);
+        self.index.clear();
+        for (i, klv) in self.items.iter().enumerate() {
+            self.index.entry(klv.key()).or_default().push(i);
+        }
+    }
+
+    /// Checks the length of every item against the constraints in the tag
+    /// table `G`, failing on the first violation when `strict` is set and
+    /// recording each as a warning otherwise.
+    fn check_lengths<G>(&mut self, strict: bool) -> Result<(), encoding::Error>
+    where
+        G: Tag,
+    {
+        for klv in &self.items {
+            let Some(expected) = klv
+                .tag()
+                .and_then(|number| G::try_from(number).ok())
+                .and_then(|tag| tag.value_length())
+            else {
+                continue;
+            };
+            if expected.allows(klv.length()) {
+                continue;
+            }
+
+            if strict {
+                return Err(encoding::Error::InvalidLength {
+                    key: klv.key(),
+                    offset: klv.value_offset(),
+                    expected,
+                    actual: klv.length(),
+                });
+            }
+            self.warnings.push(Violation::new(
+                Severity::Warning,
+                klv.key(),
+                Some(klv.value_offset()),
+                ViolationKind::InvalidLength {
+                    expected,
+                    actual: klv.length(),
+                },
+            ));
+        }
+        Ok(())
+    }
+
+    /// Checks the ST 0601 checksum item of the set against the bytes of the
+    /// packet, from the first byte of its Universal Key at
+    /// `universal_key_pos` through the length of the checksum item.
+    ///
+    /// Sets without a two byte checksum item are not checked.
+    ///
+    /// # Returns
+    ///
+    /// - `Ok(())` - The checksum matched, was not checked, or a mismatch was
+    ///   recorded as a warning because `mode` is `ChecksumMode::Warn`.
+    /// - `Err(encoding::Error::ChecksumMismatch)` - The checksum did not match
+    ///   and `mode` is `ChecksumMode::Reject`.
+    /// - `Err(encoding::Error)` - The packet could not be read back out of the
+    ///   buffer.
+    pub(crate) fn verify_checksum(
+        &mut self,
+        universal_key_pos: u64,
+        buf: &Rc<RefCell<T>>,
+        mode: ChecksumMode,
+    ) -> Result<(), encoding::Error> {
+        if mode == ChecksumMode::Ignore {
+            return Ok(());
+        }
+        let Some(klv) = self.get(&ItemKey::Tag(St0601Tag::Checksum.into())) else {
+            return Ok(());
+        };
+        let Ok(expected) = <[u8; 2]>::try_from(klv.read_value()?) else {
+            return Ok(());
+        };
+        let expected = u16::from_be_bytes(expected);
+        let (key, value_offset) = (klv.key(), klv.value_offset());
+
+        let mut packet = vec![0; (value_offset - universal_key_pos) as usize];
+        {
+            let mut buf = buf.borrow_mut();
+            buf.seek(SeekFrom::Start(universal_key_pos))?;
+            buf.read_exact(&mut packet)?;
+        }
+        let actual = st0601::checksum(&packet);
+        if actual == expected {
+            return Ok(());
+        }
+
+        if mode == ChecksumMode::Reject {
+            return Err(encoding::Error::ChecksumMismatch {
+                offset: universal_key_pos,
+                expected,
+                actual,
+            });
+        }
+        self.warnings.push(Violation::new(
+            Severity::Warning,
+            key,
+            Some(value_offset),
+            ViolationKind::ChecksumMismatch { expected, actual },
+        ));
+        Ok(())
+    }
+
+    /// Gets the item with the key `key`.
+    ///
+    /// # Returns
+    ///
+    /// - `Some(&Klv)` - The last occurrence of `key` in the set.
+    /// - `None` - `key` is not in the set.
+    pub fn get(&self, key: &ItemKey) -> Option<&Klv<T>> {
+        let i = *self.index.get(key)?.last()?;
+        Some(&self.items[i])
+    }
+
+    /// Gets every occurrence of `key` in the set, in the order they appear in
+    /// the buffer.
+    pub fn get_all(&self, key: &ItemKey) -> impl Iterator<Item = &Klv<T>> {
+        self.index
+            .get(key)
+            .into_iter()
+            .flatten()
+            .map(|&i| &self.items[i])
+    }
+
+    /// Iterates over every item in the set in the order they appear in the
+    /// buffer.
+    pub fn iter(&self) -> std::slice::Iter<'_, Klv<T>> {
+        self.items.iter()
+    }
+
+    /// Reads the last occurrence of `tag` and converts it with `Tag::decode`.
+    ///
+    /// # Returns
+    ///
+    /// - `Ok(Some(TagReading))` - `tag` is in the set and was converted, or is
+    ///   one of its special values.
+    /// - `Ok(None)` - `tag` is not in the set.
+    /// - `Err(encoding::Error)` - The value could not be read or converted.
+    pub fn get_typed<G>(&self, tag: G) -> Result<Option<TagReading<SoftwareValue>>, encoding::Error>
+    where
+        G: Tag,
+    {
+        let Some(klv) = self.get(&ItemKey::Tag(tag.into())) else {
+            return Ok(None);
+        };
+        Ok(Some(tag.decode(&klv.read_value()?)?))
+    }
+
+    /// Reads the last occurrence of `tag` as a 16 byte UUID, whatever format
+    /// its tag table gives it.
+    ///
+    /// # Returns
+    ///
+    /// - `Ok(Some(Uuid))` - `tag` is in the set and is 16 bytes long.
+    /// - `Ok(None)` - `tag` is not in the set or is empty.
+    /// - `Err(encoding::Error)` - The value could not be read or is not 16
+    ///   bytes long.
+    #[cfg(feature = "uuid")]
+    pub fn get_uuid<G>(&self, tag: G) -> Result<Option<uuid::Uuid>, encoding::Error>
+    where
+        G: Tag,
+    {
+        match self.get(&ItemKey::Tag(tag.into())) {
+            Some(klv) if klv.length() == 0 => Ok(None),
+            Some(klv) if klv.length() != 16 => Err(encoding::uuid_length_error(klv.length())),
+            Some(klv) => encoding::decode_uuid(&klv.read_value()?).map(Some),
+            None => Ok(None),
         }
+    }
+
+    /// Iterates over every item in the set in the order they appear in the
+    /// buffer, along with its tag in the table `G`.
+    ///
+    /// Items keyed by Universal Labels are looked up by `ItemKey::number`.
+    pub fn iter_typed<G>(&self) -> impl Iterator<Item = (Result<G, UnknownTag>, &Klv<T>)>
+    where
+        G: Tag,
+    {
+        self.items
+            .iter()
+            .map(|klv| (G::try_from(klv.key().number()), klv))
+    }
+
+    /// Gets the `index`th item in the set, counting in the order they appear in
+    /// the buffer.
+    pub fn entry_at(&self, index: usize) -> Option<&Klv<T>> {
+        self.items.get(index)
+    }
+
+    /// Iterates over each distinct key in the set in order.
+    pub fn keys(&self) -> impl Iterator<Item = &ItemKey> {
+        self.index.keys()
+    }
+
+    /// Number of items in the set, counting every occurrence of a repeated
+    /// key.
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    /// Copies every item out of the buffer into a set that can be edited and
+    /// serialized.
+    pub fn to_mutable(&self) -> Result<OwnedLocalSet, io::Error> {
+        self.items
+            .iter()
+            .map(Klv::to_owned_klv)
+            .collect::<Result<_, _>>()
+            .map(OwnedLocalSet::new)
+    }
+
+    /// Combines this set with a newer, possibly partial, version of it, such as
+    /// an ST 0601 packet that omits unchanged items.
+    ///
+    /// Every occurrence of a key in `newer` replaces every occurrence of it in
+    /// this set, taking the place of the first one. Keys only in `newer` are
+    /// added to the end in the order they appear in.
+    pub fn merged_with<U>(&self, newer: &LocalSet<U>) -> Result<OwnedLocalSet, io::Error>
+    where
+        U: Read + Seek,
+    {
+        let mut merged = self.to_mutable()?;
+        for key in newer.iter().map(Klv::key).unique() {
+            let items = newer
+                .get_all(&key)
+                .map(Klv::to_owned_klv)
+                .collect::<Result<Vec<_>, _>>()?;
+            merged.replace_all(key, items);
+        }
+        Ok(merged)
+    }
+
+    /// Compares this set against a newer version of it.
+    ///
+    /// Each key is compared by the value of its last occurrence. Keys in
+    /// `ignore`, such as a timestamp or checksum that are expected to differ,
+    /// are left out.
+    pub fn diff<U>(&self, newer: &LocalSet<U>, ignore: &[ItemKey]) -> Result<SetDiff, io::Error>
+    where
+        U: Read + Seek,
+    {
+        let mut entries = Vec::new();
+        for key in self.keys().merge(newer.keys()).dedup() {
+            if ignore.contains(key) {
+                continue;
+            }
+            let old = self.get(key).map(Klv::read_value).transpose()?;
+            let new = newer.get(key).map(Klv::read_value).transpose()?;
+            let kind = match (old, new) {
+                (Some(old_bytes), Some(new_bytes)) if old_bytes != new_bytes => DiffKind::Changed {
+                    old_bytes,
+                    new_bytes,
+                },
+                (Some(old_bytes), None) => DiffKind::Removed { old_bytes },
+                (None, Some(new_bytes)) => DiffKind::Added { new_bytes },
+                _ => continue,
+            };
+            entries.push(DiffEntry::new(*key, kind));
+        }
+        Ok(SetDiff::new(entries))
+    }
+
+    /// Checks the set against the tags the standard `G` defines.
+    ///
+    /// Every problem is reported rather than stopping at the first one. Fill
+    /// items are not checked.
+    ///
+    /// # Returns
+    ///
+    /// A report containing:
+    /// - An error for every required tag that is missing.
+    /// - A warning for every item whose tag the standard does not define.
+    /// - An error for every item whose length breaks the standard's
+    ///   constraint.
+    pub fn validate<G>(&self) -> ValidationReport
+    where
+        G: Tag,
+    {
+        let mut violations = G::all()
+            .iter()
+            .filter(|tag| tag.required())
+            .map(|tag| ItemKey::Tag(tag.number()))
+            .filter(|key| self.get(key).is_none())
+            .map(|key| Violation::new(Severity::Error, key, None, ViolationKind::MissingRequired))
+            .collect_vec();
+
+        for (index, klv) in self
+            .items
+            .iter()
+            .enumerate()
+            .filter(|(_, klv)| !klv.is_fill())
+        {
+            let offset = Some(klv.value_offset());
+            let context = ItemContext::new::<G>(klv.key(), index, klv.value_offset());
+            match klv.tag().and_then(|number| G::try_from(number).ok()) {
+                None => violations.push(
+                    Violation::new(
+                        Severity::Warning,
+                        klv.key(),
+                        offset,
+                        ViolationKind::UnknownTag,
+                    )
+                    .in_context(context),
+                ),
+                Some(tag) => {
+                    if let Some(expected) = tag.value_length()
+                        && !expected.allows(klv.length())
+                    {
+                        violations.push(
+                            Violation::new(
+                                Severity::Error,
+                                klv.key(),
+                                offset,
+                                ViolationKind::InvalidLength {
+                                    expected,
+                                    actual: klv.length(),
+                                },
+                            )
+                            .in_context(context),
+                        );
+                    }
+                }
+            }
+        }
+
+        ValidationReport::new(violations)
+    }
+}
+
+/// Whether `klv` is kept in a set parsed with `options`.
+fn keeps<T>(options: &ParseOptions, klv: &Klv<T>) -> bool
+where
+    T: Read + Seek,
+{
+    !(options.skip_fill && klv.is_fill())
+        && options
+            .tag_filter
+            .as_ref()
+            .is_none_or(|filter| filter.wants(&klv.key()))
+}
+
+/// Checks that another item may be kept in a set that already holds `kept`
+/// items.
+///
+/// # Returns
+///
+/// - `Ok(())` - The item is within `options.max_items_per_set`.
+/// - `Err(encoding::Error::LimitExceeded)` - The set is already full.
+fn check_item_limit(options: &ParseOptions, kept: usize) -> Result<(), encoding::Error> {
+    match options.max_items_per_set {
+        Some(max) if kept >= max => Err(encoding::Error::LimitExceeded(
+            Limit::ItemsPerSet,
+            max as u64,
+        )),
+        _ => Ok(()),
+    }
+}
+
+/// Warnings about how `klv` is encoded: a tag or length written in more
+/// bytes than it needs, or a fill item dropped because of
+/// `options.skip_fill`.
+fn item_warnings<T>(klv: &Klv<T>, encoding: &ItemEncoding, options: &ParseOptions) -> Vec<Violation>
+where
+    T: Read + Seek,
+{
+    let warning =
+        |kind| Violation::new(Severity::Warning, klv.key(), Some(klv.value_offset()), kind);
+    if klv.is_fill() {
+        return if options.skip_fill {
+            vec![warning(ViolationKind::FillItem)]
+        } else {
+            Vec::new()
+        };
+    }
+
+    let mut warnings = Vec::new();
+    if let (TagEncoding::BerOid, Some(tag)) = (encoding.tag, klv.tag()) {
+        let (width, minimal) = (klv.key_width(), encode_ber_oid(tag).len() as u64);
+        if width > minimal {
+            warnings.push(warning(ViolationKind::NonMinimalEncoding {
+                width,
+                minimal,
+            }));
+        }
+    }
+    if encoding.length == LengthEncoding::Ber {
+        let (width, minimal) = (
+            klv.length_width(),
+            encode_ber(klv.length().into()).len() as u64,
+        );
+        if width > minimal {
+            warnings.push(warning(ViolationKind::NonMinimalEncoding {
+                width,
+                minimal,
+            }));
+        }
+    }
+    warnings
+}
+
+/// Iterator over the items of a set that parses each one as it is reached.
+/// See `LocalSet::stream_items`.
+///
+/// Iteration stops after the first error.
+#[derive(Debug)]
+pub struct SetItems<T>
+where
+    T: Read + Seek,
+{
+    buf: Rc<RefCell<T>>,
+    /// Offset of the first byte of the next item.
+    position: u64,
+    /// Offset of the byte after the last byte of the set.
+    end: u64,
+    encoding: ItemEncoding,
+}
+
+impl<T> Iterator for SetItems<T>
+where
+    T: Read + Seek,
+{
+    type Item = Result<Klv<T>, encoding::Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.position >= self.end {
+            return None;
+        }
+
+        let seeked = self.buf.borrow_mut().seek(SeekFrom::Start(self.position));
+        let klv = seeked
+            .map_err(encoding::Error::from)
+            .and_then(|_| Klv::new_with_encoding(self.buf.clone(), &self.encoding))
+            .and_then(|klv| match checked_end(klv.value_offset(), klv.length())? {
+                next if next <= self.end => Ok((klv, next)),
+                _ => Err(encoding::Error::Truncated("value")),
+            });
+        Some(match klv {
+            Ok((klv, next)) => {
+                self.position = next;
+                Ok(klv)
+            }
+            Err(err) => {
+                self.position = self.end;
+                Err(err)
+            }
+        })
+    }
+}
+
+/// Local set whose items have been copied out of the buffer so they can be
+/// edited and written back out.
+///
+/// Items are kept in the order they appear in, including items whose tags
+/// no tag table defines, which are written back out unchanged.
+#[derive(Clone, Debug, Default, PartialEq, Eq, getset::Getters)]
+#[getset(get = "pub")]
+pub struct OwnedLocalSet {
+    items: Vec<OwnedKlv>,
+}
+
+impl OwnedLocalSet {
+    pub fn new(items: Vec<OwnedKlv>) -> Self {
+        Self { items }
+    }
+
+    /// Gets the last occurrence of `key` in the set.
+    pub fn get(&self, key: &ItemKey) -> Option<&OwnedKlv> {
+        self.items.iter().rev().find(|klv| klv.key() == *key)
+    }
+
+    /// Sets the value of `key`.
+    ///
+    /// The first occurrence of `key` takes the new value and any later
+    /// occurrences are removed. Keys that are not in the set yet are added to
+    /// the end.
+    pub fn set(&mut self, key: ItemKey, value: Vec<u8>) {
+        let item = OwnedKlv::with_key(key, value);
+        match self.items.iter().position(|klv| klv.key() == key) {
+            Some(first) => {
+                self.items[first] = item;
+                let rest = self.items.split_off(first + 1);
+                self.items
+                    .extend(rest.into_iter().filter(|klv| klv.key() != key));
+            }
+            None => self.items.push(item),
+        }
+    }
+
+    /// Replaces every occurrence of `key` with `items`, which take the place
+    /// of the first occurrence or are added to the end.
+    fn replace_all(&mut self, key: ItemKey, items: Vec<OwnedKlv>) {
+        match self.items.iter().position(|klv| klv.key() == key) {
+            Some(first) => {
+                let rest = self.items.split_off(first);
+                self.items.extend(items);
+                self.items
+                    .extend(rest.into_iter().filter(|klv| klv.key() != key));
+            }
+            None => self.items.extend(items),
+        }
+    }
+
+    /// Removes every occurrence of `key`.
+    ///
+    /// # Returns
+    ///
+    /// Whether `key` was in the set.
+    pub fn remove(&mut self, key: &ItemKey) -> bool {
+        let length = self.items.len();
+        self.items.retain(|klv| klv.key() != *key);
+        self.items.len() != length
+    }
+
+    /// Adds an item directly after the last occurrence of `after`.
+    ///
+    /// # Returns
+    ///
+    /// Whether `after` was in the set. Nothing is added when it was not.
+    pub fn insert_after(&mut self, after: &ItemKey, key: ItemKey, value: Vec<u8>) -> bool {
+        match self.items.iter().rposition(|klv| klv.key() == *after) {
+            Some(i) => {
+                self.items.insert(i + 1, OwnedKlv::with_key(key, value));
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Writes the set out as a complete KLV packet keyed by `key`, using
+    /// BER-OID tags and BER lengths.
+    ///
+    /// Every length is recomputed from the values. When `checksum` is set any
+    /// existing ST 0601 checksum item is dropped and a new one covering the
+    /// whole packet is written as the last item.
+    pub fn serialize(&self, key: &UniversalKey, checksum: bool) -> Vec<u8> {
+        self.serialize_with_options(
+            key,
+            &SerializeOptions {
+                checksum,
+                ..Default::default()
+            },
+        )
+        .expect("Shortest form always fits")
+    }
+
+    /// Writes the same packet as `OwnedLocalSet::serialize` with the length of
+    /// the set written in exactly `width` bytes.
+    ///
+    /// # Returns
+    ///
+    /// - `Some(Vec<u8>)` - The packet.
+    /// - `None` - The length of the set does not fit in `width` bytes.
+    pub fn serialize_with_length_width(
+        &self,
+        key: &UniversalKey,
+        checksum: bool,
+        width: usize,
+    ) -> Option<Vec<u8>> {
+        self.serialize_with_options(
+            key,
+            &SerializeOptions {
+                checksum,
+                set_length_width: Some(width),
+                ..Default::default()
+            },
+        )
+    }
+
+    /// Writes the set out as a complete KLV packet keyed by `key`, encoding
+    /// tags and lengths as `options` asks.
+    ///
+    /// # Returns
+    ///
+    /// - `Some(Vec<u8>)` - The packet.
+    /// - `None` - The length of the set or of an item does not fit in the
+    ///   width it was asked to be written in.
+    pub fn serialize_with_options(
+        &self,
+        key: &UniversalKey,
+        options: &SerializeOptions,
+    ) -> Option<Vec<u8>> {
+        let checksum = options.checksum;
+        let checksum_key = ItemKey::Tag(St0601Tag::Checksum.into());
+        let payload = self.encode_items(|klv| !(checksum && klv.key() == checksum_key), options)?;
+        // Tag and length of the checksum item, which the checksum covers.
+        let checksum_header = [0x01, 0x02];
+        let checksum_length = if checksum { 4 } else { 0 };
+
+        let length = (payload.len() + checksum_length) as u128;
+        let mut packet = key.to_vec();
+        packet.extend(match options.set_length_width {
+            Some(width) => encode_ber_padded(length, width)?,
+            None => encode_ber(length),
+        });
+        packet.extend(payload);
+        if checksum {
+            packet.extend(checksum_header);
+            packet.extend(st0601::checksum(&packet).to_be_bytes());
+        }
+        Some(packet)
+    }
+
+    /// Writes the items of the set without a Universal Key or length, as they
+    /// appear when the set is nested in the value of another item.
+    pub fn serialize_items(&self) -> Vec<u8> {
+        self.encode_items(|_| true, &SerializeOptions::default())
+            .expect("Shortest form always fits")
+    }
+
+    /// Encodes every item `keep` returns true for with BER-OID tags and BER
+    /// lengths, or with the encodings they were read with when `options`
+    /// preserves them.
+    fn encode_items(
+        &self,
+        keep: impl Fn(&OwnedKlv) -> bool,
+        options: &SerializeOptions,
+    ) -> Option<Vec<u8>> {
+        let mut payload = Vec::new();
+        for klv in self.items.iter().filter(|klv| keep(klv)) {
+            let original = klv
+                .original_header()
+                .as_ref()
+                .filter(|_| options.preserve_encodings);
+            match (original, klv.key()) {
+                (Some(header), _) => payload.extend_from_slice(&header.tag_bytes),
+                (None, ItemKey::Tag(tag)) => payload.extend(encode_ber_oid(tag)),
+                (None, ItemKey::Ul(key)) => payload.extend_from_slice(&*key),
+            }
+            let length = klv.length().into();
+            match (options.length_forms.get(&klv.key()), original) {
+                (Some(LengthForm::Width(width)), _) => {
+                    payload.extend(encode_ber_padded(length, *width)?)
+                }
+                (None, Some(header)) => payload.extend_from_slice(&header.length_bytes),
+                _ => payload.extend(encode_ber(length)),
+            }
+            payload.extend_from_slice(klv.value());
+        }
+        Some(payload)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{self, Cursor};
+
+    use super::*;
+    use crate::{
+        encoding::ber::BerForm, keys, misb::st0601::St0601Tag, options::DEFAULT_BATCH_THRESHOLD,
+        tag::ValueLength,
+    };
+    use itertools::{Itertools, chain};
+    use test_case::test_case;
+
+    /// Wraps a buffer and counts how many times it is read from or seeked.
+    struct CountingReader<T> {
+        inner: T,
+        calls: usize,
+    }
+
+    impl<T: Read> Read for CountingReader<T> {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            self.calls += 1;
+            self.inner.read(buf)
+        }
+    }
+
+    impl<T: Seek> Seek for CountingReader<T> {
+        fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+            self.calls += 1;
+            self.inner.seek(pos)
+        }
+    }
+
+    fn three_item_set() -> Vec<u8> {
+        chain!(
+            [0x00; UNIVERSAL_KEY_LENGTH],
+            [0x0C],
+            [0x01, 0x02, 0xAA, 0xBB],
+            [0x02, 0x01, 0xCC],
+            [0x03, 0x03, 0xDD, 0xEE, 0xFF]
+        )
+        .collect_vec()
+    }
+
+    fn parse_and_read(options: &ParseOptions) -> (Vec<(u128, Vec<u8>)>, usize) {
+        let buf = Rc::new(RefCell::new(CountingReader {
+            inner: Cursor::new(three_item_set()),
+            calls: 0,
+        }));
+        let set = LocalSet::read_with_options(0, buf.clone(), options).unwrap();
+        let values = set
+            .iter()
+            .map(|klv| (klv.tag().unwrap(), klv.read_value().unwrap()))
+            .collect_vec();
+        let calls = buf.borrow().calls;
+        (values, calls)
+    }
+
+    #[test]
+    fn typed_values_by_tag() {
+        let packet = OwnedLocalSet::new(vec![
+            OwnedKlv::new(3, b"MISSION01".to_vec()),
+            OwnedKlv::new(13, vec![0x55, 0x95, 0xB6, 0x6D]),
+            OwnedKlv::new(200, vec![0xAA]),
+        ])
+        .serialize(&keys::ST0601, false);
+        let set = LocalSet::read(0, Rc::new(RefCell::new(Cursor::new(packet)))).unwrap();
+
+        // Example value from MISB ST 0601.
+        let Some(TagReading::Value(SoftwareValue::Float64(latitude))) =
+            set.get_typed(St0601Tag::SensorLatitude).unwrap()
+        else {
+            panic!("Latitude is converted to degrees");
+        };
+        assert!((latitude - 60.176_822_97).abs() < 1e-8);
+        assert_eq!(
+            set.get_typed(St0601Tag::MissionId).unwrap(),
+            Some(TagReading::Value(SoftwareValue::String("MISSION01".into())))
+        );
+        assert_eq!(set.get_typed(St0601Tag::SlantRange).unwrap(), None);
+        assert_eq!(
+            set.iter_typed::<St0601Tag>()
+                .map(|(tag, _)| tag)
+                .collect_vec(),
+            [
+                Ok(St0601Tag::MissionId),
+                Ok(St0601Tag::SensorLatitude),
+                Err(UnknownTag(200))
+            ]
+        );
+    }
+
+    #[test]
+    fn streamed_items_match_eager_parse() {
+        let buf = Rc::new(RefCell::new(Cursor::new(set_with_fill())));
+        let options = ParseOptions {
+            skip_fill: false,
+            ..Default::default()
+        };
+        let eager = LocalSet::read_with_options(0, buf.clone(), &options).unwrap();
+        let streamed = LocalSet::stream_items(0, buf, &ItemEncoding::default())
+            .unwrap()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+
+        let summary = |klv: &Klv<_>| (klv.key(), klv.offset(), klv.read_value().unwrap());
+        assert_eq!(
+            streamed.iter().map(summary).collect_vec(),
+            eager.iter().map(summary).collect_vec()
+        );
+    }
+
+    #[test]
+    fn streamed_items_stop_early() {
+        let buf = Rc::new(RefCell::new(CountingReader {
+            inner: Cursor::new(three_item_set()),
+            calls: 0,
+        }));
+        let mut items = LocalSet::stream_items(0, buf.clone(), &ItemEncoding::default()).unwrap();
+
+        let second = items
+            .find(|klv| klv.as_ref().unwrap().tag() == Some(2))
+            .unwrap()
+            .unwrap();
+
+        // Parsing stopped at the start of the third item.
+        assert_eq!(buf.borrow().inner.position(), 24);
+        assert_eq!(second.read_value().unwrap(), [0xCC]);
+    }
+
+    #[test]
+    fn streamed_item_past_end_of_set() {
+        let buf = chain!(
+            [0x00; UNIVERSAL_KEY_LENGTH],
+            [0x03],
+            [0x01, 0x05, 0xAA, 0xBB]
+        )
+        .collect_vec();
+        let mut items = LocalSet::stream_items(
+            0,
+            Rc::new(RefCell::new(Cursor::new(buf))),
+            &ItemEncoding::default(),
+        )
+        .unwrap();
+
+        assert!(matches!(
+            items.next(),
+            Some(Err(encoding::Error::Truncated("value")))
+        ));
+        assert!(items.next().is_none());
+    }
+
+    #[test]
+    fn streamed_set_with_huge_length() {
+        let buf = chain!(
+            [0x00; UNIVERSAL_KEY_LENGTH],
+            [0x88, 0x80, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00]
+        )
+        .collect_vec();
+        assert!(matches!(
+            LocalSet::stream_items(
+                0,
+                Rc::new(RefCell::new(Cursor::new(buf))),
+                &ItemEncoding::default(),
+            ),
+            Err(encoding::Error::ValueOutOfRange { .. })
+        ));
+    }
+
+    #[test]
+    fn streamed_item_ending_past_i64() {
+        let buf = vec![0x01, 0x88, 0x7F, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF];
+        let mut items = LocalSet::items_in(
+            Rc::new(RefCell::new(Cursor::new(buf))),
+            0..u64::MAX,
+            &ItemEncoding::default(),
+        );
+
+        assert!(matches!(
+            items.next(),
+            Some(Err(encoding::Error::ValueOutOfRange { .. }))
+        ));
+        assert!(items.next().is_none());
+    }
+
+    #[test]
+    fn batched_read_matches_in_place_read() {
+        let (in_place, in_place_calls) = parse_and_read(&ParseOptions {
+            batch_threshold: 0,
+            ..Default::default()
+        });
+        let (batched, batched_calls) = parse_and_read(&ParseOptions::default());
+
+        assert_eq!(batched, in_place);
+        assert_eq!(
+            batched,
+            vec![
+                (1, vec![0xAA, 0xBB]),
+                (2, vec![0xCC]),
+                (3, vec![0xDD, 0xEE, 0xFF])
+            ]
+        );
+        assert!(
+            batched_calls * 4 < in_place_calls,
+            "Batched parse made {batched_calls} calls, in place parse made {in_place_calls}"
+        );
+    }
+
+    fn set_with_fill() -> Vec<u8> {
+        chain!(
+            [0x00; UNIVERSAL_KEY_LENGTH],
+            [0x1B],
+            [0x01, 0x02, 0xAA, 0xBB],
+            *keys::FILL,
+            [0x03, 0x00, 0x00, 0x00],
+            [0x02, 0x01, 0xCC]
+        )
+        .collect_vec()
+    }
+
+    #[test_case(0; "In place")]
+    #[test_case(DEFAULT_BATCH_THRESHOLD; "Batched")]
+    fn fill_items_are_skipped(batch_threshold: u64) {
+        let buf = Rc::new(RefCell::new(Cursor::new(set_with_fill())));
+        let options = ParseOptions {
+            batch_threshold,
+            ..Default::default()
+        };
+        let set = LocalSet::read_with_options(0, buf, &options).unwrap();
+
+        assert_eq!(
+            set.keys().copied().collect_vec(),
+            vec![ItemKey::Tag(1), ItemKey::Tag(2)]
+        );
+        assert_eq!(set[&ItemKey::Tag(1)].value_offset(), 19);
+        assert_eq!(set[&ItemKey::Tag(2)].value_offset(), 43);
+        assert_eq!(set[&ItemKey::Tag(2)].read_value().unwrap(), vec![0xCC]);
+    }
+
+    #[test_case(0; "In place")]
+    #[test_case(DEFAULT_BATCH_THRESHOLD; "Batched")]
+    fn item_running_into_the_next_set_is_an_error(batch_threshold: u64) {
+        // The set holds three bytes but its item claims five, which the next
+        // set would supply.
+        let buf = chain!(
+            [0x00; UNIVERSAL_KEY_LENGTH],
+            [0x03, 0x02, 0x05, 0xAA],
+            [0x00; UNIVERSAL_KEY_LENGTH],
+            [0x03, 0x02, 0x01, 0xBB]
+        )
+        .collect_vec();
+        let options = ParseOptions {
+            batch_threshold,
+            ..Default::default()
+        };
+        let error =
+            LocalSet::read_with_options(0, Rc::new(RefCell::new(Cursor::new(buf))), &options)
+                .unwrap_err();
+
+        assert!(matches!(
+            error,
+            encoding::Error::Other(ref error) if error.kind() == io::ErrorKind::UnexpectedEof
+        ));
+    }
+
+    #[test_case(0; "In place")]
+    #[test_case(DEFAULT_BATCH_THRESHOLD; "Batched")]
+    fn fill_items_can_be_kept(batch_threshold: u64) {
+        let buf = Rc::new(RefCell::new(Cursor::new(set_with_fill())));
+        let options = ParseOptions {
+            batch_threshold,
+            skip_fill: false,
+            ..Default::default()
+        };
+        let set = LocalSet::read_with_options(0, buf, &options).unwrap();
+
+        assert_eq!(
+            set.keys().copied().collect_vec(),
+            vec![ItemKey::Tag(1), ItemKey::Tag(2), ItemKey::Ul(keys::FILL)]
+        );
+        assert!(set[&ItemKey::Ul(keys::FILL)].is_fill());
+        assert_eq!(set[&ItemKey::Ul(keys::FILL)].length(), 3);
+        assert_eq!(set[&ItemKey::Ul(keys::FILL)].value_offset(), 38);
+        assert_eq!(set[&ItemKey::Tag(2)].value_offset(), 43);
+    }
+
+    fn set_with_repeated_tag() -> Vec<u8> {
+        chain!(
+            [0x00; UNIVERSAL_KEY_LENGTH],
+            [0x0C],
+            [0x05, 0x01, 0xAA],
+            [0x01, 0x01, 0x11],
+            [0x05, 0x01, 0xBB],
+            [0x05, 0x01, 0xCC]
+        )
+        .collect_vec()
+    }
+
+    #[test_case(DuplicatePolicy::KeepAll, vec![0xAA, 0xBB, 0xCC], 0xCC; "Keep all")]
+    #[test_case(DuplicatePolicy::KeepFirst, vec![0xAA], 0xAA; "Keep first")]
+    #[test_case(DuplicatePolicy::KeepLast, vec![0xCC], 0xCC; "Keep last")]
+    fn repeated_tags(policy: DuplicatePolicy, all: Vec<u8>, last: u8) {
+        for batch_threshold in [0, DEFAULT_BATCH_THRESHOLD] {
+            let buf = Rc::new(RefCell::new(Cursor::new(set_with_repeated_tag())));
+            let options = ParseOptions {
+                batch_threshold,
+                duplicates: policy,
+                ..Default::default()
+            };
+            let set = LocalSet::read_with_options(0, buf, &options).unwrap();
+
+            let tag = ItemKey::Tag(5);
+            assert_eq!(
+                set.get_all(&tag)
+                    .map(|klv| klv.read_value().unwrap()[0])
+                    .collect_vec(),
+                all
+            );
+            assert_eq!(set[&tag].read_value().unwrap(), vec![last]);
+            assert_eq!(set.len(), all.len() + 1);
+            assert_eq!(
+                set.keys().copied().collect_vec(),
+                vec![ItemKey::Tag(1), ItemKey::Tag(5)]
+            );
+        }
+    }
+
+    #[test_case(0; "In place")]
+    #[test_case(DEFAULT_BATCH_THRESHOLD; "Batched")]
+    fn repeated_tag_is_an_error(batch_threshold: u64) {
+        let buf = Rc::new(RefCell::new(Cursor::new(set_with_repeated_tag())));
+        let options = ParseOptions {
+            batch_threshold,
+            duplicates: DuplicatePolicy::Error,
+            ..Default::default()
+        };
+
+        match LocalSet::read_with_options(0, buf, &options) {
+            Err(encoding::Error::DuplicateItem {
+                key,
+                first_offset,
+                second_offset,
+            }) => {
+                assert_eq!(key, ItemKey::Tag(5));
+                assert_eq!(first_offset, 19);
+                assert_eq!(second_offset, 25);
+            }
+            other => panic!("Expected a duplicate item error, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn validate_against_st0601() {
+        let buf = chain!(
+            [0x00; UNIVERSAL_KEY_LENGTH],
+            [0x0C],
+            [0x01, 0x02, 0x00, 0x00],
+            [0x41, 0x01, 0x11],
+            [0x05, 0x03, 0xAA, 0xBB, 0xCC]
+        )
+        .collect_vec();
+        let buf = Rc::new(RefCell::new(Cursor::new(buf)));
+        let set = LocalSet::read(0, buf).unwrap();
+
+        let report = set.validate::<St0601Tag>();
+
+        assert_eq!(
+            *report.violations(),
+            vec![
+                Violation::new(
+                    Severity::Error,
+                    ItemKey::Tag(2),
+                    None,
+                    ViolationKind::MissingRequired
+                ),
+                Violation::new(
+                    Severity::Error,
+                    ItemKey::Tag(5),
+                    Some(26),
+                    ViolationKind::InvalidLength {
+                        expected: ValueLength::Required(2),
+                        actual: 3
+                    }
+                )
+                .in_context(ItemContext::new::<St0601Tag>(ItemKey::Tag(5), 2, 26)),
+            ]
+        );
+        assert!(!report.is_valid());
+    }
+
+    #[test]
+    fn unknown_tags_are_warnings() {
+        let buf = chain!(
+            [0x00; UNIVERSAL_KEY_LENGTH],
+            [0x14],
+            [0x01, 0x02, 0x00, 0x00],
+            [0x02, 0x08, 0, 0, 0, 0, 0, 0, 0, 0],
+            [0x41, 0x01, 0x11],
+            [0x81, 0x10, 0x00]
+        )
+        .collect_vec();
+        let buf = Rc::new(RefCell::new(Cursor::new(buf)));
+        let set = LocalSet::read(0, buf).unwrap();
+
+        let report = set.validate::<St0601Tag>();
+
+        assert!(report.is_valid());
+        assert_eq!(
+            report
+                .warnings()
+                .map(|violation| (violation.key(), violation.kind()))
+                .collect_vec(),
+            vec![(ItemKey::Tag(144), ViolationKind::UnknownTag)]
+        );
+    }
+
+    #[test]
+    fn zero_length_values() {
+        let buf = chain!(
+            [0x00; UNIVERSAL_KEY_LENGTH],
+            [0x13],
+            [0x02, 0x08],
+            1_000_000u64.to_be_bytes(),
+            [0x0D, 0x00],
+            [0x0B, 0x03, b'E', b'O', b'N'],
+            [0x03, 0x00]
+        )
+        .collect_vec();
+        let set = LocalSet::read(0, Rc::new(RefCell::new(Cursor::new(buf)))).unwrap();
+
+        assert_eq!(set.len(), 4);
+        assert_eq!(
+            set[&ItemKey::Tag(13)].read_value().unwrap(),
+            Vec::<u8>::new()
+        );
+        assert_eq!(
+            set[&ItemKey::Tag(3)].read_value().unwrap(),
+            Vec::<u8>::new()
+        );
+        assert_eq!(
+            set.get_typed(St0601Tag::SensorLatitude).unwrap(),
+            Some(TagReading::Empty)
+        );
+        assert_eq!(
+            set.get_typed(St0601Tag::MissionId).unwrap(),
+            Some(TagReading::Value(SoftwareValue::String(String::new())))
+        );
+        assert_eq!(
+            set.get_typed(St0601Tag::ImageSourceSensor).unwrap(),
+            Some(TagReading::Value(SoftwareValue::String("EON".into())))
+        );
+        // Only the tag with a required length is flagged.
+        assert_eq!(
+            set.validate::<St0601Tag>()
+                .violations()
+                .iter()
+                .filter(|violation| violation.kind() != ViolationKind::MissingRequired)
+                .map(|violation| (violation.key(), violation.kind()))
+                .collect_vec(),
+            [(
+                ItemKey::Tag(13),
+                ViolationKind::InvalidLength {
+                    expected: ValueLength::Required(4),
+                    actual: 0
+                }
+            )]
+        );
+    }
+
+    #[cfg(feature = "uuid")]
+    #[test]
+    fn uuid_values() {
+        let buf = chain!(
+            [0x00; UNIVERSAL_KEY_LENGTH],
+            [0x23],
+            [0x0A, 0x10],
+            0x00..0x10,
+            [0x0B, 0x0F],
+            0x00..0x0F
+        )
+        .collect_vec();
+        let set = LocalSet::read(0, Rc::new(RefCell::new(Cursor::new(buf)))).unwrap();
+
+        assert_eq!(
+            set.get_uuid(St0601Tag::PlatformDesignation)
+                .unwrap()
+                .map(|uuid| uuid.to_string()),
+            Some("00010203-0405-0607-0809-0a0b0c0d0e0f".into())
+        );
+        assert!(set.get_uuid(St0601Tag::ImageSourceSensor).is_err());
+        assert_eq!(set.get_uuid(St0601Tag::MissionId).unwrap(), None);
+    }
+
+    crate::tag_table! {
+        #[repr(u8)]
+        enum LengthTag {
+            Timestamp = 1, "Timestamp", Required(8);
+            Name = 2, "Name", Max(4);
+            Heading = 3, "Heading", Length(2);
+        }
+
+        required: []
+    }
+
+    /// Set with a single item of tag `tag` whose value is `length` bytes.
+    fn set_with_item(tag: u8, length: u8) -> Rc<RefCell<Cursor<Vec<u8>>>> {
+        let buf = chain!(
+            [0x00; UNIVERSAL_KEY_LENGTH],
+            [length + 2, tag, length],
+            vec![0xAA; length.into()]
+        )
+        .collect_vec();
+        Rc::new(RefCell::new(Cursor::new(buf)))
+    }
+
+    #[test_case(1, 7, ValueLength::Required(8); "Required")]
+    #[test_case(2, 5, ValueLength::Max(4); "Max")]
+    fn invalid_length_fails_strict_parse(tag: u8, length: u8, expected: ValueLength) {
+        let options = ParseOptions {
+            strict_lengths: true,
+            ..Default::default()
+        };
+
+        match LocalSet::read_as::<LengthTag>(0, set_with_item(tag, length), &options) {
+            Err(encoding::Error::InvalidLength {
+                key,
+                offset,
+                expected: found,
+                actual,
+            }) => {
+                assert_eq!(key, ItemKey::Tag(tag.into()));
+                assert_eq!(offset, 19);
+                assert_eq!(found, expected);
+                assert_eq!(actual, u64::from(length));
+            }
+            other => panic!("Expected an invalid length error, got {other:?}"),
+        }
+    }
+
+    #[test_case(1, 7, ValueLength::Required(8); "Required")]
+    #[test_case(2, 5, ValueLength::Max(4); "Max")]
+    fn invalid_length_warns_in_lenient_parse(tag: u8, length: u8, expected: ValueLength) {
+        let set =
+            LocalSet::read_as::<LengthTag>(0, set_with_item(tag, length), &ParseOptions::default())
+                .unwrap();
+
+        assert_eq!(
+            *set.warnings(),
+            vec![Violation::new(
+                Severity::Warning,
+                ItemKey::Tag(tag.into()),
+                Some(19),
+                ViolationKind::InvalidLength {
+                    expected,
+                    actual: length.into()
+                }
+            )]
+        );
+        assert_eq!(
+            set[&ItemKey::Tag(tag.into())].read_value().unwrap(),
+            vec![0xAA; length.into()]
+        );
+    }
+
+    #[test_case(1, 8; "Required")]
+    #[test_case(2, 3; "Under max")]
+    #[test_case(3, 5; "Typical length")]
+    fn valid_length_is_accepted(tag: u8, length: u8) {
+        let options = ParseOptions {
+            strict_lengths: true,
+            ..Default::default()
+        };
+        let set = LocalSet::read_as::<LengthTag>(0, set_with_item(tag, length), &options).unwrap();
+
+        assert!(set.warnings().is_empty());
+    }
+
+    #[test_case(0; "In place")]
+    #[test_case(DEFAULT_BATCH_THRESHOLD; "Batched")]
+    fn items_keep_stream_order_and_offsets(batch_threshold: u64) {
+        // The set starts part way through the buffer and its tags are out of
+        // order.
+        let buf = chain!(
+            [0xEE; 5],
+            [0x00; UNIVERSAL_KEY_LENGTH],
+            [0x0E],
+            [0x03, 0x03, 0xDD, 0xEE, 0xFF],
+            [0x01, 0x02, 0xAA, 0xBB],
+            [0x81, 0x10, 0x02, 0xCC, 0xDD],
+            [0xEE; 3]
+        )
+        .collect_vec();
+        let buf = Rc::new(RefCell::new(Cursor::new(buf)));
+        let options = ParseOptions {
+            batch_threshold,
+            ..Default::default()
+        };
+        let set = LocalSet::read_with_options(5, buf, &options).unwrap();
+
+        assert_eq!(
+            set.iter()
+                .map(|klv| (klv.key(), klv.offset(), klv.total_length()))
+                .collect_vec(),
+            vec![
+                (ItemKey::Tag(3), 22, 5),
+                (ItemKey::Tag(1), 27, 4),
+                (ItemKey::Tag(144), 31, 5)
+            ]
+        );
+        assert_eq!(set.entry_at(1).unwrap().value_offset(), 29);
+        assert_eq!(
+            set.entry_at(2).unwrap().read_value().unwrap(),
+            vec![0xCC, 0xDD]
+        );
+        assert!(set.entry_at(3).is_none());
+    }
+
+    #[test]
+    fn edit_owned_set() {
+        let mut set = LocalSet::read(0, Rc::new(RefCell::new(Cursor::new(three_item_set()))))
+            .unwrap()
+            .to_mutable()
+            .unwrap();
+
+        set.set(ItemKey::Tag(2), vec![0x11, 0x22]);
+        assert!(set.insert_after(&ItemKey::Tag(1), ItemKey::Tag(9), vec![]));
+        assert!(set.remove(&ItemKey::Tag(3)));
+        assert!(!set.remove(&ItemKey::Tag(3)));
+        assert!(!set.insert_after(&ItemKey::Tag(3), ItemKey::Tag(4), vec![]));
+
+        assert_eq!(
+            set.serialize(&UniversalKey::new([0x00; UNIVERSAL_KEY_LENGTH]), false),
+            chain!(
+                [0x00; UNIVERSAL_KEY_LENGTH],
+                [0x0A],
+                [0x01, 0x02, 0xAA, 0xBB],
+                [0x09, 0x00],
+                [0x02, 0x02, 0x11, 0x22]
+            )
+            .collect_vec()
+        );
+    }
+
+    #[test]
+    fn edited_set_keeps_original_encodings() {
+        let packet = |mission: &[u8], mission_length: &[u8]| {
+            let items = chain!(
+                [0x02, 0x82, 0x00, 0x02, 0xAA, 0xBB],
+                [0x03],
+                mission_length.iter().copied(),
+                mission.iter().copied(),
+                [0x80, 0x04, 0x81, 0x01, 0xCC]
+            )
+            .collect_vec();
+            chain!(
+                [0x00; UNIVERSAL_KEY_LENGTH],
+                [0x81, items.len() as u8],
+                items
+            )
+            .collect_vec()
+        };
+        let original = packet(b"ALPHA", &[0x05]);
+        let mut set = LocalSet::read(0, Rc::new(RefCell::new(Cursor::new(original.clone()))))
+            .unwrap()
+            .to_mutable()
+            .unwrap();
+        set.set(ItemKey::Tag(3), b"CHARLIE".to_vec());
+        let key = UniversalKey::new([0x00; UNIVERSAL_KEY_LENGTH]);
+        let options = SerializeOptions {
+            preserve_encodings: true,
+            set_length_width: Some(2),
+            ..Default::default()
+        };
+
+        // Untouched, the packet is written back out byte for byte.
+        assert_eq!(
+            LocalSet::read(0, Rc::new(RefCell::new(Cursor::new(original.clone()))))
+                .unwrap()
+                .to_mutable()
+                .unwrap()
+                .serialize_with_options(&key, &options),
+            Some(original)
+        );
+        assert_eq!(
+            set.serialize_with_options(&key, &options),
+            Some(packet(b"CHARLIE", &[0x07]))
+        );
+        assert_eq!(
+            set.serialize_with_options(
+                &key,
+                &options
+                    .clone()
+                    .force_length_form(ItemKey::Tag(3), LengthForm::Width(3))
+            ),
+            Some(packet(b"CHARLIE", &[0x82, 0x00, 0x07]))
+        );
+        assert_eq!(
+            set.serialize_with_options(
+                &key,
+                &options
+                    .clone()
+                    .force_length_form(ItemKey::Tag(3), BerForm::LongForm(2))
+            ),
+            Some(packet(b"CHARLIE", &[0x82, 0x00, 0x07]))
+        );
+        // Without preserving encodings every tag and length is minimal.
+        assert_eq!(
+            set.serialize(&key, false),
+            chain!(
+                [0x00; UNIVERSAL_KEY_LENGTH],
+                [0x10],
+                [0x02, 0x02, 0xAA, 0xBB],
+                [0x03, 0x07],
+                *b"CHARLIE",
+                [0x04, 0x01, 0xCC]
+            )
+            .collect_vec()
+        );
+        assert_eq!(
+            set.serialize_with_options(
+                &key,
+                &options.force_length_form(ItemKey::Tag(3), LengthForm::Width(0))
+            ),
+            None
+        );
+    }
+
+    #[test]
+    fn diff_sets() {
+        let read = |items: Vec<u8>| {
+            let buf =
+                chain!([0x00; UNIVERSAL_KEY_LENGTH], [items.len() as u8], items).collect_vec();
+            LocalSet::read(0, Rc::new(RefCell::new(Cursor::new(buf)))).unwrap()
+        };
+        let old = read(
+            chain!(
+                [0x02, 0x01, 0x01],
+                [0x03, 0x02, b'A', b'B'],
+                [0x05, 0x02, 0x00, 0x00],
+                [0x41, 0x01, 0x11]
+            )
+            .collect_vec(),
+        );
+        let new = read(
+            chain!(
+                [0x02, 0x01, 0x02],
+                [0x05, 0x02, 0x80, 0x00],
+                [0x0A, 0x01, b'C'],
+                [0x41, 0x01, 0x11]
+            )
+            .collect_vec(),
+        );
+
+        let diff = old.diff(&new, &[ItemKey::Tag(2)]).unwrap();
+
+        assert_eq!(
+            *diff.entries(),
+            vec![
+                DiffEntry::new(
+                    ItemKey::Tag(3),
+                    DiffKind::Removed {
+                        old_bytes: b"AB".to_vec()
+                    }
+                ),
+                DiffEntry::new(
+                    ItemKey::Tag(5),
+                    DiffKind::Changed {
+                        old_bytes: vec![0x00, 0x00],
+                        new_bytes: vec![0x80, 0x00]
+                    }
+                ),
+                DiffEntry::new(
+                    ItemKey::Tag(10),
+                    DiffKind::Added {
+                        new_bytes: b"C".to_vec()
+                    }
+                ),
+            ]
+        );
+        assert_eq!(diff.to_string(), "- 3: 4142\n~ 5: 0000 -> 8000\n+ 10: 43\n");
+        assert_eq!(
+            diff.decoded::<St0601Tag>().to_string(),
+            "- 3 (Mission ID): \"AB\"\n~ 5 (Platform Heading Angle): 0 -> 180.00274662394142\n+ 10 (Platform Designation): \"C\"\n"
+        );
+    }
+
+    #[test]
+    fn merge_delta_into_full_set() {
+        let read = |items: Vec<u8>| {
+            let buf =
+                chain!([0x00; UNIVERSAL_KEY_LENGTH], [items.len() as u8], items).collect_vec();
+            LocalSet::read(0, Rc::new(RefCell::new(Cursor::new(buf)))).unwrap()
+        };
+        let full = read(
+            chain!(
+                [0x02, 0x01, 0x01],
+                [0x03, 0x01, 0xAA],
+                [0x03, 0x01, 0xBB],
+                [0x05, 0x01, 0xCC],
+                [0x01, 0x02, 0x00, 0x00]
+            )
+            .collect_vec(),
+        );
+        let delta =
+            read(chain!([0x02, 0x01, 0x02], [0x03, 0x01, 0xDD], [0x0A, 0x00]).collect_vec());
+
+        let merged = full.merged_with(&delta).unwrap();
 
-        Ok(Self { data: bmap })
+        assert_eq!(
+            *merged.items(),
+            vec![
+                OwnedKlv::new(2, vec![0x02]),
+                OwnedKlv::new(3, vec![0xDD]),
+                OwnedKlv::new(5, vec![0xCC]),
+                OwnedKlv::new(1, vec![0x00, 0x00]),
+                OwnedKlv::new(10, vec![]),
+            ]
+        );
     }
 }
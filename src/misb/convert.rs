@@ -0,0 +1,215 @@
+//! Conversion of legacy sets to the MISB standards that replaced them.
+
+use crate::{
+    encoding, keys,
+    klv::{ItemKey, OwnedKlv},
+    local_set::OwnedLocalSet,
+    misb::{eg0104::Eg0104Set, st0601::St0601Tag},
+    tag::Tag,
+    universal_set::UniversalKey,
+};
+
+/// Version of ST 0601 the converted sets claim to follow, matching the tag
+/// table they are encoded with.
+pub const ST0601_VERSION: u8 = 17;
+
+/// Why an item of a legacy set was left out of the converted set.
+#[derive(Debug)]
+pub enum UnconvertedReason {
+    /// The element has no equivalent in the newer standard.
+    NoEquivalent,
+    /// The value could not be read or does not fit the newer encoding, such
+    /// as an altitude outside the range ST 0601 maps.
+    Invalid(encoding::Error),
+}
+
+/// An item of a legacy set that was left out of the converted set.
+#[derive(Debug)]
+pub struct Unconverted {
+    pub key: ItemKey,
+    pub reason: UnconvertedReason,
+}
+
+/// ST 0601 set converted from a legacy set.
+#[derive(Debug, getset::Getters)]
+#[getset(get = "pub")]
+pub struct OwnedSt0601Set {
+    /// Converted items ordered by tag, without a checksum.
+    set: OwnedLocalSet,
+    /// Items of the legacy set that are not in `set`.
+    unconverted: Vec<Unconverted>,
+}
+
+impl OwnedSt0601Set {
+    /// Writes the set as a complete ST 0601 packet with a checksum.
+    pub fn serialize(&self) -> Vec<u8> {
+        self.set.serialize(&keys::ST0601, true)
+    }
+}
+
+/// Converts an EG 0104 universal set to an ST 0601 local set using the
+/// correspondence given by `Eg0104Tag::st0601`.
+///
+/// Values are carried over as software values and re-encoded with the
+/// ST 0601 mapping, so they are quantized to its precision. The UAS
+/// Datalink LS Version Number is added to the converted set, and a checksum
+/// is added when it is serialized.
+pub fn eg0104_to_st0601(legacy: &Eg0104Set) -> OwnedSt0601Set {
+    let mut items = Vec::new();
+    let mut unconverted = Vec::new();
+    for value in legacy.items().values() {
+        let tag = value.tag();
+        let key = ItemKey::Ul(UniversalKey::new((tag as u128).to_be_bytes()));
+        let Some(st0601) = tag.st0601() else {
+            unconverted.push(Unconverted {
+                key,
+                reason: UnconvertedReason::NoEquivalent,
+            });
+            continue;
+        };
+
+        let raw = legacy
+            .items()
+            .decode(tag)
+            .and_then(|decoded| decoded.ok_or(encoding::Error::SpecialValue { what: tag.name() }))
+            .and_then(|decoded| st0601.to_klv(&decoded));
+        match raw {
+            Ok(raw) => items.push((st0601, raw)),
+            Err(error) => unconverted.push(Unconverted {
+                key,
+                reason: UnconvertedReason::Invalid(error),
+            }),
+        }
+    }
+    unconverted.extend(legacy.items().unknown().keys().map(|key| Unconverted {
+        key: *key,
+        reason: UnconvertedReason::NoEquivalent,
+    }));
+
+    items.push((St0601Tag::UasDatalinkLsVersionNumber, vec![ST0601_VERSION]));
+    items.sort_by_key(|(tag, _)| *tag);
+
+    OwnedSt0601Set {
+        set: OwnedLocalSet::new(
+            items
+                .into_iter()
+                .map(|(tag, raw)| OwnedKlv::new(tag.number(), raw))
+                .collect(),
+        ),
+        unconverted,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::RefCell, io::Cursor, rc::Rc};
+
+    use super::*;
+    use crate::{
+        encoding::ber::encode_ber, format::SoftwareValue, misb::eg0104::Eg0104Tag,
+        options::ChecksumMode, options::ParseOptions, typed::TypedLocalSet,
+        universal_set::UniversalSet,
+    };
+    use itertools::{Itertools, chain};
+
+    fn legacy_set(items: &[(Eg0104Tag, Vec<u8>)]) -> Eg0104Set {
+        let value = items
+            .iter()
+            .flat_map(|(tag, value)| {
+                chain!(
+                    (*tag as u128).to_be_bytes(),
+                    [value.len() as u8],
+                    value.iter().copied()
+                )
+            })
+            .collect_vec();
+        let buf = chain!(*keys::EG0104, encode_ber(value.len() as u128), value).collect_vec();
+        let set =
+            UniversalSet::new(keys::EG0104, Rc::new(RefCell::new(Cursor::new(buf))), 0).unwrap();
+        Eg0104Set::from_local_set(set.data()).unwrap()
+    }
+
+    fn float(tag: St0601Tag, set: &TypedLocalSet<St0601Tag>) -> f64 {
+        match set.decode(tag).unwrap() {
+            Some(SoftwareValue::Float64(value)) => value,
+            other => panic!("{tag:?} is {other:?}"),
+        }
+    }
+
+    #[test]
+    fn converted_packet_is_valid_st0601() {
+        let legacy = legacy_set(&[
+            (
+                Eg0104Tag::UserDefinedTimeStamp,
+                1_000_000u64.to_be_bytes().to_vec(),
+            ),
+            (Eg0104Tag::DeviceLatitude, 38.8895f64.to_be_bytes().to_vec()),
+            (
+                Eg0104Tag::DeviceLongitude,
+                (-77.0353f64).to_be_bytes().to_vec(),
+            ),
+            (Eg0104Tag::DeviceAltitude, 1500.0f64.to_be_bytes().to_vec()),
+            (
+                Eg0104Tag::PlatformHeadingAngle,
+                270.0f32.to_be_bytes().to_vec(),
+            ),
+            (Eg0104Tag::AngleToNorth, 90.0f32.to_be_bytes().to_vec()),
+            (Eg0104Tag::EpisodeNumber, b"PRED42".to_vec()),
+        ]);
+        let converted = eg0104_to_st0601(&legacy);
+
+        let unconverted = converted
+            .unconverted()
+            .iter()
+            .map(|item| item.key)
+            .collect_vec();
+        assert_eq!(
+            unconverted,
+            [ItemKey::Ul(UniversalKey::new(
+                (Eg0104Tag::AngleToNorth as u128).to_be_bytes()
+            ))]
+        );
+
+        let options = ParseOptions {
+            verify_checksum: ChecksumMode::Reject,
+            ..Default::default()
+        };
+        let buf = Rc::new(RefCell::new(Cursor::new(converted.serialize())));
+        let set = UniversalSet::new_with_options(keys::ST0601, buf, 0, &options).unwrap();
+        assert!(set.data().validate::<St0601Tag>().is_valid());
+
+        let typed = TypedLocalSet::<St0601Tag>::from_local_set(set.data()).unwrap();
+        assert_eq!(
+            typed.decode(St0601Tag::PrecisionTimeStamp).unwrap(),
+            Some(SoftwareValue::Uint64(1_000_000))
+        );
+        assert_eq!(
+            typed.decode(St0601Tag::MissionId).unwrap(),
+            Some(SoftwareValue::String("PRED42".into()))
+        );
+        assert!((float(St0601Tag::SensorLatitude, &typed) - 38.8895).abs() < 1e-6);
+        assert!((float(St0601Tag::SensorLongitude, &typed) + 77.0353).abs() < 1e-6);
+        assert!((float(St0601Tag::SensorTrueAltitude, &typed) - 1500.0).abs() < 0.5);
+        assert!((float(St0601Tag::PlatformHeadingAngle, &typed) - 270.0).abs() < 0.01);
+    }
+
+    #[test]
+    fn out_of_range_value_is_flagged() {
+        let legacy = legacy_set(&[(Eg0104Tag::DeviceLatitude, 95.0f64.to_be_bytes().to_vec())]);
+        let converted = eg0104_to_st0601(&legacy);
+
+        assert!(matches!(
+            converted.unconverted().as_slice(),
+            [Unconverted {
+                reason: UnconvertedReason::Invalid(_),
+                ..
+            }]
+        ));
+        assert!(
+            converted
+                .set()
+                .get(&ItemKey::Tag(St0601Tag::SensorLatitude.into()))
+                .is_none()
+        );
+    }
+}
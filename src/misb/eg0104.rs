@@ -0,0 +1,215 @@
+//! MISB EG 0104 Predator UAV Basic Universal Set.
+//!
+//! The legacy predecessor of ST 0601. Every item is keyed by the full
+//! 16-byte Universal Label of its element in the SMPTE RP 210 metadata
+//! dictionary, which the tag table gives as a big-endian number. Positions
+//! and angles are IEEE 754 values in degrees and meters rather than the
+//! mapped integers of ST 0601.
+
+use std::io::{Read, Seek};
+
+use crate::{
+    encoding, format::SoftwareValue, local_set::LocalSet, misb::st0601::St0601Tag, tag_table,
+    typed::TypedLocalSet,
+};
+
+tag_table! {
+    /// Elements of the EG 0104 universal set, keyed by their Universal Labels.
+    #[repr(u128)]
+    pub enum Eg0104Tag {
+        UserDefinedTimeStamp = 0x060E2B34_01010103_07020101_01050000, "User Defined Time Stamp", Required(8);
+        EpisodeNumber = 0x060E2B34_01010101_01050500_00000000, "Episode Number";
+        PlatformDesignation = 0x060E2B34_01010101_01012001_00000000, "Platform Designation";
+        ImageSourceDevice = 0x060E2B34_01010101_04200102_01010000, "Image Source Device";
+        ImageCoordinateSystem = 0x060E2B34_01010101_07010101_00000000, "Image Coordinate System";
+        DeviceLatitude = 0x060E2B34_01010103_07010201_02040200, "Device Latitude", Required(8);
+        DeviceLongitude = 0x060E2B34_01010103_07010201_02060200, "Device Longitude", Required(8);
+        DeviceAltitude = 0x060E2B34_01010101_07010201_02020000, "Device Altitude";
+        FrameCenterLatitude = 0x060E2B34_01010101_07010201_03020000, "Frame Center Latitude", Required(8);
+        FrameCenterLongitude = 0x060E2B34_01010101_07010201_03040000, "Frame Center Longitude", Required(8);
+        CornerLatitudePoint1 = 0x060E2B34_01010103_07010201_03070100, "Corner Latitude Point 1", Required(8);
+        CornerLatitudePoint2 = 0x060E2B34_01010103_07010201_03080100, "Corner Latitude Point 2", Required(8);
+        CornerLatitudePoint3 = 0x060E2B34_01010103_07010201_03090100, "Corner Latitude Point 3", Required(8);
+        CornerLatitudePoint4 = 0x060E2B34_01010103_07010201_030A0100, "Corner Latitude Point 4", Required(8);
+        CornerLongitudePoint1 = 0x060E2B34_01010103_07010201_030B0100, "Corner Longitude Point 1", Required(8);
+        CornerLongitudePoint2 = 0x060E2B34_01010103_07010201_030C0100, "Corner Longitude Point 2", Required(8);
+        CornerLongitudePoint3 = 0x060E2B34_01010103_07010201_030D0100, "Corner Longitude Point 3", Required(8);
+        CornerLongitudePoint4 = 0x060E2B34_01010103_07010201_030E0100, "Corner Longitude Point 4", Required(8);
+        PlatformHeadingAngle = 0x060E2B34_01010107_07011001_06000000, "Platform Heading Angle";
+        PlatformPitchAngle = 0x060E2B34_01010107_07011001_05000000, "Platform Pitch Angle";
+        PlatformRollAngle = 0x060E2B34_01010107_07011001_04000000, "Platform Roll Angle";
+        AngleToNorth = 0x060E2B34_01010101_07011001_02000000, "Angle to North";
+        ObliquityAngle = 0x060E2B34_01010101_07011001_03000000, "Obliquity Angle";
+        FieldOfViewHorizontal = 0x060E2B34_01010102_04200201_01080000, "Field of View (FOV-Horizontal)";
+        FieldOfViewVertical = 0x060E2B34_01010107_04200201_010A0100, "Field of View (FOV-Vertical)";
+        SlantRange = 0x060E2B34_01010101_07010801_01000000, "Slant Range";
+        TargetWidth = 0x060E2B34_01010101_07010902_01000000, "Target Width";
+    }
+
+    required: [UserDefinedTimeStamp]
+
+    formats: {
+        UserDefinedTimeStamp => Uint as Uint64,
+        EpisodeNumber => Iso7 as String,
+        PlatformDesignation => Iso7 as String,
+        ImageSourceDevice => Iso7 as String,
+        ImageCoordinateSystem => Iso7 as String,
+        DeviceLatitude => Float as Float64,
+        DeviceLongitude => Float as Float64,
+        DeviceAltitude => Float as Float64,
+        FrameCenterLatitude => Float as Float64,
+        FrameCenterLongitude => Float as Float64,
+        CornerLatitudePoint1 => Float as Float64,
+        CornerLatitudePoint2 => Float as Float64,
+        CornerLatitudePoint3 => Float as Float64,
+        CornerLatitudePoint4 => Float as Float64,
+        CornerLongitudePoint1 => Float as Float64,
+        CornerLongitudePoint2 => Float as Float64,
+        CornerLongitudePoint3 => Float as Float64,
+        CornerLongitudePoint4 => Float as Float64,
+        PlatformHeadingAngle => Float as Float64,
+        PlatformPitchAngle => Float as Float64,
+        PlatformRollAngle => Float as Float64,
+        AngleToNorth => Float as Float64,
+        ObliquityAngle => Float as Float64,
+        FieldOfViewHorizontal => Float as Float64,
+        FieldOfViewVertical => Float as Float64,
+        SlantRange => Float as Float64,
+        TargetWidth => Float as Float64,
+    }
+}
+
+impl Eg0104Tag {
+    /// ST 0601 tag carrying the same element, if there is one.
+    ///
+    /// Both tags convert to the same software value, in the same units, so a
+    /// value can be read through either table.
+    pub fn st0601(&self) -> Option<St0601Tag> {
+        Some(match self {
+            Self::UserDefinedTimeStamp => St0601Tag::PrecisionTimeStamp,
+            Self::EpisodeNumber => St0601Tag::MissionId,
+            Self::PlatformDesignation => St0601Tag::PlatformDesignation,
+            Self::ImageSourceDevice => St0601Tag::ImageSourceSensor,
+            Self::ImageCoordinateSystem => St0601Tag::ImageCoordinateSystem,
+            Self::DeviceLatitude => St0601Tag::SensorLatitude,
+            Self::DeviceLongitude => St0601Tag::SensorLongitude,
+            Self::DeviceAltitude => St0601Tag::SensorTrueAltitude,
+            Self::FrameCenterLatitude => St0601Tag::FrameCenterLatitude,
+            Self::FrameCenterLongitude => St0601Tag::FrameCenterLongitude,
+            Self::CornerLatitudePoint1 => St0601Tag::CornerLatitudePoint1Full,
+            Self::CornerLatitudePoint2 => St0601Tag::CornerLatitudePoint2Full,
+            Self::CornerLatitudePoint3 => St0601Tag::CornerLatitudePoint3Full,
+            Self::CornerLatitudePoint4 => St0601Tag::CornerLatitudePoint4Full,
+            Self::CornerLongitudePoint1 => St0601Tag::CornerLongitudePoint1Full,
+            Self::CornerLongitudePoint2 => St0601Tag::CornerLongitudePoint2Full,
+            Self::CornerLongitudePoint3 => St0601Tag::CornerLongitudePoint3Full,
+            Self::CornerLongitudePoint4 => St0601Tag::CornerLongitudePoint4Full,
+            Self::PlatformHeadingAngle => St0601Tag::PlatformHeadingAngle,
+            Self::PlatformPitchAngle => St0601Tag::PlatformPitchAngle,
+            Self::PlatformRollAngle => St0601Tag::PlatformRollAngle,
+            Self::FieldOfViewHorizontal => St0601Tag::SensorHorizontalFieldOfView,
+            Self::FieldOfViewVertical => St0601Tag::SensorVerticalFieldOfView,
+            Self::SlantRange => St0601Tag::SlantRange,
+            Self::TargetWidth => St0601Tag::TargetWidth,
+            Self::AngleToNorth | Self::ObliquityAngle => return None,
+        })
+    }
+}
+
+/// Values of an EG 0104 universal set.
+#[derive(Debug, getset::Getters)]
+pub struct Eg0104Set {
+    /// Every item in the set, keyed by its EG 0104 element.
+    #[getset(get = "pub")]
+    items: TypedLocalSet<Eg0104Tag>,
+}
+
+impl Eg0104Set {
+    /// Reads the items of a set parsed as a universal set, such as the data of
+    /// a `UniversalSet` keyed by `keys::EG0104`.
+    pub fn from_local_set<T>(set: &LocalSet<T>) -> Result<Self, encoding::Error>
+    where
+        T: Read + Seek,
+    {
+        Ok(Self {
+            items: TypedLocalSet::from_local_set(set)?,
+        })
+    }
+
+    /// Value of the EG 0104 element that carries what `tag` carries in
+    /// ST 0601, converted the way the ST 0601 path converts `tag`.
+    ///
+    /// # Returns
+    ///
+    /// - `Ok(Some(SoftwareValue))` - The element is in the set.
+    /// - `Ok(None)` - No element in the set corresponds to `tag`.
+    /// - `Err(encoding::Error)` - The element could not be converted.
+    pub fn get(&self, tag: St0601Tag) -> Result<Option<SoftwareValue>, encoding::Error> {
+        match self
+            .items
+            .values()
+            .find(|value| value.tag().st0601() == Some(tag))
+        {
+            Some(value) => self.items.decode(value.tag()),
+            None => Ok(None),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::RefCell, io::Cursor, rc::Rc};
+
+    use super::*;
+    use crate::{keys, universal_set::UniversalSet};
+    use itertools::{Itertools, chain};
+
+    /// Encodes an item of a universal set.
+    fn item(tag: Eg0104Tag, value: &[u8]) -> Vec<u8> {
+        chain!(
+            (tag as u128).to_be_bytes(),
+            [value.len() as u8],
+            value.iter().copied()
+        )
+        .collect_vec()
+    }
+
+    #[test]
+    fn decodes_position() {
+        let value = chain!(
+            item(Eg0104Tag::UserDefinedTimeStamp, &1_000_000u64.to_be_bytes()),
+            item(Eg0104Tag::DeviceLatitude, &38.8895f64.to_be_bytes()),
+            item(Eg0104Tag::DeviceLongitude, &(-77.0353f64).to_be_bytes()),
+            item(Eg0104Tag::EpisodeNumber, b"PRED42"),
+            item(Eg0104Tag::AngleToNorth, &90.0f32.to_be_bytes())
+        )
+        .collect_vec();
+        let buf = chain!(*keys::EG0104, [value.len() as u8], value).collect_vec();
+        let set =
+            UniversalSet::new(keys::EG0104, Rc::new(RefCell::new(Cursor::new(buf))), 0).unwrap();
+        let set = Eg0104Set::from_local_set(set.data()).unwrap();
+
+        assert_eq!(
+            set.get(St0601Tag::SensorLatitude).unwrap(),
+            Some(SoftwareValue::Float64(38.8895))
+        );
+        assert_eq!(
+            set.get(St0601Tag::SensorLongitude).unwrap(),
+            Some(SoftwareValue::Float64(-77.0353))
+        );
+        assert_eq!(
+            set.get(St0601Tag::PrecisionTimeStamp).unwrap(),
+            Some(SoftwareValue::Uint64(1_000_000))
+        );
+        assert_eq!(
+            set.get(St0601Tag::MissionId).unwrap(),
+            Some(SoftwareValue::String("PRED42".into()))
+        );
+        assert_eq!(set.get(St0601Tag::FrameCenterLatitude).unwrap(), None);
+        assert_eq!(
+            set.items().decode(Eg0104Tag::AngleToNorth).unwrap(),
+            Some(SoftwareValue::Float64(90.0))
+        );
+        assert!(set.items().unknown().is_empty());
+    }
+}
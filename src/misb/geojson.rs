@@ -0,0 +1,95 @@
+//! GeoJSON tracks of where ST 0601 sets say the platform was and where its
+//! sensor was pointed.
+
+use geojson::{Feature, FeatureCollection, Geometry, JsonObject, JsonValue, Value};
+
+use crate::{format::SoftwareValue, misb::st0601::St0601Tag, typed::TypedLocalSet};
+
+/// Value of `tag` as a float, or `None` when it is missing, one of its
+/// special values such as the out-of-range sentinel, or can't be converted.
+fn float(set: &TypedLocalSet<St0601Tag>, tag: St0601Tag) -> Option<f64> {
+    match set.decode(tag) {
+        Ok(Some(SoftwareValue::Float64(value))) => Some(value),
+        _ => None,
+    }
+}
+
+fn timestamp(set: &TypedLocalSet<St0601Tag>) -> Option<u64> {
+    match set.decode(St0601Tag::PrecisionTimeStamp) {
+        Ok(Some(SoftwareValue::Uint64(timestamp))) => Some(timestamp),
+        _ => None,
+    }
+}
+
+/// GeoJSON position of the given coordinates, with the altitude only when it
+/// is known.
+fn position(latitude: f64, longitude: f64, altitude: Option<f64>) -> Vec<f64> {
+    let mut position = vec![longitude, latitude];
+    position.extend(altitude);
+    position
+}
+
+fn feature(value: Value, properties: JsonObject) -> Feature {
+    Feature {
+        bbox: None,
+        geometry: Some(Geometry::new(value)),
+        id: None,
+        properties: Some(properties),
+        foreign_members: None,
+    }
+}
+
+/// Builds a map overlay from a sequence of ST 0601 sets.
+///
+/// The collection holds a `LineString` of the sensor's position in every set
+/// that has one, followed by a `Point` for the frame center of every set
+/// that has one. The Precision Time Stamp of each position is in the
+/// `timestamps` property of the track, in the same order as its coordinates,
+/// and in the `timestamp` property of each frame center, as microseconds
+/// since the epoch. Sets whose coordinates are missing or hold the
+/// out-of-range sentinel are skipped.
+pub fn track_from_sets<'a>(
+    sets: impl IntoIterator<Item = &'a TypedLocalSet<St0601Tag>>,
+) -> FeatureCollection {
+    let mut track = Vec::new();
+    let mut track_times = Vec::new();
+    let mut frame_centers = Vec::new();
+    for set in sets {
+        let time = JsonValue::from(timestamp(set));
+        if let (Some(latitude), Some(longitude)) = (
+            float(set, St0601Tag::SensorLatitude),
+            float(set, St0601Tag::SensorLongitude),
+        ) {
+            let altitude = float(set, St0601Tag::SensorTrueAltitude);
+            track.push(position(latitude, longitude, altitude));
+            track_times.push(time.clone());
+        }
+        if let (Some(latitude), Some(longitude)) = (
+            float(set, St0601Tag::FrameCenterLatitude),
+            float(set, St0601Tag::FrameCenterLongitude),
+        ) {
+            let elevation = float(set, St0601Tag::FrameCenterElevation);
+            let properties = JsonObject::from_iter([("timestamp".to_string(), time)]);
+            frame_centers.push(feature(
+                Value::Point(position(latitude, longitude, elevation)),
+                properties,
+            ));
+        }
+    }
+
+    let mut features = Vec::new();
+    if !track.is_empty() {
+        let properties = JsonObject::from_iter([
+            ("name".to_string(), JsonValue::from("Platform track")),
+            ("timestamps".to_string(), JsonValue::Array(track_times)),
+        ]);
+        features.push(feature(Value::LineString(track), properties));
+    }
+    features.extend(frame_centers);
+
+    FeatureCollection {
+        bbox: None,
+        features,
+        foreign_members: None,
+    }
+}
@@ -0,0 +1,13 @@
+//! Tag tables for MISB standards.
+
+pub mod convert;
+pub mod eg0104;
+#[cfg(feature = "geo")]
+pub mod geojson;
+pub mod st0102;
+pub mod st0601;
+pub mod st0808;
+pub mod st1108;
+pub mod st1204;
+pub mod st1303;
+pub mod st1607;
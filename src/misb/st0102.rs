@@ -0,0 +1,273 @@
+//! MISB ST 0102 Security Metadata Local Set.
+//!
+//! Tag numbers, names, and lengths are taken from _Table 2_ of
+//! _MISB ST 0102.12_. The set is carried in the value of tag 48 of ST 0601.
+
+use crate::{
+    encoding::{
+        self,
+        date::{Date, decode_iso_date},
+    },
+    format::SoftwareValue,
+    tag_table,
+    typed::TypedLocalSet,
+};
+
+tag_table! {
+    /// Tags of the ST 0102 Security Metadata Local Set.
+    #[repr(u8)]
+    pub enum St0102Tag {
+        SecurityClassification = 1, "Security Classification", Required(1);
+        CountryCodingMethod = 2, "Classifying Country and Releasing Instructions Country Coding Method", Required(1);
+        ClassifyingCountry = 3, "Classifying Country", Max(6);
+        SciShiInformation = 4, "Security-SCI/SHI Information", Max(40);
+        Caveats = 5, "Caveats", Max(32);
+        ReleasingInstructions = 6, "Releasing Instructions", Max(40);
+        ClassifiedBy = 7, "Classified By", Max(40);
+        DerivedFrom = 8, "Derived From", Max(40);
+        ClassificationReason = 9, "Classification Reason", Max(40);
+        DeclassificationDate = 10, "Declassification Date", Required(8);
+        ClassificationAndMarkingSystem = 11, "Classification and Marking System", Max(40);
+        ObjectCountryCodingMethod = 12, "Object Country Coding Method", Required(1);
+        ObjectCountryCodes = 13, "Object Country Codes", Max(40);
+        ClassificationComments = 14, "Classification Comments", Max(480);
+        UmidVideo = 15, "UMID Video", Required(32);
+        UmidAudio = 16, "UMID Audio", Required(32);
+        UmidData = 17, "UMID Data", Required(32);
+        UmidSystem = 18, "UMID System", Required(32);
+        StreamId = 19, "Stream ID", Required(1);
+        TransportStreamId = 20, "Transport Stream ID", Required(2);
+        ItemDesignatorId = 21, "Item Designator ID", Required(16);
+        Version = 22, "Version", Required(2);
+        CountryCodingMethodVersionDate = 23, "Classifying Country and Releasing Instructions Country Coding Method Version Date", Max(10);
+        ObjectCountryCodingMethodVersionDate = 24, "Object Country Coding Method Version Date", Max(10);
+    }
+
+    required: [
+        SecurityClassification,
+        CountryCodingMethod,
+        ClassifyingCountry,
+        ObjectCountryCodingMethod,
+        ObjectCountryCodes,
+        Version,
+    ]
+
+    formats: {
+        SecurityClassification => Uint as Uint64,
+        CountryCodingMethod => Uint as Uint64,
+        ClassifyingCountry => Iso7 as String,
+        SciShiInformation => Iso7 as String,
+        Caveats => Iso7 as String,
+        ReleasingInstructions => Iso7 as String,
+        ClassifiedBy => Iso7 as String,
+        DerivedFrom => Iso7 as String,
+        ClassificationReason => Iso7 as String,
+        DeclassificationDate => Iso7 as String,
+        ClassificationAndMarkingSystem => Iso7 as String,
+        ObjectCountryCodingMethod => Uint as Uint64,
+        ClassificationComments => Iso7 as String,
+        StreamId => Uint as Uint64,
+        TransportStreamId => Uint as Uint64,
+        Version => Uint as Uint64,
+        CountryCodingMethodVersionDate => Iso7 as String,
+        ObjectCountryCodingMethodVersionDate => Iso7 as String,
+    }
+}
+
+/// Overall classification of the data, as given by the Security
+/// Classification item.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Classification {
+    Unclassified = 1,
+    Restricted = 2,
+    Confidential = 3,
+    Secret = 4,
+    TopSecret = 5,
+}
+
+impl TryFrom<u64> for Classification {
+    type Error = encoding::Error;
+
+    fn try_from(value: u64) -> Result<Self, Self::Error> {
+        Ok(match value {
+            1 => Self::Unclassified,
+            2 => Self::Restricted,
+            3 => Self::Confidential,
+            4 => Self::Secret,
+            5 => Self::TopSecret,
+            _ => {
+                return Err(encoding::Error::ValueOutOfRange {
+                    what: "a security classification".into(),
+                    value: format!("{value:#04x}"),
+                });
+            }
+        })
+    }
+}
+
+/// ST 0102 Security Metadata Local Set.
+///
+/// The items most often needed are read into fields. Every other item is
+/// available through `SecurityLocalSet::items`.
+#[derive(Debug, getset::CopyGetters, getset::Getters)]
+pub struct SecurityLocalSet {
+    #[getset(get_copy = "pub")]
+    classification: Classification,
+    /// Country that classified the data, such as `//USA`.
+    #[getset(get = "pub")]
+    classifying_country: Option<String>,
+    /// Countries the data may be released to, such as `USA GBR`.
+    #[getset(get = "pub")]
+    releasing_instructions: Option<String>,
+    /// Countries whose objects are shown in the data, decoded from UTF-16.
+    #[getset(get = "pub")]
+    object_country_codes: Option<String>,
+    /// Date after which the data is no longer classified.
+    #[getset(get_copy = "pub")]
+    declassification_date: Option<Date>,
+    #[getset(get_copy = "pub")]
+    version: Option<u64>,
+    /// Version date of the Country Coding Method.
+    #[getset(get_copy = "pub")]
+    country_coding_method_version_date: Option<Date>,
+    /// Version date of the Object Country Coding Method.
+    #[getset(get_copy = "pub")]
+    object_country_coding_method_version_date: Option<Date>,
+    /// Every item in the set.
+    #[getset(get = "pub")]
+    items: TypedLocalSet<St0102Tag>,
+}
+
+impl SecurityLocalSet {
+    /// Reads the set out of the value of the item carrying it.
+    ///
+    /// Offsets in any error are relative to the first byte of `raw`.
+    ///
+    /// # Returns
+    ///
+    /// - `Ok(SecurityLocalSet)` - The set was read.
+    /// - `Err(encoding::Error)` - An item could not be read, the Security
+    ///   Classification item is missing or invalid, or a date item is not a
+    ///   valid ISO 8601 date.
+    pub fn from_value(raw: &[u8]) -> Result<Self, encoding::Error> {
+        let items = TypedLocalSet::<St0102Tag>::from_value(raw)?;
+
+        let classification = match items.decode(St0102Tag::SecurityClassification)? {
+            Some(SoftwareValue::Uint64(value)) => Classification::try_from(value)?,
+            _ => {
+                return Err(encoding::Error::MissingItem {
+                    set: "Security set",
+                    item: "Security Classification",
+                });
+            }
+        };
+        let string = |tag| -> Result<Option<String>, encoding::Error> {
+            Ok(match items.decode(tag)? {
+                Some(SoftwareValue::String(value)) => Some(value),
+                _ => None,
+            })
+        };
+        let date = |tag| {
+            items
+                .raw(tag)
+                .filter(|raw| !raw.is_empty())
+                .map(decode_iso_date)
+                .transpose()
+        };
+        let object_country_codes = items
+            .raw(St0102Tag::ObjectCountryCodes)
+            .map(decode_utf16)
+            .transpose()?;
+        let version = match items.decode(St0102Tag::Version)? {
+            Some(SoftwareValue::Uint64(version)) => Some(version),
+            _ => None,
+        };
+
+        Ok(Self {
+            classification,
+            classifying_country: string(St0102Tag::ClassifyingCountry)?,
+            releasing_instructions: string(St0102Tag::ReleasingInstructions)?,
+            object_country_codes,
+            declassification_date: date(St0102Tag::DeclassificationDate)?,
+            version,
+            country_coding_method_version_date: date(St0102Tag::CountryCodingMethodVersionDate)?,
+            object_country_coding_method_version_date: date(
+                St0102Tag::ObjectCountryCodingMethodVersionDate,
+            )?,
+            items,
+        })
+    }
+}
+
+/// Decodes big-endian UTF-16, as used by the Object Country Codes item.
+fn decode_utf16(raw: &[u8]) -> Result<String, encoding::Error> {
+    if !raw.len().is_multiple_of(2) {
+        return Err(encoding::Error::InvalidValueLength {
+            what: "UTF-16 string",
+            length: raw.len() as u64,
+        });
+    }
+    let mut offset = 0;
+    char::decode_utf16(
+        raw.chunks_exact(2)
+            .map(|pair| u16::from_be_bytes([pair[0], pair[1]])),
+    )
+    .map(|decoded| {
+        let decoded = decoded.map_err(|_| encoding::Error::InvalidCharacter {
+            what: "UTF-16 string",
+            offset,
+        })?;
+        offset += 2 * decoded.len_utf16();
+        Ok(decoded)
+    })
+    .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_case::test_case;
+
+    #[test_case(&[0x00, 0x55, 0x00, 0x53], Some("US"); "Two characters")]
+    #[test_case(&[], Some(""); "Empty")]
+    #[test_case(&[0x00], None; "Odd length")]
+    #[test_case(&[0xD8, 0x00], None; "Unpaired surrogate")]
+    fn test_decode_utf16(raw: &[u8], expected: Option<&str>) {
+        assert_eq!(decode_utf16(raw).ok().as_deref(), expected);
+    }
+
+    #[test]
+    fn missing_classification_is_an_error() {
+        let raw = [0x03, 0x05, b'/', b'/', b'U', b'S', b'A'];
+        assert!(SecurityLocalSet::from_value(&raw).is_err());
+    }
+
+    #[test]
+    fn invalid_classification_is_an_error() {
+        assert!(SecurityLocalSet::from_value(&[0x01, 0x01, 0x06]).is_err());
+    }
+
+    #[test_case(b"20301231"; "Compact")]
+    #[test_case(b"2030-12-31"; "Extended")]
+    fn dates_are_read(date: &[u8]) {
+        let mut raw = vec![0x01, 0x01, 0x01, 0x0A, date.len() as u8];
+        raw.extend_from_slice(date);
+        raw.extend_from_slice(&[0x17, 0x0A]);
+        raw.extend_from_slice(b"2006-11-28");
+        let set = SecurityLocalSet::from_value(&raw).unwrap();
+
+        assert_eq!(set.declassification_date(), Date::new(2030, 12, 31));
+        assert_eq!(
+            set.country_coding_method_version_date(),
+            Date::new(2006, 11, 28)
+        );
+        assert_eq!(set.object_country_coding_method_version_date(), None);
+    }
+
+    #[test]
+    fn invalid_date_is_an_error() {
+        let mut raw = vec![0x01, 0x01, 0x01, 0x0A, 0x08];
+        raw.extend_from_slice(b"20300230");
+        assert!(SecurityLocalSet::from_value(&raw).is_err());
+    }
+}
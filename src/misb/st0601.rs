@@ -0,0 +1,815 @@
+//! MISB ST 0601 UAS Datalink Local Set.
+//!
+//! Tag numbers, names, lengths, mappings, and units are taken from _Table 1_
+//! of _MISB ST 0601.17_.
+
+use std::io::{Read, Seek};
+
+use crate::{
+    encoding,
+    format::SoftwareValue,
+    klv::ItemKey,
+    local_set::LocalSet,
+    misb::{st0102::SecurityLocalSet, st1204::CoreIdentifier},
+    tag::TagReading,
+    tag_table,
+    typed::TypedLocalSet,
+};
+
+/// Computes the checksum of an ST 0601 packet.
+///
+/// The checksum is a running 16-bit sum of the packet taken two bytes at a
+/// time, from the first byte of the Universal Key through the length of the
+/// checksum item.
+pub fn checksum(bytes: &[u8]) -> u16 {
+    bytes.iter().enumerate().fold(0u16, |sum, (i, byte)| {
+        sum.wrapping_add(u16::from(*byte) << (8 * ((i + 1) % 2)))
+    })
+}
+
+tag_table! {
+    /// Tags of the ST 0601 UAS Datalink Local Set.
+    #[repr(u8)]
+    pub enum St0601Tag {
+        Checksum = 1, "Checksum", Required(2);
+        PrecisionTimeStamp = 2, "Precision Time Stamp", Required(8);
+        MissionId = 3, "Mission ID", Max(127);
+        PlatformTailNumber = 4, "Platform Tail Number", Max(127);
+        PlatformHeadingAngle = 5, "Platform Heading Angle", Required(2);
+        PlatformPitchAngle = 6, "Platform Pitch Angle", Required(2);
+        PlatformRollAngle = 7, "Platform Roll Angle", Required(2);
+        PlatformTrueAirspeed = 8, "Platform True Airspeed", Required(1);
+        PlatformIndicatedAirspeed = 9, "Platform Indicated Airspeed", Required(1);
+        PlatformDesignation = 10, "Platform Designation", Max(127);
+        ImageSourceSensor = 11, "Image Source Sensor", Max(127);
+        ImageCoordinateSystem = 12, "Image Coordinate System", Max(127);
+        SensorLatitude = 13, "Sensor Latitude", Required(4);
+        SensorLongitude = 14, "Sensor Longitude", Required(4);
+        SensorTrueAltitude = 15, "Sensor True Altitude", Required(2);
+        SensorHorizontalFieldOfView = 16, "Sensor Horizontal Field of View", Required(2);
+        SensorVerticalFieldOfView = 17, "Sensor Vertical Field of View", Required(2);
+        SensorRelativeAzimuthAngle = 18, "Sensor Relative Azimuth Angle", Required(4);
+        SensorRelativeElevationAngle = 19, "Sensor Relative Elevation Angle", Required(4);
+        SensorRelativeRollAngle = 20, "Sensor Relative Roll Angle", Required(4);
+        SlantRange = 21, "Slant Range", Required(4);
+        TargetWidth = 22, "Target Width", Required(2);
+        FrameCenterLatitude = 23, "Frame Center Latitude", Required(4);
+        FrameCenterLongitude = 24, "Frame Center Longitude", Required(4);
+        FrameCenterElevation = 25, "Frame Center Elevation", Required(2);
+        OffsetCornerLatitudePoint1 = 26, "Offset Corner Latitude Point 1", Required(2);
+        OffsetCornerLongitudePoint1 = 27, "Offset Corner Longitude Point 1", Required(2);
+        OffsetCornerLatitudePoint2 = 28, "Offset Corner Latitude Point 2", Required(2);
+        OffsetCornerLongitudePoint2 = 29, "Offset Corner Longitude Point 2", Required(2);
+        OffsetCornerLatitudePoint3 = 30, "Offset Corner Latitude Point 3", Required(2);
+        OffsetCornerLongitudePoint3 = 31, "Offset Corner Longitude Point 3", Required(2);
+        OffsetCornerLatitudePoint4 = 32, "Offset Corner Latitude Point 4", Required(2);
+        OffsetCornerLongitudePoint4 = 33, "Offset Corner Longitude Point 4", Required(2);
+        IcingDetected = 34, "Icing Detected", Required(1);
+        WindDirection = 35, "Wind Direction", Required(2);
+        WindSpeed = 36, "Wind Speed", Required(1);
+        StaticPressure = 37, "Static Pressure", Required(2);
+        DensityAltitude = 38, "Density Altitude", Required(2);
+        OutsideAirTemperature = 39, "Outside Air Temperature", Required(1);
+        TargetLocationLatitude = 40, "Target Location Latitude", Required(4);
+        TargetLocationLongitude = 41, "Target Location Longitude", Required(4);
+        TargetLocationElevation = 42, "Target Location Elevation", Required(2);
+        TargetTrackGateWidth = 43, "Target Track Gate Width", Required(1);
+        TargetTrackGateHeight = 44, "Target Track Gate Height", Required(1);
+        TargetErrorEstimateCe90 = 45, "Target Error Estimate - CE90", Required(2);
+        TargetErrorEstimateLe90 = 46, "Target Error Estimate - LE90", Required(2);
+        GenericFlagData = 47, "Generic Flag Data", Required(1);
+        SecurityLocalSet = 48, "Security Local Set";
+        DifferentialPressure = 49, "Differential Pressure", Required(2);
+        PlatformAngleOfAttack = 50, "Platform Angle of Attack", Required(2);
+        PlatformVerticalSpeed = 51, "Platform Vertical Speed", Required(2);
+        PlatformSideslipAngle = 52, "Platform Sideslip Angle", Required(2);
+        AirfieldBarometricPressure = 53, "Airfield Barometric Pressure", Required(2);
+        AirfieldElevation = 54, "Airfield Elevation", Required(2);
+        RelativeHumidity = 55, "Relative Humidity", Required(1);
+        PlatformGroundSpeed = 56, "Platform Ground Speed", Required(1);
+        GroundRange = 57, "Ground Range", Required(4);
+        PlatformFuelRemaining = 58, "Platform Fuel Remaining", Required(2);
+        PlatformCallSign = 59, "Platform Call Sign", Max(127);
+        WeaponLoad = 60, "Weapon Load", Required(2);
+        WeaponFired = 61, "Weapon Fired", Required(1);
+        LaserPrfCode = 62, "Laser PRF Code", Required(2);
+        SensorFieldOfViewName = 63, "Sensor Field of View Name", Required(1);
+        PlatformMagneticHeading = 64, "Platform Magnetic Heading", Required(2);
+        UasDatalinkLsVersionNumber = 65, "UAS Datalink LS Version Number", Required(1);
+        TargetLocationCovarianceMatrix = 66, "Target Location Covariance Matrix (deprecated)";
+        AlternatePlatformLatitude = 67, "Alternate Platform Latitude", Required(4);
+        AlternatePlatformLongitude = 68, "Alternate Platform Longitude", Required(4);
+        AlternatePlatformAltitude = 69, "Alternate Platform Altitude", Required(2);
+        AlternatePlatformName = 70, "Alternate Platform Name", Max(127);
+        AlternatePlatformHeading = 71, "Alternate Platform Heading", Required(2);
+        EventStartTimeUtc = 72, "Event Start Time - UTC", Required(8);
+        RvtLocalSet = 73, "RVT Local Set";
+        VmtiLocalSet = 74, "VMTI Local Set";
+        SensorEllipsoidHeight = 75, "Sensor Ellipsoid Height", Required(2);
+        AlternatePlatformEllipsoidHeight = 76, "Alternate Platform Ellipsoid Height", Required(2);
+        OperationalMode = 77, "Operational Mode", Required(1);
+        FrameCenterHeightAboveEllipsoid = 78, "Frame Center Height Above Ellipsoid", Required(2);
+        SensorNorthVelocity = 79, "Sensor North Velocity", Required(2);
+        SensorEastVelocity = 80, "Sensor East Velocity", Required(2);
+        ImageHorizonPixelPack = 81, "Image Horizon Pixel Pack";
+        CornerLatitudePoint1Full = 82, "Corner Latitude Point 1 (Full)", Required(4);
+        CornerLongitudePoint1Full = 83, "Corner Longitude Point 1 (Full)", Required(4);
+        CornerLatitudePoint2Full = 84, "Corner Latitude Point 2 (Full)", Required(4);
+        CornerLongitudePoint2Full = 85, "Corner Longitude Point 2 (Full)", Required(4);
+        CornerLatitudePoint3Full = 86, "Corner Latitude Point 3 (Full)", Required(4);
+        CornerLongitudePoint3Full = 87, "Corner Longitude Point 3 (Full)", Required(4);
+        CornerLatitudePoint4Full = 88, "Corner Latitude Point 4 (Full)", Required(4);
+        CornerLongitudePoint4Full = 89, "Corner Longitude Point 4 (Full)", Required(4);
+        PlatformPitchAngleFull = 90, "Platform Pitch Angle (Full)", Required(4);
+        PlatformRollAngleFull = 91, "Platform Roll Angle (Full)", Required(4);
+        PlatformAngleOfAttackFull = 92, "Platform Angle of Attack (Full)", Required(4);
+        PlatformSideslipAngleFull = 93, "Platform Sideslip Angle (Full)", Required(4);
+        MiisCoreIdentifier = 94, "MIIS Core Identifier", Max(50);
+        SarMotionImageryLocalSet = 95, "SAR Motion Imagery Local Set";
+        TargetWidthExtended = 96, "Target Width Extended";
+        RangeImageLocalSet = 97, "Range Image Local Set";
+        GeoRegistrationLocalSet = 98, "Geo-Registration Local Set";
+        CompositeImagingLocalSet = 99, "Composite Imaging Local Set";
+        SegmentLocalSet = 100, "Segment Local Set";
+        AmendLocalSet = 101, "Amend Local Set";
+        SdccFlp = 102, "SDCC-FLP";
+        DensityAltitudeExtended = 103, "Density Altitude Extended";
+        SensorEllipsoidHeightExtended = 104, "Sensor Ellipsoid Height Extended";
+        AlternatePlatformEllipsoidHeightExtended = 105, "Alternate Platform Ellipsoid Height Extended";
+        StreamDesignator = 106, "Stream Designator", Max(127);
+        OperationalBase = 107, "Operational Base", Max(127);
+        BroadcastSource = 108, "Broadcast Source", Max(127);
+        RangeToRecoveryLocation = 109, "Range To Recovery Location";
+        TimeAirborne = 110, "Time Airborne";
+        PropulsionUnitSpeed = 111, "Propulsion Unit Speed";
+        PlatformCourseAngle = 112, "Platform Course Angle";
+        AltitudeAgl = 113, "Altitude AGL";
+        RadarAltimeter = 114, "Radar Altimeter";
+        ControlCommand = 115, "Control Command";
+        ControlCommandVerificationList = 116, "Control Command Verification List";
+        SensorAzimuthRate = 117, "Sensor Azimuth Rate";
+        SensorElevationRate = 118, "Sensor Elevation Rate";
+        SensorRollRate = 119, "Sensor Roll Rate";
+        OnBoardMiStoragePercentFull = 120, "On-board MI Storage Percent Full";
+        ActiveWavelengthList = 121, "Active Wavelength List";
+        CountryCodes = 122, "Country Codes";
+        NumberOfNavsatsInView = 123, "Number of NAVSATs in View";
+        PositioningMethodSource = 124, "Positioning Method Source";
+        PlatformStatus = 125, "Platform Status";
+        SensorControlMode = 126, "Sensor Control Mode";
+        SensorFrameRatePack = 127, "Sensor Frame Rate Pack";
+        WavelengthsList = 128, "Wavelengths List";
+        TargetId = 129, "Target ID";
+        AirbaseLocations = 130, "Airbase Locations";
+        TakeOffTime = 131, "Take-off Time";
+        TransmissionFrequency = 132, "Transmission Frequency";
+        OnBoardMiStorageCapacity = 133, "On-board MI Storage Capacity";
+        ZoomPercentage = 134, "Zoom Percentage";
+        CommunicationsMethod = 135, "Communications Method", Max(127);
+        LeapSeconds = 136, "Leap Seconds";
+        CorrectionOffset = 137, "Correction Offset";
+        PayloadList = 138, "Payload List";
+        ActivePayloads = 139, "Active Payloads";
+        WeaponsStores = 140, "Weapons Stores";
+        WaypointList = 141, "Waypoint List";
+        ViewDomain = 142, "View Domain";
+        MetadataSubstreamIdPack = 143, "Metadata Substream ID Pack";
+    }
+
+    required: [Checksum, PrecisionTimeStamp, UasDatalinkLsVersionNumber]
+
+    special_values: {
+        PlatformPitchAngle => [[0x80, 0x00] => "Out of Range"],
+        PlatformRollAngle => [[0x80, 0x00] => "Out of Range"],
+        SensorLatitude => [[0x80, 0x00, 0x00, 0x00] => "N/A (Off-Earth)"],
+        SensorLongitude => [[0x80, 0x00, 0x00, 0x00] => "N/A (Off-Earth)"],
+        SensorRelativeElevationAngle => [[0x80, 0x00, 0x00, 0x00] => "Reserved"],
+        FrameCenterLatitude => [[0x80, 0x00, 0x00, 0x00] => "N/A (Off-Earth)"],
+        FrameCenterLongitude => [[0x80, 0x00, 0x00, 0x00] => "N/A (Off-Earth)"],
+        OffsetCornerLatitudePoint1 => [[0x80, 0x00] => "N/A (Off-Earth)"],
+        OffsetCornerLongitudePoint1 => [[0x80, 0x00] => "N/A (Off-Earth)"],
+        OffsetCornerLatitudePoint2 => [[0x80, 0x00] => "N/A (Off-Earth)"],
+        OffsetCornerLongitudePoint2 => [[0x80, 0x00] => "N/A (Off-Earth)"],
+        OffsetCornerLatitudePoint3 => [[0x80, 0x00] => "N/A (Off-Earth)"],
+        OffsetCornerLongitudePoint3 => [[0x80, 0x00] => "N/A (Off-Earth)"],
+        OffsetCornerLatitudePoint4 => [[0x80, 0x00] => "N/A (Off-Earth)"],
+        OffsetCornerLongitudePoint4 => [[0x80, 0x00] => "N/A (Off-Earth)"],
+        TargetLocationLatitude => [[0x80, 0x00, 0x00, 0x00] => "N/A (Off-Earth)"],
+        TargetLocationLongitude => [[0x80, 0x00, 0x00, 0x00] => "N/A (Off-Earth)"],
+        PlatformAngleOfAttack => [[0x80, 0x00] => "Out of Range"],
+        PlatformVerticalSpeed => [[0x80, 0x00] => "Out of Range"],
+        PlatformSideslipAngle => [[0x80, 0x00] => "Out of Range"],
+        AlternatePlatformLatitude => [[0x80, 0x00, 0x00, 0x00] => "N/A (Off-Earth)"],
+        AlternatePlatformLongitude => [[0x80, 0x00, 0x00, 0x00] => "N/A (Off-Earth)"],
+        SensorNorthVelocity => [[0x80, 0x00] => "Out of Range"],
+        SensorEastVelocity => [[0x80, 0x00] => "Out of Range"],
+        CornerLatitudePoint1Full => [[0x80, 0x00, 0x00, 0x00] => "N/A (Off-Earth)"],
+        CornerLongitudePoint1Full => [[0x80, 0x00, 0x00, 0x00] => "N/A (Off-Earth)"],
+        CornerLatitudePoint2Full => [[0x80, 0x00, 0x00, 0x00] => "N/A (Off-Earth)"],
+        CornerLongitudePoint2Full => [[0x80, 0x00, 0x00, 0x00] => "N/A (Off-Earth)"],
+        CornerLatitudePoint3Full => [[0x80, 0x00, 0x00, 0x00] => "N/A (Off-Earth)"],
+        CornerLongitudePoint3Full => [[0x80, 0x00, 0x00, 0x00] => "N/A (Off-Earth)"],
+        CornerLatitudePoint4Full => [[0x80, 0x00, 0x00, 0x00] => "N/A (Off-Earth)"],
+        CornerLongitudePoint4Full => [[0x80, 0x00, 0x00, 0x00] => "N/A (Off-Earth)"],
+        PlatformPitchAngleFull => [[0x80, 0x00, 0x00, 0x00] => "Out of Range"],
+        PlatformRollAngleFull => [[0x80, 0x00, 0x00, 0x00] => "Out of Range"],
+        PlatformAngleOfAttackFull => [[0x80, 0x00, 0x00, 0x00] => "Out of Range"],
+        PlatformSideslipAngleFull => [[0x80, 0x00, 0x00, 0x00] => "Out of Range"],
+    }
+
+    formats: {
+        PrecisionTimeStamp => Uint as Uint64,
+        MissionId => Utf8 as String,
+        PlatformTailNumber => Utf8 as String,
+        PlatformHeadingAngle => Uint as Float64 in (0.0, 360.0),
+        PlatformPitchAngle => Int as Float64 in (-20.0, 20.0),
+        PlatformRollAngle => Int as Float64 in (-50.0, 50.0),
+        PlatformTrueAirspeed => Uint as Uint64,
+        PlatformIndicatedAirspeed => Uint as Uint64,
+        PlatformDesignation => Utf8 as String,
+        ImageSourceSensor => Utf8 as String,
+        ImageCoordinateSystem => Utf8 as String,
+        SensorLatitude => Int as Float64 in (-90.0, 90.0),
+        SensorLongitude => Int as Float64 in (-180.0, 180.0),
+        SensorTrueAltitude => Uint as Float64 in (-900.0, 19000.0),
+        SensorHorizontalFieldOfView => Uint as Float64 in (0.0, 180.0),
+        SensorVerticalFieldOfView => Uint as Float64 in (0.0, 180.0),
+        SensorRelativeAzimuthAngle => Uint as Float64 in (0.0, 360.0),
+        SensorRelativeElevationAngle => Int as Float64 in (-180.0, 180.0),
+        SensorRelativeRollAngle => Uint as Float64 in (0.0, 360.0),
+        SlantRange => Uint as Float64 in (0.0, 5000000.0),
+        TargetWidth => Uint as Float64 in (0.0, 10000.0),
+        FrameCenterLatitude => Int as Float64 in (-90.0, 90.0),
+        FrameCenterLongitude => Int as Float64 in (-180.0, 180.0),
+        FrameCenterElevation => Uint as Float64 in (-900.0, 19000.0),
+        OffsetCornerLatitudePoint1 => Int as Float64 in (-0.075, 0.075),
+        OffsetCornerLongitudePoint1 => Int as Float64 in (-0.075, 0.075),
+        OffsetCornerLatitudePoint2 => Int as Float64 in (-0.075, 0.075),
+        OffsetCornerLongitudePoint2 => Int as Float64 in (-0.075, 0.075),
+        OffsetCornerLatitudePoint3 => Int as Float64 in (-0.075, 0.075),
+        OffsetCornerLongitudePoint3 => Int as Float64 in (-0.075, 0.075),
+        OffsetCornerLatitudePoint4 => Int as Float64 in (-0.075, 0.075),
+        OffsetCornerLongitudePoint4 => Int as Float64 in (-0.075, 0.075),
+        IcingDetected => Uint as Uint64,
+        WindDirection => Uint as Float64 in (0.0, 360.0),
+        WindSpeed => Uint as Float64 in (0.0, 100.0),
+        StaticPressure => Uint as Float64 in (0.0, 5000.0),
+        DensityAltitude => Uint as Float64 in (-900.0, 19000.0),
+        OutsideAirTemperature => Int as Int64,
+        TargetLocationLatitude => Int as Float64 in (-90.0, 90.0),
+        TargetLocationLongitude => Int as Float64 in (-180.0, 180.0),
+        TargetLocationElevation => Uint as Float64 in (-900.0, 19000.0),
+        TargetTrackGateWidth => Uint as Float64 in (0.0, 510.0),
+        TargetTrackGateHeight => Uint as Float64 in (0.0, 510.0),
+        TargetErrorEstimateCe90 => Uint as Float64 in (0.0, 4095.0),
+        TargetErrorEstimateLe90 => Uint as Float64 in (0.0, 4095.0),
+        GenericFlagData => Uint as Uint64,
+        DifferentialPressure => Uint as Float64 in (0.0, 5000.0),
+        PlatformAngleOfAttack => Int as Float64 in (-20.0, 20.0),
+        PlatformVerticalSpeed => Int as Float64 in (-180.0, 180.0),
+        PlatformSideslipAngle => Int as Float64 in (-20.0, 20.0),
+        AirfieldBarometricPressure => Uint as Float64 in (0.0, 5000.0),
+        AirfieldElevation => Uint as Float64 in (-900.0, 19000.0),
+        RelativeHumidity => Uint as Float64 in (0.0, 100.0),
+        PlatformGroundSpeed => Uint as Uint64,
+        GroundRange => Uint as Float64 in (0.0, 5000000.0),
+        PlatformFuelRemaining => Uint as Float64 in (0.0, 10000.0),
+        PlatformCallSign => Utf8 as String,
+        LaserPrfCode => Uint as Uint64,
+        SensorFieldOfViewName => Uint as Uint64,
+        PlatformMagneticHeading => Uint as Float64 in (0.0, 360.0),
+        UasDatalinkLsVersionNumber => Uint as Uint64,
+        AlternatePlatformLatitude => Int as Float64 in (-90.0, 90.0),
+        AlternatePlatformLongitude => Int as Float64 in (-180.0, 180.0),
+        AlternatePlatformAltitude => Uint as Float64 in (-900.0, 19000.0),
+        AlternatePlatformName => Utf8 as String,
+        AlternatePlatformHeading => Uint as Float64 in (0.0, 360.0),
+        EventStartTimeUtc => Uint as Uint64,
+        SensorEllipsoidHeight => Uint as Float64 in (-900.0, 19000.0),
+        AlternatePlatformEllipsoidHeight => Uint as Float64 in (-900.0, 19000.0),
+        OperationalMode => Uint as Uint64,
+        FrameCenterHeightAboveEllipsoid => Uint as Float64 in (-900.0, 19000.0),
+        SensorNorthVelocity => Int as Float64 in (-327.0, 327.0),
+        SensorEastVelocity => Int as Float64 in (-327.0, 327.0),
+        CornerLatitudePoint1Full => Int as Float64 in (-90.0, 90.0),
+        CornerLongitudePoint1Full => Int as Float64 in (-180.0, 180.0),
+        CornerLatitudePoint2Full => Int as Float64 in (-90.0, 90.0),
+        CornerLongitudePoint2Full => Int as Float64 in (-180.0, 180.0),
+        CornerLatitudePoint3Full => Int as Float64 in (-90.0, 90.0),
+        CornerLongitudePoint3Full => Int as Float64 in (-180.0, 180.0),
+        CornerLatitudePoint4Full => Int as Float64 in (-90.0, 90.0),
+        CornerLongitudePoint4Full => Int as Float64 in (-180.0, 180.0),
+        PlatformPitchAngleFull => Int as Float64 in (-90.0, 90.0),
+        PlatformRollAngleFull => Int as Float64 in (-90.0, 90.0),
+        PlatformAngleOfAttackFull => Int as Float64 in (-90.0, 90.0),
+        PlatformSideslipAngleFull => Int as Float64 in (-180.0, 180.0),
+        MiisCoreIdentifier => Binary as Bytes,
+        StreamDesignator => Utf8 as String,
+        OperationalBase => Utf8 as String,
+        BroadcastSource => Utf8 as String,
+        CommunicationsMethod => Utf8 as String,
+    }
+
+    units: {
+        PlatformHeadingAngle => "deg",
+        PlatformPitchAngle => "deg",
+        PlatformRollAngle => "deg",
+        PlatformTrueAirspeed => "m/s",
+        PlatformIndicatedAirspeed => "m/s",
+        SensorLatitude => "deg",
+        SensorLongitude => "deg",
+        SensorTrueAltitude => "m",
+        SensorHorizontalFieldOfView => "deg",
+        SensorVerticalFieldOfView => "deg",
+        SensorRelativeAzimuthAngle => "deg",
+        SensorRelativeElevationAngle => "deg",
+        SensorRelativeRollAngle => "deg",
+        SlantRange => "m",
+        TargetWidth => "m",
+        FrameCenterLatitude => "deg",
+        FrameCenterLongitude => "deg",
+        FrameCenterElevation => "m",
+        OffsetCornerLatitudePoint1 => "deg",
+        OffsetCornerLongitudePoint1 => "deg",
+        OffsetCornerLatitudePoint2 => "deg",
+        OffsetCornerLongitudePoint2 => "deg",
+        OffsetCornerLatitudePoint3 => "deg",
+        OffsetCornerLongitudePoint3 => "deg",
+        OffsetCornerLatitudePoint4 => "deg",
+        OffsetCornerLongitudePoint4 => "deg",
+        WindDirection => "deg",
+        WindSpeed => "m/s",
+        StaticPressure => "mbar",
+        DensityAltitude => "m",
+        OutsideAirTemperature => "°C",
+        TargetLocationLatitude => "deg",
+        TargetLocationLongitude => "deg",
+        TargetLocationElevation => "m",
+        TargetTrackGateWidth => "pixels",
+        TargetTrackGateHeight => "pixels",
+        TargetErrorEstimateCe90 => "m",
+        TargetErrorEstimateLe90 => "m",
+        DifferentialPressure => "mbar",
+        PlatformAngleOfAttack => "deg",
+        PlatformVerticalSpeed => "m/s",
+        PlatformSideslipAngle => "deg",
+        AirfieldBarometricPressure => "mbar",
+        AirfieldElevation => "m",
+        RelativeHumidity => "%",
+        PlatformGroundSpeed => "m/s",
+        GroundRange => "m",
+        PlatformFuelRemaining => "kg",
+        PlatformMagneticHeading => "deg",
+        AlternatePlatformLatitude => "deg",
+        AlternatePlatformLongitude => "deg",
+        AlternatePlatformAltitude => "m",
+        AlternatePlatformHeading => "deg",
+        SensorEllipsoidHeight => "m",
+        AlternatePlatformEllipsoidHeight => "m",
+        FrameCenterHeightAboveEllipsoid => "m",
+        SensorNorthVelocity => "m/s",
+        SensorEastVelocity => "m/s",
+        CornerLatitudePoint1Full => "deg",
+        CornerLongitudePoint1Full => "deg",
+        CornerLatitudePoint2Full => "deg",
+        CornerLongitudePoint2Full => "deg",
+        CornerLatitudePoint3Full => "deg",
+        CornerLongitudePoint3Full => "deg",
+        CornerLatitudePoint4Full => "deg",
+        CornerLongitudePoint4Full => "deg",
+        PlatformPitchAngleFull => "deg",
+        PlatformRollAngleFull => "deg",
+        PlatformAngleOfAttackFull => "deg",
+        PlatformSideslipAngleFull => "deg",
+    }
+
+    enumerations: {
+        IcingDetected => 0..=2,
+        SensorFieldOfViewName => 0..=8,
+        OperationalMode => 0..=5,
+    }
+}
+
+/// Flags packed into the Generic Flag Data item (tag 47).
+///
+/// Bit 0 is the least significant bit of the byte. Bits 6 and 7 are
+/// reserved and kept as they were read.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct GenericFlagData(u8);
+
+impl GenericFlagData {
+    const LASER_RANGE: u8 = 1 << 0;
+    const AUTO_TRACK: u8 = 1 << 1;
+    const IR_POLARITY: u8 = 1 << 2;
+    const ICING: u8 = 1 << 3;
+    const SLANT_RANGE: u8 = 1 << 4;
+    const IMAGE_INVALID: u8 = 1 << 5;
+
+    pub fn from_byte(byte: u8) -> Self {
+        Self(byte)
+    }
+
+    pub fn to_byte(self) -> u8 {
+        self.0
+    }
+
+    /// Bit 0. Whether the laser range finder is on.
+    pub fn laser_range(self) -> bool {
+        self.get(Self::LASER_RANGE)
+    }
+
+    /// Bit 1. Whether auto-track is on.
+    pub fn auto_track(self) -> bool {
+        self.get(Self::AUTO_TRACK)
+    }
+
+    /// Bit 2. Whether the IR sensor is black hot rather than white hot.
+    pub fn black_hot(self) -> bool {
+        self.get(Self::IR_POLARITY)
+    }
+
+    /// Bit 3. Whether icing was detected.
+    pub fn icing_detected(self) -> bool {
+        self.get(Self::ICING)
+    }
+
+    /// Bit 4. Whether the slant range was measured rather than calculated.
+    pub fn slant_range_measured(self) -> bool {
+        self.get(Self::SLANT_RANGE)
+    }
+
+    /// Bit 5. Whether the image is invalid.
+    pub fn image_invalid(self) -> bool {
+        self.get(Self::IMAGE_INVALID)
+    }
+
+    pub fn set_laser_range(&mut self, on: bool) {
+        self.set(Self::LASER_RANGE, on);
+    }
+
+    pub fn set_auto_track(&mut self, on: bool) {
+        self.set(Self::AUTO_TRACK, on);
+    }
+
+    pub fn set_black_hot(&mut self, black_hot: bool) {
+        self.set(Self::IR_POLARITY, black_hot);
+    }
+
+    pub fn set_icing_detected(&mut self, detected: bool) {
+        self.set(Self::ICING, detected);
+    }
+
+    pub fn set_slant_range_measured(&mut self, measured: bool) {
+        self.set(Self::SLANT_RANGE, measured);
+    }
+
+    pub fn set_image_invalid(&mut self, invalid: bool) {
+        self.set(Self::IMAGE_INVALID, invalid);
+    }
+
+    fn get(self, bit: u8) -> bool {
+        self.0 & bit != 0
+    }
+
+    fn set(&mut self, bit: u8, value: bool) {
+        if value {
+            self.0 |= bit;
+        } else {
+            self.0 &= !bit;
+        }
+    }
+}
+
+/// Position and attitude of the platform and where its sensor is looking,
+/// the items of a packet most programs need.
+///
+/// ST 0601 gives the position of the platform as that of its sensor. Every
+/// field is in the engineering units of its tag, and is `None` when the tag
+/// is missing, empty, or holds a special value such as "Out of Range".
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PlatformReport {
+    /// Precision Time Stamp, in microseconds since the epoch.
+    pub timestamp: Option<u64>,
+    /// Platform Heading Angle, in degrees.
+    pub heading: Option<f64>,
+    /// Platform Pitch Angle, in degrees.
+    pub pitch: Option<f64>,
+    /// Platform Roll Angle, in degrees.
+    pub roll: Option<f64>,
+    /// Sensor Latitude, in degrees.
+    pub sensor_latitude: Option<f64>,
+    /// Sensor Longitude, in degrees.
+    pub sensor_longitude: Option<f64>,
+    /// Sensor True Altitude, in meters above mean sea level.
+    pub sensor_altitude: Option<f64>,
+    /// Frame Center Latitude, in degrees.
+    pub frame_center_latitude: Option<f64>,
+    /// Frame Center Longitude, in degrees.
+    pub frame_center_longitude: Option<f64>,
+    /// Slant Range from the sensor to the frame center, in meters.
+    pub slant_range: Option<f64>,
+}
+
+impl PlatformReport {
+    /// Reads the report out of an ST 0601 set.
+    ///
+    /// Pitch and roll are read from the full range tags 90 and 91 when the
+    /// set has them, and from tags 6 and 7 otherwise.
+    ///
+    /// # Returns
+    ///
+    /// - `Ok(PlatformReport)` - The report was read.
+    /// - `Err(encoding::Error)` - One of the tags could not be read or
+    ///   converted.
+    pub fn from_set<T>(set: &LocalSet<T>) -> Result<Self, encoding::Error>
+    where
+        T: Read + Seek,
+    {
+        let float = |tag| -> Result<Option<f64>, encoding::Error> {
+            Ok(match set.get_typed(tag)? {
+                Some(TagReading::Value(SoftwareValue::Float64(value))) => Some(value),
+                _ => None,
+            })
+        };
+        let either = |full: St0601Tag, legacy| -> Result<Option<f64>, encoding::Error> {
+            match set.get(&ItemKey::Tag(full.into())) {
+                Some(_) => float(full),
+                None => float(legacy),
+            }
+        };
+        let timestamp = match set.get_typed(St0601Tag::PrecisionTimeStamp)? {
+            Some(TagReading::Value(SoftwareValue::Uint64(timestamp))) => Some(timestamp),
+            _ => None,
+        };
+
+        Ok(Self {
+            timestamp,
+            heading: float(St0601Tag::PlatformHeadingAngle)?,
+            pitch: either(
+                St0601Tag::PlatformPitchAngleFull,
+                St0601Tag::PlatformPitchAngle,
+            )?,
+            roll: either(
+                St0601Tag::PlatformRollAngleFull,
+                St0601Tag::PlatformRollAngle,
+            )?,
+            sensor_latitude: float(St0601Tag::SensorLatitude)?,
+            sensor_longitude: float(St0601Tag::SensorLongitude)?,
+            sensor_altitude: float(St0601Tag::SensorTrueAltitude)?,
+            frame_center_latitude: float(St0601Tag::FrameCenterLatitude)?,
+            frame_center_longitude: float(St0601Tag::FrameCenterLongitude)?,
+            slant_range: float(St0601Tag::SlantRange)?,
+        })
+    }
+}
+
+impl TypedLocalSet<St0601Tag> {
+    /// Reads the ST 0102 Security Local Set carried by tag 48.
+    ///
+    /// # Returns
+    ///
+    /// - `Some(Ok(SecurityLocalSet))` - The set was read.
+    /// - `Some(Err(encoding::Error::InContext))` - The set could not be read.
+    ///   The error holds where tag 48 lies.
+    /// - `None` - Tag 48 is not in the set.
+    pub fn get_security_set(&self) -> Option<Result<SecurityLocalSet, encoding::Error>> {
+        let tag = St0601Tag::SecurityLocalSet;
+        let context = self.context(tag)?;
+        self.raw(tag)
+            .map(|raw| SecurityLocalSet::from_value(raw).map_err(|error| error.in_context(context)))
+    }
+
+    /// Reads the MIIS Core Identifier carried by tag 94.
+    ///
+    /// # Returns
+    ///
+    /// - `Some(Ok(CoreIdentifier))` - The Core Identifier was read.
+    /// - `Some(Err(encoding::Error))` - The value of tag 94 is malformed.
+    /// - `None` - Tag 94 is not in the set.
+    pub fn get_core_identifier(&self) -> Option<Result<CoreIdentifier, encoding::Error>> {
+        self.raw(St0601Tag::MiisCoreIdentifier)
+            .map(CoreIdentifier::parse)
+    }
+
+    /// Reads the flags packed into tag 47.
+    ///
+    /// # Returns
+    ///
+    /// - `Some(Ok(GenericFlagData))` - The flags were read.
+    /// - `Some(Err(encoding::Error))` - The value of tag 47 is not one byte.
+    /// - `None` - Tag 47 is not in the set.
+    pub fn get_generic_flag_data(&self) -> Option<Result<GenericFlagData, encoding::Error>> {
+        self.raw(St0601Tag::GenericFlagData).map(|raw| match *raw {
+            [byte] => Ok(GenericFlagData::from_byte(byte)),
+            _ => Err(encoding::Error::InvalidValueLength {
+                what: "Generic Flag Data",
+                length: raw.len() as u64,
+            }),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::RefCell, io::Cursor, rc::Rc};
+
+    use super::*;
+    use crate::{
+        format::SoftwareValue,
+        local_set::LocalSet,
+        misb::st0102::Classification,
+        tag::{Tag, TagReading},
+        universal_set::UNIVERSAL_KEY_LENGTH,
+    };
+    use itertools::{Itertools, chain};
+    use test_case::test_case;
+
+    #[test_case(&[], 0x0000; "Empty")]
+    #[test_case(&[0x06, 0x0E], 0x060E; "Single word")]
+    #[test_case(&[0x06, 0x0E, 0x2B], 0x310E; "Odd length")]
+    #[test_case(&[0xFF, 0xFF, 0x00, 0x02], 0x0001; "Wraps")]
+    fn test_checksum(bytes: &[u8], expected: u16) {
+        assert_eq!(checksum(bytes), expected);
+    }
+
+    #[test_case(90.0, &[0x7F, 0xFF, 0xFF, 0xFF]; "North pole")]
+    #[test_case(-90.0, &[0x80, 0x00, 0x00, 0x01]; "South pole")]
+    #[test_case(0.0, &[0x00, 0x00, 0x00, 0x00]; "Equator")]
+    fn latitude_round_trip(latitude: f64, raw: &[u8]) {
+        let tag = St0601Tag::SensorLatitude;
+        let value = SoftwareValue::Float64(latitude);
+
+        assert_eq!(tag.to_klv(&value).unwrap(), raw);
+        assert_eq!(tag.decode(raw).unwrap(), TagReading::Value(value));
+    }
+
+    #[test]
+    fn latitude_sentinel() {
+        let tag = St0601Tag::SensorLatitude;
+        let off_earth = tag.decode(&[0x80, 0x00, 0x00, 0x00]).unwrap();
+
+        assert!(
+            matches!(off_earth, TagReading::Special(special) if special.meaning == "N/A (Off-Earth)")
+        );
+        assert_eq!(
+            tag.encode(&off_earth).unwrap(),
+            vec![0x80, 0x00, 0x00, 0x00]
+        );
+        assert!(tag.to_klv(&SoftwareValue::Float64(90.5)).is_err());
+    }
+
+    /// Parses a packet holding `security` as tag 48, followed by a heading.
+    fn packet_with_security_set(security: &[u8]) -> TypedLocalSet<St0601Tag> {
+        let buf = chain!(
+            [0x00; UNIVERSAL_KEY_LENGTH],
+            [security.len() as u8 + 6],
+            [0x30, security.len() as u8],
+            security.iter().copied(),
+            [0x05, 0x02, 0x80, 0x00]
+        )
+        .collect_vec();
+        let set = LocalSet::read(0, Rc::new(RefCell::new(Cursor::new(buf)))).unwrap();
+        TypedLocalSet::from_local_set(&set).unwrap()
+    }
+
+    #[test]
+    fn security_set_is_decoded() {
+        let security = chain!(
+            [0x01, 0x01, 0x04],
+            [0x02, 0x01, 0x07],
+            [0x03, 0x05],
+            *b"//USA",
+            [0x0C, 0x01, 0x06],
+            [0x0D, 0x06, 0x00, b'U', 0x00, b'S', 0x00, b'A'],
+            [0x16, 0x02, 0x00, 0x0C]
+        )
+        .collect_vec();
+        let set = packet_with_security_set(&security);
+        let security = set.get_security_set().unwrap().unwrap();
+
+        assert_eq!(security.classification(), Classification::Secret);
+        assert_eq!(security.classifying_country().as_deref(), Some("//USA"));
+        assert_eq!(security.object_country_codes().as_deref(), Some("USA"));
+        assert_eq!(security.version(), Some(12));
+        assert_eq!(security.releasing_instructions(), &None);
+    }
+
+    #[test]
+    fn security_set_cannot_read_past_its_item() {
+        // The classifying country claims 5 bytes but tag 48 ends after 2, so
+        // reading it would run into the heading that follows.
+        let set = packet_with_security_set(&[0x01, 0x01, 0x01, 0x03, 0x05, b'/', b'/']);
+
+        match set.get_security_set() {
+            Some(Err(encoding::Error::InContext { context, source })) => {
+                assert_eq!(context.key(), ItemKey::Tag(48));
+                assert_eq!(context.offset(), 19);
+                assert_eq!(context.to_string(), "ST0601[48] @ 0x13");
+                assert!(matches!(*source, encoding::Error::Truncated(_)));
+            }
+            other => panic!("Expected a nested error, got {other:?}"),
+        }
+        assert!(
+            packet_with_security_set(&[])
+                .get_security_set()
+                .unwrap()
+                .is_err()
+        );
+        assert!(
+            TypedLocalSet::<St0601Tag>::from_value(&[])
+                .unwrap()
+                .get_security_set()
+                .is_none()
+        );
+    }
+
+    #[test_case(0b0000_0001, GenericFlagData::laser_range; "Laser range")]
+    #[test_case(0b0000_0010, GenericFlagData::auto_track; "Auto-track")]
+    #[test_case(0b0000_0100, GenericFlagData::black_hot; "IR polarity")]
+    #[test_case(0b0000_1000, GenericFlagData::icing_detected; "Icing")]
+    #[test_case(0b0001_0000, GenericFlagData::slant_range_measured; "Slant range")]
+    #[test_case(0b0010_0000, GenericFlagData::image_invalid; "Image invalid")]
+    fn generic_flag_bit(byte: u8, flag: fn(GenericFlagData) -> bool) {
+        assert!(flag(GenericFlagData::from_byte(byte)));
+        assert!(!flag(GenericFlagData::from_byte(!byte)));
+    }
+
+    #[test]
+    fn generic_flags_combined() {
+        let flags = GenericFlagData::from_byte(0b0001_0011);
+        assert!(flags.laser_range() && flags.auto_track() && flags.slant_range_measured());
+        assert!(!flags.black_hot() && !flags.icing_detected() && !flags.image_invalid());
+
+        let mut flags = GenericFlagData::from_byte(0b1100_0000);
+        flags.set_black_hot(true);
+        flags.set_image_invalid(true);
+        assert_eq!(flags.to_byte(), 0b1110_0100);
+        flags.set_black_hot(false);
+        assert_eq!(flags.to_byte(), 0b1110_0000);
+    }
+
+    #[test]
+    fn generic_flags_from_set() {
+        let buf = chain!([0x00; UNIVERSAL_KEY_LENGTH], [0x03], [0x2F, 0x01, 0x09]).collect_vec();
+        let set = LocalSet::read(0, Rc::new(RefCell::new(Cursor::new(buf)))).unwrap();
+        let set = TypedLocalSet::<St0601Tag>::from_local_set(&set).unwrap();
+        let flags = set.get_generic_flag_data().unwrap().unwrap();
+
+        assert!(flags.laser_range() && flags.icing_detected());
+        assert!(!flags.auto_track());
+    }
+
+    #[test]
+    fn platform_report_is_in_engineering_units() {
+        let items = chain!(
+            [0x02, 0x08, 0x00, 0x04, 0x60, 0x50, 0x58, 0x4E, 0x01, 0x80],
+            [0x05, 0x02, 0x71, 0xC2],
+            [0x06, 0x02, 0x80, 0x00],
+            [0x07, 0x02, 0x08, 0xB8],
+            [0x0D, 0x04, 0x55, 0x95, 0xB6, 0x6D],
+            [0x0E, 0x04, 0x5B, 0x53, 0x60, 0xC4],
+            [0x0F, 0x02, 0xC2, 0x21],
+            [0x15, 0x04, 0x00, 0x03, 0x0D, 0x40],
+            [0x17, 0x04, 0x80, 0x00, 0x00, 0x00],
+            [0x5B, 0x04, 0x10, 0x00, 0x00, 0x00]
+        )
+        .collect_vec();
+        let buf = chain!([0x00; UNIVERSAL_KEY_LENGTH], [items.len() as u8], items).collect_vec();
+        let set = LocalSet::read(0, Rc::new(RefCell::new(Cursor::new(buf)))).unwrap();
+        let report = PlatformReport::from_set(&set).unwrap();
+
+        let close = |actual: Option<f64>, expected: f64| {
+            let actual = actual.unwrap();
+            assert!(
+                (actual - expected).abs() <= 1e-9 * expected.abs().max(1.0),
+                "{actual} != {expected}"
+            );
+        };
+        assert_eq!(report.timestamp, Some(1_231_798_102_000_000));
+        close(report.heading, 29_122.0 * 360.0 / 65_535.0);
+        // Out of Range.
+        assert_eq!(report.pitch, None);
+        // Tag 91 is read in place of tag 7.
+        close(report.roll, 268_435_456.0 * 180.0 / 4_294_967_294.0);
+        close(
+            report.sensor_latitude,
+            1_435_874_925.0 * 180.0 / 4_294_967_294.0,
+        );
+        close(
+            report.sensor_longitude,
+            1_532_190_916.0 * 360.0 / 4_294_967_294.0,
+        );
+        close(
+            report.sensor_altitude,
+            49_697.0 * 19_900.0 / 65_535.0 - 900.0,
+        );
+        // Off-Earth.
+        assert_eq!(report.frame_center_latitude, None);
+        assert_eq!(report.frame_center_longitude, None);
+        close(
+            report.slant_range,
+            200_000.0 * 5_000_000.0 / 4_294_967_295.0,
+        );
+    }
+}
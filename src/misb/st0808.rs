@@ -0,0 +1,277 @@
+//! MISB ST 0808 Ancillary Text Metadata Local Set.
+//!
+//! Carries free-text remarks typed by operators. The set is sent on its own,
+//! keyed by a Universal Key, or nested in the value of an item of another
+//! set, in which case it has no key or length of its own.
+//!
+//! The tag numbers and lengths below have not yet been checked against the
+//! tables of _MISB ST 0808_.
+
+use std::{
+    io::{Read, Seek},
+    time::SystemTime,
+};
+
+use crate::{
+    encoding,
+    format::SoftwareValue,
+    klv::{ItemContext, ItemKey, Klv, OwnedKlv},
+    local_set::{LocalSet, OwnedLocalSet},
+    tag::Tag,
+    tag_table,
+    time_index::precision_time_stamp,
+    typed::TypedLocalSet,
+    universal_set::UniversalKey,
+};
+
+tag_table! {
+    /// Tags of the ST 0808 Ancillary Text Local Set.
+    #[repr(u8)]
+    pub enum St0808Tag {
+        PrecisionTimeStamp = 1, "Precision Time Stamp", Required(8);
+        Originator = 2, "Originator", Max(127);
+        Message = 3, "Ancillary Text";
+        Source = 4, "Source", Max(127);
+    }
+
+    required: [PrecisionTimeStamp, Message]
+
+    formats: {
+        PrecisionTimeStamp => Uint as Uint64,
+        Originator => Utf8 as String,
+        Message => Utf8 as String,
+        Source => Utf8 as String,
+    }
+}
+
+/// ST 0808 Ancillary Text Local Set.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct AncillaryText {
+    /// Time the text was entered.
+    pub timestamp: Option<SystemTime>,
+    /// Operator or system that wrote the text.
+    pub originator: Option<String>,
+    /// Body of the message.
+    pub message: Option<String>,
+    /// Platform or sensor the text is about.
+    pub source: Option<String>,
+}
+
+impl AncillaryText {
+    /// Reads a standalone set that has already been parsed.
+    ///
+    /// # Returns
+    ///
+    /// - `Ok(AncillaryText)` - Every item was read.
+    /// - `Err(encoding::Error)` - An item could not be read or is longer than
+    ///   its tag allows.
+    pub fn from_local_set<T>(set: &LocalSet<T>) -> Result<Self, encoding::Error>
+    where
+        T: Read + Seek,
+    {
+        Self::from_typed(&TypedLocalSet::from_local_set(set)?)
+    }
+
+    /// Reads a set nested in the value of `klv`, which lies at `context`.
+    ///
+    /// Errors are `encoding::Error::InContext` and hold `context`.
+    pub fn from_klv<T>(klv: &Klv<T>, context: &ItemContext) -> Result<Self, encoding::Error>
+    where
+        T: Read + Seek,
+    {
+        Self::from_value(&klv.read_value()?).map_err(|error| error.in_context(context.clone()))
+    }
+
+    /// Reads a set nested in the value of another item.
+    ///
+    /// Offsets in any error are relative to the first byte of `raw`.
+    pub fn from_value(raw: &[u8]) -> Result<Self, encoding::Error> {
+        Self::from_typed(&TypedLocalSet::from_value(raw)?)
+    }
+
+    fn from_typed(set: &TypedLocalSet<St0808Tag>) -> Result<Self, encoding::Error> {
+        set.check_lengths()?;
+        let string = |tag| -> Result<Option<String>, encoding::Error> {
+            Ok(match set.decode(tag)? {
+                Some(SoftwareValue::String(value)) => Some(value),
+                _ => None,
+            })
+        };
+        Ok(Self {
+            timestamp: set
+                .raw(St0808Tag::PrecisionTimeStamp)
+                .and_then(precision_time_stamp),
+            originator: string(St0808Tag::Originator)?,
+            message: string(St0808Tag::Message)?,
+            source: string(St0808Tag::Source)?,
+        })
+    }
+
+    /// Encodes every field that is set as an item, in tag order.
+    ///
+    /// # Returns
+    ///
+    /// - `Ok(OwnedLocalSet)` - Every field was encoded.
+    /// - `Err(encoding::Error)` - A field is longer than its tag allows.
+    pub fn to_owned_local_set(&self) -> Result<OwnedLocalSet, encoding::Error> {
+        let micros = self
+            .timestamp
+            .map(|timestamp| {
+                timestamp
+                    .duration_since(SystemTime::UNIX_EPOCH)
+                    .ok()
+                    .and_then(|since| u64::try_from(since.as_micros()).ok())
+                    .ok_or_else(|| encoding::Error::ValueOutOfRange {
+                        what: St0808Tag::PrecisionTimeStamp.name().into(),
+                        value: format!("{timestamp:?}"),
+                    })
+            })
+            .transpose()?;
+        let fields = [
+            (
+                St0808Tag::PrecisionTimeStamp,
+                micros.map(SoftwareValue::Uint64),
+            ),
+            (
+                St0808Tag::Originator,
+                self.originator.clone().map(SoftwareValue::String),
+            ),
+            (
+                St0808Tag::Message,
+                self.message.clone().map(SoftwareValue::String),
+            ),
+            (
+                St0808Tag::Source,
+                self.source.clone().map(SoftwareValue::String),
+            ),
+        ];
+
+        let mut items = Vec::new();
+        for (tag, value) in fields {
+            let Some(value) = value else {
+                continue;
+            };
+            let raw = tag.to_klv(&value)?;
+            if let Some(expected) = tag.value_length()
+                && !expected.allows(raw.len() as u64)
+            {
+                return Err(encoding::Error::InvalidValueLength {
+                    what: tag.name(),
+                    length: raw.len() as u64,
+                });
+            }
+            items.push(OwnedKlv::with_key(ItemKey::Tag(tag.number()), raw));
+        }
+        Ok(OwnedLocalSet::new(items))
+    }
+
+    /// Writes the set as a standalone KLV packet keyed by `key`.
+    pub fn serialize(&self, key: &UniversalKey) -> Result<Vec<u8>, encoding::Error> {
+        Ok(self.to_owned_local_set()?.serialize(key, false))
+    }
+
+    /// Writes the set as the value of the item it is nested in.
+    pub fn to_value(&self) -> Result<Vec<u8>, encoding::Error> {
+        Ok(self.to_owned_local_set()?.serialize_items())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::RefCell, io::Cursor, rc::Rc, time::Duration};
+
+    use super::*;
+    use crate::misb::st0601::St0601Tag;
+    use itertools::{Itertools, chain};
+
+    fn remark() -> AncillaryText {
+        AncillaryText {
+            timestamp: Some(SystemTime::UNIX_EPOCH + Duration::from_micros(1_700_000_000_000_000)),
+            originator: Some("Sensor operator".into()),
+            message: Some("Vehicle stopped at the gate".into()),
+            source: None,
+        }
+    }
+
+    #[test]
+    fn standalone_round_trip() {
+        let key = UniversalKey::new([0x06; 16]);
+        let packet = remark().serialize(&key).unwrap();
+        let set = LocalSet::read(0, Rc::new(RefCell::new(Cursor::new(packet)))).unwrap();
+
+        assert_eq!(AncillaryText::from_local_set(&set).unwrap(), remark());
+    }
+
+    #[test]
+    fn nested_round_trip() {
+        let value = remark().to_value().unwrap();
+        assert_eq!(AncillaryText::from_value(&value).unwrap(), remark());
+
+        // The same set nested in tag 5 of an enclosing set.
+        let buf = chain!(
+            [0x00; 16],
+            [value.len() as u8 + 2],
+            [0x05, value.len() as u8],
+            value
+        )
+        .collect_vec();
+        let set = LocalSet::read(0, Rc::new(RefCell::new(Cursor::new(buf)))).unwrap();
+        let klv = set.get(&ItemKey::Tag(5)).unwrap();
+        let context = ItemContext::new::<St0601Tag>(klv.key(), 0, klv.value_offset());
+        assert_eq!(AncillaryText::from_klv(klv, &context).unwrap(), remark());
+
+        // Tag 1 claims five bytes but the enclosing value ends after two.
+        let buf = chain!([0x00; 16], [0x04], [0x05, 0x02], [0x01, 0x05]).collect_vec();
+        let set = LocalSet::read(0, Rc::new(RefCell::new(Cursor::new(buf)))).unwrap();
+        let klv = set.get(&ItemKey::Tag(5)).unwrap();
+        let context = ItemContext::new::<St0601Tag>(klv.key(), 0, klv.value_offset());
+        assert!(matches!(
+            AncillaryText::from_klv(klv, &context),
+            Err(encoding::Error::InContext { context: c, .. }) if c == context
+        ));
+    }
+
+    #[test]
+    fn timestamp_is_microseconds_since_the_epoch() {
+        let value = AncillaryText {
+            originator: None,
+            message: None,
+            ..remark()
+        }
+        .to_value()
+        .unwrap();
+        assert_eq!(
+            value,
+            chain!([0x01, 0x08], 1_700_000_000_000_000u64.to_be_bytes()).collect_vec()
+        );
+
+        let early = AncillaryText {
+            timestamp: SystemTime::UNIX_EPOCH.checked_sub(Duration::from_secs(1)),
+            ..remark()
+        };
+        assert!(matches!(
+            early.to_value(),
+            Err(encoding::Error::ValueOutOfRange { .. })
+        ));
+    }
+
+    #[test]
+    fn originator_length_is_limited() {
+        let long = AncillaryText {
+            originator: Some("x".repeat(128)),
+            ..remark()
+        };
+        assert!(long.to_value().is_err());
+
+        let raw = chain!([0x02, 0x81, 0x80], [b'x'; 128]).collect_vec();
+        assert!(matches!(
+            AncillaryText::from_value(&raw),
+            Err(encoding::Error::InvalidLength {
+                key: ItemKey::Tag(2),
+                offset: 3,
+                actual: 128,
+                ..
+            })
+        ));
+    }
+}
@@ -0,0 +1,354 @@
+//! MISB ST 1108 Motion Imagery Interpretability and Quality Local Set.
+//!
+//! Tag numbers, names, and lengths are taken from _Table 1_ and _Table 2_ of
+//! _MISB ST 1108.3_. Each metric is carried in its own Metric Local Set, so
+//! tag 4 is repeated once for every metric in the set.
+//!
+//! The Metric Value is read as a float. Whether _Table 2_ instead maps it
+//! with IMAPB has not yet been checked.
+
+use std::io::{Read, Seek};
+
+use crate::{
+    encoding::{self, ber::decode_ber},
+    format::SoftwareValue,
+    klv::{ItemContext, ItemKey},
+    local_set::LocalSet,
+    tag_table,
+    typed::TypedLocalSet,
+};
+
+tag_table! {
+    /// Tags of the ST 1108 Interpretability and Quality Local Set.
+    #[repr(u8)]
+    pub enum St1108Tag {
+        AssessmentPoint = 1, "Assessment Point", Required(1);
+        MetricPeriodPack = 2, "Metric Period Pack", Required(12);
+        WindowCornersPack = 3, "Window Corners Pack";
+        MetricLocalSet = 4, "Metric Local Set";
+        CompressionType = 5, "Compression Type", Required(1);
+        CompressionProfile = 6, "Compression Profile", Required(1);
+        CompressionLevel = 7, "Compression Level", Max(3);
+        CompressionRatio = 8, "Compression Ratio";
+        StreamBitrate = 9, "Stream Bitrate";
+        DocumentVersion = 10, "Document Version", Required(1);
+        Checksum = 11, "Checksum", Required(2);
+    }
+
+    required: [AssessmentPoint, MetricPeriodPack, MetricLocalSet, DocumentVersion]
+
+    formats: {
+        AssessmentPoint => Uint as Uint64,
+        CompressionType => Uint as Uint64,
+        CompressionProfile => Uint as Uint64,
+        CompressionLevel => Utf8 as String,
+        CompressionRatio => Float as Float64,
+        StreamBitrate => Uint as Uint64,
+        DocumentVersion => Uint as Uint64,
+    }
+}
+
+tag_table! {
+    /// Tags of the Metric Local Set nested in tag 4 of ST 1108.
+    #[repr(u8)]
+    pub enum St1108MetricTag {
+        Name = 1, "Metric Name";
+        Version = 2, "Metric Version";
+        Implementer = 3, "Metric Implementer";
+        Parameters = 4, "Metric Parameters";
+        Time = 5, "Metric Time", Required(8);
+        Value = 6, "Metric Value";
+    }
+
+    required: [Name, Version, Implementer, Time, Value]
+
+    formats: {
+        Name => Utf8 as String,
+        Version => Utf8 as String,
+        Parameters => Utf8 as String,
+        Time => Uint as Uint64,
+        Value => Float as Float64,
+    }
+}
+
+/// Point in the imagery's path where the metrics were measured.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AssessmentPoint {
+    Sensor = 1,
+    SensorEncoder = 2,
+    GcsReceived = 3,
+    GcsTransmit = 4,
+    LibraryArchive = 5,
+}
+
+impl TryFrom<u64> for AssessmentPoint {
+    type Error = encoding::Error;
+
+    fn try_from(value: u64) -> Result<Self, Self::Error> {
+        Ok(match value {
+            1 => Self::Sensor,
+            2 => Self::SensorEncoder,
+            3 => Self::GcsReceived,
+            4 => Self::GcsTransmit,
+            5 => Self::LibraryArchive,
+            _ => {
+                return Err(encoding::Error::ValueOutOfRange {
+                    what: "an assessment point".into(),
+                    value: value.to_string(),
+                });
+            }
+        })
+    }
+}
+
+/// Span of time the metrics were measured over.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct MetricPeriod {
+    /// Start of the period in microseconds since the epoch.
+    pub start: u64,
+    /// Length of the period in microseconds.
+    pub offset: u32,
+}
+
+impl MetricPeriod {
+    /// Decodes the Metric Period Pack, an 8-byte start time followed by a
+    /// 4-byte offset.
+    pub fn from_bytes(raw: &[u8]) -> Result<Self, encoding::Error> {
+        let raw: &[u8; 12] = raw
+            .try_into()
+            .map_err(|_| encoding::Error::InvalidValueLength {
+                what: "Metric Period Pack",
+                length: raw.len() as u64,
+            })?;
+        let (start, offset) = raw.split_at(8);
+        Ok(Self {
+            start: u64::from_be_bytes(start.try_into().expect("Start is 8 bytes")),
+            offset: u32::from_be_bytes(offset.try_into().expect("Offset is 4 bytes")),
+        })
+    }
+}
+
+/// Organization and group that implemented a metric.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MetricImplementer {
+    pub organization: String,
+    pub subgroup: String,
+}
+
+impl MetricImplementer {
+    /// Decodes the Metric Implementer pack, two UTF-8 strings each preceded by
+    /// its BER length.
+    pub fn from_bytes(raw: &[u8]) -> Result<Self, encoding::Error> {
+        let mut rest = raw;
+        let mut string = || -> Result<String, encoding::Error> {
+            let (length, consumed) = decode_ber(rest)?;
+            let value = usize::try_from(length)
+                .ok()
+                .and_then(|length| rest.get(consumed..consumed + length))
+                .ok_or(encoding::Error::Truncated("Metric Implementer"))?;
+            rest = &rest[consumed + value.len()..];
+            String::from_utf8(value.to_vec()).map_err(|error| encoding::Error::InvalidCharacter {
+                what: "UTF8 string",
+                offset: error.utf8_error().valid_up_to(),
+            })
+        };
+        Ok(Self {
+            organization: string()?,
+            subgroup: string()?,
+        })
+    }
+}
+
+/// A single metric read from a Metric Local Set.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Metric {
+    /// Name of the metric, such as `VNIIRS` or `Blur`.
+    pub name: Option<String>,
+    pub version: Option<String>,
+    pub implementer: Option<MetricImplementer>,
+    pub parameters: Option<String>,
+    /// Time the metric was measured in microseconds since the epoch.
+    pub time: Option<u64>,
+    pub value: Option<f64>,
+}
+
+impl Metric {
+    /// Decodes the value of a Metric Local Set.
+    ///
+    /// Offsets in any error are relative to the first byte of `raw`.
+    pub fn from_value(raw: &[u8]) -> Result<Self, encoding::Error> {
+        let set = TypedLocalSet::<St1108MetricTag>::from_value(raw)?;
+        let string = |tag| -> Result<Option<String>, encoding::Error> {
+            Ok(match set.decode(tag)? {
+                Some(SoftwareValue::String(value)) => Some(value),
+                _ => None,
+            })
+        };
+        Ok(Self {
+            name: string(St1108MetricTag::Name)?,
+            version: string(St1108MetricTag::Version)?,
+            implementer: set
+                .raw(St1108MetricTag::Implementer)
+                .map(MetricImplementer::from_bytes)
+                .transpose()?,
+            parameters: string(St1108MetricTag::Parameters)?,
+            time: match set.decode(St1108MetricTag::Time)? {
+                Some(SoftwareValue::Uint64(time)) => Some(time),
+                _ => None,
+            },
+            value: match set.decode(St1108MetricTag::Value)? {
+                Some(SoftwareValue::Float64(value)) => Some(value),
+                _ => None,
+            },
+        })
+    }
+}
+
+/// ST 1108 Interpretability and Quality Local Set.
+#[derive(Debug, getset::CopyGetters, getset::Getters)]
+pub struct QualitySet {
+    #[getset(get_copy = "pub")]
+    assessment_point: Option<AssessmentPoint>,
+    #[getset(get_copy = "pub")]
+    metric_period: Option<MetricPeriod>,
+    /// Every Metric Local Set, in the order they appear in the set.
+    #[getset(get = "pub")]
+    metrics: Vec<Metric>,
+    /// Every item in the set. Only the last Metric Local Set is kept here.
+    #[getset(get = "pub")]
+    items: TypedLocalSet<St1108Tag>,
+}
+
+impl QualitySet {
+    /// Reads the metrics out of a parsed ST 1108 set.
+    ///
+    /// The set must keep repeated keys, as it does with the default
+    /// `DuplicatePolicy::KeepAll`, for every Metric Local Set to be read.
+    ///
+    /// # Returns
+    ///
+    /// - `Ok(QualitySet)` - Every item was read.
+    /// - `Err(encoding::Error)` - An item could not be read. Errors reading a
+    ///   Metric Local Set are `encoding::Error::InContext` and hold where its
+    ///   item lies.
+    pub fn from_local_set<T>(set: &LocalSet<T>) -> Result<Self, encoding::Error>
+    where
+        T: Read + Seek,
+    {
+        let items = TypedLocalSet::<St1108Tag>::from_local_set(set)?;
+
+        let assessment_point = match items.decode(St1108Tag::AssessmentPoint)? {
+            Some(SoftwareValue::Uint64(point)) => Some(AssessmentPoint::try_from(point)?),
+            _ => None,
+        };
+        let metric_period = items
+            .raw(St1108Tag::MetricPeriodPack)
+            .map(MetricPeriod::from_bytes)
+            .transpose()?;
+
+        let key = ItemKey::Tag(St1108Tag::MetricLocalSet.into());
+        let metrics = set
+            .iter()
+            .enumerate()
+            .filter(|(_, klv)| klv.key() == key)
+            .map(|(index, klv)| {
+                let context = ItemContext::new::<St1108Tag>(key, index, klv.value_offset());
+                Metric::from_value(&klv.read_value()?).map_err(|error| error.in_context(context))
+            })
+            .collect::<Result<_, _>>()?;
+
+        Ok(Self {
+            assessment_point,
+            metric_period,
+            metrics,
+            items,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::RefCell, io::Cursor, rc::Rc};
+
+    use super::*;
+    use crate::universal_set::UNIVERSAL_KEY_LENGTH;
+    use itertools::{Itertools, chain};
+
+    fn metric(name: &str, value: f32) -> Vec<u8> {
+        chain!(
+            [0x01, name.len() as u8],
+            name.bytes(),
+            [0x02, 0x03],
+            *b"1.0",
+            [0x03, 0x0A, 0x04],
+            *b"MISB",
+            [0x04],
+            *b"ABCD",
+            [0x05, 0x08],
+            1_000_000u64.to_be_bytes(),
+            [0x06, 0x04],
+            value.to_be_bytes()
+        )
+        .collect_vec()
+    }
+
+    fn quality_set(metrics: &[Vec<u8>]) -> QualitySet {
+        let value = chain!(
+            [0x01, 0x01, 0x03],
+            [0x02, 0x0C],
+            5_000_000u64.to_be_bytes(),
+            1_000u32.to_be_bytes(),
+            metrics
+                .iter()
+                .flat_map(|metric| chain!([0x04, metric.len() as u8], metric.iter().copied()))
+        )
+        .collect_vec();
+        let buf = chain!([0x00; UNIVERSAL_KEY_LENGTH], [value.len() as u8], value).collect_vec();
+        let set = LocalSet::read(0, Rc::new(RefCell::new(Cursor::new(buf)))).unwrap();
+        QualitySet::from_local_set(&set).unwrap()
+    }
+
+    #[test]
+    fn two_metrics() {
+        let set = quality_set(&[metric("VNIIRS", 6.5), metric("Blur", 0.25)]);
+
+        assert_eq!(set.assessment_point(), Some(AssessmentPoint::GcsReceived));
+        assert_eq!(
+            set.metric_period(),
+            Some(MetricPeriod {
+                start: 5_000_000,
+                offset: 1_000
+            })
+        );
+        assert_eq!(set.metrics().len(), 2);
+        let vniirs = &set.metrics()[0];
+        assert_eq!(vniirs.name.as_deref(), Some("VNIIRS"));
+        assert_eq!(vniirs.version.as_deref(), Some("1.0"));
+        assert_eq!(
+            vniirs.implementer,
+            Some(MetricImplementer {
+                organization: "MISB".into(),
+                subgroup: "ABCD".into()
+            })
+        );
+        assert_eq!(vniirs.time, Some(1_000_000));
+        assert_eq!(vniirs.value, Some(6.5));
+        assert_eq!(set.metrics()[1].name.as_deref(), Some("Blur"));
+        assert_eq!(set.metrics()[1].value, Some(0.25));
+    }
+
+    #[test]
+    fn malformed_metric_is_nested_error() {
+        let value = [0x01, 0x05, b'B'];
+        let buf = chain!([0x00; UNIVERSAL_KEY_LENGTH], [0x05], [0x04, 0x03], value).collect_vec();
+        let set = LocalSet::read(0, Rc::new(RefCell::new(Cursor::new(buf)))).unwrap();
+
+        match QualitySet::from_local_set(&set) {
+            Err(encoding::Error::InContext { context, .. }) => {
+                assert_eq!(context.offset(), 19);
+                assert_eq!(context.to_string(), "ST1108[4] @ 0x13");
+            }
+            other => panic!("Expected a nested error, got {other:?}"),
+        }
+    }
+}
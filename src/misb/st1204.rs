@@ -0,0 +1,313 @@
+//! MISB ST 1204 Motion Imagery Identification System (MIIS) Core Identifier.
+//!
+//! The Core Identifier is carried by tag 94 of ST 0601. Its binary form is a
+//! version byte, a usage byte, and then a 16-byte UUID for each ID the usage
+//! byte marks as present, in the order sensor, platform, window, minor.
+//!
+//! The check value and the example text form below have not yet been checked
+//! against the worked example of _MISB ST 1204_.
+
+use itertools::Itertools;
+
+use crate::{crc::Crc16CcittFalse, encoding};
+
+/// Length of every ID in a Core Identifier.
+pub const UUID_LENGTH: usize = 16;
+
+/// How a sensor or platform ID was assigned, taken from two bits of the
+/// usage byte.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum IdType {
+    /// The ID is not in the Core Identifier.
+    #[default]
+    None = 0b00,
+    /// Assigned by a managing authority.
+    Managed = 0b01,
+    /// Generated by software when no physical ID is available.
+    Virtual = 0b10,
+    /// Built into the physical device.
+    Physical = 0b11,
+}
+
+impl IdType {
+    fn from_bits(bits: u8) -> Self {
+        match bits & 0b11 {
+            0b00 => Self::None,
+            0b01 => Self::Managed,
+            0b10 => Self::Virtual,
+            _ => Self::Physical,
+        }
+    }
+}
+
+/// Decoded MIIS Core Identifier.
+///
+/// The usage byte is `SSPPWM00`, where `SS` and `PP` are the `IdType` of the
+/// sensor and platform IDs and `W` and `M` mark the window and minor IDs as
+/// present.
+#[derive(Clone, Debug, Default, PartialEq, Eq, getset::CopyGetters, getset::Getters)]
+pub struct CoreIdentifier {
+    #[getset(get_copy = "pub")]
+    version: u8,
+    #[getset(get_copy = "pub")]
+    sensor_id_type: IdType,
+    #[getset(get_copy = "pub")]
+    platform_id_type: IdType,
+    #[getset(get = "pub")]
+    sensor_id: Option<[u8; UUID_LENGTH]>,
+    #[getset(get = "pub")]
+    platform_id: Option<[u8; UUID_LENGTH]>,
+    #[getset(get = "pub")]
+    window_id: Option<[u8; UUID_LENGTH]>,
+    #[getset(get = "pub")]
+    minor_id: Option<[u8; UUID_LENGTH]>,
+}
+
+impl CoreIdentifier {
+    const WINDOW_BIT: u8 = 1 << 3;
+    const MINOR_BIT: u8 = 1 << 2;
+
+    /// Decodes the binary form of a Core Identifier.
+    ///
+    /// # Returns
+    ///
+    /// - `Ok(CoreIdentifier)` - The Core Identifier was decoded.
+    /// - `Err(encoding::Error)` - `raw` is not as long as its usage byte
+    ///   requires.
+    pub fn parse(raw: &[u8]) -> Result<Self, encoding::Error> {
+        let [version, usage, ids @ ..] = raw else {
+            return Err(encoding::Error::Truncated("Core Identifier"));
+        };
+        let sensor_id_type = IdType::from_bits(usage >> 6);
+        let platform_id_type = IdType::from_bits(usage >> 4);
+        let present = [
+            sensor_id_type != IdType::None,
+            platform_id_type != IdType::None,
+            usage & Self::WINDOW_BIT != 0,
+            usage & Self::MINOR_BIT != 0,
+        ];
+
+        let expected = present.iter().filter(|present| **present).count() * UUID_LENGTH;
+        if ids.len() != expected {
+            return Err(encoding::Error::InvalidValueLength {
+                what: "Core Identifier IDs",
+                length: ids.len() as u64,
+            });
+        }
+        let mut ids = ids.chunks_exact(UUID_LENGTH).map(|id| {
+            id.try_into()
+                .expect("Chunks are exactly the length of a UUID")
+        });
+        let [sensor_id, platform_id, window_id, minor_id] =
+            present.map(|present| present.then(|| ids.next()).flatten());
+
+        Ok(Self {
+            version: *version,
+            sensor_id_type,
+            platform_id_type,
+            sensor_id,
+            platform_id,
+            window_id,
+            minor_id,
+        })
+    }
+
+    /// Usage byte describing which IDs are present.
+    pub fn usage(&self) -> u8 {
+        (self.sensor_id_type as u8) << 6
+            | (self.platform_id_type as u8) << 4
+            | if self.window_id.is_some() {
+                Self::WINDOW_BIT
+            } else {
+                0
+            }
+            | if self.minor_id.is_some() {
+                Self::MINOR_BIT
+            } else {
+                0
+            }
+    }
+
+    /// Encodes the binary form of the Core Identifier.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        [self.version, self.usage()]
+            .into_iter()
+            .chain(self.ids().flatten().copied())
+            .collect()
+    }
+
+    /// Check value of the text form, the CRC-16-CCITT of the binary form.
+    pub fn check_value(&self) -> u16 {
+        Crc16CcittFalse::checksum(&self.to_bytes())
+    }
+
+    /// Formats the Core Identifier in its text form.
+    ///
+    /// The text form is the version and usage bytes, each present ID, and the
+    /// check value, separated by colons. IDs are written as eight dash
+    /// separated groups of four hex digits and separated from each other by
+    /// slashes, such as
+    /// `0170:F592-F023-7336-4AF8-AA91-62C0-0F2E-B2DA/16B7-4341-0008-41A0-BE36-5B5A-B5A8-8A58:123A`.
+    pub fn to_text(&self) -> String {
+        let ids = self
+            .ids()
+            .map(|id| {
+                id.chunks_exact(2)
+                    .map(|pair| format!("{:02X}{:02X}", pair[0], pair[1]))
+                    .join("-")
+            })
+            .join("/");
+        format!(
+            "{:02X}{:02X}:{ids}:{:04X}",
+            self.version,
+            self.usage(),
+            self.check_value()
+        )
+    }
+
+    /// Parses the text form of a Core Identifier.
+    ///
+    /// See `CoreIdentifier::to_text`.
+    ///
+    /// # Returns
+    ///
+    /// - `Ok(CoreIdentifier)` - The text was parsed and its check value
+    ///   matches.
+    /// - `Err(encoding::Error)` - The text is malformed or its check value does
+    ///   not match its contents.
+    pub fn from_text(text: &str) -> Result<Self, encoding::Error> {
+        let invalid = || encoding::Error::Malformed {
+            what: "a Core Identifier",
+            value: format!("{text:?}"),
+        };
+        let Some((header, ids, check)) = text.split(':').collect_tuple() else {
+            return Err(invalid());
+        };
+        let hex = |digits: &str| -> Result<Vec<u8>, encoding::Error> {
+            if !digits.len().is_multiple_of(2) || !digits.is_ascii() {
+                return Err(invalid());
+            }
+            (0..digits.len())
+                .step_by(2)
+                .map(|i| u8::from_str_radix(&digits[i..i + 2], 16).map_err(|_| invalid()))
+                .collect()
+        };
+
+        let mut raw = hex(header)?;
+        if raw.len() != 2 {
+            return Err(invalid());
+        }
+        for id in ids.split('/').filter(|id| !id.is_empty()) {
+            raw.extend(hex(&id.replace('-', ""))?);
+        }
+        let identifier = Self::parse(&raw)?;
+
+        let check = u16::from_str_radix(check, 16).map_err(|_| invalid())?;
+        if check != identifier.check_value() {
+            return Err(encoding::Error::CheckValueMismatch {
+                what: "Core Identifier",
+                expected: check,
+                actual: identifier.check_value(),
+            });
+        }
+        Ok(identifier)
+    }
+
+    /// Each present ID in the order they are encoded.
+    fn ids(&self) -> impl Iterator<Item = &[u8; UUID_LENGTH]> {
+        [
+            &self.sensor_id,
+            &self.platform_id,
+            &self.window_id,
+            &self.minor_id,
+        ]
+        .into_iter()
+        .flatten()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use itertools::chain;
+
+    const SENSOR_ID: [u8; UUID_LENGTH] = [
+        0xF5, 0x92, 0xF0, 0x23, 0x73, 0x36, 0x4A, 0xF8, 0xAA, 0x91, 0x62, 0xC0, 0x0F, 0x2E, 0xB2,
+        0xDA,
+    ];
+    const PLATFORM_ID: [u8; UUID_LENGTH] = [
+        0x16, 0xB7, 0x43, 0x41, 0x00, 0x08, 0x41, 0xA0, 0xBE, 0x36, 0x5B, 0x5A, 0xB5, 0xA8, 0x8A,
+        0x58,
+    ];
+
+    /// Text form of `sensor_and_platform`. The check value was computed by
+    /// this implementation, not taken from the standard.
+    const SENSOR_AND_PLATFORM_TEXT: &str =
+        "0170:F592-F023-7336-4AF8-AA91-62C0-0F2E-B2DA/16B7-4341-0008-41A0-BE36-5B5A-B5A8-8A58:123A";
+
+    fn sensor_and_platform() -> Vec<u8> {
+        chain!([0x01, 0x70], SENSOR_ID, PLATFORM_ID).collect()
+    }
+
+    #[test]
+    fn parse_sensor_and_platform() {
+        let identifier = CoreIdentifier::parse(&sensor_and_platform()).unwrap();
+
+        assert_eq!(identifier.version(), 1);
+        assert_eq!(identifier.sensor_id_type(), IdType::Managed);
+        assert_eq!(identifier.platform_id_type(), IdType::Physical);
+        assert_eq!(identifier.sensor_id(), &Some(SENSOR_ID));
+        assert_eq!(identifier.platform_id(), &Some(PLATFORM_ID));
+        assert_eq!(identifier.window_id(), &None);
+        assert_eq!(identifier.minor_id(), &None);
+        assert_eq!(identifier.to_bytes(), sensor_and_platform());
+    }
+
+    #[test]
+    fn text_round_trip() {
+        let identifier = CoreIdentifier::parse(&sensor_and_platform()).unwrap();
+
+        assert_eq!(identifier.check_value(), 0x123A);
+        assert_eq!(identifier.to_text(), SENSOR_AND_PLATFORM_TEXT);
+        assert_eq!(
+            CoreIdentifier::from_text(SENSOR_AND_PLATFORM_TEXT).unwrap(),
+            identifier
+        );
+    }
+
+    #[test]
+    fn platform_and_window() {
+        let raw = chain!([0x01, 0x28], PLATFORM_ID, SENSOR_ID).collect_vec();
+        let identifier = CoreIdentifier::parse(&raw).unwrap();
+
+        assert_eq!(identifier.sensor_id(), &None);
+        assert_eq!(identifier.platform_id_type(), IdType::Virtual);
+        assert_eq!(identifier.platform_id(), &Some(PLATFORM_ID));
+        assert_eq!(identifier.window_id(), &Some(SENSOR_ID));
+        assert_eq!(
+            CoreIdentifier::from_text(&identifier.to_text()).unwrap(),
+            identifier
+        );
+    }
+
+    #[test]
+    fn wrong_check_value_is_an_error() {
+        let (body, _) = SENSOR_AND_PLATFORM_TEXT.rsplit_once(':').unwrap();
+
+        assert!(matches!(
+            CoreIdentifier::from_text(&format!("{body}:123B")),
+            Err(encoding::Error::CheckValueMismatch {
+                expected: 0x123B,
+                actual: 0x123A,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn length_must_match_usage() {
+        assert!(CoreIdentifier::parse(&[0x01]).is_err());
+        assert!(CoreIdentifier::parse(&sensor_and_platform()[..33]).is_err());
+        assert!(CoreIdentifier::parse(&[0x01, 0x40]).is_err());
+    }
+}
@@ -0,0 +1,283 @@
+//! MISB ST 1303 Multi-Dimensional Array Pack (MDARRAY).
+//!
+//! An MDARRAY is the number of dimensions, the size of each dimension, the
+//! size of each element, and the Array Processing Algorithm (APA) used to
+//! encode the elements, all as BER-OID values. They are followed by the
+//! parameters of the APA, if it has any, and then the elements in row-major
+//! order.
+//!
+//! The APA parameter layout, including the IMAPB bounds read as 8-byte IEEE
+//! 754 floats, has not yet been checked against the examples of
+//! _MISB ST 1303_.
+
+use crate::{
+    encoding::{self, ber_oid::decode_ber_oid},
+    format::{FormatParams, KlvFormat, SoftwareFormat, SoftwareValue, convert},
+};
+
+/// Array Processing Algorithm used to encode the elements of an MDARRAY.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Apa {
+    /// Each element is encoded in the natural format the standard carrying
+    /// the array gives it.
+    Natural,
+    /// Each element is an ST 1201 IMAPB value mapped onto `min..=max`.
+    Imapb { min: f64, max: f64 },
+    /// Each element is one bit, packed starting from the most significant
+    /// bit of each byte.
+    Boolean,
+}
+
+impl Apa {
+    const NATURAL: usize = 1;
+    const IMAPB: usize = 2;
+    const BOOLEAN: usize = 3;
+}
+
+/// Decoded MDARRAY.
+#[derive(Clone, Debug, PartialEq, getset::CopyGetters, getset::Getters)]
+pub struct MdArray {
+    /// Size of each dimension, outermost first.
+    #[getset(get = "pub")]
+    shape: Vec<usize>,
+    /// Length in bytes of each encoded element.
+    #[getset(get_copy = "pub")]
+    element_size: usize,
+    #[getset(get_copy = "pub")]
+    apa: Apa,
+    /// Every element in row-major order.
+    #[getset(get = "pub")]
+    values: Vec<SoftwareValue>,
+}
+
+impl MdArray {
+    /// Decodes an MDARRAY whose elements are encoded in `natural` when the
+    /// Natural Format APA is used.
+    ///
+    /// The MDARRAY does not say what type its elements are, so the format they
+    /// take under the Natural Format APA comes from the standard carrying it.
+    ///
+    /// # Returns
+    ///
+    /// - `Ok(MdArray)` - Every element was decoded.
+    /// - `Err(encoding::Error)` - The array is truncated, uses an APA that is
+    ///   not supported, or has an element that could not be decoded.
+    pub fn parse(raw: &[u8], natural: KlvFormat) -> Result<Self, encoding::Error> {
+        let mut reader = Reader { raw, position: 0 };
+
+        let dimensions = reader.ber_oid()?;
+        let shape = (0..dimensions)
+            .map(|_| reader.ber_oid())
+            .collect::<Result<Vec<_>, _>>()?;
+        let element_size = reader.ber_oid()?;
+        let apa = match reader.ber_oid()? {
+            Apa::NATURAL => Apa::Natural,
+            Apa::IMAPB => Apa::Imapb {
+                min: reader.f64()?,
+                max: reader.f64()?,
+            },
+            Apa::BOOLEAN => Apa::Boolean,
+            apa => {
+                return Err(encoding::Error::ValueOutOfRange {
+                    what: "the APAs of an MDARRAY".into(),
+                    value: apa.to_string(),
+                });
+            }
+        };
+
+        let count = shape
+            .iter()
+            .try_fold(1usize, |count, size| count.checked_mul(*size))
+            .ok_or_else(|| encoding::Error::Malformed {
+                what: "an MDARRAY shape",
+                value: format!("{shape:?}"),
+            })?;
+        let data = &raw[reader.position..];
+        let values = match apa {
+            Apa::Boolean => {
+                let bits = data
+                    .get(..count.div_ceil(8))
+                    .ok_or(encoding::Error::Truncated("MDARRAY"))?;
+                (0..count)
+                    .map(|i| SoftwareValue::Bool(bits[i / 8] & (0x80 >> (i % 8)) != 0))
+                    .collect()
+            }
+            Apa::Natural | Apa::Imapb { .. } => {
+                if element_size == 0 {
+                    return Err(encoding::Error::InvalidValueLength {
+                        what: "MDARRAY element",
+                        length: 0,
+                    });
+                }
+                // An array too large to count the bytes of can't be there.
+                let data = count
+                    .checked_mul(element_size)
+                    .and_then(|length| data.get(..length))
+                    .ok_or(encoding::Error::Truncated("MDARRAY"))?;
+                data.chunks_exact(element_size)
+                    .map(|element| match apa {
+                        Apa::Imapb { min, max } => convert(
+                            element,
+                            &KlvFormat::Imapb,
+                            &SoftwareFormat::Float64,
+                            &FormatParams::range(min, max),
+                        ),
+                        _ => {
+                            let (klv, software) = natural.unmapped();
+                            convert(element, &klv, &software, &FormatParams::default())
+                        }
+                    })
+                    .collect::<Result<_, _>>()?
+            }
+        };
+
+        Ok(Self {
+            shape,
+            element_size,
+            apa,
+            values,
+        })
+    }
+
+    /// Element at `index`, given outermost dimension first.
+    ///
+    /// # Returns
+    ///
+    /// - `Some(SoftwareValue)` - The element at `index`.
+    /// - `None` - `index` does not have one entry per dimension or is out of
+    ///   bounds.
+    pub fn get(&self, index: &[usize]) -> Option<&SoftwareValue> {
+        if index.len() != self.shape.len() {
+            return None;
+        }
+        let offset = index
+            .iter()
+            .zip(&self.shape)
+            .try_fold(0, |offset, (i, size)| (i < size).then(|| offset * size + i))?;
+        self.values.get(offset)
+    }
+}
+
+/// Reads the header of an MDARRAY a field at a time.
+struct Reader<'a> {
+    raw: &'a [u8],
+    position: usize,
+}
+
+impl Reader<'_> {
+    fn ber_oid(&mut self) -> Result<usize, encoding::Error> {
+        let (value, length) = decode_ber_oid(&self.raw[self.position..])?;
+        self.position += length;
+        value
+            .try_into()
+            .map_err(|_| encoding::Error::ValueOutOfRange {
+                what: "an MDARRAY field".into(),
+                value: value.to_string(),
+            })
+    }
+
+    fn f64(&mut self) -> Result<f64, encoding::Error> {
+        let bytes = self
+            .raw
+            .get(self.position..self.position + 8)
+            .ok_or(encoding::Error::Truncated("MDARRAY"))?;
+        self.position += 8;
+        Ok(f64::from_be_bytes(
+            bytes.try_into().expect("Slice is exactly 8 bytes"),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use itertools::{Itertools, chain};
+    use test_case::test_case;
+
+    #[test]
+    fn natural_two_dimensions() {
+        // A 2x3 array of 2-byte unsigned integers.
+        let raw = chain!(
+            [0x02, 0x02, 0x03, 0x02, 0x01],
+            [0x00, 0x01, 0x00, 0x02, 0x00, 0x03],
+            [0x01, 0x00, 0x02, 0x00, 0x03, 0x00]
+        )
+        .collect_vec();
+        let array = MdArray::parse(&raw, KlvFormat::Uint).unwrap();
+
+        assert_eq!(array.shape(), &vec![2, 3]);
+        assert_eq!(array.element_size(), 2);
+        assert_eq!(array.apa(), Apa::Natural);
+        assert_eq!(array.get(&[0, 2]), Some(&SoftwareValue::Uint64(3)));
+        assert_eq!(array.get(&[1, 0]), Some(&SoftwareValue::Uint64(0x0100)));
+        assert_eq!(array.get(&[2, 0]), None);
+        assert_eq!(array.get(&[0]), None);
+    }
+
+    #[test]
+    fn natural_floats() {
+        let raw = chain!(
+            [0x01, 0x02, 0x04, 0x01],
+            1.5f32.to_be_bytes(),
+            (-2.0f32).to_be_bytes()
+        )
+        .collect_vec();
+        let array = MdArray::parse(&raw, KlvFormat::Float).unwrap();
+
+        assert_eq!(
+            array.values(),
+            &vec![SoftwareValue::Float64(1.5), SoftwareValue::Float64(-2.0)]
+        );
+    }
+
+    #[test]
+    fn imapb_one_dimension() {
+        // Three 1-byte confidences mapped onto 0.0..=1.0, which ST 1201
+        // encodes in steps of 1/128.
+        let raw = chain!(
+            [0x01, 0x03, 0x01, 0x02],
+            0.0f64.to_be_bytes(),
+            1.0f64.to_be_bytes(),
+            [0x00, 0x40, 0x80]
+        )
+        .collect_vec();
+        let array = MdArray::parse(&raw, KlvFormat::Uint).unwrap();
+
+        assert_eq!(array.apa(), Apa::Imapb { min: 0.0, max: 1.0 });
+        assert_eq!(
+            array.values(),
+            &vec![
+                SoftwareValue::Float64(0.0),
+                SoftwareValue::Float64(0.5),
+                SoftwareValue::Float64(1.0)
+            ]
+        );
+    }
+
+    #[test]
+    fn boolean_elements() {
+        let raw = [0x02, 0x02, 0x05, 0x01, 0x03, 0b1010_0110, 0b1100_0000];
+        let array = MdArray::parse(&raw, KlvFormat::Boolean).unwrap();
+
+        assert_eq!(
+            array
+                .values()
+                .iter()
+                .map(|value| *value == SoftwareValue::Bool(true))
+                .collect_vec(),
+            [
+                true, false, true, false, false, true, true, false, true, true
+            ]
+        );
+        assert_eq!(array.get(&[1, 4]), Some(&SoftwareValue::Bool(true)));
+    }
+
+    #[test_case(&[0x01, 0x04, 0x01, 0x01, 0xAA]; "Too few elements")]
+    #[test_case(&[0x01, 0x01, 0x01, 0x05, 0xAA]; "Unsupported APA")]
+    #[test_case(&[0x01, 0x01, 0x01, 0x02, 0x00]; "IMAPB without range")]
+    #[test_case(&[0x02, 0x01]; "Missing dimension")]
+    #[test_case(&[0x01, 0xA0, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x00, 0x10, 0x01, 0xAA]; "Size overflows")]
+    fn malformed(raw: &[u8]) {
+        assert!(MdArray::parse(raw, KlvFormat::Uint).is_err());
+    }
+}
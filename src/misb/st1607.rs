@@ -0,0 +1,195 @@
+//! MISB ST 1607 Amend Local Set.
+//!
+//! An Amend Local Set is nested in an item of the set it corrects, such as
+//! tag 101 of ST 0601, and holds items keyed by that set's own tags. Each
+//! item overrides the item of the same key in the set it amends, and an item
+//! with an empty value removes it instead.
+
+use std::io::{Read, Seek};
+
+use crate::{
+    encoding,
+    klv::{ItemContext, ItemKey, Klv, OwnedKlv},
+    local_set::{LocalSet, OwnedLocalSet},
+    tag::Tag,
+    time_index::PRECISION_TIME_STAMP_TAG,
+};
+
+/// Items of a single Amend Local Set.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Amendment {
+    /// Key of the item the amendment was carried in, e.g. `ItemKey::Tag(101)`
+    /// in ST 0601.
+    pub key: ItemKey,
+    /// Precision Time Stamp of the set the amendment was carried in, which
+    /// identifies the earlier set it corrects when the two are delivered
+    /// separately.
+    pub target: Option<u64>,
+    /// Items that override, or remove when empty, the items of the same key.
+    pub items: Vec<OwnedKlv>,
+}
+
+impl Amendment {
+    /// Reads an amendment nested in the value of an item keyed by `key`.
+    ///
+    /// Offsets in any error are relative to the first byte of `raw`.
+    pub fn from_value(key: ItemKey, raw: &[u8]) -> Result<Self, encoding::Error> {
+        Ok(Self {
+            key,
+            target: None,
+            items: Klv::read_stream(raw).collect::<Result<_, _>>()?,
+        })
+    }
+
+    /// Reads an amendment nested in the value of `klv`, which lies at
+    /// `context`.
+    ///
+    /// Errors are `encoding::Error::InContext` and hold `context`.
+    pub fn from_klv<T>(klv: &Klv<T>, context: &ItemContext) -> Result<Self, encoding::Error>
+    where
+        T: Read + Seek,
+    {
+        Self::from_value(klv.key(), &klv.read_value()?)
+            .map_err(|error| error.in_context(context.clone()))
+    }
+
+    /// Reads every amendment carried by `key` in `parent`, a set keyed by the
+    /// tags of `G`, in the order they appear in, each targeting the Precision
+    /// Time Stamp of `parent`.
+    pub fn from_local_set<G, T>(
+        parent: &LocalSet<T>,
+        key: &ItemKey,
+    ) -> Result<Vec<Self>, encoding::Error>
+    where
+        G: Tag,
+        T: Read + Seek,
+    {
+        let target = match parent.get(&ItemKey::Tag(PRECISION_TIME_STAMP_TAG)) {
+            Some(klv) => klv.read_value()?.try_into().ok().map(u64::from_be_bytes),
+            None => None,
+        };
+        parent
+            .iter()
+            .enumerate()
+            .filter(|(_, klv)| klv.key() == *key)
+            .map(|(index, klv)| {
+                let context = ItemContext::new::<G>(*key, index, klv.value_offset());
+                Ok(Self {
+                    target,
+                    ..Self::from_klv(klv, &context)?
+                })
+            })
+            .collect()
+    }
+
+    /// Writes the amendment as the value of the item it is carried in.
+    pub fn to_value(&self) -> Vec<u8> {
+        OwnedLocalSet::new(self.items.clone()).serialize_items()
+    }
+}
+
+/// Corrects `base` with each of `amendments` in turn, so a later amendment
+/// overrides an earlier one that changes the same item.
+///
+/// Items `base` has but no amendment touches keep their value and position.
+/// Items only an amendment has are added to the end. The items carrying the
+/// amendments are left out of the corrected set.
+pub fn apply_amendments(base: &OwnedLocalSet, amendments: &[Amendment]) -> OwnedLocalSet {
+    let mut corrected = base.clone();
+    for amendment in amendments {
+        corrected.remove(&amendment.key);
+        for klv in &amendment.items {
+            if klv.value().is_empty() {
+                corrected.remove(&klv.key());
+            } else {
+                corrected.set(klv.key(), klv.value().clone());
+            }
+        }
+    }
+    corrected
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::RefCell, io::Cursor, rc::Rc};
+
+    use super::*;
+    use crate::{misb::st0601::St0601Tag, universal_set::UniversalKey};
+
+    const AMEND: ItemKey = ItemKey::Tag(101);
+
+    fn amendment(items: Vec<OwnedKlv>) -> OwnedKlv {
+        OwnedKlv::with_key(
+            AMEND,
+            Amendment {
+                key: AMEND,
+                target: None,
+                items,
+            }
+            .to_value(),
+        )
+    }
+
+    fn read(carrier: &OwnedLocalSet) -> LocalSet<Cursor<Vec<u8>>> {
+        let packet = carrier.serialize(&UniversalKey::new([0x06; 16]), false);
+        LocalSet::read(0, Rc::new(RefCell::new(Cursor::new(packet)))).unwrap()
+    }
+
+    #[test]
+    fn amendments_are_applied_in_order() {
+        let base = OwnedLocalSet::new(vec![
+            OwnedKlv::new(2, 1_000_000u64.to_be_bytes().to_vec()),
+            OwnedKlv::new(3, b"ALPHA".to_vec()),
+            OwnedKlv::new(4, b"N123".to_vec()),
+            OwnedKlv::new(5, vec![0x12, 0x34]),
+        ]);
+        // A correction delivered after `base`, holding two amendments.
+        let carrier = read(&OwnedLocalSet::new(vec![
+            OwnedKlv::new(2, 1_000_000u64.to_be_bytes().to_vec()),
+            amendment(vec![
+                OwnedKlv::new(3, b"BRAVO".to_vec()),
+                OwnedKlv::new(5, Vec::new()),
+            ]),
+            amendment(vec![
+                OwnedKlv::new(3, b"CHARLIE".to_vec()),
+                OwnedKlv::new(10, b"PREDATOR".to_vec()),
+            ]),
+        ]));
+        let amendments = Amendment::from_local_set::<St0601Tag, _>(&carrier, &AMEND).unwrap();
+
+        assert_eq!(amendments.len(), 2);
+        assert!(
+            amendments
+                .iter()
+                .all(|amendment| amendment.target == Some(1_000_000))
+        );
+        assert_eq!(
+            apply_amendments(&base, &amendments),
+            OwnedLocalSet::new(vec![
+                OwnedKlv::new(2, 1_000_000u64.to_be_bytes().to_vec()),
+                OwnedKlv::new(3, b"CHARLIE".to_vec()),
+                OwnedKlv::new(4, b"N123".to_vec()),
+                OwnedKlv::new(10, b"PREDATOR".to_vec()),
+            ])
+        );
+        // Applied the other way around, the first amendment wins.
+        let reversed = [amendments[1].clone(), amendments[0].clone()];
+        assert_eq!(
+            apply_amendments(&base, &reversed).get(&ItemKey::Tag(3)),
+            Some(&OwnedKlv::new(3, b"BRAVO".to_vec()))
+        );
+    }
+
+    #[test]
+    fn truncated_amendment_is_an_error() {
+        let carrier = read(&OwnedLocalSet::new(vec![
+            OwnedKlv::new(2, 1_000_000u64.to_be_bytes().to_vec()),
+            OwnedKlv::with_key(AMEND, vec![0x03, 0x05, b'A']),
+        ]));
+        assert!(matches!(
+            Amendment::from_local_set::<St0601Tag, _>(&carrier, &AMEND),
+            Err(encoding::Error::InContext { context, .. })
+                if context == ItemContext::new::<St0601Tag>(AMEND, 1, 29)
+        ));
+    }
+}
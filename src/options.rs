@@ -0,0 +1,255 @@
+use core::fmt;
+#[cfg(feature = "std")]
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    sync::Arc,
+};
+
+use crate::encoding::ber::BerForm;
+#[cfg(feature = "std")]
+use crate::klv::ItemKey;
+
+/// Default upper bound, in bytes, on the size of a set payload that is read
+/// into memory in a single call instead of being parsed in place.
+pub const DEFAULT_BATCH_THRESHOLD: u64 = 4096;
+
+/// Default number of sets an item may be nested in, counting the set it is
+/// read from.
+pub const DEFAULT_MAX_DEPTH: usize = 8;
+
+/// Knobs that control how sets are parsed out of a buffer.
+#[derive(Clone, Debug)]
+pub struct ParseOptions {
+    /// Set payloads whose length is at or below this many bytes are read into
+    /// memory in one read and their items are parsed out of that copy.
+    ///
+    /// Parsing in place borrows the shared buffer and seeks for every tag,
+    /// length, and value, which dominates the cost of parsing the small sets
+    /// (a few hundred bytes) that make up most streams. Set to `0` to always
+    /// parse in place.
+    pub batch_threshold: u64,
+
+    /// Drop KLV fill items from the parsed sets.
+    ///
+    /// Fill items only pad packets out to a fixed size and their values are
+    /// meaningless. Their bytes are still stepped over so the offsets of the
+    /// items after them are unaffected.
+    pub skip_fill: bool,
+
+    /// What to do when an item key appears more than once in the same set.
+    pub duplicates: DuplicatePolicy,
+
+    /// Fail the parse when an item's length breaks a `ValueLength::Required`
+    /// or `ValueLength::Max` constraint of the tag table the set is read
+    /// with.
+    ///
+    /// When unset the item is kept and a warning is recorded on the set
+    /// instead.
+    pub strict_lengths: bool,
+
+    /// What to do with ST 0601 packets whose checksum does not match their
+    /// contents.
+    ///
+    /// Only sets keyed by the ST 0601 Universal Key are checked.
+    pub verify_checksum: ChecksumMode,
+
+    /// Items to keep in the parsed sets. `None` keeps every item.
+    ///
+    /// The lengths of the other items are still read so they can be stepped
+    /// over, but their values are not read and they are not stored. The
+    /// checksum of an ST 0601 packet is verified even when its checksum item
+    /// is not wanted.
+    #[cfg(feature = "std")]
+    pub tag_filter: Option<TagFilter>,
+
+    /// Number of sets an item may be nested in, counting the outermost set,
+    /// before reading the set nested in its value fails with
+    /// `encoding::Error::DepthExceeded`.
+    ///
+    /// Guards against corrupt or crafted streams that nest sets deeply
+    /// enough to exhaust the stack of code walking them recursively.
+    pub max_depth: usize,
+
+    /// Number of Universal Sets that may be read from one stream before
+    /// reading another fails with `encoding::Error::LimitExceeded`. `None`
+    /// reads every set.
+    pub max_packets: Option<usize>,
+
+    /// Number of bytes of set payloads that may be read from one stream
+    /// before reading another set fails with
+    /// `encoding::Error::LimitExceeded`. `None` reads every set.
+    ///
+    /// A set is checked against the limit before its payload is read, so a
+    /// single set claiming a huge length is refused without reading it.
+    pub max_total_value_bytes: Option<u64>,
+
+    /// Number of items a single set may keep before parsing it fails with
+    /// `encoding::Error::LimitExceeded`. `None` keeps every item.
+    pub max_items_per_set: Option<usize>,
+}
+
+impl Default for ParseOptions {
+    fn default() -> Self {
+        Self {
+            batch_threshold: DEFAULT_BATCH_THRESHOLD,
+            skip_fill: true,
+            duplicates: DuplicatePolicy::default(),
+            strict_lengths: false,
+            verify_checksum: ChecksumMode::default(),
+            #[cfg(feature = "std")]
+            tag_filter: None,
+            max_depth: DEFAULT_MAX_DEPTH,
+            max_packets: None,
+            max_total_value_bytes: None,
+            max_items_per_set: None,
+        }
+    }
+}
+
+/// A resource ceiling of `ParseOptions` for reading untrusted streams.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Limit {
+    /// `ParseOptions::max_packets`
+    Packets,
+    /// `ParseOptions::max_total_value_bytes`
+    TotalValueBytes,
+    /// `ParseOptions::max_items_per_set`
+    ItemsPerSet,
+}
+
+impl fmt::Display for Limit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Packets => "packets",
+            Self::TotalValueBytes => "total value bytes",
+            Self::ItemsPerSet => "items per set",
+        })
+    }
+}
+
+/// How repeated item keys within a single set are handled.
+///
+/// The standards generally say a later item overrides an earlier one with
+/// the same key, but some producers repeat keys on purpose, such as strings
+/// split across several items.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum DuplicatePolicy {
+    /// Only the last occurrence of each key is kept.
+    KeepLast,
+    /// Only the first occurrence of each key is kept.
+    KeepFirst,
+    /// Every occurrence is kept. Looking up a key by itself returns the last
+    /// occurrence.
+    #[default]
+    KeepAll,
+    /// A repeated key fails the parse.
+    Error,
+}
+
+/// How packets whose checksum does not match their contents are handled.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ChecksumMode {
+    /// The checksum is not checked.
+    #[default]
+    Ignore,
+    /// The packet is kept and a warning is recorded on its set.
+    Warn,
+    /// Parsing the packet fails. Packets read in bulk are skipped.
+    Reject,
+}
+
+/// Which items of a set are kept when it is parsed.
+#[cfg(feature = "std")]
+#[derive(Clone)]
+pub enum TagFilter {
+    /// Only items with one of these keys are kept.
+    Keys(BTreeSet<ItemKey>),
+    /// Only items whose key the function returns `true` for are kept.
+    Predicate(Arc<dyn Fn(&ItemKey) -> bool + Send + Sync>),
+}
+
+#[cfg(feature = "std")]
+impl TagFilter {
+    /// Keeps only the items with one of `keys`.
+    pub fn keys(keys: impl IntoIterator<Item = ItemKey>) -> Self {
+        Self::Keys(keys.into_iter().collect())
+    }
+
+    /// Keeps only the items whose key `wanted` returns `true` for.
+    pub fn predicate(wanted: impl Fn(&ItemKey) -> bool + Send + Sync + 'static) -> Self {
+        Self::Predicate(Arc::new(wanted))
+    }
+
+    /// Whether items with `key` are kept.
+    pub fn wants(&self, key: &ItemKey) -> bool {
+        match self {
+            Self::Keys(keys) => keys.contains(key),
+            Self::Predicate(wanted) => wanted(key),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl fmt::Debug for TagFilter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Keys(keys) => f.debug_tuple("Keys").field(keys).finish(),
+            Self::Predicate(_) => f.debug_tuple("Predicate").finish_non_exhaustive(),
+        }
+    }
+}
+
+/// Knobs that control how an edited set is written back out.
+#[cfg(feature = "std")]
+#[derive(Clone, Debug, Default)]
+pub struct SerializeOptions {
+    /// Drop any existing ST 0601 checksum item and write a new one covering
+    /// the whole packet as the last item.
+    pub checksum: bool,
+
+    /// Write the tag and length of every item that was copied out of a
+    /// buffer and not changed since exactly as they were read, including any
+    /// padded BER encodings, instead of in as few bytes as they fit in.
+    ///
+    /// Keeps the bytes of a packet that is read, edited, and written back out
+    /// identical everywhere but the edited items, for tools comparing packets
+    /// byte for byte or muxers that expect packets of a fixed size.
+    pub preserve_encodings: bool,
+
+    /// Number of bytes to write the length of the set in. `None` writes it in
+    /// as few bytes as it fits in.
+    pub set_length_width: Option<usize>,
+
+    /// Forms to write the lengths of items with these keys in, which take
+    /// precedence over `preserve_encodings`.
+    pub length_forms: BTreeMap<ItemKey, LengthForm>,
+}
+
+#[cfg(feature = "std")]
+impl SerializeOptions {
+    /// Writes the length of every item keyed by `key` in `form`.
+    pub fn force_length_form(mut self, key: ItemKey, form: impl Into<LengthForm>) -> Self {
+        self.length_forms.insert(key, form.into());
+        self
+    }
+}
+
+/// How the BER length of an item is written.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum LengthForm {
+    /// As few bytes as the length fits in.
+    #[default]
+    Minimal,
+    /// Exactly this many bytes, padding the long form with leading zero bytes
+    /// as needed. A width of 1 is the short form.
+    Width(usize),
+}
+
+impl From<BerForm> for LengthForm {
+    fn from(form: BerForm) -> Self {
+        match form {
+            BerForm::Minimal => Self::Minimal,
+            BerForm::LongForm(count) => Self::Width(usize::from(count) + 1),
+        }
+    }
+}
@@ -0,0 +1,270 @@
+//! Parsing Universal Sets on a pool of worker threads, for streams arriving
+//! faster than a single thread can parse them.
+
+use std::{
+    collections::BTreeMap,
+    io::Read,
+    num::NonZeroUsize,
+    sync::{
+        Arc,
+        atomic::{AtomicBool, Ordering},
+    },
+    thread,
+};
+
+use crossbeam_channel::{Receiver, bounded};
+
+use crate::{
+    encoding,
+    klv::KlvReader,
+    local_set::OwnedLocalSet,
+    universal_set::{UniversalKey, UniversalSetReader},
+};
+
+/// Default number of sets that may wait between the stages of a `Pipeline`.
+pub const DEFAULT_CAPACITY: usize = 64;
+
+/// Knobs that control how a `Pipeline` is run.
+#[derive(Clone, Debug)]
+pub struct PipelineOptions {
+    /// Number of worker threads parsing payloads. Zero is treated as one.
+    pub workers: usize,
+
+    /// Number of payloads the reader thread may get ahead of the workers, and
+    /// of parsed sets the workers may get ahead of the iterator. Zero is
+    /// treated as one.
+    pub capacity: usize,
+}
+
+impl Default for PipelineOptions {
+    /// One worker for every thread the machine can run in parallel.
+    fn default() -> Self {
+        Self {
+            workers: thread::available_parallelism().map_or(1, NonZeroUsize::get),
+            capacity: DEFAULT_CAPACITY,
+        }
+    }
+}
+
+/// Stops the reader thread of a `Pipeline` from reading any more sets.
+///
+/// Handles can be cloned and sent to other threads.
+#[derive(Clone, Debug, Default)]
+pub struct ShutdownHandle(Arc<AtomicBool>);
+
+impl ShutdownHandle {
+    /// Stops the reader thread once it has finished reading the set it is
+    /// reading. Sets that were already read are still parsed and yielded.
+    pub fn shutdown(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    /// Whether `ShutdownHandle::shutdown` has been called on any clone of the
+    /// handle.
+    pub fn is_shutdown(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+/// Result of parsing a set, tagged with the position of the set in the
+/// stream.
+type Sequenced = (u64, Result<OwnedLocalSet, encoding::Error>);
+
+/// Iterator over the Universal Sets in a reader, read on one thread and
+/// parsed on a pool of worker threads.
+///
+/// The reader thread copies the payload of every set keyed by `key` out of
+/// the reader the same way `UniversalSetReader` does and hands it to the
+/// workers through a bounded channel. Sets are yielded in the order they
+/// appear in the stream no matter which worker parsed them.
+///
+/// A payload whose items can't be parsed yields an error in its place and
+/// iteration carries on. Errors reading the stream end iteration after every
+/// set before them has been yielded.
+///
+/// Dropping the pipeline shuts it down. A reader thread blocked reading from
+/// its reader exits once the read returns.
+#[derive(Debug)]
+pub struct Pipeline {
+    results: Receiver<Sequenced>,
+    /// Results that arrived before every result ahead of them.
+    pending: BTreeMap<u64, Result<OwnedLocalSet, encoding::Error>>,
+    /// Sequence number of the next result to yield.
+    next: u64,
+    shutdown: ShutdownHandle,
+}
+
+impl Pipeline {
+    /// Starts the reader thread and workers on `reader`.
+    pub fn spawn<R>(key: UniversalKey, reader: R, options: &PipelineOptions) -> Self
+    where
+        R: Read + Send + 'static,
+    {
+        let capacity = options.capacity.max(1);
+        let (payloads, work) = bounded::<(u64, Vec<u8>)>(capacity);
+        let (parsed, results) = bounded::<Sequenced>(capacity);
+        let shutdown = ShutdownHandle::default();
+
+        for _ in 0..options.workers.max(1) {
+            let work = work.clone();
+            let parsed = parsed.clone();
+            thread::spawn(move || {
+                for (sequence, payload) in work {
+                    if parsed.send((sequence, parse_payload(&payload))).is_err() {
+                        break;
+                    }
+                }
+            });
+        }
+
+        let stop = shutdown.clone();
+        thread::spawn(move || {
+            let mut sets = UniversalSetReader::new(key, reader);
+            let mut sequence = 0;
+            while !stop.is_shutdown()
+                && let Some(payload) = sets.next_payload()
+            {
+                let sent = match payload {
+                    Ok(payload) => payloads.send((sequence, payload)).is_ok(),
+                    Err(e) => parsed.send((sequence, Err(e))).is_ok(),
+                };
+                if !sent {
+                    break;
+                }
+                sequence += 1;
+            }
+        });
+
+        Self {
+            results,
+            pending: BTreeMap::new(),
+            next: 0,
+            shutdown,
+        }
+    }
+
+    /// Handle that stops the pipeline from reading any more sets.
+    pub fn shutdown_handle(&self) -> ShutdownHandle {
+        self.shutdown.clone()
+    }
+}
+
+impl Iterator for Pipeline {
+    type Item = Result<OwnedLocalSet, encoding::Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(result) = self.pending.remove(&self.next) {
+                self.next += 1;
+                return Some(result);
+            }
+            // Every sender is gone once the reader thread and workers have
+            // finished.
+            let (sequence, result) = self.results.recv().ok()?;
+            self.pending.insert(sequence, result);
+        }
+    }
+}
+
+impl Drop for Pipeline {
+    fn drop(&mut self) {
+        self.shutdown.shutdown();
+    }
+}
+
+/// Parses every item in the payload of a set.
+fn parse_payload(payload: &[u8]) -> Result<OwnedLocalSet, encoding::Error> {
+    KlvReader::new(payload)
+        .collect::<Result<_, _>>()
+        .map(OwnedLocalSet::new)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use super::*;
+    use crate::{
+        keys,
+        klv::{ItemKey, OwnedKlv},
+        universal_set::UNIVERSAL_KEY_LENGTH,
+    };
+    use itertools::Itertools;
+    use test_case::test_case;
+
+    const PACKETS: u64 = 300;
+
+    /// Stream of ST 0601 packets whose timestamps count up from zero.
+    fn stream() -> Vec<u8> {
+        (0..PACKETS)
+            .flat_map(|i| {
+                OwnedLocalSet::new(vec![
+                    OwnedKlv::new(2, i.to_be_bytes().to_vec()),
+                    OwnedKlv::new(3, vec![b'A'; (i % 50) as usize]),
+                ])
+                .serialize(&keys::ST0601, true)
+            })
+            .collect()
+    }
+
+    fn timestamp(set: &OwnedLocalSet) -> u64 {
+        let value = set.get(&ItemKey::Tag(2)).unwrap().value();
+        u64::from_be_bytes(value.as_slice().try_into().unwrap())
+    }
+
+    #[test_case(1; "One worker")]
+    #[test_case(4; "Four workers")]
+    fn sets_are_yielded_in_order(workers: usize) {
+        let options = PipelineOptions {
+            workers,
+            capacity: 8,
+        };
+        let sets = Pipeline::spawn(keys::ST0601, Cursor::new(stream()), &options)
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+
+        assert_eq!(
+            sets.iter().map(timestamp).collect_vec(),
+            (0..PACKETS).collect_vec()
+        );
+    }
+
+    #[test]
+    fn payload_errors_are_yielded_in_place() {
+        let mut buf = stream();
+        // The packet after the first one is cut short so its last item runs
+        // past the end of its payload.
+        let second = buf
+            .windows(UNIVERSAL_KEY_LENGTH)
+            .positions(|window| window == &keys::ST0601[..])
+            .nth(1)
+            .unwrap();
+        buf[second + UNIVERSAL_KEY_LENGTH] -= 2;
+
+        let results = Pipeline::spawn(keys::ST0601, Cursor::new(buf), &PipelineOptions::default())
+            .collect_vec();
+
+        assert!(results[0].is_ok());
+        assert!(results[1].is_err());
+        assert_eq!(timestamp(results[2].as_ref().unwrap()), 2);
+    }
+
+    #[test]
+    fn shutdown_stops_reading() {
+        let options = PipelineOptions {
+            workers: 2,
+            capacity: 1,
+        };
+        let mut pipeline = Pipeline::spawn(keys::ST0601, Cursor::new(stream()), &options);
+        let first = pipeline.next().unwrap().unwrap();
+        pipeline.shutdown_handle().shutdown();
+
+        let rest = pipeline.map(Result::unwrap).collect_vec();
+        assert_eq!(timestamp(&first), 0);
+        assert!((rest.len() as u64) < PACKETS - 1);
+        assert_eq!(
+            rest.iter().map(timestamp).collect_vec(),
+            (1..=rest.len() as u64).collect_vec()
+        );
+    }
+}
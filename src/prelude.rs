@@ -0,0 +1,28 @@
+//! Types and functions most programs reading KLV need, for glob importing.
+//!
+//! ```
+//! use klv::prelude::*;
+//! use std::{cell::RefCell, io::Cursor, rc::Rc};
+//!
+//! let packet = OwnedLocalSet::new(vec![OwnedKlv::new(2, vec![0x00; 8])])
+//!     .serialize(&keys::ST0601, true);
+//! let buf = Rc::new(RefCell::new(Cursor::new(packet)));
+//! let sets = UniversalSet::read_all(keys::ST0601, buf)?;
+//! assert_eq!(sets[0].data()[&ItemKey::Tag(2)].length(), 8);
+//! # Ok::<(), Error>(())
+//! ```
+
+pub use crate::{
+    encoding::{
+        Error,
+        ber::{decode_ber, encode_ber, read_ber, read_ber_counted},
+        ber_oid::{decode_ber_oid, encode_ber_oid, read_ber_oid, read_ber_oid_counted},
+    },
+    keys,
+    klv::{ItemKey, Klv, KlvReader, OwnedKlv, ReadSeek},
+    local_set::{LocalSet, OwnedLocalSet},
+    options::{ChecksumMode, DuplicatePolicy, ParseOptions},
+    tag::{Tag, TagReading},
+    typed::TypedLocalSet,
+    universal_set::{OwnedUniversalSet, UniversalKey, UniversalSet, UniversalSetReader},
+};
@@ -0,0 +1,105 @@
+//! Reporting how far a long scan has got and cancelling it part way through.
+
+use std::{
+    fmt,
+    sync::{
+        Arc,
+        atomic::{AtomicBool, Ordering},
+    },
+};
+
+/// Default number of bytes between calls to the callback of a
+/// `ProgressHandle`.
+pub const DEFAULT_PROGRESS_INTERVAL: u64 = 1 << 20;
+
+/// Callback given the number of bytes processed so far and the total number
+/// of bytes, if it is known.
+pub type ProgressCallback = Box<dyn FnMut(u64, Option<u64>)>;
+
+/// Lets a caller follow a scan of a large buffer and cancel it.
+///
+/// Every time the scan gets another `interval` bytes further, the callback
+/// is called and the cancellation token is checked. A cancelled scan fails
+/// with `encoding::Error::Cancelled`, which carries what it found so far.
+pub struct ProgressHandle {
+    callback: Option<ProgressCallback>,
+    cancelled: Arc<AtomicBool>,
+    interval: u64,
+    /// Number of bytes processed at which the callback is next called.
+    next_report: u64,
+}
+
+impl ProgressHandle {
+    /// Handle that reports every `interval` bytes. An interval of 0 is
+    /// treated as 1.
+    pub fn new(interval: u64) -> Self {
+        let interval = interval.max(1);
+        Self {
+            callback: None,
+            cancelled: Arc::new(AtomicBool::new(false)),
+            interval,
+            next_report: interval,
+        }
+    }
+
+    /// Calls `callback` with the progress of the scan.
+    pub fn with_callback(mut self, callback: impl FnMut(u64, Option<u64>) + 'static) -> Self {
+        self.callback = Some(Box::new(callback));
+        self
+    }
+
+    /// Number of bytes between calls to the callback.
+    pub fn interval(&self) -> u64 {
+        self.interval
+    }
+
+    /// Token that cancels the scan once set. It can be set from another
+    /// thread or from inside the callback.
+    pub fn cancel_token(&self) -> Arc<AtomicBool> {
+        self.cancelled.clone()
+    }
+
+    /// Cancels the scan at the next report.
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    /// Whether the scan has been cancelled.
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+
+    /// Records that `processed` bytes have been processed, calling the
+    /// callback and checking for cancellation if another `interval` bytes
+    /// have been processed since the last call.
+    ///
+    /// # Returns
+    ///
+    /// `false` when the scan has been cancelled and should stop.
+    pub(crate) fn report(&mut self, processed: u64, total: Option<u64>) -> bool {
+        if processed < self.next_report {
+            return true;
+        }
+        self.next_report = processed.saturating_add(self.interval);
+        if let Some(callback) = &mut self.callback {
+            callback(processed, total);
+        }
+        !self.is_cancelled()
+    }
+}
+
+impl Default for ProgressHandle {
+    fn default() -> Self {
+        Self::new(DEFAULT_PROGRESS_INTERVAL)
+    }
+}
+
+impl fmt::Debug for ProgressHandle {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ProgressHandle")
+            .field("cancelled", &self.is_cancelled())
+            .field("interval", &self.interval)
+            .field("next_report", &self.next_report)
+            .finish_non_exhaustive()
+    }
+}
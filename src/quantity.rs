@@ -0,0 +1,144 @@
+//! Values of items as physical quantities, so that values in different units
+//! can't be mixed up.
+
+use uom::si::{
+    angle::degree,
+    f64::{Angle, Length, Mass, Pressure, ThermodynamicTemperature, Velocity},
+    length::meter,
+    mass::kilogram,
+    pressure::{millibar, pascal},
+    thermodynamic_temperature::{degree_celsius, kelvin},
+    velocity::meter_per_second,
+};
+
+use crate::{encoding, format::SoftwareValue, tag::Tag, typed::TypedLocalSet};
+
+/// Value of an item in the unit its tag gives it.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Quantity {
+    Length(Length),
+    Angle(Angle),
+    Velocity(Velocity),
+    Pressure(Pressure),
+    Temperature(ThermodynamicTemperature),
+    Mass(Mass),
+    /// Value whose unit has no quantity, such as pixels or percent, or whose
+    /// tag gives no unit. The unit is kept as the tag gives it.
+    Other {
+        value: f64,
+        unit: Option<&'static str>,
+    },
+}
+
+impl Quantity {
+    /// Quantity of `value` in `unit`, as written in the _Units_ column of MISB
+    /// tag tables.
+    pub fn new(value: f64, unit: Option<&'static str>) -> Self {
+        match unit {
+            Some("m") => Self::Length(Length::new::<meter>(value)),
+            Some("deg") => Self::Angle(Angle::new::<degree>(value)),
+            Some("m/s") => Self::Velocity(Velocity::new::<meter_per_second>(value)),
+            Some("Pa") => Self::Pressure(Pressure::new::<pascal>(value)),
+            Some("mbar") => Self::Pressure(Pressure::new::<millibar>(value)),
+            Some("K") => Self::Temperature(ThermodynamicTemperature::new::<kelvin>(value)),
+            Some("°C") => {
+                Self::Temperature(ThermodynamicTemperature::new::<degree_celsius>(value))
+            }
+            Some("kg") => Self::Mass(Mass::new::<kilogram>(value)),
+            _ => Self::Other { value, unit },
+        }
+    }
+}
+
+impl<G> TypedLocalSet<G>
+where
+    G: Tag,
+{
+    /// Converts the value of `tag` with `Tag::decode` into a quantity in the
+    /// unit given by `Tag::unit`.
+    ///
+    /// # Returns
+    ///
+    /// - `Ok(Some(Quantity))` - `tag` is in the set and was converted.
+    /// - `Ok(None)` - `tag` is not in the set or is one of its special values.
+    /// - `Err(encoding::Error)` - The value could not be converted or is not a
+    ///   number.
+    pub fn get_quantity(&self, tag: G) -> Result<Option<Quantity>, encoding::Error> {
+        let value = match self.decode(tag)? {
+            None => return Ok(None),
+            Some(SoftwareValue::Float64(value)) => value,
+            Some(SoftwareValue::Uint64(value)) => value as f64,
+            Some(SoftwareValue::Int64(value)) => value as f64,
+            Some(value) => {
+                return Err(encoding::Error::IncompatibleFormats {
+                    from: format!("{:?} value of {}", value.format(), tag.name()),
+                    to: "a quantity".into(),
+                });
+            }
+        };
+        Ok(Some(Quantity::new(value, tag.unit())))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::RefCell, io::Cursor, rc::Rc};
+
+    use super::*;
+    use crate::{
+        local_set::LocalSet, misb::st0601::St0601Tag, universal_set::UNIVERSAL_KEY_LENGTH,
+    };
+    use itertools::{Itertools, chain};
+    use uom::si::length::foot;
+
+    fn set() -> TypedLocalSet<St0601Tag> {
+        let buf = chain!(
+            [0x00; UNIVERSAL_KEY_LENGTH],
+            [0x10],
+            [0x0F, 0x02, 0xC2, 0x21],
+            [0x27, 0x01, 0x54],
+            [0x2B, 0x01, 0x03],
+            [0x03, 0x04],
+            *b"MISB"
+        )
+        .collect_vec();
+        let set = LocalSet::read(0, Rc::new(RefCell::new(Cursor::new(buf)))).unwrap();
+        TypedLocalSet::from_local_set(&set).unwrap()
+    }
+
+    #[test]
+    fn altitude_is_a_length() {
+        // Example value from MISB ST 0601.
+        let Some(Quantity::Length(altitude)) =
+            set().get_quantity(St0601Tag::SensorTrueAltitude).unwrap()
+        else {
+            panic!("Altitude is a length");
+        };
+        assert!((altitude.get::<meter>() - 14190.72).abs() < 0.01);
+        assert!((altitude.get::<foot>() - 46557.48).abs() < 0.01);
+    }
+
+    #[test]
+    fn temperature_is_in_celsius() {
+        let Some(Quantity::Temperature(temperature)) = set()
+            .get_quantity(St0601Tag::OutsideAirTemperature)
+            .unwrap()
+        else {
+            panic!("Temperature is a temperature");
+        };
+        assert!((temperature.get::<kelvin>() - 357.15).abs() < 0.001);
+    }
+
+    #[test]
+    fn unknown_unit_is_kept() {
+        assert_eq!(
+            set().get_quantity(St0601Tag::TargetTrackGateWidth).unwrap(),
+            Some(Quantity::Other {
+                value: 6.0,
+                unit: Some("pixels")
+            })
+        );
+        assert_eq!(set().get_quantity(St0601Tag::SlantRange).unwrap(), None);
+        assert!(set().get_quantity(St0601Tag::MissionId).is_err());
+    }
+}
@@ -0,0 +1,225 @@
+//! Dictionaries of Universal Labels loaded at runtime.
+//!
+//! The SMPTE RP 210 and MISB registries define far too many ULs to compile
+//! into the crate, so they can instead be exported to CSV or JSON and loaded
+//! into a `UlDictionary` when needed.
+
+use std::{collections::HashMap, io::Read};
+
+use crate::universal_set::{ParseKeyError, UL_VERSION_BYTE, UNIVERSAL_KEY_LENGTH, UniversalKey};
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error(transparent)]
+    Csv(#[from] csv::Error),
+    #[error(transparent)]
+    Json(#[from] serde_json::Error),
+    #[error("Entry {entry} has an invalid UL `{ul}`: {source}")]
+    InvalidKey {
+        /// Zero based index of the entry in the dictionary file.
+        entry: usize,
+        ul: String,
+        source: ParseKeyError,
+    },
+}
+
+/// Definition of a single UL.
+#[derive(Clone, Debug, PartialEq, Eq, getset::CopyGetters, getset::Getters)]
+pub struct UlEntry {
+    #[getset(get_copy = "pub")]
+    key: UniversalKey,
+
+    #[getset(get = "pub")]
+    name: String,
+
+    #[getset(get = "pub")]
+    definition: String,
+
+    /// Type of the value identified by the UL, in whatever form the registry
+    /// it came from uses.
+    #[getset(get = "pub")]
+    format: String,
+}
+
+/// Entry as it is written in a dictionary file.
+#[derive(Debug, serde::Deserialize)]
+struct RawEntry {
+    #[serde(alias = "UL", alias = "key", alias = "Key")]
+    ul: String,
+    #[serde(alias = "Name")]
+    name: String,
+    #[serde(default, alias = "Definition")]
+    definition: String,
+    #[serde(default, alias = "Format", alias = "Type", alias = "type")]
+    format: String,
+}
+
+/// Set of UL definitions that keys can be looked up in.
+#[derive(Clone, Debug, Default)]
+pub struct UlDictionary {
+    entries: Vec<UlEntry>,
+    /// Index into `entries` for each key exactly as it was defined.
+    exact: HashMap<UniversalKey, usize>,
+    /// Index into `entries` for each key with its version byte cleared.
+    masked: HashMap<UniversalKey, usize>,
+}
+
+impl UlDictionary {
+    /// Loads a dictionary from CSV with a header row.
+    ///
+    /// The `ul` and `name` columns are required while `definition` and
+    /// `format` are optional.
+    pub fn from_csv<R>(reader: R) -> Result<Self, Error>
+    where
+        R: Read,
+    {
+        let raw = csv::ReaderBuilder::new()
+            .trim(csv::Trim::All)
+            .from_reader(reader)
+            .into_deserialize()
+            .collect::<Result<Vec<RawEntry>, _>>()?;
+        Self::from_raw(raw)
+    }
+
+    /// Loads a dictionary from a JSON array of objects with the same fields
+    /// as the columns read by `from_csv`.
+    pub fn from_json<R>(reader: R) -> Result<Self, Error>
+    where
+        R: Read,
+    {
+        Self::from_raw(serde_json::from_reader(reader)?)
+    }
+
+    fn from_raw(raw: Vec<RawEntry>) -> Result<Self, Error> {
+        let mut dictionary = Self::default();
+        for (entry, raw) in raw.into_iter().enumerate() {
+            let key = raw.ul.parse().map_err(|source| Error::InvalidKey {
+                entry,
+                ul: raw.ul.clone(),
+                source,
+            })?;
+            dictionary.insert(UlEntry {
+                key,
+                name: raw.name,
+                definition: raw.definition,
+                format: raw.format,
+            });
+        }
+        Ok(dictionary)
+    }
+
+    /// Adds an entry, replacing any existing entry with the same key.
+    pub fn insert(&mut self, entry: UlEntry) {
+        if let Some(index) = self.exact.get(&entry.key) {
+            self.entries[*index] = entry;
+            return;
+        }
+
+        let index = self.entries.len();
+        self.exact.insert(entry.key, index);
+        self.masked.entry(mask_version(&entry.key)).or_insert(index);
+        self.entries.push(entry);
+    }
+
+    /// Finds the definition of `key`.
+    ///
+    /// Keys that only differ in their version byte are considered to be the
+    /// same UL, so an exact match is preferred but any version will be
+    /// returned if there isn't one.
+    pub fn lookup(&self, key: &UniversalKey) -> Option<&UlEntry> {
+        self.exact
+            .get(key)
+            .or_else(|| self.masked.get(&mask_version(key)))
+            .map(|index| &self.entries[*index])
+    }
+
+    /// Name of `key` if it is in the dictionary, otherwise the key itself.
+    pub fn label(&self, key: &UniversalKey) -> String {
+        match self.lookup(key) {
+            Some(entry) => entry.name.clone(),
+            None => key.to_string(),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+impl UlEntry {
+    pub fn new(key: UniversalKey, name: String, definition: String, format: String) -> Self {
+        Self {
+            key,
+            name,
+            definition,
+            format,
+        }
+    }
+}
+
+fn mask_version(key: &UniversalKey) -> UniversalKey {
+    let mut bytes: [u8; UNIVERSAL_KEY_LENGTH] = **key;
+    bytes[UL_VERSION_BYTE] = 0;
+    UniversalKey::new(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::keys;
+    use test_case::test_case;
+
+    const SAMPLE_CSV: &[u8] = include_bytes!("../tests/data/ul_dictionary.csv");
+    const SAMPLE_JSON: &[u8] = include_bytes!("../tests/data/ul_dictionary.json");
+
+    #[test_case(UlDictionary::from_csv(SAMPLE_CSV).unwrap(); "CSV")]
+    #[test_case(UlDictionary::from_json(SAMPLE_JSON).unwrap(); "JSON")]
+    fn test_lookup(dictionary: UlDictionary) {
+        assert_eq!(dictionary.len(), 3);
+
+        let entry = dictionary.lookup(&keys::ST0601).unwrap();
+        assert_eq!(entry.name(), "UAS Datalink Local Set");
+        assert_eq!(entry.format(), "Local Set");
+
+        let st0102 = "060e2b34.020b0101.0e010303.02000000".parse().unwrap();
+        assert_eq!(
+            dictionary.label(&st0102),
+            "Security Metadata Local Set".to_string()
+        );
+
+        let unknown = UniversalKey::new([0x11; UNIVERSAL_KEY_LENGTH]);
+        assert_eq!(dictionary.lookup(&unknown), None);
+        assert_eq!(
+            dictionary.label(&unknown),
+            "11111111.11111111.11111111.11111111"
+        );
+    }
+
+    #[test]
+    fn test_lookup_ignores_version() {
+        let dictionary = UlDictionary::from_csv(SAMPLE_CSV).unwrap();
+        let mut bytes = *keys::ST0601;
+        bytes[UL_VERSION_BYTE] = 0x0A;
+        assert_eq!(
+            dictionary.lookup(&UniversalKey::new(bytes)).unwrap().key(),
+            keys::ST0601
+        );
+    }
+
+    #[test]
+    fn test_invalid_key() {
+        let csv = "ul,name\n060e2b34,Short\n";
+        assert!(matches!(
+            UlDictionary::from_csv(csv.as_bytes()),
+            Err(Error::InvalidKey {
+                entry: 0,
+                source: ParseKeyError::InvalidLength(8),
+                ..
+            })
+        ));
+    }
+}
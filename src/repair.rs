@@ -0,0 +1,265 @@
+//! Repair of packets whose items are intact but whose outer length or
+//! checksum is not, as left behind by tools that edit items in place.
+//!
+//! Packets are expected to hold a local set with BER-OID tags and BER
+//! lengths, such as ST 0601, and to be exactly one packet long.
+
+use crate::{
+    encoding::{
+        ber::{decode_ber, encode_ber},
+        ber_oid::decode_ber_oid,
+    },
+    misb::st0601::{self, St0601Tag},
+    universal_set::UNIVERSAL_KEY_LENGTH,
+};
+
+/// Something wrong with a packet.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Problem {
+    /// The length after the Universal Key is not the number of bytes that
+    /// follow it.
+    OuterLength { declared: u64, actual: u64 },
+    /// The length after the Universal Key could not be read. The items are
+    /// assumed to start at the byte after it.
+    UnreadableLength,
+    /// The checksum item does not match the packet.
+    StaleChecksum { stored: u16, computed: u16 },
+    /// The item whose tag starts at `offset` runs past the end of the packet.
+    TruncatedItem { offset: u64 },
+}
+
+impl Problem {
+    /// Whether `fix_packet` can repair the problem.
+    pub fn is_repairable(&self) -> bool {
+        !matches!(self, Self::UnreadableLength | Self::TruncatedItem { .. })
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, thiserror::Error)]
+pub enum RepairError {
+    #[error("Packet is shorter than a Universal Key")]
+    NoKey,
+    #[error("Packet has problems that can't be repaired: {0:?}")]
+    Unrepairable(Vec<Problem>),
+}
+
+/// Packet returned by `fix_packet`.
+#[derive(Clone, Debug, PartialEq, Eq, getset::Getters)]
+#[getset(get = "pub")]
+pub struct Repaired {
+    packet: Vec<u8>,
+    /// Every problem that was fixed. Empty when the packet was already valid.
+    fixed: Vec<Problem>,
+}
+
+impl Repaired {
+    pub fn into_packet(self) -> Vec<u8> {
+        self.packet
+    }
+}
+
+/// Where the parts of a packet lie.
+struct Layout {
+    /// Length as written after the Universal Key, if it could be read.
+    declared: Option<u64>,
+    /// Offset of the first byte of the first item.
+    items_start: usize,
+    /// Offset of the first byte of the value of the checksum item, if the
+    /// packet has a two byte one.
+    checksum_value: Option<usize>,
+    truncated: Option<usize>,
+}
+
+impl Layout {
+    fn read(bytes: &[u8]) -> Result<Self, RepairError> {
+        let rest = bytes
+            .get(UNIVERSAL_KEY_LENGTH..)
+            .ok_or(RepairError::NoKey)?;
+        let (declared, items_start) = match decode_ber(rest) {
+            Ok((length, width)) => (u64::try_from(length).ok(), UNIVERSAL_KEY_LENGTH + width),
+            Err(_) => (None, UNIVERSAL_KEY_LENGTH + 1),
+        };
+
+        let mut layout = Self {
+            declared,
+            items_start,
+            checksum_value: None,
+            truncated: None,
+        };
+        let mut position = items_start;
+        while position < bytes.len() {
+            let Some(value_end) = Self::item(bytes, position, &mut layout) else {
+                layout.truncated = Some(position);
+                break;
+            };
+            position = value_end;
+        }
+        Ok(layout)
+    }
+
+    /// Reads the item whose tag starts at `position`, returning the offset of
+    /// the byte after its value.
+    fn item(bytes: &[u8], position: usize, layout: &mut Self) -> Option<usize> {
+        let (tag, tag_width) = decode_ber_oid(&bytes[position..]).ok()?;
+        let length_start = position + tag_width;
+        let (length, length_width) = decode_ber(bytes.get(length_start..)?).ok()?;
+        let value_start = length_start + length_width;
+        let value_end = value_start.checked_add(usize::try_from(length).ok()?)?;
+        if value_end > bytes.len() {
+            return None;
+        }
+        if tag == u128::from(St0601Tag::Checksum) && length == 2 {
+            layout.checksum_value = Some(value_start);
+        }
+        Some(value_end)
+    }
+}
+
+/// Stored and computed checksum of `packet`, if it has a checksum item.
+fn checksums(packet: &[u8], layout: &Layout) -> Option<(u16, u16)> {
+    let value = layout.checksum_value?;
+    let stored = u16::from_be_bytes([packet[value], packet[value + 1]]);
+    Some((stored, st0601::checksum(&packet[..value])))
+}
+
+/// Lists every problem with `bytes` without changing it.
+///
+/// # Returns
+///
+/// - `Ok(Vec<Problem>)` - Every problem found. Empty when the packet is
+///   valid.
+/// - `Err(RepairError::NoKey)` - The packet is too short to hold a key.
+pub fn diagnose(bytes: &[u8]) -> Result<Vec<Problem>, RepairError> {
+    let layout = Layout::read(bytes)?;
+    let mut problems = Vec::new();
+
+    let actual = (bytes.len() - layout.items_start.min(bytes.len())) as u64;
+    match layout.declared {
+        Some(declared) if declared != actual => {
+            problems.push(Problem::OuterLength { declared, actual })
+        }
+        Some(_) => {}
+        None => problems.push(Problem::UnreadableLength),
+    }
+    if let Some(offset) = layout.truncated {
+        problems.push(Problem::TruncatedItem {
+            offset: offset as u64,
+        });
+    }
+    if let Some((stored, computed)) = checksums(bytes, &layout)
+        && stored != computed
+    {
+        problems.push(Problem::StaleChecksum { stored, computed });
+    }
+    Ok(problems)
+}
+
+/// Rewrites the length after the Universal Key to match the items that
+/// follow it, and recomputes the ST 0601 checksum when there is a checksum
+/// item.
+///
+/// # Returns
+///
+/// - `Ok(Repaired)` - The repaired packet and what was changed.
+/// - `Err(RepairError::NoKey)` - The packet is too short to hold a key.
+/// - `Err(RepairError::Unrepairable)` - The length or an item could not be
+///   read, so where the items lie can't be worked out. Holds every problem
+///   found.
+pub fn fix_packet(bytes: &[u8]) -> Result<Repaired, RepairError> {
+    let problems = diagnose(bytes)?;
+    if problems.iter().any(|problem| !problem.is_repairable()) {
+        return Err(RepairError::Unrepairable(problems));
+    }
+
+    let layout = Layout::read(bytes)?;
+    let items = &bytes[layout.items_start.min(bytes.len())..];
+    let mut packet = bytes[..UNIVERSAL_KEY_LENGTH].to_vec();
+    packet.extend(encode_ber(items.len() as u128));
+    packet.extend_from_slice(items);
+
+    let mut fixed = problems
+        .into_iter()
+        .filter(|problem| !matches!(problem, Problem::StaleChecksum { .. }))
+        .collect::<Vec<_>>();
+    let layout = Layout::read(&packet)?;
+    if let Some((stored, computed)) = checksums(&packet, &layout)
+        && stored != computed
+    {
+        let value = layout.checksum_value.expect("Packet has a checksum");
+        packet[value..value + 2].copy_from_slice(&computed.to_be_bytes());
+        fixed.push(Problem::StaleChecksum { stored, computed });
+    }
+
+    Ok(Repaired { packet, fixed })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{keys, klv::OwnedKlv, local_set::OwnedLocalSet};
+
+    fn packet() -> Vec<u8> {
+        OwnedLocalSet::new(vec![
+            OwnedKlv::new(2, 1_000_000u64.to_be_bytes().to_vec()),
+            OwnedKlv::new(3, b"MISSION01".to_vec()),
+        ])
+        .serialize(&keys::ST0601, true)
+    }
+
+    #[test]
+    fn valid_packet_is_unchanged() {
+        assert_eq!(diagnose(&packet()).unwrap(), []);
+        let repaired = fix_packet(&packet()).unwrap();
+        assert_eq!(repaired.packet(), &packet());
+        assert!(repaired.fixed().is_empty());
+    }
+
+    #[test]
+    fn length_and_checksum_are_repaired() {
+        let mut damaged = packet();
+        let declared = damaged[UNIVERSAL_KEY_LENGTH];
+        damaged[UNIVERSAL_KEY_LENGTH] = declared + 2;
+        let checksum = damaged.len() - 2;
+        damaged[checksum] ^= 0xFF;
+        let stored = u16::from_be_bytes([damaged[checksum], damaged[checksum + 1]]);
+
+        let problems = diagnose(&damaged).unwrap();
+        assert_eq!(
+            problems[0],
+            Problem::OuterLength {
+                declared: u64::from(declared) + 2,
+                actual: declared.into()
+            }
+        );
+        assert!(matches!(problems[1], Problem::StaleChecksum { .. }));
+
+        let repaired = fix_packet(&damaged).unwrap();
+        assert_eq!(repaired.packet(), &packet());
+        assert_eq!(diagnose(repaired.packet()).unwrap(), []);
+        assert_eq!(
+            repaired.fixed()[1],
+            Problem::StaleChecksum {
+                stored,
+                computed: st0601::checksum(&packet()[..checksum])
+            }
+        );
+    }
+
+    #[test]
+    fn truncated_item_is_not_repaired() {
+        let mut damaged = packet();
+        damaged.truncate(damaged.len() - 8);
+
+        let offset = (UNIVERSAL_KEY_LENGTH + 1 + 10) as u64;
+        assert!(
+            diagnose(&damaged)
+                .unwrap()
+                .contains(&Problem::TruncatedItem { offset })
+        );
+        assert!(matches!(
+            fix_packet(&damaged),
+            Err(RepairError::Unrepairable(problems))
+                if problems.contains(&Problem::TruncatedItem { offset })
+        ));
+    }
+}
@@ -0,0 +1,225 @@
+//! Summaries of every set found in a buffer, for checking recordings for
+//! damaged or dropped packets.
+
+use std::fmt;
+
+use crate::{universal_set::UniversalKey, validation::Violation};
+
+/// Largest number of warnings kept in a `ScanReport`. Warnings past this are
+/// only counted.
+pub const MAX_SCAN_WARNINGS: usize = 256;
+
+/// How reading a set went.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum SetStatus {
+    /// The set was read, possibly with warnings.
+    Parsed,
+    /// The set was read but its checksum does not match its contents, or it
+    /// was rejected because of that.
+    ChecksumMismatch,
+    /// The set could not be read.
+    Failed,
+}
+
+/// Summary of a single set found in the buffer.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, getset::CopyGetters)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[getset(get_copy = "pub")]
+pub struct SetSummary {
+    /// Offset of the first byte of the set's Universal Key.
+    offset: u64,
+    /// Number of bytes in the set including its key and length. `None` when
+    /// the length could not be read.
+    length: Option<u64>,
+    /// Number of items read from the set.
+    items: usize,
+    /// Number of warnings recorded while reading the set.
+    warnings: usize,
+    status: SetStatus,
+}
+
+impl SetSummary {
+    pub fn new(
+        offset: u64,
+        length: Option<u64>,
+        items: usize,
+        warnings: usize,
+        status: SetStatus,
+    ) -> Self {
+        Self {
+            offset,
+            length,
+            items,
+            warnings,
+            status,
+        }
+    }
+
+    /// Offset of the byte after the last byte of the set, if its length could
+    /// be read.
+    pub fn end(&self) -> Option<u64> {
+        self.length.map(|length| self.offset + length)
+    }
+}
+
+/// Something that went wrong while reading a set.
+#[derive(Clone, Debug, PartialEq, Eq, getset::CopyGetters, getset::Getters)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ScanWarning {
+    /// Offset of the first byte of the Universal Key of the set.
+    #[getset(get_copy = "pub")]
+    set_offset: u64,
+    #[getset(get = "pub")]
+    message: String,
+}
+
+impl ScanWarning {
+    pub fn new(set_offset: u64, message: impl Into<String>) -> Self {
+        Self {
+            set_offset,
+            message: message.into(),
+        }
+    }
+
+    /// Describes a warning recorded on a set that was read.
+    pub fn from_violation(set_offset: u64, violation: &Violation) -> Self {
+        Self::new(set_offset, violation.to_string())
+    }
+}
+
+/// Bytes between two sets that belong to neither.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, getset::CopyGetters)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[getset(get_copy = "pub")]
+pub struct Gap {
+    /// Offset of the first byte after the earlier set.
+    offset: u64,
+    /// Number of bytes before the Universal Key of the later set.
+    length: u64,
+}
+
+/// Statistics gathered over every set with the same Universal Key in a
+/// buffer.
+#[derive(Clone, Debug, PartialEq, Eq, getset::CopyGetters, getset::Getters)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ScanReport {
+    #[getset(get_copy = "pub")]
+    key: UniversalKey,
+    /// Every set found, in the order they are in the buffer.
+    #[getset(get = "pub")]
+    sets: Vec<SetSummary>,
+    /// Bytes between consecutive sets.
+    #[getset(get = "pub")]
+    gaps: Vec<Gap>,
+    /// The first `MAX_SCAN_WARNINGS` warnings recorded.
+    #[getset(get = "pub")]
+    warnings: Vec<ScanWarning>,
+    /// Number of warnings that did not fit in `warnings`.
+    #[getset(get_copy = "pub")]
+    dropped_warnings: usize,
+}
+
+impl ScanReport {
+    pub fn new(key: UniversalKey) -> Self {
+        Self {
+            key,
+            sets: Vec::new(),
+            gaps: Vec::new(),
+            warnings: Vec::new(),
+            dropped_warnings: 0,
+        }
+    }
+
+    /// Adds the next set in the buffer, recording the gap between it and the
+    /// set before it.
+    ///
+    /// Sets must be added in the order they are in the buffer.
+    pub fn push_set(&mut self, set: SetSummary) {
+        if let Some(end) = self.sets.last().and_then(SetSummary::end)
+            && set.offset > end
+        {
+            self.gaps.push(Gap {
+                offset: end,
+                length: set.offset - end,
+            });
+        }
+        self.sets.push(set);
+    }
+
+    /// Records a warning, or only counts it once `MAX_SCAN_WARNINGS` have
+    /// been recorded.
+    pub fn push_warning(&mut self, warning: ScanWarning) {
+        if self.warnings.len() < MAX_SCAN_WARNINGS {
+            self.warnings.push(warning);
+        } else {
+            self.dropped_warnings += 1;
+        }
+    }
+
+    fn count(&self, status: SetStatus) -> usize {
+        self.sets.iter().filter(|set| set.status == status).count()
+    }
+
+    /// Number of sets that were read.
+    pub fn parsed(&self) -> usize {
+        self.count(SetStatus::Parsed)
+    }
+
+    /// Number of sets whose checksum did not match their contents.
+    pub fn checksum_failures(&self) -> usize {
+        self.count(SetStatus::ChecksumMismatch)
+    }
+
+    /// Number of sets that could not be read.
+    pub fn failed(&self) -> usize {
+        self.count(SetStatus::Failed)
+    }
+
+    /// Number of bytes taken up by every set whose length could be read.
+    pub fn total_bytes(&self) -> u64 {
+        self.sets.iter().filter_map(|set| set.length).sum()
+    }
+
+    /// Offsets from the first byte of the first set to the byte after the
+    /// last byte of the last set whose length could be read.
+    pub fn byte_range(&self) -> Option<std::ops::Range<u64>> {
+        let start = self.sets.first()?.offset;
+        let end = self.sets.iter().filter_map(SetSummary::end).max()?;
+        Some(start..end)
+    }
+}
+
+impl fmt::Display for ScanReport {
+    /// Formats the totals on one line each, followed by every gap and
+    /// warning.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Key: {}", self.key)?;
+        writeln!(
+            f,
+            "Sets: {} ({} parsed, {} checksum failures, {} failed)",
+            self.sets.len(),
+            self.parsed(),
+            self.checksum_failures(),
+            self.failed()
+        )?;
+        writeln!(f, "Bytes: {}", self.total_bytes())?;
+        if let Some(range) = self.byte_range() {
+            writeln!(f, "Range: {}..{}", range.start, range.end)?;
+        }
+        for gap in &self.gaps {
+            writeln!(f, "Gap of {} bytes at offset {}", gap.length, gap.offset)?;
+        }
+        for warning in &self.warnings {
+            writeln!(
+                f,
+                "Set at offset {}: {}",
+                warning.set_offset, warning.message
+            )?;
+        }
+        if self.dropped_warnings > 0 {
+            writeln!(f, "{} more warnings", self.dropped_warnings)?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,426 @@
+use std::collections::VecDeque;
+
+use ringbuffer::{ConstGenericRingBuffer, RingBuffer};
+
+use crate::{
+    klv::OwnedKlv,
+    universal_set::{UNIVERSAL_KEY_LENGTH, UniversalKey},
+};
+
+/// Most bytes reserved up front for the value of an item. Longer values grow
+/// as their bytes arrive, so a corrupt length can't reserve memory that is
+/// never filled.
+const MAX_VALUE_RESERVATION: u64 = 64 * 1024;
+
+/// Something that happened while parsing the bytes pushed into a
+/// `StreamParser`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Event {
+    /// The Universal Key was found and the following bytes will be parsed as a
+    /// set.
+    KeyFound(UniversalKey),
+
+    /// A complete KLV triplet was parsed out of the current set.
+    Item(OwnedKlv),
+
+    /// Every byte of the current set has been parsed.
+    SetComplete,
+
+    /// The current set could not be parsed. The parser skips whatever is left
+    /// of the set and goes back to searching for the Universal Key.
+    Error(StreamError),
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, thiserror::Error)]
+pub enum StreamError {
+    #[error("BER length has the long-form bit set but no length bytes")]
+    IndefiniteLength,
+    #[error("Length does not fit in a u64")]
+    LengthTooLarge,
+    #[error("Tag does not fit in a u128")]
+    TagTooLarge,
+    #[error("Set ended part way through the tag or length of the item with tag {tag}")]
+    IncompleteItem { tag: u128 },
+    #[error(
+        "Item with tag {tag} and length {length} overruns the {remaining} bytes left in its set"
+    )]
+    ItemOverrunsSet {
+        tag: u128,
+        length: u64,
+        remaining: u64,
+    },
+}
+
+/// What the parser expects the next pushed byte to be part of.
+#[derive(Debug)]
+enum State {
+    /// Looking for the Universal Key.
+    Searching,
+
+    /// Reading the BER length of the set.
+    SetLength(BerLength),
+
+    /// Reading the BER-OID tag of an item.
+    ItemTag { remaining: u64, tag: u128 },
+
+    /// Reading the BER length of an item.
+    ItemLength {
+        remaining: u64,
+        tag: u128,
+        length: BerLength,
+    },
+
+    /// Reading the value of an item.
+    ItemValue {
+        remaining: u64,
+        tag: u128,
+        length: u64,
+        value: Vec<u8>,
+    },
+
+    /// Discarding the rest of a set that could not be parsed.
+    Skipping { remaining: u64 },
+}
+
+/// Partially read BER length.
+#[derive(Debug, Default)]
+struct BerLength {
+    /// Number of long-form bytes still to be read, `None` until the first byte
+    /// has been read.
+    bytes_left: Option<u8>,
+    value: u64,
+}
+
+impl BerLength {
+    /// Adds the next byte of the BER encoding.
+    ///
+    /// # Returns
+    ///
+    /// - `Ok(Some(u64))` - The byte completed the length.
+    /// - `Ok(None)` - More bytes are needed.
+    /// - `Err(StreamError)` - The length is malformed or too large.
+    fn push(&mut self, byte: u8) -> Result<Option<u64>, StreamError> {
+        match self.bytes_left {
+            None if byte & 0x80 == 0 => return Ok(Some(byte as u64)),
+            None if byte == 0x80 => return Err(StreamError::IndefiniteLength),
+            None => self.bytes_left = Some(byte & 0x7F),
+            Some(ref mut left) => {
+                if self.value >> 56 != 0 {
+                    return Err(StreamError::LengthTooLarge);
+                }
+                self.value = (self.value << 8) | byte as u64;
+                *left -= 1;
+            }
+        }
+
+        Ok((self.bytes_left == Some(0)).then_some(self.value))
+    }
+}
+
+/// Push-based parser for Universal Sets arriving in arbitrarily sized chunks.
+///
+/// Bytes are parsed as soon as they are pushed, so the parser never holds on
+/// to more than the 16 bytes needed to match the key and the value of the item
+/// currently being read, which is bounded by the declared length of its set.
+/// Parsed items are queued as `Event`s until they are taken with
+/// `next_event`.
+#[derive(Debug)]
+pub struct StreamParser {
+    key: UniversalKey,
+    search_buffer: ConstGenericRingBuffer<u8, UNIVERSAL_KEY_LENGTH>,
+    state: State,
+    events: VecDeque<Event>,
+}
+
+impl StreamParser {
+    pub fn new(key: UniversalKey) -> Self {
+        Self {
+            key,
+            search_buffer: ConstGenericRingBuffer::new(),
+            state: State::Searching,
+            events: VecDeque::new(),
+        }
+    }
+
+    /// Parses `bytes` as the continuation of everything pushed so far.
+    pub fn push(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.push_byte(*byte);
+        }
+    }
+
+    /// Takes the oldest event that has not been taken yet.
+    pub fn next_event(&mut self) -> Option<Event> {
+        self.events.pop_front()
+    }
+
+    fn push_byte(&mut self, byte: u8) {
+        self.state = match std::mem::replace(&mut self.state, State::Searching) {
+            State::Searching => {
+                self.search_buffer.enqueue(byte);
+                if self.search_buffer.is_full()
+                    && itertools::equal(&self.search_buffer, &self.key[..])
+                {
+                    self.search_buffer.clear();
+                    self.events.push_back(Event::KeyFound(self.key));
+                    State::SetLength(BerLength::default())
+                } else {
+                    State::Searching
+                }
+            }
+            State::SetLength(mut length) => match length.push(byte) {
+                Ok(Some(remaining)) => self.start_item(remaining),
+                Ok(None) => State::SetLength(length),
+                Err(e) => {
+                    self.events.push_back(Event::Error(e));
+                    State::Searching
+                }
+            },
+            State::ItemTag { remaining, tag } => {
+                let remaining = remaining - 1;
+                if tag >> 121 != 0 {
+                    return self.fail(StreamError::TagTooLarge, remaining);
+                }
+                let tag = (tag << 7) | (byte & 0x7F) as u128;
+                match (remaining, byte & 0x80 != 0) {
+                    (0, _) => return self.fail(StreamError::IncompleteItem { tag }, 0),
+                    (_, true) => State::ItemTag { remaining, tag },
+                    (_, false) => State::ItemLength {
+                        remaining,
+                        tag,
+                        length: BerLength::default(),
+                    },
+                }
+            }
+            State::ItemLength {
+                remaining,
+                tag,
+                mut length,
+            } => {
+                let remaining = remaining - 1;
+                match length.push(byte) {
+                    Ok(Some(length)) if length > remaining => self.overrun(tag, length, remaining),
+                    Ok(Some(0)) => {
+                        self.events
+                            .push_back(Event::Item(OwnedKlv::new(tag, Vec::new())));
+                        self.start_item(remaining)
+                    }
+                    Ok(Some(length)) => State::ItemValue {
+                        remaining,
+                        tag,
+                        length,
+                        value: Vec::with_capacity(length.min(MAX_VALUE_RESERVATION) as usize),
+                    },
+                    Ok(None) if remaining == 0 => {
+                        return self.fail(StreamError::IncompleteItem { tag }, 0);
+                    }
+                    Ok(None) => State::ItemLength {
+                        remaining,
+                        tag,
+                        length,
+                    },
+                    Err(e) => return self.fail(e, remaining),
+                }
+            }
+            State::ItemValue {
+                remaining,
+                tag,
+                length,
+                mut value,
+            } => {
+                value.push(byte);
+                let remaining = remaining - 1;
+                if value.len() as u64 == length {
+                    self.events
+                        .push_back(Event::Item(OwnedKlv::new(tag, value)));
+                    self.start_item(remaining)
+                } else {
+                    State::ItemValue {
+                        remaining,
+                        tag,
+                        length,
+                        value,
+                    }
+                }
+            }
+            State::Skipping { remaining } => match remaining - 1 {
+                0 => State::Searching,
+                remaining => State::Skipping { remaining },
+            },
+        };
+    }
+
+    /// State for reading the next item of a set with `remaining` bytes left.
+    fn start_item(&mut self, remaining: u64) -> State {
+        if remaining == 0 {
+            self.events.push_back(Event::SetComplete);
+            State::Searching
+        } else {
+            State::ItemTag { remaining, tag: 0 }
+        }
+    }
+
+    /// Records an item that is longer than what is left of its set.
+    fn overrun(&mut self, tag: u128, length: u64, remaining: u64) -> State {
+        self.events
+            .push_back(Event::Error(StreamError::ItemOverrunsSet {
+                tag,
+                length,
+                remaining,
+            }));
+        Self::skip(remaining)
+    }
+
+    /// Records an error and skips the `remaining` bytes of the set.
+    fn fail(&mut self, error: StreamError, remaining: u64) {
+        self.events.push_back(Event::Error(error));
+        self.state = Self::skip(remaining);
+    }
+
+    fn skip(remaining: u64) -> State {
+        match remaining {
+            0 => State::Searching,
+            remaining => State::Skipping { remaining },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use itertools::{Itertools, chain};
+
+    const TEST_UNIVERSAL_KEY: [u8; UNIVERSAL_KEY_LENGTH] = [
+        0x06, 0x0E, 0x2B, 0x34, 0x02, 0x0B, 0x01, 0x01, 0x0E, 0x01, 0x03, 0x01, 0x01, 0x00, 0x00,
+        0x00,
+    ];
+
+    fn multiple_uset_buf() -> Vec<u8> {
+        chain!(
+            [0x06],
+            TEST_UNIVERSAL_KEY,
+            [0x04],
+            [0x01, 0x02, 0x03, 0x04],
+            [0xFF, 0xFF, 0x06],
+            TEST_UNIVERSAL_KEY,
+            [0x07],
+            [0x01, 0x01, 0x02],
+            [0x02, 0x02, 0x04, 0x08]
+        )
+        .collect_vec()
+    }
+
+    fn expected_events() -> Vec<Event> {
+        let key = UniversalKey::new(TEST_UNIVERSAL_KEY);
+        vec![
+            Event::KeyFound(key),
+            Event::Item(OwnedKlv::new(1, vec![0x03, 0x04])),
+            Event::SetComplete,
+            Event::KeyFound(key),
+            Event::Item(OwnedKlv::new(1, vec![0x02])),
+            Event::Item(OwnedKlv::new(2, vec![0x04, 0x08])),
+            Event::SetComplete,
+        ]
+    }
+
+    fn parse_in_chunks(buf: &[u8], chunk_sizes: impl Iterator<Item = usize>) -> Vec<Event> {
+        let mut parser = StreamParser::new(UniversalKey::new(TEST_UNIVERSAL_KEY));
+        let mut events = Vec::new();
+        let mut rest = buf;
+        for size in chunk_sizes {
+            if rest.is_empty() {
+                break;
+            }
+            let (chunk, remaining) = rest.split_at(size.min(rest.len()));
+            rest = remaining;
+            parser.push(chunk);
+            events.extend(std::iter::from_fn(|| parser.next_event()));
+        }
+        events
+    }
+
+    #[test]
+    fn whole_buffer() {
+        let buf = multiple_uset_buf();
+        assert_eq!(
+            parse_in_chunks(&buf, std::iter::once(buf.len())),
+            expected_events()
+        );
+    }
+
+    #[test]
+    fn one_byte_at_a_time() {
+        assert_eq!(
+            parse_in_chunks(&multiple_uset_buf(), std::iter::repeat(1)),
+            expected_events()
+        );
+    }
+
+    #[test]
+    fn random_sized_chunks() {
+        for seed in 0..64u32 {
+            // Small LCG so the chunk sizes are arbitrary but reproducible.
+            let mut state = seed;
+            let sizes = std::iter::repeat_with(move || {
+                state = state.wrapping_mul(1_103_515_245).wrapping_add(12_345);
+                (state >> 16) as usize % 7 + 1
+            });
+            assert_eq!(
+                parse_in_chunks(&multiple_uset_buf(), sizes),
+                expected_events(),
+                "Seed {seed}"
+            );
+        }
+    }
+
+    #[test]
+    fn item_overrunning_set_is_skipped() {
+        let buf = chain!(
+            TEST_UNIVERSAL_KEY,
+            [0x03, 0x01, 0x05, 0xAA],
+            TEST_UNIVERSAL_KEY,
+            [0x03, 0x02, 0x01, 0xBB]
+        )
+        .collect_vec();
+        let key = UniversalKey::new(TEST_UNIVERSAL_KEY);
+        assert_eq!(
+            parse_in_chunks(&buf, std::iter::repeat(1)),
+            vec![
+                Event::KeyFound(key),
+                Event::Error(StreamError::ItemOverrunsSet {
+                    tag: 1,
+                    length: 5,
+                    remaining: 1
+                }),
+                Event::KeyFound(key),
+                Event::Item(OwnedKlv::new(2, vec![0xBB])),
+                Event::SetComplete,
+            ]
+        );
+    }
+
+    #[test]
+    fn huge_declared_length_is_not_reserved() {
+        let buf = chain!(
+            TEST_UNIVERSAL_KEY,
+            [0x88, 0x7F, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF],
+            [0x01, 0x88, 0x7F, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0x00],
+            [0xAA, 0xBB, 0xCC]
+        )
+        .collect_vec();
+        let mut parser = StreamParser::new(UniversalKey::new(TEST_UNIVERSAL_KEY));
+        parser.push(&buf);
+
+        assert_eq!(
+            std::iter::from_fn(|| parser.next_event()).collect_vec(),
+            [Event::KeyFound(UniversalKey::new(TEST_UNIVERSAL_KEY))]
+        );
+        match &parser.state {
+            State::ItemValue { value, .. } => {
+                assert_eq!(value, &[0xAA, 0xBB, 0xCC]);
+                assert!(value.capacity() as u64 <= MAX_VALUE_RESERVATION);
+            }
+            state => panic!("Unexpected state {state:?}"),
+        }
+    }
+}
@@ -0,0 +1,264 @@
+//! Writes sets one after another as a KLV stream.
+
+use std::io::{self, Write};
+
+use crate::{
+    encoding::ber::{decode_ber, encode_ber_padded},
+    keys,
+    local_set::OwnedLocalSet,
+    universal_set::{UNIVERSAL_KEY_LENGTH, UniversalKey},
+};
+
+/// Most bytes a BER length is written in. Longer lengths can't be read back
+/// by `UniversalSet::read_extent`.
+const MAX_BER_LENGTH: usize = 17;
+
+#[derive(Debug, thiserror::Error)]
+pub enum WriteError {
+    #[error("Packet of {length} bytes does not fit in the fixed packet size of {fixed} bytes")]
+    PacketTooLarge { length: usize, fixed: usize },
+    #[error("Packet of {length} bytes can't be padded to the fixed packet size of {fixed} bytes")]
+    CannotPad { length: usize, fixed: usize },
+    #[error(transparent)]
+    Io(#[from] io::Error),
+}
+
+/// Writes sets to `W` as complete packets, in the order they are given.
+///
+/// ST 0601 packets get a checksum. When a fixed packet size is set, every
+/// packet is followed by a KLV fill item that pads it out to that size, for
+/// carriage at a constant bitrate.
+///
+/// With the `tokio` feature enabled, async writers are supported through
+/// `StreamWriter::write_set_async`.
+#[derive(Debug)]
+pub struct StreamWriter<W> {
+    writer: W,
+    fixed_packet_size: Option<usize>,
+}
+
+impl<W> StreamWriter<W> {
+    pub fn new(writer: W) -> Self {
+        Self {
+            writer,
+            fixed_packet_size: None,
+        }
+    }
+
+    /// Pads every packet written from now on to `size` bytes, or stops
+    /// padding them when `None`.
+    pub fn set_fixed_packet_size(&mut self, size: Option<usize>) {
+        self.fixed_packet_size = size;
+    }
+
+    /// Writer the packets are written to.
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+
+    /// Packet for `items` keyed by `key`, followed by its padding.
+    fn packet(&self, key: &UniversalKey, items: &OwnedLocalSet) -> Result<Vec<u8>, WriteError> {
+        let checksum = key.eq_ignoring_version(&keys::ST0601);
+        let packet = items.serialize(key, checksum);
+        match self.fixed_packet_size {
+            Some(fixed) => pad(packet, key, items, checksum, fixed),
+            None => Ok(packet),
+        }
+    }
+}
+
+impl<W> StreamWriter<W>
+where
+    W: Write,
+{
+    /// Writes `items` as a packet keyed by `key`, followed by its padding.
+    ///
+    /// # Returns
+    ///
+    /// - `Ok(usize)` - Number of bytes written, including the padding.
+    /// - `Err(WriteError::PacketTooLarge)` - The packet is longer than the
+    ///   fixed packet size. Nothing is written.
+    /// - `Err(WriteError::CannotPad)` - The packet is too few bytes short of
+    ///   the fixed packet size to be padded. Nothing is written.
+    /// - `Err(WriteError::Io)` - The packet could not be written.
+    pub fn write_set(
+        &mut self,
+        key: &UniversalKey,
+        items: &OwnedLocalSet,
+    ) -> Result<usize, WriteError> {
+        let packet = self.packet(key, items)?;
+        self.writer.write_all(&packet)?;
+        Ok(packet.len())
+    }
+}
+
+#[cfg(feature = "tokio")]
+impl<W> StreamWriter<W>
+where
+    W: tokio::io::AsyncWrite + Unpin,
+{
+    /// Writes `items` as a packet keyed by `key`, followed by its padding, to
+    /// an async writer.
+    ///
+    /// The whole packet is written with a single `write_all`. See
+    /// `StreamWriter::write_set`.
+    pub async fn write_set_async(
+        &mut self,
+        key: &UniversalKey,
+        items: &OwnedLocalSet,
+    ) -> Result<usize, WriteError> {
+        use tokio::io::AsyncWriteExt;
+
+        let packet = self.packet(key, items)?;
+        self.writer.write_all(&packet).await?;
+        Ok(packet.len())
+    }
+}
+
+/// Pads `packet` out to `fixed` bytes.
+///
+/// A KLV fill item is added after the packet when there is room for one.
+/// Gaps too small for a fill item are closed by writing the length of the
+/// set in more bytes than it needs instead.
+fn pad(
+    packet: Vec<u8>,
+    key: &UniversalKey,
+    items: &OwnedLocalSet,
+    checksum: bool,
+    fixed: usize,
+) -> Result<Vec<u8>, WriteError> {
+    let length = packet.len();
+    let gap = fixed
+        .checked_sub(length)
+        .ok_or(WriteError::PacketTooLarge { length, fixed })?;
+    if gap == 0 {
+        return Ok(packet);
+    }
+
+    if gap > UNIVERSAL_KEY_LENGTH {
+        // Short lengths take one byte. Any longer fill fits in a 9 byte long
+        // form.
+        let room = gap - UNIVERSAL_KEY_LENGTH;
+        let width = if room - 1 < 0x80 { 1 } else { 9 };
+        let fill_length = room - width;
+        let mut packet = packet;
+        packet.extend_from_slice(&*keys::FILL);
+        packet.extend(encode_ber_padded(fill_length as u128, width).expect("Fill length fits"));
+        packet.resize(fixed, 0);
+        return Ok(packet);
+    }
+
+    let (_, current_width) =
+        decode_ber(&packet[UNIVERSAL_KEY_LENGTH..]).expect("Packet was just encoded");
+    let width = current_width + gap;
+    items
+        .serialize_with_length_width(key, checksum, width)
+        .filter(|_| width <= MAX_BER_LENGTH)
+        .ok_or(WriteError::CannotPad { length, fixed })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::RefCell, io::Cursor, rc::Rc};
+
+    use super::*;
+    use crate::{
+        klv::OwnedKlv,
+        options::{ChecksumMode, ParseOptions},
+        universal_set::UniversalSet,
+    };
+    use test_case::test_case;
+
+    fn set(timestamp: u64) -> OwnedLocalSet {
+        OwnedLocalSet::new(vec![
+            OwnedKlv::new(2, timestamp.to_be_bytes().to_vec()),
+            OwnedKlv::new(3, b"MISSION".to_vec()),
+        ])
+    }
+
+    /// Length of each packet without padding.
+    fn natural_length() -> usize {
+        set(0).serialize(&keys::ST0601, true).len()
+    }
+
+    #[test_case(None; "Unpadded")]
+    #[test_case(Some(0); "Exact")]
+    #[test_case(Some(1); "Widened length")]
+    #[test_case(Some(16); "Largest widened length")]
+    #[test_case(Some(17); "Empty fill")]
+    #[test_case(Some(200); "Short fill length")]
+    #[test_case(Some(5000); "Long fill length")]
+    fn round_trip(padding: Option<usize>) {
+        let mut writer = StreamWriter::new(Vec::new());
+        let fixed = padding.map(|padding| natural_length() + padding);
+        writer.set_fixed_packet_size(fixed);
+        for timestamp in 1..=3 {
+            let written = writer.write_set(&keys::ST0601, &set(timestamp)).unwrap();
+            assert_eq!(written, fixed.unwrap_or(natural_length()));
+        }
+        let stream = writer.into_inner();
+        assert_eq!(stream.len(), 3 * fixed.unwrap_or(natural_length()));
+
+        let options = ParseOptions {
+            verify_checksum: ChecksumMode::Reject,
+            ..ParseOptions::default()
+        };
+        let buf = Rc::new(RefCell::new(Cursor::new(stream)));
+        let sets = UniversalSet::read_all_with_options(keys::ST0601, buf, &options).unwrap();
+        assert_eq!(sets.len(), 3);
+        for (set, timestamp) in sets.iter().zip(1..=3u64) {
+            assert_eq!(
+                set.data().to_mutable().unwrap().items()[..2],
+                self::set(timestamp).items()[..]
+            );
+        }
+    }
+
+    #[cfg(feature = "tokio")]
+    #[tokio::test]
+    async fn async_round_trip() {
+        use tokio::io::AsyncReadExt;
+
+        let (writer, mut reader) = tokio::io::duplex(64);
+        let mut writer = StreamWriter::new(writer);
+        writer.set_fixed_packet_size(Some(natural_length() + 200));
+
+        let write = async {
+            for timestamp in 1..=3 {
+                writer
+                    .write_set_async(&keys::ST0601, &set(timestamp))
+                    .await
+                    .unwrap();
+            }
+            drop(writer);
+        };
+        let read = async {
+            let mut stream = Vec::new();
+            reader.read_to_end(&mut stream).await.unwrap();
+            stream
+        };
+        let ((), stream) = tokio::join!(write, read);
+
+        assert_eq!(stream.len(), 3 * (natural_length() + 200));
+        let buf = Rc::new(RefCell::new(Cursor::new(stream)));
+        let sets = UniversalSet::read_all(keys::ST0601, buf).unwrap();
+        assert_eq!(sets.len(), 3);
+        for (set, timestamp) in sets.iter().zip(1..=3u64) {
+            assert_eq!(
+                set.data().to_mutable().unwrap().items()[..2],
+                self::set(timestamp).items()[..]
+            );
+        }
+    }
+
+    #[test]
+    fn packet_too_large() {
+        let mut writer = StreamWriter::new(Vec::new());
+        writer.set_fixed_packet_size(Some(natural_length() - 1));
+        assert!(matches!(
+            writer.write_set(&keys::ST0601, &set(1)),
+            Err(WriteError::PacketTooLarge { .. })
+        ));
+        assert!(writer.into_inner().is_empty());
+    }
+}
@@ -0,0 +1,398 @@
+//! Descriptions of the items a standard defines for a local set.
+
+use std::ops::RangeInclusive;
+
+use crate::{
+    encoding,
+    format::{SoftwareFormat, SoftwareValue, ValueFormat},
+};
+
+/// Constraint a standard places on the length of an item's value, taken from
+/// the _Len_, _Max Len_, and _Required Len_ columns of MISB tag tables.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ValueLength {
+    /// The value is always exactly this many bytes.
+    Required(u64),
+    /// The value is never longer than this many bytes.
+    Max(u64),
+    /// Typical length of the value. Any length is allowed.
+    Length(u64),
+}
+
+impl ValueLength {
+    /// Whether a value that is `length` bytes long satisfies the constraint.
+    pub fn allows(&self, length: u64) -> bool {
+        match *self {
+            Self::Required(required) => length == required,
+            Self::Max(max) => length <= max,
+            Self::Length(_) => true,
+        }
+    }
+}
+
+/// Encoding of a value that stands for something other than a number, such
+/// as "Out of Range", instead of being converted like any other value.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SpecialValue {
+    /// Bytes of the value as they appear in the set.
+    pub raw: &'static [u8],
+    /// What the value means, as given in the standard.
+    pub meaning: &'static str,
+}
+
+/// Value of an item read through its tag.
+#[derive(Clone, Debug, PartialEq)]
+pub enum TagReading<S> {
+    /// The value converted to its software type.
+    Value(S),
+    /// The value is one of the special values of its tag and was not
+    /// converted.
+    Special(SpecialValue),
+    /// The value is zero bytes long, which ST 0601 uses to revert an item to
+    /// its default or mark it as unknown.
+    Empty,
+}
+
+impl<S> TagReading<S> {
+    /// The converted value, if the value was not a special value or empty.
+    pub fn value(self) -> Option<S> {
+        match self {
+            Self::Value(value) => Some(value),
+            Self::Special(_) | Self::Empty => None,
+        }
+    }
+}
+
+/// A tag defined by the standard for a local set.
+///
+/// Tag numbers the standard does not define fail to convert with
+/// `UnknownTag` rather than being forced into the table, since newer streams
+/// routinely carry tags older tables do not know about.
+pub trait Tag:
+    Sized + Copy + Ord + TryFrom<u128, Error = UnknownTag> + Into<u128> + 'static
+{
+    /// Every tag the standard defines.
+    fn all() -> &'static [Self];
+
+    /// Short name of the set the tags belong to, used when describing where
+    /// an item lies. Defaults to the name of the type without a `Tag` suffix,
+    /// such as `St0601` for `St0601Tag`.
+    fn set_name() -> &'static str {
+        let name = std::any::type_name::<Self>()
+            .rsplit("::")
+            .next()
+            .unwrap_or_default();
+        name.strip_suffix("Tag").unwrap_or(name)
+    }
+
+    /// Tag number the item is keyed by in the set.
+    fn number(&self) -> u128 {
+        (*self).into()
+    }
+
+    /// Name of the item as given in the standard.
+    fn name(&self) -> &'static str;
+
+    /// Whether every set must contain this item.
+    fn required(&self) -> bool {
+        false
+    }
+
+    /// Constraint on the length of the item's value, if the standard gives
+    /// one.
+    fn value_length(&self) -> Option<ValueLength> {
+        None
+    }
+
+    /// Values of the item that carry a special meaning instead of being
+    /// converted.
+    fn special_values(&self) -> &'static [SpecialValue] {
+        &[]
+    }
+
+    /// How the item's value is encoded and converted, if the table gives it.
+    fn format(&self) -> Option<ValueFormat> {
+        None
+    }
+
+    /// Unit of the item's converted value as written in the _Units_ column
+    /// of the standard, such as `"m"` or `"deg"`, if the table gives one.
+    fn unit(&self) -> Option<&'static str> {
+        None
+    }
+
+    /// Values an enumerated item may take, if the table gives them.
+    fn enumeration(&self) -> Option<RangeInclusive<u64>> {
+        None
+    }
+
+    /// Converts the raw bytes of the item's value to its software type.
+    ///
+    /// Items without a format are returned as bytes.
+    fn to_software(&self, raw: &[u8]) -> Result<SoftwareValue, encoding::Error> {
+        match self.format() {
+            Some(format) => format.convert(raw),
+            None => Ok(SoftwareValue::Bytes(raw.to_vec())),
+        }
+    }
+
+    /// Converts a software value back to the raw bytes of the item's value.
+    ///
+    /// Values are encoded in the tag's required length when it has one.
+    /// Items without a format only accept bytes.
+    fn to_klv(&self, value: &SoftwareValue) -> Result<Vec<u8>, encoding::Error> {
+        let length = match self.value_length() {
+            Some(ValueLength::Required(length)) => Some(length as usize),
+            _ => None,
+        };
+        match (self.format(), value) {
+            (Some(format), value) => format.convert_to_klv(value, length),
+            (None, SoftwareValue::Bytes(bytes)) => Ok(bytes.clone()),
+            (None, _) => Err(encoding::Error::MissingParameter {
+                what: self.name().into(),
+                parameter: "format",
+            }),
+        }
+    }
+
+    /// Encodes a reading of the item, writing special values as their raw
+    /// bytes.
+    fn encode(&self, reading: &TagReading<SoftwareValue>) -> Result<Vec<u8>, encoding::Error> {
+        match reading {
+            TagReading::Value(value) => self.to_klv(value),
+            TagReading::Special(special) => Ok(special.raw.to_vec()),
+            TagReading::Empty => Ok(Vec::new()),
+        }
+    }
+
+    /// Reads the raw bytes of the item's value with `Tag::to_software`.
+    ///
+    /// See `Tag::read`.
+    fn decode(&self, raw: &[u8]) -> Result<TagReading<SoftwareValue>, encoding::Error> {
+        match self.read(raw, |raw| self.to_software(raw)) {
+            TagReading::Value(value) => value.map(TagReading::Value),
+            TagReading::Special(special) => Ok(TagReading::Special(special)),
+            TagReading::Empty => Ok(TagReading::Empty),
+        }
+    }
+
+    /// Reads the raw bytes of the item's value.
+    ///
+    /// Empty values of tags that convert to strings or bytes, including tags
+    /// without a format, are the empty string or bytes rather than
+    /// `TagReading::Empty`.
+    ///
+    /// # Returns
+    ///
+    /// - `TagReading::Special` - `raw` is one of the tag's special values.
+    /// - `TagReading::Empty` - `raw` is empty.
+    /// - `TagReading::Value` - The result of passing `raw` to `to_software`.
+    fn read<S>(&self, raw: &[u8], to_software: impl FnOnce(&[u8]) -> S) -> TagReading<S> {
+        if let Some(special) = self
+            .special_values()
+            .iter()
+            .find(|special| special.raw == raw)
+        {
+            return TagReading::Special(*special);
+        }
+        let empty_is_value = self.format().is_none_or(|format| {
+            matches!(
+                format.software,
+                SoftwareFormat::String | SoftwareFormat::Bytes
+            )
+        });
+        if raw.is_empty() && !empty_is_value {
+            return TagReading::Empty;
+        }
+        TagReading::Value(to_software(raw))
+    }
+}
+
+/// A tag number the standard does not define.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, thiserror::Error)]
+#[error("Tag {0} is not defined by the standard")]
+pub struct UnknownTag(pub u128);
+
+/// Defines an enum of the tags in a standard and implements `Tag` for it.
+///
+/// Each tag is given as its variant, tag number, name, and optionally the
+/// `ValueLength` constraint on its value. Tags every set must contain are
+/// listed after the enum, followed optionally by the special values of each
+/// tag that has any, the format of each tag that has one, the unit of each
+/// tag that has one, and then the values each enumerated tag may take.
+///
+/// ```
+/// klv::tag_table! {
+///     /// Tags of an example local set.
+///     #[repr(u8)]
+///     pub enum ExampleTag {
+///         Timestamp = 1, "Timestamp", Required(8);
+///         Name = 2, "Name", Max(32);
+///         Payload = 3, "Payload";
+///         Mode = 4, "Mode", Required(1);
+///     }
+///
+///     required: [Timestamp]
+///
+///     special_values: {
+///         Name => [[0x00] => "Unknown"],
+///     }
+///
+///     formats: {
+///         Timestamp => Uint as Uint64,
+///         Name => Utf8 as String,
+///         Mode => Uint as Uint64,
+///     }
+///
+///     units: {
+///         Timestamp => "us",
+///     }
+///
+///     enumerations: {
+///         Mode => 0..=3,
+///     }
+/// }
+/// ```
+#[macro_export]
+macro_rules! tag_table {
+    (
+        $(#[$meta:meta])*
+        $vis:vis enum $enum:ident {
+            $($variant:ident = $number:literal, $name:literal $(, $length:expr)?;)*
+        }
+
+        required: [$($required:ident),* $(,)?]
+
+        $(
+            special_values: {
+                $($special:ident => [$($raw:expr => $meaning:literal),* $(,)?]),* $(,)?
+            }
+        )?
+
+        $(
+            formats: {
+                $(
+                    $formatted:ident => $klv:ident as $software:ident
+                    $(in ($min:expr, $max:expr))?
+                ),* $(,)?
+            }
+        )?
+
+        $(
+            units: {
+                $($measured:ident => $unit:literal),* $(,)?
+            }
+        )?
+
+        $(
+            enumerations: {
+                $($enumerated:ident => $values:expr),* $(,)?
+            }
+        )?
+    ) => {
+        $(#[$meta])*
+        #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+        $vis enum $enum {
+            $(
+                #[doc = $name]
+                $variant = $number,
+            )*
+        }
+
+        impl $enum {
+            const ALL: &[Self] = &[$(Self::$variant),*];
+        }
+
+        impl TryFrom<u128> for $enum {
+            type Error = $crate::tag::UnknownTag;
+
+            fn try_from(number: u128) -> Result<Self, Self::Error> {
+                match number {
+                    $($number => Ok(Self::$variant),)*
+                    _ => Err($crate::tag::UnknownTag(number)),
+                }
+            }
+        }
+
+        impl From<$enum> for u128 {
+            fn from(tag: $enum) -> Self {
+                tag as u128
+            }
+        }
+
+        impl $crate::tag::Tag for $enum {
+            fn all() -> &'static [Self] {
+                Self::ALL
+            }
+
+            fn name(&self) -> &'static str {
+                match self {
+                    $(Self::$variant => $name,)*
+                }
+            }
+
+            fn required(&self) -> bool {
+                #[allow(unreachable_patterns)]
+                match self {
+                    $(Self::$required => true,)*
+                    _ => false,
+                }
+            }
+
+            fn value_length(&self) -> Option<$crate::tag::ValueLength> {
+                #[allow(unused_imports)]
+                use $crate::tag::ValueLength::*;
+
+                match self {
+                    $(Self::$variant => None$(.or(Some($length)))?,)*
+                }
+            }
+
+            fn special_values(&self) -> &'static [$crate::tag::SpecialValue] {
+                #[allow(unreachable_patterns)]
+                match self {
+                    $($(
+                        Self::$special => &[$($crate::tag::SpecialValue {
+                            raw: &$raw,
+                            meaning: $meaning,
+                        }),*],
+                    )*)?
+                    _ => &[],
+                }
+            }
+
+            fn format(&self) -> Option<$crate::format::ValueFormat> {
+                #[allow(unreachable_patterns)]
+                match self {
+                    $($(
+                        Self::$formatted => Some($crate::format::ValueFormat {
+                            klv: $crate::format::KlvFormat::$klv,
+                            software: $crate::format::SoftwareFormat::$software,
+                            params: $crate::format::FormatParams {
+                                range: None$(.or(Some(($min, $max))))?,
+                            },
+                        }),
+                    )*)?
+                    _ => None,
+                }
+            }
+
+            fn unit(&self) -> Option<&'static str> {
+                #[allow(unreachable_patterns)]
+                match self {
+                    $($(Self::$measured => Some($unit),)*)?
+                    _ => None,
+                }
+            }
+
+            fn enumeration(&self) -> Option<::std::ops::RangeInclusive<u64>> {
+                #[allow(unreachable_patterns)]
+                match self {
+                    $($(Self::$enumerated => Some($values),)*)?
+                    _ => None,
+                }
+            }
+        }
+    };
+}
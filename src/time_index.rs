@@ -0,0 +1,49 @@
+//! Index of where the sets in a buffer are by the time they were made.
+
+use std::{
+    ops::Range,
+    time::{Duration, SystemTime},
+};
+
+/// Tag of the Precision Time Stamp in ST 0601 and most other MISB local
+/// sets.
+pub const PRECISION_TIME_STAMP_TAG: u128 = 2;
+
+/// Converts a Precision Time Stamp, in microseconds since the epoch, to a
+/// `SystemTime`.
+///
+/// # Returns
+///
+/// - `Some(SystemTime)` - `raw` is an 8-byte timestamp.
+/// - `None` - `raw` is not 8 bytes long.
+pub fn precision_time_stamp(raw: &[u8]) -> Option<SystemTime> {
+    let micros = u64::from_be_bytes(raw.try_into().ok()?);
+    SystemTime::UNIX_EPOCH.checked_add(Duration::from_micros(micros))
+}
+
+/// Time of every set with a Precision Time Stamp, and where every set without
+/// one is.
+#[derive(Clone, Debug, Default, PartialEq, Eq, getset::Getters)]
+#[getset(get = "pub")]
+pub struct TimeIndex {
+    /// Timestamp and offset of the first byte of the Universal Key of each
+    /// set, in the order they are in the buffer.
+    entries: Vec<(SystemTime, u64)>,
+    /// Offsets of the first byte of the Universal Key of each set whose
+    /// Precision Time Stamp is missing or could not be read.
+    missing: Vec<u64>,
+}
+
+impl TimeIndex {
+    pub fn new(entries: Vec<(SystemTime, u64)>, missing: Vec<u64>) -> Self {
+        Self { entries, missing }
+    }
+
+    /// Offsets of the sets whose timestamp lies in `range`.
+    pub fn offsets_in(&self, range: &Range<SystemTime>) -> impl Iterator<Item = u64> {
+        self.entries
+            .iter()
+            .filter(|(time, _)| range.contains(time))
+            .map(|(_, offset)| *offset)
+    }
+}
@@ -0,0 +1,654 @@
+//! Extraction of KLV metadata carried in an MPEG-2 Transport Stream.
+//!
+//! KLV is carried in transport streams as described by _SMPTE RP 217_ and
+//! _MISB ST 1402: MPEG-2 Transport of MISB Local Set Metadata_. The PAT and
+//! PMTs are parsed to find the elementary streams carrying KLV, the PES
+//! packets on those PIDs are reassembled, and their payloads are handed back
+//! ready to be parsed with `UniversalSet` or `UniversalSetReader`.
+
+use std::{
+    collections::{HashMap, VecDeque},
+    io::{self, Read},
+};
+
+/// Every transport stream packet is this many bytes long.
+pub const TS_PACKET_LENGTH: usize = 188;
+
+/// First byte of every transport stream packet.
+pub const SYNC_BYTE: u8 = 0x47;
+
+/// PID the Program Association Table is always carried on.
+const PAT_PID: u16 = 0x0000;
+
+/// `stream_type` used for asynchronous KLV carried as private PES data.
+pub const STREAM_TYPE_PRIVATE_PES: u8 = 0x06;
+
+/// `stream_type` used for synchronous KLV carried as metadata in PES packets.
+pub const STREAM_TYPE_METADATA_PES: u8 = 0x15;
+
+/// Format identifier found in the registration and metadata descriptors of
+/// elementary streams carrying KLV.
+pub const KLVA_FORMAT_IDENTIFIER: [u8; 4] = *b"KLVA";
+
+const REGISTRATION_DESCRIPTOR_TAG: u8 = 0x05;
+const METADATA_DESCRIPTOR_TAG: u8 = 0x26;
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error("Transport stream packet at offset {offset} does not start with a sync byte")]
+    LostSync { offset: u64 },
+    #[error(transparent)]
+    Io(#[from] io::Error),
+}
+
+/// How KLV is carried in an elementary stream, per _MISB ST 1402_.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Carriage {
+    /// Carried in private data PES packets (`stream_type` 0x06) whose
+    /// timestamps, when present, are only loosely related to the video.
+    Asynchronous,
+
+    /// Carried in metadata PES packets (`stream_type` 0x15) as metadata
+    /// access units whose PTS matches the video frame they describe.
+    Synchronous,
+}
+
+/// KLV bytes carried by a single PES packet.
+#[derive(Clone, Debug, PartialEq, Eq, getset::CopyGetters, getset::Getters)]
+pub struct KlvPayload {
+    /// PID of the elementary stream the payload was carried on.
+    #[getset(get_copy = "pub")]
+    pid: u16,
+
+    /// How the KLV was carried.
+    #[getset(get_copy = "pub")]
+    carriage: Carriage,
+
+    /// Presentation timestamp of the PES packet in 90 kHz clock ticks.
+    #[getset(get_copy = "pub")]
+    pts: Option<u64>,
+
+    /// KLV bytes with any PES and metadata access unit headers removed.
+    #[getset(get = "pub")]
+    data: Vec<u8>,
+}
+
+/// PES packet being reassembled from transport stream packets.
+#[derive(Debug, Default)]
+struct PendingPes {
+    bytes: Vec<u8>,
+}
+
+/// PSI section being reassembled from transport stream packets.
+#[derive(Debug, Default)]
+struct PendingSection {
+    bytes: Vec<u8>,
+}
+
+/// Iterator over the KLV payloads in a transport stream.
+///
+/// Only the KLV elementary streams announced by a PMT are reassembled. Every
+/// other PID is skipped.
+#[derive(Debug)]
+pub struct TsDemuxer<R>
+where
+    R: Read,
+{
+    reader: R,
+    /// Offset in the stream of the next packet.
+    offset: u64,
+    /// PIDs carrying PMTs, from the PAT.
+    pmt_pids: Vec<u16>,
+    /// PIDs carrying KLV, from the PMTs.
+    klv_pids: HashMap<u16, Carriage>,
+    sections: HashMap<u16, PendingSection>,
+    pes: HashMap<u16, PendingPes>,
+    ready: VecDeque<KlvPayload>,
+    done: bool,
+}
+
+impl<R> TsDemuxer<R>
+where
+    R: Read,
+{
+    pub fn new(reader: R) -> Self {
+        Self {
+            reader,
+            offset: 0,
+            pmt_pids: Vec::new(),
+            klv_pids: HashMap::new(),
+            sections: HashMap::new(),
+            pes: HashMap::new(),
+            ready: VecDeque::new(),
+            done: false,
+        }
+    }
+
+    /// PIDs found to be carrying KLV so far, along with how it is carried.
+    pub fn klv_pids(&self) -> &HashMap<u16, Carriage> {
+        &self.klv_pids
+    }
+
+    /// Reads the next transport stream packet.
+    ///
+    /// # Returns
+    ///
+    /// - `Ok(true)` - A packet was read and processed.
+    /// - `Ok(false)` - The stream ended. A partial trailing packet is ignored.
+    /// - `Err(Error)` - The packet was not aligned or could not be read.
+    fn read_packet(&mut self) -> Result<bool, Error> {
+        let mut packet = [0; TS_PACKET_LENGTH];
+        let mut filled = 0;
+        while filled < TS_PACKET_LENGTH {
+            match self.reader.read(&mut packet[filled..]) {
+                Ok(0) => return Ok(false),
+                Ok(n) => filled += n,
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                Err(e) => return Err(e.into()),
+            }
+        }
+
+        if packet[0] != SYNC_BYTE {
+            return Err(Error::LostSync {
+                offset: self.offset,
+            });
+        }
+        self.offset += TS_PACKET_LENGTH as u64;
+
+        let payload_unit_start = packet[1] & 0x40 != 0;
+        let pid = u16::from_be_bytes([packet[1] & 0x1F, packet[2]]);
+        let adaptation_field_control = (packet[3] >> 4) & 0x03;
+
+        let payload_start = match adaptation_field_control {
+            0b01 => 4,
+            0b11 => 5 + packet[4] as usize,
+            // Adaptation field only, or reserved.
+            _ => return Ok(true),
+        };
+        let Some(payload) = packet.get(payload_start..) else {
+            return Ok(true);
+        };
+
+        if pid == PAT_PID || self.pmt_pids.contains(&pid) {
+            self.push_section(pid, payload_unit_start, payload);
+        } else if let Some(carriage) = self.klv_pids.get(&pid).copied() {
+            self.push_pes(pid, carriage, payload_unit_start, payload);
+        }
+
+        Ok(true)
+    }
+
+    fn push_section(&mut self, pid: u16, payload_unit_start: bool, payload: &[u8]) {
+        let payload = if payload_unit_start {
+            let Some(&pointer_field) = payload.first() else {
+                return;
+            };
+            let Some(section_start) = payload.get(1 + pointer_field as usize..) else {
+                return;
+            };
+            self.sections.insert(pid, PendingSection::default());
+            section_start
+        } else {
+            payload
+        };
+
+        let Some(pending) = self.sections.get_mut(&pid) else {
+            return;
+        };
+        pending.bytes.extend_from_slice(payload);
+
+        if pending.bytes.len() < 3 {
+            return;
+        }
+        let section_length =
+            u16::from_be_bytes([pending.bytes[1] & 0x0F, pending.bytes[2]]) as usize;
+        if pending.bytes.len() < 3 + section_length {
+            return;
+        }
+
+        let pending = self.sections.remove(&pid).unwrap();
+        let section = &pending.bytes[..3 + section_length];
+        match section[0] {
+            0x00 => self.parse_pat(section),
+            0x02 => self.parse_pmt(section),
+            _ => {}
+        }
+    }
+
+    /// Records the PMT PIDs listed in a Program Association Table section.
+    fn parse_pat(&mut self, section: &[u8]) {
+        // Skip the 8 byte header and drop the trailing CRC.
+        let Some(programs) = section.get(8..section.len().saturating_sub(4)) else {
+            return;
+        };
+        for program in programs.chunks_exact(4) {
+            let program_number = u16::from_be_bytes([program[0], program[1]]);
+            let pid = u16::from_be_bytes([program[2] & 0x1F, program[3]]);
+            // Program number zero points at the network information table.
+            if program_number != 0 && !self.pmt_pids.contains(&pid) {
+                self.pmt_pids.push(pid);
+            }
+        }
+    }
+
+    /// Records the KLV elementary streams listed in a Program Map Table
+    /// section.
+    fn parse_pmt(&mut self, section: &[u8]) {
+        let end = section.len().saturating_sub(4);
+        if end < 12 {
+            return;
+        }
+        let program_info_length = u16::from_be_bytes([section[10] & 0x0F, section[11]]) as usize;
+
+        let mut position = 12 + program_info_length;
+        while position + 5 <= end {
+            let stream_type = section[position];
+            let pid = u16::from_be_bytes([section[position + 1] & 0x1F, section[position + 2]]);
+            let es_info_length =
+                u16::from_be_bytes([section[position + 3] & 0x0F, section[position + 4]]) as usize;
+            let descriptors_end = (position + 5 + es_info_length).min(end);
+            let descriptors = &section[position + 5..descriptors_end];
+
+            let carriage = match stream_type {
+                STREAM_TYPE_PRIVATE_PES
+                    if has_klva_descriptor(descriptors, REGISTRATION_DESCRIPTOR_TAG) =>
+                {
+                    Some(Carriage::Asynchronous)
+                }
+                STREAM_TYPE_METADATA_PES
+                    if has_klva_descriptor(descriptors, METADATA_DESCRIPTOR_TAG) =>
+                {
+                    Some(Carriage::Synchronous)
+                }
+                _ => None,
+            };
+            if let Some(carriage) = carriage {
+                self.klv_pids.insert(pid, carriage);
+            }
+
+            position = descriptors_end;
+        }
+    }
+
+    fn push_pes(&mut self, pid: u16, carriage: Carriage, payload_unit_start: bool, payload: &[u8]) {
+        if payload_unit_start {
+            if let Some(pending) = self.pes.remove(&pid) {
+                self.finish_pes(pid, carriage, pending);
+            }
+            self.pes.insert(pid, PendingPes::default());
+        }
+
+        let Some(pending) = self.pes.get_mut(&pid) else {
+            // Joined the stream part way through a PES packet.
+            return;
+        };
+        pending.bytes.extend_from_slice(payload);
+
+        // Bounded PES packets can be finished without waiting for the next one
+        // to start.
+        if pending.bytes.len() >= 6 {
+            let packet_length = u16::from_be_bytes([pending.bytes[4], pending.bytes[5]]) as usize;
+            if packet_length != 0 && pending.bytes.len() >= 6 + packet_length {
+                let pending = self.pes.remove(&pid).unwrap();
+                self.finish_pes(pid, carriage, pending);
+            }
+        }
+    }
+
+    /// Strips the PES header from a reassembled PES packet and queues its KLV.
+    fn finish_pes(&mut self, pid: u16, carriage: Carriage, pending: PendingPes) {
+        let bytes = pending.bytes;
+        if bytes.len() < 9 || bytes[..3] != [0x00, 0x00, 0x01] {
+            return;
+        }
+
+        let packet_length = u16::from_be_bytes([bytes[4], bytes[5]]) as usize;
+        let end = match packet_length {
+            0 => bytes.len(),
+            length => (6 + length).min(bytes.len()),
+        };
+
+        let pts_dts_flags = bytes[7] >> 6;
+        let header_data_length = bytes[8] as usize;
+        let data_start = 9 + header_data_length;
+        if data_start > end {
+            return;
+        }
+        let pts = match pts_dts_flags {
+            0b10 | 0b11 if header_data_length >= 5 => Some(parse_timestamp(&bytes[9..14])),
+            _ => None,
+        };
+
+        let data = &bytes[data_start..end];
+        let data = match carriage {
+            Carriage::Asynchronous => data.to_vec(),
+            Carriage::Synchronous => metadata_au_cells(data),
+        };
+
+        self.ready.push_back(KlvPayload {
+            pid,
+            carriage,
+            pts,
+            data,
+        });
+    }
+
+    /// Finishes every PES packet still being reassembled at the end of the
+    /// stream.
+    fn flush(&mut self) {
+        let mut pids = self.pes.keys().copied().collect::<Vec<_>>();
+        pids.sort();
+        for pid in pids {
+            let pending = self.pes.remove(&pid).unwrap();
+            if let Some(carriage) = self.klv_pids.get(&pid).copied() {
+                self.finish_pes(pid, carriage, pending);
+            }
+        }
+    }
+}
+
+impl<R> Iterator for TsDemuxer<R>
+where
+    R: Read,
+{
+    type Item = Result<KlvPayload, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(payload) = self.ready.pop_front() {
+                return Some(Ok(payload));
+            }
+            if self.done {
+                return None;
+            }
+
+            match self.read_packet() {
+                Ok(true) => {}
+                Ok(false) => {
+                    self.done = true;
+                    self.flush();
+                }
+                Err(e) => {
+                    self.done = true;
+                    return Some(Err(e));
+                }
+            }
+        }
+    }
+}
+
+/// Whether a descriptor loop contains a descriptor with the given tag whose
+/// format identifier is `KLVA`.
+///
+/// The registration descriptor starts with the format identifier. The
+/// metadata descriptor starts with a two byte application format, a one byte
+/// metadata format (0xFF), and then the format identifier.
+fn has_klva_descriptor(mut descriptors: &[u8], wanted_tag: u8) -> bool {
+    while descriptors.len() >= 2 {
+        let tag = descriptors[0];
+        let length = descriptors[1] as usize;
+        let Some(body) = descriptors.get(2..2 + length) else {
+            return false;
+        };
+
+        let format_identifier = match tag {
+            REGISTRATION_DESCRIPTOR_TAG => body.get(..4),
+            METADATA_DESCRIPTOR_TAG if body.get(2) == Some(&0xFF) => body.get(3..7),
+            _ => None,
+        };
+        if tag == wanted_tag && format_identifier == Some(&KLVA_FORMAT_IDENTIFIER[..]) {
+            return true;
+        }
+
+        descriptors = &descriptors[2 + length..];
+    }
+    false
+}
+
+/// Parses a 33 bit PES timestamp out of its 5 byte marker-bit encoding.
+fn parse_timestamp(bytes: &[u8]) -> u64 {
+    (((bytes[0] as u64 >> 1) & 0x07) << 30)
+        | ((bytes[1] as u64) << 22)
+        | ((bytes[2] as u64 >> 1) << 15)
+        | ((bytes[3] as u64) << 7)
+        | (bytes[4] as u64 >> 1)
+}
+
+/// Concatenates the data of the metadata access unit cells in a synchronous
+/// metadata PES payload.
+///
+/// Every cell starts with a 5 byte header ending in the 16 bit length of the
+/// cell's data.
+fn metadata_au_cells(mut data: &[u8]) -> Vec<u8> {
+    let mut klv = Vec::new();
+    while data.len() >= 5 {
+        let cell_length = u16::from_be_bytes([data[3], data[4]]) as usize;
+        let cell_end = (5 + cell_length).min(data.len());
+        klv.extend_from_slice(&data[5..cell_end]);
+        data = &data[cell_end..];
+    }
+    klv
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::universal_set::{UNIVERSAL_KEY_LENGTH, UniversalKey, UniversalSetReader};
+    use itertools::{Itertools, chain};
+
+    const TEST_UNIVERSAL_KEY: [u8; UNIVERSAL_KEY_LENGTH] = [
+        0x06, 0x0E, 0x2B, 0x34, 0x02, 0x0B, 0x01, 0x01, 0x0E, 0x01, 0x03, 0x01, 0x01, 0x00, 0x00,
+        0x00,
+    ];
+    const PMT_PID: u16 = 0x0100;
+    const KLV_PID: u16 = 0x0101;
+    const VIDEO_PID: u16 = 0x0102;
+
+    /// Builds one transport stream packet, padding short payloads with
+    /// adaptation field stuffing.
+    fn ts_packet(pid: u16, payload_unit_start: bool, payload: &[u8]) -> Vec<u8> {
+        let mut packet = vec![
+            SYNC_BYTE,
+            ((payload_unit_start as u8) << 6) | (pid >> 8) as u8,
+            pid as u8,
+            0x10,
+        ];
+        let stuffing = TS_PACKET_LENGTH - 4 - payload.len();
+        if stuffing > 0 {
+            packet[3] = 0x30;
+            packet.push((stuffing - 1) as u8);
+            if stuffing > 1 {
+                packet.push(0x00);
+                packet.extend(std::iter::repeat_n(0xFF, stuffing - 2));
+            }
+        }
+        packet.extend_from_slice(payload);
+        packet
+    }
+
+    /// Wraps a PSI section in a transport stream packet with a CRC
+    /// placeholder.
+    fn psi_packet(pid: u16, table_id: u8, body: &[u8]) -> Vec<u8> {
+        let section_length = body.len() + 4;
+        let section = chain!(
+            [0x00, table_id],
+            [0xB0 | (section_length >> 8) as u8, section_length as u8],
+            body.iter().copied(),
+            [0x00; 4]
+        )
+        .collect_vec();
+        ts_packet(pid, true, &section)
+    }
+
+    fn pat() -> Vec<u8> {
+        psi_packet(
+            PAT_PID,
+            0x00,
+            &[
+                0x00,
+                0x01,
+                0xC1,
+                0x00,
+                0x00,
+                0x00,
+                0x01,
+                0xE0 | (PMT_PID >> 8) as u8,
+                PMT_PID as u8,
+            ],
+        )
+    }
+
+    fn pmt(klv_stream_type: u8, klv_descriptor: &[u8]) -> Vec<u8> {
+        let body = chain!(
+            [0x00, 0x01, 0xC1, 0x00, 0x00],
+            [0xE0 | (VIDEO_PID >> 8) as u8, VIDEO_PID as u8],
+            [0xF0, 0x00],
+            [
+                0x1B,
+                0xE0 | (VIDEO_PID >> 8) as u8,
+                VIDEO_PID as u8,
+                0xF0,
+                0x00
+            ],
+            [klv_stream_type, 0xE0 | (KLV_PID >> 8) as u8, KLV_PID as u8],
+            [0xF0, klv_descriptor.len() as u8],
+            klv_descriptor.iter().copied()
+        )
+        .collect_vec();
+        psi_packet(PMT_PID, 0x02, &body)
+    }
+
+    fn encode_pts(pts: u64) -> [u8; 5] {
+        [
+            0x21 | ((pts >> 29) & 0x0E) as u8,
+            (pts >> 22) as u8,
+            ((pts >> 14) & 0xFE) as u8 | 0x01,
+            (pts >> 7) as u8,
+            ((pts << 1) & 0xFE) as u8 | 0x01,
+        ]
+    }
+
+    fn pes(stream_id: u8, pts: u64, data: &[u8]) -> Vec<u8> {
+        let packet_length = 3 + 5 + data.len();
+        chain!(
+            [0x00, 0x00, 0x01, stream_id],
+            [(packet_length >> 8) as u8, packet_length as u8],
+            [0x80, 0x80, 0x05],
+            encode_pts(pts),
+            data.iter().copied()
+        )
+        .collect_vec()
+    }
+
+    /// Splits a PES packet across as many transport stream packets as needed.
+    fn pes_packets(pes: &[u8]) -> Vec<u8> {
+        pes.chunks(TS_PACKET_LENGTH - 4)
+            .enumerate()
+            .flat_map(|(i, chunk)| ts_packet(KLV_PID, i == 0, chunk))
+            .collect_vec()
+    }
+
+    /// Universal Set long enough to need two transport stream packets.
+    fn klv_packet() -> Vec<u8> {
+        chain!(
+            TEST_UNIVERSAL_KEY,
+            [0x81, 0xF7],
+            [0x01, 0x81, 0xF0],
+            [0xAB; 0xF0],
+            [0x02, 0x02, 0x12, 0x34]
+        )
+        .collect_vec()
+    }
+
+    #[test]
+    fn asynchronous_klv() {
+        let registration_descriptor = chain!([0x05, 0x04], KLVA_FORMAT_IDENTIFIER).collect_vec();
+        let stream = chain!(
+            pat(),
+            pmt(STREAM_TYPE_PRIVATE_PES, &registration_descriptor),
+            ts_packet(VIDEO_PID, true, &[0x00; 20]),
+            pes_packets(&pes(0xBD, 0x1_2345_6789, &klv_packet()))
+        )
+        .collect_vec();
+
+        let payloads = TsDemuxer::new(&stream[..])
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        let payload = payloads.iter().exactly_one().unwrap();
+        assert_eq!(payload.pid(), KLV_PID);
+        assert_eq!(payload.carriage(), Carriage::Asynchronous);
+        assert_eq!(payload.pts(), Some(0x1_2345_6789));
+        assert_eq!(*payload.data(), klv_packet());
+
+        let sets =
+            UniversalSetReader::new(UniversalKey::new(TEST_UNIVERSAL_KEY), &payload.data()[..])
+                .collect::<Result<Vec<_>, _>>()
+                .unwrap();
+        let tags = sets[0].items().iter().map(|item| item.tag()).collect_vec();
+        assert_eq!(tags, vec![Some(1), Some(2)]);
+    }
+
+    #[test]
+    fn synchronous_klv() {
+        let metadata_descriptor = chain!(
+            [0x26, 0x09, 0x01, 0x00, 0xFF],
+            KLVA_FORMAT_IDENTIFIER,
+            [0x00, 0x0F]
+        )
+        .collect_vec();
+        let klv = klv_packet();
+        let au_cell = chain!(
+            [0x00, 0x00, 0xDF],
+            [(klv.len() >> 8) as u8, klv.len() as u8],
+            klv.iter().copied()
+        )
+        .collect_vec();
+        let stream = chain!(
+            pat(),
+            pmt(STREAM_TYPE_METADATA_PES, &metadata_descriptor),
+            pes_packets(&pes(0xFC, 3003, &au_cell))
+        )
+        .collect_vec();
+
+        let payloads = TsDemuxer::new(&stream[..])
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        let payload = payloads.iter().exactly_one().unwrap();
+        assert_eq!(payload.carriage(), Carriage::Synchronous);
+        assert_eq!(payload.pts(), Some(3003));
+        assert_eq!(*payload.data(), klv);
+    }
+
+    #[test]
+    fn empty_section_payload() {
+        let registration_descriptor = chain!([0x05, 0x04], KLVA_FORMAT_IDENTIFIER).collect_vec();
+        let stream = chain!(
+            // Adaptation field stuffing fills the whole packet.
+            ts_packet(PAT_PID, true, &[]),
+            pat(),
+            ts_packet(PMT_PID, true, &[]),
+            pmt(STREAM_TYPE_PRIVATE_PES, &registration_descriptor),
+            pes_packets(&pes(0xBD, 0x1_2345_6789, &klv_packet()))
+        )
+        .collect_vec();
+
+        let payloads = TsDemuxer::new(&stream[..])
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(payloads.iter().exactly_one().unwrap().pid(), KLV_PID);
+    }
+
+    #[test]
+    fn lost_sync() {
+        let mut stream = pat();
+        stream.extend(ts_packet(VIDEO_PID, true, &[0x00; 20]));
+        stream[TS_PACKET_LENGTH] = 0x00;
+
+        let mut demuxer = TsDemuxer::new(&stream[..]);
+        assert!(matches!(
+            demuxer.next(),
+            Some(Err(Error::LostSync { offset: 188 }))
+        ));
+        assert!(demuxer.next().is_none());
+    }
+}
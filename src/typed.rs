@@ -0,0 +1,532 @@
+//! Reading the items of a set through the tag table of its standard.
+
+use std::{
+    cell::{OnceCell, RefCell},
+    collections::BTreeMap,
+    io::{self, Cursor, Read, Seek},
+    rc::Rc,
+};
+
+use crate::{
+    encoding,
+    format::{SoftwareValue, convert},
+    group::ItemEncoding,
+    klv::{ItemContext, ItemKey, Klv, OwnedKlv},
+    local_set::{LocalSet, OwnedLocalSet},
+    tag::{Tag, TagReading},
+};
+
+/// Value of an item read through its tag.
+///
+/// The raw bytes are converted to the tag's software type the first time the
+/// value is asked for and the result is kept for later calls.
+#[derive(Debug, getset::CopyGetters, getset::Getters)]
+pub struct TagValue<G> {
+    tag: G,
+    /// Bytes of the value as they appear in the set.
+    #[getset(get = "pub")]
+    raw: Vec<u8>,
+    /// Offset of the first byte of the value in the buffer.
+    #[getset(get_copy = "pub")]
+    offset: u64,
+    value: OnceCell<Result<TagReading<SoftwareValue>, encoding::Error>>,
+}
+
+impl<G> TagValue<G>
+where
+    G: Tag,
+{
+    pub fn new(tag: G, raw: Vec<u8>, offset: u64) -> Self {
+        Self {
+            tag,
+            raw,
+            offset,
+            value: OnceCell::new(),
+        }
+    }
+
+    pub fn tag(&self) -> G {
+        self.tag
+    }
+
+    /// Value converted by the tag's `Tag::decode`.
+    ///
+    /// # Returns
+    ///
+    /// - `Ok(TagReading::Value)` - The converted value.
+    /// - `Ok(TagReading::Special)` - The value is one of the tag's special
+    ///   values.
+    /// - `Ok(TagReading::Empty)` - The value is empty and the tag does not
+    ///   convert to a string or bytes.
+    /// - `Err(encoding::Error)` - The value could not be converted.
+    pub fn value(&self) -> Result<&TagReading<SoftwareValue>, &encoding::Error> {
+        self.value
+            .get_or_init(|| self.tag.decode(&self.raw))
+            .as_ref()
+    }
+
+    /// Value as it is encoded in the set, before any mapping to its software
+    /// type. Values of tags without a format are returned as bytes.
+    pub fn klv_value(&self) -> Result<SoftwareValue, encoding::Error> {
+        match self.tag.format() {
+            Some(format) => {
+                let (klv, software) = format.klv.unmapped();
+                convert(&self.raw, &klv, &software, &Default::default())
+            }
+            None => Ok(SoftwareValue::Bytes(self.raw.clone())),
+        }
+    }
+}
+
+/// Item of a `TypedLocalSet`, as returned by `TypedLocalSet::items`.
+#[derive(Debug)]
+pub enum TypedItem<'a, G> {
+    /// Item whose key is in the tag table.
+    Known(&'a TagValue<G>),
+    /// Item whose key is not in the tag table, such as a vendor-specific tag
+    /// or one from a newer revision of the standard.
+    Unknown { key: ItemKey, raw: &'a [u8] },
+}
+
+/// Values of a local set keyed by the tags of its standard.
+#[derive(Debug, getset::Getters)]
+pub struct TypedLocalSet<G> {
+    /// Last occurrence of each tag in the set.
+    values: BTreeMap<G, TagValue<G>>,
+    /// Raw value of the last occurrence of each item whose key is not in the
+    /// tag table.
+    #[getset(get = "pub")]
+    unknown: BTreeMap<ItemKey, Vec<u8>>,
+    /// Key of every item in `values` and `unknown`, in the order their last
+    /// occurrences appear in the set.
+    order: Vec<ItemKey>,
+}
+
+impl<G> TypedLocalSet<G>
+where
+    G: Tag,
+{
+    /// Reads the value of every item in `set`.
+    ///
+    /// Items whose keys the table `G` does not define are kept as raw bytes
+    /// in `TypedLocalSet::unknown`. Items keyed by Universal Labels are looked
+    /// up by `ItemKey::number`.
+    pub fn from_local_set<T>(set: &LocalSet<T>) -> Result<Self, io::Error>
+    where
+        T: Read + Seek,
+    {
+        let mut typed = Self {
+            values: BTreeMap::new(),
+            unknown: BTreeMap::new(),
+            order: Vec::new(),
+        };
+        for klv in set.iter() {
+            typed.insert(klv)?;
+        }
+        Ok(typed)
+    }
+
+    /// Reads the items of a local set nested in the value of another item,
+    /// such as the ST 0102 Security Local Set carried by ST 0601.
+    ///
+    /// The nested set has no Universal Key or length of its own and ends with
+    /// `raw`. Offsets are relative to the first byte of `raw`.
+    ///
+    /// # Returns
+    ///
+    /// - `Ok(TypedLocalSet)` - Every item in `raw` was read.
+    /// - `Err(encoding::Error)` - An item could not be read or runs past the
+    ///   end of `raw`.
+    pub fn from_value(raw: &[u8]) -> Result<Self, encoding::Error> {
+        let buf = Rc::new(RefCell::new(Cursor::new(raw)));
+        let mut typed = Self {
+            values: BTreeMap::new(),
+            unknown: BTreeMap::new(),
+            order: Vec::new(),
+        };
+        for klv in LocalSet::items_in(buf, 0..raw.len() as u64, &ItemEncoding::default()) {
+            typed.insert(&klv?)?;
+        }
+        Ok(typed)
+    }
+
+    /// Checks the length of every value against the `ValueLength` constraint
+    /// of its tag.
+    ///
+    /// # Returns
+    ///
+    /// - `Ok(())` - Every value satisfies its tag's constraint.
+    /// - `Err(encoding::Error::InvalidLength)` - The first value that does
+    ///   not.
+    pub fn check_lengths(&self) -> Result<(), encoding::Error> {
+        for value in self.values() {
+            let actual = value.raw().len() as u64;
+            match value.tag().value_length() {
+                Some(expected) if !expected.allows(actual) => {
+                    return Err(encoding::Error::InvalidLength {
+                        key: ItemKey::Tag(value.tag().number()),
+                        offset: value.offset(),
+                        expected,
+                        actual,
+                    });
+                }
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    /// Keeps the value of `klv`, replacing any earlier item with its key.
+    fn insert<T>(&mut self, klv: &Klv<T>) -> Result<(), io::Error>
+    where
+        T: Read + Seek,
+    {
+        match G::try_from(klv.key().number()) {
+            Ok(tag) => {
+                let value = TagValue::new(tag, klv.read_value()?, klv.value_offset());
+                if self.values.insert(tag, value).is_some() {
+                    // Tags and Universal Labels with the same number are the
+                    // same item.
+                    let number = klv.key().number();
+                    self.order
+                        .retain(|key| self.unknown.contains_key(key) || key.number() != number);
+                }
+            }
+            _ => {
+                if self.unknown.insert(klv.key(), klv.read_value()?).is_some() {
+                    self.order.retain(|key| *key != klv.key());
+                }
+            }
+        }
+        self.order.push(klv.key());
+        Ok(())
+    }
+
+    /// Replaces the value of `tag` with `raw`, keeping its place in the set.
+    /// Tags that are not in the set are added to the end.
+    ///
+    /// A replaced value keeps the offset of the value it replaces. Added
+    /// values have an offset of 0.
+    pub fn set_raw(&mut self, tag: G, raw: Vec<u8>) {
+        let offset = match self.values.get(&tag) {
+            Some(value) => value.offset(),
+            None => {
+                self.order.push(ItemKey::Tag(tag.number()));
+                0
+            }
+        };
+        self.values.insert(tag, TagValue::new(tag, raw, offset));
+    }
+
+    /// Replaces the value of `tag` with `value` encoded by `Tag::to_klv`. See
+    /// `TypedLocalSet::set_raw`.
+    ///
+    /// # Returns
+    ///
+    /// - `Ok(())` - The value was replaced.
+    /// - `Err(encoding::Error)` - `value` could not be encoded. The set is
+    ///   left unchanged.
+    pub fn set(&mut self, tag: G, value: &SoftwareValue) -> Result<(), encoding::Error> {
+        self.set_raw(tag, tag.to_klv(value)?);
+        Ok(())
+    }
+
+    /// Every item in the set, known or not, in the order they appear in the
+    /// set.
+    pub fn items(&self) -> impl Iterator<Item = TypedItem<'_, G>> {
+        self.order.iter().map(|key| match self.unknown.get(key) {
+            Some(raw) => TypedItem::Unknown { key: *key, raw },
+            None => TypedItem::Known(
+                G::try_from(key.number())
+                    .ok()
+                    .and_then(|tag| self.values.get(&tag))
+                    .expect("Every ordered key is in the set"),
+            ),
+        })
+    }
+
+    /// Copies every item, including those whose keys are not in the tag
+    /// table, into an `OwnedLocalSet` in the order they appear in the set.
+    ///
+    /// Unknown items are copied byte for byte, so serializing the returned
+    /// set reproduces them exactly.
+    pub fn to_owned_local_set(&self) -> OwnedLocalSet {
+        OwnedLocalSet::new(
+            self.order
+                .iter()
+                .zip(self.items())
+                .map(|(key, item)| match item {
+                    TypedItem::Known(value) => OwnedKlv::with_key(*key, value.raw().clone()),
+                    TypedItem::Unknown { raw, .. } => OwnedKlv::with_key(*key, raw.to_vec()),
+                })
+                .collect(),
+        )
+    }
+
+    /// Value of `tag`, if it is in the set.
+    pub fn get(&self, tag: G) -> Option<&TagValue<G>> {
+        self.values.get(&tag)
+    }
+
+    /// Where the value of `tag` lies, if it is in the set.
+    pub fn context(&self, tag: G) -> Option<ItemContext> {
+        let index = self
+            .items()
+            .position(|item| matches!(item, TypedItem::Known(value) if value.tag() == tag))?;
+        Some(ItemContext::new::<G>(
+            self.order[index],
+            index,
+            self.values[&tag].offset(),
+        ))
+    }
+
+    /// Every value in the set, ordered by tag.
+    pub fn values(&self) -> impl Iterator<Item = &TagValue<G>> {
+        self.values.values()
+    }
+
+    /// Raw bytes of the value of `tag`, if it is in the set.
+    pub fn raw(&self, tag: G) -> Option<&[u8]> {
+        self.get(tag).map(|value| value.raw().as_slice())
+    }
+
+    /// Converts the value of `tag` with `Tag::decode`.
+    ///
+    /// # Returns
+    ///
+    /// - `Ok(Some(SoftwareValue))` - `tag` is in the set and was converted.
+    /// - `Ok(None)` - `tag` is not in the set, is one of its special values, or
+    ///   is empty.
+    /// - `Err(encoding::Error)` - The value could not be converted.
+    pub fn decode(&self, tag: G) -> Result<Option<SoftwareValue>, encoding::Error> {
+        self.raw(tag)
+            .map(|raw| tag.decode(raw).map(TagReading::value))
+            .transpose()
+            .map(Option::flatten)
+    }
+
+    /// Reads the value of `tag`, converting it with `to_software` unless it is
+    /// one of the tag's special values.
+    ///
+    /// # Returns
+    ///
+    /// - `Some(TagReading)` - `tag` is in the set.
+    /// - `None` - `tag` is not in the set.
+    pub fn read<S>(&self, tag: G, to_software: impl FnOnce(&[u8]) -> S) -> Option<TagReading<S>> {
+        self.raw(tag).map(|raw| tag.read(raw, to_software))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::RefCell, io::Cursor, rc::Rc};
+
+    use super::*;
+    use crate::{misb::st0601::St0601Tag, tag::SpecialValue, universal_set::UNIVERSAL_KEY_LENGTH};
+    use itertools::{Itertools, chain};
+
+    /// Maps a two byte signed value over `-range..=range` degrees.
+    fn angle(range: f64) -> impl Fn(&[u8]) -> f64 {
+        move |raw| f64::from(i16::from_be_bytes([raw[0], raw[1]])) * range / f64::from(i16::MAX)
+    }
+
+    #[test]
+    fn out_of_range_angle_is_special() {
+        let buf = chain!(
+            [0x00; UNIVERSAL_KEY_LENGTH],
+            [0x08],
+            [0x06, 0x02, 0x80, 0x00],
+            [0x07, 0x02, 0x7F, 0xFF]
+        )
+        .collect_vec();
+        let set = LocalSet::read(0, Rc::new(RefCell::new(Cursor::new(buf)))).unwrap();
+        let set = TypedLocalSet::<St0601Tag>::from_local_set(&set).unwrap();
+
+        assert_eq!(
+            set.read(St0601Tag::PlatformPitchAngle, angle(20.0)),
+            Some(TagReading::Special(SpecialValue {
+                raw: &[0x80, 0x00],
+                meaning: "Out of Range"
+            }))
+        );
+        assert_eq!(
+            set.read(St0601Tag::PlatformRollAngle, angle(50.0)),
+            Some(TagReading::Value(50.0))
+        );
+        assert_eq!(
+            set.read(St0601Tag::PlatformHeadingAngle, angle(360.0)),
+            None
+        );
+    }
+
+    #[test]
+    fn heading_is_mapped_by_its_format() {
+        let buf = chain!(
+            [0x00; UNIVERSAL_KEY_LENGTH],
+            [0x04],
+            [0x05, 0x02, 0x71, 0xC2]
+        )
+        .collect_vec();
+        let set = LocalSet::read(0, Rc::new(RefCell::new(Cursor::new(buf)))).unwrap();
+        let set = TypedLocalSet::<St0601Tag>::from_local_set(&set).unwrap();
+
+        // Example value from MISB ST 0601.
+        let Ok(Some(SoftwareValue::Float64(heading))) = set.decode(St0601Tag::PlatformHeadingAngle)
+        else {
+            panic!("Heading is converted to a float");
+        };
+        assert!((heading - 159.9744).abs() < 0.0001);
+        assert_eq!(
+            St0601Tag::PlatformHeadingAngle
+                .to_klv(&SoftwareValue::Float64(heading))
+                .unwrap(),
+            [0x71, 0xC2]
+        );
+    }
+
+    #[test]
+    fn unknown_tags_are_kept() {
+        let buf = chain!(
+            [0x00; UNIVERSAL_KEY_LENGTH],
+            [0x07],
+            [0x41, 0x01, 0x11],
+            [0x81, 0x10, 0x01, 0xAA]
+        )
+        .collect_vec();
+        let set = LocalSet::read(0, Rc::new(RefCell::new(Cursor::new(buf)))).unwrap();
+        let set = TypedLocalSet::<St0601Tag>::from_local_set(&set).unwrap();
+
+        assert_eq!(
+            set.raw(St0601Tag::UasDatalinkLsVersionNumber),
+            Some(&[0x11][..])
+        );
+        assert_eq!(set.unknown().get(&ItemKey::Tag(144)), Some(&vec![0xAA]));
+    }
+
+    #[test]
+    fn unknown_tags_survive_round_trip() {
+        use crate::{keys, klv::OwnedKlv, universal_set::UniversalSet};
+
+        let packet = OwnedLocalSet::new(vec![
+            OwnedKlv::new(2, 1_000_000u64.to_be_bytes().to_vec()),
+            OwnedKlv::new(200, vec![0xDE, 0xAD]),
+            OwnedKlv::new(3, b"ALPHA".to_vec()),
+            OwnedKlv::new(65, vec![0x11]),
+        ])
+        .serialize(&keys::ST0601, true);
+        let buf = Rc::new(RefCell::new(Cursor::new(packet.clone())));
+        let set = UniversalSet::new(keys::ST0601, buf, 0).unwrap();
+        let mut typed = TypedLocalSet::<St0601Tag>::from_local_set(set.data()).unwrap();
+
+        assert!(matches!(
+            typed.items().nth(1),
+            Some(TypedItem::Unknown {
+                key: ItemKey::Tag(200),
+                raw: [0xDE, 0xAD]
+            })
+        ));
+        typed
+            .set(St0601Tag::MissionId, &SoftwareValue::String("BRAVO".into()))
+            .unwrap();
+        let edited = typed.to_owned_local_set().serialize(&keys::ST0601, true);
+
+        assert_eq!(edited.len(), packet.len());
+        let mission = packet
+            .windows(5)
+            .position(|bytes| bytes == b"ALPHA")
+            .unwrap();
+        let changed = packet
+            .iter()
+            .zip(&edited)
+            .positions(|(before, after)| before != after)
+            .collect_vec();
+        assert!(
+            changed
+                .iter()
+                .all(|&i| (mission..mission + 5).contains(&i) || i >= packet.len() - 2)
+        );
+        assert_eq!(&edited[mission..mission + 5], b"BRAVO");
+    }
+
+    crate::tag_table! {
+        #[repr(u8)]
+        enum ExampleTag {
+            Heading = 1, "Heading", Required(2);
+            Name = 2, "Name", Max(16);
+            Blob = 3, "Blob";
+        }
+
+        required: [Heading]
+
+        special_values: {
+            Heading => [[0xFF, 0xFF] => "Unknown"],
+        }
+
+        formats: {
+            Heading => Uint as Float64 in (0.0, 360.0),
+            Name => Utf8 as String,
+        }
+    }
+
+    fn example_set(heading: [u8; 2]) -> TypedLocalSet<ExampleTag> {
+        let buf = chain!(
+            [0x00; UNIVERSAL_KEY_LENGTH],
+            [0x0E],
+            [0x01, 0x02],
+            heading,
+            [0x02, 0x04],
+            *b"MISB",
+            [0x03, 0x02, 0xAA, 0xBB]
+        )
+        .collect_vec();
+        let set = LocalSet::read(0, Rc::new(RefCell::new(Cursor::new(buf)))).unwrap();
+        TypedLocalSet::from_local_set(&set).unwrap()
+    }
+
+    #[test]
+    fn values_are_converted_by_their_tag() {
+        let set = example_set([0x80, 0x00]);
+
+        let heading = set.get(ExampleTag::Heading).unwrap();
+        assert_eq!(heading.raw(), &vec![0x80, 0x00]);
+        assert_eq!(heading.klv_value().unwrap(), SoftwareValue::Uint64(0x8000));
+        assert_eq!(
+            heading.value().unwrap(),
+            &TagReading::Value(SoftwareValue::Float64(
+                f64::from(0x8000) * 360.0 / f64::from(u16::MAX)
+            ))
+        );
+        assert_eq!(
+            set.get(ExampleTag::Name).unwrap().value().unwrap(),
+            &TagReading::Value(SoftwareValue::String("MISB".into()))
+        );
+        assert_eq!(
+            set.get(ExampleTag::Blob).unwrap().value().unwrap(),
+            &TagReading::Value(SoftwareValue::Bytes(vec![0xAA, 0xBB]))
+        );
+    }
+
+    #[test]
+    fn value_is_converted_once() {
+        let set = example_set([0x00, 0x01]);
+        let heading = set.get(ExampleTag::Heading).unwrap();
+
+        assert!(std::ptr::eq(
+            heading.value().unwrap(),
+            heading.value().unwrap()
+        ));
+    }
+
+    #[test]
+    fn special_value_is_not_converted() {
+        let set = example_set([0xFF, 0xFF]);
+
+        assert_eq!(
+            set.get(ExampleTag::Heading).unwrap().value().unwrap(),
+            &TagReading::Special(SpecialValue {
+                raw: &[0xFF, 0xFF],
+                meaning: "Unknown"
+            })
+        );
+    }
+}
@@ -1,26 +1,258 @@
 use byteorder::ReadBytesExt;
 use std::{
     cell::RefCell,
-    io::{Read, Seek},
-    ops::Deref,
+    fmt,
+    fs::File,
+    io::{self, BufReader, Read, Seek, SeekFrom},
+    ops::{Deref, Range},
+    path::{Path, PathBuf},
     rc::Rc,
+    str::FromStr,
+    time::SystemTime,
 };
 
 use ringbuffer::{ConstGenericRingBuffer, RingBuffer};
 
-use crate::{encoding, klv::Klv, local_set::LocalSet};
+#[cfg(feature = "tokio")]
+use crate::encoding::ber::read_ber_async;
+
+use crate::{
+    encoding::{
+        self,
+        ber::{decode_ber, encode_ber, read_ber},
+        checked_end,
+        io_util::{DEFAULT_CHUNK, read_up_to},
+        seekable_length,
+    },
+    group::{GroupType, ItemEncoding},
+    keys,
+    klv::{ItemKey, KlvReader, OwnedKlv, ReadSeek, hash_range, truncated_or_other},
+    local_set::LocalSet,
+    misb::st0601::St0601Tag,
+    options::{Limit, ParseOptions, TagFilter},
+    progress::ProgressHandle,
+    scan::{ScanReport, ScanWarning, SetStatus, SetSummary},
+    time_index::{PRECISION_TIME_STAMP_TAG, TimeIndex, precision_time_stamp},
+    validation::{Severity, Violation, ViolationKind},
+};
 
 /// Length of a Universal Key is always 16 bytes.
 pub const UNIVERSAL_KEY_LENGTH: usize = 16;
 
-#[derive(Clone, Copy, Debug)]
+/// Index of the byte in a UL that holds the version of the registry it was
+/// defined in.
+pub const UL_VERSION_BYTE: usize = 7;
+
+/// Prefix of a Universal Key written as a URN.
+const URN_PREFIX: &str = "urn:smpte:ul:";
+
+/// Largest number of bytes a BER length that fits in a `u128` can take up.
+const MAX_BER_LENGTH: u64 = 17;
+
+/// First byte of every SMPTE UL.
+const UL_FIRST_BYTE: u8 = 0x06;
+
+/// Number of offsets `UniversalSet::discover_keys` keeps for each key.
+pub const DISCOVERED_OFFSETS: usize = 8;
+
+/// Number of bytes `UniversalSet::last_locations` reads at a time.
+const REVERSE_CHUNK_LENGTH: u64 = 64 * 1024;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 pub struct UniversalKey([u8; UNIVERSAL_KEY_LENGTH]);
 impl UniversalKey {
-    pub fn new(key: [u8; UNIVERSAL_KEY_LENGTH]) -> Self {
+    pub const fn new(key: [u8; UNIVERSAL_KEY_LENGTH]) -> Self {
+        Self(key)
+    }
+
+    /// Parses a key written as 32 hex digits in a const context. Any `.`,
+    /// `-`, or whitespace between digits is ignored.
+    ///
+    /// Use `FromStr` to parse keys at runtime. See `key!`.
+    ///
+    /// # Panics
+    ///
+    /// - `s` contains anything other than hex digits and separators.
+    /// - `s` does not contain exactly 32 hex digits.
+    pub const fn from_hex(s: &str) -> Self {
+        let s = s.as_bytes();
+        let mut key = [0; UNIVERSAL_KEY_LENGTH];
+        let mut digits = 0;
+        let mut i = 0;
+        while i < s.len() {
+            let digit = match s[i] {
+                b'.' | b'-' | b' ' | b'\t' | b'\n' => {
+                    i += 1;
+                    continue;
+                }
+                c @ b'0'..=b'9' => c - b'0',
+                c @ b'a'..=b'f' => c - b'a' + 10,
+                c @ b'A'..=b'F' => c - b'A' + 10,
+                _ => panic!("Universal Key contains a character that is not a hex digit"),
+            };
+            if digits == UNIVERSAL_KEY_LENGTH * 2 {
+                panic!("Universal Key has more than 32 hex digits");
+            }
+            key[digits / 2] |= digit << (4 * (1 - digits % 2));
+            digits += 1;
+            i += 1;
+        }
+        if digits != UNIVERSAL_KEY_LENGTH * 2 {
+            panic!("Universal Key has fewer than 32 hex digits");
+        }
+        Self(key)
+    }
+
+    /// Formats the key as uppercase hex with a `.` between every byte, such as
+    /// `06.0E.2B.34.02.0B.01.01.0E.01.03.01.01.00.00.00`.
+    pub fn to_dotted(&self) -> String {
+        self.0.map(|byte| format!("{byte:02X}")).join(".")
+    }
+
+    /// Formats the key as a SMPTE UL URN, such as
+    /// `urn:smpte:ul:060e2b34.020b0101.0e010301.01000000`.
+    pub fn to_urn(&self) -> String {
+        format!("{URN_PREFIX}{self}")
+    }
+
+    /// Whether the two keys are the same UL, ignoring the version byte.
+    pub fn eq_ignoring_version(&self, other: &UniversalKey) -> bool {
+        self.0
+            .iter()
+            .zip(other.0.iter())
+            .enumerate()
+            .all(|(i, (a, b))| i == UL_VERSION_BYTE || a == b)
+    }
+
+    /// Kind of group this key identifies, if it is a SMPTE group key.
+    pub fn group_type(&self) -> Option<GroupType> {
+        GroupType::from_key(self)
+    }
+
+    /// Whether this is the key of a KLV fill item, of any version.
+    pub fn is_fill(&self) -> bool {
+        self.eq_ignoring_version(&keys::FILL)
+    }
+}
+
+impl fmt::Display for UniversalKey {
+    /// Formats the key as lowercase hex with a `.` between every 4 bytes, the
+    /// same way the SMPTE registries write ULs.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, chunk) in self.0.chunks(4).enumerate() {
+            if i > 0 {
+                write!(f, ".")?;
+            }
+            for byte in chunk {
+                write!(f, "{byte:02x}")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Error returned when a string could not be parsed as a `UniversalKey`.
+#[derive(Clone, Debug, PartialEq, Eq, thiserror::Error)]
+pub enum ParseKeyError {
+    #[error("Expected {expected} hex digits but found {0}", expected = UNIVERSAL_KEY_LENGTH * 2)]
+    InvalidLength(usize),
+    #[error("`{0}` is not a hex digit")]
+    InvalidDigit(char),
+    #[error("URN keys must be four groups of 8 hex digits separated by `.`")]
+    InvalidUrn,
+}
+
+impl FromStr for UniversalKey {
+    type Err = ParseKeyError;
+
+    /// Parses a key written as 32 hex digits.
+    ///
+    /// The registries that publish ULs do not agree on a format, so a leading
+    /// `0x` is ignored, as are any `.`, `-`, or whitespace between digits.
+    /// Keys with a leading `urn:smpte:ul:` must be written the way
+    /// `UniversalKey::to_urn` writes them.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let s = match s.get(..URN_PREFIX.len()) {
+            Some(prefix) if prefix.eq_ignore_ascii_case(URN_PREFIX) => {
+                let s = &s[URN_PREFIX.len()..];
+                if s.split('.').any(|group| group.len() != 8) || s.split('.').count() != 4 {
+                    return Err(ParseKeyError::InvalidUrn);
+                }
+                s
+            }
+            _ => s.strip_prefix("0x").unwrap_or(s),
+        };
+
+        let digits = s
+            .chars()
+            .filter(|c| !matches!(c, '.' | '-') && !c.is_whitespace())
+            .map(|c| c.to_digit(16).ok_or(ParseKeyError::InvalidDigit(c)))
+            .collect::<Result<Vec<_>, _>>()?;
+        if digits.len() != UNIVERSAL_KEY_LENGTH * 2 {
+            return Err(ParseKeyError::InvalidLength(digits.len()));
+        }
+
+        let mut key = [0; UNIVERSAL_KEY_LENGTH];
+        for (byte, pair) in key.iter_mut().zip(digits.chunks(2)) {
+            *byte = (pair[0] << 4 | pair[1]) as u8;
+        }
+        Ok(Self(key))
+    }
+}
+
+/// Keys are written in the same form as `Display` so they can be read by a
+/// person and parsed by `FromStr`.
+#[cfg(feature = "serde")]
+impl serde::Serialize for UniversalKey {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.collect_str(self)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for UniversalKey {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let s = <std::borrow::Cow<'de, str>>::deserialize(deserializer)?;
+        s.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+/// Error returned when a slice is not the length of a `UniversalKey`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, thiserror::Error)]
+#[error("Expected {UNIVERSAL_KEY_LENGTH} bytes but found {0}")]
+pub struct KeyLengthError(pub usize);
+
+impl TryFrom<&[u8]> for UniversalKey {
+    type Error = KeyLengthError;
+
+    fn try_from(bytes: &[u8]) -> Result<Self, Self::Error> {
+        bytes
+            .try_into()
+            .map(Self)
+            .map_err(|_| KeyLengthError(bytes.len()))
+    }
+}
+
+impl From<[u8; UNIVERSAL_KEY_LENGTH]> for UniversalKey {
+    fn from(key: [u8; UNIVERSAL_KEY_LENGTH]) -> Self {
         Self(key)
     }
 }
 
+impl From<&UniversalKey> for UniversalKey {
+    fn from(key: &UniversalKey) -> Self {
+        *key
+    }
+}
+
 impl Deref for UniversalKey {
     type Target = [u8; UNIVERSAL_KEY_LENGTH];
     fn deref(&self) -> &Self::Target {
@@ -28,162 +260,2585 @@ impl Deref for UniversalKey {
     }
 }
 
+/// Creates a `UniversalKey` from a string of 32 hex digits at compile time.
+///
+/// ```
+/// use klv::prelude::*;
+///
+/// const ST0601: UniversalKey = klv::key!("06 0E 2B 34 02 0B 01 01 0E 01 03 01 01 00 00 00");
+/// assert_eq!(ST0601, keys::ST0601);
+/// ```
+///
+/// Keys that are not 32 hex digits fail to compile. See
+/// `UniversalKey::from_hex`.
+#[macro_export]
+macro_rules! key {
+    ($hex:literal) => {
+        const { $crate::universal_set::UniversalKey::from_hex($hex) }
+    };
+}
+
+/// Where each part of a Universal Set lies in the buffer it was found in.
+///
+/// The length is checked when the extent is read, so the end of the set
+/// always fits in an `i64` and `SetExtent::end` can't overflow.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, getset::CopyGetters)]
+#[getset(get_copy = "pub")]
+pub struct SetExtent {
+    /// Offset of the first byte of the Universal Key.
+    key_offset: u64,
+
+    /// Offset of the first byte of the set's value.
+    value_offset: u64,
+
+    /// Number of bytes that make up the set's value.
+    value_length: u64,
+}
+
+impl SetExtent {
+    /// Offset of the byte after the last byte of the set.
+    pub fn end(&self) -> u64 {
+        self.value_offset + self.value_length
+    }
+
+    /// Number of bytes that make up the entire set, including the key and
+    /// length.
+    pub fn total_length(&self) -> u64 {
+        self.end() - self.key_offset
+    }
+}
+
 /// Set of data that can be found by searching for the Universal Key in the
 /// file.
 #[derive(Debug, getset::Getters)]
 #[getset(get = "pub")]
-pub struct UniversalSet<'a, T>
+pub struct UniversalSet<T>
 where
     T: Read + Seek,
 {
     /// Key used to find the beginning of the `LocalSet`.
-    key: &'a UniversalKey,
+    key: UniversalKey,
 
     /// Locations in the file for each tag that can be parsed.
     data: LocalSet<T>,
+
+    /// Where the set lies in the buffer.
+    extent: SetExtent,
+
+    /// Buffer the set was read from.
+    #[getset(skip)]
+    buf: Rc<RefCell<T>>,
+}
+
+impl<T> fmt::Display for UniversalSet<T>
+where
+    T: Read + Seek,
+{
+    /// Formats the set as `<key> at offset <offset>: <items> items,
+    /// <warnings> warnings`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} at offset {}: {}",
+            self.key, self.extent.key_offset, self.data
+        )
+    }
 }
 
-impl<'a, T> UniversalSet<'a, T>
+impl<T> UniversalSet<T>
 where
     T: Read + Seek,
 {
     pub fn new(
-        key: &'a UniversalKey,
+        key: impl Into<UniversalKey>,
+        buf: Rc<RefCell<T>>,
+        starting_location: u64,
+    ) -> Result<Self, encoding::Error> {
+        Self::new_with_options(key, buf, starting_location, &ParseOptions::default())
+    }
+
+    pub fn new_with_options(
+        key: impl Into<UniversalKey>,
+        buf: Rc<RefCell<T>>,
+        starting_location: u64,
+        options: &ParseOptions,
+    ) -> Result<Self, encoding::Error> {
+        let key = key.into();
+        #[cfg(feature = "tracing")]
+        let _span =
+            tracing::debug_span!("universal_set", %key, offset = starting_location).entered();
+
+        warn_on_err!(
+            Self::read_set(key, buf, starting_location, options),
+            starting_location
+        )
+    }
+
+    /// Reads the set keyed by `key` whose key starts at `starting_location`.
+    fn read_set(
+        key: UniversalKey,
         buf: Rc<RefCell<T>>,
         starting_location: u64,
+        options: &ParseOptions,
     ) -> Result<Self, encoding::Error> {
+        let encoding = Self::item_encoding(&key)?;
+        let extent = Self::read_extent(&mut *buf.borrow_mut(), starting_location)?;
+        if !key.eq_ignoring_version(&keys::ST0601) {
+            let mut data =
+                LocalSet::read_with_encoding(starting_location, buf.clone(), &encoding, options)?;
+            data.push_warnings_front(Self::length_warning(key, &extent));
+            return Ok(Self {
+                key,
+                data,
+                extent,
+                buf,
+            });
+        }
+
+        // The checksum item is needed to verify the packet even when the
+        // filter drops it, so it is only removed afterwards.
+        let checksum = ItemKey::Tag(St0601Tag::Checksum.into());
+        let unwanted_checksum = options
+            .tag_filter
+            .as_ref()
+            .is_some_and(|filter| !filter.wants(&checksum));
+        let mut data = if unwanted_checksum {
+            let filter = options.tag_filter.clone().expect("Filter is set");
+            let options = ParseOptions {
+                tag_filter: Some(TagFilter::predicate(move |key| {
+                    *key == checksum || filter.wants(key)
+                })),
+                ..options.clone()
+            };
+            LocalSet::read_with_encoding(starting_location, buf.clone(), &encoding, &options)?
+        } else {
+            LocalSet::read_with_encoding(starting_location, buf.clone(), &encoding, options)?
+        };
+        data.verify_checksum(starting_location, &buf, options.verify_checksum)?;
+        if unwanted_checksum {
+            data.remove_all(&checksum);
+        }
+        data.push_warnings_front(Self::length_warning(key, &extent));
+
         Ok(Self {
             key,
-            data: LocalSet::read(starting_location, buf)?,
+            data,
+            extent,
+            buf,
+        })
+    }
+
+    /// Hashes the raw bytes of the set's payload, everything after its
+    /// length, a chunk at a time.
+    ///
+    /// Hashes are only meant to be compared within one run of a program, as
+    /// the hashing algorithm may change between Rust releases.
+    ///
+    /// # Side Effects
+    ///
+    /// The position in the buffer is left where it was.
+    pub fn payload_hash(&self) -> Result<u64, io::Error> {
+        hash_range(
+            &mut *self.buf.borrow_mut(),
+            self.extent.value_offset,
+            self.extent.value_length,
+        )
+    }
+
+    /// Number of bytes in the set's payload, everything after its length.
+    ///
+    /// A set with an empty payload, such as those sent to keep a stream
+    /// alive, has no items.
+    pub fn payload_len(&self) -> u64 {
+        self.extent.value_length
+    }
+
+    /// Problems found while parsing the set that did not stop the parse,
+    /// each with the offset of the value it is about.
+    ///
+    /// See `LocalSet::warnings`. A length of the set itself written in more
+    /// bytes than it needs is reported against its Universal Key.
+    pub fn warnings(&self) -> &[Violation] {
+        self.data.warnings()
+    }
+
+    /// A warning about the length of the set keyed by `key` lying at
+    /// `extent` when it is written in more bytes than it needs.
+    fn length_warning(key: UniversalKey, extent: &SetExtent) -> Option<Violation> {
+        let width = extent.value_offset - extent.key_offset - UNIVERSAL_KEY_LENGTH as u64;
+        let minimal = encode_ber(extent.value_length.into()).len() as u64;
+        (width > minimal).then(|| {
+            Violation::new(
+                Severity::Warning,
+                ItemKey::Ul(key),
+                Some(extent.value_offset),
+                ViolationKind::NonMinimalEncoding { width, minimal },
+            )
         })
     }
 
+    /// How the items of sets keyed by `key` are encoded.
+    pub(crate) fn item_encoding(key: &UniversalKey) -> Result<ItemEncoding, encoding::Error> {
+        // Keys that aren't SMPTE group keys are assumed to be MISB local sets.
+        match key.group_type() {
+            Some(group_type) => {
+                group_type
+                    .item_encoding()
+                    .ok_or_else(|| encoding::Error::Malformed {
+                        what: "a set",
+                        value: format!("{group_type:?}"),
+                    })
+            }
+            None => Ok(ItemEncoding::default()),
+        }
+    }
+
     pub fn read_all(
-        key: &'a UniversalKey,
+        key: impl Into<UniversalKey>,
         buf: Rc<RefCell<T>>,
-    ) -> Result<Vec<UniversalSet<'a, T>>, encoding::Error> {
-        let locations = Self::start_locations(key, &mut *buf.borrow_mut())?;
-        locations
-            .iter()
-            .map(|start| UniversalSet::new(key, buf.clone(), *start))
-            .collect::<Result<Vec<UniversalSet<'a, T>>, encoding::Error>>()
+    ) -> Result<Vec<UniversalSet<T>>, encoding::Error> {
+        Self::read_all_with_options(key, buf, &ParseOptions::default())
+    }
+
+    /// Reads every set keyed by `key` in the buffer.
+    ///
+    /// ST 0601 packets rejected by `options.verify_checksum` are skipped
+    /// rather than failing the whole read. Use
+    /// `UniversalSet::read_all_partial` to keep the sets read before a set
+    /// fails or the resource ceilings of `options` are reached.
+    pub fn read_all_with_options(
+        key: impl Into<UniversalKey>,
+        buf: Rc<RefCell<T>>,
+        options: &ParseOptions,
+    ) -> Result<Vec<UniversalSet<T>>, encoding::Error> {
+        match Self::read_all_partial(key, buf, options) {
+            (sets, None) => Ok(sets),
+            (_, Some(err)) => Err(err),
+        }
+    }
+
+    /// Reads every set keyed by `key` the same way as
+    /// `UniversalSet::read_all_with_options`, stopping at the first error
+    /// but keeping the sets read before it.
+    ///
+    /// # Returns
+    ///
+    /// The sets read, in stream order, along with the error that stopped the
+    /// read:
+    ///
+    /// - `None` - Every set was read.
+    /// - `Some(encoding::Error::LimitExceeded)` - Reading another set would
+    ///   break `options.max_packets` or `options.max_total_value_bytes`, or a
+    ///   set has more than `options.max_items_per_set` items.
+    /// - `Some(encoding::Error)` - The buffer could not be searched or a set
+    ///   could not be read.
+    pub fn read_all_partial(
+        key: impl Into<UniversalKey>,
+        buf: Rc<RefCell<T>>,
+        options: &ParseOptions,
+    ) -> (Vec<UniversalSet<T>>, Option<encoding::Error>) {
+        let key = key.into();
+        #[cfg(feature = "tracing")]
+        let _span = tracing::debug_span!("read_all", %key).entered();
+
+        // The declared lengths are checked against the limit while scanning
+        // so a huge length is reported before it is jumped over.
+        let mut locations = Vec::new();
+        let scanned = KeyLocations::new(key, &mut *buf.borrow_mut(), None)
+            .with_value_limit(options.max_total_value_bytes)
+            .try_for_each(|start| start.map(|start| locations.push(start)));
+        let scan_error = warn_on_err!(
+            scanned,
+            buf.borrow_mut().stream_position().unwrap_or_default()
+        )
+        .err();
+        if scan_error.is_some() {
+            // The scan fails jumping over the value of the last set found,
+            // which is not read.
+            locations.pop();
+        }
+        let mut budget = ReadBudget::new(options);
+        let mut sets = Vec::new();
+        for start in locations {
+            match Self::read_within(key, &buf, start, options, &mut budget) {
+                Ok(set) => sets.push(set),
+                Err(encoding::Error::ChecksumMismatch { .. }) => {}
+                Err(err) => return (sets, Some(err)),
+            }
+        }
+        (sets, scan_error)
+    }
+
+    /// Reads the set whose key starts at `start` once it has been counted
+    /// against `budget`.
+    fn read_within(
+        key: UniversalKey,
+        buf: &Rc<RefCell<T>>,
+        start: u64,
+        options: &ParseOptions,
+        budget: &mut ReadBudget,
+    ) -> Result<Self, encoding::Error> {
+        // The extent is only needed to check the payload against the limit.
+        let value_length = match options.max_total_value_bytes {
+            Some(_) => Self::read_extent(&mut *buf.borrow_mut(), start)?.value_length,
+            None => 0,
+        };
+        budget.spend(value_length)?;
+        Self::new_with_options(key, buf.clone(), start, options)
+    }
+
+    /// Reads every set keyed by `key` the same way as
+    /// `UniversalSet::read_all_with_options`, reporting progress to
+    /// `progress`.
+    ///
+    /// The buffer is only borrowed while it is searched, so the progress
+    /// callback may use it.
+    ///
+    /// # Returns
+    ///
+    /// - `Ok(Vec<UniversalSet>)` - Every set was read.
+    /// - `Err(encoding::Error::Cancelled)` - The scan was cancelled. The
+    ///   offsets of the sets found before then are kept in the error.
+    /// - `Err(encoding::Error)` - The buffer could not be searched or a set
+    ///   could not be read.
+    pub fn read_all_with_progress(
+        key: impl Into<UniversalKey>,
+        buf: Rc<RefCell<T>>,
+        options: &ParseOptions,
+        progress: Option<&mut ProgressHandle>,
+    ) -> Result<Vec<UniversalSet<T>>, encoding::Error> {
+        let key = key.into();
+        let mut budget = ReadBudget::new(options);
+        let mut sets = Vec::new();
+        visit_locations(&key, &buf, progress, |start, extent| {
+            extent?;
+            match Self::read_within(key, &buf, start, options, &mut budget) {
+                Ok(set) => sets.push(set),
+                Err(encoding::Error::ChecksumMismatch { .. }) => {}
+                Err(err) => return Err(err),
+            }
+            Ok(())
+        })?;
+        Ok(sets)
+    }
+
+    /// Reads every set keyed by any of `keys` in the buffer with the default
+    /// options, finding them in a single pass with `UniversalSet::scan_keys`.
+    ///
+    /// See `UniversalSet::read_all_multi_with_options`.
+    pub fn read_all_multi(
+        keys: &[UniversalKey],
+        buf: Rc<RefCell<T>>,
+    ) -> Result<Vec<Vec<UniversalSet<T>>>, encoding::Error> {
+        Self::read_all_multi_with_options(keys, buf, &ParseOptions::default())
+    }
+
+    /// Reads every set keyed by any of `keys` in the buffer with `options`,
+    /// finding them in a single pass with `UniversalSet::scan_keys`.
+    ///
+    /// Sets are read the same way as `UniversalSet::read_all_with_options`.
+    /// The resource ceilings of `options` count the sets of every key
+    /// together.
+    ///
+    /// # Returns
+    ///
+    /// - `Ok(Vec<Vec<UniversalSet>>)` - The sets keyed by each of `keys`, in
+    ///   the same order as `keys`.
+    /// - `Err(encoding::Error::LimitExceeded)` - Reading another set would
+    ///   break one of the resource ceilings of `options`.
+    /// - `Err(encoding::Error)` - The buffer could not be searched or a set
+    ///   could not be read.
+    pub fn read_all_multi_with_options(
+        keys: &[UniversalKey],
+        buf: Rc<RefCell<T>>,
+        options: &ParseOptions,
+    ) -> Result<Vec<Vec<UniversalSet<T>>>, encoding::Error> {
+        let locations = Self::scan_keys(keys, &mut *buf.borrow_mut())?;
+        let mut budget = ReadBudget::new(options);
+        let mut sets = keys.iter().map(|_| Vec::new()).collect::<Vec<_>>();
+        for (index, start) in locations {
+            match Self::read_within(keys[index], &buf, start, options, &mut budget) {
+                Ok(set) => sets[index].push(set),
+                Err(encoding::Error::ChecksumMismatch { .. }) => {}
+                Err(err) => return Err(err),
+            }
+        }
+        Ok(sets)
+    }
+
+    /// Reads every set keyed by `key` in the buffer, skipping each set whose
+    /// payload is the same length and has the same `UniversalSet::payload_hash`
+    /// as the set before it.
+    ///
+    /// Recorders that repeat the last packet while nothing changes produce
+    /// long runs of identical sets, which are read as one.
+    pub fn read_all_deduped(
+        key: impl Into<UniversalKey>,
+        buf: Rc<RefCell<T>>,
+    ) -> Result<Vec<UniversalSet<T>>, encoding::Error> {
+        let mut previous = None;
+        let mut sets = Vec::new();
+        for set in Self::read_all(key, buf)? {
+            let fingerprint = Some((set.extent.value_length, set.payload_hash()?));
+            if fingerprint != previous {
+                sets.push(set);
+            }
+            previous = fingerprint;
+        }
+        Ok(sets)
+    }
+
+    /// Reads every set keyed by `key` in the buffer the same way as
+    /// `UniversalSet::read_all_with_options`, keeping a summary of each set
+    /// instead of the set itself.
+    ///
+    /// Sets that fail to parse are recorded in the report rather than failing
+    /// the scan. A set whose length can't be read can't be jumped over, so
+    /// the search goes on from the byte after its key.
+    ///
+    /// # Returns
+    ///
+    /// - `Ok(ScanReport)` - Every location of the key was visited.
+    /// - `Err(encoding::Error)` - The buffer could not be searched for the
+    ///   key.
+    pub fn scan_report(
+        key: impl Into<UniversalKey>,
+        buf: Rc<RefCell<T>>,
+        options: &ParseOptions,
+    ) -> Result<ScanReport, encoding::Error> {
+        let key = key.into();
+        let mut report = ScanReport::new(key);
+        loop {
+            let mut locations = Vec::new();
+            let scanned = KeyLocations::new(key, &mut *buf.borrow_mut(), None)
+                .try_for_each(|start| start.map(|start| locations.push(start)));
+            for &start in &locations {
+                Self::summarize(key, &buf, start, options, &mut report);
+            }
+            // The scan stops at the last set found when its length can't be
+            // read, which its summary records.
+            match (scanned, locations.last()) {
+                (Ok(()), _) => return Ok(report),
+                (Err(_), Some(&start)) => {
+                    buf.borrow_mut()
+                        .seek(SeekFrom::Start(start + UNIVERSAL_KEY_LENGTH as u64))?;
+                }
+                (Err(err), None) => return Err(err),
+            }
+        }
+    }
+
+    /// Builds the same report as `UniversalSet::scan_report`, reporting
+    /// progress to `progress`.
+    ///
+    /// The buffer is only borrowed while it is searched, so the progress
+    /// callback may use it.
+    ///
+    /// # Returns
+    ///
+    /// - `Ok(ScanReport)` - Every location of the key was visited.
+    /// - `Err(encoding::Error::Cancelled)` - The scan was cancelled. The
+    ///   offsets of the sets summarized before then are kept in the error.
+    /// - `Err(encoding::Error)` - The buffer could not be searched for the
+    ///   key.
+    pub fn scan_report_with_progress(
+        key: impl Into<UniversalKey>,
+        buf: Rc<RefCell<T>>,
+        options: &ParseOptions,
+        progress: Option<&mut ProgressHandle>,
+    ) -> Result<ScanReport, encoding::Error> {
+        let key = key.into();
+        let mut report = ScanReport::new(key);
+        visit_locations(&key, &buf, progress, |start, _| {
+            Self::summarize(key, &buf, start, options, &mut report);
+            Ok(())
+        })?;
+        Ok(report)
+    }
+
+    /// Adds a summary of the set whose Universal Key starts at `start` to
+    /// `report`, along with any warnings about it.
+    fn summarize(
+        key: UniversalKey,
+        buf: &Rc<RefCell<T>>,
+        start: u64,
+        options: &ParseOptions,
+        report: &mut ScanReport,
+    ) {
+        let length = Self::read_extent(&mut *buf.borrow_mut(), start)
+            .ok()
+            .map(|extent| extent.total_length());
+        let summary = match Self::new_with_options(key, buf.clone(), start, options) {
+            Ok(set) => {
+                let warnings = set.data.warnings();
+                let checksum_mismatch = warnings.iter().any(|warning| {
+                    matches!(warning.kind(), ViolationKind::ChecksumMismatch { .. })
+                });
+                for warning in warnings {
+                    report.push_warning(ScanWarning::from_violation(start, warning));
+                }
+                SetSummary::new(
+                    start,
+                    length,
+                    set.data.len(),
+                    warnings.len(),
+                    if checksum_mismatch {
+                        SetStatus::ChecksumMismatch
+                    } else {
+                        SetStatus::Parsed
+                    },
+                )
+            }
+            Err(error) => {
+                let status = match error {
+                    encoding::Error::ChecksumMismatch { .. } => SetStatus::ChecksumMismatch,
+                    _ => SetStatus::Failed,
+                };
+                report.push_warning(ScanWarning::new(start, error.to_string()));
+                SetSummary::new(start, length, 0, 1, status)
+            }
+        };
+        report.push_set(summary);
+    }
+
+    /// Reads the Precision Time Stamp, tag 2, of every set keyed by `key` in
+    /// the buffer, starting from its first byte wherever the buffer is.
+    ///
+    /// Only the items before the timestamp in each set are read, and their
+    /// values are skipped over rather than read.
+    ///
+    /// # Returns
+    ///
+    /// - `Ok(TimeIndex)` - Every set was visited. Sets without a timestamp, or
+    ///   whose items could not be read up to it, are listed by offset in
+    ///   `TimeIndex::missing`.
+    /// - `Err(encoding::Error)` - The buffer could not be searched for the
+    ///   key.
+    pub fn time_index(
+        key: impl Into<UniversalKey>,
+        buf: Rc<RefCell<T>>,
+    ) -> Result<TimeIndex, encoding::Error> {
+        let key = key.into();
+        let encoding = Self::item_encoding(&key)?;
+        let locations = {
+            let mut buf = buf.borrow_mut();
+            buf.seek(SeekFrom::Start(0))?;
+            Self::start_locations(&key, &mut *buf)?
+        };
+
+        let mut entries = Vec::new();
+        let mut missing = Vec::new();
+        for start in locations {
+            match Self::read_time_stamp(start, &buf, &encoding) {
+                Some(time) => entries.push((time, start)),
+                None => missing.push(start),
+            }
+        }
+        Ok(TimeIndex::new(entries, missing))
+    }
+
+    /// Reads the Precision Time Stamp of the set whose Universal Key starts at
+    /// `start`, stopping at the first item that cannot be read.
+    pub(crate) fn read_time_stamp(
+        start: u64,
+        buf: &Rc<RefCell<T>>,
+        encoding: &ItemEncoding,
+    ) -> Option<SystemTime> {
+        for klv in LocalSet::stream_items(start, buf.clone(), encoding).ok()? {
+            let klv = klv.ok()?;
+            if klv.key() == ItemKey::Tag(PRECISION_TIME_STAMP_TAG) {
+                return precision_time_stamp(&klv.read_value().ok()?);
+            }
+        }
+        None
+    }
+
+    /// Reads every set keyed by `key` whose Precision Time Stamp lies in
+    /// `range`, in the order they are in the buffer.
+    ///
+    /// Sets without a timestamp are never included. See
+    /// `UniversalSet::time_index`.
+    pub fn sets_in_time_range(
+        key: impl Into<UniversalKey>,
+        buf: Rc<RefCell<T>>,
+        range: Range<SystemTime>,
+    ) -> Result<Vec<UniversalSet<T>>, encoding::Error> {
+        let key = key.into();
+        Self::time_index(key, buf.clone())?
+            .offsets_in(&range)
+            .map(|start| UniversalSet::new(key, buf.clone(), start))
+            .collect()
+    }
+
+    /// Reads where the length and value of the set whose Universal Key starts
+    /// at `key_offset` lie in the buffer.
+    ///
+    /// Only the length is read and validated. The value is not read, so a set
+    /// whose value extends past the end of the buffer is not detected here.
+    ///
+    /// # Returns
+    ///
+    /// - `Ok(SetExtent)` - The length was successfully read.
+    /// - `Err(encoding::Error::ValueOutOfRange)` - The set would end past
+    ///   `i64::MAX`, which no real buffer does, so the length is corrupt.
+    /// - `Err(encoding::Error)` - The length is malformed, too large, or the
+    ///   buffer ended part way through it.
+    ///
+    /// # Side Effects
+    ///
+    /// Moves the current position in the buffer to the first byte of the
+    /// value.
+    pub fn read_extent(buf: &mut T, key_offset: u64) -> Result<SetExtent, encoding::Error> {
+        let length_offset = key_offset + UNIVERSAL_KEY_LENGTH as u64;
+        buf.seek(SeekFrom::Start(length_offset))?;
+        let mut length_bytes = Vec::new();
+        buf.take(MAX_BER_LENGTH).read_to_end(&mut length_bytes)?;
+
+        let (value_length, length_size) = decode_ber(&length_bytes)?;
+        let value_offset = length_offset + length_size as u64;
+        buf.seek(SeekFrom::Start(value_offset))?;
+
+        let value_length = seekable_length(value_length)?;
+        checked_end(value_offset, value_length)?;
+        Ok(SetExtent {
+            key_offset,
+            value_offset,
+            value_length,
+        })
     }
 
     /// Return the offsets to the first byte of the Universal Key everywhere the
     /// Universal Key was found in the buffer.
-    pub fn start_locations(
-        key: &'a UniversalKey,
+    ///
+    /// Once a key is found the value of its set is jumped over and the search
+    /// starts again at the byte after it, so keys inside values are not
+    /// reported. A key directly after a set, with no bytes between them, is
+    /// always found, including after a set with an empty value or one whose
+    /// value ends with the first bytes of the key.
+    pub fn start_locations(key: &UniversalKey, buf: &mut T) -> Result<Vec<u64>, encoding::Error> {
+        Self::scan(key, buf, None, None)
+    }
+
+    /// Finds the same offsets as `UniversalSet::start_locations`, reporting
+    /// progress to `progress`.
+    ///
+    /// # Returns
+    ///
+    /// - `Ok(Vec<u64>)` - Offsets of every Universal Key found.
+    /// - `Err(encoding::Error::Cancelled)` - The scan was cancelled. The
+    ///   offsets found before then are kept in the error.
+    /// - `Err(encoding::Error)` - The length of a set could not be read.
+    pub fn start_locations_with_progress(
+        key: &UniversalKey,
         buf: &mut T,
+        progress: Option<&mut ProgressHandle>,
     ) -> Result<Vec<u64>, encoding::Error> {
-        let mut locations = Vec::new();
+        let mut offsets = Vec::new();
+        visit_locations(key, &RefCell::new(buf), progress, |start, extent| {
+            extent?;
+            offsets.push(start);
+            Ok(())
+        })?;
+        Ok(offsets)
+    }
 
-        // The initial contents of the search buffer should be the start of the
-        // file.
-        let mut buffer_contents = [0; UNIVERSAL_KEY_LENGTH];
-        if buf.read_exact(&mut buffer_contents).is_ok() {
-            let mut search_buffer =
-                ConstGenericRingBuffer::<u8, UNIVERSAL_KEY_LENGTH>::from(buffer_contents);
-
-            loop {
-                if itertools::equal(&search_buffer, &key.0) {
-                    // Matches will only happen after the last byte of the
-                    // Universal Key has been read so we always need to subtract
-                    // the length of the key from the current position to get
-                    // the starting position.
-                    let current_pos = buf.stream_position().expect(
-                        "Failed to current current buffer position when parsing Universal Set",
-                    );
-                    let start_pos = match current_pos.checked_sub(UNIVERSAL_KEY_LENGTH as u64) {
-                        Some(pos) => pos,
-                        None => panic!(
-                            "Starting position of Key with length [{UNIVERSAL_KEY_LENGTH}] ending at index [{current_pos}] results in a negative offset in the buffer"
-                        ),
-                    };
-                    locations.push(start_pos);
-
-                    // Get how far to jump at the very least to get to the next
-                    // Universal Key.
-                    let value_length = Klv::read_length(buf)?;
-                    buf.seek_relative(
-                        value_length
-                            .try_into()
-                            .expect("Failed to convert u64 to i64 trying to jump over value"),
-                    )
-                    .expect("Failed to jump over value");
-                }
+    /// Return the offsets to the first byte of the last `n` sets keyed by
+    /// `key` in the buffer, without reading the buffer from the start.
+    ///
+    /// The buffer is searched backwards from its end in chunks. Because
+    /// searching backwards can't jump over the values of the sets it finds, a
+    /// match is only kept when a whole set can be parsed at it which ends
+    /// before the next set kept, or the end of the buffer for the last one.
+    /// Matches inside the values of sets are passed over that way, as is a
+    /// last set cut short by the end of a recording.
+    ///
+    /// # Returns
+    ///
+    /// - `Ok(Vec<u64>)` - Offsets of up to `n` sets in buffer order. Fewer
+    ///   are returned when the buffer doesn't hold `n`.
+    /// - `Err(encoding::Error)` - There was an issue reading from the buffer.
+    ///
+    /// # Side Effects
+    ///
+    /// Moves the current position in the buffer.
+    pub fn last_locations(
+        key: &UniversalKey,
+        buf: &mut T,
+        n: usize,
+    ) -> Result<Vec<u64>, encoding::Error> {
+        Self::last_locations_in_chunks(key, buf, n, REVERSE_CHUNK_LENGTH)
+    }
+
+    fn last_locations_in_chunks(
+        key: &UniversalKey,
+        buf: &mut T,
+        n: usize,
+        chunk_length: u64,
+    ) -> Result<Vec<u64>, encoding::Error> {
+        let encoding = Self::item_encoding(key)?;
+        let end = buf.seek(SeekFrom::End(0))?;
+        let mut found = Vec::new();
+        let mut scanner = KeyScanner::new(*key);
+        // Start of the chunk searched last. Keys starting at or after it have
+        // already been searched for.
+        let mut searched_from = end;
+        while searched_from > 0 && found.len() < n {
+            let chunk_start = searched_from.saturating_sub(chunk_length);
+            // Include the start of the chunk after so keys straddling the two
+            // are found.
+            let read_end = end.min(searched_from + UNIVERSAL_KEY_LENGTH as u64 - 1);
+            let mut chunk = Vec::new();
+            buf.seek(SeekFrom::Start(chunk_start))?;
+            buf.take(read_end - chunk_start).read_to_end(&mut chunk)?;
 
-                match buf.read_u8() {
-                    Ok(val) => {
-                        search_buffer.enqueue(val);
+            scanner.reset();
+            let offsets = scanner.scan_chunk(&chunk, chunk_start);
+            for offset in offsets.into_iter().rev() {
+                if offset >= searched_from {
+                    continue;
+                }
+                let limit = found.last().copied().unwrap_or(end);
+                if Self::is_set_at(buf, offset, limit, &encoding)? {
+                    found.push(offset);
+                    if found.len() == n {
+                        break;
                     }
-                    Err(_) => break,
                 }
             }
+            searched_from = chunk_start;
+        }
+        found.reverse();
+        Ok(found)
+    }
+
+    /// Whether a whole set whose items are encoded as described by `encoding`
+    /// starts at `offset` and ends at or before `limit`.
+    fn is_set_at(
+        buf: &mut T,
+        offset: u64,
+        limit: u64,
+        encoding: &ItemEncoding,
+    ) -> Result<bool, io::Error> {
+        let extent = match Self::read_extent(buf, offset) {
+            Ok(extent) if extent.end() <= limit => extent,
+            Ok(_) => return Ok(false),
+            Err(encoding::Error::Other(e)) => return Err(e),
+            Err(_) => return Ok(false),
         };
+        let mut payload = Vec::new();
+        buf.take(extent.value_length()).read_to_end(&mut payload)?;
+        let length = payload.len() as u64;
+        let payload = Rc::new(RefCell::new(io::Cursor::new(payload.as_slice())));
+        Ok(LocalSet::items_in(payload, 0..length, encoding).all(|klv| klv.is_ok()))
+    }
 
-        Ok(locations)
+    /// Return the offset of the first byte of the first Universal Key found at
+    /// or after `start`.
+    ///
+    /// # Returns
+    ///
+    /// - `Ok(Some(u64))` - Offset of the first matching Universal Key.
+    /// - `Ok(None)` - The key does not appear between `start` and the end of
+    ///   the buffer.
+    /// - `Err(encoding::Error)` - There was an issue reading from the buffer.
+    ///
+    /// # Side Effects
+    ///
+    /// Moves the current position in the buffer to the byte after the last
+    /// byte of the matched key, or to the end of the buffer if no match was
+    /// found.
+    pub fn find_next(
+        key: &UniversalKey,
+        buf: &mut T,
+        start: u64,
+    ) -> Result<Option<u64>, encoding::Error> {
+        buf.seek(SeekFrom::Start(start))?;
+        Ok(Self::scan(key, buf, None, Some(1))?.first().copied())
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use std::io::Cursor;
+    /// Return the offsets to the first byte of the Universal Key everywhere the
+    /// Universal Key was found inside of `range`.
+    ///
+    /// Only keys whose 16 bytes lie entirely inside of `range` are reported. A
+    /// key that starts inside of the range but extends past its end is
+    /// ignored.
+    ///
+    /// # Side Effects
+    ///
+    /// Moves the current position in the buffer to somewhere at or after the
+    /// end of the range.
+    pub fn start_locations_in(
+        key: &UniversalKey,
+        buf: &mut T,
+        range: Range<u64>,
+    ) -> Result<Vec<u64>, encoding::Error> {
+        if range.start >= range.end {
+            return Ok(Vec::new());
+        }
+        buf.seek(SeekFrom::Start(range.start))?;
+        Self::scan(key, buf, Some(range.end), None)
+    }
 
-    use super::*;
-    use itertools::{Itertools, chain};
-    use test_case::test_case;
+    /// Return the offsets to the first byte of the Universal Key everywhere the
+    /// Universal Key is found in the buffer, one at a time as they are found.
+    ///
+    /// Produces the same offsets as `UniversalSet::start_locations` but only
+    /// reads as far into the buffer as the last offset taken from it.
+    ///
+    /// # Side Effects
+    ///
+    /// Moves the current position in the buffer to the byte after the last
+    /// byte of the most recently found key, or to the end of the buffer once
+    /// every key has been found.
+    pub fn location_iter<'b>(key: &UniversalKey, buf: &'b mut T) -> KeyLocations<'b, T> {
+        KeyLocations::new(*key, buf, None)
+    }
 
-    const TEST_UNIVERSAL_KEY: [u8; UNIVERSAL_KEY_LENGTH] = [
-        0x06, 0x0E, 0x2B, 0x34, 0x02, 0x0B, 0x01, 0x01, 0x0E, 0x01, 0x03, 0x01, 0x01, 0x00, 0x00,
-        0x00,
-    ];
+    /// Return the offsets to the first byte of every one of `keys` found in
+    /// the buffer, reading the buffer only once.
+    ///
+    /// The value of every set found is jumped over, so a key inside the value
+    /// of a set keyed by another of `keys` is not reported. Otherwise the
+    /// offsets found for each key are the same as
+    /// `UniversalSet::start_locations` finds for it.
+    ///
+    /// # Returns
+    ///
+    /// - `Ok(Vec<(usize, u64)>)` - Index in `keys` of each key found and the
+    ///   offset of its first byte, in buffer order.
+    /// - `Err(encoding::Error)` - The length of a set could not be read.
+    ///
+    /// # Side Effects
+    ///
+    /// Moves the current position in the buffer to the end of the buffer.
+    pub fn scan_keys(
+        keys: &[UniversalKey],
+        buf: &mut T,
+    ) -> Result<Vec<(usize, u64)>, encoding::Error> {
+        let mut locations = KeyLocations::for_keys(keys.to_vec(), buf, None);
+        std::iter::from_fn(|| locations.next_match()).collect()
+    }
 
-    fn multiple_uset_buf() -> Vec<u8> {
-        chain!(
-            // Byte before the key starts
-            [0x06],
-            TEST_UNIVERSAL_KEY, // Starts at index 1
-            [0x04],
-            [0x01, 0x02, 0x03, 0x04],
-            // Bytes that should not be included in either Universal Set
-            [0xFF, 0xFF, 0x06],
-            TEST_UNIVERSAL_KEY,
-            [0x07],
-            [0x01, 0x01, 0x02],
-            [0x02, 0x02, 0x04, 0x08]
-        )
-        .collect_vec()
+    /// Finds every SMPTE group key in the buffer from the current position,
+    /// for when what a buffer holds is not known ahead of time.
+    ///
+    /// A key is only reported when its designators decode to a
+    /// `GroupType` and it is followed by a BER length whose value ends inside
+    /// the buffer. The value of every reported set is jumped over, so keys
+    /// inside of values are not reported.
+    ///
+    /// # Returns
+    ///
+    /// - `Ok(Vec<(UniversalKey, usize, Vec<u64>)>)` - Each key found, in the
+    ///   order they were first found, with how many times it was found and
+    ///   the offsets of its first `DISCOVERED_OFFSETS` occurrences.
+    /// - `Err(encoding::Error)` - There was an issue reading from the buffer.
+    ///
+    /// # Side Effects
+    ///
+    /// Moves the current position in the buffer to the end of the buffer.
+    pub fn discover_keys(
+        buf: &mut T,
+    ) -> Result<Vec<(UniversalKey, usize, Vec<u64>)>, encoding::Error> {
+        let start = buf.stream_position()?;
+        let buffer_end = buf.seek(SeekFrom::End(0))?;
+        buf.seek(SeekFrom::Start(start))?;
+        let mut found: Vec<(UniversalKey, usize, Vec<u64>)> = Vec::new();
+
+        // Offset of the byte after the last byte in the search buffer.
+        let mut window_end = start;
+        let mut search_buffer = ConstGenericRingBuffer::<u8, UNIVERSAL_KEY_LENGTH>::new();
+        loop {
+            if search_buffer.is_full() && search_buffer[0] == UL_FIRST_BYTE {
+                let key_offset = window_end - UNIVERSAL_KEY_LENGTH as u64;
+                let key = UniversalKey::new(
+                    search_buffer
+                        .to_vec()
+                        .try_into()
+                        .expect("Search buffer is full"),
+                );
+                // Lengths whose end overflows fail `read_extent` the same as
+                // unreadable ones.
+                let extent = key
+                    .group_type()
+                    .and_then(|_| Self::read_extent(buf, key_offset).ok())
+                    .filter(|extent| extent.end() <= buffer_end);
+                match extent {
+                    Some(extent) => {
+                        match found.iter_mut().find(|(found, ..)| *found == key) {
+                            Some((_, count, offsets)) => {
+                                *count += 1;
+                                if offsets.len() < DISCOVERED_OFFSETS {
+                                    offsets.push(key_offset);
+                                }
+                            }
+                            None => found.push((key, 1, vec![key_offset])),
+                        }
+                        window_end = buf.seek(SeekFrom::Start(extent.end()))?;
+                        search_buffer.clear();
+                    }
+                    None => {
+                        buf.seek(SeekFrom::Start(window_end))?;
+                    }
+                }
+            }
+
+            match buf.read_u8() {
+                Ok(byte) => {
+                    search_buffer.enqueue(byte);
+                    window_end += 1;
+                }
+                Err(err) if err.kind() == io::ErrorKind::UnexpectedEof => break,
+                Err(err) => return Err(err.into()),
+            }
+        }
+
+        Ok(found)
     }
 
-    #[test_case(&chain!(TEST_UNIVERSAL_KEY, [0x02, 0x01, 0x01]).collect_vec(), &[0]; "One at beginning")]
-    #[test_case(&chain!([0x06], TEST_UNIVERSAL_KEY, [0x02, 0x01, 0x00]).collect_vec(), &[1]; "One at offset")]
-    #[test_case(&multiple_uset_buf(), &[1, 25]; "Two at offset")]
-    fn test_start_locations(buf: &[u8], expected: &[u64]) {
-        let ukey = UniversalKey::new(TEST_UNIVERSAL_KEY);
-        assert_eq!(
-            UniversalSet::start_locations(&ukey, &mut Cursor::new(buf)).unwrap(),
-            *expected
-        )
+    /// Scan the buffer from the current position for the Universal Key.
+    ///
+    /// # Args
+    ///
+    /// - `end` - Offset that no byte of a reported key may be at or past. The
+    ///   scan never reads at or past this offset. `None` scans to the end of
+    ///   the buffer.
+    /// - `max_matches` - Stop scanning once this many keys have been found.
+    ///   `None` finds every key.
+    fn scan(
+        key: &UniversalKey,
+        buf: &mut T,
+        end: Option<u64>,
+        max_matches: Option<usize>,
+    ) -> Result<Vec<u64>, encoding::Error> {
+        KeyLocations::new(*key, buf, end)
+            .take(max_matches.unwrap_or(usize::MAX))
+            .collect()
     }
+}
 
-    #[test]
-    fn test_read_all() {
-        let ukey = UniversalKey::new(TEST_UNIVERSAL_KEY);
-        let mut binding = Cursor::new(multiple_uset_buf());
-        let sets = UniversalSet::read_all(&ukey, Rc::new(RefCell::new(&mut binding))).unwrap();
+/// Calls `visit` with the offset of every Universal Key `key` from the current
+/// position in the buffer, finding the same keys as
+/// `UniversalSet::start_locations`, along with the extent of the set there.
+///
+/// When the extent can't be read and `visit` returns `Ok` anyway, the search
+/// goes on from the byte after the key.
+///
+/// The buffer is searched `ProgressHandle::interval` bytes at a time and is
+/// only borrowed while it is being searched, so neither `visit` nor the
+/// progress callback run while it is borrowed.
+fn visit_locations<B>(
+    key: &UniversalKey,
+    buf: &RefCell<B>,
+    mut progress: Option<&mut ProgressHandle>,
+    mut visit: impl FnMut(u64, Result<SetExtent, encoding::Error>) -> Result<(), encoding::Error>,
+) -> Result<(), encoding::Error>
+where
+    B: Read + Seek,
+{
+    let (mut from, total) = {
+        let mut buf = buf.borrow_mut();
+        let from = buf.stream_position()?;
+        (from, buf.seek(SeekFrom::End(0))?)
+    };
+    let step = match &progress {
+        Some(progress) => progress.interval(),
+        None => total.saturating_sub(from).max(1),
+    };
 
-        assert_eq!(sets.len(), 2, "Number of universal sets found is incorrect");
-        let first_uset = sets.first().unwrap();
-        assert_eq!(
-            first_uset.data().len(),
-            1,
-            "Number of KLV triplets in first universal set is incorrect"
-        );
-        let first_uset_data = first_uset.data();
-        let only_klv = first_uset_data.iter().exactly_one().unwrap().1;
+    let mut offsets = Vec::new();
+    while from < total {
+        // Keys starting in the last bytes of the step are found by the next
+        // search.
+        let end = from.saturating_add(step + UNIVERSAL_KEY_LENGTH as u64 - 1);
+        let found = {
+            let mut buf = buf.borrow_mut();
+            buf.seek(SeekFrom::Start(from))?;
+            UniversalSet::scan(key, &mut *buf, Some(end), Some(1))?
+        };
+        from = match found.first() {
+            Some(&start) => {
+                let extent = UniversalSet::read_extent(&mut *buf.borrow_mut(), start);
+                let next = match &extent {
+                    Ok(extent) => extent.end(),
+                    Err(_) => start + UNIVERSAL_KEY_LENGTH as u64,
+                };
+                visit(start, extent)?;
+                offsets.push(start);
+                next
+            }
+            None => from + step,
+        };
+
+        let processed = from.min(total);
+        if let Some(progress) = progress.as_deref_mut()
+            && !progress.report(processed, Some(total))
+        {
+            return Err(encoding::Error::Cancelled { processed, offsets });
+        }
+    }
+    Ok(())
+}
+
+/// Where a `KeyLocations` search is in the buffer.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum SearchState {
+    /// The search buffer has not been filled yet.
+    Start,
+    /// The search buffer holds a key that was just reported, whose value has
+    /// not been jumped over yet.
+    Matched,
+    /// The end of the buffer or scan window was reached or reading failed.
+    Done,
+}
+
+/// Iterator over the offsets of the first byte of every Universal Key found in
+/// a buffer. See `UniversalSet::location_iter`.
+pub struct KeyLocations<'b, T> {
+    /// Keys searched for. Most searches are for a single key.
+    keys: Vec<UniversalKey>,
+    buf: &'b mut T,
+    /// Offset that no byte of a reported key may be at or past.
+    end: Option<u64>,
+    search_buffer: ConstGenericRingBuffer<u8, UNIVERSAL_KEY_LENGTH>,
+    state: SearchState,
+    /// Most value bytes the sets found may declare between them.
+    max_value_bytes: Option<u64>,
+    /// Value bytes declared by the sets jumped over so far.
+    value_bytes: u64,
+}
+
+impl<'b, T> KeyLocations<'b, T>
+where
+    T: Read + Seek,
+{
+    fn new(key: UniversalKey, buf: &'b mut T, end: Option<u64>) -> Self {
+        Self::for_keys(vec![key], buf, end)
+    }
+
+    fn for_keys(keys: Vec<UniversalKey>, buf: &'b mut T, end: Option<u64>) -> Self {
+        Self {
+            keys,
+            buf,
+            end,
+            search_buffer: ConstGenericRingBuffer::new(),
+            state: SearchState::Start,
+            max_value_bytes: None,
+            value_bytes: 0,
+        }
+    }
+
+    /// Stops the search once the sets found declare more than `max` value
+    /// bytes between them, checked before each value is jumped over.
+    fn with_value_limit(mut self, max: Option<u64>) -> Self {
+        self.max_value_bytes = max;
+        self
+    }
+
+    /// Whether reading one more byte would cross the end of the scan window.
+    fn at_end(&mut self) -> bool {
+        match self.end {
+            Some(end) => self
+                .buf
+                .stream_position()
+                .map(|pos| pos >= end)
+                .unwrap_or(true),
+            None => false,
+        }
+    }
+
+    /// Moves the search buffer forward by one byte.
+    ///
+    /// # Returns
+    ///
+    /// Whether a byte was read. Nothing is read at the end of the buffer or
+    /// scan window.
+    fn read_byte(&mut self) -> bool {
+        if self.at_end() {
+            return false;
+        }
+        match self.buf.read_u8() {
+            Ok(val) => {
+                self.search_buffer.enqueue(val);
+                true
+            }
+            Err(_) => false,
+        }
+    }
+
+    /// Finds the next key in the buffer.
+    ///
+    /// # Returns
+    ///
+    /// - `Ok(Some((usize, u64)))` - Index in `keys` of the key found and the
+    ///   offset of its first byte.
+    /// - `Ok(None)` - There are no more keys in the buffer or scan window.
+    /// - `Err(encoding::Error::LimitExceeded)` - The sets found declare more
+    ///   value bytes than the limit set with `KeyLocations::with_value_limit`.
+    /// - `Err(encoding::Error)` - The length of the last key found could not
+    ///   be read or is too large to jump over.
+    fn advance(&mut self) -> Result<Option<(usize, u64)>, encoding::Error> {
+        match self.state {
+            SearchState::Start => {}
+            SearchState::Matched => {
+                // Get how far to jump at the very least to get to the next
+                // Universal Key.
+                let value_length = read_ber(self.buf)?;
+                if let Some(max) = self.max_value_bytes {
+                    let value_bytes = u128::from(self.value_bytes) + value_length;
+                    if value_bytes > u128::from(max) {
+                        return Err(encoding::Error::LimitExceeded(Limit::TotalValueBytes, max));
+                    }
+                }
+                let value_length = seekable_length(value_length)?;
+                self.value_bytes += value_length;
+                self.buf.seek_relative(value_length as i64)?;
+            }
+            SearchState::Done => return Ok(None),
+        }
+
+        if let Some(end) = self.end {
+            let current_pos = self.buf.stream_position()?;
+            if current_pos + UNIVERSAL_KEY_LENGTH as u64 > end {
+                return Ok(None);
+            }
+        }
+
+        // The search buffer is refilled from the start of the file or the
+        // end of the last set's value, which is the start of the next key
+        // when sets are back to back or the last set's value is empty. None
+        // of the bytes of the last key are kept, so a key can't be found
+        // overlapping it.
+        let mut buffer_contents = [0; UNIVERSAL_KEY_LENGTH];
+        if self.buf.read_exact(&mut buffer_contents).is_err() {
+            return Ok(None);
+        }
+        self.search_buffer = ConstGenericRingBuffer::from(buffer_contents);
+
+        loop {
+            let matched = self
+                .keys
+                .iter()
+                .position(|key| itertools::equal(&self.search_buffer, &key.0));
+            if let Some(index) = matched {
+                // Matches will only happen after the last byte of the
+                // Universal Key has been read so we always need to subtract
+                // the length of the key from the current position to get the
+                // starting position.
+                let start_pos = self.buf.stream_position()? - UNIVERSAL_KEY_LENGTH as u64;
+                self.state = SearchState::Matched;
+                return Ok(Some((index, start_pos)));
+            }
+
+            if !self.read_byte() {
+                return Ok(None);
+            }
+        }
+    }
+}
+
+impl<T> KeyLocations<'_, T>
+where
+    T: Read + Seek,
+{
+    /// Finds the next key in the buffer along with its index in `keys`.
+    fn next_match(&mut self) -> Option<Result<(usize, u64), encoding::Error>> {
+        let next = self.advance();
+        if !matches!(next, Ok(Some(_))) {
+            self.state = SearchState::Done;
+        }
+        next.transpose()
+    }
+}
+
+impl<T> Iterator for KeyLocations<'_, T>
+where
+    T: Read + Seek,
+{
+    type Item = Result<u64, encoding::Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_match()
+            .map(|found| found.map(|(_, offset)| offset))
+    }
+}
+
+/// Finds a Universal Key in a buffer handed over in chunks, such as one
+/// filled by a capture card or a ring buffer, that can't be read through
+/// `Read + Seek`.
+///
+/// Up to `UNIVERSAL_KEY_LENGTH - 1` bytes from the end of each chunk are kept
+/// so that keys split across chunks are found. Unlike
+/// `UniversalSet::start_locations`, values are not jumped over, so a key
+/// appearing inside a value is reported too.
+#[derive(Clone, Debug)]
+pub struct KeyScanner {
+    key: UniversalKey,
+    /// Last bytes of the chunks scanned so far.
+    carry: Vec<u8>,
+    /// Offset of the byte after the last byte of the last chunk scanned.
+    next_offset: Option<u64>,
+}
+
+impl KeyScanner {
+    pub fn new(key: impl Into<UniversalKey>) -> Self {
+        Self {
+            key: key.into(),
+            carry: Vec::with_capacity(UNIVERSAL_KEY_LENGTH - 1),
+            next_offset: None,
+        }
+    }
+
+    /// Finds every key that ends in `chunk`.
+    ///
+    /// # Args
+    ///
+    /// - `chunk` - The next bytes of the buffer.
+    /// - `base_offset` - Offset in the buffer of the first byte of `chunk`.
+    ///   When it is not the byte after the last chunk scanned, the bytes kept
+    ///   from that chunk are dropped, as there is a gap between the two.
+    ///
+    /// # Returns
+    ///
+    /// Offsets of the first byte of every key found, in buffer order. A key
+    /// that started in an earlier chunk has an offset before `base_offset`.
+    pub fn scan_chunk(&mut self, chunk: &[u8], base_offset: u64) -> Vec<u64> {
+        if self.next_offset != Some(base_offset) {
+            self.carry.clear();
+        }
+        let start = base_offset - self.carry.len() as u64;
+        let mut window = std::mem::take(&mut self.carry);
+        window.extend_from_slice(chunk);
+
+        let found = window
+            .windows(UNIVERSAL_KEY_LENGTH)
+            .enumerate()
+            .filter(|(_, bytes)| *bytes == &self.key.0[..])
+            .map(|(i, _)| start + i as u64)
+            .collect();
+
+        let kept = window.len().min(UNIVERSAL_KEY_LENGTH - 1);
+        window.drain(..window.len() - kept);
+        self.carry = window;
+        self.next_offset = Some(base_offset + chunk.len() as u64);
+        found
+    }
+
+    /// Forgets the bytes kept from the chunks scanned so far, so that the
+    /// next chunk is scanned as the start of a new buffer.
+    pub fn reset(&mut self) {
+        self.carry.clear();
+        self.next_offset = None;
+    }
+}
+
+impl UniversalSet<BufReader<File>> {
+    /// Reads every Universal Set in the file at `path`.
+    ///
+    /// The file is read through a `BufReader` and every set is copied out of
+    /// it, so nothing returned borrows from the file or from `key`.
+    ///
+    /// # Returns
+    ///
+    /// - `Ok(Vec<OwnedUniversalSet>)` - Every set in the file, in file order.
+    /// - `Err(encoding::Error::File)` - The file could not be opened or one of
+    ///   its sets could not be read. The error carries `path`.
+    pub fn read_all_from_path(
+        key: &UniversalKey,
+        path: impl AsRef<Path>,
+    ) -> Result<Vec<OwnedUniversalSet>, encoding::Error> {
+        Self::iter_from_path(key, path)?.collect()
+    }
+
+    /// Opens the file at `path` and returns an iterator over the Universal
+    /// Sets in it.
+    ///
+    /// Errors opening the file, and any error yielded by the iterator, carry
+    /// `path`.
+    pub fn iter_from_path(
+        key: &UniversalKey,
+        path: impl AsRef<Path>,
+    ) -> Result<UniversalSetReader<BufReader<File>>, encoding::Error> {
+        let path = path.as_ref();
+        let file = File::open(path).map_err(|e| encoding::Error::from(e).in_file(path))?;
+        let mut reader = UniversalSetReader::new(*key, BufReader::new(file));
+        reader.path = Some(path.to_path_buf());
+        Ok(reader)
+    }
+}
+
+impl UniversalSet<Box<dyn ReadSeek>> {
+    /// Reads every set keyed by `key` in a buffer whose type has been erased,
+    /// so sets read from files, memory, or anything else that can be read
+    /// and seeked share one type.
+    ///
+    /// See `UniversalSet::read_all`.
+    pub fn read_all_dyn(
+        key: impl Into<UniversalKey>,
+        buf: Box<dyn ReadSeek>,
+    ) -> Result<Vec<Self>, encoding::Error> {
+        Self::read_all(key, Rc::new(RefCell::new(buf)))
+    }
+}
+
+/// Universal Set whose items have been copied out of the reader they were
+/// found in.
+#[derive(Clone, Debug, PartialEq, Eq, getset::CopyGetters, getset::Getters)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct OwnedUniversalSet {
+    /// Key that the set was found by.
+    #[getset(get_copy = "pub")]
+    key: UniversalKey,
+
+    /// Offset in the stream of the first byte of the Universal Key.
+    #[getset(get_copy = "pub")]
+    offset: u64,
+
+    /// KLV triplets making up the set, in stream order.
+    #[getset(get = "pub")]
+    items: Vec<OwnedKlv>,
+}
+
+impl OwnedUniversalSet {
+    pub fn new(key: UniversalKey, offset: u64, items: Vec<OwnedKlv>) -> Self {
+        Self { key, offset, items }
+    }
+}
+
+/// Running totals of a read checked against the resource ceilings of
+/// `ParseOptions`.
+#[derive(Clone, Copy, Debug, Default)]
+struct ReadBudget {
+    max_packets: Option<usize>,
+    max_total_value_bytes: Option<u64>,
+    max_items_per_set: Option<usize>,
+    /// Number of sets counted so far.
+    packets: usize,
+    /// Number of payload bytes counted so far.
+    value_bytes: u64,
+}
+
+impl ReadBudget {
+    fn new(options: &ParseOptions) -> Self {
+        Self {
+            max_packets: options.max_packets,
+            max_total_value_bytes: options.max_total_value_bytes,
+            max_items_per_set: options.max_items_per_set,
+            ..Self::default()
+        }
+    }
+
+    /// Counts a set whose payload is `value_length` bytes long.
+    ///
+    /// # Returns
+    ///
+    /// - `Ok(())` - The set may be read.
+    /// - `Err(encoding::Error::LimitExceeded)` - Reading the set would break
+    ///   `max_packets` or `max_total_value_bytes`. Nothing is counted.
+    fn spend(&mut self, value_length: u64) -> Result<(), encoding::Error> {
+        if let Some(max) = self.max_packets
+            && self.packets >= max
+        {
+            return Err(encoding::Error::LimitExceeded(Limit::Packets, max as u64));
+        }
+        let value_bytes = self.value_bytes.saturating_add(value_length);
+        if let Some(max) = self.max_total_value_bytes
+            && value_bytes > max
+        {
+            return Err(encoding::Error::LimitExceeded(Limit::TotalValueBytes, max));
+        }
+        self.packets += 1;
+        self.value_bytes = value_bytes;
+        Ok(())
+    }
+}
+
+/// Iterator over the Universal Sets in a reader that does not support seeking.
+///
+/// Only a single set's payload is buffered at a time. Bytes between sets that
+/// are not part of the Universal Key are discarded. Because every byte is
+/// read individually while searching for the key, wrap unbuffered readers in
+/// a `BufReader`.
+///
+/// Iteration ends when the reader runs out of bytes while searching for a key.
+/// Running out of bytes part way through a set's length or payload yields an
+/// `encoding::Error::Truncated` and then ends iteration.
+///
+/// With the `tokio` feature enabled, async readers are supported through
+/// `UniversalSetReader::next_async`.
+#[derive(Debug)]
+pub struct UniversalSetReader<R> {
+    key: UniversalKey,
+    reader: R,
+    /// Number of bytes read from `reader` so far.
+    position: u64,
+    /// Offset of the first byte of the most recently matched key.
+    key_offset: u64,
+    search_buffer: ConstGenericRingBuffer<u8, UNIVERSAL_KEY_LENGTH>,
+    /// File the reader was opened from, attached to any errors.
+    path: Option<PathBuf>,
+    /// Whether the last error was the reader running out of bytes part way
+    /// through a set's length or payload.
+    ended_mid_set: bool,
+    /// Resource ceilings the sets read so far are counted against.
+    budget: ReadBudget,
+    done: bool,
+}
+
+impl<R> UniversalSetReader<R> {
+    pub fn new(key: UniversalKey, reader: R) -> Self {
+        Self::starting_at(key, reader, 0)
+    }
+
+    /// Creates a reader whose first byte is at `position` in the underlying
+    /// stream so that reported offsets are relative to the start of the
+    /// stream.
+    pub(crate) fn starting_at(key: UniversalKey, reader: R, position: u64) -> Self {
+        Self {
+            key,
+            reader,
+            position,
+            key_offset: 0,
+            search_buffer: ConstGenericRingBuffer::new(),
+            path: None,
+            ended_mid_set: false,
+            budget: ReadBudget::default(),
+            done: false,
+        }
+    }
+
+    /// Enforces the resource ceilings of `options`: `max_packets`,
+    /// `max_total_value_bytes`, and `max_items_per_set`. The other options
+    /// don't apply to sets read from a forward-only reader.
+    ///
+    /// The sets read before a ceiling is reached are yielded as usual,
+    /// followed by an `encoding::Error::LimitExceeded` that ends iteration.
+    /// A set's payload is checked before it is read.
+    pub fn with_limits(mut self, options: &ParseOptions) -> Self {
+        self.budget = ReadBudget::new(options);
+        self
+    }
+
+    /// Offset in the stream of the next byte to be read.
+    pub(crate) fn position(&self) -> u64 {
+        self.position
+    }
+
+    /// Offset of the first byte of the most recently matched key.
+    pub(crate) fn key_offset(&self) -> u64 {
+        self.key_offset
+    }
+
+    /// Whether iteration ended because the reader ran out of bytes part way
+    /// through a set's length or payload.
+    pub(crate) fn ended_mid_set(&self) -> bool {
+        self.ended_mid_set
+    }
+
+    /// Converts an error reading part of a set into an `encoding::Error`,
+    /// remembering if it was caused by the reader running out of bytes.
+    fn set_error(&mut self, err: std::io::Error, element: &'static str) -> encoding::Error {
+        let err = truncated_or_other(err, element);
+        self.ended_mid_set = matches!(err, encoding::Error::Truncated(_));
+        err
+    }
+
+    /// Adds a byte read while searching for the key to the search buffer.
+    ///
+    /// # Returns
+    ///
+    /// `true` when the byte completes the key.
+    fn push_search_byte(&mut self, byte: u8) -> bool {
+        self.position += 1;
+        self.search_buffer.enqueue(byte);
+        if self.search_buffer.is_full() && itertools::equal(&self.search_buffer, &self.key.0) {
+            self.search_buffer.clear();
+            self.key_offset = self.position - UNIVERSAL_KEY_LENGTH as u64;
+            return true;
+        }
+        false
+    }
+
+    /// Builds the most recently matched set out of its payload.
+    fn finish_set(&self, payload: &[u8]) -> Result<OwnedUniversalSet, encoding::Error> {
+        let max_items = self.budget.max_items_per_set;
+        // One item past the limit is enough to know it has been exceeded.
+        let items = KlvReader::new(payload)
+            .take(max_items.map_or(usize::MAX, |max| max.saturating_add(1)))
+            .collect::<Result<Vec<_>, _>>()?;
+        if let Some(max) = max_items
+            && items.len() > max
+        {
+            return Err(encoding::Error::LimitExceeded(
+                Limit::ItemsPerSet,
+                max as u64,
+            ));
+        }
+
+        Ok(OwnedUniversalSet {
+            key: self.key,
+            offset: self.key_offset,
+            items,
+        })
+    }
+
+    /// Records the outcome of reading a set, ending iteration on anything but
+    /// a successfully read set.
+    fn record<S>(
+        &mut self,
+        result: Result<Option<S>, encoding::Error>,
+    ) -> Option<Result<S, encoding::Error>> {
+        let result = result.transpose();
+        if !matches!(result, Some(Ok(_))) {
+            self.done = true;
+        }
+        match &self.path {
+            Some(path) => result.map(|r| r.map_err(|e| e.in_file(path))),
+            None => result,
+        }
+    }
+}
+
+impl<R> UniversalSetReader<R>
+where
+    R: Read,
+{
+    /// Reads the payload of the next set without parsing its items, so that
+    /// parsing can happen elsewhere.
+    ///
+    /// Behaves the same as `Iterator::next` otherwise.
+    #[cfg(feature = "threads")]
+    pub(crate) fn next_payload(&mut self) -> Option<Result<Vec<u8>, encoding::Error>> {
+        if self.done {
+            return None;
+        }
+
+        let result = self.read_payload();
+        self.record(result)
+    }
+
+    fn read_set(&mut self) -> Result<Option<OwnedUniversalSet>, encoding::Error> {
+        self.read_payload()?
+            .map(|payload| self.finish_set(&payload))
+            .transpose()
+    }
+
+    fn read_payload(&mut self) -> Result<Option<Vec<u8>>, encoding::Error> {
+        loop {
+            match self.reader.read_u8() {
+                Ok(val) => {
+                    if self.push_search_byte(val) {
+                        break;
+                    }
+                }
+                Err(e) if e.kind() == std::io::ErrorKind::UnexpectedEof => return Ok(None),
+                Err(e) => return Err(e.into()),
+            }
+        }
+
+        // Read through `take` purely to count how many bytes the length used.
+        let mut counted = (&mut self.reader).take(u64::MAX);
+        let length = read_ber(&mut counted);
+        let length_bytes = u64::MAX - counted.limit();
+        self.position += length_bytes;
+        let length = seekable_length(length.map_err(|e| self.set_error(e, "length"))?)?;
+        self.budget.spend(length)?;
+
+        let (payload, eof) = read_up_to(&mut self.reader, length, DEFAULT_CHUNK)?;
+        self.position += payload.len() as u64;
+        if eof {
+            return Err(self.set_error(std::io::ErrorKind::UnexpectedEof.into(), "value"));
+        }
+
+        Ok(Some(payload))
+    }
+}
+
+#[cfg(feature = "tokio")]
+impl<R> UniversalSetReader<R>
+where
+    R: tokio::io::AsyncRead + Unpin,
+{
+    /// Reads the next Universal Set from an async reader.
+    ///
+    /// Behaves the same as `Iterator::next` does for synchronous readers.
+    pub async fn next_async(&mut self) -> Option<Result<OwnedUniversalSet, encoding::Error>> {
+        if self.done {
+            return None;
+        }
+
+        let result = self.read_set_async().await;
+        self.record(result)
+    }
+
+    async fn read_set_async(&mut self) -> Result<Option<OwnedUniversalSet>, encoding::Error> {
+        use tokio::io::AsyncReadExt;
+
+        loop {
+            match self.reader.read_u8().await {
+                Ok(val) => {
+                    if self.push_search_byte(val) {
+                        break;
+                    }
+                }
+                Err(e) if e.kind() == std::io::ErrorKind::UnexpectedEof => return Ok(None),
+                Err(e) => return Err(e.into()),
+            }
+        }
+
+        // Read through `take` purely to count how many bytes the length used.
+        let mut counted = (&mut self.reader).take(u64::MAX);
+        let length = read_ber_async(&mut counted).await;
+        let length_bytes = u64::MAX - counted.limit();
+        self.position += length_bytes;
+        let length = seekable_length(length.map_err(|e| self.set_error(e, "length"))?)?;
+        self.budget.spend(length)?;
+
+        let mut payload = Vec::new();
+        (&mut self.reader)
+            .take(length)
+            .read_to_end(&mut payload)
+            .await?;
+        self.position += payload.len() as u64;
+        if (payload.len() as u64) < length {
+            return Err(self.set_error(std::io::ErrorKind::UnexpectedEof.into(), "value"));
+        }
+
+        self.finish_set(&payload).map(Some)
+    }
+}
+
+impl<R> Iterator for UniversalSetReader<R>
+where
+    R: Read,
+{
+    type Item = Result<OwnedUniversalSet, encoding::Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+
+        let result = self.read_set();
+        self.record(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use super::*;
+    use crate::klv::Klv;
+    use itertools::{Itertools, chain};
+    use test_case::test_case;
+
+    const TEST_UNIVERSAL_KEY: [u8; UNIVERSAL_KEY_LENGTH] = [
+        0x06, 0x0E, 0x2B, 0x34, 0x02, 0x0B, 0x01, 0x01, 0x0E, 0x01, 0x03, 0x01, 0x01, 0x00, 0x00,
+        0x00,
+    ];
+
+    fn multiple_uset_buf() -> Vec<u8> {
+        chain!(
+            // Byte before the key starts
+            [0x06],
+            TEST_UNIVERSAL_KEY, // Starts at index 1
+            [0x04],
+            [0x01, 0x02, 0x03, 0x04],
+            // Bytes that should not be included in either Universal Set
+            [0xFF, 0xFF, 0x06],
+            TEST_UNIVERSAL_KEY,
+            [0x07],
+            [0x01, 0x01, 0x02],
+            [0x02, 0x02, 0x04, 0x08]
+        )
+        .collect_vec()
+    }
+
+    /// Two identical sets with nothing between them.
+    fn back_to_back_uset_buf() -> Vec<u8> {
+        let set = chain!(TEST_UNIVERSAL_KEY, [0x03, 0x01, 0x01, 0xAA]).collect_vec();
+        chain!(set.clone(), set).collect_vec()
+    }
+
+    /// A set whose last item's value is all but the last byte of the key,
+    /// directly followed by a set.
+    fn partial_key_uset_buf() -> Vec<u8> {
+        chain!(
+            TEST_UNIVERSAL_KEY,
+            [0x14, 0x01, 0x01, 0xAA, 0x02, 0x0F],
+            TEST_UNIVERSAL_KEY[..UNIVERSAL_KEY_LENGTH - 1]
+                .iter()
+                .copied(),
+            TEST_UNIVERSAL_KEY, // Starts at index 37
+            [0x02, 0x02, 0x00]
+        )
+        .collect_vec()
+    }
+
+    /// A key at the very start of the buffer, directly followed by a key
+    /// after a set with a two byte value.
+    fn adjacent_uset_buf() -> Vec<u8> {
+        chain!(
+            TEST_UNIVERSAL_KEY,
+            [0x02, 0x05, 0x00],
+            TEST_UNIVERSAL_KEY, // Starts at index 16 + 1 + 2
+            [0x03, 0x06, 0x01, 0xBB]
+        )
+        .collect_vec()
+    }
+
+    #[test_case(&back_to_back_uset_buf(), &[0, 20]; "Back to back")]
+    #[test_case(&partial_key_uset_buf(), &[0, 37]; "Value ends in part of the key")]
+    #[test_case(&adjacent_uset_buf(), &[0, 19]; "Key at the start")]
+    #[test_case(&chain!(partial_key_uset_buf(), back_to_back_uset_buf()).collect_vec(), &[0, 37, 56, 76]; "Every case in a row")]
+    fn adjacent_sets_are_all_found(buf: &[u8], expected: &[u64]) {
+        let key = UniversalKey::new(TEST_UNIVERSAL_KEY);
         assert_eq!(
-            only_klv.tag(),
+            UniversalSet::start_locations(&key, &mut Cursor::new(buf)).unwrap(),
+            expected
+        );
+        assert_eq!(
+            UniversalSet::last_locations(&key, &mut Cursor::new(buf), expected.len()).unwrap(),
+            expected
+        );
+
+        let sets = UniversalSet::read_all(key, Rc::new(RefCell::new(Cursor::new(buf)))).unwrap();
+        assert_eq!(
+            sets.iter()
+                .map(|set| set.extent().key_offset())
+                .collect_vec(),
+            expected
+        );
+        assert!(sets.iter().all(|set| !set.data().is_empty()));
+
+        let owned = UniversalSetReader::new(key, NoSeek(buf))
+            .map(|set| set.unwrap().offset())
+            .collect_vec();
+        assert_eq!(owned, expected);
+    }
+
+    /// A set with an empty payload between two sets with items, and another
+    /// at the very end of the buffer.
+    fn zero_length_uset_buf() -> Vec<u8> {
+        chain!(
+            TEST_UNIVERSAL_KEY, // Starts at index 0
+            [0x03, 0x01, 0x01, 0xAA],
+            TEST_UNIVERSAL_KEY, // Starts at index 20
+            [0x00],
+            TEST_UNIVERSAL_KEY, // Starts at index 37
+            [0x03, 0x02, 0x01, 0xBB],
+            TEST_UNIVERSAL_KEY, // Starts at index 57
+            [0x00]
+        )
+        .collect_vec()
+    }
+
+    #[test]
+    fn zero_length_sets_are_empty() {
+        let key = UniversalKey::new(TEST_UNIVERSAL_KEY);
+        let buf = zero_length_uset_buf();
+        assert_eq!(
+            UniversalSet::start_locations(&key, &mut Cursor::new(&buf)).unwrap(),
+            [0, 20, 37, 57]
+        );
+
+        let sets = UniversalSet::read_all(key, Rc::new(RefCell::new(Cursor::new(buf)))).unwrap();
+        assert_eq!(
+            sets.iter().map(|set| set.data().len()).collect_vec(),
+            [1, 0, 1, 0]
+        );
+        for set in [&sets[1], &sets[3]] {
+            assert!(set.data().is_empty());
+            assert_eq!(set.payload_len(), 0);
+            assert_eq!(set.extent().end(), set.extent().value_offset());
+        }
+
+        let owned = UniversalSetReader::new(key, NoSeek(&zero_length_uset_buf()))
+            .map(|set| set.unwrap().items().len())
+            .collect_vec();
+        assert_eq!(owned, [1, 0, 1, 0]);
+    }
+
+    #[test]
+    fn key_overlapping_the_last_key_is_not_found() {
+        // Every byte of this key is repeated one byte later, so a search
+        // that kept the bytes of the key it just found would find the key
+        // again one byte after it.
+        let key = UniversalKey::new([0x01, 0x00].repeat(8).try_into().unwrap());
+        let buf = chain!(*key, [0x00], [0x01, 0x00].repeat(8), [0x02, 0x01, 0xAA]).collect_vec();
+        assert_eq!(
+            UniversalSet::start_locations(&key, &mut Cursor::new(&buf)).unwrap(),
+            [0, 17]
+        );
+    }
+
+    #[test_case(7; "Keys straddle chunks")]
+    #[test_case(20; "Sets straddle chunks")]
+    #[test_case(REVERSE_CHUNK_LENGTH; "One chunk")]
+    fn last_locations_match_start_locations(chunk_length: u64) {
+        let buf = chain!(
+            [0x00],
+            TEST_UNIVERSAL_KEY,
+            [0x03, 0x02, 0x01, 0x07],
+            [0xFF, 0xFF],
+            TEST_UNIVERSAL_KEY,
+            [0x03, 0x02, 0x01, 0x08],
+            TEST_UNIVERSAL_KEY,
+            [0x03, 0x02, 0x01, 0x09],
+            // The key inside of this value is followed by a length longer
+            // than the rest of the buffer.
+            TEST_UNIVERSAL_KEY,
+            [0x13, 0x05, 0x11],
+            TEST_UNIVERSAL_KEY,
+            [0x7F]
+        )
+        .collect_vec();
+        let key = UniversalKey::new(TEST_UNIVERSAL_KEY);
+        let forward = UniversalSet::start_locations(&key, &mut Cursor::new(&buf)).unwrap();
+
+        let last = |n| {
+            UniversalSet::last_locations_in_chunks(&key, &mut Cursor::new(&buf), n, chunk_length)
+                .unwrap()
+        };
+        assert_eq!(last(2), forward[forward.len() - 2..]);
+        assert_eq!(last(10), forward);
+        assert_eq!(last(0), Vec::<u64>::new());
+
+        // A set cut short by the end of the recording is not counted.
+        let cut = &buf[..buf.len() - 3];
+        assert_eq!(
+            UniversalSet::last_locations(&key, &mut Cursor::new(cut), 1).unwrap(),
+            forward[forward.len() - 2..forward.len() - 1]
+        );
+    }
+
+    #[test_case(&[0x88, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF]; "Set length past i64")]
+    #[test_case(&[0x80]; "Indefinite set length")]
+    #[test_case(&[0x0A, 0x01, 0x88, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF]; "Item length past i64")]
+    #[test_case(&[0x0A, 0x01, 0x88, 0x7F, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF]; "Item ending past i64")]
+    #[test_case(&[0x02, 0x01, 0x80]; "Indefinite item length")]
+    fn last_locations_pass_over_garbage_lengths(decoy: &[u8]) {
+        let buf = chain!(TEST_UNIVERSAL_KEY, [0x03, 0x02, 0x01, 0x07])
+            .chain(TEST_UNIVERSAL_KEY)
+            .chain(decoy.iter().copied())
+            .collect_vec();
+        let key = UniversalKey::new(TEST_UNIVERSAL_KEY);
+        assert_eq!(
+            UniversalSet::last_locations(&key, &mut Cursor::new(&buf), 1).unwrap(),
+            [0]
+        );
+    }
+
+    #[test]
+    fn scan_is_cancelled_after_first_packet() {
+        let buf = chain!(
+            TEST_UNIVERSAL_KEY,
+            [0x03, 0x02, 0x01, 0x07],
+            [0xFF, 0xFF],
+            TEST_UNIVERSAL_KEY,
+            [0x03, 0x02, 0x01, 0x08],
+            TEST_UNIVERSAL_KEY,
+            [0x03, 0x02, 0x01, 0x09]
+        )
+        .collect_vec();
+        let key = UniversalKey::new(TEST_UNIVERSAL_KEY);
+        let shared = Rc::new(RefCell::new(Cursor::new(buf.clone())));
+        let forward = UniversalSet::start_locations(&key, &mut Cursor::new(&buf)).unwrap();
+
+        let mut progress = ProgressHandle::new(1);
+        let cancel = progress.cancel_token();
+        let callback_buf = shared.clone();
+        progress = progress.with_callback(move |_, total| {
+            // The buffer isn't borrowed while the callback runs.
+            assert_eq!(callback_buf.borrow().get_ref().len() as u64, total.unwrap());
+            cancel.store(true, std::sync::atomic::Ordering::Relaxed);
+        });
+        shared.borrow_mut().set_position(0);
+        match UniversalSet::read_all_with_progress(
+            key,
+            shared.clone(),
+            &ParseOptions::default(),
+            Some(&mut progress),
+        ) {
+            Err(encoding::Error::Cancelled { processed, offsets }) => {
+                assert_eq!(offsets, forward[..1]);
+                assert_eq!(processed, 20);
+            }
+            other => panic!("Expected the scan to be cancelled, got {other:?}"),
+        }
+
+        let mut progress = ProgressHandle::new(1);
+        assert_eq!(
+            UniversalSet::start_locations_with_progress(
+                &key,
+                &mut Cursor::new(&buf),
+                Some(&mut progress)
+            )
+            .unwrap(),
+            forward
+        );
+        shared.borrow_mut().set_position(0);
+        let sets = UniversalSet::read_all_with_progress(
+            key,
+            shared.clone(),
+            &ParseOptions::default(),
+            None,
+        )
+        .unwrap();
+        assert_eq!(
+            sets.iter()
+                .map(|set| set.extent().key_offset())
+                .collect_vec(),
+            forward
+        );
+        shared.borrow_mut().set_position(0);
+        let report = UniversalSet::scan_report_with_progress(
+            key,
+            shared.clone(),
+            &ParseOptions::default(),
+            Some(&mut ProgressHandle::new(4)),
+        )
+        .unwrap();
+        shared.borrow_mut().set_position(0);
+        assert_eq!(
+            report,
+            UniversalSet::scan_report(key, shared, &ParseOptions::default()).unwrap()
+        );
+    }
+
+    #[test]
+    fn key_scanner_finds_keys_split_across_chunks() {
+        let buf = chain!(
+            [0x00; 3],
+            TEST_UNIVERSAL_KEY,
+            [0x01, 0x02],
+            TEST_UNIVERSAL_KEY
+        )
+        .collect_vec();
+        let expected = [3, 3 + UNIVERSAL_KEY_LENGTH as u64 + 2];
+
+        // Split at every position, including every byte within each key.
+        for split in 0..=buf.len() {
+            let mut scanner = KeyScanner::new(TEST_UNIVERSAL_KEY);
+            let (first, second) = buf.split_at(split);
+            let found = chain!(
+                scanner.scan_chunk(first, 0),
+                scanner.scan_chunk(second, split as u64)
+            )
+            .collect_vec();
+            assert_eq!(found, expected, "split at {split}");
+        }
+
+        let mut scanner = KeyScanner::new(TEST_UNIVERSAL_KEY);
+        let found = buf
+            .iter()
+            .enumerate()
+            .flat_map(|(i, byte)| scanner.scan_chunk(&[*byte], i as u64))
+            .collect_vec();
+        assert_eq!(found, expected);
+
+        // The start of the first key is forgotten.
+        let mut scanner = KeyScanner::new(TEST_UNIVERSAL_KEY);
+        assert!(scanner.scan_chunk(&buf[..10], 0).is_empty());
+        scanner.reset();
+        assert_eq!(scanner.scan_chunk(&buf[10..], 10), expected[1..]);
+        // A gap between chunks also drops the kept bytes.
+        assert!(scanner.scan_chunk(&buf[..10], 0).is_empty());
+        assert!(scanner.scan_chunk(&buf[11..20], 11).is_empty());
+    }
+
+    #[test]
+    fn scan_keys_matches_start_locations() {
+        let other = UniversalKey::new([0x0A; UNIVERSAL_KEY_LENGTH]);
+        let buf = chain!(
+            [0x00],
+            TEST_UNIVERSAL_KEY,
+            [0x03, 0x02, 0x01, 0x07],
+            *other,
+            [0x02, 0x01, 0x00],
+            [0xFF],
+            *other,
+            [0x03, 0x02, 0x01, 0x08],
+            TEST_UNIVERSAL_KEY,
+            [0x03, 0x02, 0x01, 0x09]
+        )
+        .collect_vec();
+        let keys = [UniversalKey::new(TEST_UNIVERSAL_KEY), other];
+
+        let found = UniversalSet::scan_keys(&keys, &mut Cursor::new(&buf)).unwrap();
+        assert_eq!(found, [(0, 1), (1, 21), (1, 41), (0, 61)]);
+        for (index, key) in keys.iter().enumerate() {
+            assert_eq!(
+                found
+                    .iter()
+                    .filter(|(found, _)| *found == index)
+                    .map(|(_, offset)| *offset)
+                    .collect_vec(),
+                UniversalSet::start_locations(key, &mut Cursor::new(&buf)).unwrap()
+            );
+        }
+
+        let sets =
+            UniversalSet::read_all_multi(&keys, Rc::new(RefCell::new(Cursor::new(buf.clone()))))
+                .unwrap();
+        assert_eq!(
+            sets.iter()
+                .map(|sets| sets.iter().map(|set| set.data().len()).collect_vec())
+                .collect_vec(),
+            [[1, 1], [1, 1]]
+        );
+
+        // The ceiling counts the sets of both keys.
+        let options = ParseOptions {
+            max_packets: Some(3),
+            ..ParseOptions::default()
+        };
+        assert!(matches!(
+            UniversalSet::read_all_multi_with_options(
+                &keys,
+                Rc::new(RefCell::new(Cursor::new(buf))),
+                &options
+            ),
+            Err(encoding::Error::LimitExceeded(Limit::Packets, 3))
+        ));
+    }
+
+    #[test]
+    fn discover_keys_skips_decoys() {
+        let decoy = keys::EG0104;
+        let value = chain!(*decoy, [0x01, 0x00]).collect_vec();
+        let buf = chain!(
+            [0x00, 0x01],
+            TEST_UNIVERSAL_KEY,
+            [value.len() as u8],
+            value,
+            *keys::EG0104,
+            [0x02, 0xAA, 0xBB],
+            // Prefix of a UL that is not a group key.
+            [0x06, 0x0E, 0x2B, 0x34, 0x01],
+            TEST_UNIVERSAL_KEY,
+            [0x00],
+            // Group key whose length runs past the end of the buffer.
+            *decoy,
+            [0x7F, 0x00]
+        )
+        .collect_vec();
+
+        let found = UniversalSet::discover_keys(&mut Cursor::new(buf)).unwrap();
+        assert_eq!(
+            found,
+            [
+                (UniversalKey::new(TEST_UNIVERSAL_KEY), 2, vec![2, 61]),
+                (keys::EG0104, 1, vec![37]),
+            ]
+        );
+    }
+
+    #[test_case(&[0x88, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF]; "Past u64")]
+    #[test_case(&[0x88, 0x7F, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF]; "End past i64")]
+    #[test_case(&[0x91, 0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00]; "Past u128")]
+    fn discover_keys_skips_decoys_with_huge_lengths(length: &[u8]) {
+        let buf = chain!(*keys::EG0104, length.iter().copied())
+            .chain(TEST_UNIVERSAL_KEY)
+            .chain([0x00])
+            .collect_vec();
+        let offset = (UNIVERSAL_KEY_LENGTH + length.len()) as u64;
+
+        let found = UniversalSet::discover_keys(&mut Cursor::new(buf)).unwrap();
+        assert_eq!(
+            found,
+            [(UniversalKey::new(TEST_UNIVERSAL_KEY), 1, vec![offset])]
+        );
+    }
+
+    #[test]
+    fn repeated_sets_are_read_once() {
+        let buf = chain!(
+            TEST_UNIVERSAL_KEY,
+            [0x04, 0x02, 0x02, 0xAA, 0xBB],
+            TEST_UNIVERSAL_KEY,
+            [0x04, 0x02, 0x02, 0xAA, 0xBB],
+            TEST_UNIVERSAL_KEY,
+            [0x04, 0x02, 0x02, 0xAA, 0xBC]
+        )
+        .collect_vec();
+
+        let sets = UniversalSet::read_all(
+            TEST_UNIVERSAL_KEY,
+            Rc::new(RefCell::new(Cursor::new(buf.clone()))),
+        )
+        .unwrap();
+        let hashes = sets
+            .iter()
+            .map(|set| set.payload_hash().unwrap())
+            .collect_vec();
+        assert_eq!(hashes[0], hashes[1]);
+        assert_ne!(hashes[1], hashes[2]);
+
+        let buf = Rc::new(RefCell::new(Cursor::new(buf)));
+        let deduped = UniversalSet::read_all_deduped(TEST_UNIVERSAL_KEY, buf).unwrap();
+        assert_eq!(
+            deduped
+                .iter()
+                .map(|set| set.extent().key_offset())
+                .collect_vec(),
+            [0, 42]
+        );
+    }
+
+    #[test_case(&chain!(TEST_UNIVERSAL_KEY, [0x02, 0x01, 0x01]).collect_vec(), &[0]; "One at beginning")]
+    #[test_case(&chain!([0x06], TEST_UNIVERSAL_KEY, [0x02, 0x01, 0x00]).collect_vec(), &[1]; "One at offset")]
+    #[test_case(&multiple_uset_buf(), &[1, 25]; "Two at offset")]
+    fn test_start_locations(buf: &[u8], expected: &[u64]) {
+        let ukey = UniversalKey::new(TEST_UNIVERSAL_KEY);
+        assert_eq!(
+            UniversalSet::start_locations(&ukey, &mut Cursor::new(buf)).unwrap(),
+            *expected
+        )
+    }
+
+    #[test_case(&chain!(TEST_UNIVERSAL_KEY, [0x02, 0x01, 0x01]).collect_vec(); "One at beginning")]
+    #[test_case(&chain!([0x06], TEST_UNIVERSAL_KEY, [0x02, 0x01, 0x00]).collect_vec(); "One at offset")]
+    #[test_case(&multiple_uset_buf(); "Two at offset")]
+    #[test_case(&[0x06, 0x0E]; "Shorter than a key")]
+    fn test_location_iter_matches_start_locations(buf: &[u8]) {
+        let ukey = UniversalKey::new(TEST_UNIVERSAL_KEY);
+        assert_eq!(
+            UniversalSet::location_iter(&ukey, &mut Cursor::new(buf))
+                .collect::<Result<Vec<_>, _>>()
+                .unwrap(),
+            UniversalSet::start_locations(&ukey, &mut Cursor::new(buf)).unwrap()
+        )
+    }
+
+    /// An ST 0601 packet stamped `micros` after the epoch, with its mission ID
+    /// set to `id`.
+    fn stamped_packet(micros: Option<u64>, id: &[u8]) -> Vec<u8> {
+        let items = chain!(
+            micros.map(|micros| OwnedKlv::new(2, micros.to_be_bytes().to_vec())),
+            [OwnedKlv::new(3, id.to_vec())]
+        )
+        .collect_vec();
+        crate::local_set::OwnedLocalSet::new(items).serialize(&keys::ST0601, true)
+    }
+
+    #[test]
+    fn test_time_range_selects_middle_packet() {
+        let packets = [
+            stamped_packet(Some(1_000_000), b"A"),
+            stamped_packet(Some(2_000_000), b"B"),
+            stamped_packet(None, b"-"),
+            stamped_packet(Some(3_000_000), b"C"),
+        ];
+        let offsets = packets
+            .iter()
+            .scan(0, |offset, packet| {
+                let start = *offset;
+                *offset += packet.len() as u64;
+                Some(start)
+            })
+            .collect_vec();
+        let buf = Rc::new(RefCell::new(Cursor::new(packets.concat())));
+        let time = |micros| SystemTime::UNIX_EPOCH + std::time::Duration::from_micros(micros);
+
+        let index = UniversalSet::time_index(keys::ST0601, buf.clone()).unwrap();
+        assert_eq!(
+            index.entries(),
+            &vec![
+                (time(1_000_000), offsets[0]),
+                (time(2_000_000), offsets[1]),
+                (time(3_000_000), offsets[3])
+            ]
+        );
+        assert_eq!(index.missing(), &vec![offsets[2]]);
+
+        let sets =
+            UniversalSet::sets_in_time_range(keys::ST0601, buf, time(1_500_000)..time(2_500_000))
+                .unwrap();
+        assert_eq!(sets.len(), 1);
+        assert_eq!(sets[0].data()[&ItemKey::Tag(3)].read_value().unwrap(), b"B");
+    }
+
+    /// Reader that counts how many bytes have been read from it.
+    struct CountingReader {
+        inner: Cursor<Vec<u8>>,
+        bytes_read: usize,
+    }
+
+    impl Read for CountingReader {
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            let n = self.inner.read(buf)?;
+            self.bytes_read += n;
+            Ok(n)
+        }
+    }
+
+    impl Seek for CountingReader {
+        fn seek(&mut self, pos: SeekFrom) -> std::io::Result<u64> {
+            self.inner.seek(pos)
+        }
+    }
+
+    #[test]
+    fn test_location_iter_stops_early() {
+        let ukey = UniversalKey::new(TEST_UNIVERSAL_KEY);
+        let buf = chain!(multiple_uset_buf(), [0x00; 4096]).collect_vec();
+        let mut reader = CountingReader {
+            inner: Cursor::new(buf),
+            bytes_read: 0,
+        };
+
+        let first = UniversalSet::location_iter(&ukey, &mut reader).next();
+
+        assert_eq!(first.unwrap().unwrap(), 1);
+        assert_eq!(reader.bytes_read, 1 + UNIVERSAL_KEY_LENGTH);
+    }
+
+    #[test_case("060e2b34.020b0101.0e010301.01000000"; "Dotted")]
+    #[test_case("urn:smpte:ul:060E2B34.020B0101.0E010301.01000000"; "URN")]
+    #[test_case("06 0E 2B 34 02 0B 01 01 0E 01 03 01 01 00 00 00"; "Spaced")]
+    #[test_case("0x060E2B34020B01010E01030101000000"; "Prefixed")]
+    fn test_parse_key(s: &str) {
+        let key = s.parse::<UniversalKey>().unwrap();
+        assert_eq!(key, UniversalKey::new(TEST_UNIVERSAL_KEY));
+        assert_eq!(
+            key.to_string().parse::<UniversalKey>().unwrap(),
+            UniversalKey::new(TEST_UNIVERSAL_KEY)
+        );
+    }
+
+    #[test_case("060e2b34.01010102.03010210.01000000", true; "Fill version 2")]
+    #[test_case("060e2b34.01010101.03010210.01000000", true; "Fill version 1")]
+    #[test_case("060e2b34.020b0101.0e010301.01000000", false; "ST 0601")]
+    fn test_is_fill(key: &str, expected: bool) {
+        assert_eq!(key.parse::<UniversalKey>().unwrap().is_fill(), expected);
+    }
+
+    static STATIC_KEY: UniversalKey = crate::key!("060e2b34.020b0101.0e010301.01000000");
+
+    #[test]
+    fn test_const_key() {
+        assert_eq!(STATIC_KEY, UniversalKey::new(TEST_UNIVERSAL_KEY));
+        assert_eq!(STATIC_KEY, UniversalKey::from(TEST_UNIVERSAL_KEY));
+    }
+
+    #[test_case(&TEST_UNIVERSAL_KEY, Ok(UniversalKey::new(TEST_UNIVERSAL_KEY)); "Exact")]
+    #[test_case(&TEST_UNIVERSAL_KEY[..15], Err(KeyLengthError(15)); "Too short")]
+    #[test_case(&[0x00; 17], Err(KeyLengthError(17)); "Too long")]
+    fn test_try_from_slice(bytes: &[u8], expected: Result<UniversalKey, KeyLengthError>) {
+        assert_eq!(UniversalKey::try_from(bytes), expected);
+    }
+
+    #[test]
+    #[should_panic(expected = "fewer than 32 hex digits")]
+    fn test_from_hex_too_short() {
+        UniversalKey::from_hex("060e2b34");
+    }
+
+    #[test]
+    fn test_textual_renderings() {
+        let key = UniversalKey::new(TEST_UNIVERSAL_KEY);
+        assert_eq!(
+            key.to_dotted(),
+            "06.0E.2B.34.02.0B.01.01.0E.01.03.01.01.00.00.00"
+        );
+        assert_eq!(
+            key.to_urn(),
+            "urn:smpte:ul:060e2b34.020b0101.0e010301.01000000"
+        );
+        assert_eq!(key.to_dotted().parse::<UniversalKey>().unwrap(), key);
+        assert_eq!(key.to_urn().parse::<UniversalKey>().unwrap(), key);
+    }
+
+    #[test_case("060e2b34", ParseKeyError::InvalidLength(8); "Too short")]
+    #[test_case("urn:smpte:ul:060e2b34020b01010e01030101000000", ParseKeyError::InvalidUrn; "URN without groups")]
+    #[test_case("urn:smpte:ul:060e.2b34.020b0101.0e010301.01000000", ParseKeyError::InvalidUrn; "URN with short group")]
+    #[test_case("urn:smpte:ul:06.0E.2B.34.02.0B.01.01.0E.01.03.01.01.00.00.00", ParseKeyError::InvalidUrn; "URN with dotted bytes")]
+    #[test_case("060e2b34.020b0101.0e010301.0100000g", ParseKeyError::InvalidDigit('g'); "Bad digit")]
+    fn test_parse_key_errors(s: &str, expected: ParseKeyError) {
+        assert_eq!(s.parse::<UniversalKey>().unwrap_err(), expected);
+    }
+
+    #[test_case(1, 18, 4; "First set")]
+    #[test_case(25, 42, 7; "Second set")]
+    fn test_read_extent(key_offset: u64, value_offset: u64, value_length: u64) {
+        let mut buf = Cursor::new(multiple_uset_buf());
+        let extent = UniversalSet::read_extent(&mut buf, key_offset).unwrap();
+        assert_eq!(extent.key_offset(), key_offset);
+        assert_eq!(extent.value_offset(), value_offset);
+        assert_eq!(extent.value_length(), value_length);
+        assert_eq!(extent.end(), value_offset + value_length);
+        assert_eq!(buf.position(), value_offset);
+    }
+
+    #[test_case(&[0x88, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF]; "Past u64")]
+    #[test_case(&[0x88, 0x80, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00]; "2^63")]
+    #[test_case(&[0x88, 0x7F, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF]; "End past i64")]
+    fn read_extent_rejects_huge_lengths(length: &[u8]) {
+        let mut buf = Cursor::new([&TEST_UNIVERSAL_KEY[..], length].concat());
+        assert!(matches!(
+            UniversalSet::read_extent(&mut buf, 0),
+            Err(encoding::Error::ValueOutOfRange { .. })
+        ));
+    }
+
+    #[test_case(ParseOptions { max_packets: Some(1), ..Default::default() }, Limit::Packets, 1; "Packets")]
+    #[test_case(ParseOptions { max_total_value_bytes: Some(10), ..Default::default() }, Limit::TotalValueBytes, 10; "Total value bytes")]
+    #[test_case(ParseOptions { max_items_per_set: Some(1), ..Default::default() }, Limit::ItemsPerSet, 1; "Items per set")]
+    fn limits_keep_sets_read_before_them(options: ParseOptions, limit: Limit, max: u64) {
+        let key = UniversalKey::new(TEST_UNIVERSAL_KEY);
+        let buf = || Rc::new(RefCell::new(Cursor::new(multiple_uset_buf())));
+        let (sets, err) = UniversalSet::read_all_partial(key, buf(), &options);
+        assert_eq!(
+            sets.iter()
+                .map(|set| set.extent().key_offset())
+                .collect_vec(),
+            [1]
+        );
+        assert!(
+            matches!(err, Some(encoding::Error::LimitExceeded(l, m)) if l == limit && m == max)
+        );
+        assert!(UniversalSet::read_all_with_options(key, buf(), &options).is_err());
+
+        let mut sets =
+            UniversalSetReader::new(key, Cursor::new(multiple_uset_buf())).with_limits(&options);
+        assert_eq!(sets.next().unwrap().unwrap().offset(), 1);
+        assert!(matches!(
+            sets.next(),
+            Some(Err(encoding::Error::LimitExceeded(l, m))) if l == limit && m == max
+        ));
+        assert!(sets.next().is_none());
+    }
+
+    #[test_case(ParseOptions { max_total_value_bytes: Some(1000), max_packets: Some(1), ..Default::default() }, Some(Limit::TotalValueBytes); "With limits")]
+    #[test_case(ParseOptions::default(), None; "Without limits")]
+    fn read_all_partial_rejects_huge_lengths(options: ParseOptions, limit: Option<Limit>) {
+        // A length of 2^63 can't be seeked over.
+        let buf = chain!(
+            TEST_UNIVERSAL_KEY,
+            [0x88, 0x80, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00]
+        )
+        .collect_vec();
+        let key = UniversalKey::new(TEST_UNIVERSAL_KEY);
+        let (sets, err) =
+            UniversalSet::read_all_partial(key, Rc::new(RefCell::new(Cursor::new(buf))), &options);
+        assert!(sets.is_empty());
+        match limit {
+            Some(limit) => {
+                assert!(matches!(err, Some(encoding::Error::LimitExceeded(l, 1000)) if l == limit))
+            }
+            None => assert!(matches!(err, Some(encoding::Error::ValueOutOfRange { .. }))),
+        }
+    }
+
+    #[test_case(0, Some(1); "From start of buffer")]
+    #[test_case(1, Some(1); "From start of key")]
+    #[test_case(2, Some(25); "From inside first key")]
+    #[test_case(26, None; "From inside last key")]
+    fn test_find_next(start: u64, expected: Option<u64>) {
+        let ukey = UniversalKey::new(TEST_UNIVERSAL_KEY);
+        assert_eq!(
+            UniversalSet::find_next(&ukey, &mut Cursor::new(multiple_uset_buf()), start).unwrap(),
+            expected
+        )
+    }
+
+    #[test_case(0..48, &[1, 25]; "Whole buffer")]
+    #[test_case(0..17, &[1]; "Ends exactly at end of first key")]
+    #[test_case(0..16, &[]; "Ends one byte before end of first key")]
+    #[test_case(2..41, &[25]; "Starts inside first key")]
+    #[test_case(0..40, &[1]; "Second key extends past end")]
+    #[test_case(10..10, &[]; "Empty range")]
+    fn test_start_locations_in(range: Range<u64>, expected: &[u64]) {
+        let ukey = UniversalKey::new(TEST_UNIVERSAL_KEY);
+        assert_eq!(
+            UniversalSet::start_locations_in(&ukey, &mut Cursor::new(multiple_uset_buf()), range)
+                .unwrap(),
+            *expected
+        )
+    }
+
+    /// Reader that panics if anything tries to seek it.
+    struct NoSeek<'a>(&'a [u8]);
+
+    impl Read for NoSeek<'_> {
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            self.0.read(buf)
+        }
+    }
+
+    impl Seek for NoSeek<'_> {
+        fn seek(&mut self, _: SeekFrom) -> std::io::Result<u64> {
+            panic!("Forward-only reader attempted to seek");
+        }
+    }
+
+    #[test]
+    fn test_universal_set_reader() {
+        let buf = multiple_uset_buf();
+        let sets = UniversalSetReader::new(UniversalKey::new(TEST_UNIVERSAL_KEY), NoSeek(&buf))
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+
+        assert_eq!(
+            sets.iter().map(|set| set.offset()).collect_vec(),
+            vec![1, 25]
+        );
+        assert_eq!(
+            sets.iter().map(|set| set.items().clone()).collect_vec(),
+            vec![
+                vec![OwnedKlv::new(1, vec![0x03, 0x04])],
+                vec![
+                    OwnedKlv::new(1, vec![0x02]),
+                    OwnedKlv::new(2, vec![0x04, 0x08])
+                ],
+            ]
+        );
+    }
+
+    #[test]
+    fn test_universal_set_reader_truncated_payload() {
+        let buf = chain!(TEST_UNIVERSAL_KEY, [0x04, 0x01, 0x02]).collect_vec();
+        let mut reader =
+            UniversalSetReader::new(UniversalKey::new(TEST_UNIVERSAL_KEY), NoSeek(&buf));
+        assert!(matches!(
+            reader.next(),
+            Some(Err(encoding::Error::Truncated("value")))
+        ));
+        assert!(reader.next().is_none());
+    }
+
+    #[test]
+    fn test_universal_set_reader_huge_length() {
+        let buf = chain!(
+            TEST_UNIVERSAL_KEY,
+            [0x88, 0x80, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x01]
+        )
+        .collect_vec();
+        let mut reader =
+            UniversalSetReader::new(UniversalKey::new(TEST_UNIVERSAL_KEY), NoSeek(&buf));
+        assert!(matches!(
+            reader.next(),
+            Some(Err(encoding::Error::ValueOutOfRange { .. }))
+        ));
+        assert!(reader.next().is_none());
+    }
+
+    #[cfg(feature = "tokio")]
+    #[tokio::test]
+    async fn test_universal_set_reader_async_huge_length() {
+        let buf = chain!(
+            TEST_UNIVERSAL_KEY,
+            [0x88, 0x80, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x01]
+        )
+        .collect_vec();
+        let mut reader = UniversalSetReader::new(UniversalKey::new(TEST_UNIVERSAL_KEY), &buf[..]);
+        assert!(matches!(
+            reader.next_async().await,
+            Some(Err(encoding::Error::ValueOutOfRange { .. }))
+        ));
+        assert!(reader.next_async().await.is_none());
+    }
+
+    #[cfg(feature = "tokio")]
+    #[tokio::test]
+    async fn test_universal_set_reader_async() {
+        use tokio::io::AsyncWriteExt;
+
+        let buf = multiple_uset_buf();
+        let (mut writer, reader) = tokio::io::duplex(8);
+        let mut sets = UniversalSetReader::new(UniversalKey::new(TEST_UNIVERSAL_KEY), reader);
+
+        let write = async {
+            let (first_half, second_half) = buf.split_at(buf.len() / 2);
+            writer.write_all(first_half).await.unwrap();
+            writer.write_all(second_half).await.unwrap();
+            drop(writer);
+        };
+        let read = async {
+            let mut found = Vec::new();
+            while let Some(set) = sets.next_async().await {
+                found.push(set.unwrap());
+            }
+            found
+        };
+        let ((), found) = tokio::join!(write, read);
+
+        assert_eq!(
+            found.iter().map(|set| set.offset()).collect_vec(),
+            vec![1, 25]
+        );
+        assert_eq!(
+            found.iter().map(|set| set.items().clone()).collect_vec(),
+            vec![
+                vec![OwnedKlv::new(1, vec![0x03, 0x04])],
+                vec![
+                    OwnedKlv::new(1, vec![0x02]),
+                    OwnedKlv::new(2, vec![0x04, 0x08])
+                ],
+            ]
+        );
+    }
+
+    /// Builds the key locally and returns sets keyed by it, which needs the
+    /// sets to own their key.
+    fn read_test_sets(buf: Vec<u8>) -> Vec<UniversalSet<Cursor<Vec<u8>>>> {
+        let key = UniversalKey::new(TEST_UNIVERSAL_KEY);
+        UniversalSet::read_all(key, Rc::new(RefCell::new(Cursor::new(buf)))).unwrap()
+    }
+
+    #[test]
+    fn test_sets_outlive_key() {
+        let sets = read_test_sets(multiple_uset_buf());
+        assert_eq!(sets.len(), 2);
+        assert_eq!(*sets[0].key(), UniversalKey::new(TEST_UNIVERSAL_KEY));
+    }
+
+    #[test]
+    fn sets_from_different_buffers_share_a_type() {
+        use std::io::Write;
+
+        let mut file = tempfile::tempfile().unwrap();
+        file.write_all(&multiple_uset_buf()).unwrap();
+        file.rewind().unwrap();
+
+        let key = UniversalKey::new(TEST_UNIVERSAL_KEY);
+        let mut sets =
+            UniversalSet::read_all_dyn(key, Box::new(Cursor::new(multiple_uset_buf()))).unwrap();
+        sets.extend(UniversalSet::read_all_dyn(key, Box::new(BufReader::new(file))).unwrap());
+
+        assert_eq!(sets.len(), 4);
+        assert_eq!(
+            sets.iter().map(|set| set.extent().key_offset).collect_vec(),
+            [1, 25, 1, 25]
+        );
+        assert_eq!(sets[0].to_string(), sets[2].to_string());
+        let klv = Klv::new(Rc::new(RefCell::new(
+            Box::new(Cursor::new([0x01u8, 0x01, 0xAA])) as Box<dyn ReadSeek>,
+        )))
+        .unwrap();
+        assert_eq!(klv.read_value().unwrap(), [0xAA]);
+    }
+
+    #[test]
+    fn test_read_all() {
+        let ukey = UniversalKey::new(TEST_UNIVERSAL_KEY);
+        let mut binding = Cursor::new(multiple_uset_buf());
+        let sets = UniversalSet::read_all(ukey, Rc::new(RefCell::new(&mut binding))).unwrap();
+
+        assert_eq!(sets.len(), 2, "Number of universal sets found is incorrect");
+        let first_uset = sets.first().unwrap();
+        assert_eq!(
+            first_uset.data().len(),
             1,
+            "Number of KLV triplets in first universal set is incorrect"
+        );
+        let first_uset_data = first_uset.data();
+        let only_klv = first_uset_data.iter().exactly_one().unwrap();
+        assert_eq!(
+            only_klv.tag(),
+            Some(1),
             "Parsed tag value for only KLV triplet in first universal set in incorrect"
         );
         assert_eq!(
@@ -197,4 +2852,80 @@ mod tests {
             "Parsed value for only KLV triplet in first universal set in incorrect"
         );
     }
+
+    #[test]
+    fn damaged_packet_parses_with_warnings() {
+        // A Mission ID length in the long form and a fill item, in a packet
+        // whose length is padded and whose checksum is stale.
+        let items = chain!(
+            [0x02, 0x08],
+            1_000_000u64.to_be_bytes(),
+            [0x03, 0x81, 0x05],
+            *b"ALPHA",
+            *keys::FILL,
+            [0x02, 0x00, 0x00],
+            [0x01, 0x02, 0x00, 0x00]
+        )
+        .collect_vec();
+        let buf = chain!(
+            keys::ST0601.to_vec(),
+            [0x82, 0x00, items.len() as u8],
+            items
+        )
+        .collect_vec();
+        let options = ParseOptions {
+            verify_checksum: crate::options::ChecksumMode::Warn,
+            ..ParseOptions::default()
+        };
+
+        let set = UniversalSet::new_with_options(
+            keys::ST0601,
+            Rc::new(RefCell::new(Cursor::new(buf))),
+            0,
+            &options,
+        )
+        .unwrap();
+
+        assert_eq!(
+            set.warnings()
+                .iter()
+                .map(|warning| (warning.key(), warning.offset(), warning.kind()))
+                .collect_vec(),
+            [
+                (
+                    ItemKey::Ul(keys::ST0601),
+                    Some(19),
+                    ViolationKind::NonMinimalEncoding {
+                        width: 3,
+                        minimal: 1
+                    }
+                ),
+                (
+                    ItemKey::Tag(3),
+                    Some(32),
+                    ViolationKind::NonMinimalEncoding {
+                        width: 2,
+                        minimal: 1
+                    }
+                ),
+                (ItemKey::Ul(keys::FILL), Some(54), ViolationKind::FillItem),
+                (
+                    ItemKey::Tag(1),
+                    Some(58),
+                    ViolationKind::ChecksumMismatch {
+                        expected: 0,
+                        actual: set_checksum(&set)
+                    }
+                ),
+            ]
+        );
+        assert_eq!(set.data().len(), 3);
+        assert!(set.to_string().ends_with(": 3 items, 4 warnings"));
+    }
+
+    /// Checksum the packet of `set` should have carried.
+    fn set_checksum(set: &UniversalSet<Cursor<Vec<u8>>>) -> u16 {
+        let packet = set.buf.borrow().get_ref().clone();
+        crate::misb::st0601::checksum(&packet[..packet.len() - 2])
+    }
 }
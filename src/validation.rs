@@ -0,0 +1,506 @@
+//! Reports of how closely parsed sets follow the standard that defines them.
+
+use std::{
+    cell::RefCell,
+    fmt,
+    io::{Cursor, Read, Seek, SeekFrom},
+    marker::PhantomData,
+    rc::Rc,
+};
+
+use crate::{
+    encoding::{self, ber::encode_ber, ber_oid::encode_ber_oid},
+    format::SoftwareValue,
+    klv::{ItemContext, ItemKey, Klv},
+    options::{ChecksumMode, ParseOptions},
+    tag::{Tag, TagReading, ValueLength},
+    universal_set::{UNIVERSAL_KEY_LENGTH, UniversalKey, UniversalSet},
+};
+
+/// How serious a violation of the standard is.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum Severity {
+    /// The set can still be used but does not strictly follow the standard.
+    Warning,
+    /// The set breaks a rule of the standard.
+    Error,
+}
+
+/// Rule of the standard that was broken.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum ViolationKind {
+    /// An item the standard requires in every set is missing.
+    MissingRequired,
+    /// The set contains an item the standard does not define.
+    UnknownTag,
+    /// The length of the item's value does not satisfy the standard.
+    InvalidLength { expected: ValueLength, actual: u64 },
+    /// The checksum item does not match the contents of the packet.
+    ChecksumMismatch { expected: u16, actual: u16 },
+    /// The tag or length is written in more bytes than it needs.
+    NonMinimalEncoding { width: u64, minimal: u64 },
+    /// The value can't be converted with the format the standard gives it,
+    /// such as text that is not valid UTF-8 or an integer too large for its
+    /// type.
+    InvalidValue,
+    /// The value of an enumerated item is not one the standard defines.
+    OutOfRange { value: u64, min: u64, max: u64 },
+    /// The item is KLV fill.
+    FillItem,
+}
+
+/// A single way in which a set does not follow its standard.
+#[derive(Clone, Debug, PartialEq, Eq, getset::CopyGetters, getset::Getters)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Violation {
+    #[getset(get_copy = "pub")]
+    severity: Severity,
+    /// Key of the item the violation is about.
+    #[getset(get_copy = "pub")]
+    key: ItemKey,
+    /// Offset of the first byte of the item's value. `None` when the item is
+    /// missing from the set.
+    #[getset(get_copy = "pub")]
+    offset: Option<u64>,
+    #[getset(get_copy = "pub")]
+    kind: ViolationKind,
+    /// Where the item lies, when it is known.
+    #[getset(get = "pub")]
+    context: Option<ItemContext>,
+}
+
+impl Violation {
+    pub fn new(severity: Severity, key: ItemKey, offset: Option<u64>, kind: ViolationKind) -> Self {
+        Self {
+            severity,
+            key,
+            offset,
+            kind,
+            context: None,
+        }
+    }
+
+    /// Attaches where the item the violation is about lies.
+    pub fn in_context(self, context: ItemContext) -> Self {
+        Self {
+            context: Some(context),
+            ..self
+        }
+    }
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Warning => write!(f, "warning"),
+            Self::Error => write!(f, "error"),
+        }
+    }
+}
+
+impl fmt::Display for ViolationKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::MissingRequired => write!(f, "missing required item"),
+            Self::UnknownTag => write!(f, "unknown tag"),
+            Self::InvalidLength { expected, actual } => {
+                write!(f, "{actual} bytes long, expected {expected:?}")
+            }
+            Self::ChecksumMismatch { expected, actual } => {
+                write!(
+                    f,
+                    "checksum {expected:#06x} but contents sum to {actual:#06x}"
+                )
+            }
+            Self::NonMinimalEncoding { width, minimal } => {
+                write!(f, "encoded in {width} bytes where {minimal} would do")
+            }
+            Self::InvalidValue => write!(f, "value can't be converted"),
+            Self::OutOfRange { value, min, max } => {
+                write!(f, "{value} is outside of the enumeration {min}..={max}")
+            }
+            Self::FillItem => write!(f, "fill item"),
+        }
+    }
+}
+
+impl fmt::Display for Violation {
+    /// Formats the violation as `Item <key> at offset <offset>: <kind>`,
+    /// leaving out the offset when the item is missing. The key and offset
+    /// are replaced by the item's context when it has one.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(context) = &self.context {
+            return write!(f, "Item {context}: {}", self.kind);
+        }
+        write!(f, "Item {}", self.key)?;
+        if let Some(offset) = self.offset {
+            write!(f, " at offset {offset}")?;
+        }
+        write!(f, ": {}", self.kind)
+    }
+}
+
+/// Every violation found while validating a set.
+#[derive(Clone, Debug, Default, PartialEq, Eq, getset::Getters)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[getset(get = "pub")]
+pub struct ValidationReport {
+    violations: Vec<Violation>,
+}
+
+impl ValidationReport {
+    pub fn new(violations: Vec<Violation>) -> Self {
+        Self { violations }
+    }
+
+    /// Whether the set broke no rules of the standard. Warnings are allowed.
+    pub fn is_valid(&self) -> bool {
+        self.errors().next().is_none()
+    }
+
+    /// Violations with a severity of `Severity::Error`.
+    pub fn errors(&self) -> impl Iterator<Item = &Violation> {
+        self.violations
+            .iter()
+            .filter(|violation| violation.severity == Severity::Error)
+    }
+
+    /// Violations with a severity of `Severity::Warning`.
+    pub fn warnings(&self) -> impl Iterator<Item = &Violation> {
+        self.violations
+            .iter()
+            .filter(|violation| violation.severity == Severity::Warning)
+    }
+}
+
+/// Tag table and strictness a stream is validated against.
+#[derive(Clone, Copy, Debug)]
+pub struct ValidationProfile<G>
+where
+    G: Tag,
+{
+    strict: bool,
+    table: PhantomData<G>,
+}
+
+impl<G> ValidationProfile<G>
+where
+    G: Tag,
+{
+    /// Reports every finding with its usual severity.
+    pub fn lenient() -> Self {
+        Self {
+            strict: false,
+            table: PhantomData,
+        }
+    }
+
+    /// Reports every finding as an error, so that only packets that follow
+    /// the standard to the letter are valid.
+    ///
+    /// Keys and lengths written in more bytes than they need stay warnings,
+    /// as some hardware encoders pad every length to the same width and the
+    /// packets still follow the standard.
+    pub fn strict() -> Self {
+        Self {
+            strict: true,
+            table: PhantomData,
+        }
+    }
+
+    pub fn is_strict(&self) -> bool {
+        self.strict
+    }
+
+    fn severity(&self, violation: &Violation) -> Severity {
+        if self.strict && !matches!(violation.kind, ViolationKind::NonMinimalEncoding { .. }) {
+            Severity::Error
+        } else {
+            violation.severity
+        }
+    }
+}
+
+/// Findings for a single packet of a stream.
+#[derive(Clone, Debug, PartialEq, Eq, getset::CopyGetters, getset::Getters)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct PacketReport {
+    /// Offset of the first byte of the packet's Universal Key.
+    #[getset(get_copy = "pub")]
+    offset: u64,
+    #[getset(get = "pub")]
+    violations: Vec<Violation>,
+    /// Why the packet could not be parsed, if it couldn't. Its items are not
+    /// checked when it can't.
+    #[getset(get = "pub")]
+    error: Option<String>,
+}
+
+impl PacketReport {
+    /// Whether the packet was parsed and broke no rules of the standard.
+    pub fn is_valid(&self) -> bool {
+        self.error.is_none()
+            && self
+                .violations
+                .iter()
+                .all(|violation| violation.severity == Severity::Warning)
+    }
+}
+
+impl fmt::Display for PacketReport {
+    /// Formats the packet's offset on one line followed by each finding
+    /// indented on its own line.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Packet at offset {}", self.offset)?;
+        if let Some(error) = &self.error {
+            writeln!(f, "  error: {error}")?;
+        }
+        for violation in &self.violations {
+            writeln!(f, "  {}: {violation}", violation.severity)?;
+        }
+        Ok(())
+    }
+}
+
+/// Findings for every packet with the same Universal Key in a stream.
+#[derive(Clone, Debug, PartialEq, Eq, getset::CopyGetters, getset::Getters)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct StreamValidationReport {
+    #[getset(get_copy = "pub")]
+    key: UniversalKey,
+    /// Every packet found, in the order they appear in the stream.
+    #[getset(get = "pub")]
+    packets: Vec<PacketReport>,
+}
+
+impl StreamValidationReport {
+    /// Whether every packet is valid.
+    pub fn is_valid(&self) -> bool {
+        self.packets.iter().all(PacketReport::is_valid)
+    }
+
+    /// Every finding in the stream, along with the offset of its packet.
+    pub fn violations(&self) -> impl Iterator<Item = (u64, &Violation)> {
+        self.packets.iter().flat_map(|packet| {
+            packet
+                .violations
+                .iter()
+                .map(|violation| (packet.offset, violation))
+        })
+    }
+}
+
+impl fmt::Display for StreamValidationReport {
+    /// Formats each packet that has findings. Packets without any are left
+    /// out.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let with_findings = self
+            .packets
+            .iter()
+            .filter(|packet| packet.error.is_some() || !packet.violations.is_empty())
+            .collect::<Vec<_>>();
+        writeln!(
+            f,
+            "{} packets keyed by {}, {} with findings",
+            self.packets.len(),
+            self.key,
+            with_findings.len()
+        )?;
+        for packet in with_findings {
+            write!(f, "{packet}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Checks every packet keyed by `key` in the buffer against the tag table of
+/// `profile`, starting from its first byte wherever the buffer is.
+///
+/// On top of `LocalSet::validate`, tags and lengths written in more bytes
+/// than they need, values that can't be converted, ST 0601 checksums that
+/// don't match, and KLV fill items are reported. Checksum mismatches are
+/// errors and fill items are warnings.
+///
+/// # Returns
+///
+/// - `Ok(StreamValidationReport)` - Every packet was visited. Packets that
+///   could not be parsed are recorded in the report.
+/// - `Err(encoding::Error)` - The buffer could not be searched for the key.
+///
+/// # Side Effects
+///
+/// Moves the current position in the buffer.
+pub fn validate_stream<G, T>(
+    key: impl Into<UniversalKey>,
+    buf: Rc<RefCell<T>>,
+    profile: &ValidationProfile<G>,
+) -> Result<StreamValidationReport, encoding::Error>
+where
+    G: Tag,
+    T: Read + Seek,
+{
+    let key = key.into();
+    let locations = {
+        let mut buf = buf.borrow_mut();
+        buf.seek(SeekFrom::Start(0))?;
+        UniversalSet::start_locations(&key, &mut *buf)?
+    };
+    let options = validation_options();
+
+    let mut packets = Vec::new();
+    for offset in locations {
+        let packet = match validate_packet(key, buf.clone(), offset, profile, &options) {
+            Ok(violations) => PacketReport {
+                offset,
+                violations,
+                error: None,
+            },
+            Err(error) => PacketReport {
+                offset,
+                violations: Vec::new(),
+                error: Some(error.to_string()),
+            },
+        };
+        packets.push(packet);
+    }
+    Ok(StreamValidationReport { key, packets })
+}
+
+/// Options packets are parsed with to be validated, which keep every item
+/// and report bad checksums rather than failing.
+fn validation_options() -> ParseOptions {
+    ParseOptions {
+        skip_fill: false,
+        verify_checksum: ChecksumMode::Warn,
+        ..ParseOptions::default()
+    }
+}
+
+/// Validates the single packet keyed by `key` making up `packet` the same
+/// way as `validate_stream`.
+pub(crate) fn validate_packet_bytes<G>(
+    key: UniversalKey,
+    packet: &[u8],
+    profile: &ValidationProfile<G>,
+) -> Result<Vec<Violation>, encoding::Error>
+where
+    G: Tag,
+{
+    let buf = Rc::new(RefCell::new(Cursor::new(packet)));
+    validate_packet(key, buf, 0, profile, &validation_options())
+}
+
+fn validate_packet<G, T>(
+    key: UniversalKey,
+    buf: Rc<RefCell<T>>,
+    offset: u64,
+    profile: &ValidationProfile<G>,
+    options: &ParseOptions,
+) -> Result<Vec<Violation>, encoding::Error>
+where
+    G: Tag,
+    T: Read + Seek,
+{
+    let extent = UniversalSet::read_extent(&mut *buf.borrow_mut(), offset)?;
+    let set = UniversalSet::new_with_options(key, buf, offset, options)?;
+
+    let mut violations = Vec::new();
+    let length_width = extent.value_offset() - offset - UNIVERSAL_KEY_LENGTH as u64;
+    let minimal = encode_ber(extent.value_length().into()).len() as u64;
+    if length_width > minimal {
+        violations.push(Violation::new(
+            Severity::Warning,
+            ItemKey::Ul(key),
+            Some(extent.value_offset()),
+            ViolationKind::NonMinimalEncoding {
+                width: length_width,
+                minimal,
+            },
+        ));
+    }
+
+    violations.extend(set.data().validate::<G>().violations);
+    violations.extend(
+        set.data()
+            .warnings()
+            .iter()
+            .filter(|warning| matches!(warning.kind, ViolationKind::ChecksumMismatch { .. }))
+            .map(|warning| Violation {
+                severity: Severity::Error,
+                ..warning.clone()
+            }),
+    );
+    for (index, klv) in set.data().iter().enumerate() {
+        let context = ItemContext::new::<G>(klv.key(), index, klv.value_offset());
+        violations.extend(
+            check_item::<G, T>(klv)?
+                .into_iter()
+                .map(|violation| violation.in_context(context.clone())),
+        );
+    }
+
+    for violation in &mut violations {
+        violation.severity = profile.severity(violation);
+    }
+    violations.sort_by_key(|violation| violation.offset.unwrap_or(offset));
+    Ok(violations)
+}
+
+/// Findings `LocalSet::validate` does not look for in a single item.
+fn check_item<G, T>(klv: &Klv<T>) -> Result<Vec<Violation>, encoding::Error>
+where
+    G: Tag,
+    T: Read + Seek,
+{
+    let offset = Some(klv.value_offset());
+    let violation = |severity, kind| Violation::new(severity, klv.key(), offset, kind);
+    if klv.is_fill() {
+        return Ok(vec![violation(Severity::Warning, ViolationKind::FillItem)]);
+    }
+
+    let mut violations = Vec::new();
+    if let Some(tag) = klv.tag() {
+        let width = klv.key_width();
+        let minimal = encode_ber_oid(tag).len() as u64;
+        if width > minimal {
+            violations.push(violation(
+                Severity::Warning,
+                ViolationKind::NonMinimalEncoding { width, minimal },
+            ));
+        }
+    }
+    let width = klv.length_width();
+    let minimal = encode_ber(klv.length().into()).len() as u64;
+    if width > minimal {
+        violations.push(violation(
+            Severity::Warning,
+            ViolationKind::NonMinimalEncoding { width, minimal },
+        ));
+    }
+
+    if let Some(tag) = klv.tag().and_then(|number| G::try_from(number).ok())
+        && tag.format().is_some()
+    {
+        match tag.decode(&klv.read_value()?) {
+            Err(_) => violations.push(violation(Severity::Error, ViolationKind::InvalidValue)),
+            Ok(TagReading::Value(SoftwareValue::Uint64(value))) => {
+                if let Some(values) = tag.enumeration()
+                    && !values.contains(&value)
+                {
+                    violations.push(violation(
+                        Severity::Error,
+                        ViolationKind::OutOfRange {
+                            value,
+                            min: *values.start(),
+                            max: *values.end(),
+                        },
+                    ));
+                }
+            }
+            Ok(_) => {}
+        }
+    }
+    Ok(violations)
+}
@@ -0,0 +1,85 @@
+#![cfg(feature = "std")]
+
+use std::{cell::RefCell, io::Cursor, rc::Rc};
+
+use klv::{prelude::*, validation::ViolationKind};
+use test_case::test_case;
+
+const PACKET: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/data/st0601_packet.bin");
+
+/// The packet with one byte of its mission ID changed.
+fn corrupted() -> Vec<u8> {
+    let mut packet = std::fs::read(PACKET).unwrap();
+    let i = packet.iter().position(|&byte| byte == b'M').unwrap();
+    packet[i] = b'N';
+    packet
+}
+
+fn options(mode: ChecksumMode) -> ParseOptions {
+    ParseOptions {
+        verify_checksum: mode,
+        ..ParseOptions::default()
+    }
+}
+
+fn parse(packet: Vec<u8>, mode: ChecksumMode) -> Result<Vec<ViolationKind>, Error> {
+    let buf = Rc::new(RefCell::new(Cursor::new(packet)));
+    let set = UniversalSet::new_with_options(keys::ST0601, buf, 0, &options(mode))?;
+    Ok(set
+        .data()
+        .warnings()
+        .iter()
+        .map(|violation| violation.kind())
+        .collect())
+}
+
+#[test_case(ChecksumMode::Ignore; "ignore")]
+#[test_case(ChecksumMode::Warn; "warn")]
+#[test_case(ChecksumMode::Reject; "reject")]
+fn good_packet_is_accepted(mode: ChecksumMode) {
+    assert_eq!(parse(std::fs::read(PACKET).unwrap(), mode).unwrap(), vec![]);
+}
+
+#[test]
+fn corrupted_packet_is_ignored() {
+    assert_eq!(parse(corrupted(), ChecksumMode::Ignore).unwrap(), vec![]);
+}
+
+#[test]
+fn corrupted_packet_warns() {
+    assert_eq!(
+        parse(corrupted(), ChecksumMode::Warn).unwrap(),
+        vec![ViolationKind::ChecksumMismatch {
+            expected: 0x4580,
+            actual: 0x4581
+        }]
+    );
+}
+
+#[test]
+fn corrupted_packet_is_rejected() {
+    assert!(matches!(
+        parse(corrupted(), ChecksumMode::Reject),
+        Err(Error::ChecksumMismatch {
+            offset: 0,
+            expected: 0x4580,
+            ..
+        })
+    ));
+}
+
+#[test_case(ChecksumMode::Ignore, 2; "ignore")]
+#[test_case(ChecksumMode::Warn, 2; "warn")]
+#[test_case(ChecksumMode::Reject, 1; "reject")]
+fn read_all_skips_rejected_packets(mode: ChecksumMode, expected: usize) {
+    let stream = [corrupted(), std::fs::read(PACKET).unwrap()].concat();
+    let buf = Rc::new(RefCell::new(Cursor::new(stream)));
+    let sets = UniversalSet::read_all_with_options(keys::ST0601, buf, &options(mode)).unwrap();
+    assert_eq!(sets.len(), expected);
+    assert_eq!(
+        sets.iter()
+            .map(|set| set.data().warnings().len())
+            .sum::<usize>(),
+        usize::from(mode == ChecksumMode::Warn)
+    );
+}
@@ -0,0 +1,72 @@
+#![cfg(feature = "std")]
+
+use std::{
+    collections::HashMap,
+    io::{Cursor, Write},
+};
+
+use itertools::{Itertools, chain};
+use klv::{
+    demux::split_by_key, klv::OwnedKlv, local_set::OwnedLocalSet, prelude::*,
+    universal_set::OwnedUniversalSet,
+};
+
+const PACKET: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/data/st0601_packet.bin");
+
+fn eg0104_packet(name: &[u8]) -> Vec<u8> {
+    OwnedLocalSet::new(vec![OwnedKlv::new(3, name.to_vec())]).serialize(&keys::EG0104, false)
+}
+
+fn sets(key: UniversalKey, file: &tempfile::NamedTempFile) -> Vec<OwnedUniversalSet> {
+    UniversalSet::iter_from_path(&key, file.path())
+        .unwrap()
+        .collect::<Result<_, _>>()
+        .unwrap()
+}
+
+#[test]
+fn interleaved_keys_are_split() {
+    let st0601 = std::fs::read(PACKET).unwrap();
+    let buf = chain!(
+        [0xAA; 3],
+        st0601.iter().copied(),
+        eg0104_packet(b"ONE"),
+        [0xBB; 5],
+        st0601.iter().copied(),
+        eg0104_packet(b"TWO"),
+        [0xCC; 2]
+    )
+    .collect_vec();
+
+    let st0601_file = tempfile::NamedTempFile::new().unwrap();
+    let eg0104_file = tempfile::NamedTempFile::new().unwrap();
+    let mut outputs: HashMap<UniversalKey, Box<dyn Write>> = HashMap::from([
+        (
+            keys::ST0601,
+            Box::new(st0601_file.reopen().unwrap()) as Box<dyn Write>,
+        ),
+        (keys::EG0104, Box::new(eg0104_file.reopen().unwrap())),
+    ]);
+    let mut rest = Vec::new();
+    let stats = split_by_key(&mut Cursor::new(buf), &mut outputs, Some(&mut rest)).unwrap();
+    drop(outputs);
+
+    assert_eq!(stats.packets()[&keys::ST0601], 2);
+    assert_eq!(stats.packets()[&keys::EG0104], 2);
+    assert_eq!(stats.unmatched_bytes(), 10);
+    assert_eq!(rest, chain!([0xAA; 3], [0xBB; 5], [0xCC; 2]).collect_vec());
+
+    assert_eq!(
+        std::fs::read(st0601_file.path()).unwrap(),
+        [st0601.as_slice(), &st0601].concat()
+    );
+    let st0601_sets = sets(keys::ST0601, &st0601_file);
+    assert_eq!(st0601_sets.len(), 2);
+    assert_eq!(st0601_sets[0].items(), st0601_sets[1].items());
+
+    let names = sets(keys::EG0104, &eg0104_file)
+        .iter()
+        .map(|set| set.items()[0].value().clone())
+        .collect_vec();
+    assert_eq!(names, [b"ONE".to_vec(), b"TWO".to_vec()]);
+}
@@ -0,0 +1,53 @@
+#![cfg(feature = "std")]
+
+use std::{cell::RefCell, io::Cursor, rc::Rc};
+
+use klv::{
+    display::{TagTable, render_table},
+    misb::st0601::St0601Tag,
+    prelude::*,
+};
+
+const PACKET: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/data/st0601_packet.bin");
+
+fn packet() -> UniversalSet<Cursor<Vec<u8>>> {
+    let buf = Rc::new(RefCell::new(Cursor::new(std::fs::read(PACKET).unwrap())));
+    UniversalSet::new(keys::ST0601, buf, 0).unwrap()
+}
+
+#[test]
+fn render_packet_without_names() {
+    assert_eq!(
+        render_table(packet().data(), None),
+        "\
+Tag  Name  Length  Value               Unit
+2               8  00046050584e0180
+3               9  4d495353494f4e3031
+5               2  71c2
+13              4  5595b66d
+14              4  5b5360c4
+15              2  c221
+65              1  11
+1               2  4580
+"
+    );
+}
+
+#[test]
+fn render_packet_with_names() {
+    let names = TagTable::<St0601Tag>::new();
+    assert_eq!(
+        render_table(packet().data(), Some(&names)),
+        "\
+Tag  Name                            Length  Value               Unit
+2    Precision Time Stamp                 8  1231798102000000
+3    Mission ID                           9  MISSION01
+5    Platform Heading Angle               2  159.97436484321355  deg
+13   Sensor Latitude                      4  60.176822966978335  deg
+14   Sensor Longitude                     4  128.42675904204452  deg
+15   Sensor True Altitude                 2  14190.719462882429  m
+65   UAS Datalink LS Version Number       1  17
+1    Checksum                             2  4580
+"
+    );
+}
@@ -0,0 +1,37 @@
+#![cfg(feature = "std")]
+
+use std::{cell::RefCell, io::Cursor, rc::Rc};
+
+use klv::{misb::st0601, prelude::*};
+
+const PACKET: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/data/st0601_packet.bin");
+
+#[test]
+fn edit_and_serialize_packet() {
+    let buf = Rc::new(RefCell::new(Cursor::new(std::fs::read(PACKET).unwrap())));
+    let original = UniversalSet::new(keys::ST0601, buf, 0).unwrap();
+    let mission_id = ItemKey::Tag(3);
+    assert_eq!(
+        original.data()[&mission_id].read_value().unwrap(),
+        b"MISSION01"
+    );
+
+    let mut edited = original.data().to_mutable().unwrap();
+    edited.set(mission_id, b"REDACTED-MISSION".to_vec());
+    let packet = edited.serialize(&keys::ST0601, true);
+
+    let buf = Rc::new(RefCell::new(Cursor::new(packet.clone())));
+    let reparsed = UniversalSet::new(keys::ST0601, buf, 0).unwrap();
+    assert_eq!(
+        reparsed.data()[&mission_id].read_value().unwrap(),
+        b"REDACTED-MISSION"
+    );
+    assert_eq!(reparsed.data().len(), original.data().len());
+
+    let (body, checksum) = packet.split_at(packet.len() - 2);
+    assert_eq!(st0601::checksum(body).to_be_bytes(), checksum);
+    assert_eq!(
+        reparsed.data()[&ItemKey::Tag(1)].read_value().unwrap(),
+        checksum
+    );
+}
@@ -0,0 +1,75 @@
+#![cfg(feature = "std")]
+
+use std::{cell::RefCell, io::Cursor, rc::Rc};
+
+use itertools::Itertools;
+use klv::{options::TagFilter, prelude::*};
+
+const PACKET: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/data/st0601_packet.bin");
+
+fn options(mode: ChecksumMode) -> ParseOptions {
+    ParseOptions {
+        verify_checksum: mode,
+        tag_filter: Some(TagFilter::keys([ItemKey::Tag(2), ItemKey::Tag(13)])),
+        ..ParseOptions::default()
+    }
+}
+
+fn read_all(stream: Vec<u8>, options: &ParseOptions) -> Vec<UniversalSet<Cursor<Vec<u8>>>> {
+    let buf = Rc::new(RefCell::new(Cursor::new(stream)));
+    UniversalSet::read_all_with_options(keys::ST0601, buf, options).unwrap()
+}
+
+#[test]
+fn only_wanted_items_are_kept() {
+    let packet = std::fs::read(PACKET).unwrap();
+    let sets = read_all(
+        [packet.clone(), packet].concat(),
+        &options(ChecksumMode::Reject),
+    );
+
+    assert_eq!(sets.len(), 2);
+    for set in &sets {
+        assert_eq!(
+            set.data().keys().copied().collect_vec(),
+            [ItemKey::Tag(2), ItemKey::Tag(13)]
+        );
+        assert_eq!(
+            set.data()[&ItemKey::Tag(13)].read_value().unwrap(),
+            [0x55, 0x95, 0xB6, 0x6D]
+        );
+    }
+}
+
+#[test]
+fn filtered_packets_are_still_checked() {
+    let packet = std::fs::read(PACKET).unwrap();
+    let mut corrupted = packet.clone();
+    let i = corrupted.iter().position(|&byte| byte == b'M').unwrap();
+    corrupted[i] = b'N';
+
+    let sets = read_all(
+        [corrupted.clone(), packet].concat(),
+        &options(ChecksumMode::Reject),
+    );
+    assert_eq!(sets.len(), 1);
+
+    let sets = read_all(corrupted, &options(ChecksumMode::Warn));
+    assert_eq!(sets[0].data().warnings().len(), 1);
+    assert_eq!(sets[0].data().len(), 2);
+}
+
+#[test]
+fn predicate_filter() {
+    let options = ParseOptions {
+        tag_filter: Some(TagFilter::predicate(|key| {
+            key.tag().is_some_and(|tag| tag > 10)
+        })),
+        ..ParseOptions::default()
+    };
+    let sets = read_all(std::fs::read(PACKET).unwrap(), &options);
+    assert_eq!(
+        sets[0].data().keys().copied().collect_vec(),
+        [13, 14, 15, 65].map(ItemKey::Tag)
+    );
+}
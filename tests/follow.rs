@@ -0,0 +1,130 @@
+#![cfg(feature = "std")]
+
+use std::{
+    fs::OpenOptions,
+    io::Write,
+    path::Path,
+    time::{Duration, Instant},
+};
+
+use itertools::{Itertools, chain};
+use klv::{
+    follow::FollowReader,
+    klv::OwnedKlv,
+    universal_set::{UNIVERSAL_KEY_LENGTH, UniversalKey},
+};
+
+const TEST_UNIVERSAL_KEY: [u8; UNIVERSAL_KEY_LENGTH] = [
+    0x06, 0x0E, 0x2B, 0x34, 0x02, 0x0B, 0x01, 0x01, 0x0E, 0x01, 0x03, 0x01, 0x01, 0x00, 0x00, 0x00,
+];
+
+fn append(path: &Path, bytes: &[u8]) {
+    let mut file = OpenOptions::new().append(true).open(path).unwrap();
+    file.write_all(bytes).unwrap();
+}
+
+#[test]
+fn follows_growing_file() {
+    let file = tempfile::NamedTempFile::new().unwrap();
+    let key = UniversalKey::new(TEST_UNIVERSAL_KEY);
+    let mut reader = FollowReader::from_path(key, file.path()).unwrap();
+
+    assert_eq!(reader.poll().unwrap(), None);
+
+    // Junk followed by the first half of the key.
+    append(
+        file.path(),
+        &chain!([0xFF, 0xFF], TEST_UNIVERSAL_KEY[..8].iter().copied()).collect_vec(),
+    );
+    assert_eq!(reader.poll().unwrap(), None);
+
+    // Rest of the key, the length and only part of the payload.
+    append(
+        file.path(),
+        &chain!(TEST_UNIVERSAL_KEY[8..].iter().copied(), [0x07, 0x01, 0x01]).collect_vec(),
+    );
+    assert_eq!(reader.poll().unwrap(), None);
+    assert_eq!(reader.poll().unwrap(), None);
+
+    // Rest of the payload.
+    append(file.path(), &[0x02, 0x02, 0x02, 0x04, 0x08]);
+    let set = reader.poll().unwrap().unwrap();
+    assert_eq!(set.offset(), 2);
+    assert_eq!(
+        set.items(),
+        &vec![
+            OwnedKlv::new(1, vec![0x02]),
+            OwnedKlv::new(2, vec![0x04, 0x08])
+        ]
+    );
+    assert_eq!(reader.poll().unwrap(), None);
+
+    append(
+        file.path(),
+        &chain!(TEST_UNIVERSAL_KEY, [0x03, 0x05, 0x01, 0xAA]).collect_vec(),
+    );
+    let set = reader.poll().unwrap().unwrap();
+    assert_eq!(set.offset(), 26);
+    assert_eq!(set.items(), &vec![OwnedKlv::new(5, vec![0xAA])]);
+}
+
+#[test]
+fn wait_for_data_times_out() {
+    let file = tempfile::NamedTempFile::new().unwrap();
+    let key = UniversalKey::new(TEST_UNIVERSAL_KEY);
+    let mut reader = FollowReader::from_path(key, file.path())
+        .unwrap()
+        .with_poll_interval(Duration::from_millis(1));
+
+    let start = Instant::now();
+    assert_eq!(
+        reader.wait_for_data(Duration::from_millis(20)).unwrap(),
+        None
+    );
+    assert!(start.elapsed() >= Duration::from_millis(20));
+
+    append(
+        file.path(),
+        &chain!(TEST_UNIVERSAL_KEY, [0x03, 0x05, 0x01, 0xAA]).collect_vec(),
+    );
+    assert!(
+        reader
+            .wait_for_data(Duration::from_secs(1))
+            .unwrap()
+            .is_some()
+    );
+}
+
+#[test]
+fn set_too_long_to_wait_for_is_skipped() {
+    let file = tempfile::NamedTempFile::new().unwrap();
+    let key = UniversalKey::new(TEST_UNIVERSAL_KEY);
+    let mut reader = FollowReader::from_path(key, file.path())
+        .unwrap()
+        .with_max_set_length(1024);
+
+    // Key followed by a corrupt length the file will never grow to.
+    append(
+        file.path(),
+        &chain!(
+            TEST_UNIVERSAL_KEY,
+            [0x84, 0x7F, 0xFF, 0xFF, 0xFF, 0x01, 0x02]
+        )
+        .collect_vec(),
+    );
+    assert!(matches!(
+        reader.poll(),
+        Err(klv::encoding::Error::File { source, .. })
+            if matches!(*source, klv::encoding::Error::ValueOutOfRange { .. })
+    ));
+    assert_eq!(reader.position(), UNIVERSAL_KEY_LENGTH as u64);
+    assert_eq!(reader.poll().unwrap(), None);
+
+    append(
+        file.path(),
+        &chain!(TEST_UNIVERSAL_KEY, [0x03, 0x05, 0x01, 0xAA]).collect_vec(),
+    );
+    let set = reader.poll().unwrap().unwrap();
+    assert_eq!(set.offset(), 23);
+    assert_eq!(set.items(), &vec![OwnedKlv::new(5, vec![0xAA])]);
+}
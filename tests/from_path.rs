@@ -0,0 +1,97 @@
+#![cfg(feature = "std")]
+
+use std::io::Write;
+
+use itertools::{Itertools, chain};
+use klv::{
+    encoding::Error,
+    klv::{KlvReader, OwnedKlv},
+    universal_set::{UNIVERSAL_KEY_LENGTH, UniversalKey, UniversalSet},
+};
+
+const TEST_UNIVERSAL_KEY: [u8; UNIVERSAL_KEY_LENGTH] = [
+    0x06, 0x0E, 0x2B, 0x34, 0x02, 0x0B, 0x01, 0x01, 0x0E, 0x01, 0x03, 0x01, 0x01, 0x00, 0x00, 0x00,
+];
+
+fn write_temp(bytes: &[u8]) -> tempfile::NamedTempFile {
+    let mut file = tempfile::NamedTempFile::new().unwrap();
+    file.write_all(bytes).unwrap();
+    file.flush().unwrap();
+    file
+}
+
+#[test]
+fn read_all_from_path() {
+    let file = write_temp(
+        &chain!(
+            [0x06],
+            TEST_UNIVERSAL_KEY,
+            [0x04],
+            [0x01, 0x02, 0x03, 0x04],
+            [0xFF, 0xFF, 0x06],
+            TEST_UNIVERSAL_KEY,
+            [0x07],
+            [0x01, 0x01, 0x02],
+            [0x02, 0x02, 0x04, 0x08]
+        )
+        .collect_vec(),
+    );
+
+    let key = UniversalKey::new(TEST_UNIVERSAL_KEY);
+    let sets = UniversalSet::read_all_from_path(&key, file.path()).unwrap();
+    assert_eq!(sets.iter().map(|s| s.offset()).collect_vec(), vec![1, 25]);
+    assert_eq!(sets[0].items(), &vec![OwnedKlv::new(1, vec![0x03, 0x04])]);
+    assert_eq!(
+        sets[1].items(),
+        &vec![
+            OwnedKlv::new(1, vec![0x02]),
+            OwnedKlv::new(2, vec![0x04, 0x08])
+        ]
+    );
+}
+
+#[test]
+fn iter_from_path_error_carries_path() {
+    let file = write_temp(&chain!(TEST_UNIVERSAL_KEY, [0x05, 0x01, 0x01]).collect_vec());
+
+    let key = UniversalKey::new(TEST_UNIVERSAL_KEY);
+    let err = UniversalSet::iter_from_path(&key, file.path())
+        .unwrap()
+        .next()
+        .unwrap()
+        .unwrap_err();
+    match err {
+        Error::File { path, source } => {
+            assert_eq!(path, file.path());
+            assert!(matches!(*source, Error::Truncated("value")));
+        }
+        other => panic!("Expected a file error, got {other:?}"),
+    }
+}
+
+#[test]
+fn klv_reader_from_path() {
+    let file = write_temp(&[0x01, 0x02, 0xAA, 0xBB, 0x02, 0x01, 0xCC]);
+
+    let items = KlvReader::from_path(file.path())
+        .unwrap()
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+    assert_eq!(
+        items,
+        vec![
+            OwnedKlv::new(1, vec![0xAA, 0xBB]),
+            OwnedKlv::new(2, vec![0xCC])
+        ]
+    );
+}
+
+#[test]
+fn missing_file_error_carries_path() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("missing.klv");
+
+    let err = KlvReader::from_path(&path).unwrap_err();
+    assert!(matches!(err, Error::File { path: ref p, .. } if *p == path));
+    assert!(err.to_string().contains("missing.klv"));
+}
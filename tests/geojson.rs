@@ -0,0 +1,88 @@
+#![cfg(feature = "geo")]
+
+use std::{cell::RefCell, io::Cursor, rc::Rc};
+
+use itertools::Itertools;
+use klv::{
+    format::SoftwareValue,
+    misb::{geojson::track_from_sets, st0601::St0601Tag},
+    prelude::*,
+};
+
+const GOLDEN: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/data/track.geojson");
+
+/// An ST 0601 packet with the given sensor and frame center positions.
+/// `None` for the sensor latitude writes the out-of-range sentinel.
+fn packet(timestamp: u64, sensor: (Option<f64>, f64, f64), frame_center: (f64, f64)) -> Vec<u8> {
+    let float = |tag: St0601Tag, value: f64| {
+        OwnedKlv::new(
+            tag.into(),
+            tag.to_klv(&SoftwareValue::Float64(value)).unwrap(),
+        )
+    };
+    let latitude = match sensor.0 {
+        Some(latitude) => float(St0601Tag::SensorLatitude, latitude),
+        None => OwnedKlv::new(
+            St0601Tag::SensorLatitude.into(),
+            vec![0x80, 0x00, 0x00, 0x00],
+        ),
+    };
+    let items = vec![
+        OwnedKlv::new(2, timestamp.to_be_bytes().to_vec()),
+        latitude,
+        float(St0601Tag::SensorLongitude, sensor.1),
+        float(St0601Tag::SensorTrueAltitude, sensor.2),
+        float(St0601Tag::FrameCenterLatitude, frame_center.0),
+        float(St0601Tag::FrameCenterLongitude, frame_center.1),
+    ];
+    OwnedLocalSet::new(items).serialize(&keys::ST0601, true)
+}
+
+/// Asserts two JSON documents are the same, allowing numbers to differ by
+/// the precision lost writing them out.
+fn assert_near(actual: &serde_json::Value, expected: &serde_json::Value) {
+    use serde_json::Value;
+    match (actual, expected) {
+        (Value::Number(actual), Value::Number(expected)) => {
+            let (actual, expected) = (actual.as_f64().unwrap(), expected.as_f64().unwrap());
+            assert!((actual - expected).abs() < 1e-9, "{actual} != {expected}");
+        }
+        (Value::Array(actual), Value::Array(expected)) => {
+            assert_eq!(actual.len(), expected.len());
+            actual
+                .iter()
+                .zip(expected)
+                .for_each(|(actual, expected)| assert_near(actual, expected));
+        }
+        (Value::Object(actual), Value::Object(expected)) => {
+            assert_eq!(actual.keys().collect_vec(), expected.keys().collect_vec());
+            actual
+                .values()
+                .zip(expected.values())
+                .for_each(|(actual, expected)| assert_near(actual, expected));
+        }
+        _ => assert_eq!(actual, expected),
+    }
+}
+
+#[test]
+fn track_matches_golden_file() {
+    let stream = [
+        packet(1_000_000, (Some(38.5), -77.25, 1000.0), (38.51, -77.2)),
+        packet(2_000_000, (Some(38.5125), -77.25, 1010.0), (38.52, -77.2)),
+        packet(3_000_000, (None, -77.25, 1020.0), (38.53, -77.2)),
+    ]
+    .concat();
+    let buf = Rc::new(RefCell::new(Cursor::new(stream)));
+    let sets = UniversalSet::read_all(keys::ST0601, buf)
+        .unwrap()
+        .iter()
+        .map(|set| TypedLocalSet::<St0601Tag>::from_local_set(set.data()).unwrap())
+        .collect_vec();
+
+    let track = track_from_sets(&sets);
+    let actual: serde_json::Value = serde_json::to_value(&track).unwrap();
+    let expected: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(GOLDEN).unwrap()).unwrap();
+    assert_near(&actual, &expected);
+}
@@ -0,0 +1,151 @@
+#![cfg(feature = "cli")]
+
+use std::{io::Write, process::Command};
+
+use itertools::{Itertools, chain};
+use klv::keys;
+
+fn write_temp(bytes: &[u8]) -> tempfile::NamedTempFile {
+    let mut file = tempfile::NamedTempFile::new().unwrap();
+    file.write_all(bytes).unwrap();
+    file.flush().unwrap();
+    file
+}
+
+fn klv_dump(args: &[&str]) -> (bool, String) {
+    let output = Command::new(env!("CARGO_BIN_EXE_klv-dump"))
+        .args(args)
+        .output()
+        .unwrap();
+    (
+        output.status.success(),
+        String::from_utf8(output.stdout).unwrap(),
+    )
+}
+
+fn two_sets() -> Vec<u8> {
+    chain!(
+        [0xFF],
+        *keys::ST0601,
+        [0x04, 0x01, 0x02, 0x41, 0x42],
+        *keys::ST0601,
+        [0x03, 0x02, 0x01, 0xCC]
+    )
+    .collect_vec()
+}
+
+#[test]
+fn prints_one_line_per_item() {
+    let file = write_temp(&two_sets());
+    let (success, stdout) = klv_dump(&[file.path().to_str().unwrap()]);
+    assert!(success);
+
+    let lines = stdout.lines().collect_vec();
+    assert_eq!(lines.len(), 2);
+    assert_eq!(
+        lines[0].split_whitespace().take(5).collect_vec(),
+        ["18", "1", "2", "41", "42"]
+    );
+    assert!(lines[0].ends_with("|AB|"));
+    assert_eq!(
+        lines[1].split_whitespace().take(4).collect_vec(),
+        ["39", "2", "1", "CC"]
+    );
+}
+
+#[test]
+fn json_and_stats() {
+    let file = write_temp(&two_sets());
+    let (success, stdout) = klv_dump(&[file.path().to_str().unwrap(), "--json", "--stats"]);
+    assert!(success);
+
+    let lines = stdout.lines().collect_vec();
+    assert_eq!(lines.len(), 3);
+    assert!(lines[0].starts_with('{'));
+    assert!(lines[0].contains(r#""offset":1"#));
+    assert!(lines[1].contains(r#""items":[{"key":2,"value":[204]}]"#));
+    assert!(lines[1].contains(r#""key":"060e2b34.020b0101.0e010301.01000000""#));
+    assert_eq!(
+        lines[2],
+        "sets: 2, items: 2, set bytes: 41, value bytes: 3, errors: 0"
+    );
+}
+
+#[test]
+fn parse_errors_fail_unless_lenient() {
+    // The item in the first set claims more bytes than the set holds.
+    let buf = chain!(
+        *keys::ST0601,
+        [0x02, 0x01, 0x05],
+        *keys::ST0601,
+        [0x03, 0x02, 0x01, 0xCC]
+    )
+    .collect_vec();
+    let file = write_temp(&buf);
+    let path = file.path().to_str().unwrap();
+
+    let (success, stdout) = klv_dump(&[path]);
+    assert!(!success);
+    assert!(stdout.is_empty());
+
+    let (success, stdout) = klv_dump(&[path, "--lenient"]);
+    assert!(success);
+    assert_eq!(stdout.lines().count(), 1);
+}
+
+#[test]
+fn corrupt_set_length_is_skipped_when_lenient() {
+    // The first set claims a length past the end of any file.
+    let buf = chain!(
+        *keys::ST0601,
+        [0x88, 0x80, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00],
+        *keys::ST0601,
+        [0x03, 0x02, 0x01, 0xCC]
+    )
+    .collect_vec();
+    let file = write_temp(&buf);
+    let path = file.path().to_str().unwrap();
+
+    let (success, stdout) = klv_dump(&[path]);
+    assert!(!success);
+    assert!(stdout.is_empty());
+
+    let (success, stdout) = klv_dump(&[path, "--lenient", "--stats"]);
+    assert!(success);
+    let lines = stdout.lines().collect_vec();
+    assert_eq!(lines.len(), 2);
+    assert_eq!(
+        lines[0].split_whitespace().take(4).collect_vec(),
+        ["42", "2", "1", "CC"]
+    );
+    assert!(lines[1].ends_with("errors: 1"));
+}
+
+#[test]
+fn custom_key() {
+    let key = [0x11; 16];
+    let file = write_temp(&chain!(key, [0x03, 0x07, 0x01, 0x00]).collect_vec());
+    let (success, stdout) = klv_dump(&[
+        file.path().to_str().unwrap(),
+        "--key",
+        "11111111-11111111-11111111-11111111",
+    ]);
+    assert!(success);
+    assert_eq!(
+        stdout.split_whitespace().take(4).collect_vec(),
+        ["17", "7", "1", "00"]
+    );
+}
+
+#[test]
+fn dictionary_labels_sets() {
+    let file = write_temp(&two_sets());
+    let dictionary = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/data/ul_dictionary.csv");
+    let (success, stdout) = klv_dump(&[file.path().to_str().unwrap(), "--dictionary", dictionary]);
+    assert!(success);
+
+    let lines = stdout.lines().collect_vec();
+    assert_eq!(lines.len(), 4);
+    assert_eq!(lines[0], "# 1 UAS Datalink Local Set");
+    assert_eq!(lines[2], "# 22 UAS Datalink Local Set");
+}
@@ -0,0 +1,103 @@
+#![cfg(feature = "cli")]
+
+use std::{fs, io::Write, path::Path, process::Command};
+
+use itertools::{Itertools, chain};
+use klv::keys;
+
+fn write_temp(bytes: &[u8]) -> tempfile::NamedTempFile {
+    let mut file = tempfile::NamedTempFile::new().unwrap();
+    file.write_all(bytes).unwrap();
+    file.flush().unwrap();
+    file
+}
+
+fn klv_extract(input: &Path, args: &[&str]) -> bool {
+    Command::new(env!("CARGO_BIN_EXE_klv-extract"))
+        .arg(input)
+        .args(args)
+        .status()
+        .unwrap()
+        .success()
+}
+
+fn two_sets() -> Vec<u8> {
+    chain!(
+        [0xFF],
+        *keys::ST0601,
+        [0x07, 0x01, 0x02, 0x41, 0x42, 0x02, 0x01, 0x43],
+        *keys::ST0601,
+        [0x03, 0x01, 0x01, 0xCC]
+    )
+    .collect_vec()
+}
+
+#[test]
+fn set_values_to_directory() {
+    let input = write_temp(&two_sets());
+    let dir = tempfile::tempdir().unwrap();
+    let out = dir.path().join("out");
+
+    assert!(klv_extract(
+        input.path(),
+        &["--out-dir", out.to_str().unwrap()]
+    ));
+    assert_eq!(
+        fs::read(out.join("000000.bin")).unwrap(),
+        [0x01, 0x02, 0x41, 0x42, 0x02, 0x01, 0x43]
+    );
+    assert_eq!(
+        fs::read(out.join("000001.bin")).unwrap(),
+        [0x01, 0x01, 0xCC]
+    );
+    assert_eq!(fs::read_dir(&out).unwrap().count(), 2);
+}
+
+#[test]
+fn tag_values_with_offsets_to_file() {
+    let input = write_temp(&two_sets());
+    let dir = tempfile::tempdir().unwrap();
+    let out = dir.path().join("tag1.bin");
+
+    assert!(klv_extract(
+        input.path(),
+        &["--tag", "1", "--with-offsets", "-o", out.to_str().unwrap()]
+    ));
+
+    let expected = chain!(
+        20u64.to_be_bytes(),
+        2u64.to_be_bytes(),
+        [0x41, 0x42],
+        44u64.to_be_bytes(),
+        1u64.to_be_bytes(),
+        [0xCC]
+    )
+    .collect_vec();
+    assert_eq!(fs::read(out).unwrap(), expected);
+}
+
+#[test]
+fn parse_errors_fail_unless_lenient() {
+    // The item in the first set claims more bytes than the set holds.
+    let input = write_temp(
+        &chain!(
+            *keys::ST0601,
+            [0x02, 0x01, 0x05],
+            *keys::ST0601,
+            [0x03, 0x01, 0x01, 0xCC]
+        )
+        .collect_vec(),
+    );
+    let dir = tempfile::tempdir().unwrap();
+    let out = dir.path().join("out.bin");
+    let out = out.to_str().unwrap();
+
+    assert!(!klv_extract(input.path(), &["--tag", "1", "-o", out]));
+    assert!(fs::read(out).unwrap().is_empty());
+
+    assert!(klv_extract(
+        input.path(),
+        &["--tag", "1", "--lenient", "-o", out]
+    ));
+    assert_eq!(fs::read(out).unwrap(), [0xCC]);
+}
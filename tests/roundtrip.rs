@@ -0,0 +1,94 @@
+#![cfg(feature = "std")]
+
+use std::{cell::RefCell, io::Cursor, rc::Rc};
+
+use klv::{
+    encoding::{
+        ber::{decode_ber, encode_ber},
+        ber_oid::{decode_ber_oid, encode_ber_oid},
+        integer::decode_signed_integer,
+        unsigned_integer::decode_unsigned_integer,
+    },
+    format::{FormatParams, KlvFormat, SoftwareValue, convert_to_klv},
+    keys,
+    klv::{ItemKey, OwnedKlv},
+    local_set::OwnedLocalSet,
+    universal_set::UniversalSet,
+};
+use proptest::prelude::*;
+
+/// Serializes `items` as an ST 0601 packet and parses it back out.
+fn reparse(items: Vec<OwnedKlv>, checksum: bool) -> Vec<OwnedKlv> {
+    let packet = OwnedLocalSet::new(items).serialize(&keys::ST0601, checksum);
+    let buf = Rc::new(RefCell::new(Cursor::new(packet)));
+    UniversalSet::new(keys::ST0601, buf, 0)
+        .unwrap()
+        .data()
+        .to_mutable()
+        .unwrap()
+        .items()
+        .clone()
+}
+
+fn item() -> impl Strategy<Value = OwnedKlv> {
+    (2..u64::MAX, prop::collection::vec(any::<u8>(), 0..300))
+        .prop_map(|(tag, value)| OwnedKlv::new(tag.into(), value))
+}
+
+proptest! {
+    #[test]
+    fn ber(value: u128) {
+        let encoded = encode_ber(value);
+        prop_assert_eq!(decode_ber(&encoded).unwrap(), (value, encoded.len()));
+    }
+
+    #[test]
+    fn ber_oid(value: u128) {
+        let encoded = encode_ber_oid(value);
+        prop_assert_eq!(decode_ber_oid(&encoded).unwrap(), (value, encoded.len()));
+    }
+
+    #[test]
+    fn signed_integer(value: i64) {
+        let params = FormatParams::default();
+        let encoded =
+            convert_to_klv(&SoftwareValue::Int64(value), &KlvFormat::Int, None, &params).unwrap();
+        let (decoded, _) = decode_signed_integer(&encoded, encoded.len() as u8).unwrap();
+        prop_assert_eq!(i128::from(decoded), i128::from(value));
+    }
+
+    #[test]
+    fn unsigned_integer(value: u64) {
+        let params = FormatParams::default();
+        let encoded =
+            convert_to_klv(&SoftwareValue::Uint64(value), &KlvFormat::Uint, None, &params).unwrap();
+        let (decoded, _) = decode_unsigned_integer(&encoded, encoded.len() as u8).unwrap();
+        prop_assert_eq!(u128::from(decoded), u128::from(value));
+    }
+
+    #[test]
+    fn packet(items in prop::collection::vec(item(), 0..20)) {
+        prop_assert_eq!(reparse(items.clone(), false), items);
+    }
+
+    #[test]
+    fn packet_with_checksum(items in prop::collection::vec(item(), 0..20)) {
+        let reparsed = reparse(items.clone(), true);
+        let (checksum, rest) = reparsed.split_last().unwrap();
+        prop_assert_eq!(checksum.key(), ItemKey::Tag(1));
+        prop_assert_eq!(rest, &items[..]);
+    }
+
+    #[cfg(feature = "arbitrary")]
+    #[test]
+    fn arbitrary_packet(bytes in prop::collection::vec(any::<u8>(), 0..4096)) {
+        let mut u = arbitrary::Unstructured::new(&bytes);
+        let items = u
+            .arbitrary_iter::<OwnedKlv>()
+            .unwrap()
+            .filter_map(Result::ok)
+            .filter(|klv| klv.tag().is_some_and(|tag| tag > 1))
+            .collect::<Vec<_>>();
+        prop_assert_eq!(reparse(items.clone(), false), items);
+    }
+}
@@ -0,0 +1,119 @@
+#![cfg(feature = "std")]
+
+use std::{cell::RefCell, io::Cursor, rc::Rc};
+
+use itertools::{Itertools, chain};
+use klv::{
+    prelude::*,
+    progress::ProgressHandle,
+    scan::{ScanReport, SetStatus},
+};
+use test_case::test_case;
+
+const PACKET: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/data/st0601_packet.bin");
+
+/// A good packet, ten bytes of junk, a packet with one byte of its mission ID
+/// changed, and another good packet.
+fn recording() -> (Vec<u8>, u64) {
+    let packet = std::fs::read(PACKET).unwrap();
+    let mut corrupted = packet.clone();
+    let i = corrupted.iter().position(|&byte| byte == b'M').unwrap();
+    corrupted[i] = b'N';
+    let buf = chain!(
+        packet.iter().copied(),
+        [0xAA; 10],
+        corrupted,
+        packet.iter().copied()
+    )
+    .collect();
+    (buf, packet.len() as u64)
+}
+
+fn scan(mode: ChecksumMode) -> (ScanReport, u64) {
+    let (buf, length) = recording();
+    let options = ParseOptions {
+        verify_checksum: mode,
+        ..ParseOptions::default()
+    };
+    let report = UniversalSet::scan_report(
+        keys::ST0601,
+        Rc::new(RefCell::new(Cursor::new(buf))),
+        &options,
+    )
+    .unwrap();
+    (report, length)
+}
+
+#[test]
+fn counts_good_and_bad_sets() {
+    let (report, length) = scan(ChecksumMode::Reject);
+
+    assert_eq!(report.sets().len(), 3);
+    assert_eq!(report.parsed(), 2);
+    assert_eq!(report.checksum_failures(), 1);
+    assert_eq!(report.failed(), 0);
+    assert_eq!(report.sets()[1].status(), SetStatus::ChecksumMismatch);
+    assert_eq!(report.sets()[1].offset(), length + 10);
+    assert_eq!(report.total_bytes(), 3 * length);
+    assert_eq!(report.byte_range(), Some(0..3 * length + 10));
+
+    assert_eq!(report.gaps().len(), 1);
+    assert_eq!(report.gaps()[0].offset(), length);
+    assert_eq!(report.gaps()[0].length(), 10);
+
+    assert_eq!(report.warnings().len(), 1);
+    assert_eq!(report.warnings()[0].set_offset(), length + 10);
+    assert!(report.to_string().contains("Gap of 10 bytes"));
+}
+
+#[test]
+fn checksum_warnings_keep_the_set() {
+    let (report, _) = scan(ChecksumMode::Warn);
+
+    assert_eq!(report.checksum_failures(), 1);
+    assert_eq!(report.sets()[1].warnings(), 1);
+    assert!(report.sets()[1].items() > 0);
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn report_serde() {
+    let (report, _) = scan(ChecksumMode::Reject);
+    let json = serde_json::to_string(&report).unwrap();
+    assert_eq!(serde_json::from_str::<ScanReport>(&json).unwrap(), report);
+}
+
+#[test_case(&[0x80]; "Indefinite length")]
+#[test_case(&[0x91, 0x01]; "Long form past u128")]
+#[test_case(&[0x88, 0x80, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00]; "Length of 2^63")]
+fn corrupt_lengths_fail_the_set(length: &[u8]) {
+    // A packet whose one byte length is replaced by `length`, between two
+    // good packets.
+    let packet = std::fs::read(PACKET).unwrap();
+    let corrupted = chain!(&packet[..16], length, &packet[17..]).copied();
+    let buf = chain!(packet.iter().copied(), corrupted, packet.iter().copied()).collect_vec();
+    let corrupt_offset = packet.len() as u64;
+    let last_offset = (2 * packet.len() + length.len() - 1) as u64;
+
+    let shared = Rc::new(RefCell::new(Cursor::new(buf)));
+    let report =
+        UniversalSet::scan_report(keys::ST0601, shared.clone(), &ParseOptions::default()).unwrap();
+    assert_eq!(
+        report.sets().iter().map(|set| set.offset()).collect_vec(),
+        [0, corrupt_offset, last_offset]
+    );
+    assert_eq!(report.parsed(), 2);
+    assert_eq!(report.failed(), 1);
+    assert_eq!(report.sets()[1].status(), SetStatus::Failed);
+    assert_eq!(report.warnings()[0].set_offset(), corrupt_offset);
+
+    shared.borrow_mut().set_position(0);
+    let with_progress = UniversalSet::scan_report_with_progress(
+        keys::ST0601,
+        shared,
+        &ParseOptions::default(),
+        Some(&mut ProgressHandle::new(16)),
+    )
+    .unwrap();
+    assert_eq!(with_progress, report);
+}
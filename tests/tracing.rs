@@ -0,0 +1,156 @@
+#![cfg(feature = "tracing")]
+
+use std::{
+    cell::RefCell,
+    io::Cursor,
+    rc::Rc,
+    sync::{Arc, Mutex},
+};
+
+use itertools::{Itertools, chain};
+use klv::{keys, universal_set::UniversalSet};
+use tracing::{
+    Event, Level, Metadata, Subscriber,
+    span::{Attributes, Id, Record},
+};
+
+/// Span or event recorded by `Recorder`, with the name of the span it was
+/// created in.
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Recorded {
+    Span {
+        name: &'static str,
+        parent: Option<&'static str>,
+    },
+    Event {
+        level: Level,
+        parent: Option<&'static str>,
+    },
+}
+
+#[derive(Default)]
+struct Recording {
+    /// Name of every span, indexed by its ID minus one.
+    names: Vec<&'static str>,
+    /// IDs of the spans that are currently entered.
+    entered: Vec<u64>,
+    recorded: Vec<Recorded>,
+}
+
+impl Recording {
+    fn current(&self) -> Option<&'static str> {
+        self.entered.last().map(|id| self.names[*id as usize - 1])
+    }
+}
+
+/// Subscriber that records the name of every span and the level of every
+/// event along with the span they happened in.
+#[derive(Clone, Default)]
+struct Recorder(Arc<Mutex<Recording>>);
+
+impl Subscriber for Recorder {
+    fn enabled(&self, _: &Metadata<'_>) -> bool {
+        true
+    }
+
+    fn new_span(&self, span: &Attributes<'_>) -> Id {
+        let mut recording = self.0.lock().unwrap();
+        let parent = recording.current();
+        recording.names.push(span.metadata().name());
+        recording.recorded.push(Recorded::Span {
+            name: span.metadata().name(),
+            parent,
+        });
+        Id::from_u64(recording.names.len() as u64)
+    }
+
+    fn record(&self, _: &Id, _: &Record<'_>) {}
+
+    fn record_follows_from(&self, _: &Id, _: &Id) {}
+
+    fn event(&self, event: &Event<'_>) {
+        let mut recording = self.0.lock().unwrap();
+        let parent = recording.current();
+        recording.recorded.push(Recorded::Event {
+            level: *event.metadata().level(),
+            parent,
+        });
+    }
+
+    fn enter(&self, span: &Id) {
+        self.0.lock().unwrap().entered.push(span.into_u64());
+    }
+
+    fn exit(&self, _: &Id) {
+        self.0.lock().unwrap().entered.pop();
+    }
+}
+
+fn record(buf: Vec<u8>) -> Vec<Recorded> {
+    let recorder = Recorder::default();
+    tracing::subscriber::with_default(recorder.clone(), || {
+        let _ = UniversalSet::read_all(keys::ST0601, Rc::new(RefCell::new(Cursor::new(buf))));
+    });
+    let recording = recorder.0.lock().unwrap();
+    recording.recorded.clone()
+}
+
+#[test]
+fn read_all_spans_each_set() {
+    let buf = chain!(
+        *keys::ST0601,
+        [0x03, 0x02, 0x01, 0x01],
+        *keys::ST0601,
+        [0x06, 0x02, 0x01, 0x01, 0x05, 0x01, 0x02]
+    )
+    .collect_vec();
+
+    let set = Recorded::Span {
+        name: "universal_set",
+        parent: Some("read_all"),
+    };
+    let item = Recorded::Event {
+        level: Level::DEBUG,
+        parent: Some("universal_set"),
+    };
+    assert_eq!(
+        record(buf),
+        vec![
+            Recorded::Span {
+                name: "read_all",
+                parent: None
+            },
+            set.clone(),
+            item.clone(),
+            set,
+            item.clone(),
+            item
+        ]
+    );
+}
+
+#[test]
+fn errors_are_warnings() {
+    // The item's length runs past the end of the set.
+    let buf = chain!(*keys::ST0601, [0x03, 0x02, 0x05, 0x01]).collect_vec();
+
+    let warnings = record(buf)
+        .into_iter()
+        .filter(
+            |recorded| matches!(recorded, Recorded::Event { level, .. } if *level == Level::WARN),
+        )
+        .collect_vec();
+    assert_eq!(
+        warnings,
+        vec![
+            Recorded::Event {
+                level: Level::WARN,
+                parent: Some("universal_set")
+            },
+            Recorded::Event {
+                level: Level::WARN,
+                parent: Some("universal_set")
+            }
+        ]
+    );
+}
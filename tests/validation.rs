@@ -0,0 +1,186 @@
+#![cfg(feature = "std")]
+
+use std::{cell::RefCell, io::Cursor, rc::Rc};
+
+use itertools::Itertools;
+use klv::{
+    misb::st0601::{self, St0601Tag},
+    prelude::*,
+    repair::fix_packet,
+    universal_set::UNIVERSAL_KEY_LENGTH,
+    validation::{
+        Severity, StreamValidationReport, ValidationProfile, ViolationKind, validate_stream,
+    },
+};
+
+fn packet(extra: &[OwnedKlv]) -> Vec<u8> {
+    let items = [
+        vec![OwnedKlv::new(2, 1_000_000u64.to_be_bytes().to_vec())],
+        extra.to_vec(),
+        vec![OwnedKlv::new(65, vec![0x11])],
+    ]
+    .concat();
+    OwnedLocalSet::new(items).serialize(&keys::ST0601, true)
+}
+
+/// A valid packet followed by five packets that each break one rule: an
+/// unknown tag, a missing Version Number, a Mission ID that is not UTF-8, a
+/// stale checksum, and a Mission ID length written in two bytes.
+fn stream() -> Vec<u8> {
+    let mut stale = packet(&[]);
+    let checksum = stale.len() - 1;
+    stale[checksum] ^= 0xFF;
+
+    let mut long_length = packet(&[OwnedKlv::new(3, b"ALPHA".to_vec())]);
+    let mission = long_length
+        .windows(2)
+        .position(|pair| pair == [0x03, 0x05])
+        .unwrap();
+    long_length.splice(mission + 1..mission + 2, [0x81, 0x05]);
+    let long_length = fix_packet(&long_length).unwrap().into_packet();
+
+    [
+        packet(&[]),
+        packet(&[OwnedKlv::new(200, vec![0xAB])]),
+        OwnedLocalSet::new(vec![OwnedKlv::new(2, vec![0x00; 8])]).serialize(&keys::ST0601, true),
+        packet(&[OwnedKlv::new(3, vec![0xFF])]),
+        stale,
+        long_length,
+    ]
+    .concat()
+}
+
+fn validate(stream: Vec<u8>, profile: &ValidationProfile<St0601Tag>) -> StreamValidationReport {
+    let buf = Rc::new(RefCell::new(Cursor::new(stream)));
+    validate_stream(keys::ST0601, buf, profile).unwrap()
+}
+
+#[test]
+fn each_finding_is_reported_once() {
+    let report = validate(stream(), &ValidationProfile::lenient());
+
+    assert_eq!(report.packets().len(), 6);
+    assert!(report.packets()[0].violations().is_empty());
+    let findings = report
+        .violations()
+        .map(|(_, violation)| (violation.key(), violation.severity(), violation.kind()))
+        .collect_vec();
+    assert!(matches!(
+        findings[..],
+        [
+            (
+                ItemKey::Tag(200),
+                Severity::Warning,
+                ViolationKind::UnknownTag
+            ),
+            (
+                ItemKey::Tag(65),
+                Severity::Error,
+                ViolationKind::MissingRequired
+            ),
+            (
+                ItemKey::Tag(3),
+                Severity::Error,
+                ViolationKind::InvalidValue
+            ),
+            (
+                ItemKey::Tag(1),
+                Severity::Error,
+                ViolationKind::ChecksumMismatch { .. }
+            ),
+            (
+                ItemKey::Tag(3),
+                Severity::Warning,
+                ViolationKind::NonMinimalEncoding {
+                    width: 2,
+                    minimal: 1
+                }
+            ),
+        ]
+    ));
+    assert_eq!(
+        report
+            .packets()
+            .iter()
+            .map(|packet| packet.is_valid())
+            .collect_vec(),
+        [true, true, false, false, false, true]
+    );
+}
+
+#[test]
+fn strict_profile_turns_warnings_into_errors() {
+    let fill = OwnedKlv::with_key(ItemKey::Ul(keys::FILL), vec![0x00; 3]);
+    let stream = packet(&[fill]);
+
+    let report = validate(stream.clone(), &ValidationProfile::lenient());
+    let violation = &report.packets()[0].violations()[0];
+    assert_eq!(violation.kind(), ViolationKind::FillItem);
+    let context = violation.context().as_ref().unwrap();
+    assert_eq!(context.index(), 1);
+    assert_eq!(Some(context.offset()), violation.offset());
+    assert!(report.is_valid());
+
+    let report = validate(stream, &ValidationProfile::strict());
+    assert_eq!(
+        report.packets()[0].violations()[0].severity(),
+        Severity::Error
+    );
+    assert!(!report.is_valid());
+    assert!(report.to_string().contains("error: Item ST0601["));
+}
+
+#[test]
+fn padded_lengths_are_warnings_when_strict() {
+    // Hardware encoders that write every length in four bytes.
+    let set = OwnedLocalSet::new(vec![
+        OwnedKlv::new(2, 1_000_000u64.to_be_bytes().to_vec()),
+        OwnedKlv::new(3, b"ALPHA".to_vec()),
+        OwnedKlv::new(65, vec![0x11]),
+    ]);
+    let mut items = set
+        .serialize(&keys::ST0601, true)
+        .split_off(UNIVERSAL_KEY_LENGTH + 1);
+    let mission = items
+        .windows(2)
+        .position(|pair| pair == [0x03, 0x05])
+        .unwrap();
+    items.splice(mission + 1..mission + 2, [0x84, 0x00, 0x00, 0x00, 0x05]);
+    let mut stream = [
+        keys::ST0601.to_vec(),
+        vec![0x84, 0x00, 0x00, 0x00, items.len() as u8],
+        items,
+    ]
+    .concat();
+    let checksum = stream.len() - 2;
+    let computed = st0601::checksum(&stream[..checksum]);
+    stream[checksum..].copy_from_slice(&computed.to_be_bytes());
+
+    let report = validate(stream, &ValidationProfile::strict());
+    let findings = report
+        .violations()
+        .map(|(_, violation)| (violation.key(), violation.severity(), violation.kind()))
+        .collect_vec();
+    assert_eq!(
+        findings,
+        [
+            (
+                ItemKey::Ul(keys::ST0601),
+                Severity::Warning,
+                ViolationKind::NonMinimalEncoding {
+                    width: 5,
+                    minimal: 1
+                }
+            ),
+            (
+                ItemKey::Tag(3),
+                Severity::Warning,
+                ViolationKind::NonMinimalEncoding {
+                    width: 5,
+                    minimal: 1
+                }
+            ),
+        ]
+    );
+    assert!(report.is_valid());
+}